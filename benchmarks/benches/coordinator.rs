@@ -57,6 +57,9 @@ fn bench_worker_registration(c: &mut Criterion) {
                         gpu_count: 4,
                         memory_bytes: 32 * 1024 * 1024 * 1024,
                         metadata: Default::default(),
+                        protocol_version: coordinator::PROTOCOL_VERSION,
+                        labels: Default::default(),
+                        taints: Vec::new(),
                     })
                     .await
                     .unwrap();
@@ -85,6 +88,9 @@ fn bench_heartbeat_processing(c: &mut Criterion) {
                     gpu_count: 4,
                     memory_bytes: 32 * 1024 * 1024 * 1024,
                     metadata: Default::default(),
+                    protocol_version: coordinator::PROTOCOL_VERSION,
+                    labels: Default::default(),
+                    taints: Vec::new(),
                 })
                 .await
                 .unwrap();
@@ -104,12 +110,15 @@ fn bench_heartbeat_processing(c: &mut Criterion) {
                     client
                         .heartbeat(HeartbeatRequest {
                             worker_id: worker_id.clone(),
+                            session: 0,
                             timestamp_ms: 0,
                             status: Some(WorkerStatus {
                                 state: 4, // TRAINING
                                 current_step: 0,
                                 current_epoch: 0,
                                 current_task: "training".to_string(),
+                                shard_progress: vec![],
+                                loss: 0.0,
                             }),
                             resources: Some(ResourceUsage {
                                 cpu_percent: 50.0,
@@ -120,6 +129,7 @@ fn bench_heartbeat_processing(c: &mut Criterion) {
                                 network_rx_bytes: 0,
                                 network_tx_bytes: 0,
                             }),
+                            metrics: vec![],
                         })
                         .await
                         .unwrap();
@@ -159,6 +169,8 @@ fn bench_barrier_synchronization(c: &mut Criterion) {
                                             worker_id: format!("worker-{}", i),
                                             barrier_id,
                                             step: 1,
+                                            generation: 0,
+                                            no_wait: false,
                                         })
                                         .await
                                         .unwrap();
@@ -215,6 +227,7 @@ fn bench_data_shard_assignment(c: &mut Criterion) {
                         dataset_id: "bench-dataset".to_string(),
                         worker_id: format!("worker-{}", uuid::Uuid::new_v4()),
                         epoch: 0,
+                        ..Default::default()
                     })
                     .await
                     .unwrap();
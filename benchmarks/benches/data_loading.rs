@@ -2,8 +2,17 @@
 
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use data_shard::{ConsistentHash, ShardManager};
+use runtime_core::{DatasetId, WorkerId};
 use std::collections::HashMap;
 
+fn dataset_id() -> DatasetId {
+    "dataset-1".parse().unwrap()
+}
+
+fn worker_id(i: usize) -> WorkerId {
+    format!("worker-{}", i).parse().unwrap()
+}
+
 fn bench_shard_assignment(c: &mut Criterion) {
     let mut group = c.benchmark_group("shard_assignment");
 
@@ -21,7 +30,7 @@ fn bench_shard_assignment(c: &mut Criterion) {
                     b.iter(|| {
                         let manager = ShardManager::new();
                         manager.register_dataset_params(
-                            "dataset-1",
+                            &dataset_id(),
                             shards as u64 * 1000,
                             1000,
                             true,
@@ -29,12 +38,12 @@ fn bench_shard_assignment(c: &mut Criterion) {
                         );
 
                         for i in 0..workers {
-                            manager.register_worker(&format!("worker-{}", i));
+                            manager.register_worker(&worker_id(i));
                         }
 
                         // Get shard assignments for all workers
                         for i in 0..workers {
-                            manager.get_shard_for_worker("dataset-1", &format!("worker-{}", i), 0);
+                            manager.get_shard_for_worker(&dataset_id(), &worker_id(i), 0);
                         }
                     });
                 },
@@ -51,15 +60,15 @@ fn bench_epoch_management(c: &mut Criterion) {
     group.bench_function("create_and_track_100_epochs", |b| {
         b.iter(|| {
             let manager = ShardManager::new();
-            manager.register_dataset_params("dataset-1", 100000, 100, true, 42);
+            manager.register_dataset_params(&dataset_id(), 100000, 100, true, 42);
 
             for i in 0..10 {
-                manager.register_worker(&format!("worker-{}", i));
+                manager.register_worker(&worker_id(i));
             }
 
             for epoch in 0..100u64 {
                 for worker in 0..10 {
-                    manager.get_shard_for_worker("dataset-1", &format!("worker-{}", worker), epoch);
+                    manager.get_shard_for_worker(&dataset_id(), &worker_id(worker), epoch);
                 }
             }
         });
@@ -161,10 +170,10 @@ fn bench_parallel_shard_access(c: &mut Criterion) {
                 b.iter(|| {
                     rt.block_on(async {
                         let manager = std::sync::Arc::new(ShardManager::new());
-                        manager.register_dataset_params("dataset-1", 100000, 10, true, 42);
+                        manager.register_dataset_params(&dataset_id(), 100000, 10, true, 42);
 
                         for i in 0..threads {
-                            manager.register_worker(&format!("worker-{}", i));
+                            manager.register_worker(&worker_id(i));
                         }
 
                         let mut handles = vec![];
@@ -174,8 +183,8 @@ fn bench_parallel_shard_access(c: &mut Criterion) {
                             let handle = tokio::spawn(async move {
                                 for epoch in 0..10u64 {
                                     manager.get_shard_for_worker(
-                                        "dataset-1",
-                                        &format!("worker-{}", i),
+                                        &dataset_id(),
+                                        &worker_id(i),
                                         epoch,
                                     );
                                 }
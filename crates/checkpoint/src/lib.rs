@@ -5,5 +5,8 @@
 pub mod manager;
 pub mod writer;
 
-pub use manager::{CheckpointManager, CheckpointManagerConfig, CheckpointManagerHandle};
+pub use manager::{
+    CheckpointManager, CheckpointManagerConfig, CheckpointManagerHandle, PendingCheckpoint,
+    WriteStatus,
+};
 pub use writer::AsyncCheckpointWriter;
@@ -3,13 +3,17 @@
 use bytes::Bytes;
 use chrono::Utc;
 use parking_lot::RwLock;
-use runtime_core::{CheckpointId, CheckpointMetadata, CheckpointType, Epoch, Error, Result, Step};
+use runtime_core::{
+    id, CheckpointId, CheckpointMetadata, CheckpointType, Counter, Epoch, Error, Gauge, Histogram,
+    MetricsRegistry, Result, ShutdownToken, Step,
+};
 use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use storage::{LocalStorage, StorageBackend};
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, warn};
-use uuid::Uuid;
 
 use crate::writer::{AsyncCheckpointWriter, WriteRequest, WriterEvent};
 
@@ -56,6 +60,9 @@ pub struct PendingCheckpoint {
     /// Training epoch
     pub epoch: Epoch,
 
+    /// Size of the checkpoint data in bytes
+    pub size_bytes: u64,
+
     /// Write status
     pub status: WriteStatus,
 
@@ -79,10 +86,21 @@ pub enum WriteStatus {
     Failed,
 }
 
+/// Metrics kept by a checkpoint manager
+#[derive(Debug, Clone, Default)]
+struct CheckpointMetrics {
+    writes: Counter,
+    write_failures: Counter,
+    written_bytes: Counter,
+    write_seconds: Histogram,
+    writes_in_flight: Gauge,
+    evictions: Counter,
+}
+
 /// Checkpoint manager for handling async writes and versioning
 pub struct CheckpointManager {
-    /// Configuration
-    config: CheckpointManagerConfig,
+    /// Number of checkpoints to keep, adjustable at runtime
+    keep_count: Arc<AtomicUsize>,
 
     /// Checkpoint metadata indexed by step
     checkpoints: Arc<RwLock<BTreeMap<Step, CheckpointMetadata>>>,
@@ -90,9 +108,15 @@ pub struct CheckpointManager {
     /// Pending writes
     pending: Arc<RwLock<HashMap<CheckpointId, PendingCheckpoint>>>,
 
+    /// Where checkpoint data is written
+    storage: Arc<dyn StorageBackend>,
+
     /// Channel to send write requests
     write_tx: mpsc::Sender<WriteRequest>,
 
+    /// Write and retention metrics
+    metrics: CheckpointMetrics,
+
     /// Async writer handle
     _writer: AsyncCheckpointWriter,
 }
@@ -100,30 +124,57 @@ pub struct CheckpointManager {
 impl CheckpointManager {
     /// Create a new checkpoint manager
     pub async fn new(config: CheckpointManagerConfig) -> Result<Self> {
+        Self::with_shutdown(config, ShutdownToken::new()).await
+    }
+
+    /// Create a checkpoint manager whose writer stops with `shutdown`
+    ///
+    /// Writes queued before shutdown still complete; later ones fail.
+    pub async fn with_shutdown(
+        config: CheckpointManagerConfig,
+        shutdown: ShutdownToken,
+    ) -> Result<Self> {
         // Create checkpoint directory
         tokio::fs::create_dir_all(&config.base_path)
             .await
-            .map_err(|e| Error::Storage {
-                message: format!("Failed to create checkpoint directory: {}", e),
+            .map_err(|e| {
+                Error::storage("Failed to create checkpoint directory")
+                    .with_path(config.base_path.display().to_string())
+                    .with_source(e)
             })?;
 
+        let storage = Arc::new(LocalStorage::new(&config.base_path));
+        Self::with_storage(config, storage, shutdown).await
+    }
+
+    /// Create a checkpoint manager writing checkpoint data to `storage`
+    /// instead of `base_path`, e.g. an S3 bucket
+    pub async fn with_storage(
+        config: CheckpointManagerConfig,
+        storage: Arc<dyn StorageBackend>,
+        shutdown: ShutdownToken,
+    ) -> Result<Self> {
         // Shared state
         let checkpoints = Arc::new(RwLock::new(BTreeMap::new()));
         let pending = Arc::new(RwLock::new(
             HashMap::<CheckpointId, PendingCheckpoint>::new(),
         ));
-        let base_path = config.base_path.clone();
-        let keep_count = config.keep_count;
+        let listener_storage = storage.clone();
+        let keep_count = Arc::new(AtomicUsize::new(config.keep_count));
+        let listener_keep_count = keep_count.clone();
+        let metrics = CheckpointMetrics::default();
+        let listener_metrics = metrics.clone();
 
         // Create completion channel
         let (event_tx, mut event_rx) = mpsc::channel(100);
 
         // Create async writer
         let (write_tx, writer) = AsyncCheckpointWriter::new(
-            config.base_path.clone(),
+            storage.clone(),
             config.write_buffer_size,
             config.compression,
             event_tx,
+            shutdown,
         )
         .await?;
 
@@ -135,10 +186,20 @@ impl CheckpointManager {
             debug!("Checkpoint event listener started");
             while let Some(event) = event_rx.recv().await {
                 match event {
+                    WriterEvent::Started { checkpoint_id } => {
+                        if let Some(entry) = pending_clone.write().get_mut(&checkpoint_id) {
+                            entry.status = WriteStatus::InProgress;
+                        }
+                    }
                     WriterEvent::Completed {
                         checkpoint_id,
                         size_bytes,
+                        elapsed,
                     } => {
+                        listener_metrics.writes_in_flight.dec();
+                        listener_metrics.writes.inc();
+                        listener_metrics.written_bytes.inc_by(size_bytes);
+                        listener_metrics.write_seconds.observe_duration(elapsed);
                         let mut pending_lock = pending_clone.write();
 
                         if let Some(entry) = pending_lock.get_mut(&checkpoint_id) {
@@ -149,10 +210,7 @@ impl CheckpointManager {
                                 id: checkpoint_id.clone(),
                                 step: entry.step,
                                 epoch: entry.epoch,
-                                path: base_path
-                                    .join(format!("{}.ckpt", checkpoint_id))
-                                    .to_string_lossy()
-                                    .to_string(),
+                                path: listener_storage.uri(&data_key(&checkpoint_id)),
                                 size_bytes,
                                 created_at: Utc::now(),
                                 checkpoint_type: CheckpointType::Full, // TODO: preserve type
@@ -170,17 +228,13 @@ impl CheckpointManager {
 
                             // Cleanup old checkpoints
                             let mut checkpoints_lock = checkpoints_clone.write();
-                            while checkpoints_lock.len() > keep_count {
+                            while checkpoints_lock.len()
+                                > listener_keep_count.load(Ordering::Relaxed)
+                            {
                                 if let Some((&step, _)) = checkpoints_lock.first_key_value() {
                                     if let Some(meta) = checkpoints_lock.remove(&step) {
-                                        let path = meta.path.clone();
-                                        tokio::spawn(async move {
-                                            if let Err(e) = tokio::fs::remove_file(&path).await {
-                                                warn!(path = %path, error = %e, "Failed to delete old checkpoint");
-                                            } else {
-                                                debug!(path = %path, "Deleted old checkpoint");
-                                            }
-                                        });
+                                        listener_metrics.evictions.inc();
+                                        spawn_delete(listener_storage.clone(), meta);
                                     }
                                 }
                             }
@@ -190,6 +244,8 @@ impl CheckpointManager {
                         checkpoint_id,
                         error,
                     } => {
+                        listener_metrics.writes_in_flight.dec();
+                        listener_metrics.write_failures.inc();
                         let mut pending_lock = pending_clone.write();
                         if let Some(entry) = pending_lock.get_mut(&checkpoint_id) {
                             entry.status = WriteStatus::Failed;
@@ -207,14 +263,57 @@ impl CheckpointManager {
         });
 
         Ok(Self {
-            config,
+            keep_count,
             checkpoints,
             pending,
+            storage,
             write_tx,
+            metrics,
             _writer: writer,
         })
     }
 
+    /// Publish write and retention metrics in a registry
+    pub fn register_metrics(&self, registry: &MetricsRegistry) {
+        let metrics = self.metrics.clone();
+        registry.register(
+            "strata_checkpoint_writes_total",
+            "Checkpoints written to storage",
+            &[],
+            metrics.writes,
+        );
+        registry.register(
+            "strata_checkpoint_write_failures_total",
+            "Checkpoint writes that failed",
+            &[],
+            metrics.write_failures,
+        );
+        registry.register(
+            "strata_checkpoint_written_bytes_total",
+            "Bytes of checkpoints written to storage",
+            &[],
+            metrics.written_bytes,
+        );
+        registry.register(
+            "strata_checkpoint_write_seconds",
+            "Time taken to write a checkpoint",
+            &[],
+            metrics.write_seconds,
+        );
+        registry.register(
+            "strata_checkpoint_writes_in_flight",
+            "Checkpoint writes queued or in progress",
+            &[],
+            metrics.writes_in_flight,
+        );
+        registry.register(
+            "strata_checkpoint_evictions_total",
+            "Checkpoints dropped by the retention policy",
+            &[],
+            metrics.evictions,
+        );
+    }
+
     /// Save a checkpoint asynchronously (non-blocking)
     pub async fn save_async(
         &self,
@@ -224,27 +323,24 @@ impl CheckpointManager {
         checkpoint_type: CheckpointType,
         metadata: HashMap<String, String>,
     ) -> Result<CheckpointId> {
-        let checkpoint_id = format!("ckpt-{}-{}", step, Uuid::new_v4());
+        let checkpoint_id = CheckpointId::new(id::next_id("ckpt"))?;
 
         // Create pending entry
         let pending = PendingCheckpoint {
             id: checkpoint_id.clone(),
             step,
             epoch,
+            size_bytes: data.len() as u64,
             status: WriteStatus::Pending,
             error: None,
         };
         self.pending.write().insert(checkpoint_id.clone(), pending);
 
-        // Generate path
-        let filename = format!("{}.ckpt", checkpoint_id);
-        let path = self.config.base_path.join(&filename);
-
         // Create write request
         let request = WriteRequest {
             checkpoint_id: checkpoint_id.clone(),
             data,
-            path: path.clone(),
+            path: data_key(&checkpoint_id),
             step,
             epoch,
             checkpoint_type,
@@ -252,12 +348,13 @@ impl CheckpointManager {
         };
 
         // Send to async writer
-        self.write_tx
-            .send(request)
-            .await
-            .map_err(|e| Error::ChannelClosed {
+        self.metrics.writes_in_flight.inc();
+        self.write_tx.send(request).await.map_err(|e| {
+            self.metrics.writes_in_flight.dec();
+            Error::ChannelClosed {
                 channel: format!("checkpoint write channel: {}", e),
-            })?;
+            }
+        })?;
 
         debug!(checkpoint_id = %checkpoint_id, step = step, "Queued checkpoint for async write");
 
@@ -265,7 +362,7 @@ impl CheckpointManager {
     }
 
     /// Mark a checkpoint as completed (called by writer or coordinator)
-    pub fn mark_completed(&self, checkpoint_id: &str, size_bytes: u64) -> Result<()> {
+    pub fn mark_completed(&self, checkpoint_id: &CheckpointId, size_bytes: u64) -> Result<()> {
         let mut pending = self.pending.write();
 
         // Even if not in pending (e.g. from coordinator), we might want to register it
@@ -291,15 +388,10 @@ impl CheckpointManager {
 
             // Create metadata and store
             let metadata = CheckpointMetadata {
-                id: checkpoint_id.to_string(),
+                id: checkpoint_id.clone(),
                 step,
                 epoch,
-                path: self
-                    .config
-                    .base_path
-                    .join(format!("{}.ckpt", checkpoint_id))
-                    .to_string_lossy()
-                    .to_string(),
+                path: self.storage.uri(&data_key(checkpoint_id)),
                 size_bytes,
                 created_at: Utc::now(),
                 checkpoint_type: CheckpointType::Full, // TODO: preserve type
@@ -329,7 +421,7 @@ impl CheckpointManager {
     }
 
     /// Mark a checkpoint as failed
-    pub fn mark_failed(&self, checkpoint_id: &str, error: String) {
+    pub fn mark_failed(&self, checkpoint_id: &CheckpointId, error: String) {
         let mut pending = self.pending.write();
         if let Some(entry) = pending.get_mut(checkpoint_id) {
             entry.status = WriteStatus::Failed;
@@ -345,17 +437,19 @@ impl CheckpointManager {
     /// Register an external checkpoint (from remote workers via gRPC)
     /// This is used when the coordinator receives a checkpoint notification
     /// that it didn't initiate locally
+    ///
+    /// Returns the checkpoints evicted by the retention policy.
     pub fn register_external_checkpoint(
         &self,
-        checkpoint_id: &str,
+        checkpoint_id: &CheckpointId,
         step: Step,
         epoch: Epoch,
         path: &str,
         size_bytes: u64,
         metadata: HashMap<String, String>,
-    ) {
+    ) -> Vec<CheckpointMetadata> {
         let checkpoint_metadata = CheckpointMetadata {
-            id: checkpoint_id.to_string(),
+            id: checkpoint_id.clone(),
             step,
             epoch,
             path: path.to_string(),
@@ -387,7 +481,7 @@ impl CheckpointManager {
         );
 
         // Cleanup old checkpoints
-        self.cleanup_old_checkpoints();
+        self.cleanup_old_checkpoints()
     }
 
     /// Get the latest checkpoint
@@ -395,6 +489,15 @@ impl CheckpointManager {
         self.checkpoints.read().values().last().cloned()
     }
 
+    /// Get checkpoint by ID
+    pub fn get(&self, checkpoint_id: &CheckpointId) -> Option<CheckpointMetadata> {
+        self.checkpoints
+            .read()
+            .values()
+            .find(|m| &m.id == checkpoint_id)
+            .cloned()
+    }
+
     /// Get checkpoint by step
     pub fn get_by_step(&self, step: Step) -> Option<CheckpointMetadata> {
         self.checkpoints.read().get(&step).cloned()
@@ -405,6 +508,59 @@ impl CheckpointManager {
         self.checkpoints.read().values().cloned().collect()
     }
 
+    /// Number of checkpoints retained by the configured policy
+    pub fn keep_count(&self) -> usize {
+        self.keep_count.load(Ordering::Relaxed)
+    }
+
+    /// Change how many checkpoints are kept, applied when the next one completes
+    pub fn set_keep_count(&self, keep_count: usize) {
+        self.keep_count.store(keep_count.max(1), Ordering::Relaxed);
+    }
+
+    /// Checkpoints that fall outside a retention window of `keep_count`
+    ///
+    /// The most recent checkpoint is never returned so recovery always has a target.
+    pub fn prune_candidates(&self, keep_count: usize) -> Vec<CheckpointMetadata> {
+        let checkpoints = self.checkpoints.read();
+        let keep = keep_count.max(1);
+        let excess = checkpoints.len().saturating_sub(keep);
+        checkpoints.values().take(excess).cloned().collect()
+    }
+
+    /// Remove checkpoint metadata by ID without touching the stored data
+    pub fn remove_checkpoint(&self, checkpoint_id: &CheckpointId) -> Option<CheckpointMetadata> {
+        let mut checkpoints = self.checkpoints.write();
+        let step = checkpoints
+            .iter()
+            .find(|(_, m)| &m.id == checkpoint_id)
+            .map(|(&step, _)| step)?;
+        checkpoints.remove(&step)
+    }
+
+    /// Remove a checkpoint and delete its data file
+    ///
+    /// Data that does not exist locally, such as external checkpoints kept on
+    /// the workers, is left alone. Returns `None` for unknown checkpoints.
+    pub async fn delete_checkpoint(
+        &self,
+        checkpoint_id: &CheckpointId,
+    ) -> Result<Option<CheckpointMetadata>> {
+        let Some(meta) = self
+            .checkpoints
+            .read()
+            .values()
+            .find(|m| &m.id == checkpoint_id)
+            .cloned()
+        else {
+            return Ok(None);
+        };
+
+        delete_data(self.storage.as_ref(), &meta).await?;
+        debug!(checkpoint_id = %checkpoint_id, path = %meta.path, "Deleted checkpoint data");
+        Ok(self.remove_checkpoint(checkpoint_id))
+    }
+
     /// Get pending writes
     pub fn pending_writes(&self) -> Vec<PendingCheckpoint> {
         self.pending.read().values().cloned().collect()
@@ -447,48 +603,69 @@ impl CheckpointManager {
                     )
                 })
                 .collect();
-            return Err(Error::CheckpointWriteFailed {
-                message: errors.join(", "),
+            let error = Error::checkpoint_write_failed(errors.join(", "));
+            return Err(match failures.as_slice() {
+                [failure] => error.with_checkpoint_id(failure.id.to_string()),
+                _ => error,
             });
         }
 
         Ok(())
     }
 
-    /// Cleanup old checkpoints beyond keep_count
-    fn cleanup_old_checkpoints(&self) {
+    /// Cleanup old checkpoints beyond keep_count, returning the evicted entries
+    fn cleanup_old_checkpoints(&self) -> Vec<CheckpointMetadata> {
         let mut checkpoints = self.checkpoints.write();
+        let mut evicted = Vec::new();
 
-        while checkpoints.len() > self.config.keep_count {
+        while checkpoints.len() > self.keep_count() {
             if let Some((&step, _)) = checkpoints.first_key_value() {
                 if let Some(meta) = checkpoints.remove(&step) {
-                    // Delete file asynchronously (fire and forget)
-                    let path = meta.path.clone();
-                    tokio::spawn(async move {
-                        if let Err(e) = tokio::fs::remove_file(&path).await {
-                            warn!(path = %path, error = %e, "Failed to delete old checkpoint");
-                        } else {
-                            debug!(path = %path, "Deleted old checkpoint");
-                        }
-                    });
+                    self.metrics.evictions.inc();
+                    // Delete data asynchronously (fire and forget)
+                    spawn_delete(self.storage.clone(), meta.clone());
+                    evicted.push(meta);
                 }
             }
         }
+
+        evicted
     }
 
     /// Load checkpoint data from path
-    pub async fn load(&self, checkpoint_id: &str) -> Result<Bytes> {
+    pub async fn load(&self, checkpoint_id: &CheckpointId) -> Result<Bytes> {
         let meta = self
-            .checkpoints
-            .read()
-            .values()
-            .find(|m| m.id == checkpoint_id)
-            .cloned()
+            .get(checkpoint_id)
             .ok_or_else(|| Error::CheckpointNotFound {
                 checkpoint_id: checkpoint_id.to_string(),
             })?;
 
-        AsyncCheckpointWriter::read_checkpoint_data(&PathBuf::from(&meta.path)).await
+        let key = data_key(&meta.id);
+        if self.storage.uri(&key) == meta.path {
+            let file = self.storage.read(&key).await?;
+            AsyncCheckpointWriter::decode_checkpoint_data(file)
+        } else {
+            AsyncCheckpointWriter::read_checkpoint_data(&PathBuf::from(&meta.path)).await
+        }
+    }
+
+    /// Load the latest checkpoint and its metadata, `None` if there is none
+    ///
+    /// A checkpoint evicted while it is read, because a newer one completed
+    /// in the meantime, is skipped for the new latest instead of failing.
+    pub async fn load_latest(&self) -> Result<Option<(CheckpointMetadata, Bytes)>> {
+        loop {
+            let Some(meta) = self.latest() else {
+                return Ok(None);
+            };
+            match self.load(&meta.id).await {
+                Ok(data) => return Ok(Some((meta, data))),
+                Err(e) if self.get(&meta.id).is_none() => {
+                    debug!(checkpoint_id = %meta.id, error = %e, "Latest checkpoint evicted while loading");
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
 
     /// Find the best checkpoint for recovery
@@ -498,6 +675,40 @@ impl CheckpointManager {
     }
 }
 
+/// Storage path of a checkpoint's data
+fn data_key(checkpoint_id: &CheckpointId) -> String {
+    format!("{}.ckpt", checkpoint_id)
+}
+
+/// Delete a checkpoint's data from `storage` if it was written there, or else
+/// from the local file system, e.g. for external checkpoints
+///
+/// Data that is already gone is not an error.
+async fn delete_data(storage: &dyn StorageBackend, meta: &CheckpointMetadata) -> Result<()> {
+    let key = data_key(&meta.id);
+    if storage.uri(&key) == meta.path {
+        return match storage.delete(&key).await {
+            Ok(()) | Err(Error::StoragePathNotFound { .. }) => Ok(()),
+            Err(e) => Err(e),
+        };
+    }
+    match tokio::fs::remove_file(&meta.path).await {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(Error::Io(e)),
+    }
+}
+
+/// Delete an evicted checkpoint's data in the background
+fn spawn_delete(storage: Arc<dyn StorageBackend>, meta: CheckpointMetadata) {
+    tokio::spawn(async move {
+        match delete_data(storage.as_ref(), &meta).await {
+            Ok(()) => debug!(path = %meta.path, "Deleted old checkpoint"),
+            Err(e) => warn!(path = %meta.path, error = %e, "Failed to delete old checkpoint"),
+        }
+    });
+}
+
 /// Thread-safe handle to checkpoint manager
 pub type CheckpointManagerHandle = Arc<CheckpointManager>;
 
@@ -506,6 +717,10 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn checkpoint_id(id: &str) -> CheckpointId {
+        id.parse().unwrap()
+    }
+
     #[tokio::test]
     async fn test_checkpoint_manager_creation() {
         let dir = tempdir().unwrap();
@@ -517,4 +732,275 @@ mod tests {
         let manager = CheckpointManager::new(config).await.unwrap();
         assert!(manager.latest().is_none());
     }
+
+    #[tokio::test]
+    async fn test_queued_writes_finish_after_shutdown() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let shutdown = ShutdownToken::new();
+        let manager = CheckpointManager::with_shutdown(config, shutdown.clone())
+            .await
+            .unwrap();
+
+        manager
+            .save_async(
+                Bytes::from_static(b"weights"),
+                1,
+                0,
+                CheckpointType::Full,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        shutdown.shutdown("test");
+
+        manager.wait_pending().await.unwrap();
+        assert_eq!(manager.latest().unwrap().step, 1);
+    }
+
+    #[tokio::test]
+    async fn test_pending_writes() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let manager = CheckpointManager::new(config).await.unwrap();
+
+        let id = manager
+            .save_async(
+                Bytes::from_static(b"weights"),
+                3,
+                1,
+                CheckpointType::Full,
+                HashMap::new(),
+            )
+            .await
+            .unwrap();
+        let pending = manager.pending_writes();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, id);
+        assert_eq!(pending[0].size_bytes, 7);
+        assert_ne!(pending[0].status, WriteStatus::Failed);
+
+        manager.wait_pending().await.unwrap();
+        let pending = manager.pending_writes();
+        assert_eq!(pending[0].status, WriteStatus::Completed);
+        assert_eq!((pending[0].step, pending[0].epoch), (3, 1));
+    }
+
+    #[tokio::test]
+    async fn test_register_metrics() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            keep_count: 1,
+            ..Default::default()
+        };
+        let manager = CheckpointManager::new(config).await.unwrap();
+        let registry = MetricsRegistry::new();
+        manager.register_metrics(&registry);
+
+        for step in 1..=2 {
+            manager
+                .save_async(
+                    Bytes::from_static(b"weights"),
+                    step,
+                    0,
+                    CheckpointType::Full,
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+            manager.wait_pending().await.unwrap();
+        }
+
+        let body = registry.render();
+        assert!(body.contains("strata_checkpoint_writes_total 2\n"));
+        assert!(body.contains("strata_checkpoint_write_seconds_count 2\n"));
+        assert!(body.contains("strata_checkpoint_writes_in_flight 0\n"));
+        assert!(body.contains("strata_checkpoint_evictions_total 1\n"));
+        assert!(body.contains("strata_checkpoint_write_failures_total 0\n"));
+    }
+
+    #[tokio::test]
+    async fn test_prune_candidates() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            keep_count: 5,
+            ..Default::default()
+        };
+
+        let manager = CheckpointManager::new(config).await.unwrap();
+        for step in 1..=4 {
+            let evicted = manager.register_external_checkpoint(
+                &checkpoint_id(&format!("ckpt-{}", step)),
+                step,
+                0,
+                &format!("/remote/ckpt-{}", step),
+                1024,
+                HashMap::new(),
+            );
+            assert!(evicted.is_empty());
+        }
+
+        let candidates: Vec<_> = manager
+            .prune_candidates(2)
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(candidates, vec!["ckpt-1", "ckpt-2"]);
+
+        // The latest checkpoint is always retained
+        assert_eq!(manager.prune_candidates(0).len(), 3);
+
+        assert!(manager
+            .remove_checkpoint(&checkpoint_id("ckpt-1"))
+            .is_some());
+        assert!(manager
+            .remove_checkpoint(&checkpoint_id("ckpt-1"))
+            .is_none());
+        assert_eq!(manager.all_checkpoints().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_delete_checkpoint() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let manager = CheckpointManager::new(config).await.unwrap();
+
+        let local = dir.path().join("ckpt-1.bin");
+        std::fs::write(&local, b"weights").unwrap();
+        manager.register_external_checkpoint(
+            &checkpoint_id("ckpt-1"),
+            1,
+            0,
+            &local.to_string_lossy(),
+            7,
+            HashMap::new(),
+        );
+        manager.register_external_checkpoint(
+            &checkpoint_id("ckpt-2"),
+            2,
+            0,
+            "/remote/ckpt-2",
+            7,
+            HashMap::new(),
+        );
+
+        assert!(manager
+            .delete_checkpoint(&checkpoint_id("ckpt-1"))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(!local.exists());
+        // Data kept elsewhere only drops the index entry
+        assert!(manager
+            .delete_checkpoint(&checkpoint_id("ckpt-2"))
+            .await
+            .unwrap()
+            .is_some());
+        assert!(manager
+            .delete_checkpoint(&checkpoint_id("ckpt-2"))
+            .await
+            .unwrap()
+            .is_none());
+        assert!(manager.all_checkpoints().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_load_latest() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            keep_count: 1,
+            ..Default::default()
+        };
+        let manager = CheckpointManager::new(config).await.unwrap();
+        assert!(manager.load_latest().await.unwrap().is_none());
+
+        for step in 1..=2 {
+            manager
+                .save_async(
+                    Bytes::from(vec![step as u8; 10]),
+                    step,
+                    0,
+                    CheckpointType::Full,
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+            manager.wait_pending().await.unwrap();
+        }
+        let (meta, data) = manager.load_latest().await.unwrap().unwrap();
+        assert_eq!(meta.step, 2);
+        assert_eq!(data, vec![2u8; 10]);
+
+        // Missing data of a checkpoint that is still the latest is an error
+        let missing = dir.path().join("missing.bin");
+        manager.register_external_checkpoint(
+            &checkpoint_id("ckpt-3"),
+            3,
+            0,
+            &missing.to_string_lossy(),
+            10,
+            HashMap::new(),
+        );
+        assert!(manager.load_latest().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_with_storage() {
+        let dir = tempdir().unwrap();
+        let storage = Arc::new(LocalStorage::new(dir.path().join("bucket")));
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().join("unused"),
+            keep_count: 1,
+            ..Default::default()
+        };
+        let manager =
+            CheckpointManager::with_storage(config, storage.clone(), ShutdownToken::new())
+                .await
+                .unwrap();
+
+        let mut ids = Vec::new();
+        for step in 1..=2 {
+            let id = manager
+                .save_async(
+                    Bytes::from(vec![step as u8; 100]),
+                    step,
+                    0,
+                    CheckpointType::Full,
+                    HashMap::new(),
+                )
+                .await
+                .unwrap();
+            manager.wait_pending().await.unwrap();
+            ids.push(id);
+        }
+
+        let latest = manager.latest().unwrap();
+        assert_eq!(latest.path, storage.uri(&format!("{}.ckpt", ids[1])));
+        assert_eq!(manager.get(&ids[1]).unwrap().step, 2);
+        assert!(manager.get(&ids[0]).is_none());
+        assert_eq!(manager.load(&ids[1]).await.unwrap(), vec![2u8; 100]);
+        assert!(!dir.path().join("unused").exists());
+
+        // The evicted checkpoint is deleted from storage in the background
+        let evicted = format!("{}.ckpt", ids[0]);
+        for _ in 0..50 {
+            if !storage.exists(&evicted).await.unwrap() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(!storage.exists(&evicted).await.unwrap());
+    }
 }
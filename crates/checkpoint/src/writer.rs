@@ -1,11 +1,12 @@
 //! Async checkpoint writer for non-blocking I/O
 
-use bytes::Bytes;
-use runtime_core::{CheckpointType, Epoch, Error, Result, Step};
+use bytes::{Buf, Bytes};
+use runtime_core::{CheckpointId, CheckpointType, Epoch, Error, Result, ShutdownToken, Step};
 use std::collections::HashMap;
-use std::path::PathBuf;
-use tokio::fs::File;
-use tokio::io::AsyncWriteExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use storage::StorageBackend;
 use tokio::sync::mpsc;
 use tracing::{debug, error, info, instrument, warn};
 
@@ -13,13 +14,13 @@ use tracing::{debug, error, info, instrument, warn};
 #[derive(Debug)]
 pub struct WriteRequest {
     /// Checkpoint identifier
-    pub checkpoint_id: String,
+    pub checkpoint_id: CheckpointId,
 
     /// Checkpoint data
     pub data: Bytes,
 
-    /// Target path
-    pub path: PathBuf,
+    /// Storage path of the checkpoint data
+    pub path: String,
 
     /// Training step
     pub step: Step,
@@ -37,14 +38,17 @@ pub struct WriteRequest {
 /// Event reported by writer
 #[derive(Debug)]
 pub enum WriterEvent {
+    /// Writer picked the request up from the queue
+    Started { checkpoint_id: CheckpointId },
     /// Write completed successfully
     Completed {
-        checkpoint_id: String,
+        checkpoint_id: CheckpointId,
         size_bytes: u64,
+        elapsed: Duration,
     },
     /// Write failed
     Failed {
-        checkpoint_id: String,
+        checkpoint_id: CheckpointId,
         error: String,
     },
 }
@@ -57,17 +61,27 @@ pub struct AsyncCheckpointWriter {
 
 impl AsyncCheckpointWriter {
     /// Create a new async writer
+    ///
+    /// Once `shutdown` fires the writer finishes the queued writes, refuses
+    /// new ones and stops.
     pub async fn new(
-        _base_path: PathBuf,
+        storage: Arc<dyn StorageBackend>,
         buffer_size: usize,
         compression: bool,
         event_tx: mpsc::Sender<WriterEvent>,
+        shutdown: ShutdownToken,
     ) -> Result<(mpsc::Sender<WriteRequest>, Self)> {
         // Ensure minimum channel capacity of 1 to prevent blocking
         let channel_capacity = (buffer_size / (1024 * 1024)).max(1);
         let (tx, rx) = mpsc::channel::<WriteRequest>(channel_capacity);
 
-        let task = tokio::spawn(Self::writer_loop(rx, event_tx, compression));
+        let task = tokio::spawn(Self::writer_loop(
+            rx,
+            storage,
+            event_tx,
+            compression,
+            shutdown,
+        ));
 
         Ok((tx, Self { _task: task }))
     }
@@ -75,21 +89,43 @@ impl AsyncCheckpointWriter {
     /// Main writer loop
     async fn writer_loop(
         mut rx: mpsc::Receiver<WriteRequest>,
+        storage: Arc<dyn StorageBackend>,
         event_tx: mpsc::Sender<WriterEvent>,
         compression: bool,
+        shutdown: ShutdownToken,
     ) {
         info!("Checkpoint writer started");
 
-        while let Some(request) = rx.recv().await {
+        let mut draining = false;
+        loop {
+            let request = tokio::select! {
+                request = rx.recv() => request,
+                _ = shutdown.wait(), if !draining => {
+                    info!("Checkpoint writer finishing queued writes before shutdown");
+                    draining = true;
+                    rx.close();
+                    continue;
+                }
+            };
+            let Some(request) = request else {
+                break;
+            };
+
             let checkpoint_id = request.checkpoint_id.clone();
-            let result = Self::write_checkpoint(&request, compression).await;
+            let _ = event_tx
+                .send(WriterEvent::Started {
+                    checkpoint_id: checkpoint_id.clone(),
+                })
+                .await;
+            let start = Instant::now();
+            let result = Self::write_checkpoint(&request, compression, storage.as_ref()).await;
 
             match result {
                 Ok(size) => {
                     debug!(
                         checkpoint_id = %request.checkpoint_id,
                         size_bytes = size,
-                        path = %request.path.display(),
+                        path = %storage.uri(&request.path),
                         "Checkpoint written successfully"
                     );
 
@@ -97,6 +133,7 @@ impl AsyncCheckpointWriter {
                         .send(WriterEvent::Completed {
                             checkpoint_id,
                             size_bytes: size,
+                            elapsed: start.elapsed(),
                         })
                         .await;
                 }
@@ -121,9 +158,13 @@ impl AsyncCheckpointWriter {
     }
 
     /// Write a single checkpoint
-    #[instrument(skip(request), fields(checkpoint_id = %request.checkpoint_id, step = request.step))]
-    async fn write_checkpoint(request: &WriteRequest, compression: bool) -> Result<u64> {
-        let start = std::time::Instant::now();
+    #[instrument(skip(request, storage), fields(checkpoint_id = %request.checkpoint_id, step = request.step))]
+    async fn write_checkpoint(
+        request: &WriteRequest,
+        compression: bool,
+        storage: &dyn StorageBackend,
+    ) -> Result<u64> {
+        let start = Instant::now();
 
         // Prepare data (optionally compress)
         let data = if compression {
@@ -132,33 +173,13 @@ impl AsyncCheckpointWriter {
             request.data.clone()
         };
 
-        // Write to temporary file first (atomic write pattern)
-        let temp_path = request.path.with_extension("tmp");
-
-        // Ensure parent directory exists
-        if let Some(parent) = request.path.parent() {
-            tokio::fs::create_dir_all(parent).await.map_err(Error::Io)?;
-        }
-
-        // Write data
-        let mut file = File::create(&temp_path).await.map_err(Error::Io)?;
-
-        // Write header with metadata
+        // Header with metadata, then the data, written as separate parts so
+        // the data is not copied. Backends write atomically, so a crash never
+        // leaves a partial checkpoint behind.
         let header = Self::create_header(request, compression)?;
-        file.write_all(&header).await.map_err(Error::Io)?;
-
-        // Write data
-        file.write_all(&data).await.map_err(Error::Io)?;
-
-        // Sync to disk
-        file.sync_all().await.map_err(Error::Io)?;
-
-        // Atomic rename
-        tokio::fs::rename(&temp_path, &request.path)
-            .await
-            .map_err(Error::Io)?;
-
-        let size = header.len() as u64 + data.len() as u64;
+        let size = storage
+            .write_parts(&request.path, vec![Bytes::from(header), data])
+            .await?;
         let elapsed = start.elapsed();
 
         info!(
@@ -224,24 +245,29 @@ impl AsyncCheckpointWriter {
         Ok(data.clone())
     }
 
-    /// Read checkpoint data from file
-    pub async fn read_checkpoint_data(path: &PathBuf) -> Result<Bytes> {
-        use tokio::io::{AsyncReadExt, AsyncSeekExt};
-
-        let mut file = File::open(path).await.map_err(Error::Io)?;
+    /// Read checkpoint data from a local file
+    pub async fn read_checkpoint_data(path: &Path) -> Result<Bytes> {
+        let file = tokio::fs::read(path).await.map_err(Error::Io)?;
+        Self::decode_checkpoint_data(Bytes::from(file))
+            .map_err(|e| e.with_path(path.display().to_string()))
+    }
 
-        // Read magic (4)
-        let mut magic = [0u8; 4];
-        file.read_exact(&mut magic).await.map_err(Error::Io)?;
+    /// Strip the header from a checkpoint file, returning its data
+    pub fn decode_checkpoint_data(mut file: Bytes) -> Result<Bytes> {
+        // Magic (4), version (4), step (8), epoch (8), type (1),
+        // compressed (1), data size (8), metadata length (4)
+        const FIXED_HEADER_LEN: usize = 38;
+        let truncated = || Error::storage("Truncated checkpoint");
 
-        if magic != CHECKPOINT_MAGIC {
-            return Err(Error::Storage {
-                message: "Invalid checkpoint magic".to_string(),
-            });
+        if file.len() < FIXED_HEADER_LEN {
+            return Err(truncated());
+        }
+        if file[..4] != CHECKPOINT_MAGIC {
+            return Err(Error::storage("Invalid checkpoint magic"));
         }
+        file.advance(4);
 
-        // Read version (4)
-        let version = file.read_u32_le().await.map_err(Error::Io)?;
+        let version = file.get_u32_le();
         if version != CHECKPOINT_VERSION {
             warn!(
                 "Checkpoint version mismatch: expected {}, got {}",
@@ -250,26 +276,16 @@ impl AsyncCheckpointWriter {
         }
 
         // Skip step (8), epoch (8), type (1), compressed (1)
-        // 8+8+1+1 = 18 bytes
-        let mut skipped = [0u8; 18];
-        file.read_exact(&mut skipped).await.map_err(Error::Io)?;
-
-        // Read data size (8)
-        let data_size = file.read_u64_le().await.map_err(Error::Io)?;
-
-        // Read metadata length (4)
-        let meta_len = file.read_u32_le().await.map_err(Error::Io)?;
+        file.advance(18);
+        let data_size = file.get_u64_le() as usize;
+        let meta_len = file.get_u32_le() as usize;
 
         // Skip metadata
-        file.seek(std::io::SeekFrom::Current(meta_len as i64))
-            .await
-            .map_err(Error::Io)?;
-
-        // Read data
-        let mut data = vec![0u8; data_size as usize];
-        file.read_exact(&mut data).await.map_err(Error::Io)?;
-
-        Ok(Bytes::from(data))
+        if file.len() < meta_len.saturating_add(data_size) {
+            return Err(truncated());
+        }
+        file.advance(meta_len);
+        Ok(file.split_to(data_size))
     }
 }
 
@@ -295,28 +311,40 @@ pub const CHECKPOINT_VERSION: u32 = 1;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use storage::LocalStorage;
     use tempfile::tempdir;
 
     #[tokio::test]
     async fn test_write_checkpoint() {
         let dir = tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
         let path = dir.path().join("test.ckpt");
 
         let request = WriteRequest {
-            checkpoint_id: "test-1".to_string(),
+            checkpoint_id: "test-1".parse().unwrap(),
             data: Bytes::from(vec![1u8; 1000]),
-            path: path.clone(),
+            path: "test.ckpt".to_string(),
             step: 100,
             epoch: 1,
             checkpoint_type: CheckpointType::Full,
             metadata: HashMap::new(),
         };
 
-        let size = AsyncCheckpointWriter::write_checkpoint(&request, false)
+        let size = AsyncCheckpointWriter::write_checkpoint(&request, false, &storage)
             .await
             .unwrap();
 
         assert!(size > 1000);
         assert!(path.exists());
+
+        let data = AsyncCheckpointWriter::read_checkpoint_data(&path)
+            .await
+            .unwrap();
+        assert_eq!(data, request.data);
+
+        let file = storage.read("test.ckpt").await.unwrap();
+        assert!(
+            AsyncCheckpointWriter::decode_checkpoint_data(file.slice(..file.len() - 1)).is_err()
+        );
     }
 }
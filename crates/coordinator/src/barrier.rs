@@ -0,0 +1,560 @@
+//! Barrier synchronization with explicit generations
+//!
+//! A barrier is identified by its ID plus a generation, so the same ID can be
+//! reused across training steps without collisions. Released barriers linger
+//! for a short time so late arrivals get an immediate "already released"
+//! answer instead of recreating the barrier and waiting forever.
+//!
+//! An administrator can force-release a barrier that is stuck on a crashed
+//! worker, or abort it so waiting workers give up instead of timing out.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use parking_lot::Mutex;
+use tokio::sync::{oneshot, watch};
+use tracing::{debug, info, warn};
+
+use runtime_core::{BarrierId, WorkerId};
+
+/// Default time a released barrier is remembered
+pub const DEFAULT_BARRIER_LINGER: Duration = Duration::from_secs(30);
+
+/// Lifecycle state of a barrier
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarrierStatus {
+    /// Waiting for more participants
+    Waiting,
+
+    /// All participants arrived and waiters were released
+    Released,
+
+    /// An administrator aborted the barrier
+    Aborted,
+}
+
+/// How an open barrier ended for the workers waiting at it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarrierRelease {
+    /// The barrier was released
+    Released {
+        /// Number of participants that arrived
+        participants: u64,
+        /// Released by an administrator before every participant arrived
+        forced: bool,
+    },
+
+    /// The barrier was aborted; waiters must not proceed
+    Aborted,
+}
+
+/// Result of a worker arriving at a barrier
+#[derive(Debug)]
+pub enum ArriveOutcome {
+    /// This arrival completed the barrier
+    Released {
+        /// Number of participants
+        participants: u64,
+        /// Order in which this worker arrived (1-based)
+        arrival_order: u64,
+    },
+
+    /// The barrier is still open; await the receiver for release
+    Waiting {
+        /// Resolves once the barrier is released or aborted
+        release: oneshot::Receiver<BarrierRelease>,
+        /// Order in which this worker arrived (1-based)
+        arrival_order: u64,
+        /// Workers arrived so far, including this one
+        arrived: u64,
+        /// Workers needed for release
+        expected: u64,
+    },
+
+    /// The barrier had already been released before this arrival
+    AlreadyReleased {
+        /// Number of participants at release
+        participants: u64,
+    },
+
+    /// The barrier was aborted before this arrival
+    Aborted,
+}
+
+/// Point-in-time view of a barrier
+#[derive(Debug, Clone)]
+pub struct BarrierInfo {
+    /// Barrier identifier
+    pub barrier_id: BarrierId,
+    /// Barrier generation
+    pub generation: u64,
+    /// Participants that have arrived
+    pub arrived: u64,
+    /// Participants required for release
+    pub expected: u64,
+    /// Current status
+    pub status: BarrierStatus,
+    /// When the barrier was created
+    pub created_at: DateTime<Utc>,
+}
+
+/// Mutable barrier state, guarded by a single lock
+struct BarrierInner {
+    /// Arrival order per worker
+    arrivals: HashMap<WorkerId, u64>,
+    /// Channels to notify waiting workers
+    waiters: Vec<oneshot::Sender<BarrierRelease>>,
+    /// Outcome and time, set when the barrier is released or aborted
+    closed: Option<(BarrierRelease, Instant)>,
+}
+
+impl BarrierInner {
+    /// Close the barrier and notify waiting workers
+    fn close(&mut self, outcome: BarrierRelease) {
+        self.closed = Some((outcome, Instant::now()));
+        for waiter in self.waiters.drain(..) {
+            let _ = waiter.send(outcome);
+        }
+    }
+}
+
+/// A single barrier instance
+struct Barrier {
+    expected: u64,
+    created_at: DateTime<Utc>,
+    inner: Mutex<BarrierInner>,
+}
+
+impl Barrier {
+    fn new(expected: u64) -> Self {
+        Self {
+            expected,
+            created_at: Utc::now(),
+            inner: Mutex::new(BarrierInner {
+                arrivals: HashMap::new(),
+                waiters: Vec::new(),
+                closed: None,
+            }),
+        }
+    }
+}
+
+/// Registry of active and recently released barriers
+pub struct BarrierRegistry {
+    barriers: DashMap<(BarrierId, u64), Arc<Barrier>>,
+    linger: Duration,
+    /// Bumped on every arrival, release, abort and eviction
+    changes: watch::Sender<u64>,
+}
+
+impl Default for BarrierRegistry {
+    fn default() -> Self {
+        Self::new(DEFAULT_BARRIER_LINGER)
+    }
+}
+
+impl BarrierRegistry {
+    /// Create a registry that remembers released barriers for `linger`
+    pub fn new(linger: Duration) -> Self {
+        Self {
+            barriers: DashMap::new(),
+            linger,
+            changes: watch::channel(0).0,
+        }
+    }
+
+    /// Watch a counter that changes whenever any barrier does
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.changes.subscribe()
+    }
+
+    fn changed(&self) {
+        self.changes.send_modify(|changes| *changes += 1);
+    }
+
+    /// Register a worker's arrival at a barrier generation
+    ///
+    /// `expected` is only used when this arrival creates the barrier. A worker
+    /// arriving twice keeps its original arrival order and is not counted again.
+    pub fn arrive(
+        &self,
+        barrier_id: &str,
+        generation: u64,
+        worker_id: &WorkerId,
+        expected: u64,
+    ) -> ArriveOutcome {
+        let barrier = self
+            .barriers
+            .entry((barrier_id.to_string(), generation))
+            .or_insert_with(|| {
+                info!(
+                    barrier_id = %barrier_id,
+                    generation = generation,
+                    expected = expected,
+                    "Creating new barrier"
+                );
+                Arc::new(Barrier::new(expected.max(1)))
+            })
+            .clone();
+
+        let mut inner = barrier.inner.lock();
+
+        match inner.closed {
+            Some((BarrierRelease::Released { participants, .. }, _)) => {
+                return ArriveOutcome::AlreadyReleased { participants };
+            }
+            Some((BarrierRelease::Aborted, _)) => return ArriveOutcome::Aborted,
+            None => {}
+        }
+
+        let next_order = inner.arrivals.len() as u64 + 1;
+        let arrival_order = *inner
+            .arrivals
+            .entry(worker_id.clone())
+            .or_insert(next_order);
+        let arrived = inner.arrivals.len() as u64;
+        if arrival_order == next_order {
+            self.changed();
+        }
+
+        if arrived >= barrier.expected {
+            inner.close(BarrierRelease::Released {
+                participants: arrived,
+                forced: false,
+            });
+
+            info!(
+                barrier_id = %barrier_id,
+                generation = generation,
+                participants = arrived,
+                "Barrier released"
+            );
+
+            ArriveOutcome::Released {
+                participants: arrived,
+                arrival_order,
+            }
+        } else {
+            let (tx, rx) = oneshot::channel();
+            inner.waiters.push(tx);
+            ArriveOutcome::Waiting {
+                release: rx,
+                arrival_order,
+                arrived,
+                expected: barrier.expected,
+            }
+        }
+    }
+
+    /// Status of a barrier generation, if it is known
+    pub fn status(&self, barrier_id: &str, generation: u64) -> Option<BarrierStatus> {
+        self.barriers
+            .get(&(barrier_id.to_string(), generation))
+            .map(|b| Self::status_of(&b.inner.lock()))
+    }
+
+    /// Snapshot of all known barriers
+    pub fn snapshot(&self) -> Vec<BarrierInfo> {
+        self.barriers
+            .iter()
+            .map(|entry| {
+                let (barrier_id, generation) = entry.key().clone();
+                let barrier = entry.value();
+                let inner = barrier.inner.lock();
+                BarrierInfo {
+                    barrier_id,
+                    generation,
+                    arrived: inner.arrivals.len() as u64,
+                    expected: barrier.expected,
+                    status: Self::status_of(&inner),
+                    created_at: barrier.created_at,
+                }
+            })
+            .collect()
+    }
+
+    /// Number of barriers being tracked
+    pub fn len(&self) -> usize {
+        self.barriers.len()
+    }
+
+    /// Whether no barriers are being tracked
+    pub fn is_empty(&self) -> bool {
+        self.barriers.is_empty()
+    }
+
+    /// Release the open generations of a barrier without waiting for everyone
+    ///
+    /// `generation` of `None` releases every open generation. Returns the
+    /// barriers that were released.
+    pub fn force_release(&self, barrier_id: &str, generation: Option<u64>) -> Vec<BarrierInfo> {
+        self.close_open(barrier_id, generation, |arrived| BarrierRelease::Released {
+            participants: arrived,
+            forced: true,
+        })
+    }
+
+    /// Abort the open generations of a barrier
+    ///
+    /// Waiting workers and later arrivals are told the barrier was aborted.
+    /// `generation` of `None` aborts every open generation. Returns the
+    /// barriers that were aborted.
+    pub fn abort(&self, barrier_id: &str, generation: Option<u64>) -> Vec<BarrierInfo> {
+        self.close_open(barrier_id, generation, |_| BarrierRelease::Aborted)
+    }
+
+    fn close_open(
+        &self,
+        barrier_id: &str,
+        generation: Option<u64>,
+        outcome: impl Fn(u64) -> BarrierRelease,
+    ) -> Vec<BarrierInfo> {
+        let mut closed = Vec::new();
+        for entry in self.barriers.iter() {
+            let (id, gen) = entry.key();
+            if id != barrier_id || generation.is_some_and(|g| g != *gen) {
+                continue;
+            }
+            let barrier = entry.value();
+            let mut inner = barrier.inner.lock();
+            if inner.closed.is_some() {
+                continue;
+            }
+            let arrived = inner.arrivals.len() as u64;
+            let outcome = outcome(arrived);
+            warn!(
+                barrier_id = %barrier_id,
+                generation = gen,
+                arrived = arrived,
+                expected = barrier.expected,
+                outcome = ?outcome,
+                "Closing barrier by request"
+            );
+            inner.close(outcome);
+            closed.push(BarrierInfo {
+                barrier_id: id.clone(),
+                generation: *gen,
+                arrived,
+                expected: barrier.expected,
+                status: Self::status_of(&inner),
+                created_at: barrier.created_at,
+            });
+        }
+        if !closed.is_empty() {
+            self.changed();
+        }
+        closed.sort_by_key(|b| b.generation);
+        closed
+    }
+
+    /// Abort every barrier that is still waiting for participants
+    ///
+    /// Waiting workers are told the barrier was aborted, and the barriers
+    /// linger like any other closed barrier so later arrivals see the abort
+    /// too. Returns the number of barriers aborted.
+    pub fn abort_waiting(&self) -> usize {
+        let mut aborted = 0;
+        for entry in self.barriers.iter() {
+            let (barrier_id, generation) = entry.key();
+            let barrier = entry.value();
+            let mut inner = barrier.inner.lock();
+            if inner.closed.is_some() {
+                continue;
+            }
+            warn!(
+                barrier_id = %barrier_id,
+                generation = generation,
+                arrived = inner.arrivals.len(),
+                expected = barrier.expected,
+                "Aborting open barrier"
+            );
+            inner.close(BarrierRelease::Aborted);
+            aborted += 1;
+        }
+        if aborted > 0 {
+            self.changed();
+        }
+        aborted
+    }
+
+    /// Drop released barriers whose linger period has elapsed
+    ///
+    /// This walks every barrier, so it runs from the periodic garbage
+    /// collector rather than on the arrival path.
+    pub fn evict_expired(&self) {
+        let linger = self.linger;
+        let before = self.barriers.len();
+        self.barriers.retain(|(barrier_id, generation), barrier| {
+            let keep = barrier
+                .inner
+                .lock()
+                .closed
+                .is_none_or(|(_, at)| at.elapsed() < linger);
+            if !keep {
+                debug!(barrier_id = %barrier_id, generation = generation, "Evicting released barrier");
+            }
+            keep
+        });
+        if self.barriers.len() != before {
+            self.changed();
+        }
+    }
+
+    fn status_of(inner: &BarrierInner) -> BarrierStatus {
+        match inner.closed {
+            None => BarrierStatus::Waiting,
+            Some((BarrierRelease::Released { .. }, _)) => BarrierStatus::Released,
+            Some((BarrierRelease::Aborted, _)) => BarrierStatus::Aborted,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_barrier_release() {
+        let registry = BarrierRegistry::default();
+
+        let ArriveOutcome::Waiting { release, .. } =
+            registry.arrive("sync", 1, &worker_id("w1"), 2)
+        else {
+            panic!("first arrival should wait");
+        };
+        assert!(matches!(
+            registry.arrive("sync", 1, &worker_id("w2"), 2),
+            ArriveOutcome::Released {
+                participants: 2,
+                arrival_order: 2
+            }
+        ));
+        assert_eq!(
+            release.await.unwrap(),
+            BarrierRelease::Released {
+                participants: 2,
+                forced: false
+            }
+        );
+        assert_eq!(registry.status("sync", 1), Some(BarrierStatus::Released));
+    }
+
+    #[test]
+    fn test_late_arrival_gets_already_released() {
+        let registry = BarrierRegistry::default();
+        registry.arrive("sync", 1, &worker_id("w1"), 1);
+
+        assert!(matches!(
+            registry.arrive("sync", 1, &worker_id("w2"), 1),
+            ArriveOutcome::AlreadyReleased { participants: 1 }
+        ));
+
+        // A new generation of the same ID is a fresh barrier
+        assert!(matches!(
+            registry.arrive("sync", 2, &worker_id("w1"), 2),
+            ArriveOutcome::Waiting { .. }
+        ));
+    }
+
+    #[test]
+    fn test_duplicate_arrival_not_counted() {
+        let registry = BarrierRegistry::default();
+        let changes = registry.subscribe();
+        registry.arrive("sync", 1, &worker_id("w1"), 2);
+        let outcome = registry.arrive("sync", 1, &worker_id("w1"), 2);
+
+        assert!(matches!(
+            outcome,
+            ArriveOutcome::Waiting {
+                arrival_order: 1,
+                arrived: 1,
+                expected: 2,
+                ..
+            }
+        ));
+        assert_eq!(registry.snapshot()[0].arrived, 1);
+        assert_eq!(*changes.borrow(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_abort_waiting() {
+        let registry = BarrierRegistry::default();
+        registry.arrive("done", 1, &worker_id("w1"), 1);
+        let ArriveOutcome::Waiting { release, .. } =
+            registry.arrive("open", 1, &worker_id("w1"), 2)
+        else {
+            panic!("first arrival should wait");
+        };
+
+        assert_eq!(registry.abort_waiting(), 1);
+        assert_eq!(release.await, Ok(BarrierRelease::Aborted));
+        assert_eq!(registry.status("open", 1), Some(BarrierStatus::Aborted));
+        assert!(matches!(
+            registry.arrive("open", 1, &worker_id("w2"), 2),
+            ArriveOutcome::Aborted
+        ));
+        assert_eq!(registry.status("done", 1), Some(BarrierStatus::Released));
+    }
+
+    #[tokio::test]
+    async fn test_force_release_and_abort() {
+        let registry = BarrierRegistry::default();
+        let ArriveOutcome::Waiting { release: first, .. } =
+            registry.arrive("sync", 1, &worker_id("w1"), 3)
+        else {
+            panic!("first arrival should wait");
+        };
+        let ArriveOutcome::Waiting {
+            release: second, ..
+        } = registry.arrive("sync", 2, &worker_id("w1"), 3)
+        else {
+            panic!("first arrival should wait");
+        };
+        registry.arrive("other", 1, &worker_id("w1"), 3);
+
+        let released = registry.force_release("sync", Some(1));
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].status, BarrierStatus::Released);
+        assert_eq!(
+            first.await.unwrap(),
+            BarrierRelease::Released {
+                participants: 1,
+                forced: true
+            }
+        );
+        assert!(matches!(
+            registry.arrive("sync", 1, &worker_id("w2"), 3),
+            ArriveOutcome::AlreadyReleased { participants: 1 }
+        ));
+
+        // Without a generation every open generation is closed
+        let aborted = registry.abort("sync", None);
+        assert_eq!(aborted.len(), 1);
+        assert_eq!(aborted[0].generation, 2);
+        assert_eq!(second.await.unwrap(), BarrierRelease::Aborted);
+        assert!(matches!(
+            registry.arrive("sync", 2, &worker_id("w2"), 3),
+            ArriveOutcome::Aborted
+        ));
+        assert_eq!(registry.status("other", 1), Some(BarrierStatus::Waiting));
+        assert!(registry.abort("sync", None).is_empty());
+    }
+
+    #[test]
+    fn test_released_barriers_evicted_after_linger() {
+        let registry = BarrierRegistry::new(Duration::ZERO);
+        registry.arrive("sync", 1, &worker_id("w1"), 1);
+        registry.arrive("open", 1, &worker_id("w1"), 2);
+
+        registry.evict_expired();
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.status("open", 1), Some(BarrierStatus::Waiting));
+    }
+}
@@ -4,27 +4,70 @@
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use tokio::runtime::Handle;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-use coordinator::{http_api, CoordinatorServer, CoordinatorService};
+use coordinator::middleware::HttpRateLimiter;
+use coordinator::schedules::SCHEDULE_CHECK_INTERVAL;
+use coordinator::server::ServerConfig;
+use coordinator::service::GARBAGE_COLLECTION_INTERVAL;
+use coordinator::{http_api, CoordinatorServer, CoordinatorService, LogBuffer, SimulationEngine};
+use runtime_core::config::RuntimeConfig;
+use runtime_core::supervisor::TaskState;
+use runtime_core::{RestartPolicy, Supervisor};
+
+/// Restart policy of the periodic background tasks
+const BACKGROUND_RESTART_POLICY: RestartPolicy = RestartPolicy::OnFailure {
+    max_restarts: 10,
+    backoff: Duration::from_secs(1),
+};
+
+/// How long each background task gets to stop at shutdown
+const BACKGROUND_STOP_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    // Initialize tracing
+    // Initialize tracing, keeping recent logs in memory for /api/logs
+    let log_buffer = Arc::new(LogBuffer::default());
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "coordinator=info,runtime_core=info".into()),
         )
         .with(tracing_subscriber::fmt::layer())
+        .with(log_buffer.layer())
         .init();
 
-    // Parse gRPC address from args or use default
-    let grpc_addr: SocketAddr = std::env::args()
-        .nth(1)
-        .and_then(|s| s.parse().ok())
-        .unwrap_or_else(|| "0.0.0.0:50051".parse().unwrap());
+    // Config file from STRATA_CONFIG, with STRATA_<SECTION>__<FIELD> overrides
+    let mut config = match std::env::var("STRATA_CONFIG") {
+        Ok(path) => {
+            tracing::info!("Loading configuration from {}", path);
+            RuntimeConfig::from_file(&path)?
+        }
+        Err(_) => RuntimeConfig::from_env()?,
+    };
+
+    // DEMO_MODE=true runs the simulated cluster
+    if std::env::var("DEMO_MODE").is_ok_and(|v| v == "true") {
+        config.coordinator.simulation.enabled = true;
+    }
+
+    // Report every invalid setting at once instead of failing on the first
+    if let Err(violations) = config.validate() {
+        for violation in &violations {
+            tracing::error!("Invalid configuration: {}", violation);
+        }
+        return Err(runtime_core::Error::from(violations).into());
+    }
+    let mut server_config = ServerConfig::from_runtime_config(&config)?;
+
+    // gRPC address from args overrides the configured bind address
+    if let Some(addr) = std::env::args().nth(1).and_then(|s| s.parse().ok()) {
+        server_config.addr = addr;
+    }
+    let grpc_addr = server_config.addr;
 
     // HTTP API address (gRPC port + 1000)
     let http_addr: SocketAddr = format!("0.0.0.0:{}", grpc_addr.port() + 1000)
@@ -35,34 +78,167 @@ async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     tracing::info!("Starting coordinator HTTP API on {}", http_addr);
 
     // Create service (Clone-able, so we can share between gRPC and HTTP)
-    let service = CoordinatorService::new().await?;
+    let service = CoordinatorService::from_runtime_config(&config)
+        .await?
+        .with_log_buffer(log_buffer);
+
+    // Keep an append-only audit trail of cluster events when configured
+    if let Some(path) = &config.coordinator.event_log_path {
+        if let Err(e) = service.events().log().persist_to(path) {
+            tracing::warn!(path = %path, error = %e, "Event log persistence disabled");
+        }
+    }
+
+    // Background tasks are restarted if they fail and stopped newest first
+    let supervisor = Arc::new(Supervisor::new(Handle::current()));
+
+    // Periodically remove workers that stopped heartbeating
+    let reaper_interval = config.coordinator.dead_worker_check_interval;
+    let reaper = service.clone();
+    supervisor.spawn(
+        "dead-worker-reaper",
+        BACKGROUND_RESTART_POLICY,
+        move |shutdown| {
+            reaper
+                .clone()
+                .run_dead_worker_reaper(reaper_interval, shutdown)
+        },
+    );
+
+    // Drop released barriers and expired leases
+    let collector = service.clone();
+    supervisor.spawn(
+        "garbage-collector",
+        BACKGROUND_RESTART_POLICY,
+        move |shutdown| {
+            collector
+                .clone()
+                .run_garbage_collector(GARBAGE_COLLECTION_INTERVAL, shutdown)
+        },
+    );
+
+    // Sample metrics for the dashboard's history charts
+    let sample_interval = config.coordinator.metrics_history.sample_interval;
+    let sampler = service.clone();
+    supervisor.spawn(
+        "metrics-sampler",
+        BACKGROUND_RESTART_POLICY,
+        move |shutdown| {
+            sampler
+                .clone()
+                .run_metrics_sampler(sample_interval, shutdown)
+        },
+    );
+
+    // Start tasks from their schedules
+    let scheduler = service.clone();
+    supervisor.spawn(
+        "task-scheduler",
+        BACKGROUND_RESTART_POLICY,
+        move |shutdown| {
+            scheduler
+                .clone()
+                .run_task_scheduler(SCHEDULE_CHECK_INTERVAL, shutdown)
+        },
+    );
+
+    // Drive synthetic workers through the service for demos
+    if config.coordinator.simulation.enabled {
+        tracing::info!(
+            workers = config.coordinator.simulation.workers,
+            "Starting simulated cluster"
+        );
+        SimulationEngine::new(service.clone(), config.coordinator.simulation.clone()).spawn();
+    }
 
     // Create HTTP API router with cloned service
     let http_service = Arc::new(service.clone());
-    let http_router = http_api::create_router(http_service);
+    let limiter = Arc::new(HttpRateLimiter::from_config(
+        &config.coordinator.http_rate_limit,
+    ));
+    let cors = http_api::cors_layer(&config.coordinator.http_cors)?;
+    let http_router = http_api::create_rate_limited_router(http_service, limiter.clone(), cors);
 
-    // Spawn HTTP server
+    // Apply rate limit changes made through PUT /api/config
+    let config_service = service.clone();
+    supervisor.spawn(
+        "rate-limit-reloader",
+        BACKGROUND_RESTART_POLICY,
+        move |mut shutdown| {
+            let mut config_updates = config_service.subscribe_config();
+            let limiter = limiter.clone();
+            async move {
+                loop {
+                    tokio::select! {
+                        changed = config_updates.changed() => {
+                            if changed.is_err() {
+                                break;
+                            }
+                            let limits = config_updates
+                                .borrow_and_update()
+                                .coordinator
+                                .http_rate_limit
+                                .clone();
+                            limiter.reconfigure(&limits);
+                        }
+                        _ = shutdown.wait() => break,
+                    }
+                }
+                Ok(())
+            }
+        },
+    );
+
+    // Servers, background tasks and the checkpoint writer stop together
+    let shutdown = service.shutdown_token();
+
+    // Once shutdown begins, stop the background tasks while the servers drain
+    let stop_shutdown = shutdown.clone();
+    let stop_supervisor = supervisor.clone();
+    let stop_handle = tokio::spawn(async move {
+        stop_shutdown.wait().await;
+        for task in stop_supervisor.shutdown(BACKGROUND_STOP_TIMEOUT).await {
+            if task.state != TaskState::Stopped {
+                tracing::warn!(
+                    task = %task.name,
+                    state = ?task.state,
+                    restarts = task.restarts,
+                    last_error = ?task.last_error,
+                    "Background task did not stop cleanly"
+                );
+            }
+        }
+    });
+
+    // Spawn HTTP server, stopping it together with the gRPC server
+    let http_shutdown = shutdown.clone();
+    let http_tls = config.coordinator.http_tls.clone();
     let http_handle = tokio::spawn(async move {
-        let listener = tokio::net::TcpListener::bind(http_addr).await.unwrap();
-        tracing::info!("HTTP API listening on {}", http_addr);
-        axum::serve(listener, http_router).await.unwrap();
+        let shutdown = async move { http_shutdown.wait().await };
+        if let Err(e) = http_api::serve(http_addr, http_router, http_tls.as_ref(), shutdown).await {
+            tracing::error!(error = %e, "HTTP API failed");
+        }
     });
 
     // Create and run gRPC server
-    let server = CoordinatorServer::new(service);
-    let grpc_handle = tokio::spawn(async move {
-        server.run_on(grpc_addr).await.unwrap();
+    let server = CoordinatorServer::with_config(service.clone(), server_config);
+    let mut grpc_handle = tokio::spawn(async move {
+        server.run().await.unwrap();
     });
 
-    // Wait for either server to finish
+    // The gRPC server owns shutdown, including the final state flush
     tokio::select! {
         _ = http_handle => {
             tracing::info!("HTTP server stopped");
+            service.begin_shutdown("HTTP server stopped");
+            let _ = grpc_handle.await;
         }
-        _ = grpc_handle => {
+        _ = &mut grpc_handle => {
             tracing::info!("gRPC server stopped");
+            service.begin_shutdown("gRPC server stopped");
         }
     }
+    let _ = stop_handle.await;
 
     Ok(())
 }
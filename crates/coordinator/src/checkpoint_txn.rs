@@ -0,0 +1,237 @@
+//! On-demand cluster checkpoints
+//!
+//! A checkpoint transaction asks a set of workers to checkpoint at a step and
+//! follows their checkpoint notifications until each of them reported one. The
+//! transaction fails if a worker leaves first or the deadline passes, so callers
+//! can poll for a definite outcome before making risky changes.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use runtime_core::{id, CheckpointId, Step, WorkerId};
+
+/// Time workers get to report a requested checkpoint
+pub const DEFAULT_CHECKPOINT_TXN_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Number of transactions kept for polling, oldest are dropped first
+const MAX_CHECKPOINT_TRANSACTIONS: usize = 100;
+
+/// Outcome of a checkpoint transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionState {
+    /// Waiting for workers to report their checkpoints
+    Pending,
+    /// Every worker reported a checkpoint at or after the requested step
+    Completed,
+    /// A worker left or the deadline passed
+    Failed,
+}
+
+/// A requested cluster checkpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct CheckpointTransaction {
+    pub id: String,
+    /// Step the workers were asked to checkpoint at
+    pub step: Step,
+    /// Workers taking part
+    #[schema(value_type = Vec<String>)]
+    pub worker_ids: Vec<WorkerId>,
+    /// Checkpoints reported so far, by worker
+    #[schema(value_type = BTreeMap<String, String>)]
+    pub checkpoint_ids: BTreeMap<WorkerId, CheckpointId>,
+    pub state: TransactionState,
+    pub created_at_ms: i64,
+    /// Workers must report before this time (ms since epoch)
+    pub deadline_ms: i64,
+    pub completed_at_ms: Option<i64>,
+    /// Why the transaction failed
+    pub error: Option<String>,
+}
+
+impl CheckpointTransaction {
+    /// Workers that have not reported a checkpoint yet
+    pub fn pending_workers(&self) -> impl Iterator<Item = &WorkerId> {
+        self.worker_ids
+            .iter()
+            .filter(|id| !self.checkpoint_ids.contains_key(*id))
+    }
+
+    fn finish(&mut self, state: TransactionState, error: Option<String>, now_ms: i64) {
+        self.state = state;
+        self.error = error;
+        self.completed_at_ms = Some(now_ms);
+    }
+}
+
+/// Recent checkpoint transactions
+#[derive(Debug)]
+pub struct CheckpointTransactions {
+    timeout: Duration,
+    /// Oldest first
+    transactions: Mutex<VecDeque<CheckpointTransaction>>,
+}
+
+impl Default for CheckpointTransactions {
+    fn default() -> Self {
+        Self::new(DEFAULT_CHECKPOINT_TXN_TIMEOUT)
+    }
+}
+
+impl CheckpointTransactions {
+    /// Create a registry whose transactions fail after `timeout`
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            transactions: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Start a transaction for a set of workers
+    pub fn begin(&self, step: Step, worker_ids: Vec<WorkerId>) -> CheckpointTransaction {
+        let now_ms = Utc::now().timestamp_millis();
+        let txn = CheckpointTransaction {
+            id: id::next_id("ckpt_txn"),
+            step,
+            worker_ids,
+            checkpoint_ids: BTreeMap::new(),
+            state: TransactionState::Pending,
+            created_at_ms: now_ms,
+            deadline_ms: now_ms.saturating_add(self.timeout.as_millis() as i64),
+            completed_at_ms: None,
+            error: None,
+        };
+
+        let mut transactions = self.transactions.lock();
+        if transactions.len() >= MAX_CHECKPOINT_TRANSACTIONS {
+            transactions.pop_front();
+        }
+        transactions.push_back(txn.clone());
+        txn
+    }
+
+    /// Record a worker's checkpoint against pending transactions
+    ///
+    /// Returns the IDs of the transactions this completed.
+    pub fn record_checkpoint(
+        &self,
+        worker_id: &WorkerId,
+        checkpoint_id: &CheckpointId,
+        step: Step,
+    ) -> Vec<String> {
+        let now_ms = Utc::now().timestamp_millis();
+        let mut completed = Vec::new();
+
+        for txn in self.transactions.lock().iter_mut() {
+            if txn.state != TransactionState::Pending
+                || step < txn.step
+                || !txn.worker_ids.iter().any(|id| id == worker_id)
+            {
+                continue;
+            }
+            txn.checkpoint_ids
+                .entry(worker_id.clone())
+                .or_insert_with(|| checkpoint_id.clone());
+            if txn.pending_workers().next().is_none() {
+                txn.finish(TransactionState::Completed, None, now_ms);
+                completed.push(txn.id.clone());
+            }
+        }
+        completed
+    }
+
+    /// Fail pending transactions that timed out or lost a worker
+    pub fn refresh(&self, is_registered: impl Fn(&WorkerId) -> bool) {
+        self.refresh_at(Utc::now().timestamp_millis(), is_registered);
+    }
+
+    fn refresh_at(&self, now_ms: i64, is_registered: impl Fn(&WorkerId) -> bool) {
+        for txn in self.transactions.lock().iter_mut() {
+            if txn.state != TransactionState::Pending {
+                continue;
+            }
+            let error = if let Some(gone) = txn.pending_workers().find(|id| !is_registered(id)) {
+                format!("worker {} left before checkpointing", gone)
+            } else if now_ms > txn.deadline_ms {
+                "timed out waiting for checkpoints".to_string()
+            } else {
+                continue;
+            };
+            txn.finish(TransactionState::Failed, Some(error), now_ms);
+        }
+    }
+
+    /// Get a transaction by ID
+    pub fn get(&self, txn_id: &str) -> Option<CheckpointTransaction> {
+        self.transactions
+            .lock()
+            .iter()
+            .find(|txn| txn.id == txn_id)
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    fn checkpoint_id(id: &str) -> CheckpointId {
+        id.parse().unwrap()
+    }
+
+    fn workers(ids: &[&str]) -> Vec<WorkerId> {
+        ids.iter().map(|id| worker_id(id)).collect()
+    }
+
+    #[test]
+    fn test_transaction_completes() {
+        let txns = CheckpointTransactions::default();
+        let txn = txns.begin(100, workers(&["worker-1", "worker-2"]));
+
+        // Older checkpoints and other workers do not count
+        assert!(txns
+            .record_checkpoint(&worker_id("worker-1"), &checkpoint_id("old"), 50)
+            .is_empty());
+        assert!(txns
+            .record_checkpoint(&worker_id("worker-3"), &checkpoint_id("other"), 100)
+            .is_empty());
+        assert!(txns
+            .record_checkpoint(&worker_id("worker-1"), &checkpoint_id("ckpt-a"), 100)
+            .is_empty());
+        assert_eq!(txns.get(&txn.id).unwrap().state, TransactionState::Pending);
+
+        assert_eq!(
+            txns.record_checkpoint(&worker_id("worker-2"), &checkpoint_id("ckpt-b"), 120),
+            vec![txn.id.clone()]
+        );
+        let txn = txns.get(&txn.id).unwrap();
+        assert_eq!(txn.state, TransactionState::Completed);
+        assert_eq!(txn.checkpoint_ids["worker-1"], "ckpt-a");
+        assert!(txn.completed_at_ms.is_some());
+    }
+
+    #[test]
+    fn test_transaction_fails() {
+        let txns = CheckpointTransactions::new(Duration::from_secs(60));
+        let left = txns.begin(10, workers(&["worker-1", "worker-2"]));
+        let slow = txns.begin(10, workers(&["worker-1"]));
+
+        txns.refresh(|id| id != "worker-2");
+        let left = txns.get(&left.id).unwrap();
+        assert_eq!(left.state, TransactionState::Failed);
+        assert!(left.error.unwrap().contains("worker-2"));
+        assert_eq!(txns.get(&slow.id).unwrap().state, TransactionState::Pending);
+
+        txns.refresh_at(slow.deadline_ms + 1, |_| true);
+        assert_eq!(txns.get(&slow.id).unwrap().state, TransactionState::Failed);
+    }
+}
@@ -0,0 +1,435 @@
+//! Resilient coordinator client
+//!
+//! Wraps the generated gRPC client with multi-endpoint failover and retries
+//! with exponential backoff. Most exposed RPCs are safe to repeat: queries
+//! only read, dataset registration is idempotent, checkpoint notifications
+//! are deduplicated by ID and barrier arrivals by worker, so a retry after a
+//! lost response does not double-apply.
+//!
+//! Worker registration and deregistration are retried too but are not
+//! deduplicated. If the response to the first attempt is lost, the retry
+//! fails with `AlreadyExists` or `NotFound` although the worker was
+//! registered or removed; callers should treat those codes accordingly.
+//! Likewise, the commands carried by a lost heartbeat response are not sent
+//! again to a retried heartbeat.
+
+use std::future::Future;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Code, Response, Status, Streaming};
+use tracing::{debug, info, warn};
+
+use runtime_core::config::RetryConfig;
+use runtime_core::retry::Backoff;
+
+use crate::proto::{
+    coordinator_client::CoordinatorClient, BarrierRequest, BarrierResponse, CheckpointAck,
+    CheckpointInfo, ClusterMetrics, ClusterMetricsRequest, ClusterState, ClusterStateRequest,
+    DatasetAck, DatasetInfo, GetEventsRequest, GetEventsResponse, HeartbeatRequest,
+    HeartbeatResponse, RecoveryRequest, RecoveryResponse, ShardAssignment, ShardAssignmentList,
+    ShardRequest, ShipLogsRequest, ShipLogsResponse, WorkerConfig, WorkerInfo,
+};
+use crate::protocol::PROTOCOL_VERSION;
+
+/// Metadata key a non-leader coordinator can use to point at the current leader
+pub const LEADER_HINT_KEY: &str = "leader-address";
+
+/// Metadata keys carrying a server-suggested retry delay in milliseconds
+const RETRY_HINT_KEYS: [&str; 2] = [
+    runtime_core::error::RETRY_PUSHBACK_METADATA,
+    "reconnect-after-ms",
+];
+
+/// Heartbeats that can be queued on a heartbeat stream before sending waits
+const HEARTBEAT_STREAM_QUEUE: usize = 4;
+
+/// Resilient client configuration
+#[derive(Debug, Clone)]
+pub struct ResilientClientConfig {
+    /// Timeout for establishing a connection to one endpoint
+    pub connect_timeout: Duration,
+
+    /// Per-request timeout; must exceed the server's barrier timeout
+    pub request_timeout: Duration,
+
+    /// Retry and backoff settings
+    pub retry: RetryConfig,
+
+    /// Compression for outgoing requests; compressed responses are always accepted
+    pub compression: Option<CompressionEncoding>,
+}
+
+impl Default for ResilientClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(330),
+            retry: RetryConfig::default(),
+            compression: None,
+        }
+    }
+}
+
+/// Coordinator client that fails over between endpoints and retries transient errors
+pub struct ResilientCoordinatorClient {
+    endpoints: Vec<String>,
+    current: usize,
+    client: Option<CoordinatorClient<Channel>>,
+    config: ResilientClientConfig,
+}
+
+impl ResilientCoordinatorClient {
+    /// Create a client for the given coordinator addresses
+    ///
+    /// Connections are established lazily on the first call.
+    pub fn new(
+        endpoints: impl IntoIterator<Item = impl Into<String>>,
+        config: ResilientClientConfig,
+    ) -> Result<Self, Status> {
+        let endpoints: Vec<String> = endpoints.into_iter().map(Into::into).collect();
+        if endpoints.is_empty() {
+            return Err(Status::invalid_argument(
+                "At least one coordinator address is required",
+            ));
+        }
+
+        Ok(Self {
+            endpoints,
+            current: 0,
+            client: None,
+            config,
+        })
+    }
+
+    /// Address of the endpoint currently in use
+    pub fn current_endpoint(&self) -> &str {
+        &self.endpoints[self.current]
+    }
+
+    /// Register a worker
+    ///
+    /// Requests without a protocol version are sent with [`PROTOCOL_VERSION`].
+    pub async fn register_worker(
+        &mut self,
+        mut request: WorkerInfo,
+    ) -> Result<WorkerConfig, Status> {
+        if request.protocol_version == 0 {
+            request.protocol_version = PROTOCOL_VERSION;
+        }
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.register_worker(request).await }
+        })
+        .await
+    }
+
+    /// Send a heartbeat
+    pub async fn heartbeat(
+        &mut self,
+        request: HeartbeatRequest,
+    ) -> Result<HeartbeatResponse, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.heartbeat(request).await }
+        })
+        .await
+    }
+
+    /// Open a `StreamHeartbeats` stream
+    ///
+    /// Opening the stream is retried and fails over like any other call;
+    /// once open, a failed stream is not reopened. Heartbeats sent on the
+    /// returned sender are answered in order on the returned stream.
+    pub async fn stream_heartbeats(
+        &mut self,
+    ) -> Result<(mpsc::Sender<HeartbeatRequest>, Streaming<HeartbeatResponse>), Status> {
+        self.call(|mut c| async move {
+            let (requests, queued) = mpsc::channel(HEARTBEAT_STREAM_QUEUE);
+            let responses = c.stream_heartbeats(ReceiverStream::new(queued)).await?;
+            Ok(responses.map(|responses| (requests, responses)))
+        })
+        .await
+    }
+
+    /// Deregister a worker
+    pub async fn deregister_worker(&mut self, request: WorkerInfo) -> Result<WorkerConfig, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.deregister_worker(request).await }
+        })
+        .await
+    }
+
+    /// Register a dataset
+    pub async fn register_dataset(&mut self, request: DatasetInfo) -> Result<DatasetAck, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.register_dataset(request).await }
+        })
+        .await
+    }
+
+    /// Get shard assignments
+    pub async fn get_data_shard(
+        &mut self,
+        request: ShardRequest,
+    ) -> Result<ShardAssignment, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.get_data_shard(request).await }
+        })
+        .await
+    }
+
+    /// Get every shard assigned to the worker
+    pub async fn get_data_shards(
+        &mut self,
+        request: ShardRequest,
+    ) -> Result<ShardAssignmentList, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.get_data_shards(request).await }
+        })
+        .await
+    }
+
+    /// Report a completed checkpoint
+    pub async fn notify_checkpoint(
+        &mut self,
+        request: CheckpointInfo,
+    ) -> Result<CheckpointAck, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.notify_checkpoint(request).await }
+        })
+        .await
+    }
+
+    /// Get the latest checkpoint for recovery
+    pub async fn get_latest_checkpoint(
+        &mut self,
+        request: RecoveryRequest,
+    ) -> Result<RecoveryResponse, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.get_latest_checkpoint(request).await }
+        })
+        .await
+    }
+
+    /// Wait at a barrier
+    pub async fn wait_barrier(
+        &mut self,
+        request: BarrierRequest,
+    ) -> Result<BarrierResponse, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.wait_barrier(request).await }
+        })
+        .await
+    }
+
+    /// Get a cluster state snapshot
+    pub async fn get_cluster_state(
+        &mut self,
+        request: ClusterStateRequest,
+    ) -> Result<ClusterState, Status> {
+        self.call(|mut c| async move { c.get_cluster_state(request).await })
+            .await
+    }
+
+    /// Get aggregate cluster metrics
+    pub async fn get_cluster_metrics(&mut self) -> Result<ClusterMetrics, Status> {
+        self.call(|mut c| async move { c.get_cluster_metrics(ClusterMetricsRequest {}).await })
+            .await
+    }
+
+    /// Get audit events
+    pub async fn get_events(
+        &mut self,
+        request: GetEventsRequest,
+    ) -> Result<GetEventsResponse, Status> {
+        self.call(|mut c| async move { c.get_events(request).await })
+            .await
+    }
+
+    /// Send worker log lines to the coordinator
+    pub async fn ship_logs(
+        &mut self,
+        request: ShipLogsRequest,
+    ) -> Result<ShipLogsResponse, Status> {
+        self.call(|mut c| {
+            let request = request.clone();
+            async move { c.ship_logs(request).await }
+        })
+        .await
+    }
+
+    /// Run an RPC, retrying transient failures and failing over between endpoints
+    async fn call<T, F, Fut>(&mut self, mut rpc: F) -> Result<T, Status>
+    where
+        F: FnMut(CoordinatorClient<Channel>) -> Fut,
+        Fut: Future<Output = Result<Response<T>, Status>>,
+    {
+        let mut backoff = Backoff::new(self.config.retry.clone());
+        loop {
+            let result = match self.connected_client().await {
+                Ok(client) => rpc(client).await.map(Response::into_inner),
+                Err(status) => Err(status),
+            };
+
+            let status = match result {
+                Ok(response) => return Ok(response),
+                Err(status) if is_retryable(&status) => status,
+                Err(status) => return Err(status),
+            };
+
+            let Some(delay) = backoff.next_delay() else {
+                warn!(
+                    endpoint = %self.current_endpoint(),
+                    attempts = backoff.retries() + 1,
+                    error = %status,
+                    "Coordinator request failed, retries exhausted"
+                );
+                return Err(status);
+            };
+
+            self.handle_failure(&status);
+            let delay = retry_hint(&status).unwrap_or(delay);
+            debug!(
+                endpoint = %self.current_endpoint(),
+                attempt = backoff.retries(),
+                delay_ms = delay.as_millis() as u64,
+                error = %status,
+                "Retrying coordinator request"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    /// Return the client for the current endpoint, connecting if needed
+    async fn connected_client(&mut self) -> Result<CoordinatorClient<Channel>, Status> {
+        if let Some(client) = &self.client {
+            return Ok(client.clone());
+        }
+
+        let address = self.current_endpoint().to_string();
+        let endpoint = Endpoint::from_shared(address.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid address {}: {}", address, e)))?
+            .connect_timeout(self.config.connect_timeout)
+            .timeout(self.config.request_timeout);
+
+        let channel = endpoint
+            .connect()
+            .await
+            .map_err(|e| Status::unavailable(format!("Failed to connect to {}: {}", address, e)))?;
+
+        info!(endpoint = %address, "Connected to coordinator");
+        let mut client = CoordinatorClient::new(channel)
+            .accept_compressed(CompressionEncoding::Zstd)
+            .accept_compressed(CompressionEncoding::Gzip);
+        if let Some(encoding) = self.config.compression {
+            client = client.send_compressed(encoding);
+        }
+        self.client = Some(client.clone());
+        Ok(client)
+    }
+
+    /// Pick the endpoint to use after a retryable failure
+    fn handle_failure(&mut self, status: &Status) {
+        if let Some(leader) = status
+            .metadata()
+            .get(LEADER_HINT_KEY)
+            .and_then(|v| v.to_str().ok())
+        {
+            let index = match self.endpoints.iter().position(|e| e == leader) {
+                Some(index) => index,
+                None => {
+                    self.endpoints.push(leader.to_string());
+                    self.endpoints.len() - 1
+                }
+            };
+            if index != self.current {
+                info!(leader = %leader, "Following coordinator leader hint");
+                self.current = index;
+                self.client = None;
+            }
+            return;
+        }
+
+        // Overload is not an endpoint failure; back off against the same one
+        if status.code() == Code::Unavailable {
+            self.client = None;
+            self.current = (self.current + 1) % self.endpoints.len();
+        }
+    }
+}
+
+/// Whether a failed call may be retried
+///
+/// Besides the transient status codes, any status carrying a server retry
+/// hint is retried, such as a barrier or heartbeat timeout.
+pub fn is_retryable(status: &Status) -> bool {
+    matches!(
+        status.code(),
+        Code::Unavailable | Code::ResourceExhausted | Code::Aborted
+    ) || retry_hint(status).is_some()
+}
+
+/// Server-suggested retry delay, if any
+pub fn retry_hint(status: &Status) -> Option<Duration> {
+    RETRY_HINT_KEYS
+        .iter()
+        .filter_map(|key| status.metadata().get(*key))
+        .filter_map(|v| v.to_str().ok()?.parse::<u64>().ok())
+        .max()
+        .map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failover_rotation_and_leader_hint() {
+        let mut client = ResilientCoordinatorClient::new(
+            ["http://a:50051", "http://b:50051"],
+            ResilientClientConfig::default(),
+        )
+        .unwrap();
+
+        client.handle_failure(&Status::resource_exhausted("busy"));
+        assert_eq!(client.current_endpoint(), "http://a:50051");
+
+        client.handle_failure(&Status::unavailable("down"));
+        assert_eq!(client.current_endpoint(), "http://b:50051");
+
+        let mut status = Status::unavailable("not leader");
+        status
+            .metadata_mut()
+            .insert(LEADER_HINT_KEY, "http://c:50051".parse().unwrap());
+        client.handle_failure(&status);
+        assert_eq!(client.current_endpoint(), "http://c:50051");
+
+        let mut status = Status::resource_exhausted("busy");
+        status
+            .metadata_mut()
+            .insert("grpc-retry-pushback-ms", "250".parse().unwrap());
+        assert_eq!(retry_hint(&status), Some(Duration::from_millis(250)));
+
+        let status = Status::from(runtime_core::Error::BarrierTimeout {
+            barrier_id: "epoch-1".to_string(),
+            timeout_ms: 1000,
+        });
+        assert!(is_retryable(&status));
+        assert!(!is_retryable(&Status::from(
+            runtime_core::Error::WorkerNotFound {
+                worker_id: "worker-1".to_string(),
+            }
+        )));
+    }
+}
@@ -0,0 +1,247 @@
+//! Worker command queue
+//!
+//! Commands are queued per worker by the coordinator and delivered as
+//! strings in `HeartbeatResponse.pending_commands` on the next heartbeat.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use dashmap::DashMap;
+
+use runtime_core::{CheckpointId, WorkerId};
+
+/// Command sent from the coordinator to a worker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkerCommand {
+    /// Delete the worker's local shard of a checkpoint
+    DeleteCheckpoint {
+        /// Checkpoint to delete
+        checkpoint_id: CheckpointId,
+    },
+
+    /// Save a checkpoint at the given global step
+    CheckpointNow {
+        /// Step the checkpoint was scheduled for
+        step: u64,
+    },
+
+    /// Start working on a task the worker was assigned to
+    StartTask {
+        /// Task identifier
+        task_id: String,
+    },
+
+    /// Pause a running task
+    PauseTask {
+        /// Task identifier
+        task_id: String,
+    },
+
+    /// Resume a paused task
+    ResumeTask {
+        /// Task identifier
+        task_id: String,
+    },
+
+    /// Stop a task for good
+    StopTask {
+        /// Task identifier
+        task_id: String,
+    },
+
+    /// Finish up and leave the cluster; the worker's shards have been reassigned
+    Drain,
+}
+
+impl WorkerCommand {
+    /// Oldest protocol version that understands this command
+    pub fn min_protocol_version(&self) -> u32 {
+        match self {
+            WorkerCommand::DeleteCheckpoint { .. } => 1,
+            WorkerCommand::CheckpointNow { .. } => 2,
+            WorkerCommand::StartTask { .. }
+            | WorkerCommand::PauseTask { .. }
+            | WorkerCommand::ResumeTask { .. }
+            | WorkerCommand::StopTask { .. } => 3,
+            WorkerCommand::Drain => 5,
+        }
+    }
+}
+
+impl fmt::Display for WorkerCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkerCommand::DeleteCheckpoint { checkpoint_id } => {
+                write!(f, "delete_checkpoint:{}", checkpoint_id)
+            }
+            WorkerCommand::CheckpointNow { step } => write!(f, "checkpoint_now:{}", step),
+            WorkerCommand::StartTask { task_id } => write!(f, "start_task:{}", task_id),
+            WorkerCommand::PauseTask { task_id } => write!(f, "pause_task:{}", task_id),
+            WorkerCommand::ResumeTask { task_id } => write!(f, "resume_task:{}", task_id),
+            WorkerCommand::StopTask { task_id } => write!(f, "stop_task:{}", task_id),
+            WorkerCommand::Drain => write!(f, "drain"),
+        }
+    }
+}
+
+impl FromStr for WorkerCommand {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, arg) = match s.split_once(':') {
+            Some((name, arg)) => (name, Some(arg)),
+            None => (s, None),
+        };
+
+        match (name, arg) {
+            ("delete_checkpoint", Some(id)) => id
+                .parse()
+                .map(|checkpoint_id| WorkerCommand::DeleteCheckpoint { checkpoint_id })
+                .map_err(|e: runtime_core::Error| e.to_string()),
+            ("start_task", Some(id)) if !id.is_empty() => Ok(WorkerCommand::StartTask {
+                task_id: id.to_string(),
+            }),
+            ("pause_task", Some(id)) if !id.is_empty() => Ok(WorkerCommand::PauseTask {
+                task_id: id.to_string(),
+            }),
+            ("resume_task", Some(id)) if !id.is_empty() => Ok(WorkerCommand::ResumeTask {
+                task_id: id.to_string(),
+            }),
+            ("stop_task", Some(id)) if !id.is_empty() => Ok(WorkerCommand::StopTask {
+                task_id: id.to_string(),
+            }),
+            ("checkpoint_now", Some(step)) => step
+                .parse()
+                .map(|step| WorkerCommand::CheckpointNow { step })
+                .map_err(|_| format!("Invalid checkpoint step: {}", s)),
+            ("drain", None) => Ok(WorkerCommand::Drain),
+            _ => Err(format!("Unknown worker command: {}", s)),
+        }
+    }
+}
+
+/// Per-worker queue of commands awaiting delivery
+#[derive(Debug, Default)]
+pub struct CommandQueue {
+    queues: DashMap<WorkerId, VecDeque<WorkerCommand>>,
+}
+
+impl CommandQueue {
+    /// Create an empty command queue
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a command for a worker
+    pub fn push(&self, worker_id: &WorkerId, command: WorkerCommand) {
+        self.queues
+            .entry(worker_id.clone())
+            .or_default()
+            .push_back(command);
+    }
+
+    /// Return undelivered commands to the front of a worker's queue
+    pub fn requeue(&self, worker_id: &WorkerId, commands: Vec<WorkerCommand>) {
+        if commands.is_empty() {
+            return;
+        }
+        let mut queue = self.queues.entry(worker_id.clone()).or_default();
+        for command in commands.into_iter().rev() {
+            queue.push_front(command);
+        }
+    }
+
+    /// Take all queued commands for a worker, oldest first
+    pub fn drain(&self, worker_id: &WorkerId) -> Vec<WorkerCommand> {
+        self.queues
+            .get_mut(worker_id)
+            .map(|mut queue| queue.drain(..).collect())
+            .unwrap_or_default()
+    }
+
+    /// Number of commands waiting for a worker
+    pub fn pending(&self, worker_id: &WorkerId) -> usize {
+        self.queues.get(worker_id).map(|q| q.len()).unwrap_or(0)
+    }
+
+    /// Drop all commands for a worker that left the cluster
+    pub fn remove(&self, worker_id: &WorkerId) {
+        self.queues.remove(worker_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_command_round_trip() {
+        let command = WorkerCommand::DeleteCheckpoint {
+            checkpoint_id: "ckpt-100".parse().unwrap(),
+        };
+        let encoded = command.to_string();
+        assert_eq!(encoded, "delete_checkpoint:ckpt-100");
+        assert_eq!(encoded.parse::<WorkerCommand>().unwrap(), command);
+
+        assert!("delete_checkpoint".parse::<WorkerCommand>().is_err());
+        assert!("reboot:now".parse::<WorkerCommand>().is_err());
+
+        let command = WorkerCommand::CheckpointNow { step: 500 };
+        assert_eq!(command.to_string(), "checkpoint_now:500");
+        assert_eq!(
+            "checkpoint_now:500".parse::<WorkerCommand>().unwrap(),
+            command
+        );
+        assert!("checkpoint_now:soon".parse::<WorkerCommand>().is_err());
+
+        let command = WorkerCommand::PauseTask {
+            task_id: "task_1".to_string(),
+        };
+        assert_eq!(command.to_string(), "pause_task:task_1");
+        assert_eq!(
+            "pause_task:task_1".parse::<WorkerCommand>().unwrap(),
+            command
+        );
+        assert!("stop_task:".parse::<WorkerCommand>().is_err());
+
+        assert_eq!(WorkerCommand::Drain.to_string(), "drain");
+        assert_eq!(
+            "drain".parse::<WorkerCommand>().unwrap(),
+            WorkerCommand::Drain
+        );
+        assert!("drain:now".parse::<WorkerCommand>().is_err());
+    }
+
+    #[test]
+    fn test_queue_drain() {
+        let queue = CommandQueue::new();
+        for id in ["a", "b"] {
+            queue.push(
+                &worker_id("worker-1"),
+                WorkerCommand::DeleteCheckpoint {
+                    checkpoint_id: id.parse().unwrap(),
+                },
+            );
+        }
+
+        assert_eq!(queue.pending(&worker_id("worker-1")), 2);
+        assert_eq!(queue.drain(&worker_id("worker-1")).len(), 2);
+        assert_eq!(queue.pending(&worker_id("worker-1")), 0);
+        assert!(queue.drain(&worker_id("worker-2")).is_empty());
+
+        let command = |id: &str| WorkerCommand::DeleteCheckpoint {
+            checkpoint_id: id.parse().unwrap(),
+        };
+        queue.push(&worker_id("worker-1"), command("c"));
+        queue.requeue(&worker_id("worker-1"), vec![command("a"), command("b")]);
+        assert_eq!(
+            queue.drain(&worker_id("worker-1")),
+            vec![command("a"), command("b"), command("c")]
+        );
+    }
+}
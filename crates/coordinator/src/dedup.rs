@@ -0,0 +1,107 @@
+//! Request deduplication
+//!
+//! Remembers the result of recently processed requests by key so that
+//! client retries return the original response instead of being applied
+//! a second time.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+
+/// Cache of recent results keyed by an idempotency key
+#[derive(Debug)]
+pub struct DedupCache<V> {
+    /// How long a result is remembered
+    window: Duration,
+    /// key -> (processed at, result)
+    entries: DashMap<String, (Instant, V)>,
+    /// Number of duplicate requests served from the cache
+    duplicates: AtomicU64,
+}
+
+impl<V: Clone> DedupCache<V> {
+    /// Create a cache remembering results for `window`
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: DashMap::new(),
+            duplicates: AtomicU64::new(0),
+        }
+    }
+
+    /// Return the cached result for `key`, or compute and remember it
+    ///
+    /// The second element is `true` if the result came from the cache.
+    /// Concurrent callers with the same key are serialized, so `f` runs at
+    /// most once per window.
+    pub fn get_or_insert_with(&self, key: &str, f: impl FnOnce() -> V) -> (V, bool) {
+        self.evict_expired();
+
+        match self.entries.entry(key.to_string()) {
+            Entry::Occupied(entry) if entry.get().0.elapsed() < self.window => {
+                self.duplicates.fetch_add(1, Ordering::Relaxed);
+                (entry.get().1.clone(), true)
+            }
+            Entry::Occupied(mut entry) => {
+                let value = f();
+                entry.insert((Instant::now(), value.clone()));
+                (value, false)
+            }
+            Entry::Vacant(entry) => {
+                let value = f();
+                entry.insert((Instant::now(), value.clone()));
+                (value, false)
+            }
+        }
+    }
+
+    /// Number of duplicate requests served from the cache
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates.load(Ordering::Relaxed)
+    }
+
+    /// Number of remembered results
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no results are remembered
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drop results older than the window
+    fn evict_expired(&self) {
+        let window = self.window;
+        self.entries.retain(|_, (at, _)| at.elapsed() < window);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duplicate_returns_original() {
+        let cache = DedupCache::new(Duration::from_secs(60));
+
+        let (first, duplicate) = cache.get_or_insert_with("ckpt-1", || 1);
+        assert_eq!((first, duplicate), (1, false));
+
+        let (second, duplicate) = cache.get_or_insert_with("ckpt-1", || 2);
+        assert_eq!((second, duplicate), (1, true));
+        assert_eq!(cache.duplicates(), 1);
+    }
+
+    #[test]
+    fn test_expired_entries_recomputed() {
+        let cache = DedupCache::new(Duration::ZERO);
+
+        cache.get_or_insert_with("ckpt-1", || 1);
+        let (value, duplicate) = cache.get_or_insert_with("ckpt-1", || 2);
+        assert_eq!((value, duplicate), (2, false));
+        assert_eq!(cache.duplicates(), 0);
+    }
+}
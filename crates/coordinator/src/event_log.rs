@@ -0,0 +1,344 @@
+//! Append-only coordinator event log
+//!
+//! Keeps the most recent events in an in-memory ring for the events RPC and
+//! `/api/events`, and optionally appends every event as a JSON line to a file
+//! for auditing. Readers can watch the latest sequence number to tail the log.
+//!
+//! Events are recorded on the request path, so the file is written by a
+//! background thread fed through a bounded queue; a slow disk drops audit
+//! lines instead of stalling RPCs.
+
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::time::Duration;
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::warn;
+
+use runtime_core::id;
+use runtime_core::logging::LogThrottle;
+
+use crate::events::CoordinatorEvent;
+
+/// Default number of events kept in memory
+pub const DEFAULT_EVENT_LOG_CAPACITY: usize = 10_000;
+
+/// Events waiting for the file writer before new ones are dropped
+const PERSIST_QUEUE_CAPACITY: usize = 4096;
+
+/// Bytes read from the end of an existing file to find its last event
+const TAIL_WINDOW: u64 = 64 * 1024;
+
+/// Warns about events dropped because the file writer fell behind
+static PERSIST_BACKLOG_LOG: LogThrottle = LogThrottle::new(Duration::from_secs(10));
+
+/// An event with its position in the log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LoggedEvent {
+    /// Sequence number, starting at 1 and increasing without gaps
+    pub seq: u64,
+    /// Time-ordered ID, unique across coordinator restarts
+    #[serde(default)]
+    pub id: String,
+    /// When the event was recorded (ms since epoch)
+    pub timestamp_ms: i64,
+    /// The event itself
+    #[serde(flatten)]
+    pub event: CoordinatorEvent,
+}
+
+/// Work for the background file writer
+enum WriterMessage {
+    /// Append an event
+    Event(LoggedEvent),
+    /// Flush the file, then acknowledge
+    Flush(mpsc::Sender<()>),
+}
+
+struct EventLogInner {
+    /// Most recent events, oldest first
+    ring: VecDeque<LoggedEvent>,
+    /// Sequence number of the last recorded event
+    last_seq: u64,
+    /// Queue to the writer appending events to a JSON-lines file
+    writer: Option<SyncSender<WriterMessage>>,
+}
+
+/// Bounded in-memory event log with optional file persistence
+pub struct EventLog {
+    capacity: usize,
+    inner: Mutex<EventLogInner>,
+    /// Latest sequence number, for readers tailing the log
+    appended: watch::Sender<u64>,
+}
+
+impl Default for EventLog {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_LOG_CAPACITY)
+    }
+}
+
+impl EventLog {
+    /// Create a log keeping the last `capacity` events in memory
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(EventLogInner {
+                ring: VecDeque::new(),
+                last_seq: 0,
+                writer: None,
+            }),
+            appended: watch::channel(0).0,
+        }
+    }
+
+    /// Also append every future event to a JSON-lines file
+    ///
+    /// Sequence numbers continue after the last event already in the file, so
+    /// they stay unique across restarts. Events recorded before this call are
+    /// renumbered accordingly and written too; call it once, at startup.
+    pub fn persist_to(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let persisted_seq = last_persisted_seq(path)?;
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let (sender, receiver) = mpsc::sync_channel(PERSIST_QUEUE_CAPACITY);
+        std::thread::Builder::new()
+            .name("event-log-writer".to_string())
+            .spawn(move || write_events(BufWriter::new(file), receiver))?;
+
+        let mut inner = self.inner.lock();
+        inner.last_seq += persisted_seq;
+        for entry in inner.ring.iter_mut() {
+            entry.seq += persisted_seq;
+            let _ = sender.try_send(WriterMessage::Event(entry.clone()));
+        }
+        inner.writer = Some(sender);
+        self.appended.send_replace(inner.last_seq);
+        Ok(())
+    }
+
+    /// Record an event, returning its sequence number
+    pub fn append(&self, event: CoordinatorEvent) -> u64 {
+        let mut inner = self.inner.lock();
+        inner.last_seq += 1;
+
+        let entry = LoggedEvent {
+            seq: inner.last_seq,
+            id: id::next_id("evt"),
+            timestamp_ms: Utc::now().timestamp_millis(),
+            event,
+        };
+
+        if let Some(writer) = inner.writer.as_ref() {
+            match writer.try_send(WriterMessage::Event(entry.clone())) {
+                Ok(()) => {}
+                Err(TrySendError::Full(_)) => {
+                    if let Some(suppressed) = PERSIST_BACKLOG_LOG.allow() {
+                        warn!(
+                            seq = entry.seq,
+                            suppressed, "Event log writer is behind, dropping coordinator event"
+                        );
+                    }
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!("Event log writer stopped, no longer persisting events");
+                    inner.writer = None;
+                }
+            }
+        }
+
+        if inner.ring.len() >= self.capacity {
+            inner.ring.pop_front();
+        }
+        inner.ring.push_back(entry);
+        self.appended.send_replace(inner.last_seq);
+        inner.last_seq
+    }
+
+    /// Block until every event recorded so far has been written to the file
+    pub fn flush(&self) {
+        let Some(writer) = self.inner.lock().writer.clone() else {
+            return;
+        };
+        let (done, flushed) = mpsc::channel();
+        if writer.send(WriterMessage::Flush(done)).is_ok() {
+            let _ = flushed.recv();
+        }
+    }
+
+    /// Events with a sequence number greater than `after_seq`, oldest first
+    pub fn since(&self, after_seq: u64, limit: usize) -> Vec<LoggedEvent> {
+        self.inner
+            .lock()
+            .ring
+            .iter()
+            .filter(|e| e.seq > after_seq)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Sequence number of the most recent event (0 if none)
+    pub fn last_seq(&self) -> u64 {
+        self.inner.lock().last_seq
+    }
+
+    /// Watch the sequence number of the most recent event
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.appended.subscribe()
+    }
+}
+
+/// Append queued events to `file` until the log is dropped
+fn write_events(mut file: BufWriter<File>, messages: Receiver<WriterMessage>) {
+    while let Ok(message) = messages.recv() {
+        // Write whatever is queued, then flush once for the batch
+        let mut next = Some(message);
+        while let Some(message) = next {
+            match message {
+                WriterMessage::Event(entry) => {
+                    let written = serde_json::to_writer(&mut file, &entry)
+                        .map_err(io::Error::from)
+                        .and_then(|_| file.write_all(b"\n"));
+                    if let Err(e) = written {
+                        warn!(seq = entry.seq, error = %e, "Failed to persist coordinator event");
+                    }
+                }
+                WriterMessage::Flush(done) => {
+                    if let Err(e) = file.flush() {
+                        warn!(error = %e, "Failed to flush coordinator event log");
+                    }
+                    let _ = done.send(());
+                }
+            }
+            next = messages.try_recv().ok();
+        }
+        if let Err(e) = file.flush() {
+            warn!(error = %e, "Failed to flush coordinator event log");
+        }
+    }
+}
+
+/// Sequence number of the last event in an existing log file (0 if none)
+fn last_persisted_seq(path: &Path) -> io::Result<u64> {
+    #[derive(Deserialize)]
+    struct Seq {
+        seq: u64,
+    }
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let len = file.metadata()?.len();
+    let mut window = TAIL_WINDOW.min(len);
+    loop {
+        file.seek(SeekFrom::Start(len - window))?;
+        let mut tail = Vec::new();
+        Read::by_ref(&mut file)
+            .take(window)
+            .read_to_end(&mut tail)?;
+        let text = String::from_utf8_lossy(&tail);
+
+        // The first line may be cut off unless the window reaches the start,
+        // and the last one may be half-written if the coordinator crashed
+        let partial_first = usize::from(window < len);
+        let lines: Vec<&str> = text.lines().skip(partial_first).collect();
+        let last = lines
+            .iter()
+            .rev()
+            .find_map(|line| serde_json::from_str::<Seq>(line).ok());
+        if let Some(last) = last {
+            return Ok(last.seq);
+        }
+        if window == len {
+            return Ok(0);
+        }
+        window = (window * 2).min(len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joined(worker_id: &str) -> CoordinatorEvent {
+        CoordinatorEvent::WorkerJoined {
+            worker_id: worker_id.parse().unwrap(),
+            rank: 0,
+        }
+    }
+
+    #[test]
+    fn test_ring_keeps_latest_events() {
+        let log = EventLog::new(2);
+        for id in ["a", "b", "c"] {
+            log.append(joined(id));
+        }
+
+        let events = log.since(0, 10);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].seq, 2);
+        assert_eq!(log.since(2, 10).len(), 1);
+        assert_eq!(log.last_seq(), 3);
+    }
+
+    #[test]
+    fn test_persist_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+
+        let log = EventLog::default();
+        log.persist_to(&path).unwrap();
+        log.append(joined("worker-1"));
+        log.append(CoordinatorEvent::WorkerLeft {
+            worker_id: "worker-1".parse().unwrap(),
+        });
+
+        log.flush();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<LoggedEvent> = contents
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].event, joined("worker-1"));
+        assert!(contents.contains("\"type\":\"worker_left\""));
+    }
+
+    #[test]
+    fn test_seq_continues_after_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.jsonl");
+
+        let log = EventLog::default();
+        log.persist_to(&path).unwrap();
+        log.append(joined("worker-1"));
+        log.append(joined("worker-2"));
+        log.flush();
+        drop(log);
+
+        // Recorded before persistence starts, so it is renumbered
+        let restarted = EventLog::default();
+        restarted.append(joined("worker-3"));
+        restarted.persist_to(&path).unwrap();
+        assert_eq!(restarted.since(0, 10)[0].seq, 3);
+        assert_eq!(restarted.append(joined("worker-4")), 4);
+        restarted.flush();
+
+        let seqs: Vec<u64> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str::<LoggedEvent>(l).unwrap().seq)
+            .collect();
+        assert_eq!(seqs, vec![1, 2, 3, 4]);
+    }
+}
@@ -0,0 +1,254 @@
+//! Coordinator event bus
+//!
+//! Broadcasts cluster lifecycle events (worker joins, failures, rebalances)
+//! to any number of in-process subscribers and records them in the event log.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+
+use runtime_core::{BarrierId, CheckpointId, DatasetId, Epoch, Step, WorkerId, WorkerState};
+
+use crate::event_log::EventLog;
+use crate::tasks::TaskState;
+
+/// Default capacity of the event broadcast channel
+const DEFAULT_EVENT_CAPACITY: usize = 1024;
+
+/// Events emitted by the coordinator
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CoordinatorEvent {
+    /// A worker registered with the coordinator
+    WorkerJoined {
+        /// Worker identifier
+        worker_id: WorkerId,
+        /// Assigned rank
+        rank: u32,
+    },
+
+    /// A worker deregistered gracefully
+    WorkerLeft {
+        /// Worker identifier
+        worker_id: WorkerId,
+    },
+
+    /// A worker missed its heartbeat deadline and was removed
+    WorkerDead {
+        /// Worker identifier
+        worker_id: WorkerId,
+    },
+
+    /// A worker reported a different state in its heartbeat
+    WorkerStateChanged {
+        /// Worker identifier
+        worker_id: WorkerId,
+        /// New worker state
+        state: WorkerState,
+    },
+
+    /// An operator took a worker out of rotation
+    WorkerDraining {
+        /// Worker identifier
+        worker_id: WorkerId,
+    },
+
+    /// An operator blacklisted a worker
+    WorkerBlacklisted {
+        /// Worker identifier
+        worker_id: WorkerId,
+        /// Why the worker was blacklisted
+        reason: String,
+    },
+
+    /// Shards were redistributed across the remaining workers
+    ShardsRebalanced {
+        /// Number of workers after rebalancing
+        workers: usize,
+    },
+
+    /// Worker ranks were reassigned to `0..world_size`
+    RanksCompacted {
+        /// Membership generation carrying the new ranks
+        generation: u64,
+        /// Number of workers
+        world_size: usize,
+    },
+
+    /// A dataset was registered for sharding
+    DatasetRegistered {
+        /// Dataset identifier
+        dataset_id: DatasetId,
+    },
+
+    /// A dataset was deregistered
+    DatasetRemoved {
+        /// Dataset identifier
+        dataset_id: DatasetId,
+    },
+
+    /// A dataset's shuffle settings changed
+    DatasetUpdated {
+        /// Dataset identifier
+        dataset_id: DatasetId,
+    },
+
+    /// A dataset moved to a new epoch
+    EpochAdvanced {
+        /// Dataset identifier
+        dataset_id: DatasetId,
+        /// New epoch number
+        epoch: Epoch,
+    },
+
+    /// All participants arrived at a barrier
+    BarrierReleased {
+        /// Barrier identifier
+        barrier_id: BarrierId,
+        /// Barrier generation
+        generation: u64,
+        /// Number of participants
+        participants: u64,
+    },
+
+    /// An administrator released or aborted an open barrier
+    BarrierClosed {
+        /// Barrier identifier
+        barrier_id: BarrierId,
+        /// Barrier generation
+        generation: u64,
+        /// Participants that had arrived
+        arrived: u64,
+        /// Whether waiters were aborted rather than released
+        aborted: bool,
+        /// Why the barrier was closed
+        reason: String,
+    },
+
+    /// A worker reported a completed checkpoint
+    CheckpointCommitted {
+        /// Checkpoint identifier
+        checkpoint_id: CheckpointId,
+        /// Training step of the checkpoint
+        step: Step,
+        /// Worker that wrote the checkpoint
+        worker_id: WorkerId,
+    },
+
+    /// A checkpoint was removed from the index
+    CheckpointDeleted {
+        /// Checkpoint identifier
+        checkpoint_id: CheckpointId,
+        /// Whether the checkpoint data was deleted too
+        data_deleted: bool,
+    },
+
+    /// A training task changed state
+    TaskStateChanged {
+        /// Task identifier
+        task_id: String,
+        /// New state
+        state: TaskState,
+    },
+
+    /// A schedule ran, creating a task unless it failed
+    ScheduleRan {
+        /// Schedule identifier
+        schedule_id: String,
+        /// Task created by the run
+        task_id: Option<String>,
+        /// Why no task was created
+        error: Option<String>,
+    },
+
+    /// Runtime settings were changed through the API
+    ConfigUpdated {
+        /// Names of the changed settings
+        settings: Vec<String>,
+    },
+
+    /// The coordinator started shutting down
+    ShuttingDown {
+        /// Why the shutdown was requested
+        reason: String,
+        /// Open barriers that were aborted
+        barriers_aborted: usize,
+    },
+}
+
+/// Broadcast bus for coordinator events
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<CoordinatorEvent>,
+    log: Arc<EventLog>,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_CAPACITY)
+    }
+}
+
+impl EventBus {
+    /// Create a new event bus with the given channel capacity
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            log: Arc::new(EventLog::default()),
+        }
+    }
+
+    /// Publish an event to the log and all current subscribers
+    pub fn publish(&self, event: CoordinatorEvent) {
+        self.log.append(event.clone());
+        let _ = self.sender.send(event);
+    }
+
+    /// Log of every event published on this bus
+    pub fn log(&self) -> &Arc<EventLog> {
+        &self.log
+    }
+
+    /// Subscribe to events published after this call
+    pub fn subscribe(&self) -> broadcast::Receiver<CoordinatorEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of active subscribers
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_publish_subscribe() {
+        let bus = EventBus::default();
+        let mut rx = bus.subscribe();
+
+        bus.publish(CoordinatorEvent::WorkerLeft {
+            worker_id: "worker-1".parse().unwrap(),
+        });
+
+        let event = rx.recv().await.unwrap();
+        assert_eq!(
+            event,
+            CoordinatorEvent::WorkerLeft {
+                worker_id: "worker-1".parse().unwrap()
+            }
+        );
+    }
+
+    #[test]
+    fn test_publish_without_subscribers() {
+        let bus = EventBus::default();
+        bus.publish(CoordinatorEvent::ShardsRebalanced { workers: 0 });
+        assert_eq!(bus.subscriber_count(), 0);
+        assert_eq!(bus.log().last_seq(), 1);
+    }
+}
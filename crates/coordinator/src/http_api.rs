@@ -1,40 +1,126 @@
 //! HTTP API for dashboard integration
 //!
-//! Provides REST endpoints for the dashboard to query coordinator state.
-
+//! Provides REST endpoints for the dashboard to query coordinator state, and a
+//! server-sent events stream at `/api/stream` that pushes cluster events,
+//! changed state and log lines so the dashboard stays current without polling.
+//! Prometheus metrics are exported at `/metrics`. The OpenAPI description is
+//! served at `/api/openapi.json`, with Swagger UI at `/api/docs`.
+//!
+//! [`serve`] runs the API over TLS when a certificate is configured.
+//! [`create_rate_limited_router`] limits how often each client can call the
+//! API, so a tight polling loop cannot starve the coordinator. Browsers may
+//! only call the API from the origins allowed by [`cors_layer`].
+//!
+//! Responses are compressed when the client accepts it, and JSON responses to
+//! `GET` carry an `ETag` so pollers can revalidate with `If-None-Match` and get
+//! a bodiless `304 Not Modified` when nothing changed.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::convert::Infallible;
+use std::future::Future;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::IntoResponse,
-    routing::{get, post},
+    extract::{ConnectInfo, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::{self, Next},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+    routing::{delete, get, post},
     Json, Router,
 };
-use dashmap::DashMap;
+use axum_server::tls_rustls::RustlsConfig;
 use serde::Serialize;
-use std::sync::OnceLock;
-use tower_http::cors::{Any, CorsLayer};
-
-use crate::service::CoordinatorService;
-
-// Global state for stopped tasks and user-created tasks
-static STOPPED_TASKS: OnceLock<DashMap<String, i64>> = OnceLock::new();
-static TASK_STORE: OnceLock<DashMap<String, TaskResponse>> = OnceLock::new();
-
-fn get_stopped_tasks() -> &'static DashMap<String, i64> {
-    STOPPED_TASKS.get_or_init(DashMap::new)
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tower_http::compression::CompressionLayer;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
+use tracing::info;
+use utoipa::{IntoParams, OpenApi, ToSchema};
+use utoipa_swagger_ui::SwaggerUi;
+
+use runtime_core::config::{HttpCorsConfig, HttpRateLimitConfig, TlsConfig};
+use runtime_core::{prometheus, GpuSummary, ResourceRates, WorkerId};
+
+use crate::checkpoint_txn::{CheckpointTransaction, TransactionState};
+use crate::logs::{LogEntry, LogFilter, LogLevel};
+use crate::metrics_history::{parse_range, DatasetProgressSample, MetricsSample};
+use crate::middleware::HttpRateLimiter;
+use crate::proto::coordinator_server::Coordinator;
+use crate::proto::DatasetInfo;
+use crate::schedules::{Schedule, ScheduleRun, TaskTemplate};
+use crate::service::{parse_id, CoordinatorService};
+use crate::tasks::{Task, TaskState};
+use crate::worker_history::{MetricSample, ResourceSample, StateTransition, WorkerError};
+
+/// Events sent per read of the event log while streaming
+const STREAM_BATCH_SIZE: usize = 256;
+
+/// Buffered SSE messages per client before the stream applies backpressure
+const STREAM_BUFFER: usize = 64;
+
+/// Log lines returned by `/api/logs` when no limit is given
+const DEFAULT_LOGS_LIMIT: usize = 100;
+
+/// Range returned by `/api/metrics/history` when none is given
+const DEFAULT_METRICS_HISTORY_RANGE: Duration = Duration::from_secs(60 * 60);
+
+/// `Cache-Control` of JSON responses to `GET`: cacheable, but always revalidated
+const API_CACHE_CONTROL: &str = "no-cache";
+
+/// Time open HTTPS connections get to finish on shutdown
+const HTTP_SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Error body returned by the HTTP API
+#[derive(Debug, Serialize, serde::Deserialize, ToSchema)]
+pub struct ErrorResponse {
+    pub error: String,
+    /// Request field that failed validation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
 }
 
-fn get_task_store() -> &'static DashMap<String, TaskResponse> {
-    TASK_STORE.get_or_init(DashMap::new)
+/// Error response: HTTP status with an [`ErrorResponse`] body
+pub type ApiError = (StatusCode, Json<ErrorResponse>);
+
+/// Convert a service error into an HTTP error response
+fn api_error(status: tonic::Status) -> ApiError {
+    let code = match status.code() {
+        tonic::Code::NotFound => StatusCode::NOT_FOUND,
+        tonic::Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        tonic::Code::PermissionDenied => StatusCode::FORBIDDEN,
+        tonic::Code::FailedPrecondition | tonic::Code::AlreadyExists | tonic::Code::Aborted => {
+            StatusCode::CONFLICT
+        }
+        tonic::Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        tonic::Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        tonic::Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    let field = status
+        .metadata()
+        .get("invalid-field")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    (
+        code,
+        Json(ErrorResponse {
+            error: status.message().to_string(),
+            field,
+        }),
+    )
 }
 
 /// Shared state for HTTP handlers (Arc for thread-safe sharing)
 pub type AppState = Arc<CoordinatorService>;
 
 /// Worker info for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct WorkerResponse {
     pub id: String,
     pub ip: String,
@@ -48,8 +134,40 @@ pub struct WorkerResponse {
     pub current_task: String,
 }
 
+/// Worker details for API response
+#[derive(Serialize, ToSchema)]
+pub struct WorkerDetailResponse {
+    #[serde(flatten)]
+    pub worker: WorkerResponse,
+    pub rank: u32,
+    pub world_size: u32,
+    pub registered_at: i64,
+    pub metadata: BTreeMap<String, String>,
+    /// Labels selectors match on
+    pub labels: BTreeMap<String, String>,
+    /// Taints as `key` or `key=value`
+    pub taints: Vec<String>,
+    /// Shards last assigned to the worker, by dataset
+    pub shards: BTreeMap<String, Vec<u64>>,
+    /// Resources from recent heartbeats, oldest first
+    pub resource_history: Vec<ResourceSample>,
+    /// Disk and network bytes per second over recent heartbeats, unset until
+    /// two heartbeats arrived
+    #[schema(value_type = Option<Object>)]
+    pub io_rates: Option<ResourceRates>,
+    /// Totals and averages over the worker's GPUs from its last heartbeat
+    #[schema(value_type = Object)]
+    pub gpus: GpuSummary,
+    /// Recent state changes, oldest first
+    pub transitions: Vec<StateTransition>,
+    /// Training metrics from recent heartbeats by name, oldest first
+    pub metrics: BTreeMap<String, Vec<MetricSample>>,
+    /// Last error-level log line the worker shipped
+    pub last_error: Option<WorkerError>,
+}
+
 /// Dataset info for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct DatasetResponse {
     pub id: String,
     pub name: String,
@@ -59,10 +177,93 @@ pub struct DatasetResponse {
     pub format: String,
     pub shuffle: bool,
     pub registered_at: i64,
+    pub current_epoch: u64,
+    /// Fraction of the current epoch consumed (0.0 - 1.0)
+    pub epoch_progress: f64,
+    /// Estimated seconds until the current epoch completes
+    pub eta_seconds: Option<u64>,
+}
+
+/// Epoch state of a dataset for API response
+#[derive(Serialize, ToSchema)]
+pub struct EpochResponse {
+    pub dataset_id: String,
+    pub epoch: u64,
+    pub samples_consumed: u64,
+    pub total_samples: u64,
+    pub completed_shards: u64,
+    pub total_shards: u64,
+    /// Fraction of the epoch consumed (0.0 - 1.0)
+    pub progress: f64,
+    /// Seconds since the epoch started
+    pub elapsed_seconds: u64,
+    /// Estimated seconds until the epoch completes
+    pub eta_seconds: Option<u64>,
+    /// Healthy workers that reported finishing this epoch
+    pub workers_reported: Vec<String>,
+    /// Reports needed before the epoch advances on its own
+    pub workers_required: usize,
+}
+
+/// Dataset details for API response
+#[derive(Serialize, ToSchema)]
+pub struct DatasetDetailResponse {
+    pub id: String,
+    pub path: String,
+    pub format: String,
+    pub total_samples: u64,
+    pub shard_size: u64,
+    pub shard_count: u64,
+    pub shuffle: bool,
+    pub seed: u64,
+    pub metadata: BTreeMap<String, String>,
+    /// Data files under the dataset path, empty when it is not on local storage
+    pub file_paths: Vec<String>,
+    /// Number of data files in `file_paths`, at most 1000
+    pub file_count: usize,
+    /// Whether the dataset has more files than `file_paths` lists
+    pub files_truncated: bool,
+    pub epoch: EpochResponse,
+    /// Boundaries of the first 1000 shards
+    pub shards: Vec<ShardResponse>,
+    /// Start of the first data file, when requested with `?preview=`
+    pub preview: Option<DatasetPreviewResponse>,
+}
+
+/// A shard of a dataset for API response
+#[derive(Serialize, ToSchema)]
+pub struct ShardResponse {
+    pub shard_id: u64,
+    pub start_index: u64,
+    pub end_index: u64,
+    /// Samples consumed in the current epoch
+    pub samples_consumed: u64,
+    /// Worker the shard was last assigned to
+    pub worker_id: Option<String>,
+}
+
+/// Start of a dataset file for API response
+#[derive(Serialize, ToSchema)]
+pub struct DatasetPreviewResponse {
+    pub path: String,
+    /// Lines of a text file, or rows of 16 hex-encoded bytes of a binary one
+    pub lines: Vec<String>,
+    pub binary: bool,
+    /// Whether the file continues past the preview
+    pub truncated: bool,
+}
+
+/// Query parameters for dataset details
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DatasetDetailParams {
+    /// Number of preview lines to read from the first data file (at most 100)
+    #[serde(default)]
+    pub preview: usize,
 }
 
 /// Checkpoint info for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CheckpointResponse {
     pub id: String,
     pub step: u64,
@@ -75,7 +276,7 @@ pub struct CheckpointResponse {
 }
 
 /// Barrier status for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct BarrierResponse {
     pub id: String,
     pub name: String,
@@ -86,7 +287,7 @@ pub struct BarrierResponse {
 }
 
 /// System metrics for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct MetricsResponse {
     pub checkpoint_throughput: u64,
     pub coordinator_rps: u64,
@@ -94,10 +295,48 @@ pub struct MetricsResponse {
     pub total_workers: u32,
     pub barrier_latency_p99: u64,
     pub shard_assignment_time: u64,
+    pub duplicate_checkpoint_notifications: u64,
+    /// Disk and network bytes per second of all active workers
+    #[schema(value_type = Object)]
+    pub io_rates: ResourceRates,
+    /// GPU totals and averages of all active workers
+    #[schema(value_type = Object)]
+    pub gpus: GpuSummary,
+}
+
+/// Runtime settings that can be changed without a restart
+///
+/// Unset fields are left as they are.
+#[derive(Debug, Default, serde::Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigUpdateRequest {
+    /// Time without a heartbeat before a worker is removed (ms)
+    pub heartbeat_timeout_ms: Option<u64>,
+    /// Number of checkpoints to keep
+    pub checkpoint_keep_count: Option<usize>,
+    /// HTTP API rate limits, replacing the current ones
+    #[schema(value_type = Option<Object>)]
+    pub http_rate_limit: Option<HttpRateLimitConfig>,
+}
+
+/// Query parameters for the metrics history
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MetricsHistoryParams {
+    /// How far back to go, e.g. `15m`, `1h` or `1d` (default `1h`)
+    pub range: Option<String>,
+}
+
+/// Sampled metrics over a time range
+#[derive(Serialize, ToSchema)]
+pub struct MetricsHistoryResponse {
+    pub range_seconds: u64,
+    /// Oldest first
+    pub samples: Vec<MetricsSample>,
 }
 
 /// Task info for API response
-#[derive(Serialize, Clone)]
+#[derive(Serialize, Clone, ToSchema)]
 pub struct TaskResponse {
     pub id: String,
     pub name: String,
@@ -111,8 +350,33 @@ pub struct TaskResponse {
     pub logs: Vec<String>,
 }
 
+impl From<&Task> for TaskResponse {
+    fn from(task: &Task) -> Self {
+        Self {
+            id: task.id.clone(),
+            name: task.name.clone(),
+            r#type: task.task_type.clone(),
+            status: task.state.as_str().to_string(),
+            worker_ids: task.worker_ids.iter().map(WorkerId::to_string).collect(),
+            dataset_id: task.dataset_id.to_string(),
+            started_at: task.created_at_ms,
+            completed_at: task.completed_at_ms,
+            progress: (task.progress * 100.0).round() as u32,
+            logs: task
+                .logs
+                .iter()
+                .map(|line| {
+                    let time = chrono::DateTime::from_timestamp_millis(line.timestamp_ms)
+                        .unwrap_or_default();
+                    format!("[{}] {}", time.format("%H:%M:%S"), line.message)
+                })
+                .collect(),
+        }
+    }
+}
+
 /// Log entry for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct LogResponse {
     pub id: String,
     pub timestamp: i64,
@@ -123,8 +387,97 @@ pub struct LogResponse {
     pub worker_id: Option<String>,
 }
 
+impl From<LogEntry> for LogResponse {
+    fn from(entry: LogEntry) -> Self {
+        Self {
+            id: format!("log_{}", entry.seq),
+            timestamp: entry.timestamp_ms,
+            level: entry.level.to_string(),
+            message: entry.message,
+            source: entry.source,
+            task_id: entry.task_id,
+            worker_id: entry.worker_id,
+        }
+    }
+}
+
+/// Worker blacklist request
+#[derive(Default, serde::Deserialize, ToSchema)]
+pub struct BlacklistWorkerRequest {
+    #[serde(default)]
+    pub reason: String,
+}
+
+/// Dataset registration request, mirroring the gRPC `DatasetInfo`
+#[derive(serde::Deserialize, ToSchema)]
+pub struct RegisterDatasetRequest {
+    pub dataset_id: String,
+    pub path: String,
+    pub format: String,
+    pub total_samples: i64,
+    pub shard_size: i64,
+    #[serde(default)]
+    pub shuffle: bool,
+    #[serde(default)]
+    pub seed: i64,
+    #[serde(default)]
+    pub metadata: std::collections::HashMap<String, String>,
+}
+
+/// Dataset registration response
+#[derive(Serialize, ToSchema)]
+pub struct RegisterDatasetResponse {
+    pub dataset_id: String,
+    pub total_shards: i64,
+    pub message: String,
+}
+
+/// Dataset update request; omitted fields are left unchanged
+#[derive(serde::Deserialize, ToSchema)]
+pub struct UpdateDatasetRequest {
+    pub shuffle: Option<bool>,
+    pub seed: Option<i64>,
+}
+
+/// Epoch advance request
+#[derive(Default, serde::Deserialize, ToSchema)]
+pub struct AdvanceEpochRequest {
+    /// Only advance if this is still the current epoch
+    pub expected_epoch: Option<u64>,
+}
+
+/// Query parameters for deleting a checkpoint
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteCheckpointParams {
+    /// Also delete the stored checkpoint data
+    #[serde(default)]
+    pub delete_data: bool,
+}
+
+/// Query parameters for closing a barrier
+#[derive(serde::Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct CloseBarrierParams {
+    /// Generation to close, every open generation when unset
+    pub generation: Option<u64>,
+    /// Release the waiting workers instead of aborting the barrier
+    #[serde(default)]
+    pub release: bool,
+    /// Why the barrier is being closed
+    pub reason: Option<String>,
+}
+
+/// Checkpoint trigger request
+#[derive(Default, serde::Deserialize, ToSchema)]
+pub struct TriggerCheckpointRequest {
+    /// Workers to checkpoint, all live workers when empty
+    #[serde(default)]
+    pub worker_ids: Vec<String>,
+}
+
 /// Task creation request
-#[derive(serde::Deserialize)]
+#[derive(serde::Deserialize, ToSchema)]
 pub struct CreateTaskRequest {
     pub name: String,
     pub r#type: String,
@@ -134,19 +487,42 @@ pub struct CreateTaskRequest {
 }
 
 /// Task creation response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct CreateTaskResponse {
     pub task_id: String,
 }
 
 /// Task stop response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct StopTaskResponse {
     pub success: bool,
 }
 
+/// Schedule creation request
+#[derive(serde::Deserialize, ToSchema)]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    /// Five-field cron expression in UTC, e.g. `0 2 * * *` for 02:00 nightly
+    pub cron: String,
+    pub template: TaskTemplate,
+    /// Whether the schedule fires (default true)
+    pub enabled: Option<bool>,
+    /// Start runs while the previous run's task is still active
+    #[serde(default)]
+    pub allow_overlap: bool,
+}
+
+/// Schedule update request; omitted fields are left unchanged
+#[derive(serde::Deserialize, ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct UpdateScheduleRequest {
+    pub cron: Option<String>,
+    pub enabled: Option<bool>,
+    pub allow_overlap: Option<bool>,
+}
+
 /// Coordinator status for API response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct StatusResponse {
     pub connected: bool,
     pub address: String,
@@ -155,7 +531,7 @@ pub struct StatusResponse {
 }
 
 /// Full dashboard state response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct DashboardState {
     pub coordinator: StatusResponse,
     pub workers: Vec<WorkerResponse>,
@@ -167,37 +543,406 @@ pub struct DashboardState {
     pub logs: Vec<LogResponse>,
 }
 
+/// OpenAPI description of the HTTP API, served at `/api/openapi.json`
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "Strata coordinator API"),
+    paths(
+        health_check,
+        get_status,
+        get_config,
+        update_config,
+        get_workers,
+        get_worker,
+        drain_worker,
+        remove_worker,
+        blacklist_worker,
+        unblacklist_worker,
+        get_datasets,
+        get_dataset,
+        register_dataset,
+        update_dataset,
+        delete_dataset,
+        get_dataset_epoch,
+        advance_dataset_epoch,
+        get_checkpoints,
+        delete_checkpoint,
+        trigger_checkpoint,
+        get_checkpoint_transaction,
+        get_barriers,
+        close_barrier,
+        get_metrics,
+        get_metrics_history,
+        get_prometheus_metrics,
+        get_dashboard_state,
+        get_tasks,
+        create_task,
+        stop_task,
+        pause_task,
+        resume_task,
+        get_task_logs,
+        get_schedules,
+        create_schedule,
+        get_schedule,
+        update_schedule,
+        delete_schedule,
+        run_schedule,
+        get_logs,
+        get_events,
+        stream_events,
+    ),
+    components(schemas(
+        ErrorResponse,
+        LogLevel,
+        TransactionState,
+        DatasetProgressSample
+    )),
+    tags(
+        (name = "system", description = "Coordinator health and status"),
+        (name = "workers", description = "Worker membership and maintenance"),
+        (name = "datasets", description = "Dataset registration and sharding"),
+        (name = "checkpoints", description = "Checkpoint index and on-demand checkpoints"),
+        (name = "cluster", description = "Cluster-wide state for the dashboard"),
+        (name = "tasks", description = "Training tasks"),
+        (name = "schedules", description = "Recurring training tasks"),
+        (name = "logs", description = "Coordinator and worker logs"),
+        (name = "events", description = "Coordinator event log"),
+    )
+)]
+pub struct ApiDoc;
+
 /// Create the HTTP API router
-pub fn create_router(service: Arc<CoordinatorService>) -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+pub fn create_router(service: Arc<CoordinatorService>, cors: CorsLayer) -> Router {
+    routes()
+        .layer(middleware::from_fn(conditional_get))
+        .layer(CompressionLayer::new())
+        .layer(cors)
+        .with_state(service)
+}
+
+/// Create the HTTP API router, rate limiting requests per client
+///
+/// CORS is applied outside the limiter so preflight requests are never limited
+/// and browsers can read the 429 responses.
+pub fn create_rate_limited_router(
+    service: Arc<CoordinatorService>,
+    limiter: Arc<HttpRateLimiter>,
+    cors: CorsLayer,
+) -> Router {
+    routes()
+        .layer(middleware::from_fn(conditional_get))
+        .layer(middleware::from_fn_with_state(limiter, rate_limit))
+        .layer(CompressionLayer::new())
+        .layer(cors)
+        .with_state(service)
+}
+
+/// Build the CORS policy for the HTTP API
+///
+/// Fails on an origin, method or header that is not valid in an HTTP header.
+pub fn cors_layer(config: &HttpCorsConfig) -> std::io::Result<CorsLayer> {
+    let origins = if is_wildcard(&config.allowed_origins) {
+        AllowOrigin::any()
+    } else {
+        let origins = config
+            .allowed_origins
+            .iter()
+            // Browsers send origins without a trailing slash
+            .map(|origin| {
+                parse_cors_value("origin", origin.trim_end_matches('/'), |o| {
+                    o.contains("://")
+                        .then(|| HeaderValue::from_str(o).ok())
+                        .flatten()
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        AllowOrigin::list(origins)
+    };
+    let methods = if is_wildcard(&config.allowed_methods) {
+        AllowMethods::any()
+    } else {
+        let methods = config
+            .allowed_methods
+            .iter()
+            .map(|method| {
+                parse_cors_value("method", method, |m| {
+                    http::Method::from_bytes(m.to_ascii_uppercase().as_bytes()).ok()
+                })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+        AllowMethods::list(methods)
+    };
+    let headers = if is_wildcard(&config.allowed_headers) {
+        AllowHeaders::any()
+    } else {
+        let headers = config
+            .allowed_headers
+            .iter()
+            .map(|name| parse_cors_value("header", name, |h| h.parse::<header::HeaderName>().ok()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        AllowHeaders::list(headers)
+    };
+
+    Ok(CorsLayer::new()
+        .allow_origin(origins)
+        .allow_methods(methods)
+        .allow_headers(headers))
+}
 
+/// Whether a CORS list allows any value
+fn is_wildcard(values: &[String]) -> bool {
+    values.len() == 1 && values[0] == "*"
+}
+
+fn parse_cors_value<T>(
+    kind: &str,
+    value: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> std::io::Result<T> {
+    let invalid = |reason: &str| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("invalid CORS {} {:?}{}", kind, value, reason),
+        )
+    };
+    let value = value.trim();
+    if value == "*" {
+        return Err(invalid(": \"*\" must be the only entry"));
+    }
+    parse(value).ok_or_else(|| invalid(""))
+}
+
+fn routes() -> Router<AppState> {
     Router::new()
         .route("/api/health", get(health_check))
         .route("/api/status", get(get_status))
+        .route("/api/config", get(get_config).put(update_config))
         .route("/api/workers", get(get_workers))
-        .route("/api/datasets", get(get_datasets))
+        .route("/api/workers/:worker_id", get(get_worker))
+        .route("/api/workers/:worker_id/drain", post(drain_worker))
+        .route("/api/workers/:worker_id/remove", post(remove_worker))
+        .route(
+            "/api/workers/:worker_id/blacklist",
+            post(blacklist_worker).delete(unblacklist_worker),
+        )
+        .route("/api/datasets", get(get_datasets).post(register_dataset))
+        .route(
+            "/api/datasets/:dataset_id",
+            get(get_dataset)
+                .patch(update_dataset)
+                .delete(delete_dataset),
+        )
+        .route("/api/datasets/:dataset_id/epoch", get(get_dataset_epoch))
+        .route(
+            "/api/datasets/:dataset_id/epoch/advance",
+            post(advance_dataset_epoch),
+        )
         .route("/api/checkpoints", get(get_checkpoints))
+        .route("/api/checkpoints/:checkpoint_id", delete(delete_checkpoint))
+        .route("/api/checkpoints/trigger", post(trigger_checkpoint))
+        .route(
+            "/api/checkpoints/transactions/:txn_id",
+            get(get_checkpoint_transaction),
+        )
         .route("/api/barriers", get(get_barriers))
+        .route("/api/barriers/:barrier_id", delete(close_barrier))
         .route("/api/metrics", get(get_metrics))
+        .route("/api/metrics/history", get(get_metrics_history))
+        .route("/metrics", get(get_prometheus_metrics))
         .route("/api/dashboard", get(get_dashboard_state))
         .route("/api/tasks", get(get_tasks))
         .route("/api/tasks", post(create_task))
         .route("/api/tasks/:task_id/stop", post(stop_task))
+        .route("/api/tasks/:task_id/pause", post(pause_task))
+        .route("/api/tasks/:task_id/resume", post(resume_task))
         .route("/api/tasks/:task_id/logs", get(get_task_logs))
+        .route("/api/schedules", get(get_schedules).post(create_schedule))
+        .route(
+            "/api/schedules/:schedule_id",
+            get(get_schedule)
+                .patch(update_schedule)
+                .delete(delete_schedule),
+        )
+        .route("/api/schedules/:schedule_id/run", post(run_schedule))
         .route("/api/logs", get(get_logs))
-        .layer(cors)
-        .with_state(service)
+        .route("/api/events", get(get_events))
+        .route("/api/stream", get(stream_events))
+        .merge(SwaggerUi::new("/api/docs").url("/api/openapi.json", ApiDoc::openapi()))
+}
+
+/// Reject requests from clients over their rate limit with 429
+///
+/// Clients are identified by their IP address.
+pub async fn rate_limit(
+    State(limiter): State<Arc<HttpRateLimiter>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let client_id = rate_limit_client(&request);
+    match limiter.check(request.uri().path(), &client_id) {
+        Ok(()) => next.run(request).await,
+        Err(retry_after) => {
+            let retry_secs = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                Json(ErrorResponse {
+                    error: format!("rate limited, retry in {}s", retry_secs),
+                    field: None,
+                }),
+            )
+                .into_response();
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(retry_secs));
+            response
+        }
+    }
+}
+
+/// Add `ETag` and `Cache-Control` to JSON responses to `GET`
+///
+/// The tag is a hash of the uncompressed body. A request whose `If-None-Match`
+/// matches it gets `304 Not Modified` without a body.
+pub async fn conditional_get(request: Request, next: Next) -> Response {
+    if request.method() != http::Method::GET {
+        return next.run(request).await;
+    }
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+    let response = next.run(request).await;
+
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|v| v.as_bytes().starts_with(b"application/json"));
+    if response.status() != StatusCode::OK || !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            return (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ErrorResponse {
+                    error: format!("failed to read response body: {}", e),
+                    field: None,
+                }),
+            )
+                .into_response()
+        }
+    };
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    // Weak, since compression changes the bytes sent but not their meaning
+    let etag = format!("W/\"{:016x}\"", hasher.finish());
+    parts
+        .headers
+        .insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
+    parts.headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(API_CACHE_CONTROL),
+    );
+
+    if if_none_match.is_some_and(|v| etag_matches(&v, &etag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_TYPE);
+        parts.headers.remove(header::CONTENT_LENGTH);
+        return Response::from_parts(parts, axum::body::Body::empty());
+    }
+    Response::from_parts(parts, axum::body::Body::from(bytes))
+}
+
+/// Weak comparison of an `If-None-Match` header with an entity tag
+fn etag_matches(if_none_match: &HeaderValue, etag: &str) -> bool {
+    let Ok(value) = if_none_match.to_str() else {
+        return false;
+    };
+    let opaque = |tag: &str| tag.trim().trim_start_matches("W/").to_string();
+    value
+        .split(',')
+        .any(|tag| tag.trim() == "*" || opaque(tag) == opaque(etag))
+}
+
+/// Peer IP address of the request
+///
+/// Bearer tokens are not authenticated by the API, so keying on them would let
+/// a client get a fresh bucket per request by sending a new token each time.
+fn rate_limit_client(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map_or_else(|| "unknown".to_string(), |info| info.0.ip().to_string())
+}
+
+/// Serve the HTTP API until `shutdown` completes
+///
+/// Uses TLS when a certificate is configured, plain HTTP otherwise.
+pub async fn serve(
+    addr: SocketAddr,
+    router: Router,
+    tls: Option<&TlsConfig>,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) -> std::io::Result<()> {
+    let Some(tls) = tls else {
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        info!(addr = %addr, "HTTP API listening");
+        return axum::serve(
+            listener,
+            router.into_make_service_with_connect_info::<SocketAddr>(),
+        )
+        .with_graceful_shutdown(shutdown)
+        .await;
+    };
+
+    let config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+        .await
+        .map_err(|e| {
+            std::io::Error::new(
+                e.kind(),
+                format!(
+                    "failed to load TLS certificate {} and key {}: {}",
+                    tls.cert_path, tls.key_path, e
+                ),
+            )
+        })?;
+
+    let handle = axum_server::Handle::new();
+    let shutdown_handle = handle.clone();
+    tokio::spawn(async move {
+        shutdown.await;
+        shutdown_handle.graceful_shutdown(Some(HTTP_SHUTDOWN_GRACE));
+    });
+
+    info!(addr = %addr, "HTTPS API listening");
+    axum_server::bind_rustls(addr, config)
+        .handle(handle)
+        .serve(router.into_make_service_with_connect_info::<SocketAddr>())
+        .await
 }
 
 /// Health check endpoint
+#[utoipa::path(
+    get,
+    path = "/api/health",
+    tag = "system",
+    responses(
+        (status = 200, description = "Coordinator is up"),
+    )
+)]
 async fn health_check() -> impl IntoResponse {
     (StatusCode::OK, Json(serde_json::json!({"status": "ok"})))
 }
 
 /// Get coordinator status
+#[utoipa::path(
+    get,
+    path = "/api/status",
+    tag = "system",
+    responses(
+        (status = 200, description = "Coordinator status", body = StatusResponse),
+    )
+)]
 async fn get_status(State(service): State<AppState>) -> impl IntoResponse {
     let status = StatusResponse {
         connected: true,
@@ -209,62 +954,229 @@ async fn get_status(State(service): State<AppState>) -> impl IntoResponse {
 }
 
 /// Get all workers
+#[utoipa::path(
+    get,
+    path = "/api/workers",
+    tag = "workers",
+    responses(
+        (status = 200, description = "Registered workers", body = [WorkerResponse]),
+    )
+)]
 async fn get_workers(State(service): State<AppState>) -> impl IntoResponse {
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_state = get_demo_dashboard_state(service.uptime_secs());
-        return Json(demo_state.workers);
-    }
     let workers = service.get_workers_for_api();
     Json(workers)
 }
 
+/// Get a worker with its recent resources, shards and state changes
+#[utoipa::path(
+    get,
+    path = "/api/workers/{worker_id}",
+    tag = "workers",
+    params(
+        ("worker_id" = String, Path, description = "Worker ID"),
+    ),
+    responses(
+        (status = 200, description = "Worker details", body = WorkerDetailResponse),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+        (status = 404, description = "Worker not registered", body = ErrorResponse),
+    )
+)]
+async fn get_worker(
+    State(service): State<AppState>,
+    Path(worker_id): Path<String>,
+) -> Result<Json<WorkerDetailResponse>, ApiError> {
+    let worker_id = parse_id("worker_id", &worker_id).map_err(api_error)?;
+    service
+        .get_worker_detail_for_api(&worker_id)
+        .map(Json)
+        .map_err(api_error)
+}
+
 /// Get all datasets
+#[utoipa::path(
+    get,
+    path = "/api/datasets",
+    tag = "datasets",
+    responses(
+        (status = 200, description = "Registered datasets", body = [DatasetResponse]),
+    )
+)]
 async fn get_datasets(State(service): State<AppState>) -> impl IntoResponse {
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_state = get_demo_dashboard_state(service.uptime_secs());
-        return Json(demo_state.datasets);
-    }
     let datasets = service.get_datasets_for_api();
     Json(datasets)
 }
 
 /// Get recent checkpoints
+#[utoipa::path(
+    get,
+    path = "/api/checkpoints",
+    tag = "checkpoints",
+    responses(
+        (status = 200, description = "Recent checkpoints", body = [CheckpointResponse]),
+    )
+)]
 async fn get_checkpoints(State(service): State<AppState>) -> impl IntoResponse {
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_state = get_demo_dashboard_state(service.uptime_secs());
-        return Json(demo_state.checkpoints);
-    }
     let checkpoints = service.get_checkpoints_for_api();
     Json(checkpoints)
 }
 
 /// Get barrier status
+#[utoipa::path(
+    get,
+    path = "/api/barriers",
+    tag = "cluster",
+    responses(
+        (status = 200, description = "Open barriers", body = [BarrierResponse]),
+    )
+)]
 async fn get_barriers(State(service): State<AppState>) -> impl IntoResponse {
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_state = get_demo_dashboard_state(service.uptime_secs());
-        return Json(demo_state.barriers);
-    }
     let barriers = service.get_barriers_for_api();
     Json(barriers)
 }
 
+/// Abort a stuck barrier, or release its waiters with `?release=true`
+///
+/// Accepts a barrier name or an `id` from `/api/barriers` (`name@generation`).
+#[utoipa::path(
+    delete,
+    path = "/api/barriers/{barrier_id}",
+    tag = "cluster",
+    params(
+        ("barrier_id" = String, Path, description = "Barrier name or ID"),
+        CloseBarrierParams,
+    ),
+    responses(
+        (status = 200, description = "Barriers that were closed", body = [BarrierResponse]),
+        (status = 404, description = "No matching open barrier", body = ErrorResponse),
+    )
+)]
+async fn close_barrier(
+    State(service): State<AppState>,
+    Path(barrier_id): Path<String>,
+    Query(params): Query<CloseBarrierParams>,
+) -> Result<Json<Vec<BarrierResponse>>, ApiError> {
+    let (name, generation) = match (params.generation, barrier_id.rsplit_once('@')) {
+        (None, Some((name, generation))) => match generation.parse() {
+            Ok(generation) => (name, Some(generation)),
+            Err(_) => (barrier_id.as_str(), None),
+        },
+        (generation, _) => (barrier_id.as_str(), generation),
+    };
+    let reason = params
+        .reason
+        .as_deref()
+        .unwrap_or("closed from the HTTP API");
+    service
+        .close_barrier_for_api(name, generation, !params.release, reason)
+        .map(Json)
+        .map_err(api_error)
+}
+
 /// Get system metrics
+#[utoipa::path(
+    get,
+    path = "/api/metrics",
+    tag = "cluster",
+    responses(
+        (status = 200, description = "System metrics", body = MetricsResponse),
+    )
+)]
 async fn get_metrics(State(service): State<AppState>) -> impl IntoResponse {
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_state = get_demo_dashboard_state(service.uptime_secs());
-        return Json(demo_state.metrics);
-    }
     let metrics = service.get_metrics_for_api();
     Json(metrics)
 }
 
+/// Get the effective runtime configuration, with secrets redacted
+#[utoipa::path(
+    get,
+    path = "/api/config",
+    tag = "system",
+    responses(
+        (status = 200, description = "Runtime configuration", body = Object),
+    )
+)]
+async fn get_config(State(service): State<AppState>) -> impl IntoResponse {
+    Json(service.config().redacted())
+}
+
+/// Change runtime settings without restarting the coordinator
+#[utoipa::path(
+    put,
+    path = "/api/config",
+    tag = "system",
+    request_body = ConfigUpdateRequest,
+    responses(
+        (status = 200, description = "Updated runtime configuration", body = Object),
+        (status = 400, description = "Invalid setting", body = ErrorResponse),
+    )
+)]
+async fn update_config(
+    State(service): State<AppState>,
+    Json(request): Json<ConfigUpdateRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let config = service.update_config(&request).map_err(api_error)?;
+    Ok(Json(config.redacted()))
+}
+
+/// Get sampled metrics over a time range, for charts
+#[utoipa::path(
+    get,
+    path = "/api/metrics/history",
+    tag = "cluster",
+    params(MetricsHistoryParams),
+    responses(
+        (status = 200, description = "Metrics samples", body = MetricsHistoryResponse),
+        (status = 400, description = "Invalid range", body = ErrorResponse),
+    )
+)]
+async fn get_metrics_history(
+    State(service): State<AppState>,
+    Query(params): Query<MetricsHistoryParams>,
+) -> Result<Json<MetricsHistoryResponse>, ApiError> {
+    let range = match params.range.as_deref() {
+        Some(range) => parse_range(range).map_err(|error| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error,
+                    field: Some("range".to_string()),
+                }),
+            )
+        })?,
+        None => DEFAULT_METRICS_HISTORY_RANGE,
+    };
+    Ok(Json(MetricsHistoryResponse {
+        range_seconds: range.as_secs(),
+        samples: service.metrics_history(range),
+    }))
+}
+
+/// Get coordinator metrics in the Prometheus text format
+#[utoipa::path(
+    get,
+    path = "/metrics",
+    tag = "system",
+    responses(
+        (status = 200, description = "Prometheus metrics", body = String, content_type = "text/plain"),
+    )
+)]
+async fn get_prometheus_metrics(State(service): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, prometheus::CONTENT_TYPE)],
+        service.render_prometheus(),
+    )
+}
+
 /// Get full dashboard state in one request
+#[utoipa::path(
+    get,
+    path = "/api/dashboard",
+    tag = "cluster",
+    responses(
+        (status = 200, description = "Full dashboard state", body = DashboardState),
+    )
+)]
 async fn get_dashboard_state(State(service): State<AppState>) -> impl IntoResponse {
-    // Check if we should use demo data
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        return Json(get_demo_dashboard_state(service.uptime_secs()));
-    }
-
     let uptime = service.uptime_secs();
     let state = DashboardState {
         coordinator: StatusResponse {
@@ -278,579 +1190,2218 @@ async fn get_dashboard_state(State(service): State<AppState>) -> impl IntoRespon
         checkpoints: service.get_checkpoints_for_api(),
         barriers: service.get_barriers_for_api(),
         metrics: service.get_metrics_for_api(),
-        tasks: get_demo_tasks(uptime),
-        logs: get_demo_logs(uptime),
+        tasks: service.tasks().list().iter().map(Into::into).collect(),
+        logs: collect_logs(&service, LogFilter::default()),
     };
     Json(state)
 }
 
-/// Get all tasks
-async fn get_tasks(State(service): State<AppState>) -> impl IntoResponse {
-    let mut all_tasks = Vec::new();
+/// Take a worker out of rotation, moving its shards to other workers
+#[utoipa::path(
+    post,
+    path = "/api/workers/{worker_id}/drain",
+    tag = "workers",
+    params(
+        ("worker_id" = String, Path, description = "Worker ID"),
+    ),
+    responses(
+        (status = 204, description = "Worker is draining"),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+        (status = 404, description = "Worker not registered", body = ErrorResponse),
+    )
+)]
+async fn drain_worker(
+    State(service): State<AppState>,
+    Path(worker_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let worker_id = parse_id("worker_id", &worker_id).map_err(api_error)?;
+    service.drain_worker(&worker_id).map_err(api_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // Add user-created tasks
-    for entry in get_task_store().iter() {
-        all_tasks.push(entry.value().clone());
-    }
+/// Remove a worker from the cluster
+#[utoipa::path(
+    post,
+    path = "/api/workers/{worker_id}/remove",
+    tag = "workers",
+    params(
+        ("worker_id" = String, Path, description = "Worker ID"),
+    ),
+    responses(
+        (status = 204, description = "Worker removed"),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+        (status = 404, description = "Worker not registered", body = ErrorResponse),
+    )
+)]
+async fn remove_worker(
+    State(service): State<AppState>,
+    Path(worker_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let worker_id = parse_id("worker_id", &worker_id).map_err(api_error)?;
+    service.remove_worker(&worker_id).map_err(api_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    // In demo mode, also add demo tasks
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let demo_tasks = get_demo_tasks(service.uptime_secs());
-        for demo_task in demo_tasks {
-            // Skip if already exists (user-created task with same ID)
-            if !all_tasks.iter().any(|t| t.id == demo_task.id) {
-                all_tasks.push(demo_task);
-            }
-        }
+/// Remove a worker and refuse its future registrations
+#[utoipa::path(
+    post,
+    path = "/api/workers/{worker_id}/blacklist",
+    tag = "workers",
+    params(
+        ("worker_id" = String, Path, description = "Worker ID"),
+    ),
+    request_body = Option<BlacklistWorkerRequest>,
+    responses(
+        (status = 204, description = "Worker blacklisted"),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+    )
+)]
+async fn blacklist_worker(
+    State(service): State<AppState>,
+    Path(worker_id): Path<String>,
+    request: Option<Json<BlacklistWorkerRequest>>,
+) -> Result<StatusCode, ApiError> {
+    let worker_id = parse_id("worker_id", &worker_id).map_err(api_error)?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let reason = if request.reason.is_empty() {
+        "blacklisted by operator"
+    } else {
+        &request.reason
+    };
+    service.blacklist_worker(&worker_id, reason);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Allow a blacklisted worker to register again
+#[utoipa::path(
+    delete,
+    path = "/api/workers/{worker_id}/blacklist",
+    tag = "workers",
+    params(
+        ("worker_id" = String, Path, description = "Worker ID"),
+    ),
+    responses(
+        (status = 204, description = "Worker may register again"),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+        (status = 404, description = "Worker not blacklisted", body = ErrorResponse),
+    )
+)]
+async fn unblacklist_worker(
+    State(service): State<AppState>,
+    Path(worker_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let worker_id = parse_id("worker_id", &worker_id).map_err(api_error)?;
+    if !service.unblacklist_worker(&worker_id) {
+        return Err(api_error(tonic::Status::not_found(format!(
+            "Worker {} is not blacklisted",
+            worker_id
+        ))));
     }
+    Ok(StatusCode::NO_CONTENT)
+}
 
-    Json(all_tasks)
+/// Get a dataset with its shards, files and current epoch
+#[utoipa::path(
+    get,
+    path = "/api/datasets/{dataset_id}",
+    tag = "datasets",
+    params(
+        ("dataset_id" = String, Path, description = "Dataset ID"),
+        DatasetDetailParams,
+    ),
+    responses(
+        (status = 200, description = "Dataset details", body = DatasetDetailResponse),
+        (status = 400, description = "Invalid dataset ID or preview size", body = ErrorResponse),
+        (status = 403, description = "Dataset path outside the dataset roots, or previews disabled", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+    )
+)]
+async fn get_dataset(
+    State(service): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Query(params): Query<DatasetDetailParams>,
+) -> Result<Json<DatasetDetailResponse>, ApiError> {
+    let dataset_id = parse_id("dataset_id", &dataset_id).map_err(api_error)?;
+    service
+        .get_dataset_detail_for_api(&dataset_id, params.preview)
+        .await
+        .map(Json)
+        .map_err(api_error)
+}
+
+/// Register a dataset
+///
+/// Goes through the same validation as the gRPC `RegisterDataset` call.
+#[utoipa::path(
+    post,
+    path = "/api/datasets",
+    tag = "datasets",
+    request_body = RegisterDatasetRequest,
+    responses(
+        (status = 201, description = "Dataset registered", body = RegisterDatasetResponse),
+        (status = 400, description = "Invalid dataset parameters", body = ErrorResponse),
+        (status = 409, description = "Dataset registered with different parameters", body = ErrorResponse),
+        (status = 503, description = "Coordinator is shutting down", body = ErrorResponse),
+    )
+)]
+async fn register_dataset(
+    State(service): State<AppState>,
+    Json(request): Json<RegisterDatasetRequest>,
+) -> Result<(StatusCode, Json<RegisterDatasetResponse>), ApiError> {
+    let info = DatasetInfo {
+        dataset_id: request.dataset_id,
+        path: request.path,
+        format: request.format,
+        total_samples: request.total_samples,
+        shard_size: request.shard_size,
+        shuffle: request.shuffle,
+        seed: request.seed,
+        metadata: request.metadata,
+    };
+    let ack = service
+        .register_dataset(tonic::Request::new(info))
+        .await
+        .map_err(api_error)?
+        .into_inner();
+
+    Ok((
+        StatusCode::CREATED,
+        Json(RegisterDatasetResponse {
+            dataset_id: ack.dataset_id,
+            total_shards: ack.total_shards,
+            message: ack.message,
+        }),
+    ))
+}
+
+/// Change a dataset's shuffle settings
+#[utoipa::path(
+    patch,
+    path = "/api/datasets/{dataset_id}",
+    tag = "datasets",
+    params(
+        ("dataset_id" = String, Path, description = "Dataset ID"),
+    ),
+    request_body = UpdateDatasetRequest,
+    responses(
+        (status = 200, description = "Updated dataset", body = DatasetResponse),
+        (status = 400, description = "Invalid dataset ID or seed", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+    )
+)]
+async fn update_dataset(
+    State(service): State<AppState>,
+    Path(dataset_id): Path<String>,
+    Json(request): Json<UpdateDatasetRequest>,
+) -> Result<Json<DatasetResponse>, ApiError> {
+    let dataset_id = parse_id("dataset_id", &dataset_id).map_err(api_error)?;
+    service
+        .update_dataset_shuffle(&dataset_id, request.shuffle, request.seed)
+        .map_err(api_error)?;
+
+    service
+        .get_datasets_for_api()
+        .into_iter()
+        .find(|d| d.id == dataset_id)
+        .map(Json)
+        .ok_or_else(|| {
+            api_error(tonic::Status::not_found(format!(
+                "Dataset {} not registered",
+                dataset_id
+            )))
+        })
+}
+
+/// Deregister a dataset
+#[utoipa::path(
+    delete,
+    path = "/api/datasets/{dataset_id}",
+    tag = "datasets",
+    params(
+        ("dataset_id" = String, Path, description = "Dataset ID"),
+    ),
+    responses(
+        (status = 204, description = "Dataset deregistered"),
+        (status = 400, description = "Invalid dataset ID", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+        (status = 409, description = "Dataset is used by an active task", body = ErrorResponse),
+    )
+)]
+async fn delete_dataset(
+    State(service): State<AppState>,
+    Path(dataset_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    let dataset_id = parse_id("dataset_id", &dataset_id).map_err(api_error)?;
+    service.deregister_dataset(&dataset_id).map_err(api_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the current epoch and its progress for a dataset
+#[utoipa::path(
+    get,
+    path = "/api/datasets/{dataset_id}/epoch",
+    tag = "datasets",
+    params(
+        ("dataset_id" = String, Path, description = "Dataset ID"),
+    ),
+    responses(
+        (status = 200, description = "Current epoch", body = EpochResponse),
+        (status = 400, description = "Invalid dataset ID", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+    )
+)]
+async fn get_dataset_epoch(
+    State(service): State<AppState>,
+    Path(dataset_id): Path<String>,
+) -> Result<Json<EpochResponse>, ApiError> {
+    let dataset_id = parse_id("dataset_id", &dataset_id).map_err(api_error)?;
+    service
+        .get_epoch_for_api(&dataset_id)
+        .map(Json)
+        .map_err(api_error)
+}
+
+/// Advance a dataset to its next epoch without waiting for worker reports
+#[utoipa::path(
+    post,
+    path = "/api/datasets/{dataset_id}/epoch/advance",
+    tag = "datasets",
+    params(
+        ("dataset_id" = String, Path, description = "Dataset ID"),
+    ),
+    request_body(content = Option<AdvanceEpochRequest>),
+    responses(
+        (status = 200, description = "The new epoch", body = EpochResponse),
+        (status = 400, description = "Invalid dataset ID", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+        (status = 409, description = "Current epoch is not the expected one", body = ErrorResponse),
+    )
+)]
+async fn advance_dataset_epoch(
+    State(service): State<AppState>,
+    Path(dataset_id): Path<String>,
+    request: Option<Json<AdvanceEpochRequest>>,
+) -> Result<Json<EpochResponse>, ApiError> {
+    let dataset_id = parse_id("dataset_id", &dataset_id).map_err(api_error)?;
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    service
+        .force_advance_epoch(&dataset_id, request.expected_epoch)
+        .map_err(api_error)?;
+    service
+        .get_epoch_for_api(&dataset_id)
+        .map(Json)
+        .map_err(api_error)
+}
+
+/// Remove a checkpoint, optionally deleting its data with `?delete_data=true`
+#[utoipa::path(
+    delete,
+    path = "/api/checkpoints/{checkpoint_id}",
+    tag = "checkpoints",
+    params(
+        ("checkpoint_id" = String, Path, description = "Checkpoint ID"),
+        DeleteCheckpointParams,
+    ),
+    responses(
+        (status = 204, description = "Checkpoint removed"),
+        (status = 400, description = "Invalid checkpoint ID", body = ErrorResponse),
+        (status = 404, description = "Checkpoint not found", body = ErrorResponse),
+    )
+)]
+async fn delete_checkpoint(
+    State(service): State<AppState>,
+    Path(checkpoint_id): Path<String>,
+    Query(params): Query<DeleteCheckpointParams>,
+) -> Result<StatusCode, ApiError> {
+    let checkpoint_id = parse_id("checkpoint_id", &checkpoint_id).map_err(api_error)?;
+    service
+        .delete_checkpoint(&checkpoint_id, params.delete_data)
+        .await
+        .map_err(api_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Ask workers to checkpoint now
+///
+/// Responds with the checkpoint transaction; poll
+/// `/api/checkpoints/transactions/:txn_id` for its outcome.
+#[utoipa::path(
+    post,
+    path = "/api/checkpoints/trigger",
+    tag = "checkpoints",
+    request_body = Option<TriggerCheckpointRequest>,
+    responses(
+        (status = 202, description = "Checkpoint requested", body = CheckpointTransaction),
+        (status = 400, description = "Invalid worker ID", body = ErrorResponse),
+        (status = 404, description = "Worker not registered", body = ErrorResponse),
+        (status = 409, description = "No workers can checkpoint", body = ErrorResponse),
+        (status = 503, description = "Coordinator is shutting down", body = ErrorResponse),
+    )
+)]
+async fn trigger_checkpoint(
+    State(service): State<AppState>,
+    request: Option<Json<TriggerCheckpointRequest>>,
+) -> Result<(StatusCode, Json<CheckpointTransaction>), ApiError> {
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let worker_ids = request
+        .worker_ids
+        .iter()
+        .map(|id| parse_id("worker_ids", id))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(api_error)?;
+    let txn = service.trigger_checkpoint(&worker_ids).map_err(api_error)?;
+    Ok((StatusCode::ACCEPTED, Json(txn)))
+}
+
+/// Get the status of a checkpoint transaction
+#[utoipa::path(
+    get,
+    path = "/api/checkpoints/transactions/{txn_id}",
+    tag = "checkpoints",
+    params(
+        ("txn_id" = String, Path, description = "Checkpoint transaction ID"),
+    ),
+    responses(
+        (status = 200, description = "Checkpoint transaction", body = CheckpointTransaction),
+        (status = 404, description = "Transaction not found", body = ErrorResponse),
+    )
+)]
+async fn get_checkpoint_transaction(
+    State(service): State<AppState>,
+    Path(txn_id): Path<String>,
+) -> Result<Json<CheckpointTransaction>, ApiError> {
+    service
+        .checkpoint_transaction(&txn_id)
+        .map(Json)
+        .ok_or_else(|| {
+            api_error(tonic::Status::not_found(format!(
+                "Checkpoint transaction {} not found",
+                txn_id
+            )))
+        })
+}
+
+/// Get all tasks
+#[utoipa::path(
+    get,
+    path = "/api/tasks",
+    tag = "tasks",
+    responses(
+        (status = 200, description = "Tasks, newest first", body = [TaskResponse]),
+    )
+)]
+async fn get_tasks(State(service): State<AppState>) -> impl IntoResponse {
+    let tasks: Vec<TaskResponse> = service.tasks().list().iter().map(Into::into).collect();
+    Json(tasks)
 }
 
 /// Create a new task
+///
+/// The number of epochs is read from `config.epochs` (default 1).
+#[utoipa::path(
+    post,
+    path = "/api/tasks",
+    tag = "tasks",
+    request_body = CreateTaskRequest,
+    responses(
+        (status = 200, description = "Task created", body = CreateTaskResponse),
+        (status = 400, description = "Invalid task parameters", body = ErrorResponse),
+        (status = 404, description = "Dataset not registered", body = ErrorResponse),
+        (status = 409, description = "Not enough free workers", body = ErrorResponse),
+    )
+)]
 async fn create_task(
-    State(_service): State<AppState>,
+    State(service): State<AppState>,
     Json(request): Json<CreateTaskRequest>,
-) -> impl IntoResponse {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64;
-
-    let task_id = format!("task_{}", &uuid::Uuid::new_v4().to_string()[..8]);
-
-    tracing::info!(
-        task_id = %task_id,
-        name = %request.name,
-        task_type = %request.r#type,
-        dataset_id = %request.dataset_id,
-        worker_count = request.worker_count,
-        "Creating new task"
-    );
+) -> Result<Json<CreateTaskResponse>, ApiError> {
+    let epochs = request
+        .config
+        .get("epochs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(1);
+    let dataset_id = parse_id("dataset_id", &request.dataset_id).map_err(api_error)?;
+
+    let task = service
+        .create_task(
+            &request.name,
+            &request.r#type,
+            &dataset_id,
+            request.worker_count as usize,
+            epochs,
+            request.config,
+        )
+        .map_err(api_error)?;
+
+    Ok(Json(CreateTaskResponse { task_id: task.id }))
+}
 
-    // Store the task
-    let task = TaskResponse {
-        id: task_id.clone(),
-        name: request.name.clone(),
-        r#type: request.r#type,
-        status: "running".to_string(),
-        worker_ids: (0..request.worker_count)
-            .map(|i| format!("worker-{}", i))
-            .collect(),
-        dataset_id: request.dataset_id,
-        started_at: now,
-        completed_at: None,
-        progress: 0,
-        logs: vec![
-            format!(
-                "[{}] Task '{}' started",
-                chrono::Utc::now().format("%H:%M:%S"),
-                request.name
-            ),
-            format!(
-                "[{}] Allocated {} workers",
-                chrono::Utc::now().format("%H:%M:%S"),
-                request.worker_count
-            ),
-            format!(
-                "[{}] Loading dataset...",
-                chrono::Utc::now().format("%H:%M:%S")
-            ),
-        ],
-    };
+/// Get all schedules
+#[utoipa::path(
+    get,
+    path = "/api/schedules",
+    tag = "schedules",
+    responses(
+        (status = 200, description = "Schedules, newest first", body = [Schedule]),
+    )
+)]
+async fn get_schedules(State(service): State<AppState>) -> Json<Vec<Schedule>> {
+    Json(service.schedules().list())
+}
+
+/// Create a schedule that starts tasks from a template
+#[utoipa::path(
+    post,
+    path = "/api/schedules",
+    tag = "schedules",
+    request_body = CreateScheduleRequest,
+    responses(
+        (status = 201, description = "Schedule created", body = Schedule),
+        (status = 400, description = "Invalid cron expression or template", body = ErrorResponse),
+    )
+)]
+async fn create_schedule(
+    State(service): State<AppState>,
+    Json(request): Json<CreateScheduleRequest>,
+) -> Result<(StatusCode, Json<Schedule>), ApiError> {
+    let schedule = service.create_schedule(&request).map_err(api_error)?;
+    Ok((StatusCode::CREATED, Json(schedule)))
+}
 
-    get_task_store().insert(task_id.clone(), task);
+/// Get a schedule and its run history
+#[utoipa::path(
+    get,
+    path = "/api/schedules/{schedule_id}",
+    tag = "schedules",
+    params(
+        ("schedule_id" = String, Path, description = "Schedule ID"),
+    ),
+    responses(
+        (status = 200, description = "Schedule", body = Schedule),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+    )
+)]
+async fn get_schedule(
+    State(service): State<AppState>,
+    Path(schedule_id): Path<String>,
+) -> Result<Json<Schedule>, ApiError> {
+    service
+        .schedules()
+        .get(&schedule_id)
+        .map(Json)
+        .ok_or_else(|| {
+            api_error(tonic::Status::not_found(format!(
+                "Schedule {} not found",
+                schedule_id
+            )))
+        })
+}
+
+/// Change a schedule's timing or pause it
+#[utoipa::path(
+    patch,
+    path = "/api/schedules/{schedule_id}",
+    tag = "schedules",
+    params(
+        ("schedule_id" = String, Path, description = "Schedule ID"),
+    ),
+    request_body = UpdateScheduleRequest,
+    responses(
+        (status = 200, description = "Updated schedule", body = Schedule),
+        (status = 400, description = "Invalid cron expression", body = ErrorResponse),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+    )
+)]
+async fn update_schedule(
+    State(service): State<AppState>,
+    Path(schedule_id): Path<String>,
+    Json(request): Json<UpdateScheduleRequest>,
+) -> Result<Json<Schedule>, ApiError> {
+    service
+        .update_schedule(&schedule_id, &request)
+        .map(Json)
+        .map_err(api_error)
+}
 
-    Json(CreateTaskResponse { task_id })
+/// Delete a schedule; tasks it created keep running
+#[utoipa::path(
+    delete,
+    path = "/api/schedules/{schedule_id}",
+    tag = "schedules",
+    params(
+        ("schedule_id" = String, Path, description = "Schedule ID"),
+    ),
+    responses(
+        (status = 204, description = "Schedule deleted"),
+        (status = 404, description = "Schedule not found", body = ErrorResponse),
+    )
+)]
+async fn delete_schedule(
+    State(service): State<AppState>,
+    Path(schedule_id): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    service.delete_schedule(&schedule_id).map_err(api_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Start a schedule's task now
+///
+/// The run is added to the schedule's history even when it fails.
+#[utoipa::path(
+    post,
+    path = "/api/schedules/{schedule_id}/run",
+    tag = "schedules",
+    params(
+        ("schedule_id" = String, Path, description = "Schedule ID"),
+    ),
+    responses(
+        (status = 200, description = "Task started", body = ScheduleRun),
+        (status = 404, description = "Schedule or dataset not found", body = ErrorResponse),
+        (status = 409, description = "Previous run still active or not enough free workers", body = ErrorResponse),
+    )
+)]
+async fn run_schedule(
+    State(service): State<AppState>,
+    Path(schedule_id): Path<String>,
+) -> Result<Json<ScheduleRun>, ApiError> {
+    service
+        .run_schedule_now(&schedule_id)
+        .map(Json)
+        .map_err(api_error)
 }
 
 /// Stop a task
+#[utoipa::path(
+    post,
+    path = "/api/tasks/{task_id}/stop",
+    tag = "tasks",
+    params(
+        ("task_id" = String, Path, description = "Task ID"),
+    ),
+    responses(
+        (status = 200, description = "Task stopped", body = StopTaskResponse),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 409, description = "Task already finished", body = ErrorResponse),
+    )
+)]
 async fn stop_task(
-    State(_service): State<AppState>,
+    State(service): State<AppState>,
     Path(task_id): Path<String>,
-) -> impl IntoResponse {
-    tracing::info!(task_id = %task_id, "Stopping task");
-
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64;
-
-    // Mark as stopped
-    get_stopped_tasks().insert(task_id.clone(), now);
-
-    // Update task in store if it exists
-    if let Some(mut task) = get_task_store().get_mut(&task_id) {
-        task.status = "completed".to_string();
-        task.completed_at = Some(now);
-        task.logs.push(format!(
-            "[{}] Task stopped by user",
-            chrono::Utc::now().format("%H:%M:%S")
-        ));
-        tracing::info!(task_id = %task_id, "Task stopped successfully");
-    } else {
-        tracing::info!(task_id = %task_id, "Task marked as stopped");
-    }
+) -> Result<Json<StopTaskResponse>, ApiError> {
+    service
+        .control_task(&task_id, TaskState::Stopped)
+        .map_err(api_error)?;
+    Ok(Json(StopTaskResponse { success: true }))
+}
+
+/// Pause a running task
+#[utoipa::path(
+    post,
+    path = "/api/tasks/{task_id}/pause",
+    tag = "tasks",
+    params(
+        ("task_id" = String, Path, description = "Task ID"),
+    ),
+    responses(
+        (status = 200, description = "Task paused", body = TaskResponse),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 409, description = "Task is not running", body = ErrorResponse),
+    )
+)]
+async fn pause_task(
+    State(service): State<AppState>,
+    Path(task_id): Path<String>,
+) -> Result<Json<TaskResponse>, ApiError> {
+    let task = service
+        .control_task(&task_id, TaskState::Paused)
+        .map_err(api_error)?;
+    Ok(Json((&task).into()))
+}
 
-    Json(StopTaskResponse { success: true })
+/// Resume a paused task
+#[utoipa::path(
+    post,
+    path = "/api/tasks/{task_id}/resume",
+    tag = "tasks",
+    params(
+        ("task_id" = String, Path, description = "Task ID"),
+    ),
+    responses(
+        (status = 200, description = "Task resumed", body = TaskResponse),
+        (status = 404, description = "Task not found", body = ErrorResponse),
+        (status = 409, description = "Task is not paused", body = ErrorResponse),
+    )
+)]
+async fn resume_task(
+    State(service): State<AppState>,
+    Path(task_id): Path<String>,
+) -> Result<Json<TaskResponse>, ApiError> {
+    let task = service
+        .control_task(&task_id, TaskState::Running)
+        .map_err(api_error)?;
+    Ok(Json((&task).into()))
 }
 
 /// Get logs for a specific task
+#[utoipa::path(
+    get,
+    path = "/api/tasks/{task_id}/logs",
+    tag = "tasks",
+    params(
+        ("task_id" = String, Path, description = "Task ID"),
+    ),
+    responses(
+        (status = 200, description = "Task log lines, oldest first", body = [LogResponse]),
+    )
+)]
 async fn get_task_logs(
     State(service): State<AppState>,
     Path(task_id): Path<String>,
 ) -> impl IntoResponse {
-    // Check user-created tasks first
-    if let Some(task) = get_task_store().get(&task_id) {
-        let logs = task
+    if let Some(task) = service.tasks().get(&task_id) {
+        let mut logs: Vec<LogResponse> = task
             .logs
             .iter()
             .enumerate()
-            .map(|(i, log)| {
-                use std::time::{SystemTime, UNIX_EPOCH};
-                let now = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_millis() as i64;
-                LogResponse {
-                    id: format!("log_{}_{}", task_id, i),
-                    timestamp: now - ((task.logs.len() - i) * 5000) as i64,
-                    level: "info".to_string(),
-                    message: log.clone(),
-                    source: "task_manager".to_string(),
-                    task_id: Some(task_id.clone()),
-                    worker_id: None,
-                }
+            .map(|(i, line)| LogResponse {
+                id: format!("log_{}_{}", task_id, i),
+                timestamp: line.timestamp_ms,
+                level: "info".to_string(),
+                message: line.message.clone(),
+                source: "task_manager".to_string(),
+                task_id: Some(task_id.clone()),
+                worker_id: None,
             })
             .collect();
-        return Json(logs);
-    }
 
-    // Fall back to demo logs
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let logs = get_demo_logs(service.uptime_secs())
-            .into_iter()
-            .filter(|log| log.task_id.as_ref() == Some(&task_id))
-            .collect::<Vec<_>>();
+        // Lines the coordinator and workers logged for the task
+        let filter = LogFilter {
+            task_id: Some(task_id.clone()),
+            ..Default::default()
+        };
+        logs.extend(service.logs().query(&filter).into_iter().map(Into::into));
+        logs.sort_by_key(|log| log.timestamp);
         return Json(logs);
     }
     Json(Vec::<LogResponse>::new())
 }
 
 /// Get system logs
+///
+/// Supports `level` (minimum severity), `source` (prefix), `worker_id`,
+/// `task_id`, `since`/`until` (ms since epoch) and `limit` query parameters.
+#[utoipa::path(
+    get,
+    path = "/api/logs",
+    tag = "logs",
+    params(
+        LogFilter,
+    ),
+    responses(
+        (status = 200, description = "Log lines, newest first", body = [LogResponse]),
+    )
+)]
 async fn get_logs(
     State(service): State<AppState>,
-    Query(params): Query<std::collections::HashMap<String, String>>,
+    Query(filter): Query<LogFilter>,
 ) -> impl IntoResponse {
+    Json(collect_logs(&service, filter))
+}
+
+/// Most recent log lines matching a filter, newest first
+fn collect_logs(service: &CoordinatorService, mut filter: LogFilter) -> Vec<LogResponse> {
+    filter.limit.get_or_insert(DEFAULT_LOGS_LIMIT);
+    service
+        .logs()
+        .query(&filter)
+        .into_iter()
+        .rev()
+        .map(Into::into)
+        .collect()
+}
+
+/// Get coordinator audit events
+///
+/// Query parameters: `after` (sequence number, default 0), `limit` (default 100).
+/// Clients accepting `text/event-stream` get the events as a server-sent event
+/// stream instead, as from `/api/stream`.
+#[utoipa::path(
+    get,
+    path = "/api/events",
+    tag = "events",
+    params(
+        ("after" = Option<u64>, Query, description = "Return events after this sequence number"),
+        ("limit" = Option<usize>, Query, description = "Maximum number of events"),
+        ("types" = Option<String>, Query, description = "Comma-separated event types to stream"),
+    ),
+    responses(
+        (status = 200, description = "Coordinator events, oldest first", body = [Object]),
+    )
+)]
+async fn get_events(
+    State(service): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Response {
+    let wants_stream = headers
+        .get(header::ACCEPT)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"));
+    if wants_stream {
+        return event_stream(service, &headers, &params).into_response();
+    }
+
+    let after = params
+        .get("after")
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(0);
     let limit = params
         .get("limit")
         .and_then(|s| s.parse::<usize>().ok())
         .unwrap_or(100);
 
-    if std::env::var("DEMO_MODE").unwrap_or_default() == "true" {
-        let mut logs = get_demo_logs(service.uptime_secs());
-        logs.truncate(limit);
-        return Json(logs);
+    Json(service.events().log().since(after, limit)).into_response()
+}
+
+/// Stream coordinator events as server-sent events
+///
+/// Each message carries the event's sequence number as its ID and its type
+/// as the event name. Clients resume with the standard `Last-Event-ID` header
+/// or an `after` query parameter; otherwise only new events are sent. The
+/// stream ends when the coordinator shuts down.
+///
+/// Events that change the workers, datasets, checkpoints or tasks are
+/// followed by a `workers`, `datasets`, `checkpoints` or `tasks` message
+/// with the whole collection as in `/api/dashboard`. Every barrier arrival,
+/// release and abort sends a `barriers` message, and each new log line a
+/// `log` message. These carry no ID and are not replayed on resume. `types`
+/// filters them by name too.
+#[utoipa::path(
+    get,
+    path = "/api/stream",
+    tag = "events",
+    params(
+        ("after" = Option<u64>, Query, description = "Resume after this sequence number"),
+        ("types" = Option<String>, Query, description = "Comma-separated event types to stream"),
+        ("Last-Event-ID" = Option<u64>, Header, description = "Resume after this sequence number"),
+    ),
+    responses(
+        (status = 200, description = "Server-sent event stream", content_type = "text/event-stream"),
+    )
+)]
+async fn stream_events(
+    State(service): State<AppState>,
+    headers: HeaderMap,
+    Query(params): Query<std::collections::HashMap<String, String>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    event_stream(service, &headers, &params)
+}
+
+/// Server-sent event stream of the event log, see [`stream_events`]
+fn event_stream(
+    service: AppState,
+    headers: &HeaderMap,
+    params: &std::collections::HashMap<String, String>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let log = service.events().log().clone();
+    let after = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| params.get("after").map(String::as_str))
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or_else(|| log.last_seq());
+    let types: Option<HashSet<String>> = params
+        .get("types")
+        .map(|types| types.split(',').map(|t| t.trim().to_string()).collect());
+
+    // Log lines and barrier changes are only sent from now on
+    let logs = service.logs().clone();
+    let mut log_cursor = logs.last_seq();
+    let mut barriers = service.subscribe_barriers();
+    barriers.mark_unchanged();
+
+    let (tx, rx) = mpsc::channel(STREAM_BUFFER);
+    tokio::spawn(async move {
+        let wanted = |name: &str| types.as_ref().is_none_or(|types| types.contains(name));
+        let stream_logs = wanted("log");
+        let mut appended = log.subscribe();
+        let mut logged = logs.subscribe();
+        let mut barriers_changed = false;
+        let mut cursor = after;
+        loop {
+            let mut changed = BTreeSet::new();
+            for entry in log.since(cursor, STREAM_BATCH_SIZE) {
+                cursor = entry.seq;
+                let Ok(payload) = serde_json::to_value(&entry) else {
+                    continue;
+                };
+                let event_type = payload["type"].as_str().unwrap_or("event");
+                changed.extend(changed_collection(event_type));
+                if !wanted(event_type) {
+                    continue;
+                }
+                let event = Event::default()
+                    .id(entry.seq.to_string())
+                    .event(event_type)
+                    .data(payload.to_string());
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+
+            // Snapshots are taken after the batch, so a burst of events
+            // sends each affected collection once
+            if barriers_changed || barriers.has_changed().unwrap_or(false) {
+                barriers.mark_unchanged();
+                barriers_changed = false;
+                changed.insert("barriers");
+            }
+            for collection in changed.into_iter().filter(|c| wanted(c)) {
+                let event = Event::default()
+                    .event(collection)
+                    .data(collection_snapshot(&service, collection).to_string());
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+
+            if stream_logs {
+                for line in logs.since(log_cursor, STREAM_BATCH_SIZE) {
+                    log_cursor = line.seq;
+                    let Ok(payload) = serde_json::to_string(&LogResponse::from(line)) else {
+                        continue;
+                    };
+                    if tx
+                        .send(Event::default().event("log").data(payload))
+                        .await
+                        .is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+            if cursor < log.last_seq() || (stream_logs && log_cursor < logs.last_seq()) {
+                continue;
+            }
+
+            tokio::select! {
+                changed = appended.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                changed = logged.changed(), if stream_logs => {
+                    if changed.is_err() {
+                        return;
+                    }
+                }
+                changed = barriers.changed() => {
+                    if changed.is_err() {
+                        return;
+                    }
+                    barriers_changed = true;
+                }
+                _ = service.shutdown_requested() => return,
+                _ = tx.closed() => return,
+            }
+        }
+    });
+
+    Sse::new(ReceiverStream::new(rx).map(Ok)).keep_alive(KeepAlive::default())
+}
+
+/// Dashboard collection an event type changes, sent as a snapshot after it
+fn changed_collection(event_type: &str) -> Option<&'static str> {
+    match event_type {
+        "shards_rebalanced" | "ranks_compacted" => Some("workers"),
+        "epoch_advanced" => Some("datasets"),
+        "task_state_changed" => Some("tasks"),
+        t if t.starts_with("worker_") => Some("workers"),
+        t if t.starts_with("dataset_") => Some("datasets"),
+        t if t.starts_with("checkpoint_") => Some("checkpoints"),
+        _ => None,
     }
-    Json(Vec::<LogResponse>::new())
 }
 
-/// Generate demo dashboard state with active training simulation
-fn get_demo_dashboard_state(uptime: u64) -> DashboardState {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64;
-
-    // Simulate training progress based on uptime
-    let base_step = uptime / 3; // Progress every 3 seconds
-    let current_epoch = (base_step / 500) + 1; // New epoch every 500 steps
-    let current_step = base_step % 500;
-
-    // Simulate realistic loss decay
-    let _loss = 0.8 * (-0.001 * base_step as f64).exp() + 0.15;
-    let _accuracy = 0.95 * (1.0 - (-0.002 * base_step as f64).exp());
-
-    let workers = vec![
-        WorkerResponse {
-            id: "gpu-worker-01".to_string(),
-            ip: "gpu-node-01".to_string(),
-            port: 50052,
-            status: "active".to_string(),
-            gpu_count: 8,
-            last_heartbeat: now,
-            assigned_shards: 12,
-            current_epoch,
-            current_step,
-            current_task: match base_step % 4 {
-                0 => "forward_pass".to_string(),
-                1 => "backward_pass".to_string(),
-                2 => "gradient_sync".to_string(),
-                _ => "parameter_update".to_string(),
-            },
-        },
-        WorkerResponse {
-            id: "gpu-worker-02".to_string(),
-            ip: "gpu-node-02".to_string(),
-            port: 50052,
-            status: "active".to_string(),
-            gpu_count: 8,
-            last_heartbeat: now,
-            assigned_shards: 12,
-            current_epoch,
-            current_step: current_step.saturating_sub(2),
-            current_task: "backward_pass".to_string(),
-        },
-        WorkerResponse {
-            id: "cpu-worker-01".to_string(),
-            ip: "cpu-node-01".to_string(),
-            port: 50052,
-            status: if uptime % 30 < 5 {
-                "idle".to_string()
-            } else {
-                "active".to_string()
-            },
-            gpu_count: 0,
-            last_heartbeat: now,
-            assigned_shards: 8,
-            current_epoch,
-            current_step: 0,
-            current_task: "data_preprocessing".to_string(),
-        },
-    ];
-
-    let datasets = vec![
-        DatasetResponse {
-            id: "imagenet-train".to_string(),
-            name: "ImageNet Training Set".to_string(),
-            total_samples: 1281167,
-            shard_size: 10000,
-            shard_count: 128,
-            format: "tfrecord".to_string(),
-            shuffle: true,
-            registered_at: now - 3600000, // 1 hour ago
-        },
-        DatasetResponse {
-            id: "custom-vision".to_string(),
-            name: "Custom Vision Model".to_string(),
-            total_samples: 500000,
-            shard_size: 8000,
-            shard_count: 64,
-            format: "parquet".to_string(),
-            shuffle: true,
-            registered_at: now - 1800000, // 30 minutes ago
-        },
-    ];
-
-    // Generate checkpoints based on completed epochs
-    let mut checkpoints = Vec::new();
-    for epoch in 1..current_epoch {
-        checkpoints.push(CheckpointResponse {
-            id: format!("checkpoint_epoch_{}", epoch),
-            step: 500,
-            epoch,
-            size: 650 * 1024 * 1024, // 650MB
-            path: format!("/checkpoints/epoch_{}.pt", epoch),
-            created_at: now - ((current_epoch - epoch) * 1500000) as i64, // Spaced out
-            worker_id: "gpu-worker-01".to_string(),
-            status: "completed".to_string(),
-        });
+/// A collection in the same form as in `/api/dashboard`
+fn collection_snapshot(service: &CoordinatorService, collection: &str) -> serde_json::Value {
+    let snapshot = match collection {
+        "workers" => serde_json::to_value(service.get_workers_for_api()),
+        "datasets" => serde_json::to_value(service.get_datasets_for_api()),
+        "checkpoints" => serde_json::to_value(service.get_checkpoints_for_api()),
+        "barriers" => serde_json::to_value(service.get_barriers_for_api()),
+        "tasks" => serde_json::to_value(
+            service
+                .tasks()
+                .list()
+                .iter()
+                .map(TaskResponse::from)
+                .collect::<Vec<_>>(),
+        ),
+        _ => Ok(serde_json::Value::Null),
+    };
+    snapshot.unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    use crate::events::CoordinatorEvent;
+
+    /// Read a server-sent event stream until an event of type `name` arrives
+    async fn next_event(body: &mut Body, name: &str) -> String {
+        loop {
+            let frame = body.frame().await.unwrap().unwrap().into_data().unwrap();
+            let frame = String::from_utf8_lossy(&frame).into_owned();
+            if frame.contains(&format!("event: {}\n", name)) {
+                return frame;
+            }
+        }
+    }
+
+    async fn test_service() -> (tempfile::TempDir, AppState) {
+        let dir = tempfile::tempdir().unwrap();
+        let config = checkpoint::CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let service =
+            CoordinatorService::with_config(config, 10, std::time::Duration::from_secs(30))
+                .await
+                .unwrap();
+        (dir, Arc::new(service))
     }
 
-    // Add current checkpoint if we're far enough in the epoch
-    if current_step > 400 {
-        checkpoints.push(CheckpointResponse {
-            id: format!("checkpoint_epoch_{}_step_{}", current_epoch, current_step),
-            step: current_step,
-            epoch: current_epoch,
-            size: 650 * 1024 * 1024,
-            path: format!(
-                "/checkpoints/epoch_{}_step_{}.pt",
-                current_epoch, current_step
+    /// A service that lists and previews datasets under its temporary directory
+    async fn dataset_test_service() -> (tempfile::TempDir, AppState) {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = runtime_core::RuntimeConfig::default();
+        config.storage.base_path = dir.path().to_string_lossy().into_owned();
+        config.coordinator.dataset_preview = true;
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .unwrap();
+        (dir, Arc::new(service))
+    }
+
+    #[test]
+    fn test_api_error_status_codes() {
+        let cases = [
+            (
+                tonic::Status::resource_exhausted("full"),
+                StatusCode::TOO_MANY_REQUESTS,
+            ),
+            (tonic::Status::aborted("conflict"), StatusCode::CONFLICT),
+            (
+                tonic::Status::deadline_exceeded("slow"),
+                StatusCode::GATEWAY_TIMEOUT,
             ),
-            created_at: now - 30000, // 30 seconds ago
-            worker_id: "gpu-worker-01".to_string(),
-            status: "completed".to_string(),
+            (
+                tonic::Status::internal("bug"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+        ];
+        for (status, expected) in cases {
+            assert_eq!(api_error(status).0, expected);
+        }
+    }
+
+    async fn send_json(
+        service: &AppState,
+        method: &str,
+        uri: &str,
+        body: serde_json::Value,
+    ) -> (StatusCode, serde_json::Value) {
+        let response = create_router(service.clone(), CorsLayer::new())
+            .oneshot(
+                Request::builder()
+                    .method(method)
+                    .uri(uri)
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&bytes).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_stream_events() {
+        let (_dir, service) = test_service().await;
+        for rank in 0..2 {
+            service.events().publish(CoordinatorEvent::WorkerJoined {
+                worker_id: format!("worker-{}", rank).parse().unwrap(),
+                rank,
+            });
+        }
+
+        let response = create_router(service.clone(), CorsLayer::new())
+            .oneshot(
+                Request::get("/api/stream")
+                    .header("last-event-id", "1")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        // Replay starts after the given ID, then live events follow
+        let mut body = response.into_body();
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        let first = String::from_utf8_lossy(&first);
+        assert!(first.contains("id: 2"));
+        assert!(first.contains("event: worker_joined"));
+        assert!(first.contains("worker-1"));
+
+        service.events().publish(CoordinatorEvent::WorkerLeft {
+            worker_id: "worker-0".parse().unwrap(),
         });
+        next_event(&mut body, "worker_left").await;
+
+        // Shutdown ends the stream
+        service.begin_shutdown("test");
+        while let Some(frame) = body.frame().await {
+            frame.unwrap();
+        }
     }
 
-    // Generate barriers occasionally
-    let barriers = if uptime % 60 < 10 {
-        vec![BarrierResponse {
-            id: format!("epoch_{}_sync", current_epoch),
-            name: format!("Epoch {} Synchronization", current_epoch),
-            arrived: 2,
-            total: 3,
-            status: "waiting".to_string(),
-            created_at: now - 5000,
-        }]
-    } else {
-        vec![]
-    };
+    #[tokio::test]
+    async fn test_stream_pushes_state() {
+        let (_dir, service) = test_service().await;
+        let response = create_router(service.clone(), CorsLayer::new())
+            .oneshot(Request::get("/api/stream").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let mut body = response.into_body();
+
+        // Log lines are pushed as they are recorded
+        service.logs().push(crate::logs::LogRecord {
+            timestamp_ms: None,
+            level: crate::logs::LogLevel::Warn,
+            message: "disk almost full".to_string(),
+            source: "trainer".to_string(),
+            worker_id: Some("worker-1".to_string()),
+            task_id: None,
+        });
+        let log = next_event(&mut body, "log").await;
+        assert!(log.contains("disk almost full"));
+        assert!(log.contains("\"worker_id\":\"worker-1\""));
+
+        // A change to a collection is followed by its new contents
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                protocol_version: crate::protocol::PROTOCOL_VERSION,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        let workers = next_event(&mut body, "workers").await;
+        assert!(workers.contains("\"id\":\"worker-1\""));
+
+        // Every barrier arrival is pushed, not only the release
+        service
+            .wait_barrier(tonic::Request::new(crate::proto::BarrierRequest {
+                worker_id: "worker-1".to_string(),
+                barrier_id: "sync".to_string(),
+                step: 0,
+                generation: 1,
+                no_wait: true,
+            }))
+            .await
+            .unwrap();
+        let barriers = next_event(&mut body, "barriers").await;
+        assert!(barriers.contains("\"arrived\":1"));
+    }
 
-    let metrics = MetricsResponse {
-        checkpoint_throughput: 45 + (uptime % 20),
-        coordinator_rps: 120 + (uptime % 30),
-        active_workers: workers.iter().filter(|w| w.status == "active").count() as u32,
-        total_workers: workers.len() as u32,
-        barrier_latency_p99: 20 + (uptime % 15),
-        shard_assignment_time: 8 + (uptime % 5),
-    };
+    #[tokio::test]
+    async fn test_events_negotiates_stream() {
+        let (_dir, service) = test_service().await;
+        service.events().publish(CoordinatorEvent::WorkerJoined {
+            worker_id: "worker-0".parse().unwrap(),
+            rank: 0,
+        });
+        service.events().publish(CoordinatorEvent::WorkerDead {
+            worker_id: "worker-0".parse().unwrap(),
+        });
 
-    DashboardState {
-        coordinator: StatusResponse {
-            connected: true,
-            address: "localhost:50052".to_string(),
-            uptime,
-            version: "0.1.0".to_string(),
-        },
-        workers,
-        datasets,
-        checkpoints,
-        barriers,
-        metrics,
-        tasks: get_demo_tasks(uptime),
-        logs: get_demo_logs(uptime),
-    }
-}
-
-/// Generate demo tasks
-fn get_demo_tasks(uptime: u64) -> Vec<TaskResponse> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64;
-
-    let mut tasks = vec![];
-
-    // Always show at least one active training task (unless stopped by user)
-    if uptime > 10 {
-        let task_id = "task_vision_training".to_string();
-
-        // Check if this task was stopped by user
-        if let Some(stopped_at) = get_stopped_tasks().get(&task_id) {
-            // Show as completed
-            tasks.push(TaskResponse {
-                id: task_id,
-                name: "Vision Model Training".to_string(),
-                r#type: "image_classification".to_string(),
-                status: "completed".to_string(),
-                worker_ids: vec!["gpu-worker-01".to_string(), "gpu-worker-02".to_string()],
-                dataset_id: "imagenet-train".to_string(),
-                started_at: now - (uptime * 1000) as i64,
-                completed_at: Some(*stopped_at),
-                progress: 100,
-                logs: vec![
-                    format!(
-                        "[{}] Task started with 2 workers",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    ),
-                    format!(
-                        "[{}] Dataset loaded: 1,281,167 samples",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    ),
-                    format!(
-                        "[{}] Task stopped by user",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    ),
-                ],
+        // Plain requests still get the JSON list
+        let (status, body) =
+            send_json(&service, "GET", "/api/events", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body.as_array().unwrap().len(), 2);
+
+        let response = create_router(service.clone(), CorsLayer::new())
+            .oneshot(
+                Request::get("/api/events?types=worker_dead,worker_left")
+                    .header("accept", "text/event-stream")
+                    .header("last-event-id", "0")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["content-type"], "text/event-stream");
+
+        // Only the requested types are sent
+        let mut body = response.into_body();
+        let first = body.frame().await.unwrap().unwrap().into_data().unwrap();
+        let first = String::from_utf8_lossy(&first);
+        assert!(first.contains("id: 2"));
+        assert!(first.contains("event: worker_dead"));
+        assert!(!first.contains("worker_joined"));
+    }
+
+    #[tokio::test]
+    async fn test_register_dataset() {
+        let (_dir, service) = test_service().await;
+        let dataset = serde_json::json!({
+            "dataset_id": "mnist",
+            "path": "/data/mnist",
+            "format": "parquet",
+            "total_samples": 1000,
+            "shard_size": 100,
+        });
+
+        let (status, body) = send_json(&service, "POST", "/api/datasets", dataset.clone()).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body["total_shards"], 10);
+        assert_eq!(service.get_datasets_for_api().len(), 1);
+
+        let mut invalid = dataset.clone();
+        invalid["shard_size"] = 0.into();
+        let (status, body) = send_json(&service, "POST", "/api/datasets", invalid).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "shard_size");
+
+        let mut conflicting = dataset;
+        conflicting["total_samples"] = 2000.into();
+        let (status, _) = send_json(&service, "POST", "/api/datasets", conflicting).await;
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_update_and_delete_dataset() {
+        let (_dir, service) = test_service().await;
+        let dataset = serde_json::json!({
+            "dataset_id": "mnist",
+            "path": "/data/mnist",
+            "format": "parquet",
+            "total_samples": 1000,
+            "shard_size": 100,
+            "shuffle": true,
+        });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+
+        let (status, body) = send_json(
+            &service,
+            "PATCH",
+            "/api/datasets/mnist",
+            serde_json::json!({ "shuffle": false }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["shuffle"], false);
+
+        let (status, body) = send_json(
+            &service,
+            "PATCH",
+            "/api/datasets/mnist",
+            serde_json::json!({ "seed": -1 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "seed");
+
+        let (status, _) = send_json(
+            &service,
+            "DELETE",
+            "/api/datasets/mnist",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(service.get_datasets_for_api().is_empty());
+
+        let (status, _) = send_json(
+            &service,
+            "DELETE",
+            "/api/datasets/mnist",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_close_barrier() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                    worker_id: id.to_string(),
+                    protocol_version: crate::protocol::PROTOCOL_VERSION,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap();
+        }
+        let waiter = service.clone();
+        let wait = tokio::spawn(async move {
+            waiter
+                .wait_barrier(tonic::Request::new(crate::proto::BarrierRequest {
+                    worker_id: "worker-1".to_string(),
+                    barrier_id: "sync".to_string(),
+                    step: 0,
+                    generation: 3,
+                    no_wait: false,
+                }))
+                .await
+                .unwrap()
+                .into_inner()
+        });
+        while service.get_barriers_for_api().is_empty() {
+            tokio::task::yield_now().await;
+        }
+
+        let (status, body) = send_json(
+            &service,
+            "DELETE",
+            "/api/barriers/sync@3",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body[0]["id"], "sync@3");
+        assert_eq!(body[0]["status"], "aborted");
+        assert!(wait.await.unwrap().aborted);
+
+        let (status, _) = send_json(
+            &service,
+            "DELETE",
+            "/api/barriers/sync?release=true",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_detail() {
+        let (dir, service) = dataset_test_service().await;
+        let data = dir.path().join("data");
+        std::fs::create_dir_all(&data).unwrap();
+        std::fs::write(
+            data.join("part-0.jsonl"),
+            "{\"x\": 1}\n{\"x\": 2}\n{\"x\": 3}\n",
+        )
+        .unwrap();
+        std::fs::write(data.join("part-1.jsonl"), "{\"x\": 4}\n").unwrap();
+        std::fs::write(data.join(".part-2.jsonl.tmp"), "").unwrap();
+
+        let dataset = serde_json::json!({
+            "dataset_id": "points",
+            "path": data.to_string_lossy(),
+            "format": "jsonl",
+            "total_samples": 950,
+            "shard_size": 100,
+        });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/points?preview=2",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["shard_count"], 10);
+        assert_eq!(body["epoch"]["epoch"], 0);
+        assert_eq!(body["shards"].as_array().unwrap().len(), 10);
+        assert_eq!(body["shards"][9]["start_index"], 900);
+        assert_eq!(body["shards"][9]["end_index"], 950);
+        assert_eq!(body["file_count"], 2);
+        assert_eq!(body["files_truncated"], false);
+        assert!(body["file_paths"][0]
+            .as_str()
+            .unwrap()
+            .ends_with("data/part-0.jsonl"));
+        assert_eq!(
+            body["preview"]["lines"],
+            serde_json::json!(["{\"x\": 1}", "{\"x\": 2}"])
+        );
+        assert_eq!(body["preview"]["binary"], false);
+        assert_eq!(body["preview"]["truncated"], true);
+
+        // No preview unless asked for
+        let (_, body) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/points",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert!(body["preview"].is_null());
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/points?preview=1000",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "preview");
+
+        let (status, _) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/missing",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_detail_outside_roots() {
+        let (dir, service) = dataset_test_service().await;
+        for (dataset_id, path) in [("passwd", "/etc/passwd"), ("etc", "/etc")] {
+            let dataset = serde_json::json!({
+                "dataset_id": dataset_id,
+                "path": path,
+                "format": "jsonl",
+                "total_samples": 10,
+                "shard_size": 5,
             });
-        } else {
-            // Show as running
-            let progress = ((uptime % 300) * 100 / 300) as u32; // 5 minute cycles
-            tasks.push(TaskResponse {
-                id: task_id,
-                name: "Vision Model Training".to_string(),
-                r#type: "image_classification".to_string(),
-                status: if progress < 95 {
-                    "running".to_string()
-                } else {
-                    "completed".to_string()
-                },
-                worker_ids: vec!["gpu-worker-01".to_string(), "gpu-worker-02".to_string()],
-                dataset_id: "imagenet-train".to_string(),
-                started_at: now - (uptime * 1000) as i64,
-                completed_at: if progress >= 95 {
-                    Some(now - 30000)
-                } else {
-                    None
-                },
-                progress,
-                logs: vec![
-                    format!(
-                        "[{}] Task started with 2 workers",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    ),
-                    format!(
-                        "[{}] Dataset loaded: 1,281,167 samples",
-                        chrono::Utc::now().format("%H:%M:%S")
-                    ),
-                    format!(
-                        "[{}] Training progress: {}%",
-                        chrono::Utc::now().format("%H:%M:%S"),
-                        progress
-                    ),
-                ],
+            send_json(&service, "POST", "/api/datasets", dataset).await;
+
+            let (status, body) = send_json(
+                &service,
+                "GET",
+                &format!("/api/datasets/{}?preview=10", dataset_id),
+                serde_json::Value::Null,
+            )
+            .await;
+            assert_eq!(status, StatusCode::FORBIDDEN, "{}", path);
+            assert!(body.get("preview").is_none());
+        }
+
+        // A link inside a root is refused when it leads outside
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("/etc", dir.path().join("link")).unwrap();
+            let dataset = serde_json::json!({
+                "dataset_id": "link",
+                "path": dir.path().join("link").to_string_lossy(),
+                "format": "jsonl",
+                "total_samples": 10,
+                "shard_size": 5,
             });
+            send_json(&service, "POST", "/api/datasets", dataset).await;
+            let (status, _) = send_json(
+                &service,
+                "GET",
+                "/api/datasets/link",
+                serde_json::Value::Null,
+            )
+            .await;
+            assert_eq!(status, StatusCode::FORBIDDEN);
         }
     }
 
-    // Show completed task occasionally (unless stopped)
-    if uptime > 60 && uptime % 120 < 60 {
-        let task_id = "task_nlp_completed".to_string();
-
-        if !get_stopped_tasks().contains_key(&task_id) {
-            tasks.push(TaskResponse {
-                id: task_id,
-                name: "NLP Model Fine-tuning".to_string(),
-                r#type: "nlp_training".to_string(),
-                status: "completed".to_string(),
-                worker_ids: vec!["cpu-worker-01".to_string()],
-                dataset_id: "custom-vision".to_string(),
-                started_at: now - 3600000,
-                completed_at: Some(now - 1800000),
-                progress: 100,
-                logs: vec![
-                    "[14:30:15] Task started with 1 worker".to_string(),
-                    "[14:45:22] Epoch 1/5 completed - Loss: 0.234".to_string(),
-                    "[15:00:18] Epoch 5/5 completed - Final accuracy: 94.2%".to_string(),
-                    "[15:00:45] Task completed successfully".to_string(),
-                ],
+    #[tokio::test]
+    async fn test_dataset_preview_disabled() {
+        let (dir, service) = test_service().await;
+        let dataset = serde_json::json!({
+            "dataset_id": "points",
+            "path": dir.path().to_string_lossy(),
+            "format": "jsonl",
+            "total_samples": 10,
+            "shard_size": 5,
+        });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+
+        let (status, _) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/points?preview=5",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_epoch() {
+        let (_dir, service) = test_service().await;
+        let dataset = serde_json::json!({
+            "dataset_id": "mnist",
+            "path": "/data/mnist",
+            "format": "parquet",
+            "total_samples": 1000,
+            "shard_size": 100,
+        });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/mnist/epoch",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["epoch"], 0);
+        assert_eq!(body["total_shards"], 10);
+        assert_eq!(body["workers_reported"], serde_json::json!([]));
+
+        // Advancing without a body skips the quorum
+        let (status, body) = send_json(
+            &service,
+            "POST",
+            "/api/datasets/mnist/epoch/advance",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["epoch"], 1);
+
+        // A retried request expecting the old epoch does not advance again
+        let (status, _) = send_json(
+            &service,
+            "POST",
+            "/api/datasets/mnist/epoch/advance",
+            serde_json::json!({ "expected_epoch": 0 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, body) = send_json(
+            &service,
+            "POST",
+            "/api/datasets/mnist/epoch/advance",
+            serde_json::json!({ "expected_epoch": 1 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["epoch"], 2);
+
+        let (status, _) = send_json(
+            &service,
+            "GET",
+            "/api/datasets/missing/epoch",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_trigger_checkpoint() {
+        let (_dir, service) = test_service().await;
+
+        // Without workers there is nothing to checkpoint
+        let (status, body) = send_json(
+            &service,
+            "POST",
+            "/api/checkpoints/trigger",
+            serde_json::json!({}),
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+        assert!(body["error"].is_string());
+
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                protocol_version: crate::PROTOCOL_VERSION,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        let (status, txn) = send_json(
+            &service,
+            "POST",
+            "/api/checkpoints/trigger",
+            serde_json::json!({ "worker_ids": ["worker-1"] }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!(txn["state"], "pending");
+
+        let uri = format!(
+            "/api/checkpoints/transactions/{}",
+            txn["id"].as_str().unwrap()
+        );
+        let (status, polled) = send_json(&service, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(polled["worker_ids"], serde_json::json!(["worker-1"]));
+
+        let (status, _) = send_json(
+            &service,
+            "GET",
+            "/api/checkpoints/transactions/missing",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_schedules() {
+        let (_dir, service) = test_service().await;
+        for id in ["snapshot-2026-03-01", "snapshot-2026-03-02"] {
+            let dataset = serde_json::json!({
+                "dataset_id": id,
+                "path": "/data",
+                "format": "parquet",
+                "total_samples": 1000,
+                "shard_size": 100,
             });
+            send_json(&service, "POST", "/api/datasets", dataset).await;
         }
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                protocol_version: crate::PROTOCOL_VERSION,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let mut request = serde_json::json!({
+            "name": "nightly finetune",
+            "cron": "0 2 * *",
+            "template": {
+                "name": "finetune",
+                "type": "finetune",
+                "dataset_id": "snapshot-*",
+                "worker_count": 1,
+            },
+        });
+        let (status, body) = send_json(&service, "POST", "/api/schedules", request.clone()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "cron");
+
+        request["cron"] = "0 2 * * *".into();
+        let (status, schedule) = send_json(&service, "POST", "/api/schedules", request).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(schedule["enabled"], true);
+        assert!(schedule["next_run_ms"].is_i64());
+        let uri = format!("/api/schedules/{}", schedule["id"].as_str().unwrap());
+
+        // A manual run trains on the newest snapshot
+        let (status, run) = send_json(
+            &service,
+            "POST",
+            &format!("{}/run", uri),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(run["dataset_id"], "snapshot-2026-03-02");
+        assert_eq!(run["manual"], true);
+        let task = service
+            .tasks()
+            .get(run["task_id"].as_str().unwrap())
+            .unwrap();
+        assert_eq!(task.worker_ids, vec!["worker-1".to_string()]);
+
+        // The previous run is still active
+        let (status, _) = send_json(
+            &service,
+            "POST",
+            &format!("{}/run", uri),
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let (status, body) = send_json(&service, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["history"].as_array().unwrap().len(), 2);
+        assert!(body["history"][1]["error"]
+            .as_str()
+            .unwrap()
+            .contains("still pending"));
+
+        let (status, body) = send_json(
+            &service,
+            "PATCH",
+            &uri,
+            serde_json::json!({ "enabled": false }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert!(body["next_run_ms"].is_null());
+
+        let (status, _) = send_json(&service, "DELETE", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (status, _) = send_json(&service, "GET", &uri, serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
     }
 
-    tasks
-}
+    #[tokio::test]
+    async fn test_worker_detail() {
+        use crate::proto::coordinator_server::Coordinator;
+
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                protocol_version: crate::PROTOCOL_VERSION,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        let dataset = serde_json::json!({
+            "dataset_id": "mnist",
+            "path": "/data/mnist",
+            "format": "parquet",
+            "total_samples": 1000,
+            "shard_size": 100,
+        });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+        service
+            .get_data_shard(tonic::Request::new(crate::proto::ShardRequest {
+                worker_id: "worker-1".to_string(),
+                dataset_id: "mnist".to_string(),
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        for cpu_percent in [20.0, 80.0] {
+            service
+                .heartbeat(tonic::Request::new(crate::proto::HeartbeatRequest {
+                    worker_id: "worker-1".to_string(),
+                    status: Some(crate::proto::WorkerStatus {
+                        state: crate::proto::worker_status::State::Training as i32,
+                        ..Default::default()
+                    }),
+                    resources: Some(crate::proto::ResourceUsage {
+                        cpu_percent,
+                        gpu_usage: vec![crate::proto::GpuUsage {
+                            gpu_id: 0,
+                            utilization_percent: 95.0,
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }),
+                    metrics: vec![crate::proto::MetricPoint {
+                        name: "loss".to_string(),
+                        value: cpu_percent / 100.0,
+                        step: cpu_percent as i64,
+                        timestamp_ms: 0,
+                    }],
+                    ..Default::default()
+                }))
+                .await
+                .unwrap();
+        }
+        service
+            .ship_logs(tonic::Request::new(crate::proto::ShipLogsRequest {
+                worker_id: "worker-1".to_string(),
+                lines: vec![crate::proto::LogLine {
+                    level: "error".to_string(),
+                    message: "CUDA out of memory".to_string(),
+                    ..Default::default()
+                }],
+            }))
+            .await
+            .unwrap();
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/workers/worker-1",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["id"], "worker-1");
+        assert_eq!(body["status"], "active");
+        // The only worker owns every shard
+        assert_eq!(body["shards"]["mnist"].as_array().unwrap().len(), 10);
+        assert_eq!(body["assigned_shards"], 10);
+        let history = body["resource_history"].as_array().unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1]["cpu_percent"], 80.0);
+        assert_eq!(history[1]["gpus"][0]["utilization_percent"], 95.0);
+        let transitions = body["transitions"].as_array().unwrap();
+        assert_eq!(transitions.len(), 2);
+        assert!(transitions[0]["from"].is_null());
+        assert_eq!(transitions[1]["to"], "Training");
+        assert_eq!(body["last_error"]["message"], "CUDA out of memory");
+        let loss = body["metrics"]["loss"].as_array().unwrap();
+        assert_eq!(loss.len(), 2);
+        assert_eq!(loss[1]["step"], 80);
+        assert_eq!(loss[1]["value"], 0.8);
+
+        let (status, _) = send_json(
+            &service,
+            "GET",
+            "/api/workers/missing",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
 
-/// Generate demo logs
-fn get_demo_logs(uptime: u64) -> Vec<LogResponse> {
-    use std::time::{SystemTime, UNIX_EPOCH};
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as i64;
+    #[tokio::test]
+    async fn test_worker_management() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                protocol_version: crate::PROTOCOL_VERSION,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+
+        let (status, _) = send_json(
+            &service,
+            "POST",
+            "/api/workers/worker-1/drain",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(service.is_draining(&"worker-1".parse().unwrap()));
+
+        let (status, _) = send_json(
+            &service,
+            "POST",
+            "/api/workers/worker-1/blacklist",
+            serde_json::json!({ "reason": "flaky NIC" }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        assert!(service.get_workers_for_api().is_empty());
+
+        let (status, _) = send_json(
+            &service,
+            "POST",
+            "/api/workers/worker-1/remove",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _) = send_json(
+            &service,
+            "DELETE",
+            "/api/workers/worker-1/blacklist",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+    }
 
-    let mut logs = vec![];
+    #[tokio::test]
+    async fn test_openapi_spec() {
+        let (_dir, service) = test_service().await;
+        let (status, spec) = send_json(
+            &service,
+            "GET",
+            "/api/openapi.json",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let paths = spec["paths"].as_object().unwrap();
+        assert!(paths["/api/workers/{worker_id}/drain"]["post"].is_object());
+        assert!(paths["/api/checkpoints/{checkpoint_id}"]["delete"].is_object());
+        assert!(paths["/api/logs"]["get"]["parameters"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|p| p["name"] == "worker_id"));
 
-    // Generate realistic system logs
-    let log_entries = vec![
-        (
-            "info",
-            "coordinator",
-            "Coordinator service started",
-            None,
-            None,
-        ),
-        (
-            "info",
-            "worker_registry",
-            "Worker gpu-worker-01 registered successfully",
-            None,
-            Some("gpu-worker-01"),
-        ),
-        (
-            "info",
-            "worker_registry",
-            "Worker gpu-worker-02 registered successfully",
-            None,
-            Some("gpu-worker-02"),
-        ),
-        (
-            "info",
-            "task_manager",
-            "Training task started: Vision Model Training",
-            Some("task_vision_training"),
-            None,
-        ),
-        (
-            "info",
-            "data_loader",
-            "Dataset imagenet-train loaded: 1,281,167 samples",
-            Some("task_vision_training"),
-            None,
-        ),
-        (
-            "debug",
-            "shard_manager",
-            "Assigned 12 shards to gpu-worker-01",
-            Some("task_vision_training"),
-            Some("gpu-worker-01"),
-        ),
-        (
-            "debug",
-            "shard_manager",
-            "Assigned 12 shards to gpu-worker-02",
-            Some("task_vision_training"),
-            Some("gpu-worker-02"),
-        ),
-        (
-            "info",
-            "training",
-            "Epoch 1 started - 500 steps",
-            Some("task_vision_training"),
-            None,
-        ),
-        (
-            "info",
-            "checkpoint",
-            "Checkpoint saved: epoch_1.pt (650MB)",
-            Some("task_vision_training"),
-            Some("gpu-worker-01"),
-        ),
-        (
-            "warn",
-            "heartbeat",
-            "Worker cpu-worker-01 heartbeat delayed",
-            None,
-            Some("cpu-worker-01"),
-        ),
-        (
-            "info",
-            "barrier",
-            "Barrier sync completed: 3/3 workers",
-            Some("task_vision_training"),
-            None,
-        ),
-        (
-            "info",
-            "training",
-            "Training progress: 45% complete",
-            Some("task_vision_training"),
-            None,
-        ),
-    ];
-
-    for (i, (level, source, message, task_id, worker_id)) in log_entries.iter().enumerate() {
-        let timestamp = now - ((log_entries.len() - i) * 30000) as i64; // 30 seconds apart
-        logs.push(LogResponse {
-            id: format!("log_{}", &uuid::Uuid::new_v4().to_string()[..8]),
-            timestamp,
-            level: level.to_string(),
-            message: message.to_string(),
-            source: source.to_string(),
-            task_id: task_id.map(|s| s.to_string()),
-            worker_id: worker_id.map(|s| s.to_string()),
-        });
+        let schemas = &spec["components"]["schemas"];
+        assert!(schemas["WorkerResponse"].is_object());
+        assert!(schemas["ErrorResponse"].is_object());
+        assert!(schemas["TaskResponse"]["properties"]["type"].is_object());
     }
 
-    // Add some recent logs based on uptime
-    if uptime > 0 {
-        logs.push(LogResponse {
-            id: format!("log_{}", &uuid::Uuid::new_v4().to_string()[..8]),
-            timestamp: now - 5000,
-            level: "info".to_string(),
-            message: format!("System uptime: {}s, {} active workers", uptime, 2),
-            source: "coordinator".to_string(),
-            task_id: None,
-            worker_id: None,
+    #[tokio::test]
+    async fn test_cors_policy() {
+        let (_dir, service) = test_service().await;
+        let cors = cors_layer(&HttpCorsConfig {
+            allowed_origins: vec!["https://dashboard.example.com/".to_string()],
+            allowed_methods: vec!["get".to_string(), "POST".to_string()],
+            allowed_headers: vec!["Authorization".to_string()],
+        })
+        .unwrap();
+        let router = create_router(service, cors);
+        let preflight = |origin: &str| {
+            router.clone().oneshot(
+                Request::builder()
+                    .method("OPTIONS")
+                    .uri("/api/workers")
+                    .header("origin", origin)
+                    .header("access-control-request-method", "POST")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+        };
+
+        let response = preflight("https://dashboard.example.com").await.unwrap();
+        let headers = response.headers();
+        assert_eq!(
+            headers["access-control-allow-origin"],
+            "https://dashboard.example.com"
+        );
+        assert_eq!(headers["access-control-allow-methods"], "GET,POST");
+        assert_eq!(headers["access-control-allow-headers"], "authorization");
+
+        let response = preflight("https://evil.example.com").await.unwrap();
+        assert!(!response
+            .headers()
+            .contains_key("access-control-allow-origin"));
+
+        // The defaults allow any origin
+        let router = create_router(
+            test_service().await.1,
+            cors_layer(&HttpCorsConfig::default()).unwrap(),
+        );
+        let response = router
+            .oneshot(
+                Request::get("/api/health")
+                    .header("origin", "http://localhost:5173")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["access-control-allow-origin"], "*");
+
+        for config in [
+            HttpCorsConfig {
+                allowed_origins: vec!["dashboard.example.com".to_string()],
+                ..Default::default()
+            },
+            HttpCorsConfig {
+                allowed_origins: vec!["*".to_string(), "https://a.example.com".to_string()],
+                ..Default::default()
+            },
+            HttpCorsConfig {
+                allowed_methods: vec!["GET POST".to_string()],
+                ..Default::default()
+            },
+        ] {
+            let err = cors_layer(&config).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compression_and_etag() {
+        let (_dir, service) = test_service().await;
+        for i in 0..10 {
+            service
+                .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                    worker_id: format!("worker-{}", i),
+                    hostname: format!("node-{}", i),
+                    port: 50052,
+                    protocol_version: crate::PROTOCOL_VERSION,
+                    ..Default::default()
+                }))
+                .await
+                .unwrap();
+        }
+        let router = create_router(service, CorsLayer::new());
+        let get = |headers: &[(header::HeaderName, &str)]| {
+            let mut request = Request::get("/api/dashboard");
+            for (name, value) in headers {
+                request = request.header(name, *value);
+            }
+            router.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let response = get(&[]).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+        let etag = response.headers()[header::ETAG]
+            .to_str()
+            .unwrap()
+            .to_string();
+        assert!(etag.starts_with("W/\""));
+        let plain = response.into_body().collect().await.unwrap().to_bytes();
+
+        let response = get(&[(header::ACCEPT_ENCODING, "gzip")]).await.unwrap();
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        let compressed = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(compressed.len() < plain.len());
+
+        let response = get(&[(header::IF_NONE_MATCH, etag.as_str())])
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()[header::ETAG], etag.as_str());
+        assert!(response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .is_empty());
+
+        let response = get(&[(header::IF_NONE_MATCH, "W/\"stale\"")])
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit() {
+        let (_dir, service) = test_service().await;
+        let limiter = Arc::new(HttpRateLimiter::new(1, 2).with_route("/api/health", 0, 0));
+        let router = create_rate_limited_router(service, limiter, CorsLayer::new());
+        let get = |uri: &str, ip: [u8; 4], token: Option<&str>| {
+            let mut request = Request::builder()
+                .uri(uri)
+                .extension(ConnectInfo(SocketAddr::from((ip, 40000))));
+            if let Some(token) = token {
+                request = request.header("authorization", format!("Bearer {}", token));
+            }
+            router.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+        let client = [10, 0, 0, 1];
+
+        for _ in 0..2 {
+            assert_eq!(
+                get("/api/workers", client, None).await.unwrap().status(),
+                StatusCode::OK
+            );
+        }
+        let limited = get("/api/workers", client, None).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "1");
+
+        // Other clients and unlimited routes are unaffected
+        let other = get("/api/workers", [10, 0, 0, 2], None).await.unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+        let health = get("/api/health", client, None).await.unwrap();
+        assert_eq!(health.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_ignores_rotating_tokens() {
+        let (_dir, service) = test_service().await;
+        let limiter = Arc::new(HttpRateLimiter::new(1, 2));
+        let router = create_rate_limited_router(service, limiter, CorsLayer::new());
+
+        let mut statuses = Vec::new();
+        for i in 0..4 {
+            let request = Request::builder()
+                .uri("/api/workers")
+                .extension(ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 40000 + i))))
+                .header("authorization", format!("Bearer token-{}", i))
+                .body(Body::empty())
+                .unwrap();
+            statuses.push(router.clone().oneshot(request).await.unwrap().status());
+        }
+        assert_eq!(
+            statuses,
+            vec![
+                StatusCode::OK,
+                StatusCode::OK,
+                StatusCode::TOO_MANY_REQUESTS,
+                StatusCode::TOO_MANY_REQUESTS
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_runtime_config() {
+        let (_dir, service) = test_service().await;
+        let mut updates = service.subscribe_config();
+
+        let (status, body) =
+            send_json(&service, "GET", "/api/config", serde_json::Value::Null).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["coordinator"]["heartbeat_timeout"], 30_000);
+
+        let limits = HttpRateLimitConfig {
+            requests_per_second: 50,
+            ..Default::default()
+        };
+        let (status, body) = send_json(
+            &service,
+            "PUT",
+            "/api/config",
+            serde_json::json!({
+                "heartbeat_timeout_ms": 60_000,
+                "checkpoint_keep_count": 2,
+                "http_rate_limit": limits,
+            }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["coordinator"]["heartbeat_timeout"], 60_000);
+        assert_eq!(body["checkpoint"]["keep_count"], 2);
+        assert!(updates.has_changed().unwrap());
+        assert_eq!(
+            updates
+                .borrow_and_update()
+                .coordinator
+                .http_rate_limit
+                .requests_per_second,
+            50
+        );
+
+        // Invalid values and settings outside the whitelist are rejected
+        let (status, body) = send_json(
+            &service,
+            "PUT",
+            "/api/config",
+            serde_json::json!({ "heartbeat_timeout_ms": 1 }),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "heartbeat_timeout_ms");
+        let (status, _) = send_json(
+            &service,
+            "PUT",
+            "/api/config",
+            serde_json::json!({ "max_workers": 1 }),
+        )
+        .await;
+        assert!(status.is_client_error());
+        assert_eq!(service.config().coordinator.heartbeat_timeout.as_secs(), 60);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_history() {
+        let (_dir, service) = test_service().await;
+        let dataset = serde_json::json!({
+            "dataset_id": "mnist",
+            "path": "/data/mnist",
+            "format": "parquet",
+            "total_samples": 1000,
+            "shard_size": 100,
         });
+        send_json(&service, "POST", "/api/datasets", dataset).await;
+        service.sample_metrics();
+        service.sample_metrics();
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/metrics/history?range=15m",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["range_seconds"], 900);
+        assert_eq!(body["samples"].as_array().unwrap().len(), 2);
+        assert_eq!(body["samples"][1]["datasets"]["mnist"]["epoch"], 0);
+
+        let (status, body) = send_json(
+            &service,
+            "GET",
+            "/api/metrics/history?range=forever",
+            serde_json::Value::Null,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["field"], "range");
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_metrics() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(tonic::Request::new(crate::proto::WorkerInfo {
+                worker_id: "worker-1".to_string(),
+                hostname: "localhost".to_string(),
+                port: 8080,
+                gpu_count: 1,
+                ..Default::default()
+            }))
+            .await
+            .unwrap();
+        service.request_metrics().record_request("WaitBarrier");
+        service
+            .request_metrics()
+            .record_latency("WaitBarrier", 2_000);
+
+        let response = create_router(service.clone(), CorsLayer::new())
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            prometheus::CONTENT_TYPE
+        );
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let body = String::from_utf8(bytes.to_vec()).unwrap();
+
+        assert!(body.contains("# TYPE strata_workers gauge"));
+        assert!(body.contains("strata_workers{status=\"idle\"} 1\n"));
+        assert!(body.contains("strata_rpc_requests_total{method=\"WaitBarrier\"} 1\n"));
+        assert!(body.contains("strata_barrier_wait_seconds{quantile=\"0.99\"} 0.002\n"));
+        assert!(body.contains("strata_barrier_wait_seconds_count 1\n"));
+        assert!(body.contains("strata_checkpoints_total 0\n"));
+        assert!(body.contains("# TYPE strata_checkpoint_write_seconds histogram"));
+        assert!(body.contains("strata_shard_rebalances_total 0\n"));
     }
 
-    logs.reverse(); // Most recent first
-    logs
+    #[tokio::test]
+    async fn test_serve_tls() {
+        let (dir, service) = test_service().await;
+        let addr: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let missing = TlsConfig {
+            cert_path: dir.path().join("cert.pem").display().to_string(),
+            key_path: dir.path().join("key.pem").display().to_string(),
+        };
+        let err = serve(
+            addr,
+            create_router(service.clone(), CorsLayer::new()),
+            Some(&missing),
+            async {},
+        )
+        .await
+        .unwrap_err();
+        assert!(err.to_string().contains("cert.pem"));
+
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        std::fs::write(&missing.cert_path, cert.cert.pem()).unwrap();
+        std::fs::write(&missing.key_path, cert.key_pair.serialize_pem()).unwrap();
+        let served = tokio::time::timeout(
+            Duration::from_secs(5),
+            serve(
+                addr,
+                create_router(service, CorsLayer::new()),
+                Some(&missing),
+                async {},
+            ),
+        )
+        .await
+        .expect("server did not shut down");
+        served.unwrap();
+    }
 }
@@ -0,0 +1,129 @@
+//! Job-scoped key-value store
+//!
+//! Lets workers exchange small rendezvous blobs (NCCL unique ids, master
+//! addresses) through the coordinator, in the style of a TCPStore.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use dashmap::DashMap;
+use tokio::sync::Notify;
+
+/// Maximum size of a single value in bytes
+pub const MAX_KV_VALUE_BYTES: usize = 1024 * 1024;
+
+/// Key-value store with blocking waits for keys to appear
+#[derive(Debug, Default)]
+pub struct KvStore {
+    /// (job_id, key) -> value
+    entries: DashMap<(String, String), Vec<u8>>,
+    /// Woken whenever any key is set
+    changed: Notify,
+}
+
+impl KvStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a key, returning `true` if it did not exist before
+    pub fn set(&self, job_id: &str, key: &str, value: Vec<u8>) -> bool {
+        let created = self
+            .entries
+            .insert((job_id.to_string(), key.to_string()), value)
+            .is_none();
+        self.changed.notify_waiters();
+        created
+    }
+
+    /// Get a key's value
+    pub fn get(&self, job_id: &str, key: &str) -> Option<Vec<u8>> {
+        self.entries
+            .get(&(job_id.to_string(), key.to_string()))
+            .map(|v| v.clone())
+    }
+
+    /// Wait until all keys are set, returning their values
+    ///
+    /// Returns `None` if the keys are not all present before `timeout`.
+    pub async fn wait(
+        &self,
+        job_id: &str,
+        keys: &[String],
+        timeout: Duration,
+    ) -> Option<HashMap<String, Vec<u8>>> {
+        tokio::time::timeout(timeout, async {
+            loop {
+                // Register for wakeups before checking so no set is missed
+                let notified = self.changed.notified();
+                tokio::pin!(notified);
+                notified.as_mut().enable();
+
+                let values: Option<HashMap<_, _>> = keys
+                    .iter()
+                    .map(|key| self.get(job_id, key).map(|v| (key.clone(), v)))
+                    .collect();
+                if let Some(values) = values {
+                    return values;
+                }
+
+                notified.await;
+            }
+        })
+        .await
+        .ok()
+    }
+
+    /// Remove every key of a job, returning how many were removed
+    pub fn clear_job(&self, job_id: &str) -> usize {
+        let before = self.entries.len();
+        self.entries.retain(|(job, _), _| job != job_id);
+        before - self.entries.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_set_get_scoped_by_job() {
+        let store = KvStore::new();
+        assert!(store.set("job-a", "master_addr", b"10.0.0.1:29500".to_vec()));
+        assert!(!store.set("job-a", "master_addr", b"10.0.0.2:29500".to_vec()));
+
+        assert_eq!(
+            store.get("job-a", "master_addr").unwrap(),
+            b"10.0.0.2:29500"
+        );
+        assert!(store.get("job-b", "master_addr").is_none());
+
+        assert_eq!(store.clear_job("job-a"), 1);
+        assert!(store.get("job-a", "master_addr").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_keys() {
+        let store = Arc::new(KvStore::new());
+        let keys = vec!["nccl_id".to_string(), "world".to_string()];
+
+        let waiter = {
+            let store = store.clone();
+            let keys = keys.clone();
+            tokio::spawn(async move { store.wait("job", &keys, Duration::from_secs(5)).await })
+        };
+
+        store.set("job", "nccl_id", vec![1, 2, 3]);
+        store.set("job", "world", vec![4]);
+
+        let values = waiter.await.unwrap().unwrap();
+        assert_eq!(values["nccl_id"], vec![1, 2, 3]);
+
+        let missing = store
+            .wait("job", &["absent".to_string()], Duration::from_millis(10))
+            .await;
+        assert!(missing.is_none());
+    }
+}
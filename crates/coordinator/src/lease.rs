@@ -0,0 +1,235 @@
+//! Named leases for singleton duties
+//!
+//! A lease is held by one worker until it is released or its TTL expires
+//! without renewal. Every grant carries a fencing token that increases
+//! monotonically, so side effects from a stale holder can be rejected.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use dashmap::mapref::entry::Entry;
+use dashmap::DashMap;
+use tracing::info;
+
+use runtime_core::WorkerId;
+
+/// A granted lease
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeaseGrant {
+    /// Worker holding the lease
+    pub holder: WorkerId,
+    /// Fencing token, unique per acquisition
+    pub token: u64,
+    /// Time until the lease expires
+    pub expires_in: Duration,
+}
+
+/// Reasons a lease operation was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LeaseError {
+    /// Another worker holds the lease
+    Held(LeaseGrant),
+    /// The caller does not hold the lease (it expired or was never granted)
+    NotHeld,
+}
+
+#[derive(Debug)]
+struct Lease {
+    holder: WorkerId,
+    token: u64,
+    expires_at: Instant,
+}
+
+impl Lease {
+    fn is_expired(&self) -> bool {
+        Instant::now() >= self.expires_at
+    }
+
+    fn grant(&self) -> LeaseGrant {
+        LeaseGrant {
+            holder: self.holder.clone(),
+            token: self.token,
+            expires_in: self.expires_at.saturating_duration_since(Instant::now()),
+        }
+    }
+}
+
+/// Registry of named leases
+#[derive(Debug, Default)]
+pub struct LeaseManager {
+    leases: DashMap<String, Lease>,
+    next_token: AtomicU64,
+}
+
+impl LeaseManager {
+    /// Create an empty lease manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Acquire a lease, or extend it if the worker already holds it
+    pub fn acquire(
+        &self,
+        name: &str,
+        worker_id: &WorkerId,
+        ttl: Duration,
+    ) -> Result<LeaseGrant, LeaseError> {
+        match self.leases.entry(name.to_string()) {
+            Entry::Occupied(mut entry) => {
+                let lease = entry.get_mut();
+                if lease.holder == *worker_id && !lease.is_expired() {
+                    lease.expires_at = Instant::now() + ttl;
+                    return Ok(lease.grant());
+                }
+                if !lease.is_expired() {
+                    return Err(LeaseError::Held(lease.grant()));
+                }
+                *lease = self.new_lease(name, worker_id, ttl);
+                Ok(lease.grant())
+            }
+            Entry::Vacant(entry) => Ok(entry.insert(self.new_lease(name, worker_id, ttl)).grant()),
+        }
+    }
+
+    /// Extend a lease held by the worker under the given token
+    pub fn renew(
+        &self,
+        name: &str,
+        worker_id: &WorkerId,
+        token: u64,
+        ttl: Duration,
+    ) -> Result<LeaseGrant, LeaseError> {
+        let mut lease = self.leases.get_mut(name).ok_or(LeaseError::NotHeld)?;
+        if lease.holder != *worker_id || lease.token != token || lease.is_expired() {
+            return Err(LeaseError::NotHeld);
+        }
+        lease.expires_at = Instant::now() + ttl;
+        Ok(lease.grant())
+    }
+
+    /// Release a lease, returning `false` if the worker did not hold it
+    pub fn release(&self, name: &str, worker_id: &WorkerId, token: u64) -> bool {
+        self.leases
+            .remove_if(name, |_, lease| {
+                lease.holder == *worker_id && lease.token == token && !lease.is_expired()
+            })
+            .is_some()
+    }
+
+    /// Release every lease held by a worker that left the cluster
+    pub fn release_all(&self, worker_id: &WorkerId) -> usize {
+        let before = self.leases.len();
+        self.leases.retain(|_, lease| lease.holder != *worker_id);
+        before - self.leases.len()
+    }
+
+    /// Drop expired leases, returning how many were removed
+    pub fn evict_expired(&self) -> usize {
+        let before = self.leases.len();
+        self.leases.retain(|_, lease| !lease.is_expired());
+        before - self.leases.len()
+    }
+
+    /// Current holder of a lease, if any
+    pub fn holder(&self, name: &str) -> Option<LeaseGrant> {
+        self.leases
+            .get(name)
+            .filter(|lease| !lease.is_expired())
+            .map(|lease| lease.grant())
+    }
+
+    fn new_lease(&self, name: &str, worker_id: &WorkerId, ttl: Duration) -> Lease {
+        let token = self.next_token.fetch_add(1, Ordering::SeqCst) + 1;
+        info!(lease = %name, worker_id = %worker_id, token = token, "Lease acquired");
+        Lease {
+            holder: worker_id.clone(),
+            token,
+            expires_at: Instant::now() + ttl,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_single_holder() {
+        let leases = LeaseManager::new();
+        let ttl = Duration::from_secs(30);
+
+        let grant = leases
+            .acquire("upload-model", &worker_id("worker-1"), ttl)
+            .unwrap();
+        let Err(LeaseError::Held(held)) =
+            leases.acquire("upload-model", &worker_id("worker-2"), ttl)
+        else {
+            panic!("lease should be held by worker-1");
+        };
+        assert_eq!(held.holder, "worker-1");
+
+        // Re-acquiring by the holder keeps the token
+        assert_eq!(
+            leases
+                .acquire("upload-model", &worker_id("worker-1"), ttl)
+                .unwrap()
+                .token,
+            grant.token
+        );
+
+        assert!(!leases.release("upload-model", &worker_id("worker-2"), grant.token));
+        assert!(leases.release("upload-model", &worker_id("worker-1"), grant.token));
+        assert!(leases.holder("upload-model").is_none());
+    }
+
+    #[test]
+    fn test_expired_lease_taken_over() {
+        let leases = LeaseManager::new();
+
+        let stale = leases
+            .acquire("eval", &worker_id("worker-1"), Duration::ZERO)
+            .unwrap();
+        let grant = leases
+            .acquire("eval", &worker_id("worker-2"), Duration::from_secs(30))
+            .unwrap();
+        assert!(grant.token > stale.token);
+
+        assert_eq!(
+            leases.renew(
+                "eval",
+                &worker_id("worker-1"),
+                stale.token,
+                Duration::from_secs(30)
+            ),
+            Err(LeaseError::NotHeld)
+        );
+        assert!(leases
+            .renew(
+                "eval",
+                &worker_id("worker-2"),
+                grant.token,
+                Duration::from_secs(30)
+            )
+            .is_ok());
+
+        assert_eq!(leases.release_all(&worker_id("worker-2")), 1);
+    }
+
+    #[test]
+    fn test_evict_expired() {
+        let leases = LeaseManager::new();
+        leases
+            .acquire("stale", &worker_id("worker-1"), Duration::ZERO)
+            .unwrap();
+        leases
+            .acquire("live", &worker_id("worker-1"), Duration::from_secs(30))
+            .unwrap();
+
+        assert_eq!(leases.evict_expired(), 1);
+        assert!(leases.holder("live").is_some());
+    }
+}
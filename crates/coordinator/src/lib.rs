@@ -3,7 +3,7 @@
 //! This crate provides the central coordination server that manages:
 //! - **Worker lifecycle**: Registration, heartbeats, failure detection
 //! - **Data sharding**: Dataset registration and shard assignment
-//! - **Checkpointing**: Distributed checkpoint coordination
+//! - **Checkpointing**: Distributed checkpoint coordination and scheduling
 //! - **Synchronization**: Barrier-based worker synchronization
 //! - **Security**: Rate limiting, input validation, request metrics
 //!
@@ -20,19 +20,88 @@
 //! }
 //! ```
 
+// tonic::Status is large by design and is the error type of every handler
+#![allow(clippy::result_large_err)]
+
+pub mod barrier;
+pub mod checkpoint_txn;
+pub mod client;
+pub mod commands;
+pub mod dedup;
+pub mod event_log;
+pub mod events;
 pub mod http_api;
+pub mod kv;
+pub mod lease;
+pub mod logs;
+pub mod metrics_history;
 pub mod middleware;
+pub mod protocol;
+pub mod scheduler;
+pub mod schedules;
 pub mod server;
 pub mod service;
+pub mod simulation;
+pub mod tasks;
+pub mod worker_history;
 
 // Re-export generated protobuf types
 pub mod proto {
     tonic::include_proto!("coordinator");
+
+    /// Encoded file descriptor set for gRPC reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("coordinator_descriptor");
+
+    impl From<runtime_core::ResourceMetrics> for ResourceUsage {
+        fn from(metrics: runtime_core::ResourceMetrics) -> Self {
+            Self {
+                cpu_percent: metrics.cpu_percent,
+                memory_used_bytes: metrics.memory_used_bytes as i64,
+                gpu_usage: metrics
+                    .gpu_metrics
+                    .into_iter()
+                    .map(|g| GpuUsage {
+                        gpu_id: g.gpu_id as i32,
+                        utilization_percent: g.utilization_percent,
+                        memory_used_bytes: g.memory_used_bytes as i64,
+                        memory_total_bytes: g.memory_total_bytes as i64,
+                        temperature_celsius: g.temperature_celsius,
+                        power_draw_watts: g.power_draw_watts,
+                        sm_clock_mhz: g.sm_clock_mhz as i32,
+                        memory_clock_mhz: g.memory_clock_mhz as i32,
+                        ecc_errors_corrected: g.ecc_errors_corrected as i64,
+                        ecc_errors_uncorrected: g.ecc_errors_uncorrected as i64,
+                    })
+                    .collect(),
+                disk_read_bytes: metrics.disk_read_bytes as i64,
+                disk_write_bytes: metrics.disk_write_bytes as i64,
+                network_rx_bytes: metrics.network_rx_bytes as i64,
+                network_tx_bytes: metrics.network_tx_bytes as i64,
+            }
+        }
+    }
 }
 
 // Re-export main types
+pub use barrier::{BarrierRegistry, BarrierStatus};
+pub use checkpoint_txn::{CheckpointTransaction, CheckpointTransactions, TransactionState};
+pub use client::{ResilientClientConfig, ResilientCoordinatorClient};
+pub use commands::{CommandQueue, WorkerCommand};
+pub use event_log::{EventLog, LoggedEvent};
+pub use events::{CoordinatorEvent, EventBus};
+pub use kv::KvStore;
+pub use lease::{LeaseGrant, LeaseManager};
+pub use logs::{LogBuffer, LogEntry, LogFilter, LogLevel};
+pub use metrics_history::{MetricsHistory, MetricsSample};
+pub use protocol::PROTOCOL_VERSION;
+pub use scheduler::CheckpointScheduler;
+pub use schedules::{CronSchedule, Schedule, ScheduleManager};
 pub use server::CoordinatorServer;
 pub use service::CoordinatorService;
+pub use simulation::SimulationEngine;
+pub use tasks::{Task, TaskManager, TaskState};
+pub use worker_history::WorkerHistory;
 
 // Re-export proto service trait for convenience
 pub use proto::coordinator_client::CoordinatorClient;
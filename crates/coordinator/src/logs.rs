@@ -0,0 +1,442 @@
+//! Log aggregation for the dashboard
+//!
+//! Coordinator logs are captured by [`LogCaptureLayer`], a tracing layer that
+//! writes every event into a bounded [`LogBuffer`]. Workers ship their own log
+//! lines into the same buffer over the `ShipLogs` RPC, so `/api/logs` can show
+//! and filter the logs of the whole cluster in one place. Readers can watch
+//! the latest sequence number to tail the buffer.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use utoipa::{IntoParams, ToSchema};
+
+/// Default number of log lines kept in memory
+pub const DEFAULT_LOG_CAPACITY: usize = 10_000;
+
+/// Log severity, ordered from least to most severe
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ToSchema,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    /// Lowercase name, as used by the HTTP API
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            other => Err(format!("unknown log level: {}", other)),
+        }
+    }
+}
+
+impl From<&Level> for LogLevel {
+    fn from(level: &Level) -> Self {
+        match *level {
+            Level::TRACE => LogLevel::Trace,
+            Level::DEBUG => LogLevel::Debug,
+            Level::INFO => LogLevel::Info,
+            Level::WARN => LogLevel::Warn,
+            Level::ERROR => LogLevel::Error,
+        }
+    }
+}
+
+/// A captured log line
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Sequence number, increasing in the order lines were recorded
+    pub seq: u64,
+    /// When the line was logged (ms since epoch)
+    pub timestamp_ms: i64,
+    pub level: LogLevel,
+    pub message: String,
+    /// Module path for coordinator logs, the worker's target for shipped logs
+    pub source: String,
+    pub worker_id: Option<String>,
+    pub task_id: Option<String>,
+}
+
+/// A log line before it is recorded
+#[derive(Debug, Clone)]
+pub struct LogRecord {
+    /// When the line was logged (ms since epoch), `None` for now
+    pub timestamp_ms: Option<i64>,
+    pub level: LogLevel,
+    pub message: String,
+    pub source: String,
+    pub worker_id: Option<String>,
+    pub task_id: Option<String>,
+}
+
+/// Criteria for querying the log buffer
+///
+/// Unset fields match every line.
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct LogFilter {
+    /// Minimum severity
+    pub level: Option<LogLevel>,
+    /// Source prefix, so `coordinator` matches `coordinator::service`
+    pub source: Option<String>,
+    pub worker_id: Option<String>,
+    pub task_id: Option<String>,
+    /// Earliest timestamp (ms since epoch, inclusive)
+    pub since: Option<i64>,
+    /// Latest timestamp (ms since epoch, inclusive)
+    pub until: Option<i64>,
+    /// Maximum number of lines, the most recent ones are kept
+    pub limit: Option<usize>,
+}
+
+impl LogFilter {
+    /// Whether a line matches the filter, ignoring the limit
+    pub fn matches(&self, entry: &LogEntry) -> bool {
+        self.level.is_none_or(|level| entry.level >= level)
+            && self
+                .source
+                .as_deref()
+                .is_none_or(|source| entry.source.starts_with(source))
+            && self
+                .worker_id
+                .as_deref()
+                .is_none_or(|id| entry.worker_id.as_deref() == Some(id))
+            && self
+                .task_id
+                .as_deref()
+                .is_none_or(|id| entry.task_id.as_deref() == Some(id))
+            && self.since.is_none_or(|since| entry.timestamp_ms >= since)
+            && self.until.is_none_or(|until| entry.timestamp_ms <= until)
+    }
+}
+
+struct LogBufferInner {
+    /// Most recent lines, oldest first
+    ring: VecDeque<LogEntry>,
+    /// Sequence number of the last recorded line
+    last_seq: u64,
+}
+
+/// Bounded in-memory buffer of coordinator and worker logs
+pub struct LogBuffer {
+    capacity: usize,
+    inner: Mutex<LogBufferInner>,
+    /// Sequence number of the last recorded line, for tailing readers
+    appended: watch::Sender<u64>,
+}
+
+impl Default for LogBuffer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LOG_CAPACITY)
+    }
+}
+
+impl fmt::Debug for LogBuffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogBuffer")
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl LogBuffer {
+    /// Create a buffer keeping the last `capacity` lines
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(LogBufferInner {
+                ring: VecDeque::new(),
+                last_seq: 0,
+            }),
+            appended: watch::channel(0).0,
+        }
+    }
+
+    /// Record a line, evicting the oldest one when full
+    ///
+    /// Must not log itself, since it runs inside the tracing layer.
+    pub fn push(&self, record: LogRecord) -> u64 {
+        let mut inner = self.inner.lock();
+        inner.last_seq += 1;
+
+        let entry = LogEntry {
+            seq: inner.last_seq,
+            timestamp_ms: record
+                .timestamp_ms
+                .unwrap_or_else(|| Utc::now().timestamp_millis()),
+            level: record.level,
+            message: record.message,
+            source: record.source,
+            worker_id: record.worker_id,
+            task_id: record.task_id,
+        };
+
+        if inner.ring.len() >= self.capacity {
+            inner.ring.pop_front();
+        }
+        inner.ring.push_back(entry);
+        self.appended.send_replace(inner.last_seq);
+        inner.last_seq
+    }
+
+    /// Lines matching a filter, oldest first
+    pub fn query(&self, filter: &LogFilter) -> Vec<LogEntry> {
+        let inner = self.inner.lock();
+        let limit = filter.limit.unwrap_or(usize::MAX);
+        let mut entries: Vec<LogEntry> = inner
+            .ring
+            .iter()
+            .rev()
+            .filter(|e| filter.matches(e))
+            .take(limit)
+            .cloned()
+            .collect();
+        entries.reverse();
+        entries
+    }
+
+    /// Lines with a sequence number greater than `after_seq`, oldest first
+    pub fn since(&self, after_seq: u64, limit: usize) -> Vec<LogEntry> {
+        self.inner
+            .lock()
+            .ring
+            .iter()
+            .filter(|e| e.seq > after_seq)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Sequence number of the most recent line (0 if none)
+    pub fn last_seq(&self) -> u64 {
+        self.inner.lock().last_seq
+    }
+
+    /// Watch the sequence number of the most recent line
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.appended.subscribe()
+    }
+
+    /// Number of lines currently buffered
+    pub fn len(&self) -> usize {
+        self.inner.lock().ring.len()
+    }
+
+    /// Whether the buffer is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// A tracing layer recording coordinator logs into this buffer
+    pub fn layer(self: &Arc<Self>) -> LogCaptureLayer {
+        LogCaptureLayer {
+            buffer: Arc::clone(self),
+        }
+    }
+}
+
+/// Tracing layer that records events into a [`LogBuffer`]
+///
+/// `worker_id` and `task_id` fields are lifted out of the event so lines can
+/// be filtered by them; other fields are appended to the message as `key=value`.
+pub struct LogCaptureLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl<S: Subscriber> Layer<S> for LogCaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let message = if visitor.fields.is_empty() {
+            visitor.message
+        } else if visitor.message.is_empty() {
+            visitor.fields
+        } else {
+            format!("{} {}", visitor.message, visitor.fields)
+        };
+
+        self.buffer.push(LogRecord {
+            timestamp_ms: None,
+            level: metadata.level().into(),
+            message,
+            source: metadata.target().to_string(),
+            worker_id: visitor.worker_id,
+            task_id: visitor.task_id,
+        });
+    }
+}
+
+/// Collects the fields of a tracing event
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+    worker_id: Option<String>,
+    task_id: Option<String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_string(),
+            "worker_id" => self.worker_id = Some(value.to_string()),
+            "task_id" => self.task_id = Some(value.to_string()),
+            _ => self.record_debug(field, &value),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{:?}", value),
+            // Display-formatted IDs (`%worker_id`) arrive here
+            "worker_id" => self.worker_id = Some(format!("{:?}", value)),
+            "task_id" => self.task_id = Some(format!("{:?}", value)),
+            name => {
+                if !self.fields.is_empty() {
+                    self.fields.push(' ');
+                }
+                let _ = write!(self.fields, "{}={:?}", name, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing::subscriber::with_default;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    fn record(level: LogLevel, source: &str, worker_id: Option<&str>, ts: i64) -> LogRecord {
+        LogRecord {
+            timestamp_ms: Some(ts),
+            level,
+            message: format!("{} from {}", level, source),
+            source: source.to_string(),
+            worker_id: worker_id.map(str::to_string),
+            task_id: None,
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let buffer = LogBuffer::new(3);
+        for ts in 0..5 {
+            buffer.push(record(LogLevel::Info, "coordinator", None, ts));
+        }
+
+        let entries = buffer.query(&LogFilter::default());
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].timestamp_ms, 2);
+        assert_eq!(entries[2].seq, 5);
+
+        let tail = buffer.since(3, 10);
+        assert_eq!(tail.iter().map(|e| e.seq).collect::<Vec<_>>(), [4, 5]);
+        assert_eq!(*buffer.subscribe().borrow(), buffer.last_seq());
+    }
+
+    #[test]
+    fn test_query_filters() {
+        let buffer = LogBuffer::default();
+        buffer.push(record(LogLevel::Debug, "coordinator::service", None, 10));
+        buffer.push(record(LogLevel::Warn, "coordinator::service", None, 20));
+        buffer.push(record(LogLevel::Info, "trainer", Some("worker-1"), 30));
+        buffer.push(record(LogLevel::Error, "trainer", Some("worker-2"), 40));
+
+        let warnings = buffer.query(&LogFilter {
+            level: Some(LogLevel::Warn),
+            ..Default::default()
+        });
+        assert_eq!(warnings.len(), 2);
+
+        let coordinator = buffer.query(&LogFilter {
+            source: Some("coordinator".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(coordinator.len(), 2);
+
+        let worker = buffer.query(&LogFilter {
+            worker_id: Some("worker-1".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(worker.len(), 1);
+        assert_eq!(worker[0].source, "trainer");
+
+        let window = buffer.query(&LogFilter {
+            since: Some(20),
+            until: Some(30),
+            ..Default::default()
+        });
+        assert_eq!(window.len(), 2);
+
+        // The limit keeps the most recent lines
+        let latest = buffer.query(&LogFilter {
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert_eq!(latest[0].timestamp_ms, 40);
+    }
+
+    #[test]
+    fn test_capture_layer() {
+        let buffer = Arc::new(LogBuffer::default());
+        let subscriber = tracing_subscriber::registry().with(buffer.layer());
+
+        with_default(subscriber, || {
+            let worker_id = "worker-1";
+            tracing::warn!(worker_id = %worker_id, step = 42, "Worker is slow");
+            tracing::info!(task_id = "task_1", "Task started");
+        });
+
+        let entries = buffer.query(&LogFilter::default());
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].level, LogLevel::Warn);
+        assert_eq!(entries[0].message, "Worker is slow step=42");
+        assert_eq!(entries[0].worker_id.as_deref(), Some("worker-1"));
+        assert_eq!(entries[1].task_id.as_deref(), Some("task_1"));
+        assert!(entries[1].source.starts_with("coordinator"));
+    }
+}
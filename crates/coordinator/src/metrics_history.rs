@@ -0,0 +1,204 @@
+//! Metrics history for dashboard charts
+//!
+//! The coordinator samples its metrics at a fixed interval into a bounded
+//! [`MetricsHistory`]. Counters are turned into rates over the interval since
+//! the previous sample, so `/api/metrics/history` returns values that can be
+//! plotted directly.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Instantaneous metrics and cumulative counters read from the coordinator
+#[derive(Debug, Clone, Default)]
+pub struct MetricsReading {
+    /// When the reading was taken (ms since epoch)
+    pub timestamp_ms: i64,
+    pub total_workers: u32,
+    pub active_workers: u32,
+    /// RPCs served since startup
+    pub total_requests: u64,
+    /// Checkpoints committed since startup
+    pub checkpoints_committed: u64,
+    /// Epoch and progress (0.0 to 1.0) per dataset
+    pub epochs: BTreeMap<String, (u64, f64)>,
+}
+
+/// A point of the metrics history
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MetricsSample {
+    /// When the sample was taken (ms since epoch)
+    pub timestamp_ms: i64,
+    pub total_workers: u32,
+    pub active_workers: u32,
+    /// RPCs per second since the previous sample
+    pub requests_per_second: f64,
+    /// Checkpoints per minute since the previous sample
+    pub checkpoints_per_minute: f64,
+    /// Epoch progress per dataset
+    pub datasets: BTreeMap<String, DatasetProgressSample>,
+}
+
+/// Epoch progress of a dataset at a sample
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct DatasetProgressSample {
+    pub epoch: u64,
+    /// Fraction of the epoch consumed, 0.0 to 1.0
+    pub progress: f64,
+}
+
+struct HistoryInner {
+    /// Oldest first
+    samples: VecDeque<MetricsSample>,
+    /// Timestamp and counters of the previous reading
+    last: Option<(i64, u64, u64)>,
+}
+
+/// Bounded history of sampled metrics
+pub struct MetricsHistory {
+    capacity: usize,
+    inner: Mutex<HistoryInner>,
+}
+
+impl MetricsHistory {
+    /// Create a history keeping the last `capacity` samples
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            inner: Mutex::new(HistoryInner {
+                samples: VecDeque::new(),
+                last: None,
+            }),
+        }
+    }
+
+    /// Record a reading, evicting the oldest sample when full
+    ///
+    /// Rates are zero for the first reading, as there is nothing to compare to.
+    pub fn record(&self, reading: MetricsReading) -> MetricsSample {
+        let mut inner = self.inner.lock();
+
+        let (requests_per_second, checkpoints_per_minute) = match inner.last {
+            Some((ts, requests, checkpoints)) if reading.timestamp_ms > ts => {
+                let secs = (reading.timestamp_ms - ts) as f64 / 1000.0;
+                (
+                    reading.total_requests.saturating_sub(requests) as f64 / secs,
+                    reading.checkpoints_committed.saturating_sub(checkpoints) as f64 * 60.0 / secs,
+                )
+            }
+            _ => (0.0, 0.0),
+        };
+        inner.last = Some((
+            reading.timestamp_ms,
+            reading.total_requests,
+            reading.checkpoints_committed,
+        ));
+
+        let sample = MetricsSample {
+            timestamp_ms: reading.timestamp_ms,
+            total_workers: reading.total_workers,
+            active_workers: reading.active_workers,
+            requests_per_second,
+            checkpoints_per_minute,
+            datasets: reading
+                .epochs
+                .into_iter()
+                .map(|(id, (epoch, progress))| (id, DatasetProgressSample { epoch, progress }))
+                .collect(),
+        };
+
+        if inner.samples.len() >= self.capacity {
+            inner.samples.pop_front();
+        }
+        inner.samples.push_back(sample.clone());
+        sample
+    }
+
+    /// Samples taken at or after `since_ms`, oldest first
+    pub fn since(&self, since_ms: i64) -> Vec<MetricsSample> {
+        let inner = self.inner.lock();
+        let start = inner.samples.partition_point(|s| s.timestamp_ms < since_ms);
+        inner.samples.range(start..).cloned().collect()
+    }
+
+    /// Number of samples kept
+    pub fn len(&self) -> usize {
+        self.inner.lock().samples.len()
+    }
+
+    /// Whether no samples were recorded yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Parse a history range such as `30m`, `1h` or `7d`
+///
+/// A bare number is taken as seconds.
+pub fn parse_range(range: &str) -> Result<Duration, String> {
+    let range = range.trim();
+    let split = range
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(range.len());
+    let (value, unit) = range.split_at(split);
+    let value: u64 = value
+        .parse()
+        .map_err(|_| format!("invalid range: {:?}", range))?;
+    let unit_secs = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(format!("invalid range unit: {:?}", unit)),
+    };
+    Ok(Duration::from_secs(value.saturating_mul(unit_secs)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(
+        timestamp_ms: i64,
+        total_requests: u64,
+        checkpoints_committed: u64,
+    ) -> MetricsReading {
+        MetricsReading {
+            timestamp_ms,
+            total_requests,
+            checkpoints_committed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_rates_and_capacity() {
+        let history = MetricsHistory::new(2);
+        let first = history.record(reading(0, 100, 1));
+        assert_eq!(first.requests_per_second, 0.0);
+
+        let second = history.record(reading(10_000, 300, 3));
+        assert_eq!(second.requests_per_second, 20.0);
+        assert_eq!(second.checkpoints_per_minute, 12.0);
+
+        history.record(reading(20_000, 300, 3));
+        assert_eq!(history.len(), 2);
+        let samples = history.since(15_000);
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].requests_per_second, 0.0);
+        assert_eq!(history.since(0)[0].timestamp_ms, 10_000);
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_range("15m"), Ok(Duration::from_secs(900)));
+        assert_eq!(parse_range("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_range("7d"), Ok(Duration::from_secs(7 * 86400)));
+        assert!(parse_range("h").is_err());
+        assert!(parse_range("1w").is_err());
+    }
+}
@@ -1,16 +1,28 @@
 //! Security middleware for the coordinator service
 //!
-//! Provides rate limiting, input validation, and request logging.
+//! Provides rate limiting, admission control, input validation, and request
+//! logging.
 
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 
 use dashmap::DashMap;
 use parking_lot::RwLock;
-use tonic::Status;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tonic::body::BoxBody;
+use tonic::{Code, Status};
+use tower::{Layer, Service};
 use tracing::debug;
 
+use runtime_core::clock::SystemClock;
+use runtime_core::config::HttpRateLimitConfig;
+use runtime_core::{DatasetId, SharedClock, WorkerId};
+
 /// Rate limiter using token bucket algorithm
 pub struct RateLimiter {
     /// Requests per second limit
@@ -23,6 +35,8 @@ pub struct RateLimiter {
     cleanup_interval: Duration,
     /// Last cleanup time
     last_cleanup: RwLock<Instant>,
+    /// Time source for refills
+    clock: SharedClock,
 }
 
 impl RateLimiter {
@@ -38,16 +52,24 @@ impl RateLimiter {
             buckets: DashMap::new(),
             cleanup_interval: Duration::from_secs(60),
             last_cleanup: RwLock::new(Instant::now()),
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.last_cleanup = RwLock::new(clock.now());
+        self.clock = clock;
+        self
+    }
+
     /// Check if a request should be allowed
     ///
     /// Returns Ok(()) if allowed, Err with retry-after duration if rate limited
     pub fn check(&self, client_id: &str) -> Result<(), Duration> {
         self.maybe_cleanup();
 
-        let now = Instant::now();
+        let now = self.clock.now();
 
         let entry = self
             .buckets
@@ -86,7 +108,7 @@ impl RateLimiter {
 
     /// Cleanup old entries
     fn maybe_cleanup(&self) {
-        let now = Instant::now();
+        let now = self.clock.now();
         let should_cleanup = {
             let last = self.last_cleanup.read();
             now.duration_since(*last) > self.cleanup_interval
@@ -110,20 +132,116 @@ impl RateLimiter {
     }
 }
 
+/// Per-route rate limits for the HTTP API
+///
+/// Every route prefix has its own token buckets per client. The longest
+/// matching prefix applies and other paths share the default limit. A limit
+/// of 0 requests per second disables limiting.
+pub struct HttpRateLimiter {
+    rules: RwLock<RateLimitRules>,
+    clock: SharedClock,
+}
+
+#[derive(Default)]
+struct RateLimitRules {
+    default: Option<RateLimiter>,
+    /// Route limits, longest prefix first
+    routes: Vec<(String, Option<RateLimiter>)>,
+}
+
+impl HttpRateLimiter {
+    /// Create a limiter with a default limit for all routes
+    pub fn new(rate: u64, burst: u64) -> Self {
+        Self {
+            rules: RwLock::new(RateLimitRules {
+                default: limiter(rate, burst, SystemClock::shared()),
+                routes: Vec::new(),
+            }),
+            clock: SystemClock::shared(),
+        }
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        let rules = self.rules.get_mut();
+        let limiters = std::iter::once(&mut rules.default)
+            .chain(rules.routes.iter_mut().map(|(_, limiter)| limiter));
+        for limiter in limiters.flatten() {
+            limiter.clock = clock.clone();
+            *limiter.last_cleanup.get_mut() = clock.now();
+        }
+        self.clock = clock;
+        self
+    }
+
+    /// Set the limit for routes under a path prefix
+    pub fn with_route(mut self, prefix: &str, rate: u64, burst: u64) -> Self {
+        let routes = &mut self.rules.get_mut().routes;
+        routes.retain(|(p, _)| p != prefix);
+        routes.push((prefix.to_string(), limiter(rate, burst, self.clock.clone())));
+        routes.sort_by_key(|(p, _)| std::cmp::Reverse(p.len()));
+        self
+    }
+
+    /// Create a limiter from the HTTP rate limit configuration
+    ///
+    /// A disabled configuration lets every request through.
+    pub fn from_config(config: &HttpRateLimitConfig) -> Self {
+        if !config.enabled {
+            return Self {
+                rules: RwLock::default(),
+                clock: SystemClock::shared(),
+            };
+        }
+        config.routes.iter().fold(
+            Self::new(config.requests_per_second, config.burst),
+            |limiter, route| {
+                limiter.with_route(&route.path_prefix, route.requests_per_second, route.burst)
+            },
+        )
+    }
+
+    /// Replace the limits with a new configuration
+    ///
+    /// Clients start over with full buckets.
+    pub fn reconfigure(&self, config: &HttpRateLimitConfig) {
+        *self.rules.write() = Self::from_config(config)
+            .with_clock(self.clock.clone())
+            .rules
+            .into_inner();
+    }
+
+    /// Check a request from a client to a path
+    ///
+    /// Returns the time to wait before retrying if the client is over its limit.
+    pub fn check(&self, path: &str, client_id: &str) -> Result<(), Duration> {
+        let rules = self.rules.read();
+        let limiter = rules
+            .routes
+            .iter()
+            .find(|(prefix, _)| path.starts_with(prefix.as_str()))
+            .map_or(&rules.default, |(_, limiter)| limiter);
+        match limiter {
+            Some(limiter) => limiter.check(client_id),
+            None => Ok(()),
+        }
+    }
+}
+
+fn limiter(rate: u64, burst: u64, clock: SharedClock) -> Option<RateLimiter> {
+    (rate > 0).then(|| RateLimiter::new(rate, burst.max(1)).with_clock(clock))
+}
+
 /// Input validator for coordinator requests
 pub struct InputValidator {
-    /// Maximum worker ID length
-    max_worker_id_len: usize,
-    /// Maximum dataset ID length
-    max_dataset_id_len: usize,
     /// Maximum path length
     max_path_len: usize,
     /// Maximum metadata entries
     max_metadata_entries: usize,
     /// Maximum metadata value length
     max_metadata_value_len: usize,
-    /// Allowed characters pattern for IDs
-    id_pattern: regex::Regex,
+    /// Maximum number of shards per dataset
+    max_shards: u64,
 }
 
 impl Default for InputValidator {
@@ -136,58 +254,21 @@ impl InputValidator {
     /// Create a new input validator with default settings
     pub fn new() -> Self {
         Self {
-            max_worker_id_len: 128,
-            max_dataset_id_len: 256,
             max_path_len: 4096,
             max_metadata_entries: 64,
             max_metadata_value_len: 1024,
-            // Allow alphanumeric, hyphens, underscores, and dots
-            id_pattern: regex::Regex::new(r"^[a-zA-Z0-9_\-\.]+$").unwrap(),
+            max_shards: 1_000_000,
         }
     }
 
     /// Validate a worker ID
-    pub fn validate_worker_id(&self, id: &str) -> Result<(), Status> {
-        if id.is_empty() {
-            return Err(Status::invalid_argument("Worker ID cannot be empty"));
-        }
-
-        if id.len() > self.max_worker_id_len {
-            return Err(Status::invalid_argument(format!(
-                "Worker ID exceeds maximum length of {} characters",
-                self.max_worker_id_len
-            )));
-        }
-
-        if !self.id_pattern.is_match(id) {
-            return Err(Status::invalid_argument(
-                "Worker ID contains invalid characters. Only alphanumeric, hyphens, underscores, and dots are allowed"
-            ));
-        }
-
-        Ok(())
+    pub fn validate_worker_id(&self, id: &str) -> Result<WorkerId, Status> {
+        id.parse().map_err(Status::from)
     }
 
     /// Validate a dataset ID
-    pub fn validate_dataset_id(&self, id: &str) -> Result<(), Status> {
-        if id.is_empty() {
-            return Err(Status::invalid_argument("Dataset ID cannot be empty"));
-        }
-
-        if id.len() > self.max_dataset_id_len {
-            return Err(Status::invalid_argument(format!(
-                "Dataset ID exceeds maximum length of {} characters",
-                self.max_dataset_id_len
-            )));
-        }
-
-        if !self.id_pattern.is_match(id) {
-            return Err(Status::invalid_argument(
-                "Dataset ID contains invalid characters",
-            ));
-        }
-
-        Ok(())
+    pub fn validate_dataset_id(&self, id: &str) -> Result<DatasetId, Status> {
+        id.parse().map_err(Status::from)
     }
 
     /// Validate a file path
@@ -214,6 +295,58 @@ impl InputValidator {
         Ok(())
     }
 
+    /// Validate a dataset location: a local path or a `scheme://` URI
+    pub fn validate_dataset_path(&self, path: &str) -> Result<(), Status> {
+        if path.trim().is_empty() {
+            return Err(Status::invalid_argument("Dataset path cannot be empty"));
+        }
+        if path.trim() != path {
+            return Err(Status::invalid_argument(
+                "Dataset path cannot have leading or trailing whitespace",
+            ));
+        }
+
+        if let Some((scheme, rest)) = path.split_once("://") {
+            let valid_scheme = !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+            if !valid_scheme || rest.is_empty() {
+                return Err(Status::invalid_argument(format!(
+                    "Invalid dataset URI: {}",
+                    path
+                )));
+            }
+        }
+
+        self.validate_path(path)
+    }
+
+    /// Validate sample and shard sizes, returning the resulting shard count
+    ///
+    /// A shard size larger than the dataset yields a single shard.
+    pub fn validate_shard_layout(
+        &self,
+        total_samples: i64,
+        shard_size: i64,
+    ) -> Result<u64, Status> {
+        if total_samples <= 0 {
+            return Err(Status::invalid_argument("total_samples must be positive"));
+        }
+        if shard_size <= 0 {
+            return Err(Status::invalid_argument("shard_size must be positive"));
+        }
+
+        let shards = (total_samples as u64).div_ceil(shard_size as u64);
+        if shards > self.max_shards {
+            return Err(Status::invalid_argument(format!(
+                "Dataset would have {} shards, maximum is {}",
+                shards, self.max_shards
+            )));
+        }
+        Ok(shards)
+    }
+
     /// Validate metadata map
     pub fn validate_metadata(&self, metadata: &HashMap<String, String>) -> Result<(), Status> {
         if metadata.len() > self.max_metadata_entries {
@@ -267,6 +400,8 @@ pub struct RequestMetrics {
     errors: DashMap<String, AtomicU64>,
     /// Latency samples (method -> recent latencies in microseconds)
     latencies: DashMap<String, Vec<u64>>,
+    /// Completed requests and their summed latency in microseconds, by method
+    latency_totals: DashMap<String, (u64, u64)>,
     /// Max latency samples to keep
     max_samples: usize,
 }
@@ -284,6 +419,7 @@ impl RequestMetrics {
             requests: DashMap::new(),
             errors: DashMap::new(),
             latencies: DashMap::new(),
+            latency_totals: DashMap::new(),
             max_samples: 1000,
         }
     }
@@ -311,6 +447,28 @@ impl RequestMetrics {
             entry.remove(0);
         }
         entry.push(latency_us);
+        drop(entry);
+
+        let mut totals = self.latency_totals.entry(method.to_string()).or_default();
+        totals.0 += 1;
+        totals.1 += latency_us;
+    }
+
+    /// Methods that received at least one request, sorted by name
+    pub fn methods(&self) -> Vec<String> {
+        let mut methods: Vec<_> = self.requests.iter().map(|e| e.key().clone()).collect();
+        methods.sort();
+        methods
+    }
+
+    /// Completed requests and their summed latency in microseconds for a method
+    ///
+    /// Unlike the percentiles these cover every request, not just recent ones.
+    pub fn latency_totals(&self, method: &str) -> (u64, u64) {
+        self.latency_totals
+            .get(method)
+            .map(|t| *t)
+            .unwrap_or_default()
     }
 
     /// Get request count for a method
@@ -329,15 +487,28 @@ impl RequestMetrics {
             .unwrap_or(0)
     }
 
+    /// Get total request count across all methods
+    pub fn total_requests(&self) -> u64 {
+        self.requests
+            .iter()
+            .map(|v| v.value().load(Ordering::Relaxed))
+            .sum()
+    }
+
     /// Get p99 latency for a method in microseconds
     pub fn get_p99_latency(&self, method: &str) -> Option<u64> {
+        self.get_latency_percentile(method, 0.99)
+    }
+
+    /// Get a latency percentile (0.0..=1.0) for a method in microseconds
+    pub fn get_latency_percentile(&self, method: &str, percentile: f64) -> Option<u64> {
         self.latencies.get(method).and_then(|samples| {
             if samples.is_empty() {
                 return None;
             }
             let mut sorted: Vec<_> = samples.iter().copied().collect();
             sorted.sort_unstable();
-            let idx = (sorted.len() as f64 * 0.99) as usize;
+            let idx = (sorted.len() as f64 * percentile) as usize;
             sorted.get(idx.min(sorted.len() - 1)).copied()
         })
     }
@@ -358,9 +529,274 @@ impl RequestMetrics {
     }
 }
 
+/// Tower layer recording request count, errors and latency for every gRPC call
+#[derive(Clone)]
+pub struct MetricsLayer {
+    metrics: Arc<RequestMetrics>,
+}
+
+impl MetricsLayer {
+    /// Create a layer that records into the given metrics collector
+    pub fn new(metrics: Arc<RequestMetrics>) -> Self {
+        Self { metrics }
+    }
+}
+
+impl<S> Layer<S> for MetricsLayer {
+    type Service = MetricsService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        MetricsService {
+            inner,
+            metrics: self.metrics.clone(),
+        }
+    }
+}
+
+/// Service produced by [`MetricsLayer`]
+#[derive(Clone)]
+pub struct MetricsService<S> {
+    inner: S,
+    metrics: Arc<RequestMetrics>,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for MetricsService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let method = rpc_method(req.uri().path()).to_string();
+        let metrics = self.metrics.clone();
+        let start = Instant::now();
+        metrics.record_request(&method);
+
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let result = future.await;
+            metrics.record_latency(&method, start.elapsed().as_micros() as u64);
+
+            // Handler errors are sent as trailers-only responses, so the
+            // status is visible in the headers
+            let failed = match &result {
+                Ok(response) => response
+                    .headers()
+                    .get("grpc-status")
+                    .is_some_and(|code| Code::from_bytes(code.as_bytes()) != Code::Ok),
+                Err(_) => true,
+            };
+            if failed {
+                metrics.record_error(&method);
+            }
+
+            result
+        })
+    }
+}
+
+/// Limits on concurrent in-flight requests per RPC method
+#[derive(Debug, Clone)]
+pub struct AdmissionConfig {
+    /// Maximum in-flight requests for methods without an override (0 = unlimited)
+    pub default_max_in_flight: usize,
+    /// Per-method overrides keyed by RPC method name (0 = unlimited)
+    pub per_method: HashMap<String, usize>,
+    /// Backoff hint returned to rejected clients
+    pub retry_after: Duration,
+}
+
+impl Default for AdmissionConfig {
+    fn default() -> Self {
+        let per_method = [
+            // Barrier waiters are parked until every worker arrives, so a
+            // limit here would deadlock large clusters
+            ("WaitBarrier", 0),
+            // Rendezvous long-polls park every rank until rank 0 publishes
+            // its address, for the same reason
+            ("KvWait", 0),
+            // Probes must keep working while the coordinator sheds load
+            ("Check", 0),
+            ("RegisterWorker", 256),
+        ]
+        .into_iter()
+        .map(|(method, limit)| (method.to_string(), limit))
+        .collect();
+
+        Self {
+            default_max_in_flight: 1024,
+            per_method,
+            retry_after: Duration::from_millis(500),
+        }
+    }
+}
+
+impl AdmissionConfig {
+    /// In-flight limit for a method, `None` if unlimited
+    pub fn limit_for(&self, method: &str) -> Option<usize> {
+        let limit = self
+            .per_method
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_max_in_flight);
+        (limit > 0).then_some(limit)
+    }
+}
+
+/// Per-method concurrency limiter shared by all connections
+pub struct AdmissionControl {
+    config: AdmissionConfig,
+    /// Available slots per method
+    slots: DashMap<String, Arc<Semaphore>>,
+    /// Rejected requests by method
+    rejected: DashMap<String, AtomicU64>,
+}
+
+impl AdmissionControl {
+    /// Create a new admission controller
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self {
+            config,
+            slots: DashMap::new(),
+            rejected: DashMap::new(),
+        }
+    }
+
+    /// Try to admit a request
+    ///
+    /// Returns the permit to hold while the request is in flight (`None` for
+    /// unlimited methods), or the retry-after hint if the method is saturated.
+    pub fn try_admit(&self, method: &str) -> Result<Option<OwnedSemaphorePermit>, Duration> {
+        let Some(limit) = self.config.limit_for(method) else {
+            return Ok(None);
+        };
+
+        let slots = self
+            .slots
+            .entry(method.to_string())
+            .or_insert_with(|| Arc::new(Semaphore::new(limit)))
+            .clone();
+
+        match slots.try_acquire_owned() {
+            Ok(permit) => Ok(Some(permit)),
+            Err(_) => {
+                self.rejected
+                    .entry(method.to_string())
+                    .or_insert_with(|| AtomicU64::new(0))
+                    .fetch_add(1, Ordering::Relaxed);
+                Err(self.config.retry_after)
+            }
+        }
+    }
+
+    /// Get rejected request count for a method
+    pub fn rejected_count(&self, method: &str) -> u64 {
+        self.rejected
+            .get(method)
+            .map(|v| v.load(Ordering::Relaxed))
+            .unwrap_or(0)
+    }
+}
+
+/// Tower layer shedding gRPC calls once a method's in-flight limit is reached
+#[derive(Clone)]
+pub struct AdmissionLayer {
+    admission: Arc<AdmissionControl>,
+}
+
+impl AdmissionLayer {
+    /// Create a layer backed by the given admission controller
+    pub fn new(admission: Arc<AdmissionControl>) -> Self {
+        Self { admission }
+    }
+}
+
+impl<S> Layer<S> for AdmissionLayer {
+    type Service = AdmissionService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        AdmissionService {
+            inner,
+            admission: self.admission.clone(),
+        }
+    }
+}
+
+/// Service produced by [`AdmissionLayer`]
+#[derive(Clone)]
+pub struct AdmissionService<S> {
+    inner: S,
+    admission: Arc<AdmissionControl>,
+}
+
+impl<S, ReqBody> Service<http::Request<ReqBody>> for AdmissionService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<BoxBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: http::Request<ReqBody>) -> Self::Future {
+        let method = rpc_method(req.uri().path());
+
+        let permit = match self.admission.try_admit(method) {
+            Ok(permit) => permit,
+            Err(retry_after) => {
+                debug!(method = %method, "Shedding request, too many in flight");
+                let response = overloaded_status(method, retry_after).into_http();
+                return Box::pin(async move { Ok(response) });
+            }
+        };
+
+        let future = self.inner.call(req);
+        Box::pin(async move {
+            let result = future.await;
+            drop(permit);
+            result
+        })
+    }
+}
+
+/// RESOURCE_EXHAUSTED status carrying retry hints
+fn overloaded_status(method: &str, retry_after: Duration) -> Status {
+    let mut status = Status::resource_exhausted(format!(
+        "Coordinator overloaded: too many in-flight {} requests, retry after {}ms",
+        method,
+        retry_after.as_millis()
+    ));
+    // Standard gRPC retry pushback hint, honoured by clients with retry policies
+    if let Ok(value) = retry_after.as_millis().to_string().parse() {
+        status
+            .metadata_mut()
+            .insert("grpc-retry-pushback-ms", value);
+    }
+    status
+}
+
+/// Extract the RPC method name from a gRPC request path
+///
+/// `/coordinator.Coordinator/WaitBarrier` becomes `WaitBarrier`.
+pub fn rpc_method(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use runtime_core::clock::MockClock;
 
     #[test]
     fn test_rate_limiter_allows_burst() {
@@ -375,6 +811,26 @@ mod tests {
         assert!(limiter.check("client-1").is_err());
     }
 
+    #[test]
+    fn test_rate_limiter_refills() {
+        let clock = Arc::new(MockClock::new());
+        let limiter = RateLimiter::new(10, 2).with_clock(clock.clone());
+
+        assert!(limiter.check("client-1").is_ok());
+        assert!(limiter.check("client-1").is_ok());
+        assert!(limiter.check("client-1").is_err());
+
+        clock.advance(Duration::from_millis(100));
+        assert!(limiter.check("client-1").is_ok());
+        assert!(limiter.check("client-1").is_err());
+
+        // Never refills past the burst
+        clock.advance(Duration::from_secs(10));
+        assert!(limiter.check("client-1").is_ok());
+        assert!(limiter.check("client-1").is_ok());
+        assert!(limiter.check("client-1").is_err());
+    }
+
     #[test]
     fn test_rate_limiter_different_clients() {
         let limiter = RateLimiter::new(10, 2);
@@ -388,6 +844,38 @@ mod tests {
         assert!(limiter.check("client-2").is_ok());
     }
 
+    #[test]
+    fn test_http_rate_limiter_routes() {
+        let limiter = HttpRateLimiter::new(1, 2)
+            .with_route("/api/health", 0, 0)
+            .with_route("/api", 1, 1)
+            .with_route("/api/stream", 1, 3);
+
+        // Longest prefix wins, and each route has its own buckets
+        for _ in 0..3 {
+            assert!(limiter.check("/api/stream", "client-1").is_ok());
+        }
+        assert!(limiter.check("/api/stream", "client-1").is_err());
+        assert!(limiter.check("/api/workers", "client-1").is_ok());
+        assert!(limiter.check("/api/workers", "client-1").is_err());
+        assert!(limiter.check("/api/workers", "client-2").is_ok());
+
+        // Unlimited routes and the default limit
+        for _ in 0..10 {
+            assert!(limiter.check("/api/health", "client-1").is_ok());
+        }
+        assert!(limiter.check("/metrics", "client-1").is_ok());
+        assert!(limiter.check("/metrics", "client-1").is_ok());
+        assert!(limiter.check("/metrics", "client-1").is_err());
+
+        // Disabling the limits lets everything through
+        limiter.reconfigure(&HttpRateLimitConfig {
+            enabled: false,
+            ..Default::default()
+        });
+        assert!(limiter.check("/metrics", "client-1").is_ok());
+    }
+
     #[test]
     fn test_input_validator_worker_id() {
         let validator = InputValidator::new();
@@ -412,6 +900,26 @@ mod tests {
         assert!(validator.validate_path("/data/file\0.txt").is_err());
     }
 
+    #[test]
+    fn test_input_validator_dataset() {
+        let validator = InputValidator::new();
+
+        assert!(validator.validate_dataset_path("/data/mnist").is_ok());
+        assert!(validator
+            .validate_dataset_path("s3://bucket/imagenet")
+            .is_ok());
+        assert!(validator.validate_dataset_path("").is_err());
+        assert!(validator.validate_dataset_path("://bucket").is_err());
+        assert!(validator.validate_dataset_path("s3://").is_err());
+
+        assert_eq!(validator.validate_shard_layout(1000, 100).unwrap(), 10);
+        assert_eq!(validator.validate_shard_layout(1001, 100).unwrap(), 11);
+        assert!(validator.validate_shard_layout(1000, 0).is_err());
+        assert!(validator.validate_shard_layout(-1, 10).is_err());
+        assert_eq!(validator.validate_shard_layout(10, 100).unwrap(), 1);
+        assert!(validator.validate_shard_layout(10_000_000, 1).is_err());
+    }
+
     #[test]
     fn test_request_metrics() {
         let metrics = RequestMetrics::new();
@@ -425,5 +933,76 @@ mod tests {
         assert_eq!(metrics.get_request_count("register_worker"), 2);
         assert_eq!(metrics.get_error_count("register_worker"), 1);
         assert!(metrics.get_p99_latency("register_worker").is_some());
+        assert_eq!(metrics.latency_totals("register_worker"), (2, 3000));
+        assert_eq!(metrics.methods(), vec!["register_worker".to_string()]);
+        assert_eq!(metrics.total_requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_layer_records_calls() {
+        let metrics = Arc::new(RequestMetrics::new());
+        let mut service = MetricsLayer::new(metrics.clone()).layer(tower::service_fn(
+            |req: http::Request<()>| async move {
+                let mut response = http::Response::new(());
+                if req.uri().path().ends_with("WaitBarrier") {
+                    response
+                        .headers_mut()
+                        .insert("grpc-status", http::HeaderValue::from_static("4"));
+                }
+                Ok::<_, std::convert::Infallible>(response)
+            },
+        ));
+
+        for path in [
+            "/coordinator.Coordinator/Heartbeat",
+            "/coordinator.Coordinator/WaitBarrier",
+        ] {
+            let req = http::Request::builder().uri(path).body(()).unwrap();
+            service.call(req).await.unwrap();
+        }
+
+        assert_eq!(metrics.get_request_count("Heartbeat"), 1);
+        assert_eq!(metrics.get_error_count("Heartbeat"), 0);
+        assert_eq!(metrics.get_error_count("WaitBarrier"), 1);
+        assert!(metrics.get_p99_latency("WaitBarrier").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_admission_sheds_when_saturated() {
+        let config = AdmissionConfig {
+            default_max_in_flight: 1,
+            ..Default::default()
+        };
+        let admission = Arc::new(AdmissionControl::new(config));
+        let mut service = AdmissionLayer::new(admission.clone()).layer(tower::service_fn(
+            |_req: http::Request<()>| async move {
+                Ok::<_, std::convert::Infallible>(http::Response::new(tonic::body::empty_body()))
+            },
+        ));
+        let request = |method: &str| {
+            http::Request::builder()
+                .uri(format!("/coordinator.Coordinator/{}", method))
+                .body(())
+                .unwrap()
+        };
+
+        // The first call holds its slot until its future completes
+        let in_flight = service.call(request("Heartbeat"));
+        let rejected = service.call(request("Heartbeat")).await.unwrap();
+        let status = Status::from_header_map(rejected.headers()).unwrap();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status.metadata().get("grpc-retry-pushback-ms").unwrap(),
+            "500"
+        );
+        assert_eq!(admission.rejected_count("Heartbeat"), 1);
+
+        // Exempt methods are never shed
+        assert!(admission.try_admit("WaitBarrier").unwrap().is_none());
+        assert!(admission.try_admit("KvWait").unwrap().is_none());
+
+        in_flight.await.unwrap();
+        let admitted = service.call(request("Heartbeat")).await.unwrap();
+        assert!(admitted.headers().get("grpc-status").is_none());
     }
 }
@@ -0,0 +1,55 @@
+//! Wire protocol versioning
+//!
+//! Workers send their protocol version on registration and the coordinator
+//! answers with the version both sides will speak. Version history:
+//!
+//! - **1**: initial protocol (also assumed for clients that send no version)
+//! - **2**: `checkpoint_now` commands, batched shard assignments, loss in heartbeats
+//! - **3**: task control commands (`start_task`, `pause_task`, `resume_task`, `stop_task`)
+//! - **4**: `ShipLogs` RPC for sending worker logs to the coordinator
+//! - **5**: `drain` command telling a worker it is being taken out of rotation
+
+use tonic::Status;
+
+/// Protocol version spoken by this build
+pub const PROTOCOL_VERSION: u32 = 5;
+
+/// Oldest protocol version the coordinator still accepts
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Version assumed for clients that predate version negotiation
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// Negotiate the protocol version for a client
+///
+/// Newer clients are downgraded to the server version, older ones keep their
+/// own version as long as it is still supported.
+pub fn negotiate(client_version: u32) -> Result<u32, Status> {
+    let client_version = if client_version == 0 {
+        LEGACY_PROTOCOL_VERSION
+    } else {
+        client_version
+    };
+
+    if client_version < MIN_PROTOCOL_VERSION {
+        return Err(Status::failed_precondition(format!(
+            "Protocol version {} is no longer supported, minimum is {}",
+            client_version, MIN_PROTOCOL_VERSION
+        )));
+    }
+
+    Ok(client_version.min(PROTOCOL_VERSION))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(negotiate(0).unwrap(), LEGACY_PROTOCOL_VERSION);
+        assert_eq!(negotiate(1).unwrap(), 1);
+        assert_eq!(negotiate(PROTOCOL_VERSION).unwrap(), PROTOCOL_VERSION);
+        assert_eq!(negotiate(PROTOCOL_VERSION + 5).unwrap(), PROTOCOL_VERSION);
+    }
+}
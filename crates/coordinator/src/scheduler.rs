@@ -0,0 +1,203 @@
+//! Coordinator-driven checkpoint scheduling
+//!
+//! The scheduler follows the global training step reported in heartbeats and
+//! decides when the cluster should checkpoint according to the configured
+//! [`CheckpointStrategy`]. The service turns each decision into a
+//! `checkpoint_now` command for every worker.
+
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use tracing::debug;
+
+use runtime_core::config::CheckpointStrategy;
+
+/// Mutable scheduling state
+#[derive(Debug)]
+struct ScheduleState {
+    /// Highest step reported by any worker
+    global_step: u64,
+    /// Step of the last requested or committed checkpoint
+    last_step: u64,
+    /// When the last checkpoint was requested or committed
+    last_time: Instant,
+    /// Loss at the last checkpoint, used by the adaptive strategy
+    last_loss: Option<f64>,
+}
+
+/// Decides when the cluster should checkpoint
+#[derive(Debug)]
+pub struct CheckpointScheduler {
+    strategy: CheckpointStrategy,
+    state: Mutex<ScheduleState>,
+}
+
+impl Default for CheckpointScheduler {
+    fn default() -> Self {
+        Self::new(CheckpointStrategy::Manual)
+    }
+}
+
+impl CheckpointScheduler {
+    /// Create a scheduler for a strategy
+    pub fn new(strategy: CheckpointStrategy) -> Self {
+        Self {
+            strategy,
+            state: Mutex::new(ScheduleState {
+                global_step: 0,
+                last_step: 0,
+                last_time: Instant::now(),
+                last_loss: None,
+            }),
+        }
+    }
+
+    /// The configured strategy
+    pub fn strategy(&self) -> &CheckpointStrategy {
+        &self.strategy
+    }
+
+    /// Highest step reported so far
+    pub fn global_step(&self) -> u64 {
+        self.state.lock().global_step
+    }
+
+    /// Record a worker's reported step and training loss
+    ///
+    /// Returns the step to checkpoint at if a checkpoint is now due. The
+    /// schedule restarts from that step, so each decision is returned once.
+    pub fn observe(&self, step: u64, loss: Option<f64>) -> Option<u64> {
+        self.observe_at(step, loss, Instant::now())
+    }
+
+    fn observe_at(&self, step: u64, loss: Option<f64>, now: Instant) -> Option<u64> {
+        let mut state = self.state.lock();
+        state.global_step = state.global_step.max(step);
+
+        let global_step = state.global_step;
+        if global_step <= state.last_step {
+            return None;
+        }
+        let steps_since = global_step - state.last_step;
+        let loss = loss.filter(|l| l.is_finite());
+
+        let due = match &self.strategy {
+            CheckpointStrategy::Steps { interval } => *interval > 0 && steps_since >= *interval,
+            CheckpointStrategy::Time { interval } => {
+                !interval.is_zero() && now.duration_since(state.last_time) >= *interval
+            }
+            CheckpointStrategy::Adaptive {
+                min_steps,
+                max_steps,
+                loss_threshold,
+            } => {
+                if *max_steps > 0 && steps_since >= *max_steps {
+                    true
+                } else if steps_since < *min_steps {
+                    false
+                } else {
+                    match (state.last_loss, loss) {
+                        (Some(previous), Some(current)) => previous - current >= *loss_threshold,
+                        _ => false,
+                    }
+                }
+            }
+            CheckpointStrategy::Manual => false,
+        };
+
+        if state.last_loss.is_none() {
+            state.last_loss = loss;
+        }
+
+        if !due {
+            return None;
+        }
+
+        debug!(
+            global_step = global_step,
+            steps_since = steps_since,
+            "Checkpoint due"
+        );
+        state.last_step = global_step;
+        state.last_time = now;
+        if loss.is_some() {
+            state.last_loss = loss;
+        }
+        Some(global_step)
+    }
+
+    /// Restart the schedule after a checkpoint was committed
+    ///
+    /// Checkpoints taken by user code count towards the cadence too.
+    pub fn record_checkpoint(&self, step: u64) {
+        let mut state = self.state.lock();
+        if step > state.last_step {
+            state.last_step = step;
+            state.last_time = Instant::now();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_steps_strategy() {
+        let scheduler = CheckpointScheduler::new(CheckpointStrategy::Steps { interval: 100 });
+
+        assert_eq!(scheduler.observe(50, None), None);
+        assert_eq!(scheduler.observe(100, None), Some(100));
+        // Other workers reporting the same step do not trigger it again
+        assert_eq!(scheduler.observe(100, None), None);
+        assert_eq!(scheduler.observe(150, None), None);
+
+        // A user checkpoint restarts the cadence
+        scheduler.record_checkpoint(180);
+        assert_eq!(scheduler.observe(250, None), None);
+        assert_eq!(scheduler.observe(280, None), Some(280));
+    }
+
+    #[test]
+    fn test_time_strategy() {
+        let scheduler = CheckpointScheduler::new(CheckpointStrategy::Time {
+            interval: Duration::from_secs(60),
+        });
+        let start = Instant::now();
+
+        assert_eq!(scheduler.observe_at(10, None, start), None);
+        let later = start + Duration::from_secs(61);
+        assert_eq!(scheduler.observe_at(20, None, later), Some(20));
+        // No progress since the last checkpoint
+        assert_eq!(
+            scheduler.observe_at(20, None, later + Duration::from_secs(120)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_adaptive_strategy() {
+        let scheduler = CheckpointScheduler::new(CheckpointStrategy::Adaptive {
+            min_steps: 10,
+            max_steps: 100,
+            loss_threshold: 0.5,
+        });
+
+        assert_eq!(scheduler.observe(5, Some(3.0)), None);
+        // Improvement before min_steps is ignored
+        assert_eq!(scheduler.observe(8, Some(1.0)), None);
+        assert_eq!(scheduler.observe(12, Some(2.8)), None);
+        assert_eq!(scheduler.observe(15, Some(2.4)), Some(15));
+        // Without further improvement, max_steps forces a checkpoint
+        assert_eq!(scheduler.observe(60, Some(2.3)), None);
+        assert_eq!(scheduler.observe(115, Some(2.3)), Some(115));
+    }
+
+    #[test]
+    fn test_manual_strategy() {
+        let scheduler = CheckpointScheduler::default();
+        assert_eq!(scheduler.observe(1_000_000, Some(0.1)), None);
+        assert_eq!(scheduler.global_step(), 1_000_000);
+    }
+}
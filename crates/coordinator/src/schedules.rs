@@ -0,0 +1,677 @@
+//! Recurring training tasks
+//!
+//! A schedule creates a task from a template whenever its cron expression
+//! fires, e.g. a nightly fine-tune on the newest data snapshot. The coordinator
+//! checks schedules periodically and records every run in the schedule's
+//! history, including runs that could not start a task. Schedules are saved to
+//! a JSON file like tasks so they survive coordinator restarts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, TimeZone, Timelike, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use runtime_core::{id, DatasetId};
+
+/// How often the coordinator checks for due schedules
+pub const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Runs kept in each schedule's history
+pub const MAX_SCHEDULE_HISTORY: usize = 50;
+
+/// How far ahead to look for the next matching minute
+const MAX_LOOKAHEAD_DAYS: u64 = 366 * 5;
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A five-field cron expression, evaluated in UTC
+///
+/// Fields are minute, hour, day of month, month and day of week. Each accepts
+/// `*`, values, ranges, lists and `/step`; months and weekdays may also be
+/// given by three-letter name. `@hourly`, `@daily`, `@weekly` and `@monthly`
+/// are shorthands. As in cron, when both day fields are restricted a day
+/// matches if either of them does.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronSchedule {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+impl CronSchedule {
+    /// First matching minute strictly after `after`
+    ///
+    /// Returns `None` if the expression never matches, e.g. `0 0 30 2 *`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut t = after.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let limit = t.checked_add_days(Days::new(MAX_LOOKAHEAD_DAYS))?;
+
+        while t < limit {
+            let date = t.date_naive();
+            if !bit(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = midnight(NaiveDate::from_ymd_opt(year, month, 1)?);
+            } else if !self.day_matches(date) {
+                t = midnight(date.checked_add_days(Days::new(1))?);
+            } else if !bit(self.hours, t.hour()) {
+                t = t.with_minute(0)? + chrono::Duration::hours(1);
+            } else if !bit(self.minutes, t.minute()) {
+                t += chrono::Duration::minutes(1);
+            } else {
+                return Some(t);
+            }
+        }
+        None
+    }
+
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let day = bit(self.days, date.day());
+        let weekday = bit(self.weekdays, date.weekday().num_days_from_sunday());
+        if self.days_restricted && self.weekdays_restricted {
+            day || weekday
+        } else {
+            day && weekday
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expr = s.trim();
+        let expanded = match expr {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            return Err(format!(
+                "cron expression {:?} must have 5 fields: minute hour day month weekday",
+                expr
+            ));
+        };
+
+        // Both 0 and 7 are Sunday
+        let mut weekdays = parse_field(weekday, 0, 7, &WEEKDAY_NAMES)?;
+        if weekdays & (1 << 7) != 0 {
+            weekdays = (weekdays & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            expr: expr.to_string(),
+            minutes: parse_field(minute, 0, 59, &[])?,
+            hours: parse_field(hour, 0, 23, &[])?,
+            days: parse_field(day, 1, 31, &[])?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES)?,
+            weekdays,
+            days_restricted: !day.starts_with('*'),
+            weekdays_restricted: !weekday.starts_with('*'),
+        })
+    }
+}
+
+impl TryFrom<String> for CronSchedule {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<CronSchedule> for String {
+    fn from(cron: CronSchedule) -> Self {
+        cron.expr
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+/// Parse one cron field into a bitmask of the values it matches
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let invalid = || format!("invalid cron field {:?}", field);
+    let value = |s: &str| -> Result<u32, String> {
+        let v = match names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            Some(i) => min + i as u32,
+            None => s.parse().map_err(|_| invalid())?,
+        };
+        if (min..=max).contains(&v) {
+            Ok(v)
+        } else {
+            Err(format!(
+                "cron value {} in {:?} is outside {}-{}",
+                v, field, min, max
+            ))
+        }
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<usize>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(invalid()),
+            },
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((lo, hi)) = range.split_once('-') {
+            (value(lo)?, value(hi)?)
+        } else {
+            // `5/15` runs from 5 to the end of the range
+            let v = value(range)?;
+            (v, if step > 1 { max } else { v })
+        };
+        if lo > hi {
+            return Err(invalid());
+        }
+        for v in (lo..=hi).step_by(step) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+fn bit(mask: u64, value: u32) -> bool {
+    mask & (1 << value) != 0
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_time(chrono::NaiveTime::MIN))
+}
+
+/// Parameters of the tasks a schedule creates
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct TaskTemplate {
+    /// Display name of created tasks
+    pub name: String,
+    /// Free-form task type
+    #[serde(rename = "type")]
+    pub task_type: String,
+    /// Dataset to train on; a trailing `*` picks the greatest matching ID,
+    /// e.g. the newest of date-stamped snapshots
+    pub dataset_id: String,
+    /// Idle workers assigned to each run
+    pub worker_count: usize,
+    /// Number of epochs to train, 0 trains for a single epoch
+    #[serde(default)]
+    pub epochs: u64,
+    /// User configuration passed through to workers
+    #[serde(default)]
+    pub config: HashMap<String, serde_json::Value>,
+}
+
+impl TaskTemplate {
+    /// Pick the dataset a run trains on from the registered ones
+    pub fn resolve_dataset<'a>(
+        &self,
+        datasets: impl IntoIterator<Item = &'a DatasetId>,
+    ) -> Option<DatasetId> {
+        match self.dataset_id.strip_suffix('*') {
+            Some(prefix) => datasets
+                .into_iter()
+                .filter(|id| id.as_str().starts_with(prefix))
+                .max()
+                .cloned(),
+            None => datasets
+                .into_iter()
+                .find(|id| **id == self.dataset_id)
+                .cloned(),
+        }
+    }
+}
+
+/// A run of a schedule
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ScheduleRun {
+    /// When the run was due (ms since epoch)
+    pub scheduled_at_ms: i64,
+    /// When the coordinator started it (ms since epoch)
+    pub started_at_ms: i64,
+    /// Whether the run was requested through the API
+    pub manual: bool,
+    /// Dataset the run resolved to
+    #[schema(value_type = Option<String>)]
+    pub dataset_id: Option<DatasetId>,
+    /// Task created by the run
+    pub task_id: Option<String>,
+    /// Why no task was created
+    pub error: Option<String>,
+}
+
+/// Parameters for a new schedule
+#[derive(Debug, Clone)]
+pub struct ScheduleSpec {
+    /// Display name
+    pub name: String,
+    /// When to create tasks
+    pub cron: CronSchedule,
+    /// Tasks to create
+    pub template: TaskTemplate,
+    /// Whether the schedule fires
+    pub enabled: bool,
+    /// Start runs while the previous run's task is still active
+    pub allow_overlap: bool,
+}
+
+/// A recurring task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct Schedule {
+    /// Unique schedule identifier
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// When to create tasks, a cron expression in UTC
+    #[schema(value_type = String, example = "0 2 * * *")]
+    pub cron: CronSchedule,
+    /// Tasks to create
+    pub template: TaskTemplate,
+    /// Whether the schedule fires
+    pub enabled: bool,
+    /// Start runs while the previous run's task is still active
+    pub allow_overlap: bool,
+    /// When the schedule was created (ms since epoch)
+    pub created_at_ms: i64,
+    /// When the schedule fires next (ms since epoch), unset while disabled
+    pub next_run_ms: Option<i64>,
+    /// Most recent runs, oldest first
+    pub history: Vec<ScheduleRun>,
+}
+
+impl Schedule {
+    /// Task of the most recent run that created one
+    pub fn last_task_id(&self) -> Option<&str> {
+        self.history.iter().rev().find_map(|r| r.task_id.as_deref())
+    }
+
+    fn plan_next(&mut self, now: DateTime<Utc>) {
+        self.next_run_ms = if self.enabled {
+            self.cron.next_after(now).map(|t| t.timestamp_millis())
+        } else {
+            None
+        };
+    }
+}
+
+/// Changes to a schedule; unset fields are left as they are
+#[derive(Debug, Clone, Default)]
+pub struct ScheduleUpdate {
+    pub cron: Option<CronSchedule>,
+    pub enabled: Option<bool>,
+    pub allow_overlap: Option<bool>,
+}
+
+/// Registry of schedules
+#[derive(Debug, Default)]
+pub struct ScheduleManager {
+    schedules: Mutex<HashMap<String, Schedule>>,
+    /// File the schedules are saved to after every change
+    store: Option<Arc<Store>>,
+}
+
+impl ScheduleManager {
+    /// Create an in-memory schedule manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a schedule manager saved to `path`, loading any schedules already there
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let schedules = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Vec<Schedule>>(&bytes)
+                .map_err(io::Error::from)?
+                .into_iter()
+                .map(|s| (s.id.clone(), s))
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            schedules: Mutex::new(schedules),
+            store: Some(Arc::new(Store {
+                path,
+                changes: AtomicU64::new(0),
+                saved: Mutex::new(0),
+            })),
+        })
+    }
+
+    /// Create a schedule, planning its first run
+    pub fn create(&self, spec: ScheduleSpec) -> Schedule {
+        let now = Utc::now();
+        let id = id::next_id("sched");
+        let mut schedule = Schedule {
+            id: id.clone(),
+            name: spec.name,
+            cron: spec.cron,
+            template: spec.template,
+            enabled: spec.enabled,
+            allow_overlap: spec.allow_overlap,
+            created_at_ms: now.timestamp_millis(),
+            next_run_ms: None,
+            history: Vec::new(),
+        };
+        schedule.plan_next(now);
+
+        self.update_all(|schedules| {
+            schedules.insert(id, schedule.clone());
+        });
+        schedule
+    }
+
+    /// Get a schedule by ID
+    pub fn get(&self, schedule_id: &str) -> Option<Schedule> {
+        self.schedules.lock().get(schedule_id).cloned()
+    }
+
+    /// All schedules, newest first
+    pub fn list(&self) -> Vec<Schedule> {
+        let mut schedules: Vec<Schedule> = self.schedules.lock().values().cloned().collect();
+        schedules.sort_by_key(|s| std::cmp::Reverse(s.created_at_ms));
+        schedules
+    }
+
+    /// Change a schedule, replanning its next run
+    pub fn update(&self, schedule_id: &str, update: ScheduleUpdate) -> Option<Schedule> {
+        self.update_all(|schedules| {
+            let schedule = schedules.get_mut(schedule_id)?;
+            if let Some(cron) = update.cron {
+                schedule.cron = cron;
+            }
+            if let Some(enabled) = update.enabled {
+                schedule.enabled = enabled;
+            }
+            if let Some(allow_overlap) = update.allow_overlap {
+                schedule.allow_overlap = allow_overlap;
+            }
+            schedule.plan_next(Utc::now());
+            Some(schedule.clone())
+        })
+    }
+
+    /// Delete a schedule; tasks it created are left alone
+    pub fn remove(&self, schedule_id: &str) -> Option<Schedule> {
+        self.update_all(|schedules| schedules.remove(schedule_id))
+    }
+
+    /// Enabled schedules whose next run is at or before `now_ms`
+    pub fn due(&self, now_ms: i64) -> Vec<Schedule> {
+        self.schedules
+            .lock()
+            .values()
+            .filter(|s| s.enabled && s.next_run_ms.is_some_and(|next| next <= now_ms))
+            .cloned()
+            .collect()
+    }
+
+    /// Add a run to a schedule's history
+    ///
+    /// Scheduled runs move the next run past now, so runs missed while the
+    /// coordinator was down are caught up once rather than one by one.
+    pub fn record_run(&self, schedule_id: &str, run: ScheduleRun) -> Option<Schedule> {
+        self.update_all(|schedules| {
+            let schedule = schedules.get_mut(schedule_id)?;
+            if !run.manual {
+                let started_at =
+                    DateTime::from_timestamp_millis(run.started_at_ms).unwrap_or_else(Utc::now);
+                schedule.plan_next(started_at);
+            }
+            if schedule.history.len() >= MAX_SCHEDULE_HISTORY {
+                schedule.history.remove(0);
+            }
+            schedule.history.push(run);
+            Some(schedule.clone())
+        })
+    }
+
+    /// Apply a change and save the result
+    ///
+    /// The schedules are serialized under the lock but written after it is
+    /// released, on the blocking pool when called from the runtime.
+    fn update_all<T>(&self, f: impl FnOnce(&mut HashMap<String, Schedule>) -> T) -> T {
+        let (result, pending) = {
+            let mut schedules = self.schedules.lock();
+            let result = f(&mut schedules);
+            let pending = self.store.as_ref().and_then(|store| {
+                let schedules: Vec<&Schedule> = schedules.values().collect();
+                match serde_json::to_vec(&schedules) {
+                    Ok(data) => {
+                        let change = store.changes.fetch_add(1, Ordering::SeqCst) + 1;
+                        Some((store.clone(), change, data))
+                    }
+                    Err(e) => {
+                        warn!(error = %e, "Failed to serialize schedules");
+                        None
+                    }
+                }
+            });
+            (result, pending)
+        };
+
+        if let Some((store, change, data)) = pending {
+            match tokio::runtime::Handle::try_current() {
+                Ok(runtime) => {
+                    runtime.spawn_blocking(move || store.save(change, &data));
+                }
+                Err(_) => store.save(change, &data),
+            }
+        }
+        result
+    }
+}
+
+/// File the schedules are saved to
+#[derive(Debug)]
+struct Store {
+    path: PathBuf,
+    /// Changes made so far
+    changes: AtomicU64,
+    /// Latest change written to the file
+    saved: Mutex<u64>,
+}
+
+impl Store {
+    /// Write the schedules as of `change`, unless a later change was written
+    fn save(&self, change: u64, data: &[u8]) {
+        let mut saved = self.saved.lock();
+        if change <= *saved {
+            return;
+        }
+        if let Err(e) = save(&self.path, data) {
+            warn!(path = %self.path.display(), error = %e, "Failed to save schedules");
+            return;
+        }
+        *saved = change;
+    }
+}
+
+/// Write serialized schedules to `path` atomically
+fn save(path: &Path, data: &[u8]) -> io::Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, data)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    fn next(expr: &str, after: &str) -> Option<DateTime<Utc>> {
+        expr.parse::<CronSchedule>().unwrap().next_after(at(after))
+    }
+
+    fn spec(cron: &str) -> ScheduleSpec {
+        ScheduleSpec {
+            name: "nightly".to_string(),
+            cron: cron.parse().unwrap(),
+            template: TaskTemplate {
+                name: "finetune".to_string(),
+                task_type: "finetune".to_string(),
+                dataset_id: "snapshot-*".to_string(),
+                worker_count: 2,
+                epochs: 1,
+                config: HashMap::new(),
+            },
+            enabled: true,
+            allow_overlap: false,
+        }
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        // Nightly at 02:00
+        assert_eq!(
+            next("0 2 * * *", "2026-03-01T01:59:30Z"),
+            Some(at("2026-03-01T02:00:00Z"))
+        );
+        assert_eq!(
+            next("0 2 * * *", "2026-03-01T02:00:00Z"),
+            Some(at("2026-03-02T02:00:00Z"))
+        );
+        // Every 15 minutes during working hours on weekdays
+        assert_eq!(
+            next("*/15 9-17 * * mon-fri", "2026-03-06T17:50:00Z"),
+            Some(at("2026-03-09T09:00:00Z"))
+        );
+        // Year rollover and named months
+        assert_eq!(
+            next("30 6 1 jan *", "2026-06-01T00:00:00Z"),
+            Some(at("2027-01-01T06:30:00Z"))
+        );
+        // Restricted day of month and weekday match either
+        assert_eq!(
+            next("0 0 13 * fri", "2026-03-01T00:00:00Z"),
+            Some(at("2026-03-06T00:00:00Z"))
+        );
+        // Sunday as 7 and shorthands
+        assert_eq!(
+            next("0 0 * * 7", "2026-03-01T00:00:00Z"),
+            next("@weekly", "2026-03-01T00:00:00Z")
+        );
+        assert_eq!(next("0 0 30 2 *", "2026-03-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn test_cron_rejects_invalid() {
+        for expr in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+        ] {
+            assert!(expr.parse::<CronSchedule>().is_err(), "{:?}", expr);
+        }
+        let cron: CronSchedule = serde_json::from_str("\"@daily\"").unwrap();
+        assert_eq!(serde_json::to_string(&cron).unwrap(), "\"@daily\"");
+        assert!(serde_json::from_str::<CronSchedule>("\"daily\"").is_err());
+    }
+
+    #[test]
+    fn test_template_resolves_latest_snapshot() {
+        let template = spec("@daily").template;
+        let datasets: Vec<DatasetId> = ["snapshot-2026-03-01", "snapshot-2026-03-02", "other"]
+            .iter()
+            .map(|id| id.parse().unwrap())
+            .collect();
+        assert_eq!(
+            template.resolve_dataset(&datasets).unwrap(),
+            "snapshot-2026-03-02"
+        );
+
+        let exact = TaskTemplate {
+            dataset_id: "other".to_string(),
+            ..template
+        };
+        assert_eq!(exact.resolve_dataset(&datasets).unwrap(), "other");
+        assert_eq!(exact.resolve_dataset(&datasets[..1]), None);
+    }
+
+    #[test]
+    fn test_due_and_record_run() {
+        let manager = ScheduleManager::new();
+        let schedule = manager.create(spec("* * * * *"));
+        let next_run = schedule.next_run_ms.unwrap();
+        assert!(manager.due(next_run - 1).is_empty());
+        assert_eq!(manager.due(next_run).len(), 1);
+
+        let run = ScheduleRun {
+            scheduled_at_ms: next_run,
+            started_at_ms: next_run,
+            manual: false,
+            dataset_id: Some("snapshot-1".parse().unwrap()),
+            task_id: Some("task_1".to_string()),
+            error: None,
+        };
+        let updated = manager.record_run(&schedule.id, run).unwrap();
+        assert_eq!(updated.next_run_ms, Some(next_run + 60_000));
+        assert_eq!(updated.last_task_id(), Some("task_1"));
+        assert!(manager.due(next_run).is_empty());
+
+        // Disabled schedules never come due
+        let disabled = manager
+            .update(
+                &schedule.id,
+                ScheduleUpdate {
+                    enabled: Some(false),
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert_eq!(disabled.next_run_ms, None);
+        assert!(manager.due(i64::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_schedules_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("schedules.json");
+
+        let manager = ScheduleManager::load(&path).unwrap();
+        let schedule = manager.create(spec("0 2 * * *"));
+
+        let reloaded = ScheduleManager::load(&path).unwrap();
+        assert_eq!(reloaded.get(&schedule.id), Some(schedule.clone()));
+        assert!(reloaded.remove(&schedule.id).is_some());
+        assert!(ScheduleManager::load(&path).unwrap().list().is_empty());
+    }
+}
@@ -3,14 +3,21 @@
 //! Provides the Tonic server setup with configurable bind address,
 //! graceful shutdown handling, and health check endpoints.
 
-use std::net::SocketAddr;
+use std::future::Future;
+use std::net::{AddrParseError, IpAddr, SocketAddr};
 use std::sync::Arc;
 use std::time::Duration;
 
-use tokio::signal;
+use tokio::net::TcpListener;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::server::TcpIncoming;
 use tonic::transport::Server;
 use tracing::{error, info};
 
+use runtime_core::config::RuntimeConfig;
+use runtime_core::shutdown::wait_for_signal;
+
+use crate::middleware::{AdmissionConfig, AdmissionControl, AdmissionLayer, MetricsLayer};
 use crate::proto::coordinator_server::CoordinatorServer as CoordinatorGrpcServer;
 use crate::service::CoordinatorService;
 
@@ -31,6 +38,17 @@ pub struct ServerConfig {
 
     /// Enable gRPC reflection
     pub enable_reflection: bool,
+
+    /// Maximum gRPC message size in bytes
+    pub max_message_size: usize,
+
+    /// Compression encodings accepted from and offered to clients (empty = disabled)
+    ///
+    /// Responses are only compressed for clients that advertise support.
+    pub compression: Vec<CompressionEncoding>,
+
+    /// Per-method in-flight limits for load shedding
+    pub admission: AdmissionConfig,
 }
 
 impl Default for ServerConfig {
@@ -40,10 +58,27 @@ impl Default for ServerConfig {
             tcp_keepalive: Some(Duration::from_secs(60)),
             request_timeout: Some(Duration::from_secs(300)),
             enable_reflection: true,
+            max_message_size: 64 * 1024 * 1024, // 64MB
+            compression: vec![CompressionEncoding::Zstd, CompressionEncoding::Gzip],
+            admission: AdmissionConfig::default(),
         }
     }
 }
 
+impl ServerConfig {
+    /// Build server settings from the shared runtime configuration
+    pub fn from_runtime_config(config: &RuntimeConfig) -> Result<Self, AddrParseError> {
+        let ip: IpAddr = config.coordinator.bind_address.parse()?;
+
+        Ok(Self {
+            addr: SocketAddr::new(ip, config.coordinator.port),
+            tcp_keepalive: Some(config.network.keepalive_interval),
+            max_message_size: config.network.max_message_size,
+            ..Default::default()
+        })
+    }
+}
+
 /// Coordinator gRPC server
 pub struct CoordinatorServer {
     config: ServerConfig,
@@ -64,31 +99,107 @@ impl CoordinatorServer {
         Self { config, service }
     }
 
-    /// Run the server until shutdown signal
+    /// Run the server until Ctrl+C or SIGTERM
     pub async fn run(self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-        let addr = self.config.addr;
+        let service = self.service.clone();
+        self.run_with_shutdown(async move {
+            let signal = wait_for_signal().await;
+            service.begin_shutdown(&format!("{signal} received"));
+        })
+        .await
+    }
+
+    /// Run the server until the given future completes or a Shutdown RPC arrives
+    ///
+    /// Once shutdown begins new registrations are refused and open barriers
+    /// are aborted. After in-flight requests drain, pending checkpoint writes
+    /// are flushed and the cluster state is saved.
+    pub async fn run_with_shutdown<F>(
+        self,
+        signal: F,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let listener = TcpListener::bind(self.config.addr).await?;
+        self.run_on_listener(listener, signal).await
+    }
+
+    /// Like [`run_with_shutdown`](Self::run_with_shutdown), accepting
+    /// connections on an already bound listener
+    ///
+    /// Binding port 0 first and reading the listener's address lets callers
+    /// such as tests or an in-process coordinator serve on a free port.
+    pub async fn run_on_listener<F>(
+        self,
+        listener: TcpListener,
+        signal: F,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: Future<Output = ()> + Send,
+    {
+        let addr = listener.local_addr()?;
+        let service = self.service.clone();
+        let metrics = self.service.request_metrics().clone();
+        let admission = Arc::new(AdmissionControl::new(self.config.admission.clone()));
 
         info!(address = %addr, "Starting coordinator server");
 
         // Build the gRPC service
-        let grpc_service = CoordinatorGrpcServer::new(self.service)
-            .max_decoding_message_size(64 * 1024 * 1024) // 64MB
-            .max_encoding_message_size(64 * 1024 * 1024);
+        let mut grpc_service = CoordinatorGrpcServer::new(self.service)
+            .max_decoding_message_size(self.config.max_message_size)
+            .max_encoding_message_size(self.config.max_message_size);
+        for encoding in &self.config.compression {
+            grpc_service = grpc_service
+                .accept_compressed(*encoding)
+                .send_compressed(*encoding);
+        }
+
+        // Standard grpc.health.v1 service for probes and load balancers
+        let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+        health_reporter
+            .set_serving::<CoordinatorGrpcServer<CoordinatorService>>()
+            .await;
 
         // Build server
         let mut server_builder = Server::builder();
 
-        if let Some(keepalive) = self.config.tcp_keepalive {
-            server_builder = server_builder.tcp_keepalive(Some(keepalive));
-        }
-
         if let Some(timeout) = self.config.request_timeout {
             server_builder = server_builder.timeout(timeout);
         }
 
+        // Report NOT_SERVING as soon as shutdown starts so traffic drains away
+        let shutdown_service = service.clone();
+        let shutdown = async move {
+            tokio::select! {
+                _ = signal => {
+                    shutdown_service.begin_shutdown("signal received");
+                }
+                _ = shutdown_service.shutdown_requested() => {}
+            }
+            health_reporter
+                .set_not_serving::<CoordinatorGrpcServer<CoordinatorService>>()
+                .await;
+        };
+
+        // Reflection lets grpcurl/grpcui discover the API without local protos
+        let (reflection_v1, reflection_v1alpha) = if self.config.enable_reflection {
+            let v1 = reflection_builder().build_v1()?;
+            let v1alpha = reflection_builder().build_v1alpha()?;
+            (Some(v1), Some(v1alpha))
+        } else {
+            (None, None)
+        };
+
+        let incoming = TcpIncoming::from_listener(listener, true, self.config.tcp_keepalive)?;
         let server = server_builder
+            .layer(MetricsLayer::new(metrics))
+            .layer(AdmissionLayer::new(admission))
+            .add_service(health_service)
+            .add_optional_service(reflection_v1)
+            .add_optional_service(reflection_v1alpha)
             .add_service(grpc_service)
-            .serve_with_shutdown(addr, shutdown_signal());
+            .serve_with_incoming_shutdown(incoming, shutdown);
 
         info!(address = %addr, "Coordinator server listening");
 
@@ -97,6 +208,7 @@ impl CoordinatorServer {
             Box::new(e) as Box<dyn std::error::Error + Send + Sync>
         })?;
 
+        service.flush_state().await?;
         info!("Coordinator server shutdown complete");
         Ok(())
     }
@@ -112,33 +224,11 @@ impl CoordinatorServer {
     }
 }
 
-/// Wait for shutdown signal (Ctrl+C or SIGTERM)
-async fn shutdown_signal() {
-    let ctrl_c = async {
-        signal::ctrl_c()
-            .await
-            .expect("Failed to install Ctrl+C handler");
-    };
-
-    #[cfg(unix)]
-    let terminate = async {
-        signal::unix::signal(signal::unix::SignalKind::terminate())
-            .expect("Failed to install signal handler")
-            .recv()
-            .await;
-    };
-
-    #[cfg(not(unix))]
-    let terminate = std::future::pending::<()>();
-
-    tokio::select! {
-        _ = ctrl_c => {
-            info!("Received Ctrl+C, initiating graceful shutdown");
-        }
-        _ = terminate => {
-            info!("Received SIGTERM, initiating graceful shutdown");
-        }
-    }
+/// Reflection builder with the coordinator and health descriptors registered
+fn reflection_builder() -> tonic_reflection::server::Builder<'static> {
+    tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(crate::proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
 }
 
 #[cfg(test)]
@@ -152,4 +242,127 @@ mod tests {
         assert!(config.tcp_keepalive.is_some());
         assert!(config.enable_reflection);
     }
+
+    #[test]
+    fn test_config_from_runtime_config() {
+        let mut runtime = RuntimeConfig::default();
+        runtime.coordinator.bind_address = "127.0.0.1".to_string();
+        runtime.coordinator.port = 6000;
+
+        let config = ServerConfig::from_runtime_config(&runtime).unwrap();
+        assert_eq!(config.addr, "127.0.0.1:6000".parse().unwrap());
+        assert_eq!(config.max_message_size, runtime.network.max_message_size);
+
+        runtime.coordinator.bind_address = "not-an-ip".to_string();
+        assert!(ServerConfig::from_runtime_config(&runtime).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_run_on_listener() {
+        use crate::proto::coordinator_client::CoordinatorClient;
+        use crate::proto::ClusterStateRequest;
+
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = checkpoint::CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let service = CoordinatorService::with_config(checkpoint, 10, Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(CoordinatorServer::new(service).run_on_listener(
+            listener,
+            async {
+                let _ = stopped.await;
+            },
+        ));
+
+        let mut client = CoordinatorClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+        let state = client
+            .get_cluster_state(ClusterStateRequest::default())
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(state.workers.is_empty());
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_parked_kv_waits_are_not_shed() {
+        use crate::proto::coordinator_client::CoordinatorClient;
+        use crate::proto::{KvSetRequest, KvWaitRequest};
+
+        let dir = tempfile::tempdir().unwrap();
+        let checkpoint = checkpoint::CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let service = CoordinatorService::with_config(checkpoint, 10, Duration::from_secs(30))
+            .await
+            .unwrap();
+        let metrics = service.request_metrics().clone();
+        let config = ServerConfig {
+            admission: AdmissionConfig {
+                default_max_in_flight: 1,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (stop, stopped) = tokio::sync::oneshot::channel::<()>();
+        let server = tokio::spawn(
+            CoordinatorServer::with_config(service, config).run_on_listener(listener, async {
+                let _ = stopped.await;
+            }),
+        );
+        let client = CoordinatorClient::connect(format!("http://{addr}"))
+            .await
+            .unwrap();
+
+        // More ranks wait than the default limit allows in flight
+        let waits: Vec<_> = (0..3)
+            .map(|_| {
+                let mut client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .kv_wait(KvWaitRequest {
+                            job_id: "job".to_string(),
+                            keys: vec!["master_addr".to_string()],
+                            timeout_ms: 0,
+                        })
+                        .await
+                })
+            })
+            .collect();
+        while metrics.get_request_count("KvWait") < 3 {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+
+        client
+            .clone()
+            .kv_set(KvSetRequest {
+                job_id: "job".to_string(),
+                key: "master_addr".to_string(),
+                value: b"10.0.0.1:29500".to_vec(),
+            })
+            .await
+            .unwrap();
+        for wait in waits {
+            let response = wait.await.unwrap().unwrap().into_inner();
+            assert_eq!(response.values["master_addr"], b"10.0.0.1:29500");
+        }
+
+        stop.send(()).unwrap();
+        server.await.unwrap().unwrap();
+    }
 }
@@ -2,44 +2,151 @@
 //!
 //! Implements all methods defined in coordinator.proto
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use chrono::Utc;
 use dashmap::DashMap;
-use tokio::sync::mpsc;
+use tokio::sync::{broadcast, mpsc, watch};
 use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
 use tonic::{Request, Response, Status, Streaming};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use checkpoint::{CheckpointManager, CheckpointManagerConfig, CheckpointManagerHandle};
 use data_shard::ShardManager;
+use runtime_core::config::{
+    CheckpointStrategy, MetricsHistoryConfig, RuntimeConfig, StorageBackend,
+};
+use runtime_core::logging::{LogSampler, LogThrottle};
+use runtime_core::prometheus::{MetricType, PrometheusWriter};
+use runtime_core::supervisor::{run_periodic, ShutdownSignal};
+use runtime_core::worker::WorkerQuery;
 use runtime_core::{
-    ResourceMetrics, WorkerInfo as CoreWorkerInfo, WorkerRegistry, WorkerRegistryHandle,
+    CheckpointId, CheckpointMetadata, CheckpointType as CoreCheckpointType, Counter, DatasetId,
+    MembershipChange, MembershipEvent, MetricsRegistry, ResourceMetrics, ShutdownToken, Taint,
+    WorkerId, WorkerInfo as CoreWorkerInfo, WorkerRegistry, WorkerRegistryHandle,
     WorkerState as CoreWorkerState,
 };
 
+use crate::barrier::{ArriveOutcome, BarrierInfo, BarrierRegistry, BarrierRelease, BarrierStatus};
+use crate::checkpoint_txn::{CheckpointTransaction, CheckpointTransactions};
+use crate::commands::{CommandQueue, WorkerCommand};
+use crate::dedup::DedupCache;
+use crate::events::{CoordinatorEvent, EventBus};
 use crate::http_api::{
-    BarrierResponse as ApiBarrierResponse, CheckpointResponse, DatasetResponse, MetricsResponse,
+    BarrierResponse as ApiBarrierResponse, CheckpointResponse, ConfigUpdateRequest,
+    CreateScheduleRequest, DatasetDetailResponse, DatasetPreviewResponse, DatasetResponse,
+    EpochResponse, MetricsResponse, ShardResponse, UpdateScheduleRequest, WorkerDetailResponse,
     WorkerResponse,
 };
+use crate::kv::{KvStore, MAX_KV_VALUE_BYTES};
+use crate::lease::{LeaseError, LeaseGrant, LeaseManager};
+use crate::logs::{LogBuffer, LogLevel, LogRecord};
+use crate::metrics_history::{MetricsHistory, MetricsReading, MetricsSample};
+use crate::middleware::{InputValidator, RequestMetrics};
 use crate::proto::{
-    self, coordinator_server::Coordinator, BarrierRequest, BarrierResponse, CheckpointAck,
-    CheckpointInfo, DatasetAck, DatasetInfo, HeartbeatRequest, HeartbeatResponse, RecoveryRequest,
-    RecoveryResponse, ShardAssignment, ShardRequest, WorkerConfig, WorkerInfo,
+    self, coordinator_server::Coordinator, AcquireLeaseRequest, BarrierRequest, BarrierResponse,
+    BarrierSnapshot, CheckpointAck, CheckpointInfo, ClusterMetrics, ClusterMetricsRequest,
+    ClusterState, ClusterStateRequest, DatasetAck, DatasetInfo, DatasetState, DrainWorkerRequest,
+    DrainWorkerResponse, EpochCompleteRequest, EpochCompleteResponse, EventRecord,
+    GetEventsRequest, GetEventsResponse, HeartbeatRequest, HeartbeatResponse, KvGetRequest,
+    KvGetResponse, KvSetRequest, KvSetResponse, KvWaitRequest, KvWaitResponse, LeaseResponse,
+    PruneCheckpointsRequest, PruneCheckpointsResponse, RecoveryRequest, RecoveryResponse,
+    ReleaseBarrierRequest, ReleaseBarrierResponse, ReleaseLeaseRequest, ReleaseLeaseResponse,
+    RenewLeaseRequest, ShardAssignment, ShardAssignmentList, ShardAssignmentUpdate, ShardRange,
+    ShardRequest, ShipLogsRequest, ShipLogsResponse, ShutdownRequest, ShutdownResponse,
+    WatchShardAssignmentsRequest, WatchWorkersRequest, WorkerConfig, WorkerEvent, WorkerInfo,
+    WorkerSnapshot,
+};
+use crate::protocol::{self, PROTOCOL_VERSION};
+use crate::scheduler::CheckpointScheduler;
+use crate::schedules::{
+    CronSchedule, Schedule, ScheduleManager, ScheduleRun, ScheduleSpec, ScheduleUpdate,
 };
+use crate::tasks::{Task, TaskError, TaskManager, TaskObservation, TaskSpec, TaskState};
+use crate::worker_history::{MetricSample, WorkerHistory};
+use storage::{LocalStorage, StorageBackend as _};
+
+/// How often released barriers and expired leases are dropped
+pub const GARBAGE_COLLECTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Number of checkpoints included in a cluster state snapshot by default
+const DEFAULT_STATE_CHECKPOINTS: usize = 10;
+
+/// Shards and data files listed in dataset details
+const MAX_DATASET_DETAIL_ITEMS: usize = 1000;
+
+/// Most lines a dataset preview may ask for
+const MAX_DATASET_PREVIEW_LINES: usize = 100;
+
+/// Bytes read from a data file for a dataset preview
+const DATASET_PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// How long checkpoint notifications are remembered for deduplication
+const CHECKPOINT_DEDUP_WINDOW: Duration = Duration::from_secs(600);
+
+/// Responses buffered per heartbeat stream before the consumer counts as slow
+const HEARTBEAT_STREAM_BUFFER: usize = 32;
+
+/// Consecutive dropped responses before a slow heartbeat stream is closed
+const HEARTBEAT_STREAM_MAX_DROPS: u64 = 8;
+
+/// Number of events returned by GetEvents when no limit is given
+const DEFAULT_EVENTS_LIMIT: usize = 1000;
+
+/// Default and maximum time a KvWait call blocks
+const DEFAULT_KV_WAIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Lease duration when the request does not specify one
+const DEFAULT_LEASE_TTL: Duration = Duration::from_secs(30);
+
+/// Longest lease duration a worker may request
+const MAX_LEASE_TTL: Duration = Duration::from_secs(3600);
+
+/// Delay suggested to workers whose heartbeat stream was closed
+const HEARTBEAT_RECONNECT_AFTER: Duration = Duration::from_secs(1);
+
+/// Longest time shutdown waits for pending checkpoint writes
+const SHUTDOWN_FLUSH_TIMEOUT: Duration = Duration::from_secs(60);
 
-/// Active barrier tracking
-struct BarrierState {
-    /// Expected participants
-    expected: u64,
-    /// Arrived participants
-    arrived: AtomicU64,
-    /// Channels to notify waiting workers
-    waiters: parking_lot::Mutex<Vec<tokio::sync::oneshot::Sender<u64>>>,
+/// Per-request debug logs, sampled so large clusters don't flood the log
+static HEARTBEAT_LOG: LogSampler = LogSampler::new(100);
+static SHARD_REQUEST_LOG: LogSampler = LogSampler::new(100);
+
+/// Progress reports for unknown shards repeat every heartbeat until the
+/// worker catches up, so they are logged at most once per interval
+static UNKNOWN_SHARD_LOG: LogThrottle = LogThrottle::new(Duration::from_secs(10));
+
+/// Dataset metadata key giving the average sample size in bytes
+pub const BYTES_PER_SAMPLE_KEY: &str = "bytes_per_sample";
+
+/// Share of a worker's available memory that shard buffers may use
+const SHARD_MEMORY_FRACTION: f64 = 0.25;
+
+/// Prefetch depth suggested when the shard size in memory is unknown
+const DEFAULT_PREFETCH_DEPTH: u64 = 2;
+
+/// Upper bound on the suggested prefetch depth
+const MAX_PREFETCH_DEPTH: u64 = 8;
+
+/// Maximum log lines a worker may ship in one request
+const MAX_SHIPPED_LOG_LINES: usize = 1000;
+
+/// Source recorded for shipped lines without a target
+const DEFAULT_WORKER_LOG_SOURCE: &str = "worker";
+
+/// Delivery counters for a worker's heartbeat stream
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HeartbeatStreamStats {
+    /// Heartbeats received from the worker
+    pub received: u64,
+    /// Responses delivered to the stream buffer
+    pub sent: u64,
+    /// Responses dropped because the buffer was full
+    pub dropped: u64,
 }
 
 /// Coordinator gRPC service
@@ -54,11 +161,11 @@ pub struct CoordinatorService {
     /// Shard manager for data distribution
     shard_manager: Arc<ShardManager>,
 
-    /// Active barriers: barrier_id -> BarrierState
-    barriers: Arc<DashMap<String, Arc<BarrierState>>>,
+    /// Active and recently released barriers
+    barriers: Arc<BarrierRegistry>,
 
     /// Registered datasets for tracking
-    datasets: Arc<DashMap<String, DatasetInfo>>,
+    datasets: Arc<DashMap<DatasetId, DatasetInfo>>,
 
     /// Default heartbeat interval in ms
     heartbeat_interval_ms: u64,
@@ -66,8 +173,83 @@ pub struct CoordinatorService {
     /// Server start time for uptime tracking
     start_time: Instant,
 
-    /// Request counter for metrics
-    request_count: Arc<AtomicU64>,
+    /// Per-RPC request metrics, recorded by the server's metrics layer
+    request_metrics: Arc<RequestMetrics>,
+
+    /// Cluster event bus
+    events: EventBus,
+
+    /// Commands waiting to be delivered to workers on their next heartbeat
+    commands: Arc<CommandQueue>,
+
+    /// Acks of recent checkpoint notifications, so retries are not re-applied
+    checkpoint_acks: Arc<DedupCache<CheckpointAck>>,
+
+    /// Counters for open heartbeat streams: worker_id -> stats
+    heartbeat_streams: Arc<DashMap<WorkerId, HeartbeatStreamStats>>,
+
+    /// Rendezvous key-value store
+    kv: Arc<KvStore>,
+
+    /// Named leases for singleton duties
+    leases: Arc<LeaseManager>,
+
+    /// Workers that finished the current epoch: dataset_id -> (epoch, workers)
+    epoch_reports: Arc<DashMap<DatasetId, (u64, HashSet<WorkerId>)>>,
+
+    /// Fraction of healthy workers that must finish an epoch before it advances
+    epoch_quorum: f64,
+
+    /// Request field validation
+    validator: Arc<InputValidator>,
+
+    /// Decides when workers are told to checkpoint
+    checkpoint_scheduler: Arc<CheckpointScheduler>,
+
+    /// Negotiated protocol version per worker
+    protocol_versions: Arc<DashMap<WorkerId, u32>>,
+
+    /// Fires once shutdown begins
+    shutdown: ShutdownToken,
+
+    /// Where the cluster state snapshot is written on shutdown
+    state_path: Option<PathBuf>,
+
+    /// Training tasks
+    tasks: Arc<TaskManager>,
+
+    /// Recurring tasks
+    schedules: Arc<ScheduleManager>,
+
+    /// Recent resources, state changes and errors of each worker
+    worker_history: Arc<WorkerHistory>,
+
+    /// Coordinator and worker logs
+    logs: Arc<LogBuffer>,
+
+    /// On-demand checkpoints requested through the HTTP API
+    checkpoint_txns: Arc<CheckpointTransactions>,
+
+    /// Workers taken out of rotation, with when draining started (ms since epoch)
+    draining: Arc<DashMap<WorkerId, i64>>,
+
+    /// Workers refused registration, with the reason
+    blacklist: Arc<DashMap<WorkerId, String>>,
+
+    /// Metrics published by the coordinator and its components
+    metrics: Arc<MetricsRegistry>,
+
+    /// Checkpoints committed since startup
+    checkpoints_committed: Counter,
+
+    /// Bytes of the checkpoints committed since startup
+    checkpoint_bytes: Counter,
+
+    /// Periodically sampled metrics for charts
+    metrics_history: Arc<MetricsHistory>,
+
+    /// Effective runtime configuration; subscribers are told about changes
+    config: Arc<watch::Sender<RuntimeConfig>>,
 }
 
 impl CoordinatorService {
@@ -88,772 +270,5804 @@ impl CoordinatorService {
         heartbeat_timeout: Duration,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
         let workers = Arc::new(WorkerRegistry::new(max_workers, heartbeat_timeout));
+        let shutdown = ShutdownToken::new();
         let checkpoint_manager = Arc::new(
-            CheckpointManager::new(checkpoint_config)
+            CheckpointManager::with_shutdown(checkpoint_config, shutdown.clone())
                 .await
                 .map_err(|e| Box::new(e) as Box<dyn std::error::Error + Send + Sync>)?,
         );
         let shard_manager = Arc::new(ShardManager::new());
 
+        let metrics = Arc::new(MetricsRegistry::new());
+        checkpoint_manager.register_metrics(&metrics);
+        shard_manager.register_metrics(&metrics);
+        let checkpoints_committed = metrics.counter(
+            "strata_checkpoints_total",
+            "Checkpoints committed since startup",
+        );
+        let checkpoint_bytes = metrics.counter(
+            "strata_checkpoint_bytes_total",
+            "Size of the checkpoints committed since startup",
+        );
+
+        let mut config = RuntimeConfig::default();
+        config.coordinator.max_workers = max_workers;
+        config.coordinator.heartbeat_timeout = heartbeat_timeout;
+        config.checkpoint.keep_count = checkpoint_manager.keep_count();
+
         Ok(Self {
             workers,
             checkpoint_manager,
             shard_manager,
-            barriers: Arc::new(DashMap::new()),
+            barriers: Arc::new(BarrierRegistry::default()),
             datasets: Arc::new(DashMap::new()),
             heartbeat_interval_ms: 5000,
             start_time: Instant::now(),
-            request_count: Arc::new(AtomicU64::new(0)),
+            request_metrics: Arc::new(RequestMetrics::new()),
+            events: EventBus::default(),
+            commands: Arc::new(CommandQueue::new()),
+            checkpoint_acks: Arc::new(DedupCache::new(CHECKPOINT_DEDUP_WINDOW)),
+            heartbeat_streams: Arc::new(DashMap::new()),
+            kv: Arc::new(KvStore::new()),
+            leases: Arc::new(LeaseManager::new()),
+            epoch_reports: Arc::new(DashMap::new()),
+            epoch_quorum: 1.0,
+            validator: Arc::new(InputValidator::new()),
+            checkpoint_scheduler: Arc::new(CheckpointScheduler::default()),
+            protocol_versions: Arc::new(DashMap::new()),
+            shutdown,
+            state_path: None,
+            tasks: Arc::new(TaskManager::new()),
+            schedules: Arc::new(ScheduleManager::new()),
+            worker_history: Arc::new(WorkerHistory::default()),
+            logs: Arc::new(LogBuffer::default()),
+            checkpoint_txns: Arc::new(CheckpointTransactions::default()),
+            draining: Arc::new(DashMap::new()),
+            blacklist: Arc::new(DashMap::new()),
+            metrics,
+            checkpoints_committed,
+            checkpoint_bytes,
+            metrics_history: Arc::new(MetricsHistory::new(
+                MetricsHistoryConfig::default().capacity(),
+            )),
+            config: Arc::new(watch::channel(config).0),
         })
     }
 
-    /// Convert proto WorkerStatus::State to core WorkerState
-    fn proto_to_core_state(state: i32) -> CoreWorkerState {
-        match proto::worker_status::State::try_from(state) {
-            Ok(proto::worker_status::State::Unknown) => CoreWorkerState::Initializing,
-            Ok(proto::worker_status::State::Initializing) => CoreWorkerState::Initializing,
-            Ok(proto::worker_status::State::Idle) => CoreWorkerState::Idle,
-            Ok(proto::worker_status::State::LoadingData) => CoreWorkerState::LoadingData,
-            Ok(proto::worker_status::State::Training) => CoreWorkerState::Training,
-            Ok(proto::worker_status::State::Checkpointing) => CoreWorkerState::Checkpointing,
-            Ok(proto::worker_status::State::Recovering) => CoreWorkerState::Recovering,
-            Ok(proto::worker_status::State::Error) => CoreWorkerState::Error,
-            Err(_) => CoreWorkerState::Initializing,
+    /// Create a coordinator service from the shared runtime configuration
+    ///
+    /// Checkpoints are stored under `<storage.base_path>/checkpoints` and the
+    /// cluster state is saved to `<storage.base_path>/cluster_state.pb` on shutdown.
+    /// Tasks are kept in `<storage.base_path>/tasks.json` and schedules in
+    /// `<storage.base_path>/schedules.json`.
+    pub async fn from_runtime_config(
+        config: &RuntimeConfig,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if let StorageBackend::S3 { bucket, .. } = &config.storage.backend {
+            warn!(
+                bucket = %bucket,
+                "Coordinator checkpoint metadata is kept on local storage, ignoring S3 backend"
+            );
         }
-    }
 
-    /// Convert proto ResourceUsage to core ResourceMetrics
-    fn proto_to_core_resources(resources: Option<proto::ResourceUsage>) -> ResourceMetrics {
-        let Some(res) = resources else {
-            return ResourceMetrics::default();
+        let checkpoint_config = CheckpointManagerConfig {
+            base_path: Path::new(&config.storage.base_path).join("checkpoints"),
+            keep_count: config.checkpoint.keep_count,
+            write_buffer_size: config.checkpoint.write_buffer_size,
+            compression: config.checkpoint.compression,
+            compression_level: config.checkpoint.compression_level,
         };
 
-        ResourceMetrics {
-            cpu_percent: res.cpu_percent,
-            memory_used_bytes: res.memory_used_bytes as u64,
-            disk_read_bytes: res.disk_read_bytes as u64,
-            disk_write_bytes: res.disk_write_bytes as u64,
-            network_rx_bytes: res.network_rx_bytes as u64,
-            network_tx_bytes: res.network_tx_bytes as u64,
-            gpu_metrics: res
-                .gpu_usage
-                .into_iter()
-                .map(|g| runtime_core::GpuMetrics {
-                    gpu_id: g.gpu_id as u32,
-                    utilization_percent: g.utilization_percent,
-                    memory_used_bytes: g.memory_used_bytes as u64,
-                    memory_total_bytes: g.memory_total_bytes as u64,
-                    temperature_celsius: g.temperature_celsius,
-                })
-                .collect(),
-        }
-    }
+        let mut service = Self::with_config(
+            checkpoint_config,
+            config.coordinator.max_workers,
+            config.coordinator.heartbeat_timeout,
+        )
+        .await?;
+        service.workers = Arc::new(
+            WorkerRegistry::new(
+                config.coordinator.max_workers,
+                config.coordinator.heartbeat_timeout,
+            )
+            .with_rank_policy(config.coordinator.rank_policy),
+        );
+        service.heartbeat_interval_ms = config.worker.heartbeat_interval.as_millis() as u64;
+        service.checkpoint_scheduler =
+            Arc::new(CheckpointScheduler::new(config.checkpoint.strategy.clone()));
+        service.state_path = Some(Path::new(&config.storage.base_path).join("cluster_state.pb"));
+        service.tasks = Arc::new(TaskManager::load(
+            Path::new(&config.storage.base_path).join("tasks.json"),
+        )?);
+        service.schedules = Arc::new(ScheduleManager::load(
+            Path::new(&config.storage.base_path).join("schedules.json"),
+        )?);
+        service.metrics_history = Arc::new(MetricsHistory::new(
+            config.coordinator.metrics_history.capacity(),
+        ));
+        service.config = Arc::new(watch::channel(config.clone()).0);
 
-    // ========== HTTP API Helper Methods ==========
+        Ok(service)
+    }
 
-    /// Get server uptime in seconds
-    pub fn uptime_secs(&self) -> u64 {
-        self.start_time.elapsed().as_secs()
+    /// Effective runtime configuration
+    pub fn config(&self) -> RuntimeConfig {
+        self.config.borrow().clone()
     }
 
-    /// Get workers for API response
-    pub fn get_workers_for_api(&self) -> Vec<WorkerResponse> {
-        self.workers
-            .all_workers()
-            .into_iter()
-            .map(|w| {
-                let status = match w.state {
-                    CoreWorkerState::Idle => "idle",
-                    CoreWorkerState::Training => "active",
-                    CoreWorkerState::Error => "failed",
-                    _ => "unknown",
-                };
-                WorkerResponse {
-                    id: w.id.clone(),
-                    ip: w.hostname.clone(),
-                    port: w.port,
-                    status: status.to_string(),
-                    gpu_count: w.gpu_count,
-                    last_heartbeat: w.last_heartbeat.timestamp_millis(),
-                    assigned_shards: 0,
-                    current_epoch: w.current_epoch,
-                    current_step: w.current_step,
-                    current_task: w.current_task.clone(),
-                }
-            })
-            .collect()
+    /// Subscribe to runtime configuration changes
+    pub fn subscribe_config(&self) -> watch::Receiver<RuntimeConfig> {
+        self.config.subscribe()
     }
 
-    /// Get datasets for API response
-    pub fn get_datasets_for_api(&self) -> Vec<DatasetResponse> {
-        self.datasets
+    /// Change runtime settings without a restart
+    ///
+    /// Only the settings in [`ConfigUpdateRequest`] can change. Settings the
+    /// service owns take effect immediately; others are picked up by
+    /// [`subscribe_config`](Self::subscribe_config) subscribers.
+    pub fn update_config(&self, update: &ConfigUpdateRequest) -> Result<RuntimeConfig, Status> {
+        if let Some(timeout_ms) = update.heartbeat_timeout_ms {
+            if timeout_ms <= self.heartbeat_interval_ms {
+                return Err(invalid_field(
+                    "heartbeat_timeout_ms",
+                    Status::invalid_argument(format!(
+                        "heartbeat_timeout_ms must exceed the worker heartbeat interval of {}ms",
+                        self.heartbeat_interval_ms
+                    )),
+                ));
+            }
+        }
+        if update.checkpoint_keep_count == Some(0) {
+            return Err(invalid_field(
+                "checkpoint_keep_count",
+                Status::invalid_argument("checkpoint_keep_count must be at least 1"),
+            ));
+        }
+        if let Some(route) = update
+            .http_rate_limit
             .iter()
-            .map(|entry| {
-                let d = entry.value();
-                let shard_count = (d.total_samples as f64 / d.shard_size as f64).ceil() as u64;
-                DatasetResponse {
-                    id: d.dataset_id.clone(),
-                    name: d.dataset_id.clone(), // Use ID as name for now
-                    total_samples: d.total_samples as u64,
-                    shard_size: d.shard_size as u64,
-                    shard_count,
-                    format: d.format.clone(),
-                    shuffle: d.shuffle,
-                    // Note: Using current time as registration time since we don't persist this yet
-                    // In production, this should be stored when dataset is first registered
-                    registered_at: Utc::now().timestamp_millis(),
-                }
-            })
-            .collect()
-    }
+            .flat_map(|limits| &limits.routes)
+            .find(|route| !route.path_prefix.starts_with('/'))
+        {
+            return Err(invalid_field(
+                "http_rate_limit",
+                Status::invalid_argument(format!(
+                    "Route prefix {:?} must start with /",
+                    route.path_prefix
+                )),
+            ));
+        }
 
-    /// Get checkpoints for API response
-    pub fn get_checkpoints_for_api(&self) -> Vec<CheckpointResponse> {
-        self.checkpoint_manager
-            .all_checkpoints()
-            .into_iter()
-            .take(20)
-            .map(|c| CheckpointResponse {
-                id: c.id,
-                step: c.step,
-                epoch: c.epoch,
-                size: c.size_bytes,
-                path: c.path,
-                created_at: c.created_at.timestamp_millis(),
-                worker_id: c.metadata.get("worker_id").cloned().unwrap_or_default(),
-                status: "completed".to_string(), // All checkpoints in the list are completed
-            })
-            .collect()
-    }
+        let mut settings = Vec::new();
+        self.config.send_modify(|config| {
+            if let Some(timeout_ms) = update.heartbeat_timeout_ms {
+                let timeout = Duration::from_millis(timeout_ms);
+                config.coordinator.heartbeat_timeout = timeout;
+                self.workers.set_heartbeat_timeout(timeout);
+                settings.push("heartbeat_timeout_ms".to_string());
+            }
+            if let Some(keep_count) = update.checkpoint_keep_count {
+                config.checkpoint.keep_count = keep_count;
+                self.checkpoint_manager.set_keep_count(keep_count);
+                settings.push("checkpoint_keep_count".to_string());
+            }
+            if let Some(limits) = &update.http_rate_limit {
+                config.coordinator.http_rate_limit = limits.clone();
+                settings.push("http_rate_limit".to_string());
+            }
+        });
 
-    /// Get barriers for API response
-    pub fn get_barriers_for_api(&self) -> Vec<ApiBarrierResponse> {
-        self.barriers
-            .iter()
-            .map(|entry| {
-                let id = entry.key().clone();
-                let barrier = entry.value();
-                let arrived = barrier.arrived.load(Ordering::Relaxed);
-                let status = if arrived >= barrier.expected {
-                    "complete"
-                } else {
-                    "waiting"
-                };
-                ApiBarrierResponse {
-                    id: id.clone(),
-                    name: id,
-                    arrived,
-                    total: barrier.expected,
-                    status: status.to_string(),
-                    created_at: Utc::now().timestamp_millis(),
-                }
-            })
-            .collect()
+        if !settings.is_empty() {
+            info!(settings = ?settings, "Runtime configuration updated");
+            self.events
+                .publish(CoordinatorEvent::ConfigUpdated { settings });
+        }
+        Ok(self.config())
     }
 
-    /// Get metrics for API response
-    pub fn get_metrics_for_api(&self) -> MetricsResponse {
-        let workers = self.workers.all_workers();
-        let active_workers = workers
-            .iter()
-            .filter(|w| matches!(w.state, CoreWorkerState::Training | CoreWorkerState::Idle))
-            .count() as u32;
+    /// Get the per-RPC request metrics
+    pub fn request_metrics(&self) -> &Arc<RequestMetrics> {
+        &self.request_metrics
+    }
 
-        // Calculate actual metrics from tracked data
-        let uptime = self.uptime_secs().max(1);
-        let total_requests = self.request_count.load(Ordering::Relaxed);
+    /// Registry the coordinator's components publish their metrics in
+    pub fn metrics(&self) -> &Arc<MetricsRegistry> {
+        &self.metrics
+    }
 
-        MetricsResponse {
-            // Checkpoint throughput: checkpoints per minute
-            // Note: This is a placeholder until we implement checkpoint event tracking
-            checkpoint_throughput: 0,
-            // Coordinator requests per second
-            coordinator_rps: total_requests / uptime,
-            active_workers,
-            total_workers: workers.len() as u32,
-            // Barrier latency P99: would require histogram tracking
-            // Note: Placeholder until we implement latency histograms
-            barrier_latency_p99: 50,
-            // Shard assignment time: would require timing each assignment
-            // Note: Placeholder until we implement operation timing
-            shard_assignment_time: 10,
-        }
+    /// Delivery counters for a worker's open heartbeat stream
+    pub fn heartbeat_stream_stats(&self, worker_id: &WorkerId) -> Option<HeartbeatStreamStats> {
+        self.heartbeat_streams.get(worker_id).map(|s| *s)
     }
 
-    /// Increment request counter (used by gRPC interceptor)
-    #[allow(dead_code)]
-    fn increment_request_count(&self) {
-        self.request_count.fetch_add(1, Ordering::Relaxed);
+    /// Get the task manager
+    pub fn tasks(&self) -> &TaskManager {
+        &self.tasks
     }
-}
 
-#[tonic::async_trait]
-impl Coordinator for CoordinatorService {
-    /// Register a new worker with the coordinator
-    async fn register_worker(
+    /// Create a task on a registered dataset and assign it idle workers
+    ///
+    /// Workers are picked by rank among those not already in an active task.
+    /// `epochs` of 0 trains for a single epoch.
+    pub fn create_task(
         &self,
-        request: Request<WorkerInfo>,
-    ) -> Result<Response<WorkerConfig>, Status> {
-        let info = request.into_inner();
+        name: &str,
+        task_type: &str,
+        dataset_id: &DatasetId,
+        worker_count: usize,
+        epochs: u64,
+        config: HashMap<String, serde_json::Value>,
+    ) -> Result<Task, Status> {
+        if !self.datasets.contains_key(dataset_id) {
+            return Err(Status::not_found(format!(
+                "Dataset {} not registered",
+                dataset_id
+            )));
+        }
+        if worker_count == 0 {
+            return Err(Status::invalid_argument("worker_count must be positive"));
+        }
+
+        let busy: HashSet<WorkerId> = self
+            .tasks
+            .active()
+            .into_iter()
+            .flat_map(|t| t.worker_ids)
+            .collect();
+        let available: Vec<_> = self
+            .workers
+            .find_workers(&WorkerQuery::new().not_in_state(CoreWorkerState::Dead))
+            .into_iter()
+            .filter(|w| !busy.contains(&w.id) && !self.is_draining(&w.id))
+            .collect();
+        if available.len() < worker_count {
+            return Err(Status::failed_precondition(format!(
+                "Task needs {} workers but only {} are available",
+                worker_count,
+                available.len()
+            )));
+        }
+
+        let task = self.tasks.create(TaskSpec {
+            name: name.to_string(),
+            task_type: task_type.to_string(),
+            dataset_id: dataset_id.clone(),
+            worker_ids: available
+                .into_iter()
+                .take(worker_count)
+                .map(|w| w.id)
+                .collect(),
+            start_epoch: self.shard_manager.current_epoch(dataset_id),
+            epochs,
+            config,
+        });
+
+        for worker_id in &task.worker_ids {
+            self.commands.push(
+                worker_id,
+                WorkerCommand::StartTask {
+                    task_id: task.id.clone(),
+                },
+            );
+        }
         info!(
-            worker_id = %info.worker_id,
-            hostname = %info.hostname,
-            port = info.port,
-            gpu_count = info.gpu_count,
-            "Worker registration request"
+            task_id = %task.id,
+            dataset_id = %dataset_id,
+            workers = task.worker_ids.len(),
+            "Task created"
         );
+        self.events.publish(CoordinatorEvent::TaskStateChanged {
+            task_id: task.id.clone(),
+            state: task.state,
+        });
+        Ok(task)
+    }
 
-        // Create core worker info
-        let core_info = CoreWorkerInfo::new(
-            info.worker_id.clone(),
-            info.hostname.clone(),
-            info.port as u16,
-            0, // rank assigned by registry
-            0, // world_size updated after registration
-        );
+    /// Pause, resume or stop a task and tell its workers
+    pub fn control_task(&self, task_id: &str, state: TaskState) -> Result<Task, Status> {
+        let command = |task_id: String| match state {
+            TaskState::Paused => Some(WorkerCommand::PauseTask { task_id }),
+            TaskState::Running => Some(WorkerCommand::ResumeTask { task_id }),
+            TaskState::Stopped => Some(WorkerCommand::StopTask { task_id }),
+            _ => None,
+        };
+        if command(String::new()).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "Tasks cannot be moved to {} on request",
+                state
+            )));
+        }
 
-        // Register with worker registry
-        let registered = self
-            .workers
-            .register(core_info)
-            .map_err(|e| Status::already_exists(format!("Worker registration failed: {}", e)))?;
+        let task = self.tasks.transition(task_id, state).map_err(|e| match e {
+            TaskError::NotFound(_) => Status::not_found(e.to_string()),
+            TaskError::InvalidTransition { .. } => Status::failed_precondition(e.to_string()),
+        })?;
 
-        // Also register with shard manager for data distribution
-        self.shard_manager.register_worker(&info.worker_id);
+        for worker_id in &task.worker_ids {
+            if let Some(command) = command(task.id.clone()) {
+                self.commands.push(worker_id, command);
+            }
+        }
+        info!(task_id = %task_id, state = %state, "Task state changed on request");
+        self.events.publish(CoordinatorEvent::TaskStateChanged {
+            task_id: task.id.clone(),
+            state,
+        });
+        Ok(task)
+    }
 
-        // Build response
-        let config = WorkerConfig {
-            assigned_id: registered.id.clone(),
-            rank: registered.rank as i32,
-            world_size: self.workers.world_size() as i32,
-            heartbeat_interval_ms: self.heartbeat_interval_ms as i64,
-            config: info.metadata,
-        };
+    /// Get the schedule manager
+    pub fn schedules(&self) -> &ScheduleManager {
+        &self.schedules
+    }
+
+    /// Create a recurring task
+    pub fn create_schedule(&self, request: &CreateScheduleRequest) -> Result<Schedule, Status> {
+        let cron = parse_cron(&request.cron)?;
+        if request.template.dataset_id.is_empty() {
+            return Err(invalid_field(
+                "template.dataset_id",
+                Status::invalid_argument("template.dataset_id must not be empty"),
+            ));
+        }
+        if request.template.worker_count == 0 {
+            return Err(invalid_field(
+                "template.worker_count",
+                Status::invalid_argument("template.worker_count must be positive"),
+            ));
+        }
 
+        let schedule = self.schedules.create(ScheduleSpec {
+            name: request.name.clone(),
+            cron,
+            template: request.template.clone(),
+            enabled: request.enabled.unwrap_or(true),
+            allow_overlap: request.allow_overlap,
+        });
         info!(
-            worker_id = %registered.id,
-            rank = registered.rank,
-            world_size = config.world_size,
-            "Worker registered successfully"
+            schedule_id = %schedule.id,
+            cron = %schedule.cron,
+            dataset_id = %schedule.template.dataset_id,
+            "Schedule created"
         );
-
-        Ok(Response::new(config))
+        Ok(schedule)
     }
 
-    /// Process worker heartbeat
-    async fn heartbeat(
+    /// Change a schedule's timing or pause it
+    pub fn update_schedule(
         &self,
-        request: Request<HeartbeatRequest>,
-    ) -> Result<Response<HeartbeatResponse>, Status> {
-        let hb = request.into_inner();
+        schedule_id: &str,
+        request: &UpdateScheduleRequest,
+    ) -> Result<Schedule, Status> {
+        let update = ScheduleUpdate {
+            cron: request.cron.as_deref().map(parse_cron).transpose()?,
+            enabled: request.enabled,
+            allow_overlap: request.allow_overlap,
+        };
+        let schedule = self
+            .schedules
+            .update(schedule_id, update)
+            .ok_or_else(|| Status::not_found(format!("Schedule {} not found", schedule_id)))?;
+        info!(schedule_id = %schedule_id, enabled = schedule.enabled, cron = %schedule.cron, "Schedule updated");
+        Ok(schedule)
+    }
 
-        let state = hb
-            .status
-            .as_ref()
-            .map(|s| Self::proto_to_core_state(s.state))
-            .unwrap_or(CoreWorkerState::Idle);
+    /// Delete a schedule, leaving the tasks it created alone
+    pub fn delete_schedule(&self, schedule_id: &str) -> Result<Schedule, Status> {
+        let schedule = self
+            .schedules
+            .remove(schedule_id)
+            .ok_or_else(|| Status::not_found(format!("Schedule {} not found", schedule_id)))?;
+        info!(schedule_id = %schedule_id, "Schedule deleted");
+        Ok(schedule)
+    }
 
-        let resources = Self::proto_to_core_resources(hb.resources);
+    /// Run a schedule now, outside its timetable
+    ///
+    /// The run is recorded in the schedule's history either way; errors
+    /// starting the task are returned as well.
+    pub fn run_schedule_now(&self, schedule_id: &str) -> Result<ScheduleRun, Status> {
+        let schedule = self
+            .schedules
+            .get(schedule_id)
+            .ok_or_else(|| Status::not_found(format!("Schedule {} not found", schedule_id)))?;
+        let now_ms = Utc::now().timestamp_millis();
+        let (run, result) = self.run_schedule(&schedule, now_ms, true);
+        result.map(|_| run)
+    }
 
-        // Update worker registry
-        self.workers
-            .heartbeat(&hb.worker_id, state, resources)
-            .map_err(|e| Status::not_found(format!("Worker not found: {}", e)))?;
+    /// Start a run of every schedule that is due
+    ///
+    /// Returns the number of runs that created a task.
+    pub fn run_due_schedules(&self) -> usize {
+        let now_ms = Utc::now().timestamp_millis();
+        self.schedules
+            .due(now_ms)
+            .iter()
+            .filter(|schedule| {
+                let scheduled_at_ms = schedule.next_run_ms.unwrap_or(now_ms);
+                self.run_schedule(schedule, scheduled_at_ms, false)
+                    .1
+                    .is_ok()
+            })
+            .count()
+    }
 
-        // Update progress if provided
-        if let Some(status) = &hb.status {
-            let _ = self.workers.update_progress(
-                &hb.worker_id,
-                status.current_step as u64,
-                status.current_epoch as u64,
-                Some(status.current_task.clone()),
-            );
-        }
+    /// Periodically start due schedules until shutdown
+    pub async fn run_task_scheduler(
+        self,
+        interval: Duration,
+        shutdown: ShutdownSignal,
+    ) -> runtime_core::Result<()> {
+        run_periodic(interval, shutdown, || {
+            self.run_due_schedules();
+        })
+        .await;
+        Ok(())
+    }
 
-        debug!(worker_id = %hb.worker_id, "Heartbeat processed");
+    /// Create a schedule's task and record the run
+    fn run_schedule(
+        &self,
+        schedule: &Schedule,
+        scheduled_at_ms: i64,
+        manual: bool,
+    ) -> (ScheduleRun, Result<Task, Status>) {
+        let template = &schedule.template;
+        let previous = schedule
+            .last_task_id()
+            .and_then(|id| self.tasks.get(id))
+            .filter(|task| !task.state.is_terminal());
+        let datasets: Vec<DatasetId> = self.datasets.iter().map(|d| d.key().clone()).collect();
+        let dataset_id = template.resolve_dataset(&datasets);
 
-        Ok(Response::new(HeartbeatResponse {
-            acknowledged: true,
-            server_timestamp_ms: Utc::now().timestamp_millis(),
-            pending_commands: vec![],
-        }))
+        let result = match (&previous, &dataset_id) {
+            (Some(task), _) if !schedule.allow_overlap => Err(Status::failed_precondition(
+                format!("Previous run {} is still {}", task.id, task.state),
+            )),
+            (_, None) => Err(Status::not_found(format!(
+                "No registered dataset matches {}",
+                template.dataset_id
+            ))),
+            (_, Some(dataset_id)) => self.create_task(
+                &template.name,
+                &template.task_type,
+                dataset_id,
+                template.worker_count,
+                template.epochs,
+                template.config.clone(),
+            ),
+        };
+
+        let run = ScheduleRun {
+            scheduled_at_ms,
+            started_at_ms: Utc::now().timestamp_millis(),
+            manual,
+            dataset_id,
+            task_id: result.as_ref().ok().map(|task| task.id.clone()),
+            error: result.as_ref().err().map(|e| e.message().to_string()),
+        };
+        match &run.error {
+            None => info!(
+                schedule_id = %schedule.id,
+                task_id = run.task_id.as_deref().unwrap_or_default(),
+                "Schedule started a task"
+            ),
+            Some(error) => warn!(schedule_id = %schedule.id, error = %error, "Schedule run failed"),
+        }
+        self.schedules.record_run(&schedule.id, run.clone());
+        self.events.publish(CoordinatorEvent::ScheduleRan {
+            schedule_id: schedule.id.clone(),
+            task_id: run.task_id.clone(),
+            error: run.error.clone(),
+        });
+        (run, result)
     }
 
-    /// Deregister a worker
-    async fn deregister_worker(
-        &self,
-        request: Request<WorkerInfo>,
-    ) -> Result<Response<WorkerConfig>, Status> {
-        let info = request.into_inner();
-        info!(worker_id = %info.worker_id, "Worker deregistration request");
+    /// Advance active tasks from their workers' state and dataset progress
+    fn refresh_tasks(&self) {
+        for task in self.tasks.active() {
+            let mut observation = TaskObservation::default();
+            for worker in task.worker_ids.iter().filter_map(|id| self.workers.get(id)) {
+                if worker.state == CoreWorkerState::Dead {
+                    continue;
+                }
+                observation.live_workers += 1;
+                if worker.state == CoreWorkerState::Training {
+                    observation.training_workers += 1;
+                }
+            }
+            match self.shard_manager.epoch_progress(&task.dataset_id) {
+                Some(progress) => {
+                    observation.current_epoch = progress.epoch;
+                    observation.epoch_fraction = progress.fraction();
+                }
+                None => observation.current_epoch = task.start_epoch,
+            }
 
-        // Remove from registries
-        let removed = self
-            .workers
-            .deregister(&info.worker_id)
-            .map_err(|e| Status::not_found(format!("Worker not found: {}", e)))?;
+            if let Some(state) = self.tasks.observe(&task.id, observation) {
+                info!(task_id = %task.id, state = %state, "Task state changed");
+                self.events.publish(CoordinatorEvent::TaskStateChanged {
+                    task_id: task.id,
+                    state,
+                });
+            }
+        }
+    }
 
-        self.shard_manager.remove_worker(&info.worker_id);
+    /// Whether shutdown has begun
+    pub fn is_shutting_down(&self) -> bool {
+        self.shutdown.is_shutdown()
+    }
 
-        // Rebalance shards after worker removal
-        self.shard_manager.rebalance_shards();
+    /// Wait until shutdown begins
+    pub async fn shutdown_requested(&self) {
+        self.shutdown.wait().await
+    }
 
-        Ok(Response::new(WorkerConfig {
-            assigned_id: removed.id,
-            rank: removed.rank as i32,
-            world_size: self.workers.world_size() as i32,
-            heartbeat_interval_ms: self.heartbeat_interval_ms as i64,
-            config: HashMap::new(),
-        }))
+    /// Token firing once shutdown begins, for the servers and background tasks
+    ///
+    /// Shutdown is started through [`begin_shutdown`](Self::begin_shutdown) so
+    /// open barriers are aborted first.
+    pub fn shutdown_token(&self) -> ShutdownToken {
+        self.shutdown.clone()
     }
 
-    /// Register a dataset for sharding
-    async fn register_dataset(
-        &self,
-        request: Request<DatasetInfo>,
-    ) -> Result<Response<DatasetAck>, Status> {
-        let info = request.into_inner();
-        info!(
-            dataset_id = %info.dataset_id,
-            total_samples = info.total_samples,
-            shard_size = info.shard_size,
-            "Dataset registration request"
+    /// Stop accepting new work and abort open barriers
+    ///
+    /// Returns the number of aborted barriers, or `None` if shutdown had
+    /// already begun.
+    pub fn begin_shutdown(&self, reason: &str) -> Option<usize> {
+        if !self.shutdown.shutdown(reason) {
+            return None;
+        }
+
+        let barriers_aborted = self.barriers.abort_waiting();
+        warn!(
+            reason = %reason,
+            barriers_aborted = barriers_aborted,
+            "Coordinator shutting down"
         );
+        self.events.publish(CoordinatorEvent::ShuttingDown {
+            reason: reason.to_string(),
+            barriers_aborted,
+        });
+        Some(barriers_aborted)
+    }
 
-        // Calculate total shards
-        let total_shards = (info.total_samples as f64 / info.shard_size as f64).ceil() as u64;
+    /// Wait for pending checkpoint and event log writes and save the cluster state
+    ///
+    /// Called after the server stopped serving requests.
+    pub async fn flush_state(&self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        match tokio::time::timeout(
+            SHUTDOWN_FLUSH_TIMEOUT,
+            self.checkpoint_manager.wait_pending(),
+        )
+        .await
+        {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => warn!(error = %e, "Some checkpoint writes failed before shutdown"),
+            Err(_) => warn!(
+                pending = self.checkpoint_manager.pending_writes().len(),
+                "Timed out waiting for checkpoint writes"
+            ),
+        }
 
-        // Register with shard manager
-        self.shard_manager.register_dataset_params(
-            &info.dataset_id,
-            info.total_samples as u64,
-            info.shard_size as u64,
-            info.shuffle,
-            info.seed as u64,
-        );
+        let event_log = self.events.log().clone();
+        if let Err(e) = tokio::task::spawn_blocking(move || event_log.flush()).await {
+            warn!(error = %e, "Failed to flush the event log");
+        }
 
-        // Track dataset info
-        self.datasets.insert(info.dataset_id.clone(), info.clone());
+        if let Some(path) = &self.state_path {
+            let state = self.cluster_state(usize::MAX);
+            tokio::fs::write(path, prost::Message::encode_to_vec(&state)).await?;
+            info!(path = %path.display(), "Saved cluster state");
+        }
+        Ok(())
+    }
 
-        Ok(Response::new(DatasetAck {
-            success: true,
-            dataset_id: info.dataset_id,
-            total_shards: total_shards as i64,
-            message: format!("Dataset registered with {} shards", total_shards),
-        }))
+    /// Protocol version negotiated with a registered worker
+    pub fn worker_protocol_version(&self, worker_id: &WorkerId) -> Option<u32> {
+        self.protocol_versions.get(worker_id).map(|v| *v)
     }
 
-    /// Get shard assignment for a worker
-    async fn get_data_shard(
-        &self,
-        request: Request<ShardRequest>,
-    ) -> Result<Response<ShardAssignment>, Status> {
-        let req = request.into_inner();
-        debug!(
-            worker_id = %req.worker_id,
-            dataset_id = %req.dataset_id,
-            epoch = req.epoch,
-            "Shard request"
-        );
+    /// Take a worker's queued commands, dropping those its protocol predates
+    fn drain_commands(&self, worker_id: &WorkerId) -> Vec<String> {
+        let version = self
+            .worker_protocol_version(worker_id)
+            .unwrap_or(protocol::LEGACY_PROTOCOL_VERSION);
+        self.commands
+            .drain(worker_id)
+            .into_iter()
+            .filter(|command| {
+                let supported = command.min_protocol_version() <= version;
+                if !supported {
+                    debug!(
+                        worker_id = %worker_id,
+                        command = %command,
+                        protocol_version = version,
+                        "Dropping command unsupported by worker protocol"
+                    );
+                }
+                supported
+            })
+            .map(|command| command.to_string())
+            .collect()
+    }
 
-        // Get dataset info
-        let dataset_info = self
-            .datasets
-            .get(&req.dataset_id)
-            .ok_or_else(|| Status::not_found(format!("Dataset not found: {}", req.dataset_id)))?;
+    /// Put commands from an undelivered heartbeat response back in the queue
+    fn requeue_commands(&self, worker_id: &WorkerId, commands: &[String]) {
+        let commands = commands
+            .iter()
+            .filter_map(|c| c.parse::<WorkerCommand>().ok())
+            .collect();
+        self.commands.requeue(worker_id, commands);
+    }
 
-        // Get shard assignments from manager
-        let shards = self
-            .shard_manager
-            .get_shard_for_worker(&req.dataset_id, &req.worker_id, req.epoch as u64)
-            .ok_or_else(|| {
-                Status::internal(format!(
-                    "Failed to get shards for worker {} on dataset {}",
-                    req.worker_id, req.dataset_id
-                ))
-            })?;
+    /// Get the coordinator event bus
+    pub fn events(&self) -> &EventBus {
+        &self.events
+    }
 
-        // Return first shard (primary assignment)
-        // In practice, a worker might request multiple shards
-        if let Some(shard) = shards.first() {
-            let total_shards =
-                (dataset_info.total_samples as f64 / dataset_info.shard_size as f64).ceil() as i64;
-
-            Ok(Response::new(ShardAssignment {
-                dataset_id: req.dataset_id,
-                shard_id: shard.shard_id as i64,
-                total_shards,
-                start_index: shard.start_index as i64,
-                end_index: shard.end_index as i64,
-                file_paths: vec![dataset_info.path.clone()],
-                epoch: req.epoch,
-            }))
-        } else {
-            Err(Status::not_found("No shards available for this worker"))
+    /// Watch a counter that changes whenever any barrier does
+    pub fn subscribe_barriers(&self) -> watch::Receiver<u64> {
+        self.barriers.subscribe()
+    }
+
+    /// Get the log buffer behind `/api/logs`
+    pub fn logs(&self) -> &Arc<LogBuffer> {
+        &self.logs
+    }
+
+    /// Get the pending worker command queue
+    pub fn commands(&self) -> &CommandQueue {
+        &self.commands
+    }
+
+    /// Queue a command for every registered worker
+    ///
+    /// Returns the number of workers the command was queued for.
+    pub fn broadcast_command(&self, command: WorkerCommand) -> usize {
+        let workers = self.workers.all_workers();
+        for worker in &workers {
+            self.commands.push(&worker.id, command.clone());
         }
+        workers.len()
     }
 
-    /// Notify coordinator of a completed checkpoint
-    async fn notify_checkpoint(
+    /// Prune checkpoints outside the retention window across the cluster
+    ///
+    /// Every registered worker is told to delete its shard of each pruned
+    /// checkpoint. With `dry_run` only the candidate list is computed.
+    pub fn collect_checkpoint_garbage(
         &self,
-        request: Request<CheckpointInfo>,
-    ) -> Result<Response<CheckpointAck>, Status> {
-        let info = request.into_inner();
+        keep_count: usize,
+        dry_run: bool,
+    ) -> PruneCheckpointsResponse {
+        let candidates = self.checkpoint_manager.prune_candidates(keep_count);
+        let mut pruned = Vec::with_capacity(candidates.len());
+        let mut workers_notified = 0;
 
-        // Validate required fields
-        if info.checkpoint_id.is_empty() {
-            return Err(Status::invalid_argument("checkpoint_id cannot be empty"));
-        }
-        if info.worker_id.is_empty() {
-            return Err(Status::invalid_argument("worker_id cannot be empty"));
-        }
-        if info.step < 0 {
-            return Err(Status::invalid_argument("step must be non-negative"));
-        }
-        if info.size_bytes < 0 {
-            return Err(Status::invalid_argument("size_bytes must be non-negative"));
+        for ckpt in candidates {
+            if !dry_run {
+                if self
+                    .checkpoint_manager
+                    .remove_checkpoint(&ckpt.id)
+                    .is_none()
+                {
+                    continue;
+                }
+                workers_notified = self.broadcast_command(WorkerCommand::DeleteCheckpoint {
+                    checkpoint_id: ckpt.id.clone(),
+                });
+            }
+            pruned.push(ckpt.id);
         }
 
-        info!(
-            worker_id = %info.worker_id,
-            checkpoint_id = %info.checkpoint_id,
-            step = info.step,
-            epoch = info.epoch,
-            size_bytes = info.size_bytes,
-            "Checkpoint notification"
-        );
-
-        // Register this checkpoint from the remote worker
-        let mut metadata = info.metadata.clone();
-        metadata.insert("worker_id".to_string(), info.worker_id.clone());
+        let retained = self
+            .checkpoint_manager
+            .all_checkpoints()
+            .into_iter()
+            .map(|c| c.id)
+            .filter(|id| !dry_run || !pruned.contains(id))
+            .map(String::from)
+            .collect();
 
-        self.checkpoint_manager.register_external_checkpoint(
-            &info.checkpoint_id,
-            info.step as u64,
-            info.epoch as u64,
-            &info.storage_path,
-            info.size_bytes as u64,
-            metadata,
+        info!(
+            pruned = pruned.len(),
+            keep_count = keep_count,
+            dry_run = dry_run,
+            "Checkpoint prune completed"
         );
 
-        Ok(Response::new(CheckpointAck {
-            success: true,
-            checkpoint_id: info.checkpoint_id,
-            message: "Checkpoint acknowledged".to_string(),
-            global_step: info.step,
-        }))
+        PruneCheckpointsResponse {
+            pruned_checkpoint_ids: pruned.into_iter().map(String::from).collect(),
+            retained_checkpoint_ids: retained,
+            workers_notified: workers_notified as i32,
+            dry_run,
+        }
     }
 
-    /// Get latest checkpoint for recovery
-    async fn get_latest_checkpoint(
+    /// Ask workers to checkpoint now, returning a transaction to poll
+    ///
+    /// An empty `worker_ids` targets every live worker. Workers checkpoint at
+    /// the highest step reported so far.
+    pub fn trigger_checkpoint(
         &self,
-        request: Request<RecoveryRequest>,
-    ) -> Result<Response<RecoveryResponse>, Status> {
-        let req = request.into_inner();
-        info!(
-            worker_id = %req.worker_id,
-            job_id = %req.job_id,
-            "Recovery request"
-        );
-
-        // Get latest checkpoint from manager
-        let latest = self.checkpoint_manager.find_recovery_checkpoint();
+        worker_ids: &[WorkerId],
+    ) -> Result<CheckpointTransaction, Status> {
+        if self.is_shutting_down() {
+            return Err(shutting_down_status());
+        }
 
-        if let Some(ckpt) = latest {
-            // Get shard assignments for all registered datasets
-            let mut shard_assignments = Vec::new();
-            for entry in self.datasets.iter() {
-                let dataset_info = entry.value();
-                if let Some(shards) = self.shard_manager.get_shard_for_worker(
-                    &dataset_info.dataset_id,
-                    &req.worker_id,
-                    ckpt.epoch,
-                ) {
-                    for shard in shards {
-                        let total_shards = (dataset_info.total_samples as f64
-                            / dataset_info.shard_size as f64)
-                            .ceil() as i64;
-
-                        shard_assignments.push(ShardAssignment {
-                            dataset_id: dataset_info.dataset_id.clone(),
-                            shard_id: shard.shard_id as i64,
-                            total_shards,
-                            start_index: shard.start_index as i64,
-                            end_index: shard.end_index as i64,
-                            file_paths: vec![dataset_info.path.clone()],
-                            epoch: ckpt.epoch as i64,
-                        });
-                    }
+        let targets: Vec<WorkerId> = if worker_ids.is_empty() {
+            self.workers
+                .find_workers(&WorkerQuery::new().not_in_state(CoreWorkerState::Dead))
+                .into_iter()
+                .map(|w| w.id)
+                .collect()
+        } else {
+            for worker_id in worker_ids {
+                if self.workers.get(worker_id).is_none() {
+                    return Err(Status::not_found(format!(
+                        "Worker {} not registered",
+                        worker_id
+                    )));
                 }
             }
-
-            Ok(Response::new(RecoveryResponse {
-                has_checkpoint: true,
-                latest_checkpoint: Some(proto::CheckpointInfo {
-                    worker_id: String::new(), // Not applicable for recovery
-                    checkpoint_id: ckpt.id,
-                    step: ckpt.step as i64,
-                    epoch: ckpt.epoch as i64,
-                    storage_path: ckpt.path,
-                    size_bytes: ckpt.size_bytes as i64,
-                    timestamp_ms: ckpt.created_at.timestamp_millis(),
-                    r#type: proto::CheckpointType::Full as i32,
-                    metadata: ckpt.metadata,
-                }),
-                resume_step: ckpt.step as i64,
-                resume_epoch: ckpt.epoch as i64,
-                shard_assignments,
-            }))
-        } else {
-            Ok(Response::new(RecoveryResponse {
-                has_checkpoint: false,
-                latest_checkpoint: None,
-                resume_step: 0,
-                resume_epoch: 0,
-                shard_assignments: vec![],
-            }))
+            worker_ids.to_vec()
+        };
+        if targets.is_empty() {
+            return Err(Status::failed_precondition("No workers to checkpoint"));
         }
-    }
 
-    /// Barrier synchronization
-    async fn wait_barrier(
-        &self,
-        request: Request<BarrierRequest>,
-    ) -> Result<Response<BarrierResponse>, Status> {
-        let req = request.into_inner();
-        let world_size = self.workers.world_size() as u64;
+        let step = self.checkpoint_scheduler.global_step();
+        let command = WorkerCommand::CheckpointNow { step };
+        if let Some(legacy) = targets.iter().find(|id| {
+            self.worker_protocol_version(id)
+                .is_some_and(|v| v < command.min_protocol_version())
+        }) {
+            return Err(Status::failed_precondition(format!(
+                "Worker {} does not support checkpoint_now commands",
+                legacy
+            )));
+        }
 
+        for worker_id in &targets {
+            self.commands.push(worker_id, command.clone());
+        }
+        let txn = self.checkpoint_txns.begin(step, targets);
         info!(
-            worker_id = %req.worker_id,
-            barrier_id = %req.barrier_id,
-            step = req.step,
-            world_size = world_size,
-            "Barrier wait request"
+            txn_id = %txn.id,
+            step = step,
+            workers = txn.worker_ids.len(),
+            "Triggered cluster checkpoint"
         );
+        Ok(txn)
+    }
 
-        // Get or create barrier state - avoid holding entry lock
-        let barrier_ref = {
-            if let Some(existing) = self.barriers.get(&req.barrier_id) {
-                existing.clone()
-            } else {
-                let new_barrier = Arc::new(BarrierState {
-                    expected: world_size,
-                    arrived: AtomicU64::new(0),
-                    waiters: parking_lot::Mutex::new(Vec::new()),
-                });
-                self.barriers
-                    .entry(req.barrier_id.clone())
-                    .or_insert_with(|| {
-                        info!(
-                            barrier_id = %req.barrier_id,
-                            expected = world_size,
-                            "Creating new barrier"
-                        );
-                        new_barrier.clone()
-                    });
-                self.barriers.get(&req.barrier_id).unwrap().clone()
-            }
-        };
+    /// Current status of a checkpoint transaction
+    pub fn checkpoint_transaction(&self, txn_id: &str) -> Option<CheckpointTransaction> {
+        self.checkpoint_txns
+            .refresh(|worker_id| self.workers.get(worker_id).is_some());
+        self.checkpoint_txns.get(txn_id)
+    }
 
-        // Increment arrived counter
-        let arrival_order = barrier_ref.arrived.fetch_add(1, Ordering::SeqCst) + 1;
+    /// Remove a checkpoint from the index
+    ///
+    /// With `delete_data`, the coordinator's copy is deleted and workers are
+    /// told to delete theirs; otherwise the stored data is left in place.
+    pub async fn delete_checkpoint(
+        &self,
+        checkpoint_id: &CheckpointId,
+        delete_data: bool,
+    ) -> Result<CheckpointMetadata, Status> {
+        let removed = if delete_data {
+            self.checkpoint_manager
+                .delete_checkpoint(checkpoint_id)
+                .await?
+        } else {
+            self.checkpoint_manager.remove_checkpoint(checkpoint_id)
+        };
+        let ckpt = removed
+            .ok_or_else(|| Status::not_found(format!("Checkpoint {} not found", checkpoint_id)))?;
 
+        if delete_data {
+            self.broadcast_command(WorkerCommand::DeleteCheckpoint {
+                checkpoint_id: ckpt.id.clone(),
+            });
+        }
         info!(
-            barrier_id = %req.barrier_id,
-            worker_id = %req.worker_id,
-            arrival_order = arrival_order,
-            expected = barrier_ref.expected,
-            "Worker arrived at barrier"
+            checkpoint_id = %ckpt.id,
+            step = ckpt.step,
+            delete_data = delete_data,
+            "Checkpoint deleted"
         );
+        self.events.publish(CoordinatorEvent::CheckpointDeleted {
+            checkpoint_id: ckpt.id.clone(),
+            data_deleted: delete_data,
+        });
+        Ok(ckpt)
+    }
 
-        if arrival_order >= barrier_ref.expected {
-            // Last worker to arrive - release all waiters
-            let waiters: Vec<_> = barrier_ref.waiters.lock().drain(..).collect();
-            for waiter in waiters {
-                let _ = waiter.send(arrival_order);
-            }
+    /// Deregister a dataset and clear its shard state
+    ///
+    /// Datasets used by an active task cannot be removed.
+    pub fn deregister_dataset(&self, dataset_id: &DatasetId) -> Result<DatasetInfo, Status> {
+        if !self.datasets.contains_key(dataset_id) {
+            return Err(Status::not_found(format!(
+                "Dataset {} not registered",
+                dataset_id
+            )));
+        }
+        if let Some(task) = self
+            .tasks
+            .active()
+            .into_iter()
+            .find(|t| t.dataset_id == *dataset_id)
+        {
+            return Err(Status::failed_precondition(format!(
+                "Dataset {} is used by active task {}",
+                dataset_id, task.id
+            )));
+        }
 
-            // Remove barrier for cleanup
-            self.barriers.remove(&req.barrier_id);
+        let (_, info) = self
+            .datasets
+            .remove(dataset_id)
+            .ok_or_else(|| Status::not_found(format!("Dataset {} not registered", dataset_id)))?;
+        self.shard_manager.remove_dataset(dataset_id);
+        self.epoch_reports.remove(dataset_id);
 
-            info!(
-                barrier_id = %req.barrier_id,
-                participants = arrival_order,
-                "Barrier released"
-            );
+        info!(dataset_id = %dataset_id, "Dataset deregistered");
+        self.events.publish(CoordinatorEvent::DatasetRemoved {
+            dataset_id: dataset_id.clone(),
+        });
+        Ok(info)
+    }
 
-            Ok(Response::new(BarrierResponse {
-                released: true,
-                barrier_id: req.barrier_id,
-                participants: arrival_order as i64,
-                arrival_order: arrival_order as i64,
-            }))
-        } else {
-            // Wait for barrier release
-            let (tx, rx) = tokio::sync::oneshot::channel();
-            barrier_ref.waiters.lock().push(tx);
+    /// Change a dataset's shuffle flag and seed
+    ///
+    /// Only shard assignments computed afterwards are affected.
+    pub fn update_dataset_shuffle(
+        &self,
+        dataset_id: &DatasetId,
+        shuffle: Option<bool>,
+        seed: Option<i64>,
+    ) -> Result<DatasetInfo, Status> {
+        if let Some(seed) = seed {
+            self.validator
+                .validate_positive(seed, "seed")
+                .map_err(|e| invalid_field("seed", e))?;
+        }
 
-            // Wait with timeout
-            match tokio::time::timeout(Duration::from_secs(300), rx).await {
-                Ok(Ok(participants)) => Ok(Response::new(BarrierResponse {
-                    released: true,
-                    barrier_id: req.barrier_id,
-                    participants: participants as i64,
-                    arrival_order: arrival_order as i64,
-                })),
-                Ok(Err(_)) => Err(Status::internal("Barrier channel closed")),
-                Err(_) => Err(Status::deadline_exceeded("Barrier timeout")),
+        let info = {
+            let mut entry = self.datasets.get_mut(dataset_id).ok_or_else(|| {
+                Status::not_found(format!("Dataset {} not registered", dataset_id))
+            })?;
+            if let Some(shuffle) = shuffle {
+                entry.shuffle = shuffle;
             }
-        }
+            if let Some(seed) = seed {
+                entry.seed = seed;
+            }
+            entry.clone()
+        };
+        self.shard_manager
+            .update_shuffle(dataset_id, info.shuffle, info.seed as u64);
+
+        self.events.publish(CoordinatorEvent::DatasetUpdated {
+            dataset_id: dataset_id.clone(),
+        });
+        Ok(info)
     }
 
-    /// Streaming heartbeats for efficient real-time updates
-    type StreamHeartbeatsStream =
-        Pin<Box<dyn Stream<Item = Result<HeartbeatResponse, Status>> + Send>>;
+    /// Remove a worker from the cluster and rebalance its shards
+    ///
+    /// Used for graceful deregistration and by operators. A removed worker may
+    /// register again unless it was blacklisted.
+    pub fn remove_worker(&self, worker_id: &WorkerId) -> Result<WorkerConfig, Status> {
+        let removed = self.workers.deregister(worker_id)?;
 
-    async fn stream_heartbeats(
-        &self,
-        request: Request<Streaming<HeartbeatRequest>>,
-    ) -> Result<Response<Self::StreamHeartbeatsStream>, Status> {
-        let mut stream = request.into_inner();
-        let workers = self.workers.clone();
+        self.shard_manager.remove_worker(worker_id);
+        self.commands.remove(worker_id);
+        self.draining.remove(worker_id);
+        self.worker_history.remove(worker_id);
+        let protocol_version = self
+            .protocol_versions
+            .remove(worker_id)
+            .map(|(_, v)| v)
+            .unwrap_or(protocol::LEGACY_PROTOCOL_VERSION);
+        self.leases.release_all(worker_id);
 
-        // Create response channel
+        // Rebalance shards after worker removal
+        self.shard_manager.rebalance_shards();
+
+        self.events.publish(CoordinatorEvent::WorkerLeft {
+            worker_id: removed.id.clone(),
+        });
+        self.events.publish(CoordinatorEvent::ShardsRebalanced {
+            workers: self.shard_manager.active_worker_count(),
+        });
+        self.recheck_epoch_quorums();
+        self.refresh_tasks();
+
+        Ok(WorkerConfig {
+            assigned_id: removed.id.into(),
+            rank: removed.rank as i32,
+            world_size: self.workers.world_size() as i32,
+            heartbeat_interval_ms: self.heartbeat_interval_ms as i64,
+            config: HashMap::new(),
+            membership_generation: self.workers.generation() as i64,
+            server_protocol_version: PROTOCOL_VERSION,
+            protocol_version,
+            session: removed.session as i64,
+        })
+    }
+
+    /// Take a worker out of rotation without removing it
+    ///
+    /// Its shards move to the other workers and it is not picked for new
+    /// tasks, but it stays registered so running work can finish. Returns
+    /// false if the worker was already draining.
+    pub fn drain_worker(&self, worker_id: &WorkerId) -> Result<bool, Status> {
+        if self.workers.get(worker_id).is_none() {
+            return Err(Status::not_found(format!(
+                "Worker {} not registered",
+                worker_id
+            )));
+        }
+        if self
+            .draining
+            .insert(worker_id.clone(), Utc::now().timestamp_millis())
+            .is_some()
+        {
+            return Ok(false);
+        }
+
+        self.shard_manager.remove_worker(worker_id);
+        self.shard_manager.rebalance_shards();
+        // Workers that predate the command only see their shards move
+        self.commands.push(worker_id, WorkerCommand::Drain);
+        info!(worker_id = %worker_id, "Worker draining");
+        self.events.publish(CoordinatorEvent::WorkerDraining {
+            worker_id: worker_id.clone(),
+        });
+        self.events.publish(CoordinatorEvent::ShardsRebalanced {
+            workers: self.shard_manager.active_worker_count(),
+        });
+        Ok(true)
+    }
+
+    /// Whether a worker is being drained
+    pub fn is_draining(&self, worker_id: &WorkerId) -> bool {
+        self.draining.contains_key(worker_id)
+    }
+
+    /// Remove a worker and refuse its future registrations
+    ///
+    /// Workers can be blacklisted before they register.
+    pub fn blacklist_worker(&self, worker_id: &WorkerId, reason: &str) {
+        self.blacklist.insert(worker_id.clone(), reason.to_string());
+        warn!(worker_id = %worker_id, reason = %reason, "Worker blacklisted");
+        if self.workers.get(worker_id).is_some() {
+            // Ignore a concurrent deregistration
+            let _ = self.remove_worker(worker_id);
+        }
+        self.events.publish(CoordinatorEvent::WorkerBlacklisted {
+            worker_id: worker_id.clone(),
+            reason: reason.to_string(),
+        });
+    }
+
+    /// Allow a blacklisted worker to register again
+    ///
+    /// Returns false if the worker was not blacklisted.
+    pub fn unblacklist_worker(&self, worker_id: &WorkerId) -> bool {
+        let removed = self.blacklist.remove(worker_id).is_some();
+        if removed {
+            info!(worker_id = %worker_id, "Worker removed from blacklist");
+        }
+        removed
+    }
+
+    /// Remove workers that missed their heartbeat deadline and rebalance shards
+    ///
+    /// Returns the IDs of the workers that were removed.
+    pub fn reap_dead_workers(&self) -> Vec<WorkerId> {
+        if self.workers.check_dead_workers().is_empty() {
+            return Vec::new();
+        }
+        self.remove_dead_workers()
+    }
+
+    /// Remove workers already marked dead and rebalance shards
+    fn remove_dead_workers(&self) -> Vec<WorkerId> {
+        let removed: Vec<WorkerId> = self
+            .workers
+            .remove_dead_workers()
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+
+        if removed.is_empty() {
+            return removed;
+        }
+
+        for worker_id in &removed {
+            warn!(worker_id = %worker_id, "Removing dead worker");
+            self.shard_manager.remove_worker(worker_id);
+            self.commands.remove(worker_id);
+            self.draining.remove(worker_id);
+            self.worker_history.remove(worker_id);
+            self.protocol_versions.remove(worker_id);
+            self.leases.release_all(worker_id);
+            self.events.publish(CoordinatorEvent::WorkerDead {
+                worker_id: worker_id.clone(),
+            });
+        }
+
+        self.shard_manager.rebalance_shards();
+        self.events.publish(CoordinatorEvent::ShardsRebalanced {
+            workers: self.shard_manager.active_worker_count(),
+        });
+        self.recheck_epoch_quorums();
+        self.refresh_tasks();
+
+        removed
+    }
+
+    /// Compact worker ranks once membership has settled, per the rank policy
+    ///
+    /// Workers learn their new rank from the bumped membership generation in
+    /// their next heartbeat.
+    pub fn compact_ranks_if_settled(&self) -> Option<u64> {
+        let generation = self.workers.compact_ranks_if_settled()?;
+        self.events.publish(CoordinatorEvent::RanksCompacted {
+            generation,
+            world_size: self.workers.world_size(),
+        });
+        Some(generation)
+    }
+
+    /// Reap dead workers until shutdown
+    ///
+    /// Heartbeat deadlines are checked and task progress is refreshed every
+    /// `interval`; workers are removed as soon as the registry reports them
+    /// dead.
+    pub async fn run_dead_worker_reaper(
+        self,
+        interval: Duration,
+        mut shutdown: ShutdownSignal,
+    ) -> runtime_core::Result<()> {
+        let mut membership = self.workers.subscribe();
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+        loop {
+            let reap = tokio::select! {
+                _ = ticker.tick() => {
+                    self.workers.check_dead_workers();
+                    self.compact_ranks_if_settled();
+                    self.refresh_tasks();
+                    false
+                }
+                event = membership.recv() => match event {
+                    Ok(event) => event.change == MembershipChange::Dead,
+                    // Missed events may have included deaths
+                    Err(broadcast::error::RecvError::Lagged(_)) => true,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                _ = shutdown.wait() => break,
+            };
+
+            if reap {
+                let removed = self.remove_dead_workers();
+                if !removed.is_empty() {
+                    info!(count = removed.len(), "Dead worker reaper removed workers");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Periodically sample metrics into the history until shutdown
+    pub async fn run_metrics_sampler(
+        self,
+        interval: Duration,
+        shutdown: ShutdownSignal,
+    ) -> runtime_core::Result<()> {
+        run_periodic(interval, shutdown, || {
+            self.sample_metrics();
+        })
+        .await;
+        Ok(())
+    }
+
+    /// Periodically drop released barriers and expired leases until shutdown
+    pub async fn run_garbage_collector(
+        self,
+        interval: Duration,
+        shutdown: ShutdownSignal,
+    ) -> runtime_core::Result<()> {
+        run_periodic(interval, shutdown, || self.collect_garbage()).await;
+        Ok(())
+    }
+
+    /// Drop released barriers past their linger period and expired leases
+    pub fn collect_garbage(&self) {
+        self.barriers.evict_expired();
+        let leases = self.leases.evict_expired();
+        if leases > 0 {
+            debug!(leases = leases, "Evicted expired leases");
+        }
+    }
+
+    /// Record the current metrics in the history
+    pub fn sample_metrics(&self) -> MetricsSample {
+        let epochs = self
+            .datasets
+            .iter()
+            .filter_map(|entry| self.shard_manager.epoch_progress(entry.key()))
+            .map(|p| (p.dataset_id.to_string(), (p.epoch, p.fraction())))
+            .collect();
+
+        self.metrics_history.record(MetricsReading {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            total_workers: self.workers.world_size() as u32,
+            active_workers: self.active_worker_count(),
+            total_requests: self.request_metrics.total_requests(),
+            checkpoints_committed: self.checkpoints_committed.get(),
+            epochs,
+        })
+    }
+
+    /// Sampled metrics from the last `range`, oldest first
+    pub fn metrics_history(&self, range: Duration) -> Vec<MetricsSample> {
+        let since = Utc::now().timestamp_millis() - range.as_millis().min(i64::MAX as u128) as i64;
+        self.metrics_history.since(since)
+    }
+
+    /// Convert proto WorkerStatus::State to core WorkerState
+    fn proto_to_core_state(state: i32) -> CoreWorkerState {
+        match proto::worker_status::State::try_from(state) {
+            Ok(proto::worker_status::State::Unknown) => CoreWorkerState::Initializing,
+            Ok(proto::worker_status::State::Initializing) => CoreWorkerState::Initializing,
+            Ok(proto::worker_status::State::Idle) => CoreWorkerState::Idle,
+            Ok(proto::worker_status::State::LoadingData) => CoreWorkerState::LoadingData,
+            Ok(proto::worker_status::State::Training) => CoreWorkerState::Training,
+            Ok(proto::worker_status::State::Checkpointing) => CoreWorkerState::Checkpointing,
+            Ok(proto::worker_status::State::Recovering) => CoreWorkerState::Recovering,
+            Ok(proto::worker_status::State::Error) => CoreWorkerState::Error,
+            Ok(proto::worker_status::State::Disconnecting) => CoreWorkerState::Disconnecting,
+            // Only the coordinator may declare a worker dead
+            Ok(proto::worker_status::State::Dead) => CoreWorkerState::Error,
+            Err(_) => CoreWorkerState::Initializing,
+        }
+    }
+
+    /// Convert proto ResourceUsage to core ResourceMetrics
+    fn proto_to_core_resources(resources: Option<proto::ResourceUsage>) -> ResourceMetrics {
+        let Some(res) = resources else {
+            return ResourceMetrics::default();
+        };
+
+        ResourceMetrics {
+            cpu_percent: res.cpu_percent,
+            memory_used_bytes: res.memory_used_bytes as u64,
+            disk_read_bytes: res.disk_read_bytes as u64,
+            disk_write_bytes: res.disk_write_bytes as u64,
+            network_rx_bytes: res.network_rx_bytes as u64,
+            network_tx_bytes: res.network_tx_bytes as u64,
+            gpu_metrics: res
+                .gpu_usage
+                .into_iter()
+                .map(|g| runtime_core::GpuMetrics {
+                    gpu_id: g.gpu_id as u32,
+                    utilization_percent: g.utilization_percent,
+                    memory_used_bytes: g.memory_used_bytes as u64,
+                    memory_total_bytes: g.memory_total_bytes as u64,
+                    temperature_celsius: g.temperature_celsius,
+                    power_draw_watts: g.power_draw_watts,
+                    sm_clock_mhz: g.sm_clock_mhz.max(0) as u32,
+                    memory_clock_mhz: g.memory_clock_mhz.max(0) as u32,
+                    ecc_errors_corrected: g.ecc_errors_corrected.max(0) as u64,
+                    ecc_errors_uncorrected: g.ecc_errors_uncorrected.max(0) as u64,
+                })
+                .collect(),
+        }
+    }
+
+    /// Convert core WorkerState to proto WorkerStatus::State
+    fn core_to_proto_state(state: CoreWorkerState) -> proto::worker_status::State {
+        use proto::worker_status::State;
+        match state {
+            CoreWorkerState::Initializing => State::Initializing,
+            CoreWorkerState::Idle => State::Idle,
+            CoreWorkerState::LoadingData => State::LoadingData,
+            CoreWorkerState::Training => State::Training,
+            CoreWorkerState::Checkpointing => State::Checkpointing,
+            CoreWorkerState::Recovering => State::Recovering,
+            CoreWorkerState::Error => State::Error,
+            CoreWorkerState::Disconnecting => State::Disconnecting,
+            CoreWorkerState::Dead => State::Dead,
+        }
+    }
+
+    /// Apply a heartbeat to the registry and build the response
+    ///
+    /// Shared by the unary and streaming heartbeat RPCs.
+    fn process_heartbeat(&self, hb: HeartbeatRequest) -> Result<HeartbeatResponse, Status> {
+        let worker_id: WorkerId = parse_id("worker_id", &hb.worker_id)?;
+        // A delayed heartbeat from a process that since re-registered must
+        // not overwrite the new one's state
+        self.workers
+            .check_session(&worker_id, hb.session.max(0) as u64)?;
+        let state = hb
+            .status
+            .as_ref()
+            .map(|s| Self::proto_to_core_state(s.state))
+            .unwrap_or(CoreWorkerState::Idle);
+
+        let reported_resources = hb.resources.is_some();
+        let resources = Self::proto_to_core_resources(hb.resources);
+        let previous_state = self.workers.get(&worker_id).map(|w| w.state);
+
+        // Update worker registry
+        if reported_resources {
+            self.worker_history.record_resources(&worker_id, &resources);
+        }
+        self.workers.heartbeat(&worker_id, state, resources)?;
+        if !hb.metrics.is_empty() {
+            let metrics = hb
+                .metrics
+                .into_iter()
+                .filter(|m| !m.name.is_empty())
+                .map(|m| {
+                    let sample = MetricSample {
+                        timestamp_ms: m.timestamp_ms,
+                        step: m.step.max(0) as u64,
+                        value: m.value,
+                    };
+                    (m.name, sample)
+                });
+            self.worker_history.record_metrics(&worker_id, metrics);
+        }
+
+        // Update progress if provided
+        if let Some(status) = &hb.status {
+            let _ = self.workers.update_progress(
+                &worker_id,
+                status.current_step as u64,
+                status.current_epoch as u64,
+                Some(status.current_task.clone()),
+            );
+
+            for progress in &status.shard_progress {
+                if progress.shard_id < 0 || progress.samples_consumed < 0 {
+                    continue;
+                }
+                let reported = progress.dataset_id.parse().is_ok_and(|dataset_id| {
+                    self.shard_manager.report_shard_progress(
+                        &dataset_id,
+                        progress.shard_id as u64,
+                        progress.samples_consumed as u64,
+                    )
+                });
+                if reported {
+                    continue;
+                }
+                if let Some(suppressed) = UNKNOWN_SHARD_LOG.allow() {
+                    debug!(
+                        worker_id = %worker_id,
+                        dataset_id = %progress.dataset_id,
+                        shard_id = progress.shard_id,
+                        suppressed = suppressed,
+                        "Ignoring progress for unknown shard"
+                    );
+                }
+            }
+
+            let loss = (status.loss != 0.0).then_some(status.loss);
+            if let Some(step) = self
+                .checkpoint_scheduler
+                .observe(status.current_step.max(0) as u64, loss)
+            {
+                let workers = self.broadcast_command(WorkerCommand::CheckpointNow { step });
+                info!(
+                    step = step,
+                    workers = workers,
+                    "Scheduled cluster checkpoint"
+                );
+            }
+        }
+
+        // Epoch progress alone is picked up by the reaper tick
+        if previous_state.is_some_and(|prev| prev != state) {
+            self.worker_history
+                .record_transition(&worker_id, previous_state, state);
+            self.events.publish(CoordinatorEvent::WorkerStateChanged {
+                worker_id: worker_id.clone(),
+                state,
+            });
+            self.refresh_tasks();
+        }
+
+        if HEARTBEAT_LOG.sample() {
+            debug!(
+                worker_id = %worker_id,
+                sample_rate = HEARTBEAT_LOG.rate(),
+                "Heartbeat processed"
+            );
+        }
+
+        let rank = self
+            .workers
+            .get(&worker_id)
+            .map(|w| w.rank as i32)
+            .unwrap_or(-1);
+
+        Ok(HeartbeatResponse {
+            acknowledged: true,
+            server_timestamp_ms: Utc::now().timestamp_millis(),
+            pending_commands: self.drain_commands(&worker_id),
+            membership_generation: self.workers.generation() as i64,
+            rank,
+            world_size: self.workers.world_size() as i32,
+        })
+    }
+
+    /// Convert checkpoint metadata to its proto representation
+    fn checkpoint_to_proto(ckpt: CheckpointMetadata) -> CheckpointInfo {
+        let r#type = match ckpt.checkpoint_type {
+            CoreCheckpointType::Full => proto::CheckpointType::Full,
+            CoreCheckpointType::Incremental => proto::CheckpointType::Incremental,
+            CoreCheckpointType::OptimizerOnly => proto::CheckpointType::OptimizerOnly,
+            CoreCheckpointType::ModelOnly => proto::CheckpointType::ModelOnly,
+        };
+
+        CheckpointInfo {
+            worker_id: ckpt.metadata.get("worker_id").cloned().unwrap_or_default(),
+            checkpoint_id: ckpt.id.into(),
+            step: ckpt.step as i64,
+            epoch: ckpt.epoch as i64,
+            storage_path: ckpt.path,
+            size_bytes: ckpt.size_bytes as i64,
+            timestamp_ms: ckpt.created_at.timestamp_millis(),
+            r#type: r#type as i32,
+            metadata: ckpt.metadata,
+        }
+    }
+
+    /// Take a snapshot of the whole cluster
+    ///
+    /// The snapshot is retried if worker membership changes while it is being
+    /// assembled, so workers and shard epochs are always from the same generation.
+    pub fn cluster_state(&self, max_checkpoints: usize) -> ClusterState {
+        const MAX_ATTEMPTS: usize = 5;
+
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let generation = self.workers.generation();
+
+            let workers = self
+                .workers
+                .all_workers()
+                .into_iter()
+                .map(|w| WorkerSnapshot {
+                    draining: self.is_draining(&w.id),
+                    worker_id: w.id.into(),
+                    hostname: w.hostname,
+                    port: w.port as i32,
+                    rank: w.rank as i32,
+                    state: Self::core_to_proto_state(w.state) as i32,
+                    current_step: w.current_step as i64,
+                    current_epoch: w.current_epoch as i64,
+                    last_heartbeat_ms: w.last_heartbeat.timestamp_millis(),
+                    registered_at_ms: w.registered_at.timestamp_millis(),
+                })
+                .collect();
+
+            let datasets = self
+                .datasets
+                .iter()
+                .map(|entry| {
+                    let info = entry.value().clone();
+                    let total_shards =
+                        (info.total_samples as f64 / info.shard_size as f64).ceil() as i64;
+                    DatasetState {
+                        current_epoch: self.shard_manager.current_epoch(entry.key()) as i64,
+                        total_shards,
+                        info: Some(info),
+                    }
+                })
+                .collect();
+
+            let barriers = self
+                .barriers
+                .snapshot()
+                .into_iter()
+                .map(barrier_snapshot)
+                .collect();
+
+            let checkpoints = self
+                .checkpoint_manager
+                .all_checkpoints()
+                .into_iter()
+                .rev()
+                .take(max_checkpoints)
+                .map(Self::checkpoint_to_proto)
+                .collect();
+
+            if self.workers.generation() == generation || attempt >= MAX_ATTEMPTS {
+                if attempt >= MAX_ATTEMPTS {
+                    warn!(
+                        attempts = attempt,
+                        "Cluster state snapshot raced with membership changes"
+                    );
+                }
+                return ClusterState {
+                    membership_generation: generation as i64,
+                    timestamp_ms: Utc::now().timestamp_millis(),
+                    workers,
+                    datasets,
+                    barriers,
+                    checkpoints,
+                };
+            }
+        }
+    }
+
+    /// Build proto shard assignments for a worker on a dataset at an epoch
+    fn worker_shard_assignments(
+        &self,
+        dataset_id: &DatasetId,
+        dataset_info: &DatasetInfo,
+        worker_id: &WorkerId,
+        epoch: u64,
+    ) -> Option<Vec<ShardAssignment>> {
+        let shards = self
+            .shard_manager
+            .get_shard_for_worker(dataset_id, worker_id, epoch)?;
+        let total_shards =
+            (dataset_info.total_samples as f64 / dataset_info.shard_size as f64).ceil() as i64;
+        let generation = self.workers.generation() as i64;
+
+        Some(
+            shards
+                .into_iter()
+                .map(|shard| ShardAssignment {
+                    dataset_id: dataset_info.dataset_id.clone(),
+                    shard_id: shard.shard_id as i64,
+                    total_shards,
+                    start_index: shard.start_index as i64,
+                    end_index: shard.end_index as i64,
+                    file_paths: vec![dataset_info.path.clone()],
+                    epoch: epoch as i64,
+                    membership_generation: generation,
+                    shards: vec![],
+                    prefetch_depth: 0,
+                })
+                .collect(),
+        )
+    }
+
+    /// Recompute a worker's shard assignments and diff them against the last known set
+    ///
+    /// `known` maps dataset ID to the epoch and shard IDs last delivered to the worker
+    /// and is updated in place. Only datasets whose assignment changed produce an update.
+    fn shard_assignment_updates(
+        &self,
+        worker_id: &WorkerId,
+        dataset_filter: &str,
+        known: &mut HashMap<String, (u64, BTreeSet<i64>)>,
+    ) -> Vec<ShardAssignmentUpdate> {
+        let mut updates = Vec::new();
+
+        for entry in self.datasets.iter() {
+            let dataset_info = entry.value();
+            if !dataset_filter.is_empty() && dataset_info.dataset_id != dataset_filter {
+                continue;
+            }
+
+            let epoch = self.shard_manager.current_epoch(entry.key());
+            let assignments = self
+                .worker_shard_assignments(entry.key(), dataset_info, worker_id, epoch)
+                .unwrap_or_default();
+            let current: BTreeSet<i64> = assignments.iter().map(|a| a.shard_id).collect();
+
+            let (added, removed) = match known.get(&dataset_info.dataset_id) {
+                Some((known_epoch, previous)) if *known_epoch == epoch => {
+                    if *previous == current {
+                        continue;
+                    }
+                    (
+                        current.difference(previous).copied().collect(),
+                        previous.difference(&current).copied().collect(),
+                    )
+                }
+                // New dataset or new epoch: the whole set is fresh
+                Some((_, previous)) => (
+                    current.iter().copied().collect(),
+                    previous.iter().copied().collect(),
+                ),
+                None => (current.iter().copied().collect(), Vec::new()),
+            };
+
+            known.insert(dataset_info.dataset_id.clone(), (epoch, current));
+            updates.push(ShardAssignmentUpdate {
+                worker_id: worker_id.to_string(),
+                dataset_id: dataset_info.dataset_id.clone(),
+                epoch: epoch as i64,
+                membership_generation: self.workers.generation() as i64,
+                added_shard_ids: added,
+                removed_shard_ids: removed,
+                assignments,
+            });
+        }
+
+        updates
+    }
+
+    /// Epoch a shard request asks for
+    fn requested_epoch(&self, dataset_id: &DatasetId, request: &ShardRequest) -> u64 {
+        if request.use_current_epoch {
+            self.shard_manager.current_epoch(dataset_id)
+        } else {
+            request.epoch as u64
+        }
+    }
+
+    /// Validate a dataset registration, returning its ID and shard count
+    fn validate_dataset(&self, info: &DatasetInfo) -> Result<(DatasetId, u64), Status> {
+        let dataset_id = self
+            .validator
+            .validate_dataset_id(&info.dataset_id)
+            .map_err(|e| invalid_field("dataset_id", e))?;
+        self.validator
+            .validate_dataset_path(&info.path)
+            .map_err(|e| invalid_field("path", e))?;
+        self.validator
+            .validate_metadata(&info.metadata)
+            .map_err(|e| invalid_field("metadata", e))?;
+        self.validator
+            .validate_positive(info.seed, "seed")
+            .map_err(|e| invalid_field("seed", e))?;
+        let total_shards = self
+            .validator
+            .validate_shard_layout(info.total_samples, info.shard_size)
+            .map_err(|e| {
+                let field = if info.total_samples <= 0 {
+                    "total_samples"
+                } else {
+                    "shard_size"
+                };
+                invalid_field(field, e)
+            })?;
+        Ok((dataset_id, total_shards))
+    }
+
+    /// Require only a fraction of healthy workers to finish an epoch
+    ///
+    /// The fraction is clamped to `(0, 1]`; the default of 1.0 waits for all.
+    pub fn with_epoch_quorum(mut self, fraction: f64) -> Self {
+        self.epoch_quorum = fraction.clamp(f64::EPSILON, 1.0);
+        self
+    }
+
+    /// Schedule checkpoints with the given strategy
+    ///
+    /// Services built with [`Self::with_config`] leave checkpointing to user code.
+    pub fn with_checkpoint_strategy(mut self, strategy: CheckpointStrategy) -> Self {
+        self.checkpoint_scheduler = Arc::new(CheckpointScheduler::new(strategy));
+        self
+    }
+
+    /// Share a log buffer, typically the one the tracing subscriber writes to
+    pub fn with_log_buffer(mut self, logs: Arc<LogBuffer>) -> Self {
+        self.logs = logs;
+        self
+    }
+
+    /// Get the checkpoint scheduler
+    pub fn checkpoint_scheduler(&self) -> &CheckpointScheduler {
+        &self.checkpoint_scheduler
+    }
+
+    /// Record that a worker finished an epoch, advancing it once quorum is reached
+    ///
+    /// Returns `(advanced, reported, required)`.
+    fn record_epoch_complete(
+        &self,
+        dataset_id: &DatasetId,
+        worker_id: &WorkerId,
+        epoch: u64,
+    ) -> (bool, usize, usize) {
+        {
+            let mut reports = self
+                .epoch_reports
+                .entry(dataset_id.clone())
+                .or_insert_with(|| (epoch, HashSet::new()));
+            if reports.0 != epoch {
+                *reports = (epoch, HashSet::new());
+            }
+            reports.1.insert(worker_id.clone());
+        }
+        self.try_complete_epoch(dataset_id)
+    }
+
+    /// Healthy workers that reported the current epoch of a dataset, and how
+    /// many reports the epoch quorum requires
+    fn epoch_quorum(&self, dataset_id: &DatasetId) -> (Vec<WorkerId>, usize) {
+        let healthy: HashSet<WorkerId> = self
+            .workers
+            .find_workers(
+                &WorkerQuery::new()
+                    .not_in_state(CoreWorkerState::Error)
+                    .not_in_state(CoreWorkerState::Disconnecting)
+                    .not_in_state(CoreWorkerState::Dead),
+            )
+            .into_iter()
+            .map(|w| w.id)
+            .collect();
+        let required = ((healthy.len() as f64 * self.epoch_quorum).ceil() as usize).max(1);
+
+        let current = self.shard_manager.current_epoch(dataset_id);
+        let mut reported: Vec<WorkerId> = match self.epoch_reports.get(dataset_id) {
+            Some(reports) if reports.0 == current => reports
+                .1
+                .iter()
+                .filter(|w| healthy.contains(*w))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+        reported.sort();
+        (reported, required)
+    }
+
+    /// Advance a dataset's epoch if enough healthy workers have reported
+    ///
+    /// Returns `(advanced, reported, required)`.
+    fn try_complete_epoch(&self, dataset_id: &DatasetId) -> (bool, usize, usize) {
+        let current = self.shard_manager.current_epoch(dataset_id);
+        let (reported, required) = self.epoch_quorum(dataset_id);
+        let reported = reported.len();
+
+        if reported < required {
+            return (false, reported, required);
+        }
+
+        // Only the caller that removes the reports advances the epoch
+        let claimed = self
+            .epoch_reports
+            .remove_if(dataset_id, |_, reports| reports.0 == current)
+            .is_some();
+        if claimed {
+            if let Some(epoch) = self.advance_epoch(dataset_id) {
+                info!(
+                    dataset_id = %dataset_id,
+                    epoch = epoch,
+                    reported = reported,
+                    required = required,
+                    "Epoch quorum reached, advanced epoch"
+                );
+            }
+        }
+        (claimed, reported, required)
+    }
+
+    /// Re-check pending epoch quorums after workers left the cluster
+    fn recheck_epoch_quorums(&self) {
+        let datasets: Vec<DatasetId> = self.epoch_reports.iter().map(|e| e.key().clone()).collect();
+        for dataset_id in datasets {
+            self.try_complete_epoch(&dataset_id);
+        }
+    }
+
+    /// Advance the epoch of a dataset and notify watchers
+    pub fn advance_epoch(&self, dataset_id: &DatasetId) -> Option<u64> {
+        let epoch = self.shard_manager.advance_epoch(dataset_id)?;
+        self.events.publish(CoordinatorEvent::EpochAdvanced {
+            dataset_id: dataset_id.clone(),
+            epoch,
+        });
+        self.refresh_tasks();
+        Some(epoch)
+    }
+
+    /// Advance a dataset's epoch on request, ignoring the epoch quorum
+    ///
+    /// With `expected_epoch` set, the epoch only advances if it is still the
+    /// current one, so retried requests do not skip epochs.
+    pub fn force_advance_epoch(
+        &self,
+        dataset_id: &DatasetId,
+        expected_epoch: Option<u64>,
+    ) -> Result<u64, Status> {
+        if !self.datasets.contains_key(dataset_id) {
+            return Err(Status::not_found(format!(
+                "Dataset {} not registered",
+                dataset_id
+            )));
+        }
+        let current = self.shard_manager.current_epoch(dataset_id);
+        if let Some(expected) = expected_epoch.filter(|e| *e != current) {
+            return Err(Status::failed_precondition(format!(
+                "Dataset {} is at epoch {}, expected {}",
+                dataset_id, current, expected
+            )));
+        }
+
+        self.epoch_reports.remove(dataset_id);
+        let epoch = self
+            .advance_epoch(dataset_id)
+            .ok_or_else(|| Status::not_found(format!("Dataset {} not registered", dataset_id)))?;
+        info!(dataset_id = %dataset_id, epoch = epoch, "Epoch advanced on request");
+        Ok(epoch)
+    }
+
+    /// Release or abort the open generations of a stuck barrier
+    ///
+    /// `generation` of `None` closes every open generation. Fails with
+    /// `NotFound` if no matching barrier is open.
+    pub fn close_barrier(
+        &self,
+        barrier_id: &str,
+        generation: Option<u64>,
+        abort: bool,
+        reason: &str,
+    ) -> Result<Vec<BarrierInfo>, Status> {
+        let closed = if abort {
+            self.barriers.abort(barrier_id, generation)
+        } else {
+            self.barriers.force_release(barrier_id, generation)
+        };
+        if closed.is_empty() {
+            return Err(Status::not_found(format!(
+                "No open barrier {}{}",
+                barrier_id,
+                generation
+                    .map(|g| format!(" at generation {}", g))
+                    .unwrap_or_default()
+            )));
+        }
+
+        for barrier in &closed {
+            warn!(
+                barrier_id = %barrier.barrier_id,
+                generation = barrier.generation,
+                aborted = abort,
+                reason = %reason,
+                "Barrier closed on request"
+            );
+            self.events.publish(CoordinatorEvent::BarrierClosed {
+                barrier_id: barrier.barrier_id.clone(),
+                generation: barrier.generation,
+                arrived: barrier.arrived,
+                aborted: abort,
+                reason: reason.to_string(),
+            });
+        }
+        Ok(closed)
+    }
+
+    /// Convert a registry membership event into a worker watch event
+    ///
+    /// Returns `None` for the removal of a worker already reported dead.
+    fn to_worker_event(&self, event: &MembershipEvent) -> Option<proto::WorkerEvent> {
+        use proto::worker_event::Kind;
+
+        let (kind, rank, state) = match event.change {
+            MembershipChange::Joined => (Kind::Joined, event.rank as i32, CoreWorkerState::Idle),
+            MembershipChange::Left => (Kind::Left, -1, CoreWorkerState::Disconnecting),
+            MembershipChange::Dead => (Kind::Dead, -1, CoreWorkerState::Dead),
+            MembershipChange::Removed => return None,
+            MembershipChange::StateChanged { to, .. } => {
+                (Kind::StateChanged, event.rank as i32, to)
+            }
+            MembershipChange::RankChanged { .. } => {
+                let state = self
+                    .workers
+                    .get(&event.worker_id)
+                    .map(|w| w.state)
+                    .unwrap_or(CoreWorkerState::Idle);
+                (Kind::RankChanged, event.rank as i32, state)
+            }
+        };
+
+        Some(proto::WorkerEvent {
+            kind: kind as i32,
+            worker_id: event.worker_id.to_string(),
+            rank,
+            state: Self::core_to_proto_state(state) as i32,
+            membership_generation: event.generation as i64,
+            timestamp_ms: event.timestamp.timestamp_millis(),
+        })
+    }
+
+    // ========== HTTP API Helper Methods ==========
+
+    /// Get server uptime in seconds
+    pub fn uptime_secs(&self) -> u64 {
+        self.start_time.elapsed().as_secs()
+    }
+
+    /// Get workers for API response
+    pub fn get_workers_for_api(&self) -> Vec<WorkerResponse> {
+        self.workers
+            .all_workers()
+            .iter()
+            .map(|w| self.worker_response(w))
+            .collect()
+    }
+
+    /// Get a worker with its recent activity for API response
+    pub fn get_worker_detail_for_api(
+        &self,
+        worker_id: &WorkerId,
+    ) -> Result<WorkerDetailResponse, Status> {
+        let worker = self
+            .workers
+            .get(worker_id)
+            .ok_or_else(|| Status::not_found(format!("Worker {} not registered", worker_id)))?;
+        let activity = self.worker_history.get(worker_id).unwrap_or_default();
+
+        Ok(WorkerDetailResponse {
+            worker: self.worker_response(&worker),
+            rank: worker.rank,
+            world_size: self.workers.world_size() as u32,
+            registered_at: worker.registered_at.timestamp_millis(),
+            metadata: worker.metadata.into_iter().collect(),
+            labels: worker.labels.into_iter().collect(),
+            taints: worker.taints.iter().map(Taint::to_string).collect(),
+            shards: self
+                .shard_manager
+                .worker_shards(worker_id)
+                .into_iter()
+                .map(|(dataset_id, shards)| (dataset_id.into(), shards))
+                .collect(),
+            resource_history: activity.resources.into(),
+            io_rates: worker.resource_window.rates(),
+            gpus: worker.resources.gpu_summary(),
+            transitions: activity.transitions.into(),
+            metrics: activity
+                .metrics
+                .into_iter()
+                .map(|(name, samples)| (name, samples.into()))
+                .collect(),
+            last_error: activity.last_error,
+        })
+    }
+
+    fn worker_response(&self, w: &runtime_core::WorkerInfo) -> WorkerResponse {
+        WorkerResponse {
+            id: w.id.to_string(),
+            ip: w.hostname.clone(),
+            port: w.port,
+            status: self.worker_status(w).to_string(),
+            gpu_count: w.gpu_count,
+            last_heartbeat: w.last_heartbeat.timestamp_millis(),
+            assigned_shards: self
+                .shard_manager
+                .worker_shards(&w.id)
+                .values()
+                .map(|shards| shards.len() as u32)
+                .sum(),
+            current_epoch: w.current_epoch,
+            current_step: w.current_step,
+            current_task: w.current_task.clone(),
+        }
+    }
+
+    /// Dashboard status of a worker
+    fn worker_status(&self, worker: &runtime_core::WorkerInfo) -> &'static str {
+        match worker.state {
+            _ if self.is_draining(&worker.id) => "draining",
+            CoreWorkerState::Idle => "idle",
+            CoreWorkerState::Training => "active",
+            CoreWorkerState::Error => "failed",
+            _ => "unknown",
+        }
+    }
+
+    /// Get datasets for API response
+    pub fn get_datasets_for_api(&self) -> Vec<DatasetResponse> {
+        self.datasets
+            .iter()
+            .map(|entry| {
+                let d = entry.value();
+                let shard_count = (d.total_samples as f64 / d.shard_size as f64).ceil() as u64;
+                let progress = self.shard_manager.epoch_progress(entry.key());
+                DatasetResponse {
+                    id: d.dataset_id.clone(),
+                    name: d.dataset_id.clone(), // Use ID as name for now
+                    total_samples: d.total_samples as u64,
+                    shard_size: d.shard_size as u64,
+                    shard_count,
+                    format: d.format.clone(),
+                    shuffle: d.shuffle,
+                    // Note: Using current time as registration time since we don't persist this yet
+                    // In production, this should be stored when dataset is first registered
+                    registered_at: Utc::now().timestamp_millis(),
+                    current_epoch: progress.as_ref().map(|p| p.epoch).unwrap_or(0),
+                    epoch_progress: progress.as_ref().map(|p| p.fraction()).unwrap_or(0.0),
+                    eta_seconds: progress.and_then(|p| p.eta()).map(|eta| eta.as_secs()),
+                }
+            })
+            .collect()
+    }
+
+    /// Get the epoch state of a dataset for API response
+    pub fn get_epoch_for_api(&self, dataset_id: &DatasetId) -> Result<EpochResponse, Status> {
+        let progress = self
+            .datasets
+            .contains_key(dataset_id)
+            .then(|| self.shard_manager.epoch_progress(dataset_id))
+            .flatten()
+            .ok_or_else(|| Status::not_found(format!("Dataset {} not registered", dataset_id)))?;
+        let (workers_reported, workers_required) = self.epoch_quorum(dataset_id);
+
+        Ok(EpochResponse {
+            dataset_id: dataset_id.to_string(),
+            epoch: progress.epoch,
+            samples_consumed: progress.samples_consumed,
+            total_samples: progress.total_samples,
+            completed_shards: progress.completed_shards,
+            total_shards: progress.total_shards,
+            progress: progress.fraction(),
+            elapsed_seconds: progress.elapsed.as_secs(),
+            eta_seconds: progress.eta().map(|eta| eta.as_secs()),
+            workers_reported: workers_reported.into_iter().map(String::from).collect(),
+            workers_required,
+        })
+    }
+
+    /// Get a dataset's shards, files and epoch state for API response
+    ///
+    /// Files are listed only for local paths under `coordinator.dataset_roots`,
+    /// and `preview_lines` lines of the first one are included when non-zero
+    /// and `coordinator.dataset_preview` allows it.
+    pub async fn get_dataset_detail_for_api(
+        &self,
+        dataset_id: &DatasetId,
+        preview_lines: usize,
+    ) -> Result<DatasetDetailResponse, Status> {
+        if preview_lines > MAX_DATASET_PREVIEW_LINES {
+            return Err(invalid_field(
+                "preview",
+                Status::invalid_argument(format!(
+                    "preview must be at most {} lines",
+                    MAX_DATASET_PREVIEW_LINES
+                )),
+            ));
+        }
+        let info = self
+            .datasets
+            .get(dataset_id)
+            .map(|d| d.clone())
+            .ok_or_else(|| Status::not_found(format!("Dataset {} not registered", dataset_id)))?;
+        let epoch = self.get_epoch_for_api(dataset_id)?;
+
+        let total_samples = info.total_samples as u64;
+        let shard_size = info.shard_size as u64;
+        let owners = self.shard_manager.shard_owners(dataset_id);
+        let consumed = self.shard_manager.shard_progress(dataset_id);
+        let shards = (0..epoch.total_shards)
+            .take(MAX_DATASET_DETAIL_ITEMS)
+            .map(|shard_id| {
+                let start_index = shard_id * shard_size;
+                ShardResponse {
+                    shard_id,
+                    start_index,
+                    end_index: (start_index + shard_size).min(total_samples),
+                    samples_consumed: consumed.get(&shard_id).copied().unwrap_or(0),
+                    worker_id: owners.get(&shard_id).map(WorkerId::to_string),
+                }
+            })
+            .collect();
+
+        let (roots, preview_enabled) = {
+            let config = self.config.borrow();
+            let roots = if config.coordinator.dataset_roots.is_empty() {
+                vec![config.storage.base_path.clone()]
+            } else {
+                config.coordinator.dataset_roots.clone()
+            };
+            (roots, config.coordinator.dataset_preview)
+        };
+        if preview_lines > 0 && !preview_enabled {
+            return Err(Status::permission_denied(
+                "Dataset previews are disabled, set coordinator.dataset_preview to allow them",
+            ));
+        }
+
+        // One file more than is shown tells whether the list was cut short
+        let (storage, mut files) =
+            match dataset_files(&info.path, &roots, MAX_DATASET_DETAIL_ITEMS + 1).await? {
+                Some((storage, files)) => (Some(storage), files),
+                None => (None, Vec::new()),
+            };
+        let files_truncated = files.len() > MAX_DATASET_DETAIL_ITEMS;
+        files.truncate(MAX_DATASET_DETAIL_ITEMS);
+
+        let preview = match (&storage, files.first()) {
+            (Some(storage), Some(file)) if preview_lines > 0 => {
+                let data = storage.read_range(file, 0, DATASET_PREVIEW_BYTES).await?;
+                let (lines, binary, mut truncated) = preview_lines_of(&data, preview_lines);
+                truncated |= data.len() as u64 == DATASET_PREVIEW_BYTES;
+                Some(DatasetPreviewResponse {
+                    path: storage
+                        .base_path()
+                        .join(file)
+                        .to_string_lossy()
+                        .into_owned(),
+                    lines,
+                    binary,
+                    truncated,
+                })
+            }
+            _ => None,
+        };
+
+        let file_count = files.len();
+        let file_paths = match &storage {
+            Some(storage) => files
+                .iter()
+                .map(|file| {
+                    storage
+                        .base_path()
+                        .join(file)
+                        .to_string_lossy()
+                        .into_owned()
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+
+        Ok(DatasetDetailResponse {
+            id: info.dataset_id,
+            path: info.path,
+            format: info.format,
+            total_samples,
+            shard_size,
+            shard_count: epoch.total_shards,
+            shuffle: info.shuffle,
+            seed: info.seed as u64,
+            metadata: info.metadata.into_iter().collect(),
+            file_paths,
+            file_count,
+            files_truncated,
+            epoch,
+            shards,
+            preview,
+        })
+    }
+
+    /// Get checkpoints for API response
+    pub fn get_checkpoints_for_api(&self) -> Vec<CheckpointResponse> {
+        self.checkpoint_manager
+            .all_checkpoints()
+            .into_iter()
+            .take(20)
+            .map(|c| CheckpointResponse {
+                id: c.id.into(),
+                step: c.step,
+                epoch: c.epoch,
+                size: c.size_bytes,
+                path: c.path,
+                created_at: c.created_at.timestamp_millis(),
+                worker_id: c.metadata.get("worker_id").cloned().unwrap_or_default(),
+                status: "completed".to_string(), // All checkpoints in the list are completed
+            })
+            .collect()
+    }
+
+    /// Get barriers for API response
+    pub fn get_barriers_for_api(&self) -> Vec<ApiBarrierResponse> {
+        self.barriers
+            .snapshot()
+            .into_iter()
+            .map(api_barrier)
+            .collect()
+    }
+
+    /// Release or abort a barrier, for API response
+    pub fn close_barrier_for_api(
+        &self,
+        barrier_id: &str,
+        generation: Option<u64>,
+        abort: bool,
+        reason: &str,
+    ) -> Result<Vec<ApiBarrierResponse>, Status> {
+        Ok(self
+            .close_barrier(barrier_id, generation, abort, reason)?
+            .into_iter()
+            .map(api_barrier)
+            .collect())
+    }
+
+    /// Workers that are idle or training
+    fn active_worker_count(&self) -> u32 {
+        self.workers.count_workers(
+            &WorkerQuery::new()
+                .in_state(CoreWorkerState::Training)
+                .in_state(CoreWorkerState::Idle),
+        ) as u32
+    }
+
+    /// Get metrics for API response
+    pub fn get_metrics_for_api(&self) -> MetricsResponse {
+        let active_workers = self.active_worker_count();
+
+        // Calculate actual metrics from tracked data
+        let uptime = self.uptime_secs().max(1);
+        let total_requests = self.request_metrics.total_requests();
+        let latency_ms = |method: &str, percentile: f64| {
+            self.request_metrics
+                .get_latency_percentile(method, percentile)
+                .map(|us| us / 1000)
+                .unwrap_or(0)
+        };
+
+        MetricsResponse {
+            // Checkpoint throughput: checkpoints per minute since startup
+            checkpoint_throughput: self.checkpoints_committed.get() * 60 / uptime,
+            // Coordinator requests per second
+            coordinator_rps: total_requests / uptime,
+            active_workers,
+            total_workers: self.workers.world_size() as u32,
+            // Barrier latency P99 in ms, including time spent waiting for peers
+            barrier_latency_p99: latency_ms("WaitBarrier", 0.99),
+            // Median shard assignment time in ms
+            shard_assignment_time: latency_ms("GetDataShard", 0.5),
+            duplicate_checkpoint_notifications: self.checkpoint_acks.duplicates(),
+            io_rates: self.workers.aggregate_rates(),
+            gpus: self.workers.gpu_summary(),
+        }
+    }
+
+    /// Render coordinator metrics in the Prometheus text format
+    pub fn render_prometheus(&self) -> String {
+        let mut out = PrometheusWriter::new();
+
+        out.family(
+            "strata_uptime_seconds",
+            MetricType::Gauge,
+            "Seconds since the coordinator started",
+        )
+        .sample("strata_uptime_seconds", &[], self.uptime_secs() as f64);
+
+        let mut by_status: BTreeMap<&str, usize> =
+            ["active", "idle", "draining", "failed", "unknown"]
+                .into_iter()
+                .map(|status| (status, 0))
+                .collect();
+        for worker in self.workers.all_workers() {
+            *by_status.entry(self.worker_status(&worker)).or_default() += 1;
+        }
+        out.family(
+            "strata_workers",
+            MetricType::Gauge,
+            "Registered workers by status",
+        );
+        for (status, count) in &by_status {
+            out.sample("strata_workers", &[("status", status)], *count as f64);
+        }
+
+        out.family(
+            "strata_gpus",
+            MetricType::Gauge,
+            "GPUs across workers that are not dead",
+        )
+        .sample("strata_gpus", &[], self.workers.total_gpu_count() as f64);
+
+        let gpus = self.workers.gpu_summary();
+        out.family(
+            "strata_gpu_utilization_percent",
+            MetricType::Gauge,
+            "Mean utilization of GPUs on active workers",
+        )
+        .sample(
+            "strata_gpu_utilization_percent",
+            &[],
+            gpus.avg_utilization_percent,
+        );
+        out.family(
+            "strata_gpu_power_watts",
+            MetricType::Gauge,
+            "Power draw of GPUs on active workers",
+        )
+        .sample("strata_gpu_power_watts", &[], gpus.power_draw_watts);
+        out.family(
+            "strata_gpu_ecc_errors_uncorrected",
+            MetricType::Gauge,
+            "Uncorrectable ECC errors of GPUs on active workers",
+        )
+        .sample(
+            "strata_gpu_ecc_errors_uncorrected",
+            &[],
+            gpus.ecc_errors_uncorrected as f64,
+        );
+
+        out.family("strata_datasets", MetricType::Gauge, "Registered datasets")
+            .sample("strata_datasets", &[], self.datasets.len() as f64);
+
+        let metrics = &self.request_metrics;
+        let methods = metrics.methods();
+        out.family(
+            "strata_rpc_requests_total",
+            MetricType::Counter,
+            "gRPC requests received, by method",
+        );
+        for method in &methods {
+            out.sample(
+                "strata_rpc_requests_total",
+                &[("method", method)],
+                metrics.get_request_count(method) as f64,
+            );
+        }
+        out.family(
+            "strata_rpc_errors_total",
+            MetricType::Counter,
+            "gRPC requests that failed, by method",
+        );
+        for method in &methods {
+            out.sample(
+                "strata_rpc_errors_total",
+                &[("method", method)],
+                metrics.get_error_count(method) as f64,
+            );
+        }
+        out.family(
+            "strata_rpc_latency_seconds",
+            MetricType::Summary,
+            "gRPC request latency, by method",
+        );
+        for method in &methods {
+            self.write_latency_summary(
+                &mut out,
+                "strata_rpc_latency_seconds",
+                method,
+                &[("method", method)],
+            );
+        }
+
+        out.family(
+            "strata_barrier_wait_seconds",
+            MetricType::Summary,
+            "Time workers spend waiting at barriers",
+        );
+        self.write_latency_summary(&mut out, "strata_barrier_wait_seconds", "WaitBarrier", &[]);
+
+        out.family(
+            "strata_checkpoint_duplicate_notifications_total",
+            MetricType::Counter,
+            "Retried checkpoint notifications answered from the dedup cache",
+        )
+        .sample(
+            "strata_checkpoint_duplicate_notifications_total",
+            &[],
+            self.checkpoint_acks.duplicates() as f64,
+        );
+
+        out.family(
+            "strata_shard_imbalance_ratio",
+            MetricType::Gauge,
+            "Most shards assigned to one worker divided by the mean, by dataset (1 is balanced)",
+        );
+        let mut datasets = self.shard_manager.datasets();
+        datasets.sort();
+        for dataset_id in &datasets {
+            let counts = self.shard_manager.shard_counts(dataset_id);
+            let total: usize = counts.iter().map(|(_, n)| n).sum();
+            if total == 0 {
+                continue;
+            }
+            let max = counts.iter().map(|(_, n)| *n).max().unwrap_or(0);
+            let mean = total as f64 / counts.len() as f64;
+            out.sample(
+                "strata_shard_imbalance_ratio",
+                &[("dataset", dataset_id.as_str())],
+                max as f64 / mean,
+            );
+        }
+
+        self.metrics.encode(&mut out);
+        out.finish()
+    }
+
+    /// Write quantile, sum and count samples for an RPC method's latency
+    fn write_latency_summary(
+        &self,
+        out: &mut PrometheusWriter,
+        name: &str,
+        method: &str,
+        labels: &[(&str, &str)],
+    ) {
+        let metrics = &self.request_metrics;
+        for (quantile, label) in [(0.5, "0.5"), (0.9, "0.9"), (0.99, "0.99")] {
+            if let Some(us) = metrics.get_latency_percentile(method, quantile) {
+                let mut labels = labels.to_vec();
+                labels.push(("quantile", label));
+                out.sample(name, &labels, us as f64 / 1e6);
+            }
+        }
+        let (count, sum_us) = metrics.latency_totals(method);
+        out.sample(&format!("{}_sum", name), labels, sum_us as f64 / 1e6);
+        out.sample(&format!("{}_count", name), labels, count as f64);
+    }
+}
+
+#[tonic::async_trait]
+impl Coordinator for CoordinatorService {
+    /// Register a new worker with the coordinator
+    async fn register_worker(
+        &self,
+        request: Request<WorkerInfo>,
+    ) -> Result<Response<WorkerConfig>, Status> {
+        let info = request.into_inner();
+        info!(
+            worker_id = %info.worker_id,
+            hostname = %info.hostname,
+            port = info.port,
+            gpu_count = info.gpu_count,
+            protocol_version = info.protocol_version,
+            "Worker registration request"
+        );
+
+        if self.is_shutting_down() {
+            return Err(shutting_down_status());
+        }
+
+        let worker_id: WorkerId = parse_id("worker_id", &info.worker_id)?;
+        if let Some(reason) = self.blacklist.get(&worker_id) {
+            return Err(Status::permission_denied(format!(
+                "Worker {} is blacklisted: {}",
+                info.worker_id,
+                reason.value()
+            )));
+        }
+
+        let protocol_version = protocol::negotiate(info.protocol_version)?;
+        if protocol_version < PROTOCOL_VERSION {
+            warn!(
+                worker_id = %info.worker_id,
+                client_version = info.protocol_version,
+                server_version = PROTOCOL_VERSION,
+                "Worker speaks an older protocol, newer features are disabled"
+            );
+        }
+
+        runtime_core::labels::validate_labels(&info.labels)
+            .map_err(|e| invalid_field("labels", e.into()))?;
+        let taints = info
+            .taints
+            .iter()
+            .map(|taint| parse_id("taints", taint))
+            .collect::<Result<Vec<Taint>, _>>()?;
+
+        // Create core worker info
+        let mut core_info = CoreWorkerInfo::new(
+            worker_id.clone(),
+            info.hostname.clone(),
+            info.port as u16,
+            0, // rank assigned by registry
+            0, // world_size updated after registration
+        );
+        core_info.gpu_count = info.gpu_count.max(0) as u32;
+        core_info.memory_bytes = info.memory_bytes.max(0) as u64;
+        core_info.metadata = info.metadata.clone();
+        core_info.labels = info.labels;
+        core_info.taints = taints;
+
+        // Register with worker registry
+        let registered = self.workers.register(core_info)?;
+
+        // Also register with shard manager for data distribution
+        self.shard_manager.register_worker(&worker_id);
+        self.protocol_versions
+            .insert(registered.id.clone(), protocol_version);
+        self.worker_history.remove(&registered.id);
+        self.worker_history
+            .record_transition(&registered.id, None, registered.state);
+        self.events.publish(CoordinatorEvent::WorkerJoined {
+            worker_id: registered.id.clone(),
+            rank: registered.rank,
+        });
+
+        // Build response
+        let config = WorkerConfig {
+            assigned_id: registered.id.to_string(),
+            rank: registered.rank as i32,
+            world_size: self.workers.world_size() as i32,
+            heartbeat_interval_ms: self.heartbeat_interval_ms as i64,
+            config: info.metadata,
+            membership_generation: self.workers.generation() as i64,
+            server_protocol_version: PROTOCOL_VERSION,
+            protocol_version,
+            session: registered.session as i64,
+        };
+
+        info!(
+            worker_id = %registered.id,
+            rank = registered.rank,
+            world_size = config.world_size,
+            generation = config.membership_generation,
+            "Worker registered successfully"
+        );
+
+        Ok(Response::new(config))
+    }
+
+    /// Process worker heartbeat
+    async fn heartbeat(
+        &self,
+        request: Request<HeartbeatRequest>,
+    ) -> Result<Response<HeartbeatResponse>, Status> {
+        let hb = request.into_inner();
+        let response = self.process_heartbeat(hb)?;
+        Ok(Response::new(response))
+    }
+
+    /// Deregister a worker
+    async fn deregister_worker(
+        &self,
+        request: Request<WorkerInfo>,
+    ) -> Result<Response<WorkerConfig>, Status> {
+        let info = request.into_inner();
+        info!(worker_id = %info.worker_id, "Worker deregistration request");
+
+        let worker_id = parse_id("worker_id", &info.worker_id)?;
+        self.remove_worker(&worker_id).map(Response::new)
+    }
+
+    /// Register a dataset for sharding
+    async fn register_dataset(
+        &self,
+        request: Request<DatasetInfo>,
+    ) -> Result<Response<DatasetAck>, Status> {
+        let info = request.into_inner();
+        info!(
+            dataset_id = %info.dataset_id,
+            total_samples = info.total_samples,
+            shard_size = info.shard_size,
+            "Dataset registration request"
+        );
+
+        if self.is_shutting_down() {
+            return Err(shutting_down_status());
+        }
+
+        let (dataset_id, total_shards) = self.validate_dataset(&info)?;
+
+        // Re-registering is idempotent, but only with identical parameters
+        if let Some(existing) = self.datasets.get(&dataset_id) {
+            if *existing == info {
+                return Ok(Response::new(DatasetAck {
+                    success: true,
+                    dataset_id: info.dataset_id,
+                    total_shards: total_shards as i64,
+                    message: format!("Dataset already registered with {} shards", total_shards),
+                }));
+            }
+            return Err(invalid_field(
+                "dataset_id",
+                Status::already_exists(format!(
+                    "Dataset {} is already registered with different parameters",
+                    info.dataset_id
+                )),
+            ));
+        }
+
+        // Register with shard manager
+        self.shard_manager.register_dataset_params(
+            &dataset_id,
+            info.total_samples as u64,
+            info.shard_size as u64,
+            info.shuffle,
+            info.seed as u64,
+        );
+
+        // Track dataset info
+        self.datasets.insert(dataset_id.clone(), info.clone());
+        self.events
+            .publish(CoordinatorEvent::DatasetRegistered { dataset_id });
+
+        Ok(Response::new(DatasetAck {
+            success: true,
+            dataset_id: info.dataset_id,
+            total_shards: total_shards as i64,
+            message: format!("Dataset registered with {} shards", total_shards),
+        }))
+    }
+
+    /// Get shard assignment for a worker
+    async fn get_data_shard(
+        &self,
+        request: Request<ShardRequest>,
+    ) -> Result<Response<ShardAssignment>, Status> {
+        let req = request.into_inner();
+        if SHARD_REQUEST_LOG.sample() {
+            debug!(
+                worker_id = %req.worker_id,
+                dataset_id = %req.dataset_id,
+                epoch = req.epoch,
+                sample_rate = SHARD_REQUEST_LOG.rate(),
+                "Shard request"
+            );
+        }
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+        let dataset_id: DatasetId = parse_id("dataset_id", &req.dataset_id)?;
+
+        // Get dataset info
+        let dataset_info = self
+            .datasets
+            .get(&dataset_id)
+            .ok_or_else(|| Status::not_found(format!("Dataset not found: {}", req.dataset_id)))?;
+
+        let epoch = self.requested_epoch(&dataset_id, &req);
+
+        // Get shard assignments from manager
+        let shards = self
+            .shard_manager
+            .get_shard_for_worker(&dataset_id, &worker_id, epoch)
+            .ok_or_else(|| {
+                Status::internal(format!(
+                    "Failed to get shards for worker {} on dataset {}",
+                    req.worker_id, req.dataset_id
+                ))
+            })?;
+
+        let (count, prefetch_depth) = plan_shard_batch(&req, &dataset_info, shards.len());
+        let Some(primary) = shards.first() else {
+            return Err(Status::not_found("No shards available for this worker"));
+        };
+        let total_shards =
+            (dataset_info.total_samples as f64 / dataset_info.shard_size as f64).ceil() as i64;
+
+        Ok(Response::new(ShardAssignment {
+            dataset_id: req.dataset_id,
+            shard_id: primary.shard_id as i64,
+            total_shards,
+            start_index: primary.start_index as i64,
+            end_index: primary.end_index as i64,
+            file_paths: vec![dataset_info.path.clone()],
+            epoch: epoch as i64,
+            membership_generation: self.workers.generation() as i64,
+            shards: shards
+                .iter()
+                .take(count)
+                .map(|shard| ShardRange {
+                    shard_id: shard.shard_id as i64,
+                    start_index: shard.start_index as i64,
+                    end_index: shard.end_index as i64,
+                })
+                .collect(),
+            prefetch_depth: prefetch_depth as i32,
+        }))
+    }
+
+    /// Get every shard assigned to a worker for an epoch
+    ///
+    /// Unlike `get_data_shard` the whole assignment is returned, capped only
+    /// by `max_shards`; resource hints are ignored.
+    async fn get_data_shards(
+        &self,
+        request: Request<ShardRequest>,
+    ) -> Result<Response<ShardAssignmentList>, Status> {
+        let req = request.into_inner();
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+        let dataset_id: DatasetId = parse_id("dataset_id", &req.dataset_id)?;
+
+        let dataset_info = self
+            .datasets
+            .get(&dataset_id)
+            .ok_or_else(|| Status::not_found(format!("Dataset not found: {}", req.dataset_id)))?;
+        let epoch = self.requested_epoch(&dataset_id, &req);
+        let mut assignments = self
+            .worker_shard_assignments(&dataset_id, &dataset_info, &worker_id, epoch)
+            .ok_or_else(|| {
+                Status::internal(format!(
+                    "Failed to get shards for worker {} on dataset {}",
+                    req.worker_id, req.dataset_id
+                ))
+            })?;
+        if req.max_shards > 0 {
+            assignments.truncate(req.max_shards as usize);
+        }
+
+        Ok(Response::new(ShardAssignmentList {
+            dataset_id: req.dataset_id,
+            epoch: epoch as i64,
+            membership_generation: self.workers.generation() as i64,
+            assignments,
+        }))
+    }
+
+    /// Notify coordinator of a completed checkpoint
+    async fn notify_checkpoint(
+        &self,
+        request: Request<CheckpointInfo>,
+    ) -> Result<Response<CheckpointAck>, Status> {
+        let info = request.into_inner();
+
+        // Validate required fields
+        let checkpoint_id: CheckpointId = parse_id("checkpoint_id", &info.checkpoint_id)?;
+        let worker_id: WorkerId = parse_id("worker_id", &info.worker_id)?;
+        if info.step < 0 {
+            return Err(Status::invalid_argument("step must be non-negative"));
+        }
+        if info.size_bytes < 0 {
+            return Err(Status::invalid_argument("size_bytes must be non-negative"));
+        }
+
+        info!(
+            worker_id = %info.worker_id,
+            checkpoint_id = %info.checkpoint_id,
+            step = info.step,
+            epoch = info.epoch,
+            size_bytes = info.size_bytes,
+            "Checkpoint notification"
+        );
+
+        // Retries of an already processed notification get the original ack
+        let (ack, duplicate) = self
+            .checkpoint_acks
+            .get_or_insert_with(&info.checkpoint_id, || {
+                // Register this checkpoint from the remote worker
+                let mut metadata = info.metadata.clone();
+                metadata.insert("worker_id".to_string(), info.worker_id.clone());
+
+                let evicted = self.checkpoint_manager.register_external_checkpoint(
+                    &checkpoint_id,
+                    info.step as u64,
+                    info.epoch as u64,
+                    &info.storage_path,
+                    info.size_bytes as u64,
+                    metadata,
+                );
+
+                self.checkpoint_scheduler
+                    .record_checkpoint(info.step as u64);
+                self.checkpoints_committed.inc();
+                self.checkpoint_bytes.inc_by(info.size_bytes as u64);
+                for txn_id in self.checkpoint_txns.record_checkpoint(
+                    &worker_id,
+                    &checkpoint_id,
+                    info.step as u64,
+                ) {
+                    info!(txn_id = %txn_id, step = info.step, "Cluster checkpoint completed");
+                }
+                self.events.publish(CoordinatorEvent::CheckpointCommitted {
+                    checkpoint_id: checkpoint_id.clone(),
+                    step: info.step as u64,
+                    worker_id: worker_id.clone(),
+                });
+
+                // Checkpoints dropped by the retention policy must also go on every worker
+                for ckpt in &evicted {
+                    self.broadcast_command(WorkerCommand::DeleteCheckpoint {
+                        checkpoint_id: ckpt.id.clone(),
+                    });
+                }
+
+                CheckpointAck {
+                    success: true,
+                    checkpoint_id: info.checkpoint_id.clone(),
+                    message: "Checkpoint acknowledged".to_string(),
+                    global_step: info.step,
+                }
+            });
+
+        if duplicate {
+            warn!(
+                worker_id = %info.worker_id,
+                checkpoint_id = %info.checkpoint_id,
+                "Duplicate checkpoint notification, returning original ack"
+            );
+        }
+
+        Ok(Response::new(ack))
+    }
+
+    /// Garbage-collect checkpoints outside the retention policy
+    async fn prune_checkpoints(
+        &self,
+        request: Request<PruneCheckpointsRequest>,
+    ) -> Result<Response<PruneCheckpointsResponse>, Status> {
+        let req = request.into_inner();
+        if req.keep_count < 0 {
+            return Err(Status::invalid_argument("keep_count must be non-negative"));
+        }
+
+        let keep_count = if req.keep_count > 0 {
+            req.keep_count as usize
+        } else {
+            self.checkpoint_manager.keep_count()
+        };
+
+        Ok(Response::new(
+            self.collect_checkpoint_garbage(keep_count, req.dry_run),
+        ))
+    }
+
+    /// Get latest checkpoint for recovery
+    async fn get_latest_checkpoint(
+        &self,
+        request: Request<RecoveryRequest>,
+    ) -> Result<Response<RecoveryResponse>, Status> {
+        let req = request.into_inner();
+        info!(
+            worker_id = %req.worker_id,
+            job_id = %req.job_id,
+            "Recovery request"
+        );
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+
+        // Get latest checkpoint from manager
+        let latest = self.checkpoint_manager.find_recovery_checkpoint();
+
+        if let Some(ckpt) = latest {
+            // Get shard assignments for all registered datasets
+            let mut shard_assignments = Vec::new();
+            for entry in self.datasets.iter() {
+                if let Some(assignments) = self.worker_shard_assignments(
+                    entry.key(),
+                    entry.value(),
+                    &worker_id,
+                    ckpt.epoch,
+                ) {
+                    shard_assignments.extend(assignments);
+                }
+            }
+
+            Ok(Response::new(RecoveryResponse {
+                has_checkpoint: true,
+                resume_step: ckpt.step as i64,
+                resume_epoch: ckpt.epoch as i64,
+                latest_checkpoint: Some(Self::checkpoint_to_proto(ckpt)),
+                shard_assignments,
+            }))
+        } else {
+            Ok(Response::new(RecoveryResponse {
+                has_checkpoint: false,
+                latest_checkpoint: None,
+                resume_step: 0,
+                resume_epoch: 0,
+                shard_assignments: vec![],
+            }))
+        }
+    }
+
+    /// Barrier synchronization
+    async fn wait_barrier(
+        &self,
+        request: Request<BarrierRequest>,
+    ) -> Result<Response<BarrierResponse>, Status> {
+        let req = request.into_inner();
+        let world_size = self.workers.world_size() as u64;
+
+        // Generation 0 means the client did not set one; fall back to the step
+        let generation = if req.generation > 0 {
+            req.generation
+        } else {
+            req.step.max(0)
+        } as u64;
+
+        info!(
+            worker_id = %req.worker_id,
+            barrier_id = %req.barrier_id,
+            generation = generation,
+            world_size = world_size,
+            "Barrier wait request"
+        );
+
+        if self.is_shutting_down() {
+            return Err(shutting_down_status());
+        }
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+
+        match self
+            .barriers
+            .arrive(&req.barrier_id, generation, &worker_id, world_size)
+        {
+            ArriveOutcome::Released {
+                participants,
+                arrival_order,
+            } => {
+                self.events.publish(CoordinatorEvent::BarrierReleased {
+                    barrier_id: req.barrier_id.clone(),
+                    generation,
+                    participants,
+                });
+                Ok(Response::new(BarrierResponse {
+                    released: true,
+                    barrier_id: req.barrier_id,
+                    participants: participants as i64,
+                    arrival_order: arrival_order as i64,
+                    already_released: false,
+                    generation: generation as i64,
+                    aborted: false,
+                    forced: false,
+                    expected: 0,
+                }))
+            }
+            ArriveOutcome::Aborted => {
+                warn!(
+                    worker_id = %req.worker_id,
+                    barrier_id = %req.barrier_id,
+                    generation = generation,
+                    "Worker arrived at an aborted barrier"
+                );
+                Ok(Response::new(aborted_barrier_response(
+                    req.barrier_id,
+                    generation,
+                    0,
+                )))
+            }
+            ArriveOutcome::AlreadyReleased { participants } => {
+                warn!(
+                    worker_id = %req.worker_id,
+                    barrier_id = %req.barrier_id,
+                    generation = generation,
+                    "Worker arrived at an already released barrier"
+                );
+                Ok(Response::new(BarrierResponse {
+                    released: true,
+                    barrier_id: req.barrier_id,
+                    participants: participants as i64,
+                    arrival_order: 0,
+                    already_released: true,
+                    generation: generation as i64,
+                    aborted: false,
+                    forced: false,
+                    expected: 0,
+                }))
+            }
+            ArriveOutcome::Waiting {
+                release,
+                arrival_order,
+                arrived,
+                expected,
+            } => {
+                if req.no_wait {
+                    return Ok(Response::new(BarrierResponse {
+                        released: false,
+                        barrier_id: req.barrier_id,
+                        participants: arrived as i64,
+                        arrival_order: arrival_order as i64,
+                        already_released: false,
+                        generation: generation as i64,
+                        aborted: false,
+                        forced: false,
+                        expected: expected as i64,
+                    }));
+                }
+
+                debug!(
+                    barrier_id = %req.barrier_id,
+                    worker_id = %req.worker_id,
+                    arrival_order = arrival_order,
+                    "Worker waiting at barrier"
+                );
+
+                match tokio::time::timeout(Duration::from_secs(300), release).await {
+                    Ok(Ok(BarrierRelease::Released {
+                        participants,
+                        forced,
+                    })) => Ok(Response::new(BarrierResponse {
+                        released: true,
+                        barrier_id: req.barrier_id,
+                        participants: participants as i64,
+                        arrival_order: arrival_order as i64,
+                        already_released: false,
+                        generation: generation as i64,
+                        aborted: false,
+                        forced,
+                        expected: 0,
+                    })),
+                    Ok(Ok(BarrierRelease::Aborted)) if self.is_shutting_down() => {
+                        Err(Status::unavailable(
+                            "Barrier aborted because the coordinator is shutting down",
+                        ))
+                    }
+                    Ok(Ok(BarrierRelease::Aborted)) => Ok(Response::new(aborted_barrier_response(
+                        req.barrier_id,
+                        generation,
+                        arrival_order,
+                    ))),
+                    Ok(Err(_)) => Err(Status::internal("Barrier channel closed")),
+                    Err(_) => Err(Status::deadline_exceeded("Barrier timeout")),
+                }
+            }
+        }
+    }
+
+    /// Record that a worker finished an epoch
+    async fn report_epoch_complete(
+        &self,
+        request: Request<EpochCompleteRequest>,
+    ) -> Result<Response<EpochCompleteResponse>, Status> {
+        let req = request.into_inner();
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+        let dataset_id: DatasetId = parse_id("dataset_id", &req.dataset_id)?;
+        if self.workers.get(&worker_id).is_none() {
+            return Err(Status::not_found(format!(
+                "Worker {} not registered",
+                req.worker_id
+            )));
+        }
+        self.workers
+            .check_session(&worker_id, req.session.max(0) as u64)?;
+        if !self.datasets.contains_key(&dataset_id) {
+            return Err(Status::not_found(format!(
+                "Dataset {} not registered",
+                req.dataset_id
+            )));
+        }
+        if req.epoch < 0 {
+            return Err(Status::invalid_argument("epoch must be non-negative"));
+        }
+
+        let epoch = req.epoch as u64;
+        let current = self.shard_manager.current_epoch(&dataset_id);
+        if epoch > current {
+            return Err(Status::failed_precondition(format!(
+                "Epoch {} has not started, current epoch is {}",
+                epoch, current
+            )));
+        }
+
+        // Late reports for an epoch that already advanced are acknowledged as-is
+        let (advanced, reported, required) = if epoch < current {
+            (false, 0, 0)
+        } else {
+            self.record_epoch_complete(&dataset_id, &worker_id, epoch)
+        };
+
+        debug!(
+            worker_id = %req.worker_id,
+            dataset_id = %req.dataset_id,
+            epoch = epoch,
+            reported = reported,
+            required = required,
+            "Epoch complete report"
+        );
+
+        Ok(Response::new(EpochCompleteResponse {
+            advanced,
+            current_epoch: self.shard_manager.current_epoch(&dataset_id) as i64,
+            reported: reported as i32,
+            required: required as i32,
+        }))
+    }
+
+    /// Set a rendezvous key
+    async fn kv_set(
+        &self,
+        request: Request<KvSetRequest>,
+    ) -> Result<Response<KvSetResponse>, Status> {
+        let req = request.into_inner();
+        validate_kv_key(&req.job_id, &req.key)?;
+        if req.value.len() > MAX_KV_VALUE_BYTES {
+            return Err(Status::invalid_argument(format!(
+                "Value exceeds maximum size of {} bytes",
+                MAX_KV_VALUE_BYTES
+            )));
+        }
+
+        debug!(job_id = %req.job_id, key = %req.key, bytes = req.value.len(), "KV set");
+        let created = self.kv.set(&req.job_id, &req.key, req.value);
+        Ok(Response::new(KvSetResponse { created }))
+    }
+
+    /// Get a rendezvous key without waiting
+    async fn kv_get(
+        &self,
+        request: Request<KvGetRequest>,
+    ) -> Result<Response<KvGetResponse>, Status> {
+        let req = request.into_inner();
+        validate_kv_key(&req.job_id, &req.key)?;
+
+        let value = self.kv.get(&req.job_id, &req.key);
+        Ok(Response::new(KvGetResponse {
+            found: value.is_some(),
+            value: value.unwrap_or_default(),
+        }))
+    }
+
+    /// Block until all requested keys are set
+    async fn kv_wait(
+        &self,
+        request: Request<KvWaitRequest>,
+    ) -> Result<Response<KvWaitResponse>, Status> {
+        let req = request.into_inner();
+        if req.keys.is_empty() {
+            return Err(Status::invalid_argument("keys cannot be empty"));
+        }
+        for key in &req.keys {
+            validate_kv_key(&req.job_id, key)?;
+        }
+        let timeout = match req.timeout_ms {
+            ms if ms < 0 => {
+                return Err(Status::invalid_argument("timeout_ms must be non-negative"))
+            }
+            0 => DEFAULT_KV_WAIT_TIMEOUT,
+            ms => Duration::from_millis(ms as u64).min(DEFAULT_KV_WAIT_TIMEOUT),
+        };
+
+        match self.kv.wait(&req.job_id, &req.keys, timeout).await {
+            Some(values) => Ok(Response::new(KvWaitResponse { values })),
+            None => Err(Status::deadline_exceeded(format!(
+                "Timed out waiting for keys in job {}",
+                req.job_id
+            ))),
+        }
+    }
+
+    /// Acquire a named lease
+    async fn acquire_lease(
+        &self,
+        request: Request<AcquireLeaseRequest>,
+    ) -> Result<Response<LeaseResponse>, Status> {
+        let req = request.into_inner();
+        let worker_id = validate_lease(&req.name, &req.worker_id)?;
+        let ttl = lease_ttl(req.ttl_ms)?;
+
+        let response = match self.leases.acquire(&req.name, &worker_id, ttl) {
+            Ok(grant) => lease_response(true, grant),
+            Err(LeaseError::Held(grant)) => lease_response(false, grant),
+            Err(LeaseError::NotHeld) => {
+                return Err(Status::internal("Lease acquisition failed"));
+            }
+        };
+        Ok(Response::new(response))
+    }
+
+    /// Extend a held lease
+    async fn renew_lease(
+        &self,
+        request: Request<RenewLeaseRequest>,
+    ) -> Result<Response<LeaseResponse>, Status> {
+        let req = request.into_inner();
+        let worker_id = validate_lease(&req.name, &req.worker_id)?;
+        let ttl = lease_ttl(req.ttl_ms)?;
+
+        match self
+            .leases
+            .renew(&req.name, &worker_id, req.token as u64, ttl)
+        {
+            Ok(grant) => Ok(Response::new(lease_response(true, grant))),
+            Err(_) => Err(Status::failed_precondition(format!(
+                "Worker {} does not hold lease {}",
+                req.worker_id, req.name
+            ))),
+        }
+    }
+
+    /// Release a held lease
+    async fn release_lease(
+        &self,
+        request: Request<ReleaseLeaseRequest>,
+    ) -> Result<Response<ReleaseLeaseResponse>, Status> {
+        let req = request.into_inner();
+        let worker_id = validate_lease(&req.name, &req.worker_id)?;
+
+        let released = self.leases.release(&req.name, &worker_id, req.token as u64);
+        Ok(Response::new(ReleaseLeaseResponse { released }))
+    }
+
+    /// Audit events recorded after a sequence number
+    async fn get_events(
+        &self,
+        request: Request<GetEventsRequest>,
+    ) -> Result<Response<GetEventsResponse>, Status> {
+        let req = request.into_inner();
+        if req.after_seq < 0 {
+            return Err(Status::invalid_argument("after_seq must be non-negative"));
+        }
+        let limit = match req.limit {
+            0 => DEFAULT_EVENTS_LIMIT,
+            n if n < 0 => return Err(Status::invalid_argument("limit must be non-negative")),
+            n => n as usize,
+        };
+
+        let log = self.events.log();
+        let events = log
+            .since(req.after_seq as u64, limit)
+            .into_iter()
+            .map(|e| {
+                let payload = serde_json::to_value(&e.event)
+                    .map_err(|e| Status::internal(format!("Failed to encode event: {}", e)))?;
+                Ok(EventRecord {
+                    seq: e.seq as i64,
+                    id: e.id,
+                    timestamp_ms: e.timestamp_ms,
+                    r#type: payload["type"].as_str().unwrap_or_default().to_string(),
+                    payload_json: payload.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        Ok(Response::new(GetEventsResponse {
+            events,
+            last_seq: log.last_seq() as i64,
+        }))
+    }
+
+    /// Add worker log lines to the coordinator's log buffer
+    async fn ship_logs(
+        &self,
+        request: Request<ShipLogsRequest>,
+    ) -> Result<Response<ShipLogsResponse>, Status> {
+        let req = request.into_inner();
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+        if self.workers.get(&worker_id).is_none() {
+            return Err(Status::not_found(format!(
+                "Worker {} not registered",
+                worker_id
+            )));
+        }
+        if req.lines.len() > MAX_SHIPPED_LOG_LINES {
+            return Err(Status::invalid_argument(format!(
+                "Too many log lines: {} (max {})",
+                req.lines.len(),
+                MAX_SHIPPED_LOG_LINES
+            )));
+        }
+
+        // Validate the whole batch before recording any of it
+        let records = req
+            .lines
+            .into_iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let level = if line.level.is_empty() {
+                    LogLevel::Info
+                } else {
+                    line.level.parse().map_err(|e| {
+                        invalid_field(
+                            "level",
+                            Status::invalid_argument(format!("lines[{}]: {}", i, e)),
+                        )
+                    })?
+                };
+                Ok(LogRecord {
+                    timestamp_ms: (line.timestamp_ms > 0).then_some(line.timestamp_ms),
+                    level,
+                    message: line.message,
+                    source: if line.target.is_empty() {
+                        DEFAULT_WORKER_LOG_SOURCE.to_string()
+                    } else {
+                        line.target
+                    },
+                    worker_id: Some(req.worker_id.clone()),
+                    task_id: (!line.task_id.is_empty()).then_some(line.task_id),
+                })
+            })
+            .collect::<Result<Vec<_>, Status>>()?;
+
+        let accepted = records.len();
+        for record in records {
+            if record.level == LogLevel::Error {
+                self.worker_history
+                    .record_error(&worker_id, record.message.clone());
+            }
+            self.logs.push(record);
+        }
+        Ok(Response::new(ShipLogsResponse {
+            accepted: accepted as i32,
+        }))
+    }
+
+    /// Snapshot of workers, datasets, barriers and recent checkpoints
+    async fn get_cluster_state(
+        &self,
+        request: Request<ClusterStateRequest>,
+    ) -> Result<Response<ClusterState>, Status> {
+        let req = request.into_inner();
+        let max_checkpoints = if req.max_checkpoints > 0 {
+            req.max_checkpoints as usize
+        } else {
+            DEFAULT_STATE_CHECKPOINTS
+        };
+
+        Ok(Response::new(self.cluster_state(max_checkpoints)))
+    }
+
+    /// Aggregate metrics, the same as `GET /api/metrics`
+    async fn get_cluster_metrics(
+        &self,
+        _request: Request<ClusterMetricsRequest>,
+    ) -> Result<Response<ClusterMetrics>, Status> {
+        let metrics = self.get_metrics_for_api();
+        Ok(Response::new(ClusterMetrics {
+            checkpoint_throughput: metrics.checkpoint_throughput,
+            coordinator_rps: metrics.coordinator_rps,
+            active_workers: metrics.active_workers,
+            total_workers: metrics.total_workers,
+            barrier_latency_p99_ms: metrics.barrier_latency_p99,
+            shard_assignment_time_ms: metrics.shard_assignment_time,
+            duplicate_checkpoint_notifications: metrics.duplicate_checkpoint_notifications,
+            disk_read_bytes_per_sec: metrics.io_rates.disk_read_bytes_per_sec,
+            disk_write_bytes_per_sec: metrics.io_rates.disk_write_bytes_per_sec,
+            network_rx_bytes_per_sec: metrics.io_rates.network_rx_bytes_per_sec,
+            network_tx_bytes_per_sec: metrics.io_rates.network_tx_bytes_per_sec,
+            gpu_count: metrics.gpus.gpu_count,
+            avg_gpu_utilization_percent: metrics.gpus.avg_utilization_percent,
+            gpu_memory_used_bytes: metrics.gpus.memory_used_bytes,
+            gpu_memory_total_bytes: metrics.gpus.memory_total_bytes,
+            gpu_power_draw_watts: metrics.gpus.power_draw_watts,
+            max_gpu_temperature_celsius: metrics.gpus.max_temperature_celsius,
+        }))
+    }
+
+    /// Begin a graceful coordinator shutdown
+    async fn shutdown(
+        &self,
+        request: Request<ShutdownRequest>,
+    ) -> Result<Response<ShutdownResponse>, Status> {
+        let req = request.into_inner();
+        let reason = if req.reason.is_empty() {
+            "shutdown requested"
+        } else {
+            &req.reason
+        };
+
+        let response = match self.begin_shutdown(reason) {
+            Some(barriers_aborted) => ShutdownResponse {
+                accepted: true,
+                barriers_aborted: barriers_aborted as i32,
+            },
+            None => ShutdownResponse {
+                accepted: false,
+                barriers_aborted: 0,
+            },
+        };
+        Ok(Response::new(response))
+    }
+
+    /// Release or abort a barrier that is stuck on missing workers
+    async fn release_barrier(
+        &self,
+        request: Request<ReleaseBarrierRequest>,
+    ) -> Result<Response<ReleaseBarrierResponse>, Status> {
+        let req = request.into_inner();
+        if req.barrier_id.is_empty() {
+            return Err(invalid_field(
+                "barrier_id",
+                Status::invalid_argument("barrier_id is required"),
+            ));
+        }
+        let generation = (req.generation > 0).then_some(req.generation as u64);
+        let reason = if req.reason.is_empty() {
+            "closed by administrator"
+        } else {
+            &req.reason
+        };
+
+        let barriers = self
+            .close_barrier(&req.barrier_id, generation, req.abort, reason)?
+            .into_iter()
+            .map(barrier_snapshot)
+            .collect();
+        Ok(Response::new(ReleaseBarrierResponse { barriers }))
+    }
+
+    /// Take a worker out of rotation; it is told to leave on its next heartbeat
+    async fn drain_worker(
+        &self,
+        request: Request<DrainWorkerRequest>,
+    ) -> Result<Response<DrainWorkerResponse>, Status> {
+        let worker_id: WorkerId = parse_id("worker_id", &request.into_inner().worker_id)?;
+        let started = CoordinatorService::drain_worker(self, &worker_id)?;
+        Ok(Response::new(DrainWorkerResponse {
+            already_draining: !started,
+        }))
+    }
+
+    /// Streaming heartbeats for efficient real-time updates
+    type StreamHeartbeatsStream =
+        Pin<Box<dyn Stream<Item = Result<HeartbeatResponse, Status>> + Send>>;
+
+    async fn stream_heartbeats(
+        &self,
+        request: Request<Streaming<HeartbeatRequest>>,
+    ) -> Result<Response<Self::StreamHeartbeatsStream>, Status> {
+        let mut stream = request.into_inner();
+        let service = self.clone();
+
+        // Bounded buffer; a consumer that falls behind gets responses dropped
+        // rather than stalling heartbeat processing
+        let (tx, rx) = mpsc::channel(HEARTBEAT_STREAM_BUFFER);
+
+        tokio::spawn(async move {
+            let mut stream_worker: Option<WorkerId> = None;
+            let mut consecutive_drops = 0;
+
+            loop {
+                let result = tokio::select! {
+                    next = stream.next() => match next {
+                        Some(result) => result,
+                        None => break,
+                    },
+                    // Stop as soon as the worker stops reading responses
+                    _ = tx.closed() => break,
+                };
+
+                let hb = match result {
+                    Ok(hb) => hb,
+                    Err(e) => {
+                        error!(error = %e, "Error in heartbeat stream");
+                        break;
+                    }
+                };
+
+                let worker_id: WorkerId = match parse_id("worker_id", &hb.worker_id) {
+                    Ok(worker_id) => worker_id,
+                    Err(e) => {
+                        error!(error = %e, "Invalid worker ID in heartbeat stream");
+                        let _ = tx.try_send(Err(e));
+                        continue;
+                    }
+                };
+                if stream_worker.as_ref() != Some(&worker_id) {
+                    if let Some(previous) = stream_worker.replace(worker_id.clone()) {
+                        service.heartbeat_streams.remove(&previous);
+                    }
+                }
+
+                let response = service.process_heartbeat(hb);
+                if let Err(e) = &response {
+                    error!(worker_id = %worker_id, error = %e, "Failed to process heartbeat");
+                }
+
+                let mut stats = *service
+                    .heartbeat_streams
+                    .entry(worker_id.clone())
+                    .or_default();
+                stats.received += 1;
+
+                match tx.try_send(response) {
+                    Ok(()) => {
+                        stats.sent += 1;
+                        consecutive_drops = 0;
+                    }
+                    Err(mpsc::error::TrySendError::Full(dropped)) => {
+                        stats.dropped += 1;
+                        consecutive_drops += 1;
+
+                        // Commands drained into the dropped response go back in the queue
+                        if let Ok(response) = dropped {
+                            service.requeue_commands(&worker_id, &response.pending_commands);
+                        }
+
+                        warn!(
+                            worker_id = %worker_id,
+                            consecutive_drops = consecutive_drops,
+                            "Heartbeat stream consumer is slow, dropped response"
+                        );
+                    }
+                    Err(mpsc::error::TrySendError::Closed(_)) => break,
+                }
+                service.heartbeat_streams.insert(worker_id.clone(), stats);
+
+                if consecutive_drops >= HEARTBEAT_STREAM_MAX_DROPS {
+                    warn!(
+                        worker_id = %worker_id,
+                        "Closing heartbeat stream of slow consumer"
+                    );
+                    let _ = tx
+                        .send_timeout(
+                            Err(reconnect_status(
+                                "Heartbeat stream consumer too slow",
+                                HEARTBEAT_RECONNECT_AFTER,
+                            )),
+                            HEARTBEAT_RECONNECT_AFTER,
+                        )
+                        .await;
+                    break;
+                }
+            }
+
+            if let Some(worker_id) = stream_worker {
+                service.heartbeat_streams.remove(&worker_id);
+            }
+        });
+
+        let output_stream = ReceiverStream::new(rx);
+        Ok(Response::new(
+            Box::pin(output_stream) as Self::StreamHeartbeatsStream
+        ))
+    }
+
+    /// Stream worker membership and state changes
+    type WatchWorkersStream = Pin<Box<dyn Stream<Item = Result<WorkerEvent, Status>> + Send>>;
+
+    async fn watch_workers(
+        &self,
+        request: Request<WatchWorkersRequest>,
+    ) -> Result<Response<Self::WatchWorkersStream>, Status> {
+        let req = request.into_inner();
+        info!(subscriber_id = %req.subscriber_id, "Worker watch started");
+
+        // Subscribe before taking the snapshot so no event falls in between
+        let mut events = self.workers.subscribe();
+        let (tx, rx) = mpsc::channel(32);
+
+        let snapshot: Vec<WorkerEvent> = if req.include_snapshot {
+            let generation = self.workers.generation() as i64;
+            self.workers
+                .all_workers()
+                .into_iter()
+                .map(|worker| WorkerEvent {
+                    kind: proto::worker_event::Kind::Joined as i32,
+                    worker_id: worker.id.into(),
+                    rank: worker.rank as i32,
+                    state: Self::core_to_proto_state(worker.state) as i32,
+                    membership_generation: generation,
+                    timestamp_ms: worker.registered_at.timestamp_millis(),
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let service = self.clone();
+        tokio::spawn(async move {
+            for event in snapshot {
+                if tx.send(Ok(event)).await.is_err() {
+                    return;
+                }
+            }
+
+            loop {
+                tokio::select! {
+                    _ = tx.closed() => break,
+                    received = events.recv() => match received {
+                        Ok(event) => {
+                            let Some(worker_event) = service.to_worker_event(&event) else {
+                                continue;
+                            };
+                            if tx.send(Ok(worker_event)).await.is_err() {
+                                break;
+                            }
+                        }
+                        // A gap in membership events cannot be patched up from a
+                        // snapshot (departed workers would never be reported), so
+                        // end the stream and let the client re-subscribe
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(
+                                subscriber_id = %req.subscriber_id,
+                                skipped = skipped,
+                                "Worker watch subscriber lagged, closing stream"
+                            );
+                            let _ = tx
+                                .send(Err(Status::data_loss(format!(
+                                    "Worker watch lagged, {} events dropped; re-subscribe with include_snapshot",
+                                    skipped
+                                ))))
+                                .await;
+                            break;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+            }
+            debug!(subscriber_id = %req.subscriber_id, "Worker watch ended");
+        });
+
+        let output_stream = ReceiverStream::new(rx);
+        Ok(Response::new(
+            Box::pin(output_stream) as Self::WatchWorkersStream
+        ))
+    }
+
+    /// Stream shard assignment changes for a single worker
+    type WatchShardAssignmentsStream =
+        Pin<Box<dyn Stream<Item = Result<ShardAssignmentUpdate, Status>> + Send>>;
+
+    async fn watch_shard_assignments(
+        &self,
+        request: Request<WatchShardAssignmentsRequest>,
+    ) -> Result<Response<Self::WatchShardAssignmentsStream>, Status> {
+        let req = request.into_inner();
+        let worker_id: WorkerId = parse_id("worker_id", &req.worker_id)?;
+        if self.workers.get(&worker_id).is_none() {
+            return Err(Status::not_found(format!(
+                "Worker not found: {}",
+                worker_id
+            )));
+        }
+        if !req.dataset_id.is_empty() {
+            let dataset_id: DatasetId = parse_id("dataset_id", &req.dataset_id)?;
+            if !self.datasets.contains_key(&dataset_id) {
+                return Err(Status::not_found(format!(
+                    "Dataset not found: {}",
+                    dataset_id
+                )));
+            }
+        }
+
+        info!(
+            worker_id = %req.worker_id,
+            dataset_id = %req.dataset_id,
+            "Shard assignment watch started"
+        );
+
+        let mut events = self.events.subscribe();
         let (tx, rx) = mpsc::channel(32);
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut known = HashMap::new();
+
+            // Initial assignment set
+            for update in service.shard_assignment_updates(&worker_id, &req.dataset_id, &mut known)
+            {
+                if tx.send(Ok(update)).await.is_err() {
+                    return;
+                }
+            }
+
+            loop {
+                tokio::select! {
+                    _ = tx.closed() => break,
+                    received = events.recv() => match received {
+                        Ok(CoordinatorEvent::WorkerStateChanged { .. }) => continue,
+                        // Missed events just mean we recompute now
+                        Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    },
+                }
+
+                for update in
+                    service.shard_assignment_updates(&worker_id, &req.dataset_id, &mut known)
+                {
+                    if tx.send(Ok(update)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+            debug!(worker_id = %worker_id, "Shard assignment watch ended");
+        });
+
+        let output_stream = ReceiverStream::new(rx);
+        Ok(Response::new(
+            Box::pin(output_stream) as Self::WatchShardAssignmentsStream
+        ))
+    }
+}
+
+/// Tag a validation error with the offending request field
+///
+/// The field name is returned in the `invalid-field` metadata entry.
+fn invalid_field(field: &'static str, mut status: Status) -> Status {
+    status.metadata_mut().insert(
+        "invalid-field",
+        tonic::metadata::MetadataValue::from_static(field),
+    );
+    status
+}
+
+/// Parse the ID in a request field
+pub(crate) fn parse_id<T>(field: &'static str, id: &str) -> Result<T, Status>
+where
+    T: std::str::FromStr<Err = runtime_core::Error>,
+{
+    id.parse()
+        .map_err(|e: runtime_core::Error| invalid_field(field, e.into()))
+}
+
+/// Validate the job and key of a KV request
+fn validate_kv_key(job_id: &str, key: &str) -> Result<(), Status> {
+    if job_id.is_empty() {
+        return Err(Status::invalid_argument("job_id cannot be empty"));
+    }
+    if key.is_empty() {
+        return Err(Status::invalid_argument("key cannot be empty"));
+    }
+    Ok(())
+}
+
+/// Validate the name and worker of a lease request, returning the worker
+fn validate_lease(name: &str, worker_id: &str) -> Result<WorkerId, Status> {
+    if name.is_empty() {
+        return Err(Status::invalid_argument("Lease name cannot be empty"));
+    }
+    parse_id("worker_id", worker_id)
+}
+
+/// Error returned for new work once shutdown has begun
+fn shutting_down_status() -> Status {
+    Status::unavailable("Coordinator is shutting down")
+}
+
+/// Size a GetDataShard batch from the worker's resource hints
+///
+/// Returns the number of shards to hand out (at least one, at most
+/// `assigned`) and the suggested prefetch depth. Without hints a single shard
+/// is returned. One shard per GPU is handed out when the GPU count is known,
+/// limited by how many shards fit in the memory budget if the dataset
+/// declares its sample size under [`BYTES_PER_SAMPLE_KEY`].
+fn plan_shard_batch(req: &ShardRequest, dataset: &DatasetInfo, assigned: usize) -> (usize, u64) {
+    let shard_bytes = dataset
+        .metadata
+        .get(BYTES_PER_SAMPLE_KEY)
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|&b| b > 0)
+        .map(|b| b.saturating_mul(dataset.shard_size.max(1) as u64));
+    let memory_slots = shard_bytes
+        .filter(|_| req.available_memory_bytes > 0)
+        .map(|bytes| {
+            let budget = req.available_memory_bytes as f64 * SHARD_MEMORY_FRACTION;
+            ((budget / bytes as f64) as u64).max(1)
+        });
+
+    let wanted = if req.max_shards > 0 {
+        req.max_shards as u64
+    } else {
+        req.gpu_count.max(1) as u64
+    };
+    let count = wanted
+        .min(memory_slots.unwrap_or(u64::MAX))
+        .min(assigned as u64)
+        .max(1);
+
+    let prefetch_depth = memory_slots
+        .map(|slots| slots.saturating_sub(count))
+        .unwrap_or(DEFAULT_PREFETCH_DEPTH)
+        .min(MAX_PREFETCH_DEPTH);
+
+    (count as usize, prefetch_depth)
+}
+
+/// Resolve a requested lease TTL
+fn lease_ttl(ttl_ms: i64) -> Result<Duration, Status> {
+    match ttl_ms {
+        ms if ms < 0 => Err(Status::invalid_argument("ttl_ms must be non-negative")),
+        0 => Ok(DEFAULT_LEASE_TTL),
+        ms => Ok(Duration::from_millis(ms as u64).min(MAX_LEASE_TTL)),
+    }
+}
+
+/// Build a lease response from a grant
+fn lease_response(acquired: bool, grant: LeaseGrant) -> LeaseResponse {
+    LeaseResponse {
+        acquired,
+        holder: grant.holder.into(),
+        token: grant.token as i64,
+        expires_in_ms: grant.expires_in.as_millis() as i64,
+    }
+}
+
+/// Barrier response telling a worker the barrier was aborted
+fn aborted_barrier_response(
+    barrier_id: String,
+    generation: u64,
+    arrival_order: u64,
+) -> BarrierResponse {
+    BarrierResponse {
+        released: false,
+        barrier_id,
+        participants: 0,
+        arrival_order: arrival_order as i64,
+        already_released: false,
+        generation: generation as i64,
+        aborted: true,
+        forced: false,
+        expected: 0,
+    }
+}
+
+/// UNAVAILABLE status telling the worker when to reconnect
+fn reconnect_status(message: &str, reconnect_after: Duration) -> Status {
+    let mut status = Status::unavailable(format!(
+        "{}, reconnect after {}ms",
+        message,
+        reconnect_after.as_millis()
+    ));
+    if let Ok(value) = reconnect_after.as_millis().to_string().parse() {
+        status.metadata_mut().insert("reconnect-after-ms", value);
+    }
+    status
+}
+
+/// Convert a barrier into its HTTP API form
+fn parse_cron(expr: &str) -> Result<CronSchedule, Status> {
+    expr.parse()
+        .map_err(|e: String| invalid_field("cron", Status::invalid_argument(e)))
+}
+
+/// Local storage holding a dataset path, and its data files
+///
+/// Returns `None` for remote paths such as `s3://`, which only workers read,
+/// and for local paths that do not exist here. Paths outside `roots` are
+/// refused, so the HTTP API cannot read arbitrary files on the host. The first
+/// `limit` files in sorted order are listed, hidden files skipped.
+async fn dataset_files(
+    path: &str,
+    roots: &[String],
+    limit: usize,
+) -> Result<Option<(LocalStorage, Vec<String>)>, Status> {
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    if path.is_empty() || path.contains("://") {
+        return Ok(None);
+    }
+
+    // Resolving symlinks can block on slow filesystems, so run the check off
+    // the runtime's worker threads
+    let (path, roots) = (path.to_string(), roots.to_vec());
+    let resolved = tokio::task::spawn_blocking(move || resolve_dataset_path(&path, &roots))
+        .await
+        .map_err(|e| Status::internal(format!("Failed to check dataset path: {}", e)))??;
+    let Some((path, is_dir)) = resolved else {
+        return Ok(None);
+    };
+    if is_dir {
+        let storage = LocalStorage::new(&path);
+        let files = storage.list_at_most("", limit).await?;
+        return Ok(Some((storage, files)));
+    }
+    match (path.parent(), path.file_name()) {
+        (Some(parent), Some(file_name)) => Ok(Some((
+            LocalStorage::new(parent),
+            vec![file_name.to_string_lossy().into_owned()],
+        ))),
+        _ => Ok(None),
+    }
+}
+
+/// A local dataset path with symlinks resolved, and whether it is a directory
+///
+/// Fails if the path lies outside `roots`; returns `None` if it does not exist.
+fn resolve_dataset_path(path: &str, roots: &[String]) -> Result<Option<(PathBuf, bool)>, Status> {
+    // Checked as written and again with symlinks resolved, so neither `..`
+    // nor a link can lead outside the roots
+    let outside = |path: &Path, resolve: fn(&Path) -> Option<PathBuf>| {
+        !roots
+            .iter()
+            .filter_map(|root| resolve(Path::new(root)))
+            .any(|root| path.starts_with(root))
+    };
+    let canonical = std::fs::canonicalize(path).ok();
+    if outside(&normalize_path(Path::new(path)), |root| {
+        Some(normalize_path(root))
+    }) || canonical
+        .as_deref()
+        .is_some_and(|canonical| outside(canonical, |root| std::fs::canonicalize(root).ok()))
+    {
+        return Err(Status::permission_denied(format!(
+            "Dataset path {} is outside the configured dataset roots",
+            path
+        )));
+    }
+    Ok(canonical.map(|path| {
+        let is_dir = path.is_dir();
+        (path, is_dir)
+    }))
+}
+
+/// `path` made absolute with `.` and `..` removed, without touching the filesystem
+fn normalize_path(path: &Path) -> PathBuf {
+    let absolute = std::env::current_dir().unwrap_or_default().join(path);
+    let mut normalized = PathBuf::new();
+    for component in absolute.components() {
+        match component {
+            std::path::Component::CurDir => {}
+            std::path::Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Split the start of a file into preview lines
+///
+/// Text is split into lines; anything else is shown as rows of hex bytes.
+/// Returns the lines, whether the data was binary and whether lines were cut.
+fn preview_lines_of(data: &[u8], max_lines: usize) -> (Vec<String>, bool, bool) {
+    // A multi-byte character may be cut off at the end of the read
+    let text = match std::str::from_utf8(data) {
+        Ok(text) => Some(text),
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&data[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    };
+    match text {
+        Some(text) => {
+            let mut lines = text.lines();
+            let preview: Vec<String> = lines.by_ref().take(max_lines).map(String::from).collect();
+            (preview, false, lines.next().is_some())
+        }
+        None => {
+            let mut rows = data.chunks(16);
+            let preview = rows
+                .by_ref()
+                .take(max_lines)
+                .map(|row| {
+                    row.iter()
+                        .map(|b| format!("{:02x}", b))
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                })
+                .collect();
+            (preview, true, rows.next().is_some())
+        }
+    }
+}
+
+fn api_barrier(b: BarrierInfo) -> ApiBarrierResponse {
+    let status = match b.status {
+        BarrierStatus::Released => "complete",
+        BarrierStatus::Waiting => "waiting",
+        BarrierStatus::Aborted => "aborted",
+    };
+    ApiBarrierResponse {
+        id: format!("{}@{}", b.barrier_id, b.generation),
+        name: b.barrier_id,
+        arrived: b.arrived,
+        total: b.expected,
+        status: status.to_string(),
+        created_at: b.created_at.timestamp_millis(),
+    }
+}
+
+/// Convert a barrier into its cluster state form
+fn barrier_snapshot(b: BarrierInfo) -> BarrierSnapshot {
+    BarrierSnapshot {
+        barrier_id: b.barrier_id,
+        arrived: b.arrived as i64,
+        expected: b.expected as i64,
+        generation: b.generation as i64,
+        released: b.status == BarrierStatus::Released,
+        aborted: b.status == BarrierStatus::Aborted,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checkpoint_txn::TransactionState;
+    use tempfile::{tempdir, TempDir};
+
+    async fn test_service() -> (TempDir, CoordinatorService) {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+        (dir, service)
+    }
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    fn dataset_id(id: &str) -> DatasetId {
+        id.parse().unwrap()
+    }
+
+    fn checkpoint_id(id: &str) -> CheckpointId {
+        id.parse().unwrap()
+    }
+
+    fn worker_info(worker_id: &str) -> WorkerInfo {
+        WorkerInfo {
+            worker_id: worker_id.to_string(),
+            hostname: "localhost".to_string(),
+            port: 50052,
+            gpu_count: 1,
+            memory_bytes: 8 * 1024 * 1024 * 1024,
+            metadata: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_register_worker_labels_and_taints() {
+        let (_dir, service) = test_service().await;
+
+        let mut info = worker_info("worker-1");
+        info.gpu_count = 8;
+        info.labels = HashMap::from([("gpu".to_string(), "a100".to_string())]);
+        info.taints = vec!["pool=eval".to_string()];
+        service.register_worker(Request::new(info)).await.unwrap();
+
+        let worker = service.workers.get(&worker_id("worker-1")).unwrap();
+        assert_eq!(worker.gpu_count, 8);
+        let selector: runtime_core::LabelSelector = "gpu=a100".parse().unwrap();
+        assert!(!worker.selected_by(&selector));
+        assert!(worker.selected_by(&selector.tolerate("pool".parse().unwrap())));
+
+        let detail = service
+            .get_worker_detail_for_api(&worker_id("worker-1"))
+            .unwrap();
+        assert_eq!(detail.labels["gpu"], "a100");
+        assert_eq!(detail.taints, vec!["pool=eval"]);
+
+        let mut info = worker_info("worker-2");
+        info.taints = vec!["bad key".to_string()];
+        let err = service
+            .register_worker(Request::new(info))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert_eq!(err.metadata().get("invalid-field").unwrap(), "taints");
+    }
+
+    #[tokio::test]
+    async fn test_stale_session_rejected() {
+        let (_dir, service) = test_service().await;
+        let register = || async {
+            service
+                .register_worker(Request::new(worker_info("worker-1")))
+                .await
+                .unwrap()
+                .into_inner()
+                .session
+        };
+        let heartbeat = |session, step| HeartbeatRequest {
+            worker_id: "worker-1".to_string(),
+            session,
+            status: Some(proto::WorkerStatus {
+                state: proto::worker_status::State::Training as i32,
+                current_step: step,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let old = register().await;
+        service.process_heartbeat(heartbeat(old, 10)).unwrap();
+
+        // The worker crashes and comes back under the same ID
+        service.remove_worker(&worker_id("worker-1")).unwrap();
+        let new = register().await;
+        assert_ne!(new, old);
+        service.process_heartbeat(heartbeat(new, 1)).unwrap();
+
+        // A heartbeat from the old process arrives late
+        let err = service.process_heartbeat(heartbeat(old, 11)).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+        assert_eq!(
+            runtime_core::ErrorCode::from_status(&err),
+            Some(runtime_core::ErrorCode::StaleSession)
+        );
+        let worker = service.workers.get(&worker_id("worker-1")).unwrap();
+        assert_eq!(worker.current_step, 1);
+
+        // Clients that don't send a session are not checked
+        service.process_heartbeat(heartbeat(0, 2)).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_from_runtime_config() {
+        let dir = tempdir().unwrap();
+        let mut config = RuntimeConfig::default();
+        config.storage.base_path = dir.path().to_string_lossy().into_owned();
+        config.coordinator.max_workers = 1;
+        config.worker.heartbeat_interval = Duration::from_secs(2);
+
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .unwrap();
+        assert!(dir.path().join("checkpoints").is_dir());
+
+        let worker_config = service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        assert_eq!(worker_config.get_ref().heartbeat_interval_ms, 2000);
+        assert!(service
+            .register_worker(Request::new(worker_info("worker-2")))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_service_creation() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        assert!(service.workers.world_size() == 0);
+    }
+
+    #[tokio::test]
+    async fn test_worker_registration() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        let request = Request::new(WorkerInfo {
+            worker_id: "worker-1".to_string(),
+            hostname: "localhost".to_string(),
+            port: 50052,
+            gpu_count: 2,
+            memory_bytes: 16 * 1024 * 1024 * 1024,
+            metadata: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        });
+
+        let response = service.register_worker(request).await.unwrap();
+        let config = response.into_inner();
+
+        assert_eq!(config.assigned_id, "worker-1");
+        assert_eq!(config.rank, 0);
+        assert_eq!(config.world_size, 1);
+    }
+
+    #[tokio::test]
+    async fn test_dataset_registration() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        // Register a worker first
+        let worker_req = Request::new(WorkerInfo {
+            worker_id: "worker-1".to_string(),
+            hostname: "localhost".to_string(),
+            port: 50052,
+            gpu_count: 1,
+            memory_bytes: 8 * 1024 * 1024 * 1024,
+            metadata: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        });
+        service.register_worker(worker_req).await.unwrap();
+
+        // Register dataset
+        let dataset_req = Request::new(DatasetInfo {
+            dataset_id: "imagenet".to_string(),
+            path: "/data/imagenet".to_string(),
+            format: "tfrecord".to_string(),
+            total_samples: 1_281_167,
+            shard_size: 10_000,
+            shuffle: true,
+            seed: 42,
+            metadata: HashMap::new(),
+        });
+
+        let response = service.register_dataset(dataset_req).await.unwrap();
+        let ack = response.into_inner();
+
+        assert!(ack.success);
+        assert_eq!(ack.dataset_id, "imagenet");
+        assert!(ack.total_shards > 0);
+    }
+
+    #[tokio::test]
+    async fn test_reap_dead_workers() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let mut service = CoordinatorService::with_config(config, 100, Duration::from_millis(10))
+            .await
+            .unwrap();
+        let clock = Arc::new(runtime_core::clock::MockClock::new());
+        service.workers =
+            Arc::new(WorkerRegistry::new(100, Duration::from_millis(10)).with_clock(clock.clone()));
+        let mut events = service.events().subscribe();
+
+        let worker_req = Request::new(WorkerInfo {
+            worker_id: "worker-1".to_string(),
+            hostname: "localhost".to_string(),
+            port: 50052,
+            gpu_count: 1,
+            memory_bytes: 8 * 1024 * 1024 * 1024,
+            metadata: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        });
+        service.register_worker(worker_req).await.unwrap();
+        assert!(matches!(
+            events.recv().await.unwrap(),
+            CoordinatorEvent::WorkerJoined { .. }
+        ));
+
+        clock.advance(Duration::from_millis(50));
+
+        let removed = service.reap_dead_workers();
+        assert_eq!(removed, vec!["worker-1".to_string()]);
+        assert_eq!(service.workers.world_size(), 0);
+        assert_eq!(service.shard_manager.active_worker_count(), 0);
+        assert_eq!(
+            events.recv().await.unwrap(),
+            CoordinatorEvent::WorkerDead {
+                worker_id: worker_id("worker-1")
+            }
+        );
+        assert_eq!(
+            events.recv().await.unwrap(),
+            CoordinatorEvent::ShardsRebalanced { workers: 0 }
+        );
+
+        // Nothing left to reap
+        assert!(service.reap_dead_workers().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_reports_membership_generation() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+
+        for id in ["worker-1", "worker-2"] {
+            let worker_req = Request::new(WorkerInfo {
+                worker_id: id.to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                gpu_count: 1,
+                memory_bytes: 8 * 1024 * 1024 * 1024,
+                metadata: HashMap::new(),
+                protocol_version: PROTOCOL_VERSION,
+                labels: HashMap::new(),
+                taints: Vec::new(),
+            });
+            service.register_worker(worker_req).await.unwrap();
+        }
+
+        let heartbeat = |worker_id: &str| {
+            Request::new(HeartbeatRequest {
+                worker_id: worker_id.to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: None,
+                resources: None,
+            })
+        };
+
+        let before = service
+            .heartbeat(heartbeat("worker-1"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(before.rank, 0);
+        assert_eq!(before.world_size, 2);
+
+        let leave_req = Request::new(WorkerInfo {
+            worker_id: "worker-2".to_string(),
+            hostname: String::new(),
+            port: 0,
+            gpu_count: 0,
+            memory_bytes: 0,
+            metadata: HashMap::new(),
+            protocol_version: PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        });
+        service.deregister_worker(leave_req).await.unwrap();
+
+        let after = service
+            .heartbeat(heartbeat("worker-1"))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(after.world_size, 1);
+        assert!(after.membership_generation > before.membership_generation);
+    }
+
+    #[tokio::test]
+    async fn test_compacted_rank_reaches_worker_in_heartbeat() {
+        let dir = tempdir().unwrap();
+        let config = CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+
+        let mut service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+            .await
+            .unwrap();
+        service.workers = Arc::new(
+            WorkerRegistry::new(100, Duration::from_secs(30)).with_rank_policy(
+                runtime_core::config::RankPolicy::Compact {
+                    settle: Duration::ZERO,
+                },
+            ),
+        );
+
+        let worker = |id: &str| {
+            Request::new(WorkerInfo {
+                worker_id: id.to_string(),
+                hostname: "localhost".to_string(),
+                port: 50052,
+                gpu_count: 1,
+                memory_bytes: 0,
+                metadata: HashMap::new(),
+                protocol_version: PROTOCOL_VERSION,
+                labels: HashMap::new(),
+                taints: Vec::new(),
+            })
+        };
+        service.register_worker(worker("worker-1")).await.unwrap();
+        service.register_worker(worker("worker-2")).await.unwrap();
+        service.deregister_worker(worker("worker-1")).await.unwrap();
+
+        let heartbeat = || {
+            Request::new(HeartbeatRequest {
+                worker_id: "worker-2".to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: None,
+                resources: None,
+            })
+        };
+        let before = service.heartbeat(heartbeat()).await.unwrap().into_inner();
+        assert_eq!((before.rank, before.world_size), (1, 1));
+
+        let mut events = service.events().subscribe();
+        let generation = service.compact_ranks_if_settled().unwrap();
+        assert_eq!(
+            events.try_recv().unwrap(),
+            CoordinatorEvent::RanksCompacted {
+                generation,
+                world_size: 1,
+            }
+        );
+
+        let after = service.heartbeat(heartbeat()).await.unwrap().into_inner();
+        assert_eq!(after.rank, 0);
+        assert_eq!(after.membership_generation, generation as i64);
+        assert_eq!(service.compact_ranks_if_settled(), None);
+    }
+
+    #[tokio::test]
+    async fn test_watch_workers() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+
+        let mut stream = service
+            .watch_workers(Request::new(WatchWorkersRequest {
+                subscriber_id: "test".to_string(),
+                include_snapshot: true,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let snapshot = stream.next().await.unwrap().unwrap();
+        assert_eq!(snapshot.kind, proto::worker_event::Kind::Joined as i32);
+        assert_eq!(snapshot.worker_id, "worker-1");
+
+        service
+            .register_worker(Request::new(worker_info("worker-2")))
+            .await
+            .unwrap();
+        let joined = stream.next().await.unwrap().unwrap();
+        assert_eq!(joined.kind, proto::worker_event::Kind::Joined as i32);
+        assert_eq!(joined.worker_id, "worker-2");
+        assert_eq!(joined.rank, 1);
+
+        service
+            .heartbeat(Request::new(HeartbeatRequest {
+                worker_id: "worker-2".to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: Some(proto::WorkerStatus {
+                    state: proto::worker_status::State::Training as i32,
+                    current_step: 1,
+                    current_epoch: 0,
+                    current_task: String::new(),
+                    shard_progress: vec![],
+                    loss: 0.0,
+                }),
+                resources: None,
+            }))
+            .await
+            .unwrap();
+        let changed = stream.next().await.unwrap().unwrap();
+        assert_eq!(changed.kind, proto::worker_event::Kind::StateChanged as i32);
+        assert_eq!(changed.state, proto::worker_status::State::Training as i32);
+
+        service
+            .deregister_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        let left = stream.next().await.unwrap().unwrap();
+        assert_eq!(left.kind, proto::worker_event::Kind::Left as i32);
+        assert_eq!(left.worker_id, "worker-1");
+    }
+
+    #[tokio::test]
+    async fn test_watch_workers_lag_ends_stream() {
+        let (_dir, service) = test_service().await;
+        let mut stream = service
+            .watch_workers(Request::new(WatchWorkersRequest {
+                subscriber_id: "slow".to_string(),
+                include_snapshot: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Overflow both the forwarding buffer and the membership broadcast
+        for i in 0..600 {
+            let worker = worker_info(&format!("worker-{}", i));
+            service
+                .register_worker(Request::new(worker.clone()))
+                .await
+                .unwrap();
+            service
+                .deregister_worker(Request::new(worker))
+                .await
+                .unwrap();
+        }
+
+        let mut status = None;
+        while let Some(item) = stream.next().await {
+            if let Err(err) = item {
+                status = Some(err);
+                break;
+            }
+        }
+        let status = status.expect("lagged watch must end with an error");
+        assert_eq!(status.code(), tonic::Code::DataLoss);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reaper_removes_workers_reported_dead() {
+        let (_dir, service) = test_service().await;
+        let supervisor = runtime_core::Supervisor::new(tokio::runtime::Handle::current());
+        let reaper = service.clone();
+        supervisor.spawn(
+            "reaper",
+            runtime_core::RestartPolicy::Never,
+            move |shutdown| {
+                reaper
+                    .clone()
+                    .run_dead_worker_reaper(Duration::from_secs(3600), shutdown)
+            },
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        let mut events = service.events().subscribe();
+        service.workers.set_heartbeat_timeout(Duration::ZERO);
+        tokio::time::sleep(Duration::from_millis(5)).await;
+        // Marked dead outside the reaper's tick
+        service.workers.check_dead_workers();
+
+        let event = tokio::time::timeout(Duration::from_secs(1), events.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            event,
+            CoordinatorEvent::WorkerDead {
+                worker_id: worker_id("worker-1"),
+            }
+        );
+        assert_eq!(service.workers.world_size(), 0);
+        supervisor.shutdown(Duration::from_secs(1)).await;
+    }
+
+    #[tokio::test]
+    async fn test_watch_shard_assignments() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "mnist".to_string(),
+                path: "/data/mnist".to_string(),
+                format: "parquet".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: true,
+                seed: 42,
+                metadata: HashMap::new(),
+            }))
+            .await
+            .unwrap();
+
+        let mut stream = service
+            .watch_shard_assignments(Request::new(WatchShardAssignmentsRequest {
+                worker_id: "worker-1".to_string(),
+                dataset_id: "mnist".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Sole worker owns every shard
+        let initial = stream.next().await.unwrap().unwrap();
+        assert_eq!(initial.added_shard_ids.len(), 10);
+        assert!(initial.removed_shard_ids.is_empty());
+
+        // A second worker takes over part of the dataset
+        service
+            .register_worker(Request::new(worker_info("worker-2")))
+            .await
+            .unwrap();
+        let rebalanced = stream.next().await.unwrap().unwrap();
+        assert!(rebalanced.added_shard_ids.is_empty());
+        assert_eq!(rebalanced.removed_shard_ids.len(), 5);
+        assert_eq!(rebalanced.assignments.len(), 5);
+
+        service.advance_epoch(&dataset_id("mnist")).unwrap();
+        let next_epoch = stream.next().await.unwrap().unwrap();
+        assert_eq!(next_epoch.epoch, 1);
+        assert_eq!(next_epoch.assignments.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_cluster_state() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "mnist".to_string(),
+                path: "/data/mnist".to_string(),
+                format: "parquet".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::new(),
+            }))
+            .await
+            .unwrap();
+        for step in [100, 200] {
+            service
+                .notify_checkpoint(Request::new(CheckpointInfo {
+                    worker_id: "worker-1".to_string(),
+                    checkpoint_id: format!("ckpt-{}", step),
+                    step,
+                    epoch: 0,
+                    storage_path: format!("/ckpt/{}", step),
+                    size_bytes: 1024,
+                    timestamp_ms: 0,
+                    r#type: proto::CheckpointType::Full as i32,
+                    metadata: HashMap::new(),
+                }))
+                .await
+                .unwrap();
+        }
+
+        let state = service
+            .get_cluster_state(Request::new(ClusterStateRequest { max_checkpoints: 1 }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(state.membership_generation, 1);
+        assert_eq!(state.workers.len(), 1);
+        assert_eq!(state.workers[0].worker_id, "worker-1");
+        assert_eq!(state.datasets.len(), 1);
+        assert_eq!(state.datasets[0].total_shards, 10);
+        assert_eq!(state.checkpoints.len(), 1);
+        assert_eq!(state.checkpoints[0].checkpoint_id, "ckpt-200");
+        assert_eq!(state.checkpoints[0].worker_id, "worker-1");
+
+        let metrics = service
+            .get_cluster_metrics(Request::new(ClusterMetricsRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        let api = service.get_metrics_for_api();
+        assert_eq!(metrics.total_workers, 1);
+        assert_eq!(metrics.active_workers, api.active_workers);
+        assert_eq!(metrics.checkpoint_throughput, api.checkpoint_throughput);
+    }
+
+    #[tokio::test]
+    async fn test_prune_checkpoints_notifies_workers() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        for step in [100, 200, 300] {
+            service
+                .notify_checkpoint(Request::new(CheckpointInfo {
+                    worker_id: "worker-1".to_string(),
+                    checkpoint_id: format!("ckpt-{}", step),
+                    step,
+                    epoch: 0,
+                    storage_path: format!("/ckpt/{}", step),
+                    size_bytes: 1024,
+                    timestamp_ms: 0,
+                    r#type: proto::CheckpointType::Full as i32,
+                    metadata: HashMap::new(),
+                }))
+                .await
+                .unwrap();
+        }
+
+        let dry_run = service
+            .prune_checkpoints(Request::new(PruneCheckpointsRequest {
+                keep_count: 1,
+                dry_run: true,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(dry_run.pruned_checkpoint_ids, vec!["ckpt-100", "ckpt-200"]);
+        assert_eq!(dry_run.retained_checkpoint_ids, vec!["ckpt-300"]);
+        assert_eq!(service.commands().pending(&worker_id("worker-1")), 0);
+
+        let pruned = service
+            .prune_checkpoints(Request::new(PruneCheckpointsRequest {
+                keep_count: 1,
+                dry_run: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(pruned.pruned_checkpoint_ids.len(), 2);
+        assert_eq!(pruned.workers_notified, 1);
+
+        let response = service
+            .heartbeat(Request::new(HeartbeatRequest {
+                worker_id: "worker-1".to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: None,
+                resources: None,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            response.pending_commands,
+            vec!["delete_checkpoint:ckpt-100", "delete_checkpoint:ckpt-200"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trigger_checkpoint() {
+        let (_dir, service) = test_service().await;
+        let err = service.trigger_checkpoint(&[]).unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        let err = service
+            .trigger_checkpoint(&[worker_id("worker-9")])
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        let txn = service.trigger_checkpoint(&[]).unwrap();
+        assert_eq!(txn.worker_ids.len(), 2);
+        for id in ["worker-1", "worker-2"] {
+            assert_eq!(
+                service.drain_commands(&worker_id(id)),
+                vec!["checkpoint_now:0"]
+            );
+            service
+                .notify_checkpoint(Request::new(CheckpointInfo {
+                    worker_id: id.to_string(),
+                    checkpoint_id: format!("ckpt-{}", id),
+                    step: 0,
+                    epoch: 0,
+                    storage_path: format!("/ckpt/{}", id),
+                    size_bytes: 1024,
+                    timestamp_ms: 0,
+                    r#type: proto::CheckpointType::Full as i32,
+                    metadata: HashMap::new(),
+                }))
+                .await
+                .unwrap();
+        }
+        let done = service.checkpoint_transaction(&txn.id).unwrap();
+        assert_eq!(done.state, TransactionState::Completed);
+        assert_eq!(done.checkpoint_ids["worker-2"], "ckpt-worker-2");
+
+        // A selected worker leaving fails the transaction
+        let txn = service
+            .trigger_checkpoint(&[worker_id("worker-1")])
+            .unwrap();
+        assert_eq!(service.commands().pending(&worker_id("worker-2")), 0);
+        service
+            .deregister_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        let failed = service.checkpoint_transaction(&txn.id).unwrap();
+        assert_eq!(failed.state, TransactionState::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_delete_checkpoint() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        for step in [100, 200] {
+            service
+                .notify_checkpoint(Request::new(CheckpointInfo {
+                    worker_id: "worker-1".to_string(),
+                    checkpoint_id: format!("ckpt-{}", step),
+                    step,
+                    epoch: 0,
+                    storage_path: format!("/ckpt/{}", step),
+                    size_bytes: 1024,
+                    timestamp_ms: 0,
+                    r#type: proto::CheckpointType::Full as i32,
+                    metadata: HashMap::new(),
+                }))
+                .await
+                .unwrap();
+        }
+
+        // Dropping the index entry leaves the workers' copies alone
+        service
+            .delete_checkpoint(&checkpoint_id("ckpt-100"), false)
+            .await
+            .unwrap();
+        assert_eq!(service.commands().pending(&worker_id("worker-1")), 0);
+
+        service
+            .delete_checkpoint(&checkpoint_id("ckpt-200"), true)
+            .await
+            .unwrap();
+        assert_eq!(
+            service.drain_commands(&worker_id("worker-1")),
+            vec!["delete_checkpoint:ckpt-200"]
+        );
+        assert!(service.checkpoint_manager.all_checkpoints().is_empty());
+
+        let err = service
+            .delete_checkpoint(&checkpoint_id("ckpt-200"), true)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_shard_progress() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "mnist".to_string(),
+                path: "/data/mnist".to_string(),
+                format: "parquet".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::new(),
+            }))
+            .await
+            .unwrap();
+
+        service
+            .heartbeat(Request::new(HeartbeatRequest {
+                worker_id: "worker-1".to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: Some(proto::WorkerStatus {
+                    state: proto::worker_status::State::Training as i32,
+                    current_step: 10,
+                    current_epoch: 0,
+                    current_task: String::new(),
+                    shard_progress: vec![
+                        proto::ShardProgress {
+                            dataset_id: "mnist".to_string(),
+                            shard_id: 0,
+                            samples_consumed: 100,
+                        },
+                        proto::ShardProgress {
+                            dataset_id: "mnist".to_string(),
+                            shard_id: 1,
+                            samples_consumed: 50,
+                        },
+                    ],
+                    loss: 0.0,
+                }),
+                resources: None,
+            }))
+            .await
+            .unwrap();
+
+        let progress = service
+            .shard_manager
+            .epoch_progress(&dataset_id("mnist"))
+            .unwrap();
+        assert_eq!(progress.samples_consumed, 150);
+        assert_eq!(progress.completed_shards, 1);
+
+        let datasets = service.get_datasets_for_api();
+        assert!((datasets[0].epoch_progress - 0.15).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_barrier_no_wait() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        let arrive = |worker: &str| BarrierRequest {
+            worker_id: worker.to_string(),
+            barrier_id: "sync".to_string(),
+            step: 0,
+            generation: 1,
+            no_wait: true,
+        };
+
+        let first = service
+            .wait_barrier(Request::new(arrive("worker-1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!first.released);
+        assert_eq!((first.participants, first.expected), (1, 2));
+
+        // Polling again doesn't count twice
+        let again = service
+            .wait_barrier(Request::new(arrive("worker-1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!((again.participants, again.arrival_order), (1, 1));
+
+        let last = service
+            .wait_barrier(Request::new(arrive("worker-2")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(last.released);
+        assert_eq!(last.participants, 2);
+    }
+
+    #[tokio::test]
+    async fn test_barrier_reuse_across_generations() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+
+        let barrier = |generation| BarrierRequest {
+            worker_id: "worker-1".to_string(),
+            barrier_id: "sync".to_string(),
+            step: 0,
+            generation,
+            no_wait: false,
+        };
+
+        let first = service
+            .wait_barrier(Request::new(barrier(1)))
+            .await
+            .unwrap();
+        assert!(first.get_ref().released);
+        assert!(!first.get_ref().already_released);
+
+        // A straggler retrying the released generation must not block
+        let late = service
+            .wait_barrier(Request::new(barrier(1)))
+            .await
+            .unwrap();
+        assert!(late.get_ref().already_released);
+
+        // The next generation of the same ID is a new barrier
+        let second = service
+            .wait_barrier(Request::new(barrier(2)))
+            .await
+            .unwrap();
+        assert!(!second.get_ref().already_released);
+        assert_eq!(second.get_ref().generation, 2);
+        assert_eq!(service.cluster_state(0).barriers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_release_barrier() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        let wait = |generation| {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .wait_barrier(Request::new(BarrierRequest {
+                        worker_id: "worker-1".to_string(),
+                        barrier_id: "sync".to_string(),
+                        step: 0,
+                        generation,
+                        no_wait: false,
+                    }))
+                    .await
+                    .unwrap()
+                    .into_inner()
+            })
+        };
+        let close = |generation, abort| ReleaseBarrierRequest {
+            barrier_id: "sync".to_string(),
+            generation,
+            abort,
+            reason: String::new(),
+        };
+
+        let released = wait(1);
+        let aborted = wait(2);
+        while service.cluster_state(0).barriers.len() < 2 {
+            tokio::task::yield_now().await;
+        }
+
+        let response = service
+            .release_barrier(Request::new(close(1, false)))
+            .await
+            .unwrap();
+        assert_eq!(response.get_ref().barriers.len(), 1);
+        let released = released.await.unwrap();
+        assert!(released.released && released.forced);
+        assert_eq!(released.participants, 1);
+
+        let response = service
+            .release_barrier(Request::new(close(0, true)))
+            .await
+            .unwrap();
+        assert!(response.get_ref().barriers[0].aborted);
+        let aborted = aborted.await.unwrap();
+        assert!(aborted.aborted && !aborted.released);
+
+        // Nothing is left open
+        let err = service
+            .release_barrier(Request::new(close(0, true)))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_checkpoint_notification() {
+        let (_dir, service) = test_service().await;
+        let notification = |step| CheckpointInfo {
+            worker_id: "worker-1".to_string(),
+            checkpoint_id: "ckpt-100".to_string(),
+            step,
+            epoch: 0,
+            storage_path: "/ckpt/100".to_string(),
+            size_bytes: 1024,
+            timestamp_ms: 0,
+            r#type: proto::CheckpointType::Full as i32,
+            metadata: HashMap::new(),
+        };
+
+        let first = service
+            .notify_checkpoint(Request::new(notification(100)))
+            .await
+            .unwrap()
+            .into_inner();
+        // A retry carrying different data still gets the original ack
+        let retry = service
+            .notify_checkpoint(Request::new(notification(200)))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(retry, first);
+        assert_eq!(service.checkpoint_manager.all_checkpoints().len(), 1);
+        assert_eq!(
+            service
+                .get_metrics_for_api()
+                .duplicate_checkpoint_notifications,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_events() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        service
+            .wait_barrier(Request::new(BarrierRequest {
+                worker_id: "worker-1".to_string(),
+                barrier_id: "sync".to_string(),
+                step: 1,
+                generation: 0,
+                no_wait: false,
+            }))
+            .await
+            .unwrap();
+
+        let response = service
+            .get_events(Request::new(GetEventsRequest {
+                after_seq: 0,
+                limit: 0,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let types: Vec<_> = response.events.iter().map(|e| e.r#type.as_str()).collect();
+        assert_eq!(types, vec!["worker_joined", "barrier_released"]);
+        assert_eq!(response.last_seq, 2);
+
+        let after = service
+            .get_events(Request::new(GetEventsRequest {
+                after_seq: 1,
+                limit: 10,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(after.events.len(), 1);
+        assert!(after.events[0]
+            .payload_json
+            .contains("\"barrier_id\":\"sync\""));
+    }
+
+    #[tokio::test]
+    async fn test_kv_rendezvous() {
+        let (_dir, service) = test_service().await;
+        let service = Arc::new(service);
+
+        let waiter = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .kv_wait(Request::new(KvWaitRequest {
+                        job_id: "job-1".to_string(),
+                        keys: vec!["nccl_unique_id".to_string()],
+                        timeout_ms: 5000,
+                    }))
+                    .await
+            })
+        };
+
+        let set = service
+            .kv_set(Request::new(KvSetRequest {
+                job_id: "job-1".to_string(),
+                key: "nccl_unique_id".to_string(),
+                value: vec![7; 128],
+            }))
+            .await
+            .unwrap();
+        assert!(set.get_ref().created);
 
-        // Spawn task to process incoming heartbeats
-        tokio::spawn(async move {
-            while let Some(result) = stream.next().await {
-                match result {
-                    Ok(hb) => {
-                        let state = hb
-                            .status
-                            .as_ref()
-                            .map(|s| CoordinatorService::proto_to_core_state(s.state))
-                            .unwrap_or(CoreWorkerState::Idle);
-
-                        let resources = CoordinatorService::proto_to_core_resources(hb.resources);
-
-                        // Update worker state
-                        if let Err(e) = workers.heartbeat(&hb.worker_id, state, resources) {
-                            error!(worker_id = %hb.worker_id, error = %e, "Failed to process heartbeat");
-                        }
+        let values = waiter.await.unwrap().unwrap().into_inner().values;
+        assert_eq!(values["nccl_unique_id"], vec![7; 128]);
 
-                        // Send response
-                        let response = HeartbeatResponse {
-                            acknowledged: true,
-                            server_timestamp_ms: Utc::now().timestamp_millis(),
-                            pending_commands: vec![],
-                        };
+        let other_job = service
+            .kv_get(Request::new(KvGetRequest {
+                job_id: "job-2".to_string(),
+                key: "nccl_unique_id".to_string(),
+            }))
+            .await
+            .unwrap();
+        assert!(!other_job.get_ref().found);
+    }
 
-                        if tx.send(Ok(response)).await.is_err() {
-                            break;
-                        }
-                    }
-                    Err(e) => {
-                        error!(error = %e, "Error in heartbeat stream");
-                        break;
-                    }
-                }
-            }
-        });
+    #[tokio::test]
+    async fn test_lease_released_when_holder_leaves() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        let acquire = |worker_id: &str| AcquireLeaseRequest {
+            name: "write-eval".to_string(),
+            worker_id: worker_id.to_string(),
+            ttl_ms: 0,
+        };
 
-        let output_stream = ReceiverStream::new(rx);
-        Ok(Response::new(
-            Box::pin(output_stream) as Self::StreamHeartbeatsStream
-        ))
+        let first = service
+            .acquire_lease(Request::new(acquire("worker-1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(first.acquired);
+
+        let second = service
+            .acquire_lease(Request::new(acquire("worker-2")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!second.acquired);
+        assert_eq!(second.holder, "worker-1");
+
+        service
+            .deregister_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        let takeover = service
+            .acquire_lease(Request::new(acquire("worker-2")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(takeover.acquired);
+        assert!(takeover.token > first.token);
+
+        let renew = service
+            .renew_lease(Request::new(RenewLeaseRequest {
+                name: "write-eval".to_string(),
+                worker_id: "worker-1".to_string(),
+                token: first.token,
+                ttl_ms: 0,
+            }))
+            .await;
+        assert_eq!(renew.unwrap_err().code(), tonic::Code::FailedPrecondition);
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use tempfile::tempdir;
+    #[tokio::test]
+    async fn test_epoch_advances_on_quorum() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "mnist".to_string(),
+                path: "/data/mnist".to_string(),
+                format: "parquet".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::new(),
+            }))
+            .await
+            .unwrap();
+        let report = |worker_id: &str| EpochCompleteRequest {
+            worker_id: worker_id.to_string(),
+            session: 0,
+            dataset_id: "mnist".to_string(),
+            epoch: 0,
+        };
+
+        let first = service
+            .report_epoch_complete(Request::new(report("worker-1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!first.advanced);
+        assert_eq!((first.reported, first.required), (1, 2));
+
+        let second = service
+            .report_epoch_complete(Request::new(report("worker-2")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(second.advanced);
+        assert_eq!(second.current_epoch, 1);
+
+        // A duplicate report for the finished epoch does not advance again
+        let late = service
+            .report_epoch_complete(Request::new(report("worker-1")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!late.advanced);
+        assert_eq!(late.current_epoch, 1);
+
+        // Once the only other worker leaves, one report completes the epoch
+        let mut next = report("worker-1");
+        next.epoch = 1;
+        service
+            .report_epoch_complete(Request::new(next))
+            .await
+            .unwrap();
+        service
+            .deregister_worker(Request::new(worker_info("worker-2")))
+            .await
+            .unwrap();
+        assert_eq!(service.shard_manager.current_epoch(&dataset_id("mnist")), 2);
+    }
 
     #[tokio::test]
-    async fn test_service_creation() {
-        let dir = tempdir().unwrap();
-        let config = CheckpointManagerConfig {
-            base_path: dir.path().to_path_buf(),
-            ..Default::default()
+    async fn test_register_dataset_validation() {
+        let (_dir, service) = test_service().await;
+        let dataset = DatasetInfo {
+            dataset_id: "mnist".to_string(),
+            path: "/data/mnist".to_string(),
+            format: "parquet".to_string(),
+            total_samples: 1000,
+            shard_size: 100,
+            shuffle: false,
+            seed: 0,
+            metadata: HashMap::new(),
         };
 
-        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+        let invalid = [
+            (
+                DatasetInfo {
+                    shard_size: 0,
+                    ..dataset.clone()
+                },
+                "shard_size",
+            ),
+            (
+                DatasetInfo {
+                    total_samples: -5,
+                    ..dataset.clone()
+                },
+                "total_samples",
+            ),
+            (
+                DatasetInfo {
+                    path: "../etc".to_string(),
+                    ..dataset.clone()
+                },
+                "path",
+            ),
+            (
+                DatasetInfo {
+                    dataset_id: "bad id".to_string(),
+                    ..dataset.clone()
+                },
+                "dataset_id",
+            ),
+        ];
+        for (info, field) in invalid {
+            let err = service
+                .register_dataset(Request::new(info))
+                .await
+                .unwrap_err();
+            assert_eq!(err.code(), tonic::Code::InvalidArgument);
+            assert_eq!(err.metadata().get("invalid-field").unwrap(), field);
+        }
+
+        service
+            .register_dataset(Request::new(dataset.clone()))
+            .await
+            .unwrap();
+        // Identical re-registration is accepted, conflicting parameters are not
+        service
+            .register_dataset(Request::new(dataset.clone()))
             .await
             .unwrap();
+        let conflict = service
+            .register_dataset(Request::new(DatasetInfo {
+                shard_size: 50,
+                ..dataset
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(conflict.code(), tonic::Code::AlreadyExists);
+    }
 
-        assert!(service.workers.world_size() == 0);
+    #[tokio::test]
+    async fn test_scheduled_checkpoint_commands() {
+        let (_dir, service) = test_service().await;
+        let service = service.with_checkpoint_strategy(CheckpointStrategy::Steps { interval: 100 });
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+
+        let heartbeat = |worker_id: &str, step: i64| HeartbeatRequest {
+            worker_id: worker_id.to_string(),
+            session: 0,
+            metrics: vec![],
+            timestamp_ms: 0,
+            status: Some(proto::WorkerStatus {
+                state: proto::worker_status::State::Training as i32,
+                current_step: step,
+                current_epoch: 0,
+                current_task: String::new(),
+                shard_progress: vec![],
+                loss: 0.0,
+            }),
+            resources: None,
+        };
+
+        let response = service
+            .heartbeat(Request::new(heartbeat("worker-1", 50)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.pending_commands.is_empty());
+
+        let response = service
+            .heartbeat(Request::new(heartbeat("worker-1", 100)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.pending_commands, vec!["checkpoint_now:100"]);
+
+        // Every worker is told once, even when reporting the same step
+        let response = service
+            .heartbeat(Request::new(heartbeat("worker-2", 100)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.pending_commands, vec!["checkpoint_now:100"]);
+        assert_eq!(service.checkpoint_scheduler().global_step(), 100);
     }
 
     #[tokio::test]
-    async fn test_worker_registration() {
-        let dir = tempdir().unwrap();
-        let config = CheckpointManagerConfig {
-            base_path: dir.path().to_path_buf(),
+    async fn test_get_data_shard_resource_hints() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "imagenet".to_string(),
+                path: "/data/imagenet".to_string(),
+                format: "tfrecord".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::from([(BYTES_PER_SAMPLE_KEY.to_string(), "1000".to_string())]),
+            }))
+            .await
+            .unwrap();
+
+        let request = ShardRequest {
+            worker_id: "worker-1".to_string(),
+            dataset_id: "imagenet".to_string(),
+            epoch: 0,
             ..Default::default()
         };
 
-        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+        // No hints: a single shard, default prefetch
+        let shard = service
+            .get_data_shard(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(shard.shards.len(), 1);
+        assert_eq!(shard.shards[0].shard_id, shard.shard_id);
+        assert_eq!(shard.prefetch_depth, DEFAULT_PREFETCH_DEPTH as i32);
+
+        // 4 GPUs with room for 6 shards of 100KB: 4 shards, 2 more prefetched
+        let shard = service
+            .get_data_shard(Request::new(ShardRequest {
+                gpu_count: 4,
+                available_memory_bytes: 2_400_000,
+                ..request.clone()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(shard.shards.len(), 4);
+        assert_eq!(shard.prefetch_depth, 2);
+
+        // Memory limits the batch below the GPU count
+        let shard = service
+            .get_data_shard(Request::new(ShardRequest {
+                gpu_count: 8,
+                available_memory_bytes: 800_000,
+                ..request
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(shard.shards.len(), 2);
+        assert_eq!(shard.prefetch_depth, 0);
+    }
+
+    #[tokio::test]
+    async fn test_get_data_shards() {
+        let (_dir, service) = test_service().await;
+        for worker in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(worker)))
+                .await
+                .unwrap();
+        }
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "imagenet".to_string(),
+                path: "/data/imagenet".to_string(),
+                format: "tfrecord".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::new(),
+            }))
             .await
             .unwrap();
 
-        let request = Request::new(WorkerInfo {
+        let request = ShardRequest {
             worker_id: "worker-1".to_string(),
-            hostname: "localhost".to_string(),
-            port: 50052,
-            gpu_count: 2,
-            memory_bytes: 16 * 1024 * 1024 * 1024,
-            metadata: HashMap::new(),
-        });
+            dataset_id: "imagenet".to_string(),
+            epoch: 0,
+            ..Default::default()
+        };
+        let all = service
+            .get_data_shards(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        let primary = service
+            .get_data_shard(Request::new(request.clone()))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(all.assignments.len() > 1);
+        assert_eq!(all.assignments[0].shard_id, primary.shard_id);
+        assert!(all.assignments.iter().all(|a| a.total_shards == 10));
 
-        let response = service.register_worker(request).await.unwrap();
-        let config = response.into_inner();
+        // Together the workers cover every shard exactly once
+        let other = service
+            .get_data_shards(Request::new(ShardRequest {
+                worker_id: "worker-2".to_string(),
+                ..request.clone()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let mut ids: Vec<_> = all
+            .assignments
+            .iter()
+            .chain(&other.assignments)
+            .map(|a| a.shard_id)
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (0..10).collect::<Vec<_>>());
 
-        assert_eq!(config.assigned_id, "worker-1");
-        assert_eq!(config.rank, 0);
-        assert_eq!(config.world_size, 1);
+        let capped = service
+            .get_data_shards(Request::new(ShardRequest {
+                max_shards: 1,
+                ..request.clone()
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(capped.assignments.len(), 1);
+
+        // Asking for the current epoch reports which one it is
+        service.shard_manager.advance_epoch(&dataset_id("imagenet"));
+        let current = service
+            .get_data_shards(Request::new(ShardRequest {
+                use_current_epoch: true,
+                ..request
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(current.epoch, 1);
     }
 
     #[tokio::test]
-    async fn test_dataset_registration() {
+    async fn test_protocol_version_negotiation() {
+        let (_dir, service) = test_service().await;
+
+        let config = service
+            .register_worker(Request::new(worker_info("current")))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(config.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(config.server_protocol_version, PROTOCOL_VERSION);
+
+        // Newer clients are downgraded, legacy clients get version 1
+        let config = service
+            .register_worker(Request::new(WorkerInfo {
+                protocol_version: PROTOCOL_VERSION + 1,
+                ..worker_info("newer")
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(config.protocol_version, PROTOCOL_VERSION);
+        let config = service
+            .register_worker(Request::new(WorkerInfo {
+                protocol_version: 0,
+                ..worker_info("legacy")
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(config.protocol_version, protocol::LEGACY_PROTOCOL_VERSION);
+
+        // Legacy workers do not receive commands their protocol predates
+        service.broadcast_command(WorkerCommand::CheckpointNow { step: 10 });
+        service.broadcast_command(WorkerCommand::DeleteCheckpoint {
+            checkpoint_id: checkpoint_id("old"),
+        });
+        assert_eq!(
+            service.drain_commands(&worker_id("legacy")),
+            vec!["delete_checkpoint:old"]
+        );
+        assert_eq!(service.drain_commands(&worker_id("current")).len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_graceful_shutdown() {
         let dir = tempdir().unwrap();
-        let config = CheckpointManagerConfig {
-            base_path: dir.path().to_path_buf(),
-            ..Default::default()
+        let mut config = RuntimeConfig::default();
+        config.storage.base_path = dir.path().to_string_lossy().into_owned();
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .unwrap();
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+
+        let waiter = {
+            let service = service.clone();
+            tokio::spawn(async move {
+                service
+                    .wait_barrier(Request::new(BarrierRequest {
+                        worker_id: "worker-1".to_string(),
+                        barrier_id: "sync".to_string(),
+                        step: 1,
+                        generation: 0,
+                        no_wait: false,
+                    }))
+                    .await
+            })
         };
+        while service.barriers.is_empty() {
+            tokio::task::yield_now().await;
+        }
 
-        let service = CoordinatorService::with_config(config, 100, Duration::from_secs(30))
+        let response = service
+            .shutdown(Request::new(ShutdownRequest {
+                reason: "maintenance".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(response.accepted);
+        assert_eq!(response.barriers_aborted, 1);
+        assert_eq!(
+            waiter.await.unwrap().unwrap_err().code(),
+            tonic::Code::Unavailable
+        );
+
+        // New work is refused and a second request is a no-op
+        let err = service
+            .register_worker(Request::new(worker_info("worker-3")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::Unavailable);
+        assert!(service.begin_shutdown("again").is_none());
+        service.shutdown_requested().await;
+
+        service.flush_state().await.unwrap();
+        let saved = std::fs::read(dir.path().join("cluster_state.pb")).unwrap();
+        let state = <ClusterState as prost::Message>::decode(saved.as_slice()).unwrap();
+        assert_eq!(state.workers.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_worker_management() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2", "worker-3"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+
+        // Drained workers keep their registration but leave the rotation
+        assert!(service.drain_worker(&worker_id("worker-1")).unwrap());
+        let response = Coordinator::drain_worker(
+            &service,
+            Request::new(DrainWorkerRequest {
+                worker_id: "worker-1".to_string(),
+            }),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+        assert!(response.already_draining);
+        assert!(service.is_draining(&worker_id("worker-1")));
+        assert!(service.cluster_state(0).workers.iter().any(|w| w.draining));
+        assert_eq!(service.commands.pending(&worker_id("worker-1")), 1);
+        assert_eq!(service.shard_manager.active_worker_count(), 2);
+        let workers = service.get_workers_for_api();
+        let drained = workers.iter().find(|w| w.id == "worker-1").unwrap();
+        assert_eq!(drained.status, "draining");
+        assert_eq!(
+            service
+                .drain_worker(&worker_id("missing"))
+                .unwrap_err()
+                .code(),
+            tonic::Code::NotFound
+        );
+
+        service.remove_worker(&worker_id("worker-1")).unwrap();
+        assert!(!service.is_draining(&worker_id("worker-1")));
+        assert_eq!(service.workers.world_size(), 2);
+
+        // Blacklisted workers are removed and cannot come back
+        service.blacklist_worker(&worker_id("worker-2"), "bad GPU");
+        assert!(service.workers.get(&worker_id("worker-2")).is_none());
+        let err = service
+            .register_worker(Request::new(worker_info("worker-2")))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        assert!(err.message().contains("bad GPU"));
+
+        assert!(service.unblacklist_worker(&worker_id("worker-2")));
+        assert!(!service.unblacklist_worker(&worker_id("worker-2")));
+        service
+            .register_worker(Request::new(worker_info("worker-2")))
             .await
             .unwrap();
+    }
 
-        // Register a worker first
-        let worker_req = Request::new(WorkerInfo {
-            worker_id: "worker-1".to_string(),
-            hostname: "localhost".to_string(),
-            port: 50052,
-            gpu_count: 1,
-            memory_bytes: 8 * 1024 * 1024 * 1024,
-            metadata: HashMap::new(),
-        });
-        service.register_worker(worker_req).await.unwrap();
+    #[tokio::test]
+    async fn test_task_lifecycle() {
+        let (_dir, service) = test_service().await;
+        for id in ["worker-1", "worker-2"] {
+            service
+                .register_worker(Request::new(worker_info(id)))
+                .await
+                .unwrap();
+        }
+        service
+            .register_dataset(Request::new(DatasetInfo {
+                dataset_id: "mnist".to_string(),
+                path: "/data/mnist".to_string(),
+                format: "parquet".to_string(),
+                total_samples: 1000,
+                shard_size: 100,
+                shuffle: false,
+                seed: 0,
+                metadata: HashMap::new(),
+            }))
+            .await
+            .unwrap();
 
-        // Register dataset
-        let dataset_req = Request::new(DatasetInfo {
-            dataset_id: "imagenet".to_string(),
-            path: "/data/imagenet".to_string(),
-            format: "tfrecord".to_string(),
-            total_samples: 1_281_167,
-            shard_size: 10_000,
-            shuffle: true,
-            seed: 42,
-            metadata: HashMap::new(),
+        let err = service
+            .create_task(
+                "train",
+                "training",
+                &dataset_id("missing"),
+                1,
+                1,
+                HashMap::new(),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+        let err = service
+            .create_task(
+                "train",
+                "training",
+                &dataset_id("mnist"),
+                3,
+                1,
+                HashMap::new(),
+            )
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::FailedPrecondition);
+
+        let task = service
+            .create_task(
+                "train",
+                "training",
+                &dataset_id("mnist"),
+                1,
+                1,
+                HashMap::new(),
+            )
+            .unwrap();
+        assert_eq!(task.state, TaskState::Pending);
+        let worker_id = task.worker_ids[0].clone();
+        assert_eq!(
+            service.drain_commands(&worker_id),
+            vec![format!("start_task:{}", task.id)]
+        );
+
+        // Workers picking up the task move it to running
+        service
+            .heartbeat(Request::new(HeartbeatRequest {
+                worker_id: worker_id.to_string(),
+                session: 0,
+                metrics: vec![],
+                timestamp_ms: 0,
+                status: Some(proto::WorkerStatus {
+                    state: proto::worker_status::State::Training as i32,
+                    current_step: 1,
+                    current_epoch: 0,
+                    current_task: task.id.clone(),
+                    shard_progress: vec![],
+                    loss: 0.0,
+                }),
+                resources: None,
+            }))
+            .await
+            .unwrap();
+        assert_eq!(
+            service.tasks().get(&task.id).unwrap().state,
+            TaskState::Running
+        );
+
+        let paused = service.control_task(&task.id, TaskState::Paused).unwrap();
+        assert_eq!(paused.state, TaskState::Paused);
+        assert_eq!(
+            service.drain_commands(&worker_id),
+            vec![format!("pause_task:{}", task.id)]
+        );
+        let err = service
+            .control_task(&task.id, TaskState::Completed)
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+
+        // Losing every assigned worker fails the task
+        service.control_task(&task.id, TaskState::Running).unwrap();
+        service
+            .deregister_worker(Request::new(worker_info(worker_id.as_str())))
+            .await
+            .unwrap();
+        assert_eq!(
+            service.tasks().get(&task.id).unwrap().state,
+            TaskState::Failed
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ship_logs() {
+        let (_dir, service) = test_service().await;
+        service
+            .register_worker(Request::new(worker_info("worker-1")))
+            .await
+            .unwrap();
+
+        let line = |level: &str, message: &str| proto::LogLine {
+            timestamp_ms: 1_000,
+            level: level.to_string(),
+            message: message.to_string(),
+            target: String::new(),
+            task_id: "task_1".to_string(),
+        };
+
+        let err = service
+            .ship_logs(Request::new(ShipLogsRequest {
+                worker_id: "unknown".to_string(),
+                lines: vec![line("info", "hello")],
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::NotFound);
+
+        // One bad line rejects the whole batch
+        let err = service
+            .ship_logs(Request::new(ShipLogsRequest {
+                worker_id: "worker-1".to_string(),
+                lines: vec![line("info", "hello"), line("loud", "oops")],
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code(), tonic::Code::InvalidArgument);
+        assert!(err.message().contains("lines[1]"));
+
+        let response = service
+            .ship_logs(Request::new(ShipLogsRequest {
+                worker_id: "worker-1".to_string(),
+                lines: vec![line("", "loading data"), line("warn", "loss is NaN")],
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.accepted, 2);
+
+        let entries = service.logs().query(&crate::logs::LogFilter {
+            worker_id: Some("worker-1".to_string()),
+            level: Some(LogLevel::Warn),
+            ..Default::default()
         });
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].message, "loss is NaN");
+        assert_eq!(entries[0].source, "worker");
+        assert_eq!(entries[0].task_id.as_deref(), Some("task_1"));
+        assert_eq!(entries[0].timestamp_ms, 1_000);
+    }
 
-        let response = service.register_dataset(dataset_req).await.unwrap();
-        let ack = response.into_inner();
+    #[test]
+    fn test_preview_lines() {
+        // A character cut off at the end of the read is still text
+        let (lines, binary, truncated) =
+            preview_lines_of("a\nb\nc\u{e9}".as_bytes()[..6].as_ref(), 2);
+        assert_eq!(lines, vec!["a", "b"]);
+        assert!(!binary);
+        assert!(truncated);
 
-        assert!(ack.success);
-        assert_eq!(ack.dataset_id, "imagenet");
-        assert!(ack.total_shards > 0);
+        let data: Vec<u8> = (0..20).map(|b| b * 13).collect();
+        let (lines, binary, truncated) = preview_lines_of(&data, 5);
+        assert!(binary);
+        assert!(!truncated);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "d0 dd ea f7");
+
+        assert_eq!(
+            normalize_path(Path::new("/data/./train/../../etc/passwd")),
+            Path::new("/etc/passwd")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dataset_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("train.parquet"), "").unwrap();
+        let roots = [dir.path().to_string_lossy().into_owned()];
+
+        assert!(dataset_files("s3://bucket/data", &roots, 10)
+            .await
+            .unwrap()
+            .is_none());
+        let path = format!("file://{}/train.parquet", roots[0]);
+        let (_, files) = dataset_files(&path, &roots, 10).await.unwrap().unwrap();
+        assert_eq!(files, ["train.parquet"]);
+
+        for path in ["/etc/passwd", &format!("{}/../../etc/passwd", roots[0])] {
+            let err = dataset_files(path, &roots, 10).await.unwrap_err();
+            assert_eq!(err.code(), tonic::Code::PermissionDenied);
+        }
     }
 }
@@ -0,0 +1,580 @@
+//! Simulated cluster for demos
+//!
+//! [`SimulationEngine`] registers synthetic workers and datasets and drives
+//! them through the same gRPC handlers real workers call: heartbeats with shard
+//! progress, shard requests, checkpoint notifications, epoch reports, barriers
+//! and shipped logs. The HTTP API then serves the service's real state, so the
+//! demo exercises the production code paths instead of canned responses.
+
+use std::collections::HashMap;
+
+use chrono::Utc;
+use tokio::task::JoinHandle;
+use tonic::{Request, Status};
+use tracing::{debug, info, warn};
+
+use runtime_core::config::SimulationConfig;
+use runtime_core::WorkerId;
+
+use crate::commands::WorkerCommand;
+use crate::proto::coordinator_server::Coordinator;
+use crate::proto::{
+    worker_status, BarrierRequest, CheckpointInfo, DatasetInfo, EpochCompleteRequest, GpuUsage,
+    HeartbeatRequest, LogLine, MetricPoint, ResourceUsage, ShardProgress, ShardRequest,
+    ShipLogsRequest, WorkerInfo, WorkerStatus,
+};
+use crate::protocol::PROTOCOL_VERSION;
+use crate::service::{parse_id, CoordinatorService};
+
+/// Datasets of the simulated cluster: (id, format, total samples, shard size)
+const SIM_DATASETS: &[(&str, &str, i64, i64)] = &[
+    ("imagenet-train", "tfrecord", 1_281_167, 10_000),
+    ("custom-vision", "parquet", 500_000, 8_000),
+];
+
+/// Tasks the simulation cycles through: (name, type, dataset)
+const SIM_TASKS: &[(&str, &str, &str)] = &[
+    (
+        "Vision Model Training",
+        "image_classification",
+        "imagenet-train",
+    ),
+    (
+        "Custom Vision Fine-tuning",
+        "image_classification",
+        "custom-vision",
+    ),
+];
+
+/// GPUs reported by each simulated worker
+const SIM_GPUS_PER_WORKER: i32 = 8;
+
+/// Memory of each simulated GPU
+const SIM_GPU_MEMORY_BYTES: i64 = 80 * 1024 * 1024 * 1024;
+
+/// Power draw of a simulated GPU when idle and fully loaded, in watts
+const SIM_GPU_IDLE_WATTS: f64 = 60.0;
+const SIM_GPU_MAX_WATTS: f64 = 400.0;
+
+/// Size of a simulated checkpoint
+const SIM_CHECKPOINT_BYTES: i64 = 650 * 1024 * 1024;
+
+/// A shard being consumed by a simulated worker
+#[derive(Debug)]
+struct SimShard {
+    shard_id: i64,
+    len: i64,
+    consumed: i64,
+}
+
+/// A simulated worker's part in a task
+#[derive(Debug)]
+struct SimTask {
+    task_id: String,
+    dataset_id: String,
+    paused: bool,
+    /// Epoch the shards were fetched for
+    epoch: Option<u64>,
+    shards: Vec<SimShard>,
+    /// Whether the end of `epoch` was reported
+    reported: bool,
+    /// Step of a checkpoint the coordinator asked for
+    checkpoint_requested: Option<u64>,
+}
+
+/// A synthetic worker
+#[derive(Debug)]
+struct SimWorker {
+    id: String,
+    /// Session from registration
+    session: i64,
+    step: u64,
+    task: Option<SimTask>,
+}
+
+impl SimWorker {
+    /// Simulated training loss, decaying with the step
+    fn loss(&self) -> f64 {
+        0.8 * (-0.001 * self.step as f64).exp() + 0.15
+    }
+
+    fn heartbeat(&self) -> HeartbeatRequest {
+        let training = self.task.as_ref().is_some_and(|t| !t.paused);
+        let state = if training {
+            worker_status::State::Training
+        } else {
+            worker_status::State::Idle
+        };
+        let utilization = if training {
+            85.0 + (self.step % 10) as f64
+        } else {
+            0.0
+        };
+        let loss = if training { self.loss() } else { 0.0 };
+        let timestamp_ms = Utc::now().timestamp_millis();
+
+        HeartbeatRequest {
+            worker_id: self.id.clone(),
+            session: self.session,
+            timestamp_ms,
+            status: Some(WorkerStatus {
+                state: state as i32,
+                current_step: self.step as i64,
+                current_epoch: self.task.as_ref().and_then(|t| t.epoch).unwrap_or(0) as i64,
+                current_task: self
+                    .task
+                    .as_ref()
+                    .map(|t| t.task_id.clone())
+                    .unwrap_or_default(),
+                shard_progress: self
+                    .task
+                    .iter()
+                    .flat_map(|t| {
+                        t.shards
+                            .iter()
+                            .filter(|s| s.consumed > 0)
+                            .map(|s| ShardProgress {
+                                dataset_id: t.dataset_id.clone(),
+                                shard_id: s.shard_id,
+                                samples_consumed: s.consumed,
+                            })
+                    })
+                    .collect(),
+                loss,
+            }),
+            resources: Some(ResourceUsage {
+                cpu_percent: utilization / 2.0,
+                gpu_usage: (0..SIM_GPUS_PER_WORKER)
+                    .map(|gpu_id| GpuUsage {
+                        gpu_id,
+                        utilization_percent: utilization,
+                        memory_used_bytes: (SIM_GPU_MEMORY_BYTES as f64 * utilization / 100.0)
+                            as i64,
+                        memory_total_bytes: SIM_GPU_MEMORY_BYTES,
+                        temperature_celsius: 40.0 + utilization / 3.0,
+                        power_draw_watts: SIM_GPU_IDLE_WATTS
+                            + (SIM_GPU_MAX_WATTS - SIM_GPU_IDLE_WATTS) * utilization / 100.0,
+                        sm_clock_mhz: if training { 1410 } else { 210 },
+                        memory_clock_mhz: 1593,
+                        ..Default::default()
+                    })
+                    .collect(),
+                ..Default::default()
+            }),
+            metrics: training
+                .then(|| MetricPoint {
+                    name: "loss".to_string(),
+                    value: loss,
+                    step: self.step as i64,
+                    timestamp_ms,
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Apply a command delivered on a heartbeat
+    fn apply(&mut self, command: WorkerCommand) {
+        match command {
+            WorkerCommand::StartTask { task_id } => {
+                self.task = Some(SimTask {
+                    task_id,
+                    dataset_id: String::new(),
+                    paused: false,
+                    epoch: None,
+                    shards: Vec::new(),
+                    reported: false,
+                    checkpoint_requested: None,
+                });
+            }
+            WorkerCommand::PauseTask { task_id } => self.set_paused(&task_id, true),
+            WorkerCommand::ResumeTask { task_id } => self.set_paused(&task_id, false),
+            WorkerCommand::StopTask { task_id } => {
+                if self.task.as_ref().is_some_and(|t| t.task_id == task_id) {
+                    self.task = None;
+                }
+            }
+            WorkerCommand::CheckpointNow { step } => {
+                if let Some(task) = self.task.as_mut() {
+                    task.checkpoint_requested = Some(step);
+                }
+            }
+            // Drained workers stop training and sit idle until removed
+            WorkerCommand::Drain => self.task = None,
+            // Simulated checkpoints have no local data to delete
+            WorkerCommand::DeleteCheckpoint { .. } => {}
+        }
+    }
+
+    fn set_paused(&mut self, task_id: &str, paused: bool) {
+        if let Some(task) = self.task.as_mut().filter(|t| t.task_id == task_id) {
+            task.paused = paused;
+        }
+    }
+}
+
+/// Drives a simulated cluster through a coordinator service
+pub struct SimulationEngine {
+    service: CoordinatorService,
+    config: SimulationConfig,
+    workers: Vec<SimWorker>,
+    /// Tasks created so far, used to cycle through the simulated tasks
+    tasks_created: usize,
+}
+
+impl SimulationEngine {
+    /// Create an engine for a service
+    pub fn new(service: CoordinatorService, config: SimulationConfig) -> Self {
+        Self {
+            service,
+            config,
+            workers: Vec::new(),
+            tasks_created: 0,
+        }
+    }
+
+    /// Register the simulated datasets and workers
+    pub async fn start(&mut self) -> Result<(), Status> {
+        for (dataset_id, format, total_samples, shard_size) in SIM_DATASETS {
+            self.service
+                .register_dataset(Request::new(DatasetInfo {
+                    dataset_id: dataset_id.to_string(),
+                    path: format!("/data/{}", dataset_id),
+                    format: format.to_string(),
+                    total_samples: *total_samples,
+                    shard_size: *shard_size,
+                    shuffle: true,
+                    seed: 42,
+                    metadata: HashMap::new(),
+                }))
+                .await?;
+        }
+
+        for n in 1..=self.config.workers {
+            let worker_id = format!("gpu-worker-{:02}", n);
+            let config = self
+                .service
+                .register_worker(Request::new(WorkerInfo {
+                    worker_id: worker_id.clone(),
+                    hostname: format!("gpu-node-{:02}", n),
+                    port: 50052,
+                    gpu_count: SIM_GPUS_PER_WORKER,
+                    memory_bytes: 512 * 1024 * 1024 * 1024,
+                    metadata: HashMap::from([("simulated".to_string(), "true".to_string())]),
+                    protocol_version: PROTOCOL_VERSION,
+                    labels: HashMap::new(),
+                    taints: Vec::new(),
+                }))
+                .await?
+                .into_inner();
+            self.workers.push(SimWorker {
+                id: worker_id,
+                session: config.session,
+                step: 0,
+                task: None,
+            });
+        }
+
+        info!(
+            workers = self.workers.len(),
+            datasets = SIM_DATASETS.len(),
+            "Simulated cluster started"
+        );
+        Ok(())
+    }
+
+    /// Run one training step on every simulated worker
+    pub async fn step(&mut self) {
+        self.ensure_task();
+        for worker in &mut self.workers {
+            if let Err(e) = step_worker(&self.service, &self.config, worker).await {
+                warn!(worker_id = %worker.id, error = %e, "Simulated worker step failed");
+            }
+        }
+    }
+
+    /// Start the next simulated task once the previous one finished
+    fn ensure_task(&mut self) {
+        if !self.service.tasks().active().is_empty() || self.workers.is_empty() {
+            return;
+        }
+
+        let (name, task_type, dataset_id) = SIM_TASKS[self.tasks_created % SIM_TASKS.len()];
+        let epochs = self.config.epochs_per_task.max(1);
+        let created = parse_id("dataset_id", dataset_id).and_then(|dataset_id| {
+            self.service.create_task(
+                name,
+                task_type,
+                &dataset_id,
+                self.workers.len(),
+                epochs,
+                HashMap::from([("epochs".to_string(), serde_json::json!(epochs))]),
+            )
+        });
+        match created {
+            Ok(task) => {
+                self.tasks_created += 1;
+                info!(task_id = %task.id, name = %name, "Simulated task created");
+            }
+            Err(e) => debug!(error = %e, "Simulated task not created"),
+        }
+    }
+
+    /// Run the simulation until the coordinator shuts down
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if let Err(e) = self.start().await {
+                warn!(error = %e, "Failed to start simulated cluster");
+                return;
+            }
+
+            let mut interval = tokio::time::interval(self.config.step_interval);
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => self.step().await,
+                    _ = self.service.shutdown_requested() => break,
+                }
+            }
+            info!("Simulated cluster stopped");
+        })
+    }
+}
+
+/// Heartbeat, then advance a worker's task by one step
+async fn step_worker(
+    service: &CoordinatorService,
+    config: &SimulationConfig,
+    worker: &mut SimWorker,
+) -> Result<(), Status> {
+    let response = service
+        .heartbeat(Request::new(worker.heartbeat()))
+        .await?
+        .into_inner();
+    for command in &response.pending_commands {
+        match command.parse::<WorkerCommand>() {
+            Ok(command) => worker.apply(command),
+            Err(e) => warn!(worker_id = %worker.id, error = %e, "Unknown command"),
+        }
+    }
+
+    let SimWorker {
+        id,
+        session,
+        step,
+        task,
+    } = worker;
+    let Some(sim_task) = task.as_mut().filter(|t| !t.paused) else {
+        return Ok(());
+    };
+    // Tasks finish on the coordinator without a command to the workers
+    let Some(task_info) = service
+        .tasks()
+        .get(&sim_task.task_id)
+        .filter(|t| !t.state.is_terminal())
+    else {
+        *task = None;
+        return Ok(());
+    };
+    sim_task.dataset_id = task_info.dataset_id.to_string();
+
+    let epoch = service.get_epoch_for_api(&task_info.dataset_id)?.epoch;
+    if sim_task.epoch != Some(epoch) {
+        let assignment = service
+            .get_data_shard(Request::new(ShardRequest {
+                worker_id: id.clone(),
+                dataset_id: sim_task.dataset_id.clone(),
+                epoch: epoch as i64,
+                max_shards: i32::MAX,
+                ..Default::default()
+            }))
+            .await?
+            .into_inner();
+        sim_task.shards = assignment
+            .shards
+            .iter()
+            .map(|s| SimShard {
+                shard_id: s.shard_id,
+                len: s.end_index - s.start_index,
+                consumed: 0,
+            })
+            .collect();
+        sim_task.epoch = Some(epoch);
+        sim_task.reported = false;
+        ship_log(
+            service,
+            id,
+            &sim_task.task_id,
+            "info",
+            format!(
+                "Epoch {} started with {} shards",
+                epoch,
+                sim_task.shards.len()
+            ),
+        )
+        .await;
+    }
+
+    let mut budget = config.samples_per_step as i64;
+    for shard in &mut sim_task.shards {
+        let take = (shard.len - shard.consumed).min(budget);
+        shard.consumed += take;
+        budget -= take;
+        if budget == 0 {
+            break;
+        }
+    }
+    *step += 1;
+
+    if !sim_task.reported && sim_task.shards.iter().all(|s| s.consumed >= s.len) {
+        sim_task.reported = true;
+        service
+            .report_epoch_complete(Request::new(EpochCompleteRequest {
+                worker_id: id.clone(),
+                dataset_id: sim_task.dataset_id.clone(),
+                epoch: epoch as i64,
+                session: *session,
+            }))
+            .await?;
+        ship_log(
+            service,
+            id,
+            &sim_task.task_id,
+            "info",
+            format!("Finished epoch {} at step {}", epoch, step),
+        )
+        .await;
+
+        // Workers wait for each other at the end of an epoch; the barrier
+        // blocks, so it runs beside the simulation loop
+        let barrier = BarrierRequest {
+            worker_id: id.clone(),
+            barrier_id: format!("{}-epoch", sim_task.task_id),
+            step: *step as i64,
+            generation: epoch as i64 + 1,
+            no_wait: false,
+        };
+        let service = service.clone();
+        tokio::spawn(async move {
+            let _ = service.wait_barrier(Request::new(barrier)).await;
+        });
+    }
+
+    let lead = task_info.worker_ids.first().map(WorkerId::as_str) == Some(id.as_str());
+    let periodic = lead
+        && config.checkpoint_interval_steps > 0
+        && *step % config.checkpoint_interval_steps == 0;
+    if sim_task.checkpoint_requested.take().is_some() || periodic {
+        let checkpoint_id = format!("{}_{}_step_{}", sim_task.task_id, id, step);
+        service
+            .notify_checkpoint(Request::new(CheckpointInfo {
+                worker_id: id.clone(),
+                checkpoint_id: checkpoint_id.clone(),
+                step: *step as i64,
+                epoch: epoch as i64,
+                storage_path: format!("/checkpoints/{}/step_{}.pt", sim_task.task_id, step),
+                size_bytes: SIM_CHECKPOINT_BYTES,
+                timestamp_ms: Utc::now().timestamp_millis(),
+                ..Default::default()
+            }))
+            .await?;
+        ship_log(
+            service,
+            id,
+            &sim_task.task_id,
+            "info",
+            format!("Checkpoint saved: {}", checkpoint_id),
+        )
+        .await;
+    }
+
+    Ok(())
+}
+
+/// Ship one log line for a simulated worker
+async fn ship_log(
+    service: &CoordinatorService,
+    worker_id: &str,
+    task_id: &str,
+    level: &str,
+    message: String,
+) {
+    let request = ShipLogsRequest {
+        worker_id: worker_id.to_string(),
+        lines: vec![LogLine {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            level: level.to_string(),
+            message,
+            target: "trainer".to_string(),
+            task_id: task_id.to_string(),
+        }],
+    };
+    if let Err(e) = service.ship_logs(Request::new(request)).await {
+        debug!(worker_id = %worker_id, error = %e, "Failed to ship simulated log");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tasks::TaskState;
+
+    #[tokio::test]
+    async fn test_simulation_drives_service() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = checkpoint::CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        };
+        let service =
+            CoordinatorService::with_config(config, 10, std::time::Duration::from_secs(30))
+                .await
+                .unwrap();
+
+        let mut engine = SimulationEngine::new(
+            service.clone(),
+            SimulationConfig {
+                enabled: true,
+                workers: 2,
+                samples_per_step: 200_000,
+                checkpoint_interval_steps: 2,
+                epochs_per_task: 1,
+                ..Default::default()
+            },
+        );
+        engine.start().await.unwrap();
+        assert_eq!(service.get_workers_for_api().len(), 2);
+        assert_eq!(service.get_datasets_for_api().len(), SIM_DATASETS.len());
+
+        // The first step creates a task, the second delivers its start command
+        engine.step().await;
+        engine.step().await;
+        let task = service.tasks().list().pop().unwrap();
+        assert_eq!(task.state, TaskState::Running);
+        assert!(
+            service
+                .get_epoch_for_api(&task.dataset_id)
+                .unwrap()
+                .progress
+                > 0.0
+        );
+
+        // 1.28M samples over two workers at 200k per step finishes within 4 steps
+        for _ in 0..6 {
+            engine.step().await;
+        }
+        assert_eq!(
+            service.tasks().get(&task.id).unwrap().state,
+            TaskState::Completed
+        );
+        assert!(!service.get_checkpoints_for_api().is_empty());
+        let filter = crate::logs::LogFilter {
+            task_id: Some(task.id.clone()),
+            ..Default::default()
+        };
+        assert!(!service.logs().query(&filter).is_empty());
+
+        // The next task starts once the first one finished
+        engine.step().await;
+        assert_eq!(service.tasks().active().len(), 1);
+    }
+}
@@ -0,0 +1,467 @@
+//! Training task tracking
+//!
+//! A task ties a registered dataset to a set of workers for a number of
+//! epochs. Its state follows the progress workers report in heartbeats, and
+//! pause/resume/stop requests are delivered to its workers as commands. Tasks
+//! can be saved to a JSON file so they survive coordinator restarts.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use runtime_core::{id, DatasetId, Epoch, WorkerId};
+
+/// Log lines kept per task
+pub const MAX_TASK_LOG_LINES: usize = 500;
+
+/// Lifecycle state of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    /// Created, waiting for its workers to start training
+    Pending,
+    /// At least one worker is training
+    Running,
+    /// Paused by a user
+    Paused,
+    /// All requested epochs finished
+    Completed,
+    /// Every worker of the task left the cluster
+    Failed,
+    /// Stopped by a user
+    Stopped,
+}
+
+impl TaskState {
+    /// Whether the task can no longer change state
+    pub fn is_terminal(self) -> bool {
+        matches!(
+            self,
+            TaskState::Completed | TaskState::Failed | TaskState::Stopped
+        )
+    }
+
+    /// Lowercase name, as used by the HTTP API
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TaskState::Pending => "pending",
+            TaskState::Running => "running",
+            TaskState::Paused => "paused",
+            TaskState::Completed => "completed",
+            TaskState::Failed => "failed",
+            TaskState::Stopped => "stopped",
+        }
+    }
+
+    fn can_transition_to(self, to: TaskState) -> bool {
+        match self {
+            TaskState::Pending => to != TaskState::Pending,
+            TaskState::Running => !matches!(to, TaskState::Pending | TaskState::Running),
+            TaskState::Paused => !matches!(to, TaskState::Pending | TaskState::Paused),
+            TaskState::Completed | TaskState::Failed | TaskState::Stopped => false,
+        }
+    }
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A line in a task's log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskLogLine {
+    /// When the line was recorded (ms since epoch)
+    pub timestamp_ms: i64,
+    /// Message text
+    pub message: String,
+}
+
+/// Parameters for a new task
+#[derive(Debug, Clone)]
+pub struct TaskSpec {
+    /// Display name
+    pub name: String,
+    /// Free-form task type, e.g. "image_classification"
+    pub task_type: String,
+    /// Dataset the task trains on
+    pub dataset_id: DatasetId,
+    /// Workers assigned to the task
+    pub worker_ids: Vec<WorkerId>,
+    /// Dataset epoch the task starts at
+    pub start_epoch: Epoch,
+    /// Number of epochs to train
+    pub epochs: u64,
+    /// User configuration passed through to workers
+    pub config: HashMap<String, serde_json::Value>,
+}
+
+/// A training task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    /// Unique task identifier
+    pub id: String,
+    /// Display name
+    pub name: String,
+    /// Free-form task type
+    pub task_type: String,
+    /// Dataset the task trains on
+    pub dataset_id: DatasetId,
+    /// Workers assigned to the task
+    pub worker_ids: Vec<WorkerId>,
+    /// Dataset epoch the task started at
+    pub start_epoch: Epoch,
+    /// Number of epochs to train
+    pub epochs: u64,
+    /// User configuration
+    pub config: HashMap<String, serde_json::Value>,
+    /// Current state
+    pub state: TaskState,
+    /// Fraction of the task completed (0.0 - 1.0)
+    pub progress: f64,
+    /// When the task was created (ms since epoch)
+    pub created_at_ms: i64,
+    /// When the task reached a terminal state (ms since epoch)
+    pub completed_at_ms: Option<i64>,
+    /// Most recent log lines, oldest first
+    pub logs: Vec<TaskLogLine>,
+}
+
+impl Task {
+    fn log(&mut self, message: impl Into<String>) {
+        if self.logs.len() >= MAX_TASK_LOG_LINES {
+            self.logs.remove(0);
+        }
+        self.logs.push(TaskLogLine {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            message: message.into(),
+        });
+    }
+
+    fn set_state(&mut self, state: TaskState, reason: &str) {
+        self.state = state;
+        if state.is_terminal() {
+            self.completed_at_ms = Some(Utc::now().timestamp_millis());
+        }
+        self.log(format!("Task {}: {}", state, reason));
+    }
+}
+
+/// Cluster view of a task's workers and dataset, used to advance its state
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TaskObservation {
+    /// Assigned workers still registered
+    pub live_workers: usize,
+    /// Assigned workers currently training
+    pub training_workers: usize,
+    /// Current epoch of the task's dataset
+    pub current_epoch: Epoch,
+    /// Fraction of the current epoch consumed (0.0 - 1.0)
+    pub epoch_fraction: f64,
+}
+
+/// Errors from task operations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskError {
+    /// No task with this ID
+    NotFound(String),
+    /// The task cannot move to the requested state
+    InvalidTransition {
+        /// Task identifier
+        task_id: String,
+        /// Current state
+        from: TaskState,
+        /// Requested state
+        to: TaskState,
+    },
+}
+
+impl fmt::Display for TaskError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskError::NotFound(id) => write!(f, "Task not found: {}", id),
+            TaskError::InvalidTransition { task_id, from, to } => {
+                write!(f, "Task {} cannot move from {} to {}", task_id, from, to)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TaskError {}
+
+/// Registry of training tasks
+#[derive(Debug, Default)]
+pub struct TaskManager {
+    tasks: Mutex<HashMap<String, Task>>,
+    /// File the tasks are saved to after every change
+    path: Option<PathBuf>,
+}
+
+impl TaskManager {
+    /// Create an in-memory task manager
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a task manager saved to `path`, loading any tasks already there
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let tasks = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice::<Vec<Task>>(&bytes)
+                .map_err(io::Error::from)?
+                .into_iter()
+                .map(|t| (t.id.clone(), t))
+                .collect(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(e),
+        };
+
+        Ok(Self {
+            tasks: Mutex::new(tasks),
+            path: Some(path),
+        })
+    }
+
+    /// Create a task in the pending state
+    pub fn create(&self, spec: TaskSpec) -> Task {
+        let id = id::next_id("task");
+        let mut task = Task {
+            id: id.clone(),
+            name: spec.name,
+            task_type: spec.task_type,
+            dataset_id: spec.dataset_id,
+            worker_ids: spec.worker_ids,
+            start_epoch: spec.start_epoch,
+            epochs: spec.epochs.max(1),
+            config: spec.config,
+            state: TaskState::Pending,
+            progress: 0.0,
+            created_at_ms: Utc::now().timestamp_millis(),
+            completed_at_ms: None,
+            logs: Vec::new(),
+        };
+        task.log(format!(
+            "Task created on dataset {} with {} workers for {} epochs",
+            task.dataset_id,
+            task.worker_ids.len(),
+            task.epochs
+        ));
+
+        self.update(|tasks| {
+            tasks.insert(id, task.clone());
+        });
+        task
+    }
+
+    /// Get a task by ID
+    pub fn get(&self, task_id: &str) -> Option<Task> {
+        self.tasks.lock().get(task_id).cloned()
+    }
+
+    /// All tasks, newest first
+    pub fn list(&self) -> Vec<Task> {
+        let mut tasks: Vec<Task> = self.tasks.lock().values().cloned().collect();
+        tasks.sort_by_key(|t| std::cmp::Reverse(t.created_at_ms));
+        tasks
+    }
+
+    /// Tasks that have not reached a terminal state
+    pub fn active(&self) -> Vec<Task> {
+        self.tasks
+            .lock()
+            .values()
+            .filter(|t| !t.state.is_terminal())
+            .cloned()
+            .collect()
+    }
+
+    /// Move a task to a new state on behalf of a user
+    pub fn transition(&self, task_id: &str, to: TaskState) -> Result<Task, TaskError> {
+        self.update(|tasks| {
+            let task = tasks
+                .get_mut(task_id)
+                .ok_or_else(|| TaskError::NotFound(task_id.to_string()))?;
+            if !task.state.can_transition_to(to) {
+                return Err(TaskError::InvalidTransition {
+                    task_id: task_id.to_string(),
+                    from: task.state,
+                    to,
+                });
+            }
+            task.set_state(to, "requested by user");
+            Ok(task.clone())
+        })
+    }
+
+    /// Advance a task from what the cluster reports about it
+    ///
+    /// Returns the new state if it changed. Progress alone is not saved, since
+    /// it is recomputed from the cluster after a restart.
+    pub fn observe(&self, task_id: &str, observation: TaskObservation) -> Option<TaskState> {
+        let mut tasks = self.tasks.lock();
+        let task = tasks.get_mut(task_id)?;
+        if task.state.is_terminal() {
+            return None;
+        }
+
+        let finished_epochs = observation.current_epoch.saturating_sub(task.start_epoch);
+        task.progress = ((finished_epochs as f64 + observation.epoch_fraction)
+            / task.epochs as f64)
+            .clamp(0.0, 1.0);
+
+        let (state, reason) = if finished_epochs >= task.epochs {
+            task.progress = 1.0;
+            (TaskState::Completed, "all epochs finished")
+        } else if observation.live_workers == 0 {
+            (TaskState::Failed, "all workers left the cluster")
+        } else if task.state == TaskState::Pending && observation.training_workers > 0 {
+            (TaskState::Running, "workers started training")
+        } else {
+            return None;
+        };
+
+        task.set_state(state, reason);
+        self.persist(&tasks);
+        Some(state)
+    }
+
+    /// Append a line to a task's log
+    pub fn log(&self, task_id: &str, message: impl Into<String>) {
+        self.update(|tasks| {
+            if let Some(task) = tasks.get_mut(task_id) {
+                task.log(message);
+            }
+        });
+    }
+
+    /// Apply a change and save the result
+    fn update<T>(&self, f: impl FnOnce(&mut HashMap<String, Task>) -> T) -> T {
+        let mut tasks = self.tasks.lock();
+        let result = f(&mut tasks);
+        self.persist(&tasks);
+        result
+    }
+
+    fn persist(&self, tasks: &HashMap<String, Task>) {
+        if let Some(path) = &self.path {
+            if let Err(e) = save(path, tasks) {
+                warn!(path = %path.display(), error = %e, "Failed to save tasks");
+            }
+        }
+    }
+}
+
+/// Write tasks to `path` atomically
+fn save(path: &Path, tasks: &HashMap<String, Task>) -> io::Result<()> {
+    let tasks: Vec<&Task> = tasks.values().collect();
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_vec(&tasks)?)?;
+    std::fs::rename(tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> TaskSpec {
+        TaskSpec {
+            name: "resnet".to_string(),
+            task_type: "image_classification".to_string(),
+            dataset_id: "imagenet".parse().unwrap(),
+            worker_ids: vec!["worker-1".parse().unwrap(), "worker-2".parse().unwrap()],
+            start_epoch: 1,
+            epochs: 2,
+            config: HashMap::new(),
+        }
+    }
+
+    fn observation(live: usize, training: usize, epoch: Epoch, fraction: f64) -> TaskObservation {
+        TaskObservation {
+            live_workers: live,
+            training_workers: training,
+            current_epoch: epoch,
+            epoch_fraction: fraction,
+        }
+    }
+
+    #[test]
+    fn test_task_follows_progress() {
+        let manager = TaskManager::new();
+        let task = manager.create(spec());
+        assert_eq!(task.state, TaskState::Pending);
+
+        assert_eq!(manager.observe(&task.id, observation(2, 0, 1, 0.0)), None);
+        assert_eq!(
+            manager.observe(&task.id, observation(2, 2, 1, 0.5)),
+            Some(TaskState::Running)
+        );
+        assert!((manager.get(&task.id).unwrap().progress - 0.25).abs() < f64::EPSILON);
+
+        assert_eq!(
+            manager.observe(&task.id, observation(2, 2, 3, 0.0)),
+            Some(TaskState::Completed)
+        );
+        let task = manager.get(&task.id).unwrap();
+        assert_eq!(task.progress, 1.0);
+        assert!(task.completed_at_ms.is_some());
+    }
+
+    #[test]
+    fn test_task_fails_without_workers() {
+        let manager = TaskManager::new();
+        let task = manager.create(spec());
+        assert_eq!(
+            manager.observe(&task.id, observation(0, 0, 1, 0.0)),
+            Some(TaskState::Failed)
+        );
+        // Terminal tasks ignore further observations
+        assert_eq!(manager.observe(&task.id, observation(2, 2, 1, 0.0)), None);
+    }
+
+    #[test]
+    fn test_user_transitions() {
+        let manager = TaskManager::new();
+        let task = manager.create(spec());
+
+        manager.transition(&task.id, TaskState::Paused).unwrap();
+        // Paused tasks are not resumed by worker progress
+        assert_eq!(manager.observe(&task.id, observation(2, 2, 1, 0.1)), None);
+        manager.transition(&task.id, TaskState::Running).unwrap();
+        manager.transition(&task.id, TaskState::Stopped).unwrap();
+
+        assert_eq!(
+            manager.transition(&task.id, TaskState::Running),
+            Err(TaskError::InvalidTransition {
+                task_id: task.id.clone(),
+                from: TaskState::Stopped,
+                to: TaskState::Running,
+            })
+        );
+        assert!(matches!(
+            manager.transition("missing", TaskState::Stopped),
+            Err(TaskError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_tasks_survive_reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tasks.json");
+
+        let manager = TaskManager::load(&path).unwrap();
+        let task = manager.create(spec());
+        manager.transition(&task.id, TaskState::Paused).unwrap();
+
+        let reloaded = TaskManager::load(&path).unwrap();
+        let restored = reloaded.get(&task.id).unwrap();
+        assert_eq!(restored.state, TaskState::Paused);
+        assert_eq!(restored.logs.len(), 2);
+    }
+}
@@ -0,0 +1,255 @@
+//! Recent activity of each worker
+//!
+//! Heartbeats only carry a worker's current resources and state, so the
+//! coordinator keeps a short rolling history of both, along with the
+//! training metrics and last error a worker reported, for the dashboard's
+//! worker drill-down.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use chrono::Utc;
+use dashmap::DashMap;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use runtime_core::{ResourceMetrics, WorkerId, WorkerState};
+
+/// Resource samples kept per worker, about 10 minutes at the default 5s heartbeat
+pub const DEFAULT_RESOURCE_HISTORY: usize = 120;
+
+/// State transitions kept per worker
+pub const MAX_STATE_TRANSITIONS: usize = 50;
+
+/// Distinct training metrics kept per worker, further names are ignored
+pub const MAX_METRIC_NAMES: usize = 64;
+
+/// Resources reported in a heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct ResourceSample {
+    /// When the heartbeat arrived (ms since epoch)
+    pub timestamp_ms: i64,
+    pub cpu_percent: f64,
+    pub memory_used_bytes: u64,
+    pub gpus: Vec<GpuSample>,
+}
+
+/// Usage of one GPU at a sample
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct GpuSample {
+    pub gpu_id: u32,
+    pub utilization_percent: f64,
+    pub memory_used_bytes: u64,
+    pub temperature_celsius: f64,
+}
+
+/// A training metric logged by a worker
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct MetricSample {
+    /// When the worker logged it (ms since epoch)
+    pub timestamp_ms: i64,
+    pub step: u64,
+    pub value: f64,
+}
+
+/// A change of a worker's state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct StateTransition {
+    /// When the change was seen (ms since epoch)
+    pub timestamp_ms: i64,
+    /// Previous state, unset when the worker registered
+    #[schema(value_type = Option<String>)]
+    pub from: Option<WorkerState>,
+    #[schema(value_type = String)]
+    pub to: WorkerState,
+}
+
+/// An error reported by a worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct WorkerError {
+    /// When the error was reported (ms since epoch)
+    pub timestamp_ms: i64,
+    pub message: String,
+}
+
+/// Rolling activity of a worker
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerActivity {
+    /// Oldest first
+    pub resources: VecDeque<ResourceSample>,
+    /// Oldest first
+    pub transitions: VecDeque<StateTransition>,
+    /// Samples of each training metric by name, oldest first
+    pub metrics: BTreeMap<String, VecDeque<MetricSample>>,
+    pub last_error: Option<WorkerError>,
+}
+
+/// Rolling activity of every registered worker
+#[derive(Debug)]
+pub struct WorkerHistory {
+    capacity: usize,
+    workers: DashMap<WorkerId, WorkerActivity>,
+}
+
+impl Default for WorkerHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_RESOURCE_HISTORY)
+    }
+}
+
+impl WorkerHistory {
+    /// Create a history keeping `capacity` resource and metric samples per worker
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            workers: DashMap::new(),
+        }
+    }
+
+    /// Record the resources from a heartbeat
+    pub fn record_resources(&self, worker_id: &WorkerId, resources: &ResourceMetrics) {
+        let sample = ResourceSample {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            cpu_percent: resources.cpu_percent,
+            memory_used_bytes: resources.memory_used_bytes,
+            gpus: resources
+                .gpu_metrics
+                .iter()
+                .map(|g| GpuSample {
+                    gpu_id: g.gpu_id,
+                    utilization_percent: g.utilization_percent,
+                    memory_used_bytes: g.memory_used_bytes,
+                    temperature_celsius: g.temperature_celsius,
+                })
+                .collect(),
+        };
+
+        let mut activity = self.workers.entry(worker_id.clone()).or_default();
+        if activity.resources.len() >= self.capacity {
+            activity.resources.pop_front();
+        }
+        activity.resources.push_back(sample);
+    }
+
+    /// Record training metrics from a heartbeat, oldest first
+    pub fn record_metrics(
+        &self,
+        worker_id: &WorkerId,
+        metrics: impl IntoIterator<Item = (String, MetricSample)>,
+    ) {
+        let mut activity = self.workers.entry(worker_id.clone()).or_default();
+        for (name, sample) in metrics {
+            if !activity.metrics.contains_key(&name) && activity.metrics.len() >= MAX_METRIC_NAMES {
+                continue;
+            }
+            let samples = activity.metrics.entry(name).or_default();
+            if samples.len() >= self.capacity {
+                samples.pop_front();
+            }
+            samples.push_back(sample);
+        }
+    }
+
+    /// Record a state change, or the initial state when `from` is unset
+    pub fn record_transition(
+        &self,
+        worker_id: &WorkerId,
+        from: Option<WorkerState>,
+        to: WorkerState,
+    ) {
+        let mut activity = self.workers.entry(worker_id.clone()).or_default();
+        if activity.transitions.len() >= MAX_STATE_TRANSITIONS {
+            activity.transitions.pop_front();
+        }
+        activity.transitions.push_back(StateTransition {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            from,
+            to,
+        });
+    }
+
+    /// Record an error reported by a worker
+    pub fn record_error(&self, worker_id: &WorkerId, message: impl Into<String>) {
+        self.workers
+            .entry(worker_id.clone())
+            .or_default()
+            .last_error = Some(WorkerError {
+            timestamp_ms: Utc::now().timestamp_millis(),
+            message: message.into(),
+        });
+    }
+
+    /// Activity of a worker
+    pub fn get(&self, worker_id: &WorkerId) -> Option<WorkerActivity> {
+        self.workers.get(worker_id).map(|a| a.clone())
+    }
+
+    /// Forget a worker that left the cluster
+    pub fn remove(&self, worker_id: &WorkerId) {
+        self.workers.remove(worker_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let history = WorkerHistory::new(3);
+        for i in 0..5 {
+            history.record_resources(
+                &worker_id("worker-1"),
+                &ResourceMetrics {
+                    cpu_percent: i as f64,
+                    ..Default::default()
+                },
+            );
+        }
+        for _ in 0..MAX_STATE_TRANSITIONS + 1 {
+            history.record_transition(
+                &worker_id("worker-1"),
+                Some(WorkerState::Idle),
+                WorkerState::Training,
+            );
+        }
+        history.record_error(&worker_id("worker-1"), "CUDA out of memory");
+        history.record_metrics(
+            &worker_id("worker-1"),
+            (0..5).map(|step| {
+                let sample = MetricSample {
+                    timestamp_ms: 0,
+                    step,
+                    value: 1.0 / (step + 1) as f64,
+                };
+                ("loss".to_string(), sample)
+            }),
+        );
+        history.record_metrics(
+            &worker_id("worker-1"),
+            (0..MAX_METRIC_NAMES + 1).map(|i| {
+                let sample = MetricSample {
+                    timestamp_ms: 0,
+                    step: 0,
+                    value: 0.0,
+                };
+                (format!("metric-{}", i), sample)
+            }),
+        );
+
+        let activity = history.get(&worker_id("worker-1")).unwrap();
+        let cpu: Vec<f64> = activity.resources.iter().map(|s| s.cpu_percent).collect();
+        assert_eq!(cpu, vec![2.0, 3.0, 4.0]);
+        assert_eq!(activity.transitions.len(), MAX_STATE_TRANSITIONS);
+        assert_eq!(activity.last_error.unwrap().message, "CUDA out of memory");
+        let steps: Vec<u64> = activity.metrics["loss"].iter().map(|s| s.step).collect();
+        assert_eq!(steps, vec![2, 3, 4]);
+        assert_eq!(activity.metrics.len(), MAX_METRIC_NAMES);
+
+        history.remove(&worker_id("worker-1"));
+        assert!(history.get(&worker_id("worker-1")).is_none());
+    }
+}
@@ -5,6 +5,7 @@
 
 use fnv::FnvHasher;
 use parking_lot::RwLock;
+use runtime_core::envelope::Versioned;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::hash::{Hash, Hasher};
@@ -163,6 +164,11 @@ pub struct ConsistentHashState {
     pub virtual_nodes: usize,
 }
 
+impl Versioned for ConsistentHashState {
+    const KIND: &'static str = "consistent_hash";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 impl From<&ConsistentHash> for ConsistentHashState {
     fn from(hash: &ConsistentHash) -> Self {
         Self {
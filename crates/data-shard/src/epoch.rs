@@ -6,6 +6,7 @@ use dashmap::DashMap;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
+use runtime_core::envelope::Versioned;
 use runtime_core::types::{DatasetId, Epoch};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -19,6 +20,9 @@ pub struct EpochCoordinator {
     /// Base seed for deterministic shuffling
     base_seed: u64,
 
+    /// Per-dataset seeds used in place of the base seed
+    seeds: DashMap<DatasetId, u64>,
+
     /// Shuffle cache: (dataset_id, epoch) -> shuffled shard indices
     shuffle_cache: DashMap<(DatasetId, Epoch), Arc<Vec<u64>>>,
 }
@@ -40,31 +44,47 @@ impl EpochCoordinator {
         Self {
             epochs: DashMap::new(),
             base_seed: seed,
+            seeds: DashMap::new(),
             shuffle_cache: DashMap::new(),
         }
     }
 
     /// Get current epoch for a dataset
-    pub fn current_epoch(&self, dataset_id: &str) -> Epoch {
+    pub fn current_epoch(&self, dataset_id: &DatasetId) -> Epoch {
         self.epochs.get(dataset_id).map(|e| *e).unwrap_or(0)
     }
 
     /// Initialize or reset epoch for a dataset
-    pub fn init_epoch(&self, dataset_id: &str, epoch: Epoch) {
-        self.epochs.insert(dataset_id.to_string(), epoch);
-        tracing::info!(dataset = dataset_id, epoch = epoch, "Initialized epoch");
+    pub fn init_epoch(&self, dataset_id: &DatasetId, epoch: Epoch) {
+        self.epochs.insert(dataset_id.clone(), epoch);
+        tracing::info!(dataset = %dataset_id, epoch = epoch, "Initialized epoch");
+    }
+
+    /// Shuffle a dataset with `seed` instead of the base seed
+    pub fn set_seed(&self, dataset_id: &DatasetId, seed: u64) {
+        if self.seeds.insert(dataset_id.clone(), seed) != Some(seed) {
+            self.clear_cache(dataset_id);
+        }
+    }
+
+    /// Seed a dataset is shuffled with
+    pub fn seed(&self, dataset_id: &DatasetId) -> u64 {
+        self.seeds
+            .get(dataset_id)
+            .map(|s| *s)
+            .unwrap_or(self.base_seed)
     }
 
     /// Advance to the next epoch for a dataset
     /// Returns the new epoch number
-    pub fn advance_epoch(&self, dataset_id: &str) -> Epoch {
+    pub fn advance_epoch(&self, dataset_id: &DatasetId) -> Epoch {
         let new_epoch = self
             .epochs
-            .entry(dataset_id.to_string())
+            .entry(dataset_id.clone())
             .and_modify(|e| *e += 1)
             .or_insert(1);
 
-        tracing::info!(dataset = dataset_id, epoch = *new_epoch, "Advanced epoch");
+        tracing::info!(dataset = %dataset_id, epoch = *new_epoch, "Advanced epoch");
         *new_epoch
     }
 
@@ -72,11 +92,11 @@ impl EpochCoordinator {
     /// Uses deterministic shuffling based on epoch and seed
     pub fn get_shuffled_shards(
         &self,
-        dataset_id: &str,
+        dataset_id: &DatasetId,
         epoch: Epoch,
         total_shards: u64,
     ) -> Arc<Vec<u64>> {
-        let key = (dataset_id.to_string(), epoch);
+        let key = (dataset_id.clone(), epoch);
 
         // Check cache first
         if let Some(cached) = self.shuffle_cache.get(&key) {
@@ -95,7 +115,7 @@ impl EpochCoordinator {
         self.shuffle_cache.insert(key, result.clone());
 
         tracing::debug!(
-            dataset = dataset_id,
+            dataset = %dataset_id,
             epoch = epoch,
             total_shards = total_shards,
             "Generated shuffled shard order"
@@ -108,7 +128,7 @@ impl EpochCoordinator {
     /// Returns a subset of shards for the worker to process
     pub fn get_worker_shards(
         &self,
-        dataset_id: &str,
+        dataset_id: &DatasetId,
         epoch: Epoch,
         total_shards: u64,
         worker_rank: u32,
@@ -135,9 +155,16 @@ impl EpochCoordinator {
     }
 
     /// Clear shuffle cache for a dataset (useful when dataset is modified)
-    pub fn clear_cache(&self, dataset_id: &str) {
+    pub fn clear_cache(&self, dataset_id: &DatasetId) {
         self.shuffle_cache.retain(|(id, _), _| id != dataset_id);
-        tracing::debug!(dataset = dataset_id, "Cleared shuffle cache");
+        tracing::debug!(dataset = %dataset_id, "Cleared shuffle cache");
+    }
+
+    /// Forget a dataset's epoch and shuffle cache
+    pub fn remove_dataset(&self, dataset_id: &DatasetId) {
+        self.epochs.remove(dataset_id);
+        self.seeds.remove(dataset_id);
+        self.clear_cache(dataset_id);
     }
 
     /// Clear all caches
@@ -146,12 +173,12 @@ impl EpochCoordinator {
     }
 
     /// Compute epoch-specific seed deterministically
-    fn compute_epoch_seed(&self, dataset_id: &str, epoch: Epoch) -> u64 {
+    fn compute_epoch_seed(&self, dataset_id: &DatasetId, epoch: Epoch) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
         let mut hasher = DefaultHasher::new();
-        self.base_seed.hash(&mut hasher);
+        self.seed(dataset_id).hash(&mut hasher);
         dataset_id.hash(&mut hasher);
         epoch.hash(&mut hasher);
         hasher.finish()
@@ -181,6 +208,11 @@ pub struct EpochCoordinatorState {
     pub base_seed: u64,
 }
 
+impl Versioned for EpochCoordinatorState {
+    const KIND: &'static str = "epoch_coordinator";
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 impl From<&EpochCoordinator> for EpochCoordinatorState {
     fn from(coord: &EpochCoordinator) -> Self {
         Self {
@@ -204,20 +236,24 @@ impl From<EpochCoordinatorState> for EpochCoordinator {
 mod tests {
     use super::*;
 
+    fn dataset_id(id: &str) -> DatasetId {
+        id.parse().unwrap()
+    }
+
     #[test]
     fn test_epoch_progression() {
         let coord = EpochCoordinator::new();
 
-        assert_eq!(coord.current_epoch("dataset-1"), 0);
+        assert_eq!(coord.current_epoch(&dataset_id("dataset-1")), 0);
 
-        coord.init_epoch("dataset-1", 0);
-        assert_eq!(coord.current_epoch("dataset-1"), 0);
+        coord.init_epoch(&dataset_id("dataset-1"), 0);
+        assert_eq!(coord.current_epoch(&dataset_id("dataset-1")), 0);
 
-        assert_eq!(coord.advance_epoch("dataset-1"), 1);
-        assert_eq!(coord.current_epoch("dataset-1"), 1);
+        assert_eq!(coord.advance_epoch(&dataset_id("dataset-1")), 1);
+        assert_eq!(coord.current_epoch(&dataset_id("dataset-1")), 1);
 
-        assert_eq!(coord.advance_epoch("dataset-1"), 2);
-        assert_eq!(coord.current_epoch("dataset-1"), 2);
+        assert_eq!(coord.advance_epoch(&dataset_id("dataset-1")), 2);
+        assert_eq!(coord.current_epoch(&dataset_id("dataset-1")), 2);
     }
 
     #[test]
@@ -226,8 +262,8 @@ mod tests {
         let coord1 = EpochCoordinator::with_seed(seed);
         let coord2 = EpochCoordinator::with_seed(seed);
 
-        let shards1 = coord1.get_shuffled_shards("dataset-1", 0, 100);
-        let shards2 = coord2.get_shuffled_shards("dataset-1", 0, 100);
+        let shards1 = coord1.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        let shards2 = coord2.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
 
         assert_eq!(*shards1, *shards2);
     }
@@ -236,8 +272,8 @@ mod tests {
     fn test_different_epochs_different_shuffle() {
         let coord = EpochCoordinator::with_seed(42);
 
-        let epoch0 = coord.get_shuffled_shards("dataset-1", 0, 100);
-        let epoch1 = coord.get_shuffled_shards("dataset-1", 1, 100);
+        let epoch0 = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        let epoch1 = coord.get_shuffled_shards(&dataset_id("dataset-1"), 1, 100);
 
         assert_ne!(*epoch0, *epoch1);
     }
@@ -246,8 +282,8 @@ mod tests {
     fn test_different_datasets_different_shuffle() {
         let coord = EpochCoordinator::with_seed(42);
 
-        let ds1 = coord.get_shuffled_shards("dataset-1", 0, 100);
-        let ds2 = coord.get_shuffled_shards("dataset-2", 0, 100);
+        let ds1 = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        let ds2 = coord.get_shuffled_shards(&dataset_id("dataset-2"), 0, 100);
 
         assert_ne!(*ds1, *ds2);
     }
@@ -256,10 +292,10 @@ mod tests {
     fn test_worker_shard_distribution() {
         let coord = EpochCoordinator::with_seed(42);
 
-        let w0_shards = coord.get_worker_shards("dataset-1", 0, 100, 0, 4);
-        let w1_shards = coord.get_worker_shards("dataset-1", 0, 100, 1, 4);
-        let w2_shards = coord.get_worker_shards("dataset-1", 0, 100, 2, 4);
-        let w3_shards = coord.get_worker_shards("dataset-1", 0, 100, 3, 4);
+        let w0_shards = coord.get_worker_shards(&dataset_id("dataset-1"), 0, 100, 0, 4);
+        let w1_shards = coord.get_worker_shards(&dataset_id("dataset-1"), 0, 100, 1, 4);
+        let w2_shards = coord.get_worker_shards(&dataset_id("dataset-1"), 0, 100, 2, 4);
+        let w3_shards = coord.get_worker_shards(&dataset_id("dataset-1"), 0, 100, 3, 4);
 
         // Each worker should get 25 shards
         assert_eq!(w0_shards.len(), 25);
@@ -284,9 +320,9 @@ mod tests {
         let coord = EpochCoordinator::with_seed(42);
 
         // First call computes
-        let first = coord.get_shuffled_shards("dataset-1", 0, 100);
+        let first = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
         // Second call should use cache (same Arc)
-        let second = coord.get_shuffled_shards("dataset-1", 0, 100);
+        let second = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
 
         assert!(Arc::ptr_eq(&first, &second));
     }
@@ -294,8 +330,8 @@ mod tests {
     #[test]
     fn test_state_serialization() {
         let coord = EpochCoordinator::with_seed(42);
-        coord.init_epoch("dataset-1", 5);
-        coord.init_epoch("dataset-2", 10);
+        coord.init_epoch(&dataset_id("dataset-1"), 5);
+        coord.init_epoch(&dataset_id("dataset-2"), 10);
 
         let state = EpochCoordinatorState::from(&coord);
         let json = serde_json::to_string(&state).unwrap();
@@ -303,23 +339,40 @@ mod tests {
         let restored = EpochCoordinator::from(restored_state);
 
         assert_eq!(restored.base_seed(), 42);
-        assert_eq!(restored.current_epoch("dataset-1"), 5);
-        assert_eq!(restored.current_epoch("dataset-2"), 10);
+        assert_eq!(restored.current_epoch(&dataset_id("dataset-1")), 5);
+        assert_eq!(restored.current_epoch(&dataset_id("dataset-2")), 10);
     }
 
     #[test]
     fn test_clear_cache() {
         let coord = EpochCoordinator::with_seed(42);
 
-        coord.get_shuffled_shards("dataset-1", 0, 100);
-        coord.get_shuffled_shards("dataset-1", 1, 100);
-        coord.get_shuffled_shards("dataset-2", 0, 100);
+        coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        coord.get_shuffled_shards(&dataset_id("dataset-1"), 1, 100);
+        coord.get_shuffled_shards(&dataset_id("dataset-2"), 0, 100);
 
-        coord.clear_cache("dataset-1");
+        coord.clear_cache(&dataset_id("dataset-1"));
 
         // dataset-2 cache should still exist
         assert!(coord
             .shuffle_cache
-            .contains_key(&("dataset-2".to_string(), 0)));
+            .contains_key(&(dataset_id("dataset-2"), 0)));
+    }
+
+    #[test]
+    fn test_dataset_seed() {
+        let coord = EpochCoordinator::with_seed(1);
+        let first = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+
+        coord.set_seed(&dataset_id("dataset-1"), 42);
+        assert_eq!(coord.seed(&dataset_id("dataset-1")), 42);
+        assert_eq!(coord.seed(&dataset_id("dataset-2")), 1);
+
+        // A dataset seeded with 42 shuffles like a coordinator seeded with 42
+        let seeded = coord.get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        let expected =
+            EpochCoordinator::with_seed(42).get_shuffled_shards(&dataset_id("dataset-1"), 0, 100);
+        assert_ne!(first, seeded);
+        assert_eq!(seeded, expected);
     }
 }
@@ -4,23 +4,25 @@
 //! - **Consistent hashing** for stable shard distribution across workers
 //! - **Epoch coordination** for deterministic shuffling per training epoch
 //! - **Shard management** for dataset registration and dynamic rebalancing
+//! - **Dataset manifests** counting the samples in a dataset's files
 //!
 //! # Example
 //!
 //! ```rust
-//! use data_shard::{ShardManager, ConsistentHash, EpochCoordinator};
-//! use runtime_core::types::DatasetMetadata;
+//! use data_shard::{DatasetId, ShardManager, WorkerId};
 //!
 //! // Create a shard manager
 //! let manager = ShardManager::new();
 //!
 //! // Register workers
-//! manager.register_worker("worker-0");
-//! manager.register_worker("worker-1");
+//! let worker: WorkerId = "worker-0".parse()?;
+//! manager.register_worker(&worker);
+//! manager.register_worker(&"worker-1".parse()?);
 //!
 //! // Register a dataset
+//! let dataset: DatasetId = "imagenet".parse()?;
 //! manager.register_dataset_params(
-//!     "imagenet",
+//!     &dataset,
 //!     1_281_167, // total samples
 //!     10_000,    // shard size
 //!     true,      // shuffle
@@ -28,17 +30,20 @@
 //! );
 //!
 //! // Get shard assignments for a worker
-//! let shards = manager.get_shard_for_worker("imagenet", "worker-0", 0);
+//! let shards = manager.get_shard_for_worker(&dataset, &worker, 0);
+//! # Ok::<(), runtime_core::Error>(())
 //! ```
 
 mod consistent_hash;
 mod epoch;
+mod manifest;
 mod shard_manager;
 
 // Re-export main types
 pub use consistent_hash::{ConsistentHash, ConsistentHashState};
 pub use epoch::{EpochCoordinator, EpochCoordinatorState};
-pub use shard_manager::{ShardManager, ShardManagerState, WorkerState};
+pub use manifest::{split_pattern, DatasetManifest, ManifestFile};
+pub use shard_manager::{EpochProgress, ShardManager, ShardManagerState, WorkerState};
 
 // Re-export types from runtime-core for convenience
 pub use runtime_core::types::{
@@ -49,6 +54,14 @@ pub use runtime_core::types::{
 mod tests {
     use super::*;
 
+    fn dataset_id(id: &str) -> DatasetId {
+        id.parse().unwrap()
+    }
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
+
     /// Integration test: Full workflow from registration to shard assignment
     #[test]
     fn test_full_workflow() {
@@ -56,14 +69,15 @@ mod tests {
         let manager = ShardManager::new();
 
         // Register workers
-        manager.register_worker("worker-0");
-        manager.register_worker("worker-1");
-        manager.register_worker("worker-2");
-        manager.register_worker("worker-3");
+        manager.register_worker(&worker_id("worker-0"));
+        manager.register_worker(&worker_id("worker-1"));
+        manager.register_worker(&worker_id("worker-2"));
+        manager.register_worker(&worker_id("worker-3"));
 
         // Register dataset
         manager.register_dataset_params(
-            "cifar10", 60_000, // total samples
+            &dataset_id("cifar10"),
+            60_000, // total samples
             1_000,  // shard size -> 60 shards
             true,   // shuffle
             42,     // seed
@@ -72,9 +86,9 @@ mod tests {
         // Get epoch 0 assignments
         let mut total_samples_assigned = 0;
         for i in 0..4 {
-            let worker_id = format!("worker-{}", i);
+            let worker = worker_id(&format!("worker-{}", i));
             let assignments = manager
-                .get_shard_for_worker("cifar10", &worker_id, 0)
+                .get_shard_for_worker(&dataset_id("cifar10"), &worker, 0)
                 .unwrap();
 
             // Each worker should get ~15 shards (60/4)
@@ -89,15 +103,15 @@ mod tests {
         assert_eq!(total_samples_assigned, 60_000);
 
         // Advance epoch and verify different assignments
-        manager.advance_epoch("cifar10");
+        manager.advance_epoch(&dataset_id("cifar10"));
         let epoch0_shards: Vec<_> = manager
-            .get_shard_for_worker("cifar10", "worker-0", 0)
+            .get_shard_for_worker(&dataset_id("cifar10"), &worker_id("worker-0"), 0)
             .unwrap()
             .iter()
             .map(|s| s.shard_id)
             .collect();
         let epoch1_shards: Vec<_> = manager
-            .get_shard_for_worker("cifar10", "worker-0", 1)
+            .get_shard_for_worker(&dataset_id("cifar10"), &worker_id("worker-0"), 1)
             .unwrap()
             .iter()
             .map(|s| s.shard_id)
@@ -113,20 +127,20 @@ mod tests {
     fn test_worker_failure_recovery() {
         let manager = ShardManager::new();
 
-        manager.register_worker("worker-0");
-        manager.register_worker("worker-1");
-        manager.register_worker("worker-2");
+        manager.register_worker(&worker_id("worker-0"));
+        manager.register_worker(&worker_id("worker-1"));
+        manager.register_worker(&worker_id("worker-2"));
 
-        manager.register_dataset_params("dataset", 30_000, 1_000, false, 0);
+        manager.register_dataset_params(&dataset_id("dataset"), 30_000, 1_000, false, 0);
 
         // Initial assignment
         let initial_0 = manager
-            .get_shard_for_worker("dataset", "worker-0", 0)
+            .get_shard_for_worker(&dataset_id("dataset"), &worker_id("worker-0"), 0)
             .unwrap();
         let initial_shards: Vec<_> = initial_0.iter().map(|s| s.shard_id).collect();
 
         // Simulate worker-1 failure
-        manager.remove_worker("worker-1");
+        manager.remove_worker(&worker_id("worker-1"));
 
         // Rebalance
         let final_assignments = manager.rebalance_shards();
@@ -144,7 +158,7 @@ mod tests {
 
         // Consistent hashing: worker-0 should keep most of its original shards
         let final_0 = manager
-            .get_shard_for_worker("dataset", "worker-0", 0)
+            .get_shard_for_worker(&dataset_id("dataset"), &worker_id("worker-0"), 0)
             .unwrap();
         let final_shards: Vec<_> = final_0.iter().map(|s| s.shard_id).collect();
 
@@ -179,21 +193,21 @@ mod tests {
 
         // Same operations on both
         for m in [&manager1, &manager2] {
-            m.register_worker("worker-0");
-            m.register_worker("worker-1");
-            m.register_dataset_params("data", 10_000, 100, true, 0);
+            m.register_worker(&worker_id("worker-0"));
+            m.register_worker(&worker_id("worker-1"));
+            m.register_dataset_params(&dataset_id("data"), 10_000, 100, true, 0);
         }
 
         // Should produce identical assignments
         let shards1: Vec<_> = manager1
-            .get_shard_for_worker("data", "worker-0", 0)
+            .get_shard_for_worker(&dataset_id("data"), &worker_id("worker-0"), 0)
             .unwrap()
             .iter()
             .map(|s| s.shard_id)
             .collect();
 
         let shards2: Vec<_> = manager2
-            .get_shard_for_worker("data", "worker-0", 0)
+            .get_shard_for_worker(&dataset_id("data"), &worker_id("worker-0"), 0)
             .unwrap()
             .iter()
             .map(|s| s.shard_id)
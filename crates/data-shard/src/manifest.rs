@@ -0,0 +1,551 @@
+//! Dataset manifests built from the files in storage
+//!
+//! A [`DatasetManifest`] lists the files of a dataset with the number of
+//! samples in each, so a dataset can be registered without working out its
+//! total sample count by hand. Samples are counted without reading whole
+//! files where the format allows:
+//!
+//! - Parquet files from the row count in their footer
+//! - Any file with an index next to it (`train-0.tfrecord.idx` or
+//!   `.index`), from the non-empty lines of the index
+//! - JSON Lines files (`.jsonl`, `.ndjson`) from their non-empty lines
+
+use std::collections::HashSet;
+use std::ops::Range;
+
+use runtime_core::{Error, Result};
+use storage::StorageBackend;
+
+/// Extensions of index files counted in place of the file they index
+const INDEX_EXTENSIONS: [&str; 2] = ["idx", "index"];
+
+/// Extensions of files with one sample per line
+const LINE_EXTENSIONS: [&str; 2] = ["jsonl", "ndjson"];
+
+/// Parquet files start and end with this
+const PARQUET_MAGIC: &[u8; 4] = b"PAR1";
+
+/// Deepest Thrift nesting followed in a Parquet footer
+const MAX_THRIFT_DEPTH: usize = 64;
+
+/// A data file and where its samples fall in the dataset
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestFile {
+    /// Full location, e.g. an absolute path or `s3://` URL
+    pub path: String,
+
+    /// Key of the file in the storage the manifest was built from
+    pub key: String,
+
+    /// Index of the file's first sample in the dataset
+    pub start: u64,
+
+    /// Number of samples in the file
+    pub samples: u64,
+
+    /// Format, e.g. "parquet", or the file extension for indexed files
+    pub format: String,
+}
+
+/// The files of a dataset, in order, with their sample counts
+#[derive(Debug, Clone, Default)]
+pub struct DatasetManifest {
+    files: Vec<ManifestFile>,
+}
+
+impl DatasetManifest {
+    /// Manifest of the files in `storage` matching `pattern`
+    ///
+    /// `pattern` is relative to the storage root and may use `*` and `?`
+    /// within a path segment and `**` for any number of directories. A
+    /// pattern naming a directory matches every file under it, and an
+    /// empty one matches all files. Index files are used for counting,
+    /// never listed themselves.
+    pub async fn build(storage: &dyn StorageBackend, pattern: &str) -> Result<Self> {
+        let listed = storage.list("").await?;
+        let pattern = pattern.trim_matches('/');
+        let mut files: Vec<String> = listed
+            .iter()
+            .filter(|key| !is_index(key) && pattern_matches(pattern, key))
+            .cloned()
+            .collect();
+        if files.is_empty() {
+            return Err(Error::InvalidShardConfig {
+                message: format!("No data files match '{}'", storage.uri(pattern)),
+            });
+        }
+        files.sort();
+
+        let listed: HashSet<String> = listed.into_iter().collect();
+        Self::count(storage, files, Some(&listed)).await
+    }
+
+    /// Manifest of `files` in `storage`, in the given order
+    pub async fn from_files(storage: &dyn StorageBackend, files: Vec<String>) -> Result<Self> {
+        if files.is_empty() {
+            return Err(Error::InvalidShardConfig {
+                message: "A dataset needs at least one file".to_string(),
+            });
+        }
+        Self::count(storage, files, None).await
+    }
+
+    async fn count(
+        storage: &dyn StorageBackend,
+        files: Vec<String>,
+        listed: Option<&HashSet<String>>,
+    ) -> Result<Self> {
+        let mut manifest = Self::default();
+        let mut start = 0;
+        for key in files {
+            let (samples, format) = count_samples(storage, &key, listed).await?;
+            manifest.files.push(ManifestFile {
+                path: storage.uri(&key),
+                key,
+                start,
+                samples,
+                format,
+            });
+            start += samples;
+        }
+        Ok(manifest)
+    }
+
+    /// The files, in sample order
+    pub fn files(&self) -> &[ManifestFile] {
+        &self.files
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.files.iter().map(|f| f.samples).sum()
+    }
+
+    /// Format shared by all files, or "mixed"
+    pub fn format(&self) -> &str {
+        match self.files.split_first() {
+            Some((first, rest)) if rest.iter().all(|f| f.format == first.format) => &first.format,
+            Some(_) => "mixed",
+            None => "",
+        }
+    }
+
+    /// Shard size giving about one shard per file
+    pub fn default_shard_size(&self) -> u64 {
+        self.total_samples()
+            .div_ceil(self.files.len().max(1) as u64)
+            .max(1)
+    }
+
+    /// The file holding sample `index`, and the sample's index within it
+    pub fn locate(&self, index: u64) -> Option<(&ManifestFile, u64)> {
+        let after = self.files.partition_point(|f| f.start <= index);
+        let file = self.files[..after]
+            .iter()
+            .rev()
+            .find(|f| index < f.start + f.samples)?;
+        Some((file, index - file.start))
+    }
+
+    /// The files holding samples `start..end`, each with the range of
+    /// those samples within the file
+    pub fn files_in(&self, start: u64, end: u64) -> Vec<(&ManifestFile, Range<u64>)> {
+        self.files
+            .iter()
+            .filter_map(|f| {
+                let from = start.max(f.start);
+                let to = end.min(f.start + f.samples);
+                (from < to).then(|| (f, from - f.start..to - f.start))
+            })
+            .collect()
+    }
+}
+
+/// Split `pattern` into its leading path without wildcards and the rest
+///
+/// `/data/train/*.parquet` splits into `/data/train` and `*.parquet`, and
+/// a pattern without wildcards into itself and an empty rest.
+pub fn split_pattern(pattern: &str) -> (&str, &str) {
+    let mut base_end = 0;
+    for (offset, segment) in segment_offsets(pattern) {
+        if segment.contains(['*', '?']) {
+            return (&pattern[..base_end], &pattern[offset..]);
+        }
+        base_end = offset + segment.len();
+    }
+    (pattern, "")
+}
+
+/// Segments of `path` with their byte offsets
+fn segment_offsets(path: &str) -> impl Iterator<Item = (usize, &str)> {
+    path.split('/')
+        .scan(0, |offset, segment| {
+            let start = *offset;
+            *offset += segment.len() + 1;
+            Some((start, segment))
+        })
+        .filter(|(_, segment)| !segment.is_empty())
+}
+
+fn is_index(key: &str) -> bool {
+    INDEX_EXTENSIONS
+        .iter()
+        .any(|ext| extension(key) == Some(ext))
+}
+
+fn extension(key: &str) -> Option<&str> {
+    let name = key.rsplit('/').next()?;
+    name.rsplit_once('.').map(|(_, ext)| ext)
+}
+
+/// Whether `key` matches `pattern`, or lies under a directory it names
+fn pattern_matches(pattern: &str, key: &str) -> bool {
+    if pattern.is_empty() {
+        return true;
+    }
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let key: Vec<&str> = key.split('/').filter(|s| !s.is_empty()).collect();
+    (1..=key.len()).any(|len| glob_segments(&pattern, &key[..len]))
+}
+
+fn glob_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => (0..=path.len()).any(|skip| glob_segments(rest, &path[skip..])),
+        Some((segment, rest)) => path.split_first().is_some_and(|(name, path)| {
+            let segment: Vec<char> = segment.chars().collect();
+            let name: Vec<char> = name.chars().collect();
+            glob_segment(&segment, &name) && glob_segments(rest, path)
+        }),
+    }
+}
+
+fn glob_segment(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| glob_segment(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && glob_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && glob_segment(rest, &name[1..]),
+    }
+}
+
+/// Samples in `key` and its format
+async fn count_samples(
+    storage: &dyn StorageBackend,
+    key: &str,
+    listed: Option<&HashSet<String>>,
+) -> Result<(u64, String)> {
+    let format = extension(key).unwrap_or_default().to_ascii_lowercase();
+    for ext in INDEX_EXTENSIONS {
+        let index = format!("{}.{}", key, ext);
+        let found = match listed {
+            Some(listed) => listed.contains(&index),
+            None => storage.exists(&index).await?,
+        };
+        if found {
+            return Ok((count_lines(&storage.read(&index).await?), format));
+        }
+    }
+
+    if format == "parquet" {
+        return Ok((parquet_rows(storage, key).await?, format));
+    }
+    if LINE_EXTENSIONS.contains(&format.as_str()) {
+        return Ok((count_lines(&storage.read(key).await?), "jsonl".to_string()));
+    }
+    Err(
+        Error::storage("Can't count samples without an index file, e.g. '<file>.idx'")
+            .with_path(storage.uri(key)),
+    )
+}
+
+fn count_lines(data: &[u8]) -> u64 {
+    data.split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .count() as u64
+}
+
+/// Row count from the footer of a Parquet file
+async fn parquet_rows(storage: &dyn StorageBackend, key: &str) -> Result<u64> {
+    let corrupt = |message: &str| Error::storage(message).with_path(storage.uri(key));
+
+    let size = storage.size(key).await?;
+    if size < 12 {
+        return Err(corrupt("Truncated Parquet file"));
+    }
+    let tail = storage.read_range(key, size - 8, 8).await?;
+    if tail.len() != 8 || &tail[4..] != PARQUET_MAGIC {
+        return Err(corrupt("Invalid Parquet footer magic"));
+    }
+    let footer_len = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]) as u64;
+    if footer_len > size - 12 {
+        return Err(corrupt("Truncated Parquet file"));
+    }
+    let footer = storage
+        .read_range(key, size - 8 - footer_len, footer_len)
+        .await?;
+    file_metadata_rows(&footer).ok_or_else(|| corrupt("Invalid Parquet file metadata"))
+}
+
+/// `num_rows` of a Thrift compact-encoded Parquet `FileMetaData`
+fn file_metadata_rows(footer: &[u8]) -> Option<u64> {
+    const NUM_ROWS_FIELD: i16 = 3;
+    const I64: u8 = 6;
+
+    let mut reader = CompactReader {
+        data: footer,
+        pos: 0,
+    };
+    let mut last_id = 0;
+    while let Some((id, kind)) = reader.field_header(&mut last_id)? {
+        if id == NUM_ROWS_FIELD && kind == I64 {
+            return u64::try_from(reader.zigzag()?).ok();
+        }
+        reader.skip(kind, false, 0)?;
+    }
+    None
+}
+
+/// Just enough of the Thrift compact protocol to find a field
+struct CompactReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl CompactReader<'_> {
+    fn byte(&mut self) -> Option<u8> {
+        let byte = *self.data.get(self.pos)?;
+        self.pos += 1;
+        Some(byte)
+    }
+
+    fn advance(&mut self, len: usize) -> Option<()> {
+        let end = self.pos.checked_add(len)?;
+        (end <= self.data.len()).then(|| self.pos = end)
+    }
+
+    fn varint(&mut self) -> Option<u64> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+        None
+    }
+
+    fn zigzag(&mut self) -> Option<i64> {
+        let value = self.varint()?;
+        Some((value >> 1) as i64 ^ -((value & 1) as i64))
+    }
+
+    /// Next field's ID and type, `None` inside at the end of the struct
+    fn field_header(&mut self, last_id: &mut i16) -> Option<Option<(i16, u8)>> {
+        let header = self.byte()?;
+        if header == 0 {
+            return Some(None);
+        }
+        let delta = (header >> 4) as i16;
+        let id = if delta == 0 {
+            self.zigzag()? as i16
+        } else {
+            last_id.checked_add(delta)?
+        };
+        *last_id = id;
+        Some(Some((id, header & 0x0f)))
+    }
+
+    /// Skip a value of compact type `kind`
+    ///
+    /// Booleans are in the field header, except as collection elements.
+    fn skip(&mut self, kind: u8, element: bool, depth: usize) -> Option<()> {
+        if depth > MAX_THRIFT_DEPTH {
+            return None;
+        }
+        match kind {
+            1 | 2 if element => self.advance(1),
+            1 | 2 => Some(()),
+            3 => self.advance(1),
+            4..=6 => self.varint().map(|_| ()),
+            7 => self.advance(8),
+            8 => {
+                let len = usize::try_from(self.varint()?).ok()?;
+                self.advance(len)
+            }
+            9 | 10 => {
+                let header = self.byte()?;
+                let len = match header >> 4 {
+                    15 => self.varint()?,
+                    len => u64::from(len),
+                };
+                for _ in 0..len {
+                    self.skip(header & 0x0f, true, depth + 1)?;
+                }
+                Some(())
+            }
+            11 => {
+                let len = self.varint()?;
+                if len > 0 {
+                    let kinds = self.byte()?;
+                    for _ in 0..len {
+                        self.skip(kinds >> 4, true, depth + 1)?;
+                        self.skip(kinds & 0x0f, true, depth + 1)?;
+                    }
+                }
+                Some(())
+            }
+            12 => {
+                let mut last_id = 0;
+                while let Some((_, kind)) = self.field_header(&mut last_id)? {
+                    self.skip(kind, false, depth + 1)?;
+                }
+                Some(())
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use storage::LocalStorage;
+    use tempfile::tempdir;
+
+    /// A Parquet file with `rows` rows and no data pages
+    fn parquet_file(rows: u64) -> Bytes {
+        let mut footer = vec![
+            0x15, 0x02, // version: i32 = 1
+            0x19, 0x1c, // schema: list of one struct
+            0x48, 0x01, b'x', // name: binary "x"
+            0x15, 0x00, // num_children: i32 = 0
+            0x00, // end of SchemaElement
+            0x16, // num_rows: i64
+        ];
+        let mut zigzag = rows << 1;
+        while zigzag >= 0x80 {
+            footer.push((zigzag as u8 & 0x7f) | 0x80);
+            zigzag >>= 7;
+        }
+        footer.push(zigzag as u8);
+        footer.extend_from_slice(&[0x19, 0x0c, 0x00]); // row_groups: empty list
+
+        let mut file = PARQUET_MAGIC.to_vec();
+        file.extend_from_slice(&footer);
+        file.extend_from_slice(&(footer.len() as u32).to_le_bytes());
+        file.extend_from_slice(PARQUET_MAGIC);
+        Bytes::from(file)
+    }
+
+    #[test]
+    fn test_split_pattern() {
+        assert_eq!(
+            split_pattern("/data/train/*.parquet"),
+            ("/data/train", "*.parquet")
+        );
+        assert_eq!(split_pattern("data/**/x.jsonl"), ("data", "**/x.jsonl"));
+        assert_eq!(split_pattern("*.jsonl"), ("", "*.jsonl"));
+        assert_eq!(split_pattern("/data/train"), ("/data/train", ""));
+    }
+
+    #[test]
+    fn test_pattern_matches() {
+        assert!(pattern_matches("*.parquet", "a.parquet"));
+        assert!(!pattern_matches("*.parquet", "dir/a.parquet"));
+        assert!(pattern_matches("**/*.parquet", "dir/sub/a.parquet"));
+        assert!(pattern_matches("**/*.parquet", "a.parquet"));
+        assert!(pattern_matches("part-?.jsonl", "part-1.jsonl"));
+        assert!(!pattern_matches("part-?.jsonl", "part-10.jsonl"));
+        // Directories match the files under them
+        assert!(pattern_matches("train", "train/a.jsonl"));
+        assert!(!pattern_matches("train", "training/a.jsonl"));
+        assert!(pattern_matches("", "anything"));
+    }
+
+    #[test]
+    fn test_file_metadata_rows() {
+        let file = parquet_file(1_000_000);
+        let footer_len =
+            u32::from_le_bytes(file[file.len() - 8..file.len() - 4].try_into().unwrap());
+        let footer = &file[file.len() - 8 - footer_len as usize..file.len() - 8];
+        assert_eq!(file_metadata_rows(footer), Some(1_000_000));
+        assert_eq!(file_metadata_rows(&footer[..5]), None);
+    }
+
+    #[tokio::test]
+    async fn test_build_manifest() {
+        let dir = tempdir().unwrap();
+        let storage = LocalStorage::new(dir.path());
+        storage
+            .write("train/a.parquet", parquet_file(300))
+            .await
+            .unwrap();
+        storage
+            .write("train/b.jsonl", Bytes::from("{}\n{}\n\n{}"))
+            .await
+            .unwrap();
+        storage
+            .write("train/c.tfrecord", Bytes::from_static(b"\x00\x01"))
+            .await
+            .unwrap();
+        storage
+            .write("train/c.tfrecord.idx", Bytes::from("0 10\n10 10\n"))
+            .await
+            .unwrap();
+        storage
+            .write("other/d.jsonl", Bytes::from("{}\n"))
+            .await
+            .unwrap();
+
+        let manifest = DatasetManifest::build(&storage, "train").await.unwrap();
+        let counts: Vec<_> = manifest
+            .files()
+            .iter()
+            .map(|f| (f.start, f.samples, f.format.as_str()))
+            .collect();
+        assert_eq!(
+            counts,
+            [(0, 300, "parquet"), (300, 3, "jsonl"), (303, 2, "tfrecord")]
+        );
+        assert_eq!(manifest.total_samples(), 305);
+        assert_eq!(manifest.format(), "mixed");
+        assert_eq!(manifest.default_shard_size(), 102);
+        assert!(manifest.files()[0].path.ends_with("train/a.parquet"));
+        assert_eq!(manifest.files()[0].key, "train/a.parquet");
+
+        let (file, offset) = manifest.locate(301).unwrap();
+        assert_eq!((file.format.as_str(), offset), ("jsonl", 1));
+        assert!(manifest.locate(305).is_none());
+
+        let spans: Vec<_> = manifest
+            .files_in(299, 304)
+            .into_iter()
+            .map(|(f, range)| (f.format.as_str(), range))
+            .collect();
+        assert_eq!(
+            spans,
+            [("parquet", 299..300), ("jsonl", 0..3), ("tfrecord", 0..1)]
+        );
+        assert!(manifest.files_in(305, 400).is_empty());
+
+        let manifest = DatasetManifest::build(&storage, "**/*.jsonl")
+            .await
+            .unwrap();
+        assert_eq!(manifest.total_samples(), 4);
+        assert_eq!(manifest.format(), "jsonl");
+
+        let manifest = DatasetManifest::from_files(&storage, vec!["train/c.tfrecord".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(manifest.total_samples(), 2);
+
+        assert!(DatasetManifest::build(&storage, "*.csv").await.is_err());
+        storage
+            .write("unknown/e.bin", Bytes::from_static(b"\x00"))
+            .await
+            .unwrap();
+        assert!(DatasetManifest::build(&storage, "unknown").await.is_err());
+    }
+}
@@ -1,16 +1,115 @@
 //! Checkpoint manager Python bindings
 //!
 //! Exposes async checkpoint operations with synchronous Python wrappers.
+//!
+//! Checkpoint data moves between Python and Rust without copies where it can:
+//! `save` reads any buffer-protocol object in place and large loads come
+//! back as a `memoryview` over the loaded data. `open_save` streams a
+//! checkpoint in chunks so it never has to exist as one Python object.
 
-use bytes::Bytes;
-use checkpoint::{CheckpointManager as RustCheckpointManager, CheckpointManagerConfig};
+use bytes::{Bytes, BytesMut};
+use checkpoint::{
+    CheckpointManager as RustCheckpointManager, CheckpointManagerConfig,
+    PendingCheckpoint as RustPendingCheckpoint, WriteStatus,
+};
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::{PyBufferError, PyRuntimeError, PyValueError};
 use pyo3::prelude::*;
-use pyo3::types::PyBytes;
+use pyo3::types::{PyBytes, PyDict, PyMemoryView};
+use runtime_core::{CheckpointId, CheckpointMetadata, ShutdownToken};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
+use storage::StorageBackend;
 use tokio::runtime::Runtime;
 
+use crate::parse_id;
+use crate::runtime::{ProcessLocal, SharedRuntime};
+
+/// Loads at least this large are returned as a `memoryview` instead of `bytes`
+pub const ZERO_COPY_LOAD_BYTES: usize = 64 * 1024 * 1024;
+
+/// Metadata keys recorded by `save_torch`
+const FRAMEWORK_KEY: &str = "framework";
+const FRAMEWORK_VERSION_KEY: &str = "framework_version";
+const FORMAT_KEY: &str = "format";
+
+/// A Python buffer handed to the checkpoint writer without copying
+struct BorrowedBuffer(PyBuffer<u8>);
+
+impl AsRef<[u8]> for BorrowedBuffer {
+    fn as_ref(&self) -> &[u8] {
+        let len = self.0.len_bytes();
+        if len == 0 {
+            return &[];
+        }
+        // SAFETY: the buffer was checked to be C-contiguous, and the exporter
+        // keeps its memory alive until the `PyBuffer` is released on drop
+        unsafe { std::slice::from_raw_parts(self.0.buf_ptr() as *const u8, len) }
+    }
+}
+
+/// View `data` as checkpoint bytes without copying it
+///
+/// Typed buffers such as float32 numpy arrays are read as their raw bytes.
+fn borrow_buffer(data: &Bound<'_, PyAny>) -> PyResult<Bytes> {
+    let buffer = match PyBuffer::<u8>::get_bound(data) {
+        Ok(buffer) => buffer,
+        Err(_) => {
+            let raw = PyMemoryView::from_bound(data)?.call_method1("cast", ("B",))?;
+            PyBuffer::<u8>::get_bound(&raw)?
+        }
+    };
+    if !buffer.is_c_contiguous() {
+        return Err(PyValueError::new_err(
+            "Checkpoint data must be a contiguous buffer",
+        ));
+    }
+    Ok(Bytes::from_owner(BorrowedBuffer(buffer)))
+}
+
+/// Read-only checkpoint data exposed through the buffer protocol
+///
+/// Returned wrapped in a `memoryview` by `CheckpointManager.load` for large
+/// checkpoints, so e.g. `numpy.frombuffer` reads it without a copy.
+#[pyclass(frozen)]
+pub struct CheckpointBuffer {
+    data: Bytes,
+}
+
+#[pymethods]
+impl CheckpointBuffer {
+    /// # Safety
+    ///
+    /// `view` must be a valid `Py_buffer` to fill, as passed by Python.
+    unsafe fn __getbuffer__(
+        slf: Bound<'_, Self>,
+        view: *mut pyo3::ffi::Py_buffer,
+        flags: std::os::raw::c_int,
+    ) -> PyResult<()> {
+        if flags & pyo3::ffi::PyBUF_WRITABLE == pyo3::ffi::PyBUF_WRITABLE {
+            return Err(PyBufferError::new_err("Checkpoint data is read-only"));
+        }
+        let data = &slf.get().data;
+        let filled = pyo3::ffi::PyBuffer_FillInfo(
+            view,
+            slf.as_ptr(),
+            data.as_ptr() as *mut std::os::raw::c_void,
+            data.len() as pyo3::ffi::Py_ssize_t,
+            1,
+            flags,
+        );
+        if filled == -1 {
+            return Err(PyErr::fetch(slf.py()));
+        }
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.data.len()
+    }
+}
+
 /// Metadata about a saved checkpoint
 #[pyclass]
 #[derive(Clone)]
@@ -38,6 +137,10 @@ pub struct CheckpointInfo {
     /// Creation timestamp (ISO 8601 string)
     #[pyo3(get)]
     pub created_at: String,
+
+    /// Metadata saved with the checkpoint
+    #[pyo3(get)]
+    pub metadata: HashMap<String, String>,
 }
 
 #[pymethods]
@@ -50,12 +153,135 @@ impl CheckpointInfo {
     }
 }
 
+impl From<CheckpointMetadata> for CheckpointInfo {
+    fn from(m: CheckpointMetadata) -> Self {
+        Self {
+            checkpoint_id: m.id.into(),
+            step: m.step,
+            epoch: m.epoch,
+            path: m.path,
+            size_bytes: m.size_bytes,
+            created_at: m.created_at.to_rfc3339(),
+            metadata: m.metadata,
+        }
+    }
+}
+
+/// A checkpoint write started by `save` that hasn't completed
+#[pyclass]
+#[derive(Clone)]
+pub struct PendingCheckpoint {
+    /// Checkpoint identifier returned by `save`
+    #[pyo3(get)]
+    pub checkpoint_id: String,
+
+    /// Training step at checkpoint
+    #[pyo3(get)]
+    pub step: u64,
+
+    /// Training epoch at checkpoint
+    #[pyo3(get)]
+    pub epoch: u64,
+
+    /// Size of the checkpoint data in bytes
+    #[pyo3(get)]
+    pub size_bytes: u64,
+
+    /// "queued", "writing" or "failed"
+    #[pyo3(get)]
+    pub status: String,
+
+    /// Why the write failed, if it did
+    #[pyo3(get)]
+    pub error: Option<String>,
+}
+
+#[pymethods]
+impl PendingCheckpoint {
+    fn __repr__(&self) -> String {
+        format!(
+            "PendingCheckpoint(id='{}', step={}, status='{}')",
+            self.checkpoint_id, self.step, self.status
+        )
+    }
+}
+
+impl From<RustPendingCheckpoint> for PendingCheckpoint {
+    fn from(p: RustPendingCheckpoint) -> Self {
+        let status = match p.status {
+            WriteStatus::Pending => "queued",
+            WriteStatus::InProgress => "writing",
+            WriteStatus::Completed => "completed",
+            WriteStatus::Failed => "failed",
+        };
+        Self {
+            checkpoint_id: p.id.into(),
+            step: p.step,
+            epoch: p.epoch,
+            size_bytes: p.size_bytes,
+            status: status.to_string(),
+            error: p.error,
+        }
+    }
+}
+
+/// Storage for `backend="s3"`
+#[cfg(feature = "s3")]
+fn s3_storage(
+    runtime: &Runtime,
+    bucket: Option<String>,
+    prefix: Option<String>,
+    endpoint: Option<String>,
+    region: Option<String>,
+) -> PyResult<Arc<dyn StorageBackend>> {
+    let bucket =
+        bucket.ok_or_else(|| PyValueError::new_err("bucket is required for the s3 backend"))?;
+    let config = s3_config(bucket, prefix, endpoint, region);
+    Ok(Arc::new(
+        runtime.block_on(storage::S3Storage::with_config(config)),
+    ))
+}
+
+/// S3 settings, path-style when talking to a custom endpoint
+#[cfg(feature = "s3")]
+pub(crate) fn s3_config(
+    bucket: String,
+    prefix: Option<String>,
+    endpoint: Option<String>,
+    region: Option<String>,
+) -> storage::S3Config {
+    let defaults = storage::S3Config::default();
+    storage::S3Config {
+        bucket,
+        prefix,
+        force_path_style: endpoint.is_some(),
+        endpoint_url: endpoint,
+        region: region.or(defaults.region),
+        ..defaults
+    }
+}
+
+#[cfg(not(feature = "s3"))]
+fn s3_storage(
+    _runtime: &Runtime,
+    _bucket: Option<String>,
+    _prefix: Option<String>,
+    _endpoint: Option<String>,
+    _region: Option<String>,
+) -> PyResult<Arc<dyn StorageBackend>> {
+    Err(PyValueError::new_err(
+        "dtruntime was built without S3 support, rebuild with the s3 feature",
+    ))
+}
+
 /// Checkpoint manager for saving and loading training checkpoints
 ///
 /// Provides async checkpoint writing with configurable retention.
 ///
 /// Example:
 ///     ckpt = CheckpointManager("/tmp/checkpoints", keep_count=5)
+///     # or straight to object storage
+///     ckpt = CheckpointManager(backend="s3", bucket="ckpts", prefix="run-1/")
 ///     
 ///     # Save a checkpoint
 ///     checkpoint_id = ckpt.save(model_bytes, step=1000, epoch=5)
@@ -66,7 +292,10 @@ impl CheckpointInfo {
 #[pyclass]
 pub struct CheckpointManager {
     inner: Arc<RustCheckpointManager>,
-    runtime: Arc<Runtime>,
+    runtime: SharedRuntime,
+    /// Stops the checkpoint writer on `close`. The writer runs in the
+    /// process that created the manager, not in forked children.
+    shutdown: ProcessLocal<ShutdownToken>,
 }
 
 #[pymethods]
@@ -74,33 +303,83 @@ impl CheckpointManager {
     /// Create a new checkpoint manager
     ///
     /// Args:
-    ///     base_path: Directory to store checkpoints
+    ///     base_path: Directory to store checkpoints, required for the local
+    ///         backend
     ///     keep_count: Number of checkpoints to retain (default: 5)
     ///     compression: Enable compression (default: True)
+    ///     backend: Where checkpoint data is written, "local" or "s3"
+    ///         (default: "local")
+    ///     bucket: S3 bucket, required for the s3 backend
+    ///     prefix: Prefix for checkpoint keys in the bucket
+    ///     endpoint: Custom S3 endpoint such as MinIO, uses path-style
+    ///         addressing
+    ///     region: AWS region (default: "us-east-1")
+    ///
+    /// Raises:
+    ///     ValueError: If the backend is unknown or missing its location
     #[new]
-    #[pyo3(signature = (base_path, keep_count=5, compression=true))]
-    fn new(base_path: &str, keep_count: usize, compression: bool) -> PyResult<Self> {
+    #[pyo3(signature = (
+        base_path=None,
+        keep_count=5,
+        compression=true,
+        backend="local",
+        bucket=None,
+        prefix=None,
+        endpoint=None,
+        region=None
+    ))]
+    #[allow(clippy::too_many_arguments)]
+    fn new(
+        base_path: Option<&str>,
+        keep_count: usize,
+        compression: bool,
+        backend: &str,
+        bucket: Option<String>,
+        prefix: Option<String>,
+        endpoint: Option<String>,
+        region: Option<String>,
+    ) -> PyResult<Self> {
+        // Create tokio runtime for async operations
+        let runtime = SharedRuntime::new()?;
+        let rt = runtime.get()?;
+        let shutdown = ShutdownToken::new();
+
+        let storage = match backend {
+            "local" if base_path.is_none() => {
+                return Err(PyValueError::new_err(
+                    "base_path is required for the local backend",
+                ))
+            }
+            "local" => None,
+            "s3" => Some(s3_storage(&rt, bucket, prefix, endpoint, region)?),
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown storage backend '{}', expected 'local' or 's3'",
+                    other
+                )))
+            }
+        };
+
         let config = CheckpointManagerConfig {
-            base_path: PathBuf::from(base_path),
+            base_path: base_path.map(PathBuf::from).unwrap_or_default(),
             keep_count,
             compression,
             ..Default::default()
         };
-
-        // Create tokio runtime for async operations
-        let runtime = Runtime::new().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Failed to create async runtime: {}",
-                e
-            ))
-        })?;
-
-        let inner = runtime.block_on(async { RustCheckpointManager::new(config).await });
+        let inner = rt.block_on(async {
+            match storage {
+                Some(storage) => {
+                    RustCheckpointManager::with_storage(config, storage, shutdown.clone()).await
+                }
+                None => RustCheckpointManager::with_shutdown(config, shutdown.clone()).await,
+            }
+        });
 
         match inner {
             Ok(manager) => Ok(Self {
                 inner: Arc::new(manager),
-                runtime: Arc::new(runtime),
+                runtime,
+                shutdown: ProcessLocal::new(shutdown),
             }),
             Err(e) => Err(pyo3::exceptions::PyIOError::new_err(format!(
                 "Failed to create checkpoint manager: {}",
@@ -111,8 +390,12 @@ impl CheckpointManager {
 
     /// Save a checkpoint asynchronously
     ///
+    /// The data is read in place rather than copied, so a mutable buffer
+    /// such as a numpy array must not change until `wait_pending()` returns.
+    ///
     /// Args:
-    ///     data: Checkpoint data as bytes
+    ///     data: Checkpoint data, bytes or any contiguous buffer such as a
+    ///         numpy array
     ///     step: Current training step
     ///     epoch: Current training epoch
     ///     metadata: Optional metadata dictionary
@@ -123,35 +406,48 @@ impl CheckpointManager {
     fn save(
         &self,
         py: Python<'_>,
-        data: &Bound<'_, PyBytes>,
+        data: &Bound<'_, PyAny>,
         step: u64,
         epoch: u64,
         metadata: Option<HashMap<String, String>>,
     ) -> PyResult<String> {
-        let bytes_data = Bytes::copy_from_slice(data.as_bytes());
-        let meta = metadata.unwrap_or_default();
-        let inner = self.inner.clone();
+        let data = borrow_buffer(data)?;
+        self.save_bytes(py, data, step, epoch, metadata.unwrap_or_default())
+    }
 
-        // Release GIL during async operation
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                inner
-                    .save_async(
-                        bytes_data,
-                        step,
-                        epoch,
-                        runtime_core::CheckpointType::Full,
-                        meta,
-                    )
-                    .await
-                    .map_err(|e| {
-                        pyo3::exceptions::PyIOError::new_err(format!(
-                            "Failed to save checkpoint: {}",
-                            e
-                        ))
-                    })
-            })
-        })
+    /// Start a checkpoint to be written in chunks
+    ///
+    /// Useful with `torch.save`, which can write straight into the returned
+    /// writer instead of building the whole checkpoint as `bytes` first.
+    /// The checkpoint is queued for saving when the writer is closed.
+    ///
+    /// Args:
+    ///     step: Current training step
+    ///     epoch: Current training epoch
+    ///     metadata: Optional metadata dictionary
+    ///
+    /// Returns:
+    ///     A file-like CheckpointWriter
+    ///
+    /// Example:
+    ///     with ckpt.open_save(step=1000, epoch=5) as f:
+    ///         torch.save(model.state_dict(), f)
+    ///     checkpoint_id = f.checkpoint_id
+    #[pyo3(signature = (step, epoch, metadata=None))]
+    fn open_save(
+        slf: Py<Self>,
+        step: u64,
+        epoch: u64,
+        metadata: Option<HashMap<String, String>>,
+    ) -> CheckpointWriter {
+        CheckpointWriter {
+            manager: slf,
+            step,
+            epoch,
+            metadata: metadata.unwrap_or_default(),
+            buffer: Some(BytesMut::new()),
+            checkpoint_id: None,
+        }
     }
 
     /// Load checkpoint data by ID
@@ -160,13 +456,15 @@ impl CheckpointManager {
     ///     checkpoint_id: The checkpoint ID to load
     ///
     /// Returns:
-    ///     Checkpoint data as bytes
+    ///     Checkpoint data as bytes, or as a read-only memoryview without a
+    ///     copy for checkpoints of 64 MiB or more
     fn load(&self, py: Python<'_>, checkpoint_id: &str) -> PyResult<PyObject> {
+        let ckpt_id: CheckpointId = parse_id(checkpoint_id)?;
         let inner = self.inner.clone();
-        let ckpt_id = checkpoint_id.to_string();
 
+        let runtime = self.runtime.get()?;
         let data = py.allow_threads(|| {
-            self.runtime.block_on(async move {
+            runtime.block_on(async move {
                 inner.load(&ckpt_id).await.map_err(|e| {
                     pyo3::exceptions::PyIOError::new_err(format!(
                         "Failed to load checkpoint: {}",
@@ -175,8 +473,169 @@ impl CheckpointManager {
                 })
             })
         })?;
+        checkpoint_data(py, data)
+    }
+
+    /// Load the latest checkpoint
+    ///
+    /// Unlike `latest()` followed by `load()`, a checkpoint removed by the
+    /// retention policy in between is skipped for the newer one instead of
+    /// failing.
+    ///
+    /// Returns:
+    ///     `(data, info)` with data as returned by `load`, or None if no
+    ///     checkpoints exist
+    fn load_latest(&self, py: Python<'_>) -> PyResult<Option<(PyObject, CheckpointInfo)>> {
+        let inner = self.inner.clone();
+
+        let runtime = self.runtime.get()?;
+        let latest = py.allow_threads(|| {
+            runtime.block_on(async move {
+                inner.load_latest().await.map_err(|e| {
+                    pyo3::exceptions::PyIOError::new_err(format!(
+                        "Failed to load latest checkpoint: {}",
+                        e
+                    ))
+                })
+            })
+        })?;
+        latest
+            .map(|(meta, data)| Ok((checkpoint_data(py, data)?, CheckpointInfo::from(meta))))
+            .transpose()
+    }
+
+    /// Where to resume training from
+    ///
+    /// Returns:
+    ///     `(data, step, epoch)` of the latest checkpoint, or None when
+    ///     training starts from scratch
+    ///
+    /// Example:
+    ///     start_step, start_epoch = 0, 0
+    ///     resume = ckpt.resume_or_none()
+    ///     if resume is not None:
+    ///         data, start_step, start_epoch = resume
+    fn resume_or_none(&self, py: Python<'_>) -> PyResult<Option<(PyObject, u64, u64)>> {
+        Ok(self
+            .load_latest(py)?
+            .map(|(data, info)| (data, info.step, info.epoch)))
+    }
 
-        Ok(PyBytes::new_bound(py, &data).into())
+    /// Save a PyTorch state dict
+    ///
+    /// Serializes `state_dict` with `torch.save`, or with safetensors for
+    /// `format="safetensors"`, which only takes a flat dict of tensors. The
+    /// format and torch version are added to the metadata under `format`,
+    /// `framework` and `framework_version` so `load_torch` can read the
+    /// checkpoint back.
+    ///
+    /// Args:
+    ///     state_dict: State to save, e.g. `model.state_dict()`
+    ///     step: Current training step
+    ///     epoch: Current training epoch
+    ///     metadata: Optional metadata dictionary
+    ///     format: "torch" (default) or "safetensors"
+    ///
+    /// Returns:
+    ///     Checkpoint ID string
+    ///
+    /// Example:
+    ///     ckpt_id = ckpt.save_torch(model.state_dict(), step=1000, epoch=5)
+    ///     model.load_state_dict(ckpt.load_torch(ckpt_id, map_location="cpu"))
+    #[pyo3(signature = (state_dict, step, epoch, metadata=None, format="torch"))]
+    fn save_torch(
+        &self,
+        py: Python<'_>,
+        state_dict: &Bound<'_, PyAny>,
+        step: u64,
+        epoch: u64,
+        metadata: Option<HashMap<String, String>>,
+        format: &str,
+    ) -> PyResult<String> {
+        let torch = py.import_bound("torch")?;
+        let data = match format {
+            "torch" => {
+                let file = py.import_bound("io")?.call_method0("BytesIO")?;
+                torch.call_method1("save", (state_dict, &file))?;
+                borrow_buffer(&file.call_method0("getbuffer")?)?
+            }
+            "safetensors" => {
+                let safetensors = py.import_bound("safetensors.torch")?;
+                borrow_buffer(&safetensors.call_method1("save", (state_dict,))?)?
+            }
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "Unknown format '{}', expected 'torch' or 'safetensors'",
+                    other
+                )))
+            }
+        };
+
+        let mut metadata = metadata.unwrap_or_default();
+        metadata.insert(FRAMEWORK_KEY.to_string(), "torch".to_string());
+        metadata.insert(
+            FRAMEWORK_VERSION_KEY.to_string(),
+            torch.getattr("__version__")?.str()?.to_string(),
+        );
+        metadata.insert(FORMAT_KEY.to_string(), format.to_string());
+        self.save_bytes(py, data, step, epoch, metadata)
+    }
+
+    /// Load a state dict saved with `save_torch`
+    ///
+    /// Args:
+    ///     checkpoint_id: The checkpoint ID to load
+    ///     map_location: Device to load tensors onto, as for `torch.load`
+    ///     weights_only: Only unpickle tensors and plain containers, as for
+    ///         `torch.load` (default: True). Ignored for safetensors.
+    ///
+    /// Returns:
+    ///     The saved state dict
+    #[pyo3(signature = (checkpoint_id, map_location=None, weights_only=true))]
+    fn load_torch(
+        &self,
+        py: Python<'_>,
+        checkpoint_id: &str,
+        map_location: Option<&Bound<'_, PyAny>>,
+        weights_only: bool,
+    ) -> PyResult<PyObject> {
+        let ckpt_id: CheckpointId = parse_id(checkpoint_id)?;
+        let format = self
+            .inner
+            .get(&ckpt_id)
+            .and_then(|m| m.metadata.get(FORMAT_KEY).cloned())
+            .unwrap_or_else(|| "torch".to_string());
+        let data = self.load(py, checkpoint_id)?;
+
+        match format.as_str() {
+            "torch" => {
+                let file = py.import_bound("io")?.call_method1("BytesIO", (data,))?;
+                let kwargs = PyDict::new_bound(py);
+                kwargs.set_item("map_location", map_location)?;
+                kwargs.set_item("weights_only", weights_only)?;
+                let torch = py.import_bound("torch")?;
+                Ok(torch.call_method("load", (file,), Some(&kwargs))?.unbind())
+            }
+            "safetensors" => {
+                let safetensors = py.import_bound("safetensors.torch")?;
+                let bytes = py
+                    .import_bound("builtins")?
+                    .call_method1("bytes", (data,))?;
+                let state = safetensors
+                    .call_method1("load", (bytes,))?
+                    .downcast_into::<PyDict>()?;
+                if let Some(device) = map_location {
+                    for (name, tensor) in state.items().extract::<Vec<(PyObject, PyObject)>>()? {
+                        state.set_item(name, tensor.call_method1(py, "to", (device,))?)?;
+                    }
+                }
+                Ok(state.into_any().unbind())
+            }
+            other => Err(PyValueError::new_err(format!(
+                "Checkpoint {} has format '{}', not a torch checkpoint",
+                checkpoint_id, other
+            ))),
+        }
     }
 
     /// Get the latest checkpoint info
@@ -184,14 +643,7 @@ impl CheckpointManager {
     /// Returns:
     ///     CheckpointInfo or None if no checkpoints exist
     fn latest(&self) -> Option<CheckpointInfo> {
-        self.inner.latest().map(|m| CheckpointInfo {
-            checkpoint_id: m.id,
-            step: m.step,
-            epoch: m.epoch,
-            path: m.path,
-            size_bytes: m.size_bytes,
-            created_at: m.created_at.to_rfc3339(),
-        })
+        self.inner.latest().map(CheckpointInfo::from)
     }
 
     /// Get checkpoint info by step
@@ -202,14 +654,7 @@ impl CheckpointManager {
     /// Returns:
     ///     CheckpointInfo or None if not found
     fn get_by_step(&self, step: u64) -> Option<CheckpointInfo> {
-        self.inner.get_by_step(step).map(|m| CheckpointInfo {
-            checkpoint_id: m.id,
-            step: m.step,
-            epoch: m.epoch,
-            path: m.path,
-            size_bytes: m.size_bytes,
-            created_at: m.created_at.to_rfc3339(),
-        })
+        self.inner.get_by_step(step).map(CheckpointInfo::from)
     }
 
     /// Get all checkpoint infos
@@ -220,23 +665,42 @@ impl CheckpointManager {
         self.inner
             .all_checkpoints()
             .into_iter()
-            .map(|m| CheckpointInfo {
-                checkpoint_id: m.id,
-                step: m.step,
-                epoch: m.epoch,
-                path: m.path,
-                size_bytes: m.size_bytes,
-                created_at: m.created_at.to_rfc3339(),
-            })
+            .map(CheckpointInfo::from)
             .collect()
     }
 
+    /// Get the checkpoint writes that haven't completed
+    ///
+    /// Writes are queued, then written one at a time. Failed writes stay
+    /// listed so they can be reported; completed ones show up in
+    /// `all_checkpoints()` instead.
+    ///
+    /// Returns:
+    ///     List of PendingCheckpoint objects, oldest step first
+    ///
+    /// Example:
+    ///     for write in ckpt.pending():
+    ///         print(write.checkpoint_id, write.status)
+    fn pending(&self) -> Vec<PendingCheckpoint> {
+        let mut pending: Vec<_> = self
+            .inner
+            .pending_writes()
+            .into_iter()
+            .filter(|p| p.status != WriteStatus::Completed)
+            .map(PendingCheckpoint::from)
+            .collect();
+        pending.sort_by(|a, b| (a.step, &a.checkpoint_id).cmp(&(b.step, &b.checkpoint_id)));
+        pending
+    }
+
     /// Wait for all pending checkpoint writes to complete
     fn wait_pending(&self, py: Python<'_>) -> PyResult<()> {
+        self.check_writer()?;
         let inner = self.inner.clone();
 
+        let runtime = self.runtime.get()?;
         py.allow_threads(|| {
-            self.runtime.block_on(async move {
+            runtime.block_on(async move {
                 inner.wait_pending().await.map_err(|e| {
                     pyo3::exceptions::PyIOError::new_err(format!(
                         "Pending checkpoint writes failed: {}",
@@ -247,8 +711,243 @@ impl CheckpointManager {
         })
     }
 
+    /// Wait for pending writes, then stop the writer
+    ///
+    /// The manager can't save or load afterwards. Closing twice is a no-op.
+    /// In a forked child, writes pending in the parent are left to it.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        if self.runtime.is_closed() {
+            return Ok(());
+        }
+        let flushed = match self.shutdown.get() {
+            Some(shutdown) => {
+                let flushed = self.wait_pending(py);
+                shutdown.shutdown("checkpoint manager closed");
+                flushed
+            }
+            None => Ok(()),
+        };
+        self.runtime.close();
+        flushed
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Close the manager, see `close`
+    ///
+    /// If the block raised, failed writes are logged instead of hiding the
+    /// original exception.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        match self.close(py) {
+            Err(e) if exc_type.is_some() => {
+                tracing::warn!(error = %e, "Failed to close checkpoint manager");
+                Ok(false)
+            }
+            result => result.map(|()| false),
+        }
+    }
+
     fn __repr__(&self) -> String {
         let count = self.inner.all_checkpoints().len();
         format!("CheckpointManager(checkpoints={})", count)
     }
 }
+
+/// Checkpoint data for Python, without a copy for large checkpoints
+fn checkpoint_data(py: Python<'_>, data: Bytes) -> PyResult<PyObject> {
+    if data.len() >= ZERO_COPY_LOAD_BYTES {
+        let buffer = Bound::new(py, CheckpointBuffer { data })?;
+        return Ok(PyMemoryView::from_bound(buffer.as_any())?
+            .into_any()
+            .unbind());
+    }
+    Ok(PyBytes::new_bound(py, &data).into())
+}
+
+impl CheckpointManager {
+    /// Fail if the writer isn't running in this process
+    ///
+    /// A forked child inherits the queue of the parent's writer, but not
+    /// the thread draining it, so saving would wait forever.
+    fn check_writer(&self) -> PyResult<()> {
+        if self.shutdown.is_local() {
+            return Ok(());
+        }
+        Err(PyRuntimeError::new_err(
+            "CheckpointManager was created before this process forked; \
+             create a new one to save from here",
+        ))
+    }
+
+    /// Queue `data` for writing, releasing the GIL while it is handed over
+    fn save_bytes(
+        &self,
+        py: Python<'_>,
+        data: Bytes,
+        step: u64,
+        epoch: u64,
+        metadata: HashMap<String, String>,
+    ) -> PyResult<String> {
+        self.check_writer()?;
+        let inner = self.inner.clone();
+
+        // Release GIL during async operation
+        let runtime = self.runtime.get()?;
+        py.allow_threads(|| {
+            runtime.block_on(async move {
+                inner
+                    .save_async(
+                        data,
+                        step,
+                        epoch,
+                        runtime_core::CheckpointType::Full,
+                        metadata,
+                    )
+                    .await
+                    .map(String::from)
+                    .map_err(|e| {
+                        pyo3::exceptions::PyIOError::new_err(format!(
+                            "Failed to save checkpoint: {}",
+                            e
+                        ))
+                    })
+            })
+        })
+    }
+}
+
+/// File-like writer for a checkpoint saved in chunks
+///
+/// Returned by `CheckpointManager.open_save`. Chunks are copied into memory
+/// owned by the manager as they are written, and the checkpoint is queued
+/// for saving on `close()`. Leaving a `with` block with an exception
+/// discards it instead.
+#[pyclass]
+pub struct CheckpointWriter {
+    manager: Py<CheckpointManager>,
+    step: u64,
+    epoch: u64,
+    metadata: HashMap<String, String>,
+    /// Data written so far, `None` once closed or aborted
+    buffer: Option<BytesMut>,
+    /// ID of the checkpoint once saved
+    checkpoint_id: Option<String>,
+}
+
+impl CheckpointWriter {
+    fn buffer(&mut self) -> PyResult<&mut BytesMut> {
+        self.buffer
+            .as_mut()
+            .ok_or_else(|| PyValueError::new_err("I/O operation on closed checkpoint writer"))
+    }
+}
+
+#[pymethods]
+impl CheckpointWriter {
+    /// Append a chunk, bytes or any contiguous buffer
+    ///
+    /// Returns:
+    ///     Number of bytes written
+    fn write(&mut self, data: &Bound<'_, PyAny>) -> PyResult<usize> {
+        let chunk = borrow_buffer(data)?;
+        let buffer = self.buffer()?;
+        buffer.extend_from_slice(&chunk);
+        Ok(chunk.len())
+    }
+
+    /// Bytes written so far
+    fn tell(&mut self) -> PyResult<usize> {
+        Ok(self.buffer()?.len())
+    }
+
+    /// No-op, data is held until `close()`
+    fn flush(&mut self) -> PyResult<()> {
+        self.buffer().map(|_| ())
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    /// Whether the writer was closed or aborted
+    #[getter]
+    fn closed(&self) -> bool {
+        self.buffer.is_none()
+    }
+
+    /// ID of the saved checkpoint, `None` until closed
+    #[getter]
+    fn checkpoint_id(&self) -> Option<String> {
+        self.checkpoint_id.clone()
+    }
+
+    /// Queue the checkpoint for saving
+    ///
+    /// Like `save`, the write happens in the background; call
+    /// `wait_pending()` on the manager to wait for it. Closing twice
+    /// returns the same ID.
+    ///
+    /// Returns:
+    ///     Checkpoint ID string, or None if the writer was aborted
+    fn close(&mut self, py: Python<'_>) -> PyResult<Option<String>> {
+        if let Some(buffer) = self.buffer.take() {
+            let manager = self.manager.borrow(py);
+            let checkpoint_id = manager.save_bytes(
+                py,
+                buffer.freeze(),
+                self.step,
+                self.epoch,
+                std::mem::take(&mut self.metadata),
+            )?;
+            self.checkpoint_id = Some(checkpoint_id);
+        }
+        Ok(self.checkpoint_id.clone())
+    }
+
+    /// Discard the data written so far without saving it
+    fn abort(&mut self) {
+        self.buffer = None;
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Close the writer, or abort it if the block raised
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &mut self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        if exc_type.is_some() {
+            self.abort();
+        } else {
+            self.close(py)?;
+        }
+        Ok(false)
+    }
+
+    fn __repr__(&self) -> String {
+        match (&self.buffer, &self.checkpoint_id) {
+            (Some(buffer), _) => format!(
+                "CheckpointWriter(step={}, written={})",
+                self.step,
+                buffer.len()
+            ),
+            (None, Some(id)) => format!("CheckpointWriter(step={}, id='{}')", self.step, id),
+            (None, None) => format!("CheckpointWriter(step={}, aborted)", self.step),
+        }
+    }
+}
@@ -1,10 +1,21 @@
 //! Dataset registry Python bindings
 //!
-//! Exposes `ShardManager` functionality for dataset registration and shard assignment.
+//! Exposes `ShardManager` functionality for dataset registration and shard assignment,
+//! the hashing and shuffling behind it, and dataset manifests built from the files in
+//! storage.
 
-use data_shard::ShardManager;
+use data_shard::{
+    split_pattern, ConsistentHash as RustConsistentHash, DatasetManifest as RustDatasetManifest,
+    EpochCoordinator as RustEpochCoordinator, ShardManager,
+};
+use pyo3::exceptions::{PyIOError, PyValueError};
 use pyo3::prelude::*;
+use runtime_core::{DatasetId, WorkerId};
+use std::path::Path;
 use std::sync::Arc;
+use storage::LocalStorage;
+
+use crate::parse_id;
 
 /// Information about a shard assignment
 #[pyclass]
@@ -89,16 +100,20 @@ impl DatasetRegistry {
     ///
     /// Args:
     ///     worker_id: Unique identifier for the worker
-    fn register_worker(&self, worker_id: &str) {
-        self.manager.register_worker(worker_id);
+    fn register_worker(&self, worker_id: &str) -> PyResult<()> {
+        let worker_id: WorkerId = parse_id(worker_id)?;
+        self.manager.register_worker(&worker_id);
+        Ok(())
     }
 
     /// Remove a worker from the registry
     ///
     /// Args:
     ///     worker_id: Identifier of the worker to remove
-    fn remove_worker(&self, worker_id: &str) {
-        self.manager.remove_worker(worker_id);
+    fn remove_worker(&self, worker_id: &str) -> PyResult<()> {
+        let worker_id: WorkerId = parse_id(worker_id)?;
+        self.manager.remove_worker(&worker_id);
+        Ok(())
     }
 
     /// Register a dataset for sharding
@@ -117,9 +132,11 @@ impl DatasetRegistry {
         shard_size: u64,
         shuffle: bool,
         seed: u64,
-    ) {
+    ) -> PyResult<()> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
         self.manager
-            .register_dataset_params(dataset_id, total_samples, shard_size, shuffle, seed);
+            .register_dataset_params(&dataset_id, total_samples, shard_size, shuffle, seed);
+        Ok(())
     }
 
     /// Get shard assignments for a worker in a given epoch
@@ -137,9 +154,11 @@ impl DatasetRegistry {
         worker_id: &str,
         epoch: u64,
     ) -> PyResult<Vec<ShardInfo>> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        let worker_id: WorkerId = parse_id(worker_id)?;
         let assignments = self
             .manager
-            .get_shard_for_worker(dataset_id, worker_id, epoch)
+            .get_shard_for_worker(&dataset_id, &worker_id, epoch)
             .ok_or_else(|| {
                 pyo3::exceptions::PyValueError::new_err(format!(
                     "Failed to get shards for worker '{}' on dataset '{}'",
@@ -150,7 +169,7 @@ impl DatasetRegistry {
         Ok(assignments
             .into_iter()
             .map(|a| ShardInfo {
-                dataset_id: a.dataset_id,
+                dataset_id: a.dataset_id.into(),
                 shard_id: a.shard_id,
                 total_shards: a.total_shards,
                 start_index: a.start_index,
@@ -169,7 +188,8 @@ impl DatasetRegistry {
     /// Returns:
     ///     The new epoch number
     fn advance_epoch(&self, dataset_id: &str) -> PyResult<u64> {
-        self.manager.advance_epoch(dataset_id).ok_or_else(|| {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        self.manager.advance_epoch(&dataset_id).ok_or_else(|| {
             pyo3::exceptions::PyValueError::new_err(format!("Dataset '{}' not found", dataset_id))
         })
     }
@@ -181,8 +201,9 @@ impl DatasetRegistry {
     ///
     /// Returns:
     ///     Current epoch number
-    fn current_epoch(&self, dataset_id: &str) -> u64 {
-        self.manager.current_epoch(dataset_id)
+    fn current_epoch(&self, dataset_id: &str) -> PyResult<u64> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        Ok(self.manager.current_epoch(&dataset_id))
     }
 
     /// Get the number of active workers
@@ -199,12 +220,20 @@ impl DatasetRegistry {
 
     /// Get list of active worker IDs
     fn active_workers(&self) -> Vec<String> {
-        self.manager.active_workers()
+        self.manager
+            .active_workers()
+            .into_iter()
+            .map(String::from)
+            .collect()
     }
 
     /// Get list of registered dataset IDs
     fn datasets(&self) -> Vec<String> {
-        self.manager.datasets()
+        self.manager
+            .datasets()
+            .into_iter()
+            .map(String::from)
+            .collect()
     }
 
     fn __repr__(&self) -> String {
@@ -215,3 +244,483 @@ impl DatasetRegistry {
         )
     }
 }
+
+/// Consistent hash ring assigning unshuffled shards to workers
+///
+/// The ring the coordinator uses for datasets registered with
+/// `shuffle=False`, keyed by worker ID.
+///
+/// Example:
+///     ring = ConsistentHash()
+///     ring.add_node("worker-0")
+///     ring.add_node("worker-1")
+///     shards = ring.get_shards_for_node("worker-0", "imagenet", total_shards=128)
+#[pyclass]
+pub struct ConsistentHash {
+    ring: RustConsistentHash,
+}
+
+#[pymethods]
+impl ConsistentHash {
+    /// Create an empty hash ring
+    ///
+    /// Args:
+    ///     virtual_nodes: Ring positions per node (default: as the coordinator)
+    #[new]
+    #[pyo3(signature = (virtual_nodes=None))]
+    fn new(virtual_nodes: Option<usize>) -> PyResult<Self> {
+        let ring = match virtual_nodes {
+            Some(0) => return Err(PyValueError::new_err("virtual_nodes must be positive")),
+            Some(n) => RustConsistentHash::with_virtual_nodes(n),
+            None => RustConsistentHash::new(),
+        };
+        Ok(Self { ring })
+    }
+
+    /// Add a node to the ring
+    fn add_node(&self, node_id: &str) {
+        self.ring.add_node(node_id);
+    }
+
+    /// Remove a node from the ring
+    fn remove_node(&self, node_id: &str) {
+        self.ring.remove_node(node_id);
+    }
+
+    /// Node responsible for a key, or None for an empty ring
+    fn get_node(&self, key: &str) -> Option<String> {
+        self.ring.get_node(key)
+    }
+
+    /// Node responsible for a shard of a dataset
+    fn get_node_for_shard(&self, dataset_id: &str, shard_id: u64) -> Option<String> {
+        self.ring.get_node_for_shard(dataset_id, shard_id)
+    }
+
+    /// Shards of a dataset assigned to a node, in shard order
+    ///
+    /// Args:
+    ///     node_id: Node identifier
+    ///     dataset_id: Dataset identifier
+    ///     total_shards: Number of shards in the dataset
+    fn get_shards_for_node(&self, node_id: &str, dataset_id: &str, total_shards: u64) -> Vec<u64> {
+        self.ring
+            .get_shards_for_node(node_id, dataset_id, total_shards)
+    }
+
+    /// Node IDs, in the order they were added
+    fn nodes(&self) -> Vec<String> {
+        self.ring.nodes()
+    }
+
+    /// Remove every node
+    fn clear(&self) {
+        self.ring.clear();
+    }
+
+    fn __len__(&self) -> usize {
+        self.ring.node_count()
+    }
+
+    fn __contains__(&self, node_id: &str) -> bool {
+        self.ring.contains_node(node_id)
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ConsistentHash(nodes={})", self.ring.node_count())
+    }
+}
+
+/// Deterministic per-epoch shard shuffling
+///
+/// Shuffles like the coordinator does for a dataset registered with
+/// `shuffle=True` and the same seed, and deals the shuffled shards to
+/// workers round-robin by rank.
+///
+/// Example:
+///     epochs = EpochCoordinator(seed=42)
+///     order = epochs.get_shuffled_shards("imagenet", epoch=0, total_shards=128)
+///     mine = epochs.get_worker_shards("imagenet", 0, 128, worker_rank=rank, world_size=8)
+#[pyclass]
+pub struct EpochCoordinator {
+    coordinator: RustEpochCoordinator,
+}
+
+#[pymethods]
+impl EpochCoordinator {
+    /// Create an epoch coordinator
+    ///
+    /// Args:
+    ///     seed: Seed for every dataset without its own (default: random)
+    #[new]
+    #[pyo3(signature = (seed=None))]
+    fn new(seed: Option<u64>) -> Self {
+        let coordinator = match seed {
+            Some(seed) => RustEpochCoordinator::with_seed(seed),
+            None => RustEpochCoordinator::new(),
+        };
+        Self { coordinator }
+    }
+
+    /// Seed for datasets without their own
+    #[getter]
+    fn base_seed(&self) -> u64 {
+        self.coordinator.base_seed()
+    }
+
+    /// Shuffle a dataset with its own seed, as given to `register_dataset`
+    fn set_seed(&self, dataset_id: &str, seed: u64) -> PyResult<()> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        self.coordinator.set_seed(&dataset_id, seed);
+        Ok(())
+    }
+
+    /// Seed a dataset is shuffled with
+    fn seed(&self, dataset_id: &str) -> PyResult<u64> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        Ok(self.coordinator.seed(&dataset_id))
+    }
+
+    /// Current epoch for a dataset
+    fn current_epoch(&self, dataset_id: &str) -> PyResult<u64> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        Ok(self.coordinator.current_epoch(&dataset_id))
+    }
+
+    /// Set the current epoch for a dataset
+    fn init_epoch(&self, dataset_id: &str, epoch: u64) -> PyResult<()> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        self.coordinator.init_epoch(&dataset_id, epoch);
+        Ok(())
+    }
+
+    /// Advance a dataset to its next epoch
+    ///
+    /// Returns:
+    ///     The new epoch number
+    fn advance_epoch(&self, dataset_id: &str) -> PyResult<u64> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        Ok(self.coordinator.advance_epoch(&dataset_id))
+    }
+
+    /// Every shard of a dataset in its shuffled order for an epoch
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///     epoch: Training epoch
+    ///     total_shards: Number of shards in the dataset
+    fn get_shuffled_shards(
+        &self,
+        dataset_id: &str,
+        epoch: u64,
+        total_shards: u64,
+    ) -> PyResult<Vec<u64>> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        let shards = self
+            .coordinator
+            .get_shuffled_shards(&dataset_id, epoch, total_shards);
+        Ok(shards.to_vec())
+    }
+
+    /// Shards of a dataset for one worker in an epoch, in shuffled order
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///     epoch: Training epoch
+    ///     total_shards: Number of shards in the dataset
+    ///     worker_rank: Rank of the worker
+    ///     world_size: Number of workers
+    fn get_worker_shards(
+        &self,
+        dataset_id: &str,
+        epoch: u64,
+        total_shards: u64,
+        worker_rank: u32,
+        world_size: u32,
+    ) -> PyResult<Vec<u64>> {
+        if worker_rank >= world_size {
+            return Err(PyValueError::new_err(format!(
+                "worker_rank {} is out of range for world_size {}",
+                worker_rank, world_size
+            )));
+        }
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        Ok(self.coordinator.get_worker_shards(
+            &dataset_id,
+            epoch,
+            total_shards,
+            worker_rank,
+            world_size,
+        ))
+    }
+
+    /// Forget a dataset's epoch, seed and cached shuffles
+    fn remove_dataset(&self, dataset_id: &str) -> PyResult<()> {
+        let dataset_id: DatasetId = parse_id(dataset_id)?;
+        self.coordinator.remove_dataset(&dataset_id);
+        Ok(())
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "EpochCoordinator(base_seed={}, datasets={})",
+            self.coordinator.base_seed(),
+            self.coordinator.all_epochs().len()
+        )
+    }
+}
+
+/// Files of a dataset, given as a glob, directory or file, or as a list of files
+#[derive(FromPyObject)]
+pub enum DatasetPaths {
+    Pattern(String),
+    Files(Vec<String>),
+}
+
+/// Manifest of the files behind a registered dataset
+///
+/// Returned by `TrainingOrchestrator.register_dataset_from_manifest`.
+#[pyclass]
+#[derive(Clone)]
+pub struct DatasetManifest {
+    /// Dataset identifier
+    #[pyo3(get)]
+    pub dataset_id: String,
+
+    /// Directory holding the files, as registered with the coordinator
+    #[pyo3(get)]
+    pub path: String,
+
+    /// Format shared by the files, or "mixed"
+    #[pyo3(get)]
+    pub format: String,
+
+    /// Samples per shard
+    #[pyo3(get)]
+    pub shard_size: u64,
+
+    /// Total number of shards, as assigned by the coordinator
+    #[pyo3(get)]
+    pub total_shards: u64,
+
+    manifest: RustDatasetManifest,
+}
+
+impl DatasetManifest {
+    pub(crate) fn new(
+        dataset_id: String,
+        path: String,
+        manifest: RustDatasetManifest,
+        shard_size: u64,
+        total_shards: u64,
+    ) -> Self {
+        Self {
+            dataset_id,
+            path,
+            format: manifest.format().to_string(),
+            shard_size,
+            total_shards,
+            manifest,
+        }
+    }
+}
+
+#[pymethods]
+impl DatasetManifest {
+    /// Total number of samples across the files
+    #[getter]
+    fn total_samples(&self) -> u64 {
+        self.manifest.total_samples()
+    }
+
+    /// Files in sample order, as (path, first sample, sample count)
+    #[getter]
+    fn files(&self) -> Vec<(String, u64, u64)> {
+        self.manifest
+            .files()
+            .iter()
+            .map(|f| (f.path.clone(), f.start, f.samples))
+            .collect()
+    }
+
+    /// The file holding a sample, and the sample's index within it
+    ///
+    /// Args:
+    ///     index: Sample index in the dataset
+    ///
+    /// Returns:
+    ///     (path, index in file), or None past the last sample
+    fn locate(&self, index: u64) -> Option<(String, u64)> {
+        self.manifest
+            .locate(index)
+            .map(|(file, offset)| (file.path.clone(), offset))
+    }
+
+    /// The parts of files holding a range of samples, e.g. a shard
+    ///
+    /// Args:
+    ///     start_index: First sample (inclusive)
+    ///     end_index: Last sample (exclusive)
+    ///
+    /// Returns:
+    ///     List of (path, start in file, end in file)
+    fn files_in(&self, start_index: u64, end_index: u64) -> Vec<(String, u64, u64)> {
+        self.manifest
+            .files_in(start_index, end_index)
+            .into_iter()
+            .map(|(f, range)| (f.path.clone(), range.start, range.end))
+            .collect()
+    }
+
+    fn __len__(&self) -> usize {
+        self.manifest.files().len()
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "DatasetManifest(dataset_id='{}', files={}, total_samples={}, shard_size={}, total_shards={})",
+            self.dataset_id,
+            self.manifest.files().len(),
+            self.manifest.total_samples(),
+            self.shard_size,
+            self.total_shards
+        )
+    }
+}
+
+/// Count the samples in the dataset files at `paths`
+///
+/// Returns the manifest and the directory holding the files. `s3://` paths
+/// need the `s3` feature, `endpoint` and `region` apply to them only.
+pub(crate) async fn build_manifest(
+    paths: DatasetPaths,
+    endpoint: Option<String>,
+    region: Option<String>,
+) -> PyResult<(RustDatasetManifest, String)> {
+    let is_s3 = match &paths {
+        DatasetPaths::Pattern(pattern) => pattern.starts_with(S3_SCHEME),
+        DatasetPaths::Files(files) => files.iter().any(|f| f.starts_with(S3_SCHEME)),
+    };
+    let manifest = if is_s3 {
+        s3_manifest(paths, endpoint, region).await?
+    } else {
+        local_manifest(paths).await?
+    };
+    let directory = common_directory(manifest.files().iter().map(|f| f.path.as_str()));
+    Ok((manifest, directory))
+}
+
+const S3_SCHEME: &str = "s3://";
+
+async fn local_manifest(paths: DatasetPaths) -> PyResult<RustDatasetManifest> {
+    let cwd = std::env::current_dir()?;
+    let result = match paths {
+        DatasetPaths::Pattern(pattern) => {
+            let (base, rest) = split_pattern(&pattern);
+            let base = cwd.join(if base.is_empty() { "." } else { base });
+            let (root, rest) = match base.file_name() {
+                Some(name) if rest.is_empty() && base.is_file() => (
+                    base.parent().map_or_else(|| cwd.clone(), Path::to_path_buf),
+                    name.to_string_lossy().into_owned(),
+                ),
+                _ => (base, rest.to_string()),
+            };
+            RustDatasetManifest::build(&LocalStorage::new(root), &rest).await
+        }
+        // Absolute paths replace the storage root when joined, relative
+        // ones resolve against the working directory
+        DatasetPaths::Files(files) => {
+            RustDatasetManifest::from_files(&LocalStorage::new(cwd), files).await
+        }
+    };
+    result.map_err(manifest_error)
+}
+
+#[cfg(feature = "s3")]
+async fn s3_manifest(
+    paths: DatasetPaths,
+    endpoint: Option<String>,
+    region: Option<String>,
+) -> PyResult<RustDatasetManifest> {
+    let storage = |bucket: &str, prefix: Option<String>| {
+        let config = crate::checkpoint::s3_config(
+            bucket.to_string(),
+            prefix,
+            endpoint.clone(),
+            region.clone(),
+        );
+        storage::S3Storage::with_config(config)
+    };
+    let result = match paths {
+        DatasetPaths::Pattern(pattern) => {
+            let (bucket, key) = split_s3_url(&pattern)?;
+            let (prefix, rest) = split_pattern(key);
+            let prefix = (!prefix.is_empty()).then(|| prefix.to_string());
+            RustDatasetManifest::build(&storage(bucket, prefix).await, rest).await
+        }
+        DatasetPaths::Files(files) => {
+            let mut bucket = None;
+            let mut keys = Vec::with_capacity(files.len());
+            for file in &files {
+                let (file_bucket, key) = split_s3_url(file)?;
+                if bucket.is_some_and(|b| b != file_bucket) {
+                    return Err(PyValueError::new_err(
+                        "All files of a dataset must be in the same bucket",
+                    ));
+                }
+                bucket = Some(file_bucket);
+                keys.push(key.to_string());
+            }
+            let bucket = bucket.unwrap_or_default();
+            RustDatasetManifest::from_files(&storage(bucket, None).await, keys).await
+        }
+    };
+    result.map_err(manifest_error)
+}
+
+#[cfg(not(feature = "s3"))]
+async fn s3_manifest(
+    _paths: DatasetPaths,
+    _endpoint: Option<String>,
+    _region: Option<String>,
+) -> PyResult<RustDatasetManifest> {
+    Err(PyValueError::new_err(
+        "dtruntime was built without S3 support, rebuild with the s3 feature",
+    ))
+}
+
+/// Bucket and key of an `s3://bucket/key` URL
+#[cfg(feature = "s3")]
+fn split_s3_url(url: &str) -> PyResult<(&str, &str)> {
+    url.strip_prefix(S3_SCHEME)
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(bucket, _)| !bucket.is_empty())
+        .ok_or_else(|| PyValueError::new_err(format!("Expected s3://bucket/key, got '{}'", url)))
+}
+
+/// Deepest directory containing every path
+fn common_directory<'a>(mut paths: impl Iterator<Item = &'a str>) -> String {
+    let Some(first) = paths.next() else {
+        return String::new();
+    };
+    let mut common = first.rsplit_once('/').map_or("", |(dir, _)| dir);
+    for path in paths {
+        while !path.starts_with(common) || !path[common.len()..].starts_with('/') {
+            match common.rsplit_once('/') {
+                Some((dir, _)) => common = dir,
+                None => return String::new(),
+            }
+        }
+    }
+    match common {
+        "" if first.starts_with('/') => "/".to_string(),
+        _ => common.to_string(),
+    }
+}
+
+fn manifest_error(e: runtime_core::Error) -> PyErr {
+    match e {
+        runtime_core::Error::InvalidShardConfig { message } => PyValueError::new_err(message),
+        e => PyIOError::new_err(format!("Failed to build dataset manifest: {}", e)),
+    }
+}
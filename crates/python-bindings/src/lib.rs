@@ -6,6 +6,7 @@
 //! - `DatasetRegistry`: Register datasets and get shard assignments
 //! - `CheckpointManager`: Save and load training checkpoints
 //! - `TrainingOrchestrator`: High-level training coordination
+//! - `enable_logging`: Forward Rust log events to Python `logging`
 //!
 //! # Example
 //!
@@ -21,9 +22,27 @@
 
 use pyo3::prelude::*;
 
+// pyo3 0.22 converts every `PyResult` error with `Into::<PyErr>` in the glue
+// it generates beside `#[pymethods]` and `#[pyfunction]` items, where an
+// attribute on the item itself does not reach, so allow it per module
+#[allow(clippy::useless_conversion)]
 mod checkpoint;
+#[allow(clippy::useless_conversion)]
 mod dataset;
+#[allow(clippy::useless_conversion)]
+mod logging;
+#[allow(clippy::useless_conversion)]
 mod orchestrator;
+mod runtime;
+
+/// Parse a worker, dataset or checkpoint ID passed from Python
+pub(crate) fn parse_id<T>(id: &str) -> PyResult<T>
+where
+    T: std::str::FromStr<Err = runtime_core::Error>,
+{
+    id.parse()
+        .map_err(|e: runtime_core::Error| pyo3::exceptions::PyValueError::new_err(e.to_string()))
+}
 
 /// Python module for the distributed training runtime
 #[pymodule]
@@ -31,10 +50,36 @@ fn _core(m: &Bound<'_, PyModule>) -> PyResult<()> {
     // Register classes
     m.add_class::<dataset::DatasetRegistry>()?;
     m.add_class::<dataset::ShardInfo>()?;
+    m.add_class::<dataset::DatasetManifest>()?;
+    m.add_class::<dataset::ConsistentHash>()?;
+    m.add_class::<dataset::EpochCoordinator>()?;
     m.add_class::<checkpoint::CheckpointManager>()?;
     m.add_class::<checkpoint::CheckpointInfo>()?;
+    m.add_class::<checkpoint::CheckpointBuffer>()?;
+    m.add_class::<checkpoint::CheckpointWriter>()?;
+    m.add_class::<checkpoint::PendingCheckpoint>()?;
     m.add_class::<orchestrator::TrainingOrchestrator>()?;
     m.add_class::<orchestrator::WorkerConfig>()?;
+    m.add_class::<orchestrator::WorkerSnapshot>()?;
+    m.add_class::<orchestrator::MembershipEvent>()?;
+    m.add_class::<orchestrator::MetricsReporter>()?;
+    m.add_class::<orchestrator::ClusterMetrics>()?;
+    m.add_class::<orchestrator::CoordinatorCheckpointInfo>()?;
+    m.add(
+        "BarrierTimeout",
+        m.py().get_type_bound::<orchestrator::BarrierTimeout>(),
+    )?;
+
+    m.add_function(wrap_pyfunction!(logging::enable_logging, m)?)?;
+
+    // Rebuild runtimes in forked children such as DataLoader workers
+    runtime::register_fork_hook(m)?;
+
+    // Stop the shared runtime at exit, after the objects using it
+    runtime::register_exit_hook(m)?;
+
+    // Deregister workers that are still registered at exit
+    orchestrator::register_exit_hook(m)?;
 
     // Add version info
     m.add("__version__", env!("CARGO_PKG_VERSION"))?;
@@ -0,0 +1,176 @@
+//! Rust log events forwarded to Python `logging`
+//!
+//! `enable_logging()` installs a tracing subscriber that hands each event to
+//! the Python logger named after its target, e.g. `dtruntime.coordinator.client`
+//! for events from `coordinator::client`. Events are queued and logged from
+//! a dedicated thread, so the thread emitting one never waits for the GIL.
+
+use std::fmt::{self, Write as _};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::mpsc::{self, Sender};
+use std::sync::OnceLock;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Metadata, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::Layer;
+
+/// Python logging levels
+const PY_TRACE: u8 = 5;
+const PY_DEBUG: u8 = 10;
+const PY_INFO: u8 = 20;
+const PY_WARNING: u8 = 30;
+const PY_ERROR: u8 = 40;
+
+/// Least severe Python level forwarded
+static MIN_LEVEL: AtomicU8 = AtomicU8::new(PY_WARNING);
+
+/// Queue to the thread logging records, once installed
+static RECORDS: OnceLock<Sender<Record>> = OnceLock::new();
+
+/// An event on its way to Python
+struct Record {
+    logger: String,
+    level: u8,
+    message: String,
+}
+
+fn py_level(level: &Level) -> u8 {
+    match *level {
+        Level::TRACE => PY_TRACE,
+        Level::DEBUG => PY_DEBUG,
+        Level::INFO => PY_INFO,
+        Level::WARN => PY_WARNING,
+        Level::ERROR => PY_ERROR,
+    }
+}
+
+/// Python logger for a tracing target, e.g. `dtruntime.checkpoint.writer`
+fn logger_name(target: &str) -> String {
+    format!("dtruntime.{}", target.replace("::", "."))
+}
+
+/// Forward Rust log events to Python `logging`
+///
+/// Events from the coordinator client, checkpoint writer and the rest of
+/// the runtime go to loggers under `dtruntime`, named after the Rust
+/// module, with matching levels. Calling it again changes the level.
+///
+/// Args:
+///     level: Least severe level forwarded, as a `logging` level or its
+///         name (default: the effective level of the `dtruntime` logger)
+///
+/// Raises:
+///     RuntimeError: If another tracing subscriber is already installed
+///
+/// Example:
+///     logging.basicConfig(level=logging.INFO)
+///     dtruntime.enable_logging()
+#[pyfunction]
+#[pyo3(signature = (level=None))]
+pub fn enable_logging(py: Python<'_>, level: Option<&Bound<'_, PyAny>>) -> PyResult<()> {
+    let logging = py.import_bound("logging")?;
+    let level: i64 = match level {
+        None => logging
+            .call_method1("getLogger", ("dtruntime",))?
+            .call_method0("getEffectiveLevel")?
+            .extract()?,
+        Some(level) => match level.extract::<String>() {
+            Ok(name) => logging
+                .getattr(name.to_uppercase().as_str())
+                .and_then(|level| level.extract())
+                .map_err(|_| PyValueError::new_err(format!("Unknown logging level '{}'", name)))?,
+            Err(_) => level.extract()?,
+        },
+    };
+    MIN_LEVEL.store(level.clamp(0, u8::MAX as i64) as u8, Ordering::Relaxed);
+
+    if RECORDS.get().is_none() {
+        let (tx, rx) = mpsc::channel();
+        let subscriber = tracing_subscriber::registry().with(PythonLogLayer {
+            records: tx.clone(),
+        });
+        tracing::subscriber::set_global_default(subscriber).map_err(|_| {
+            PyRuntimeError::new_err("Another tracing subscriber is already installed")
+        })?;
+        let _ = RECORDS.set(tx);
+        std::thread::Builder::new()
+            .name("dtruntime-logging".to_string())
+            .spawn(move || forward(rx))?;
+    }
+    // Callsites cache whether they are enabled
+    tracing::callsite::rebuild_interest_cache();
+    Ok(())
+}
+
+/// Log queued records until the interpreter goes away
+fn forward(records: mpsc::Receiver<Record>) {
+    for record in records {
+        // SAFETY: only reads interpreter state
+        if unsafe { pyo3::ffi::Py_IsInitialized() } == 0 {
+            return;
+        }
+        Python::with_gil(|py| {
+            // A failing handler shouldn't stop later records
+            let _ = py
+                .import_bound("logging")
+                .and_then(|logging| logging.call_method1("getLogger", (record.logger,)))
+                .and_then(|logger| logger.call_method1("log", (record.level, record.message)));
+        });
+    }
+}
+
+/// Tracing layer queueing events for Python
+struct PythonLogLayer {
+    records: Sender<Record>,
+}
+
+impl<S: Subscriber> Layer<S> for PythonLogLayer {
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        py_level(metadata.level()) >= MIN_LEVEL.load(Ordering::Relaxed)
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let _ = self.records.send(Record {
+            logger: logger_name(metadata.target()),
+            level: py_level(metadata.level()),
+            message: visitor.finish(),
+        });
+    }
+}
+
+/// Formats an event as its message followed by `key=value` fields
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(self) -> String {
+        match (self.message.is_empty(), self.fields.is_empty()) {
+            (_, true) => self.message,
+            (true, false) => self.fields,
+            (false, false) => format!("{} {}", self.message, self.fields),
+        }
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+            return;
+        }
+        if !self.fields.is_empty() {
+            self.fields.push(' ');
+        }
+        let _ = write!(self.fields, "{}={:?}", field.name(), value);
+    }
+}
@@ -1,14 +1,77 @@
 //! Training orchestrator Python bindings
 //!
 //! High-level orchestration wrapper connecting to coordinator gRPC server.
+//!
+//! Every coordinator call has a blocking form and an `*_async` form returning
+//! an awaitable, for asyncio training loops and Ray actors. Both run the same
+//! request in [`Inner`].
 
+use coordinator::client::{is_retryable, retry_hint};
 use coordinator::proto::coordinator_client::CoordinatorClient;
+use coordinator::server::ServerConfig;
+use coordinator::{CoordinatorServer, CoordinatorService};
+use pyo3::exceptions::{
+    PyConnectionError, PyRuntimeError, PyTimeoutError, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
-use std::collections::HashMap;
+use pyo3::sync::GILOnceCell;
+use runtime_core::config::{RetryConfig, RuntimeConfig};
+use runtime_core::retry::Backoff;
+use runtime_core::ResourceCollector;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::runtime::Runtime;
-use tokio::sync::Mutex;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tonic::codec::CompressionEncoding;
 use tonic::transport::Channel;
+use tonic::{Code, Status};
+
+use crate::dataset::{self, DatasetManifest, DatasetPaths};
+use crate::runtime::{self, ProcessLocal, SharedRuntime};
+
+/// How long closing at interpreter exit or on garbage collection waits for
+/// the worker to deregister
+const IMPLICIT_CLOSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `weakref.WeakSet` of orchestrators that registered a worker, closed at
+/// interpreter exit
+static REGISTERED: GILOnceCell<PyObject> = GILOnceCell::new();
+
+/// Close orchestrators still registered when the interpreter exits, so
+/// e.g. a killed notebook kernel doesn't leave its worker counted in the
+/// world size until heartbeats time out
+pub(crate) fn register_exit_hook(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let hook = wrap_pyfunction!(close_at_exit, m)?;
+    m.py()
+        .import_bound("atexit")?
+        .call_method1("register", (hook,))?;
+    Ok(())
+}
+
+#[pyfunction]
+fn close_at_exit(py: Python<'_>) -> PyResult<()> {
+    let Some(registered) = REGISTERED.get(py) else {
+        return Ok(());
+    };
+    // Collected first, as closing changes the set
+    let orchestrators = registered.bind(py).iter()?.collect::<PyResult<Vec<_>>>()?;
+    for orchestrator in orchestrators {
+        let orchestrator = orchestrator.downcast::<TrainingOrchestrator>()?;
+        if let Err(e) = orchestrator
+            .borrow()
+            .close_within(py, Some(IMPLICIT_CLOSE_TIMEOUT))
+        {
+            tracing::warn!(error = %e, "Failed to close orchestrator at exit");
+        }
+    }
+    Ok(())
+}
 
 /// Worker configuration returned after registration
 #[pyclass]
@@ -29,6 +92,18 @@ pub struct WorkerConfig {
     /// Recommended heartbeat interval in milliseconds
     #[pyo3(get)]
     pub heartbeat_interval_ms: i64,
+
+    /// Cluster membership generation this configuration was issued for
+    #[pyo3(get)]
+    pub membership_generation: i64,
+
+    /// Protocol version negotiated with the coordinator
+    #[pyo3(get)]
+    pub protocol_version: u32,
+
+    /// Token for this registration, sent with every heartbeat
+    #[pyo3(get)]
+    pub session: i64,
 }
 
 #[pymethods]
@@ -72,6 +147,14 @@ pub struct CoordinatorShardInfo {
     /// Training epoch
     #[pyo3(get)]
     pub epoch: i64,
+
+    /// All shards handed out, as (shard_id, start_index, end_index)
+    #[pyo3(get)]
+    pub shards: Vec<(i64, i64, i64)>,
+
+    /// Suggested number of shards to prefetch
+    #[pyo3(get)]
+    pub prefetch_depth: i32,
 }
 
 #[pymethods]
@@ -84,6 +167,26 @@ impl CoordinatorShardInfo {
     }
 }
 
+impl From<coordinator::proto::ShardAssignment> for CoordinatorShardInfo {
+    fn from(shard: coordinator::proto::ShardAssignment) -> Self {
+        Self {
+            dataset_id: shard.dataset_id,
+            shard_id: shard.shard_id,
+            total_shards: shard.total_shards,
+            start_index: shard.start_index,
+            end_index: shard.end_index,
+            file_paths: shard.file_paths,
+            epoch: shard.epoch,
+            shards: shard
+                .shards
+                .into_iter()
+                .map(|s| (s.shard_id, s.start_index, s.end_index))
+                .collect(),
+            prefetch_depth: shard.prefetch_depth,
+        }
+    }
+}
+
 /// Barrier synchronization result
 #[pyclass]
 #[derive(Clone)]
@@ -99,435 +202,2530 @@ pub struct BarrierResult {
     /// This worker's arrival order
     #[pyo3(get)]
     pub arrival_order: i64,
+
+    /// Whether the barrier had already been released before this worker arrived
+    #[pyo3(get)]
+    pub already_released: bool,
+
+    /// Workers needed for release
+    #[pyo3(get)]
+    pub expected: i64,
 }
 
 #[pymethods]
 impl BarrierResult {
     fn __repr__(&self) -> String {
         format!(
-            "BarrierResult(released={}, participants={}, arrival_order={})",
-            self.released, self.participants, self.arrival_order
+            "BarrierResult(released={}, participants={}, expected={}, arrival_order={}, already_released={})",
+            self.released, self.participants, self.expected, self.arrival_order, self.already_released
         )
     }
 }
 
-// Type alias for the gRPC client
-type Client = CoordinatorClient<Channel>;
-
-/// High-level training orchestrator for distributed training coordination
-///
-/// Connects to a coordinator gRPC server to manage worker registration,
-/// heartbeats, data sharding, and synchronization barriers.
-///
-/// Example:
-///     orch = TrainingOrchestrator("http://localhost:50051")
-///     config = orch.register_worker("worker-0", "localhost", 50052, gpu_count=8)
-///     print(f"Registered as rank {config.rank} of {config.world_size}")
-///     
-///     # Get data shard for this worker
-///     shard = orch.get_shard("imagenet", epoch=0)
-///     
-///     # Synchronize with other workers
-///     orch.barrier("epoch-0", step=100)
+/// Result of reporting an epoch complete
 #[pyclass]
-pub struct TrainingOrchestrator {
-    client: Arc<Mutex<Option<Client>>>,
-    coordinator_url: String,
-    runtime: Arc<Runtime>,
-    worker_id: Arc<Mutex<Option<String>>>,
+#[derive(Clone)]
+pub struct EpochStatus {
+    /// Whether this report completed the quorum and advanced the epoch
+    #[pyo3(get)]
+    pub advanced: bool,
+
+    /// Dataset epoch after the report
+    #[pyo3(get)]
+    pub current_epoch: i64,
+
+    /// Workers that reported the epoch complete so far
+    #[pyo3(get)]
+    pub reported: i32,
+
+    /// Reports needed to advance
+    #[pyo3(get)]
+    pub required: i32,
 }
 
 #[pymethods]
-impl TrainingOrchestrator {
-    /// Create a new training orchestrator
-    ///
-    /// Args:
-    ///     coordinator_url: URL of the coordinator gRPC server (e.g., "http://localhost:50051")
-    #[new]
-    fn new(coordinator_url: &str) -> PyResult<Self> {
-        let runtime = Runtime::new().map_err(|e| {
-            pyo3::exceptions::PyRuntimeError::new_err(format!(
-                "Failed to create async runtime: {}",
-                e
-            ))
-        })?;
-
-        Ok(Self {
-            client: Arc::new(Mutex::new(None)),
-            coordinator_url: coordinator_url.to_string(),
-            runtime: Arc::new(runtime),
-            worker_id: Arc::new(Mutex::new(None)),
-        })
+impl EpochStatus {
+    fn __repr__(&self) -> String {
+        format!(
+            "EpochStatus(advanced={}, current_epoch={}, reported={}, required={})",
+            self.advanced, self.current_epoch, self.reported, self.required
+        )
     }
+}
 
-    /// Connect to the coordinator server
-    ///
-    /// This is called automatically by other methods if not already connected.
-    fn connect(&self, py: Python<'_>) -> PyResult<()> {
-        let url = self.coordinator_url.clone();
-        let client_lock = self.client.clone();
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let channel = Channel::from_shared(url)
-                    .map_err(|e| {
-                        pyo3::exceptions::PyValueError::new_err(format!(
-                            "Invalid coordinator URL: {}",
-                            e
-                        ))
-                    })?
-                    .connect()
-                    .await
-                    .map_err(|e| {
-                        pyo3::exceptions::PyConnectionError::new_err(format!(
-                            "Failed to connect to coordinator: {}",
-                            e
-                        ))
-                    })?;
-
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                *guard = Some(CoordinatorClient::new(channel));
-                Ok(())
-            })
-        })
-    }
+/// Where to resume training after a restart
+#[pyclass]
+#[derive(Clone)]
+pub struct RecoveryInfo {
+    /// Checkpoint to restore
+    #[pyo3(get)]
+    pub checkpoint_id: String,
 
-    /// Register this worker with the coordinator
-    ///
-    /// Args:
-    ///     worker_id: Unique identifier for this worker
-    ///     hostname: Hostname or IP address
-    ///     port: Port number for worker-to-worker communication
-    ///     gpu_count: Number of GPUs available (default: 0)
-    ///     memory_bytes: Available memory in bytes (default: 0)
-    ///     metadata: Optional metadata dictionary
-    ///
-    /// Returns:
-    ///     WorkerConfig with assigned rank and world size
-    #[pyo3(signature = (worker_id, hostname, port, gpu_count=0, memory_bytes=0, metadata=None))]
-    fn register_worker(
-        &self,
-        py: Python<'_>,
-        worker_id: &str,
-        hostname: &str,
-        port: i32,
-        gpu_count: i32,
-        memory_bytes: i64,
-        metadata: Option<HashMap<String, String>>,
-    ) -> PyResult<WorkerConfig> {
-        // Ensure connected
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let worker_id_store = self.worker_id.clone();
-        let wid = worker_id.to_string();
-        let host = hostname.to_string();
-        let meta = metadata.unwrap_or_default();
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let request = coordinator::proto::WorkerInfo {
-                    worker_id: wid.clone(),
-                    hostname: host,
-                    port,
-                    gpu_count,
-                    memory_bytes,
-                    metadata: meta,
-                };
-
-                let response = grpc_client.register_worker(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to register worker: {}",
-                        e
-                    ))
-                })?;
-
-                let config = response.into_inner();
-
-                // Store worker ID for future calls
-                *worker_id_store.lock().await = Some(wid);
-
-                Ok(WorkerConfig {
-                    worker_id: config.assigned_id,
-                    rank: config.rank,
-                    world_size: config.world_size,
-                    heartbeat_interval_ms: config.heartbeat_interval_ms,
-                })
-            })
-        })
-    }
+    /// Worker that wrote the checkpoint
+    #[pyo3(get)]
+    pub worker_id: String,
 
-    /// Send a heartbeat to the coordinator
-    ///
-    /// Args:
-    ///     current_step: Current training step (default: 0)
-    ///     current_epoch: Current training epoch (default: 0)
-    ///
-    /// Returns:
-    ///     True if acknowledged
-    #[pyo3(signature = (current_step=0, current_epoch=0))]
-    fn heartbeat(&self, py: Python<'_>, current_step: i64, current_epoch: i64) -> PyResult<bool> {
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let worker_id = self.get_worker_id(py)?;
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let status = coordinator::proto::WorkerStatus {
-                    state: coordinator::proto::worker_status::State::Training as i32,
-                    current_step,
-                    current_epoch,
-                    current_task: String::new(),
-                };
-
-                let request = coordinator::proto::HeartbeatRequest {
-                    worker_id,
-                    timestamp_ms: chrono::Utc::now().timestamp_millis(),
-                    status: Some(status),
-                    resources: None,
-                };
-
-                let response = grpc_client.heartbeat(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("Heartbeat failed: {}", e))
-                })?;
-
-                Ok(response.into_inner().acknowledged)
-            })
-        })
-    }
+    /// Where the checkpoint data is stored
+    #[pyo3(get)]
+    pub storage_path: String,
 
-    /// Register a dataset with the coordinator
-    ///
-    /// Args:
-    ///     dataset_id: Unique dataset identifier
-    ///     path: Path to the dataset
-    ///     total_samples: Total number of samples
-    ///     shard_size: Samples per shard
-    ///     shuffle: Whether to shuffle (default: True)
-    ///     seed: Random seed (default: 42)
-    #[pyo3(signature = (dataset_id, path, total_samples, shard_size, shuffle=true, seed=42))]
-    fn register_dataset(
-        &self,
-        py: Python<'_>,
-        dataset_id: &str,
-        path: &str,
-        total_samples: i64,
-        shard_size: i64,
-        shuffle: bool,
-        seed: i64,
-    ) -> PyResult<i64> {
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let did = dataset_id.to_string();
-        let p = path.to_string();
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let request = coordinator::proto::DatasetInfo {
-                    dataset_id: did,
-                    path: p,
-                    format: "auto".to_string(),
-                    total_samples,
-                    shard_size,
-                    shuffle,
-                    seed,
-                    metadata: HashMap::new(),
-                };
-
-                let response = grpc_client.register_dataset(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to register dataset: {}",
-                        e
-                    ))
-                })?;
-
-                Ok(response.into_inner().total_shards)
-            })
-        })
+    /// Checkpoint size in bytes
+    #[pyo3(get)]
+    pub size_bytes: i64,
+
+    /// Step to resume training from
+    #[pyo3(get)]
+    pub resume_step: i64,
+
+    /// Epoch to resume training from
+    #[pyo3(get)]
+    pub resume_epoch: i64,
+
+    /// Checkpoint metadata
+    #[pyo3(get)]
+    pub metadata: HashMap<String, String>,
+
+    /// This worker's shards in the resume epoch, across all datasets
+    #[pyo3(get)]
+    pub shard_assignments: Vec<CoordinatorShardInfo>,
+}
+
+#[pymethods]
+impl RecoveryInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "RecoveryInfo(checkpoint_id='{}', resume_step={}, resume_epoch={}, shards={})",
+            self.checkpoint_id,
+            self.resume_step,
+            self.resume_epoch,
+            self.shard_assignments.len()
+        )
     }
+}
 
-    /// Get shard assignment for this worker
-    ///
-    /// Args:
-    ///     dataset_id: Dataset identifier
-    ///     epoch: Training epoch
-    ///
-    /// Returns:
-    ///     CoordinatorShardInfo with shard assignment details
-    fn get_shard(
-        &self,
-        py: Python<'_>,
-        dataset_id: &str,
-        epoch: i64,
-    ) -> PyResult<CoordinatorShardInfo> {
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let worker_id = self.get_worker_id(py)?;
-        let did = dataset_id.to_string();
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let request = coordinator::proto::ShardRequest {
-                    worker_id,
-                    dataset_id: did,
-                    epoch,
-                };
-
-                let response = grpc_client.get_data_shard(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("Failed to get shard: {}", e))
-                })?;
-
-                let shard = response.into_inner();
-                Ok(CoordinatorShardInfo {
-                    dataset_id: shard.dataset_id,
-                    shard_id: shard.shard_id,
-                    total_shards: shard.total_shards,
-                    start_index: shard.start_index,
-                    end_index: shard.end_index,
-                    file_paths: shard.file_paths,
-                    epoch: shard.epoch,
-                })
-            })
-        })
+/// A change in cluster membership, passed to `watch_membership` callbacks
+#[pyclass]
+#[derive(Clone)]
+pub struct MembershipEvent {
+    /// "joined", "left", "dead", "state_changed" or "rank_changed"
+    #[pyo3(get)]
+    pub kind: String,
+
+    #[pyo3(get)]
+    pub worker_id: String,
+
+    /// Rank of the worker, -1 once it left or died
+    #[pyo3(get)]
+    pub rank: i32,
+
+    /// Lifecycle state of the worker, e.g. "idle" or "dead"
+    #[pyo3(get)]
+    pub state: String,
+
+    /// Membership generation after the change
+    #[pyo3(get)]
+    pub membership_generation: i64,
+
+    /// Live workers after the change, as seen by this watch
+    #[pyo3(get)]
+    pub world_size: usize,
+
+    /// When the change happened, in ms since the Unix epoch
+    #[pyo3(get)]
+    pub timestamp_ms: i64,
+}
+
+#[pymethods]
+impl MembershipEvent {
+    fn __repr__(&self) -> String {
+        format!(
+            "MembershipEvent(kind='{}', worker_id='{}', rank={}, world_size={})",
+            self.kind, self.worker_id, self.rank, self.world_size
+        )
     }
+}
 
-    /// Wait at a synchronization barrier
-    ///
-    /// Args:
-    ///     barrier_id: Unique barrier identifier
-    ///     step: Training step for this barrier
-    ///
-    /// Returns:
-    ///     BarrierResult with synchronization details
-    fn barrier(&self, py: Python<'_>, barrier_id: &str, step: i64) -> PyResult<BarrierResult> {
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let worker_id = self.get_worker_id(py)?;
-        let bid = barrier_id.to_string();
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let request = coordinator::proto::BarrierRequest {
-                    worker_id,
-                    barrier_id: bid,
-                    step,
-                };
-
-                let response = grpc_client.wait_barrier(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!("Barrier failed: {}", e))
-                })?;
-
-                let result = response.into_inner();
-                Ok(BarrierResult {
-                    released: result.released,
-                    participants: result.participants,
-                    arrival_order: result.arrival_order,
-                })
-            })
-        })
+/// Live workers seen by a membership watch
+#[derive(Default)]
+struct Members(HashSet<String>);
+
+impl Members {
+    /// Apply `event`, returning what the callback receives
+    fn apply(&mut self, event: coordinator::proto::WorkerEvent) -> MembershipEvent {
+        use coordinator::proto::worker_event::Kind;
+        use coordinator::proto::worker_status::State;
+
+        let kind = Kind::try_from(event.kind).unwrap_or(Kind::Unknown);
+        let state = State::try_from(event.state).unwrap_or(State::Unknown);
+        if matches!(kind, Kind::Left | Kind::Dead) || state == State::Dead {
+            self.0.remove(&event.worker_id);
+        } else {
+            self.0.insert(event.worker_id.clone());
+        }
+        MembershipEvent {
+            kind: kind.as_str_name().to_lowercase(),
+            worker_id: event.worker_id,
+            rank: event.rank,
+            state: state.as_str_name().to_lowercase(),
+            membership_generation: event.membership_generation,
+            world_size: self.0.len(),
+            timestamp_ms: event.timestamp_ms,
+        }
     }
+}
 
-    /// Deregister this worker from the coordinator
-    fn deregister(&self, py: Python<'_>) -> PyResult<()> {
-        self.ensure_connected(py)?;
-
-        let client_lock = self.client.clone();
-        let worker_id = self.get_worker_id(py)?;
-
-        py.allow_threads(|| {
-            self.runtime.block_on(async move {
-                let mut guard: tokio::sync::MutexGuard<'_, Option<Client>> =
-                    client_lock.lock().await;
-                let grpc_client = guard.as_mut().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err("Not connected to coordinator")
-                })?;
-
-                let request = coordinator::proto::WorkerInfo {
-                    worker_id,
-                    hostname: String::new(),
-                    port: 0,
-                    gpu_count: 0,
-                    memory_bytes: 0,
-                    metadata: HashMap::new(),
-                };
-
-                grpc_client.deregister_worker(request).await.map_err(|e| {
-                    pyo3::exceptions::PyRuntimeError::new_err(format!(
-                        "Failed to deregister: {}",
-                        e
-                    ))
-                })?;
+/// A worker as seen by the coordinator, returned by `list_workers`
+#[pyclass]
+#[derive(Clone)]
+pub struct WorkerSnapshot {
+    #[pyo3(get)]
+    pub worker_id: String,
 
-                Ok(())
-            })
-        })
-    }
+    #[pyo3(get)]
+    pub hostname: String,
+
+    #[pyo3(get)]
+    pub port: i32,
+
+    #[pyo3(get)]
+    pub rank: i32,
+
+    /// Lifecycle state, e.g. "training" or "dead"
+    #[pyo3(get)]
+    pub state: String,
+
+    #[pyo3(get)]
+    pub current_step: i64,
+
+    #[pyo3(get)]
+    pub current_epoch: i64,
+
+    /// Last heartbeat, in ms since the Unix epoch
+    #[pyo3(get)]
+    pub last_heartbeat_ms: i64,
+
+    /// Registration time, in ms since the Unix epoch
+    #[pyo3(get)]
+    pub registered_at_ms: i64,
+
+    /// Out of rotation until it deregisters
+    #[pyo3(get)]
+    pub draining: bool,
+}
 
+#[pymethods]
+impl WorkerSnapshot {
     fn __repr__(&self) -> String {
-        format!("TrainingOrchestrator(url='{}')", self.coordinator_url)
+        format!(
+            "WorkerSnapshot(worker_id='{}', rank={}, state='{}', step={})",
+            self.worker_id, self.rank, self.state, self.current_step
+        )
     }
 }
 
-impl TrainingOrchestrator {
-    fn ensure_connected(&self, py: Python<'_>) -> PyResult<()> {
-        let client_lock = self.client.clone();
-        let is_connected = self.runtime.block_on(async {
-            let guard: tokio::sync::MutexGuard<'_, Option<Client>> = client_lock.lock().await;
-            guard.is_some()
-        });
-
-        if !is_connected {
-            self.connect(py)?;
+impl From<coordinator::proto::WorkerSnapshot> for WorkerSnapshot {
+    fn from(worker: coordinator::proto::WorkerSnapshot) -> Self {
+        let state = coordinator::proto::worker_status::State::try_from(worker.state)
+            .unwrap_or(coordinator::proto::worker_status::State::Unknown);
+        Self {
+            worker_id: worker.worker_id,
+            hostname: worker.hostname,
+            port: worker.port,
+            rank: worker.rank,
+            state: state.as_str_name().to_lowercase(),
+            current_step: worker.current_step,
+            current_epoch: worker.current_epoch,
+            last_heartbeat_ms: worker.last_heartbeat_ms,
+            registered_at_ms: worker.registered_at_ms,
+            draining: worker.draining,
         }
-        Ok(())
     }
+}
 
-    fn get_worker_id(&self, py: Python<'_>) -> PyResult<String> {
-        let worker_id = self.worker_id.clone();
-        py.allow_threads(|| {
-            self.runtime.block_on(async {
-                let guard = worker_id.lock().await;
-                guard.clone().ok_or_else(|| {
-                    pyo3::exceptions::PyRuntimeError::new_err(
-                        "Worker not registered. Call register_worker() first.",
-                    )
-                })
-            })
-        })
+/// A checkpoint recorded by the coordinator, returned by `list_checkpoints`
+#[pyclass]
+#[derive(Clone)]
+pub struct CoordinatorCheckpointInfo {
+    #[pyo3(get)]
+    pub checkpoint_id: String,
+
+    /// Worker that wrote the checkpoint
+    #[pyo3(get)]
+    pub worker_id: String,
+
+    #[pyo3(get)]
+    pub step: i64,
+
+    #[pyo3(get)]
+    pub epoch: i64,
+
+    /// Where the checkpoint data is stored
+    #[pyo3(get)]
+    pub storage_path: String,
+
+    #[pyo3(get)]
+    pub size_bytes: i64,
+
+    /// When the checkpoint was written, in ms since the Unix epoch
+    #[pyo3(get)]
+    pub timestamp_ms: i64,
+
+    /// "full", "incremental", "optimizer_only" or "model_only"
+    #[pyo3(get)]
+    pub checkpoint_type: String,
+
+    #[pyo3(get)]
+    pub metadata: HashMap<String, String>,
+}
+
+#[pymethods]
+impl CoordinatorCheckpointInfo {
+    fn __repr__(&self) -> String {
+        format!(
+            "CoordinatorCheckpointInfo(id='{}', step={}, epoch={}, size={})",
+            self.checkpoint_id, self.step, self.epoch, self.size_bytes
+        )
+    }
+}
+
+impl From<coordinator::proto::CheckpointInfo> for CoordinatorCheckpointInfo {
+    fn from(checkpoint: coordinator::proto::CheckpointInfo) -> Self {
+        let checkpoint_type = coordinator::proto::CheckpointType::try_from(checkpoint.r#type)
+            .unwrap_or(coordinator::proto::CheckpointType::Full);
+        Self {
+            checkpoint_id: checkpoint.checkpoint_id,
+            worker_id: checkpoint.worker_id,
+            step: checkpoint.step,
+            epoch: checkpoint.epoch,
+            storage_path: checkpoint.storage_path,
+            size_bytes: checkpoint.size_bytes,
+            timestamp_ms: checkpoint.timestamp_ms,
+            checkpoint_type: checkpoint_type.as_str_name().to_lowercase(),
+            metadata: checkpoint.metadata,
+        }
+    }
+}
+
+/// Aggregate cluster metrics, returned by `cluster_metrics`
+///
+/// The same numbers as the coordinator's `/api/metrics` endpoint.
+#[pyclass]
+#[derive(Clone)]
+pub struct ClusterMetrics {
+    /// Checkpoints committed per minute since the coordinator started
+    #[pyo3(get)]
+    pub checkpoint_throughput: u64,
+
+    /// Coordinator requests per second since it started
+    #[pyo3(get)]
+    pub coordinator_rps: u64,
+
+    #[pyo3(get)]
+    pub active_workers: u32,
+
+    #[pyo3(get)]
+    pub total_workers: u32,
+
+    /// P99 barrier latency in ms, including time spent waiting for peers
+    #[pyo3(get)]
+    pub barrier_latency_p99_ms: u64,
+
+    /// Median shard assignment latency in ms
+    #[pyo3(get)]
+    pub shard_assignment_time_ms: u64,
+
+    #[pyo3(get)]
+    pub duplicate_checkpoint_notifications: u64,
+
+    /// Disk and network bytes per second of all active workers
+    #[pyo3(get)]
+    pub disk_read_bytes_per_sec: f64,
+
+    #[pyo3(get)]
+    pub disk_write_bytes_per_sec: f64,
+
+    #[pyo3(get)]
+    pub network_rx_bytes_per_sec: f64,
+
+    #[pyo3(get)]
+    pub network_tx_bytes_per_sec: f64,
+
+    /// GPUs of all active workers
+    #[pyo3(get)]
+    pub gpu_count: u32,
+
+    #[pyo3(get)]
+    pub avg_gpu_utilization_percent: f64,
+
+    #[pyo3(get)]
+    pub gpu_memory_used_bytes: u64,
+
+    #[pyo3(get)]
+    pub gpu_memory_total_bytes: u64,
+
+    #[pyo3(get)]
+    pub gpu_power_draw_watts: f64,
+
+    /// Hottest GPU in Celsius
+    #[pyo3(get)]
+    pub max_gpu_temperature_celsius: f64,
+}
+
+#[pymethods]
+impl ClusterMetrics {
+    fn __repr__(&self) -> String {
+        format!(
+            "ClusterMetrics(active_workers={}, total_workers={}, gpus={}, rps={})",
+            self.active_workers, self.total_workers, self.gpu_count, self.coordinator_rps
+        )
+    }
+}
+
+impl From<coordinator::proto::ClusterMetrics> for ClusterMetrics {
+    fn from(m: coordinator::proto::ClusterMetrics) -> Self {
+        Self {
+            checkpoint_throughput: m.checkpoint_throughput,
+            coordinator_rps: m.coordinator_rps,
+            active_workers: m.active_workers,
+            total_workers: m.total_workers,
+            barrier_latency_p99_ms: m.barrier_latency_p99_ms,
+            shard_assignment_time_ms: m.shard_assignment_time_ms,
+            duplicate_checkpoint_notifications: m.duplicate_checkpoint_notifications,
+            disk_read_bytes_per_sec: m.disk_read_bytes_per_sec,
+            disk_write_bytes_per_sec: m.disk_write_bytes_per_sec,
+            network_rx_bytes_per_sec: m.network_rx_bytes_per_sec,
+            network_tx_bytes_per_sec: m.network_tx_bytes_per_sec,
+            gpu_count: m.gpu_count,
+            avg_gpu_utilization_percent: m.avg_gpu_utilization_percent,
+            gpu_memory_used_bytes: m.gpu_memory_used_bytes,
+            gpu_memory_total_bytes: m.gpu_memory_total_bytes,
+            gpu_power_draw_watts: m.gpu_power_draw_watts,
+            max_gpu_temperature_celsius: m.max_gpu_temperature_celsius,
+        }
+    }
+}
+
+pyo3::create_exception!(
+    dtruntime,
+    BarrierTimeout,
+    PyTimeoutError,
+    "A barrier wasn't released within the timeout given to `barrier`"
+);
+
+// Type alias for the gRPC client
+type Client = CoordinatorClient<Channel>;
+
+/// Training progress reported by background heartbeats
+#[derive(Debug, Clone, Default)]
+struct Progress {
+    step: i64,
+    epoch: i64,
+    loss: Option<f64>,
+    shard_progress: Vec<(String, i64, i64)>,
+}
+
+/// Coordinator served in the orchestrator's process, see `local`
+struct LocalCoordinator {
+    url: String,
+    state_dir: PathBuf,
+    /// Whether `state_dir` was made for this coordinator and is removed with it
+    temporary: bool,
+    stop: oneshot::Sender<()>,
+    task: JoinHandle<Result<(), Box<dyn std::error::Error + Send + Sync>>>,
+}
+
+impl LocalCoordinator {
+    /// Serve a coordinator keeping its state in `state_dir` on a free
+    /// loopback port
+    async fn start(state_dir: Option<PathBuf>) -> PyResult<Self> {
+        let temporary = state_dir.is_none();
+        let state_dir = state_dir
+            .unwrap_or_else(|| std::env::temp_dir().join(runtime_core::id::next_id("strata")));
+
+        let mut config = RuntimeConfig::default();
+        config.storage.base_path = state_dir.to_string_lossy().into_owned();
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .map_err(|e| PyRuntimeError::new_err(format!("Failed to start coordinator: {}", e)))?;
+
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).await?;
+        let url = format!("http://{}", listener.local_addr()?);
+        let server = CoordinatorServer::with_config(
+            service,
+            ServerConfig {
+                enable_reflection: false,
+                ..Default::default()
+            },
+        );
+        let (stop, stopped) = oneshot::channel();
+        let task = tokio::spawn(server.run_on_listener(listener, async {
+            let _ = stopped.await;
+        }));
+
+        Ok(Self {
+            url,
+            state_dir,
+            temporary,
+            stop,
+            task,
+        })
+    }
+
+    /// Shut the coordinator down, saving its state
+    async fn stop(self) -> PyResult<()> {
+        let _ = self.stop.send(());
+        let result = match self.task.await {
+            Ok(result) => result.map_err(|e| e.to_string()),
+            Err(e) => Err(e.to_string()),
+        }
+        .map_err(|e| PyRuntimeError::new_err(format!("Local coordinator failed: {}", e)));
+        if self.temporary {
+            let _ = std::fs::remove_dir_all(&self.state_dir);
+        }
+        result
+    }
+}
+
+/// Connection and registration state, shared with calls in flight
+struct Inner {
+    coordinator_url: String,
+    /// Channel opened by this process; a forked child opens its own
+    client: Mutex<Option<ProcessLocal<Client>>>,
+    /// Set by `close`, after which no new connection is made
+    closed: AtomicBool,
+    /// Backoff for retrying idempotent calls
+    retry: RetryConfig,
+    worker_id: Mutex<Option<String>>,
+    /// Details the worker registered with, to register again if the
+    /// coordinator forgets it
+    registration: Mutex<Option<coordinator::proto::WorkerInfo>>,
+    /// `runtime::forks()` when the worker registered, so forked children
+    /// such as DataLoader workers don't deregister it on close
+    registered_in: AtomicU64,
+    /// Session from the last registration, 0 before registering
+    session: AtomicI64,
+    /// Heartbeat interval recommended at registration, 0 before registering
+    heartbeat_interval_ms: AtomicI64,
+    /// Latest progress from `update_progress`
+    progress: std::sync::Mutex<Progress>,
+    /// Samples the resources attached to every heartbeat
+    resources: std::sync::Mutex<ResourceCollector>,
+    /// Callbacks from `on_command`, by command name
+    command_handlers: std::sync::Mutex<HashMap<String, Vec<PyObject>>>,
+}
+
+impl Inner {
+    /// Open a new connection to the coordinator
+    async fn connect(&self) -> Result<Client, Status> {
+        let channel = Channel::from_shared(self.coordinator_url.clone())
+            .map_err(|e| Status::invalid_argument(format!("Invalid coordinator URL: {}", e)))?
+            .connect()
+            .await
+            .map_err(|e| Status::unavailable(format!("Failed to connect to coordinator: {}", e)))?;
+
+        // Recovery responses can carry thousands of shard assignments
+        let client = CoordinatorClient::new(channel)
+            .accept_compressed(CompressionEncoding::Zstd)
+            .accept_compressed(CompressionEncoding::Gzip);
+        *self.client.lock().await = Some(ProcessLocal::new(client.clone()));
+        Ok(client)
+    }
+
+    /// The coordinator client, connecting first if needed
+    ///
+    /// Clients share one channel, so calls don't wait for each other.
+    /// After a fork the child connects again, as the channel's connection
+    /// lived on the parent's runtime.
+    async fn client(&self) -> Result<Client, Status> {
+        let existing = self
+            .client
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|client| client.get().cloned());
+        match existing {
+            Some(client) => Ok(client),
+            None => self.connect().await,
+        }
+    }
+
+    /// Run an RPC, reconnecting after the coordinator becomes unavailable
+    ///
+    /// A connection that fails is dropped so the next call reconnects.
+    /// `idempotent` calls are also retried with backoff on transient
+    /// errors, including failing to reconnect while the coordinator
+    /// restarts. `context` prefixes the error message.
+    async fn call<T, F, Fut>(&self, context: &str, idempotent: bool, rpc: F) -> PyResult<T>
+    where
+        F: FnMut(Client) -> Fut,
+        Fut: Future<Output = Result<tonic::Response<T>, Status>>,
+    {
+        self.try_call(context, idempotent, rpc)
+            .await
+            .map_err(|e| e.into_py(context))
+    }
+
+    /// Like [`call`](Self::call), keeping the coordinator's status for
+    /// callers that handle some errors themselves
+    async fn try_call<T, F, Fut>(
+        &self,
+        context: &str,
+        idempotent: bool,
+        mut rpc: F,
+    ) -> Result<T, CallError>
+    where
+        F: FnMut(Client) -> Fut,
+        Fut: Future<Output = Result<tonic::Response<T>, Status>>,
+    {
+        let mut backoff = Backoff::new(self.retry.clone());
+        loop {
+            if self.closed.load(Ordering::Relaxed) {
+                return Err(CallError::Closed);
+            }
+            let (status, connected) = match self.client().await {
+                Ok(client) => match rpc(client).await {
+                    Ok(response) => return Ok(response.into_inner()),
+                    Err(status) => (status, true),
+                },
+                Err(status) => (status, false),
+            };
+            if connected && status.code() == Code::Unavailable {
+                *self.client.lock().await = None;
+            }
+
+            let delay = match backoff.next_delay() {
+                Some(delay) if idempotent && is_retryable(&status) => delay,
+                _ if connected => return Err(CallError::Rpc(status)),
+                _ => return Err(CallError::Connect(status)),
+            };
+            let delay = retry_hint(&status).map_or(delay, |hint| delay.max(hint));
+            tracing::warn!(
+                call = context,
+                attempt = backoff.retries(),
+                delay_ms = delay.as_millis() as u64,
+                error = %status,
+                "Retrying coordinator call"
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    async fn worker_id(&self) -> PyResult<String> {
+        self.worker_id.lock().await.clone().ok_or_else(|| {
+            PyRuntimeError::new_err("Worker not registered. Call register_worker() first.")
+        })
+    }
+
+    async fn register_worker(
+        &self,
+        request: coordinator::proto::WorkerInfo,
+    ) -> PyResult<WorkerConfig> {
+        let worker_id = request.worker_id.clone();
+        let config = self
+            .call("Failed to register worker", false, |mut c| {
+                let request = request.clone();
+                async move { c.register_worker(request).await }
+            })
+            .await?;
+
+        // Store worker ID for future calls
+        *self.worker_id.lock().await = Some(worker_id);
+        *self.registration.lock().await = Some(request);
+        self.registered_in
+            .store(runtime::forks(), Ordering::Relaxed);
+        self.session.store(config.session, Ordering::Relaxed);
+        self.heartbeat_interval_ms
+            .store(config.heartbeat_interval_ms, Ordering::Relaxed);
+
+        Ok(WorkerConfig {
+            worker_id: config.assigned_id,
+            rank: config.rank,
+            world_size: config.world_size,
+            heartbeat_interval_ms: config.heartbeat_interval_ms,
+            membership_generation: config.membership_generation,
+            protocol_version: config.protocol_version,
+            session: config.session,
+        })
+    }
+
+    async fn heartbeat(
+        &self,
+        status: coordinator::proto::WorkerStatus,
+        metrics: &[coordinator::proto::MetricPoint],
+    ) -> PyResult<bool> {
+        let resources = self
+            .resources
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .collect();
+
+        let request = coordinator::proto::HeartbeatRequest {
+            worker_id: self.worker_id().await?,
+            session: self.session.load(Ordering::Relaxed),
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            status: Some(status),
+            resources: Some(resources.into()),
+            metrics: metrics.to_vec(),
+        };
+
+        // Not retried: the response carries the worker's pending commands,
+        // which a retry after a lost response would never see
+        let response = match self
+            .try_call("Heartbeat failed", false, |mut c| {
+                let request = request.clone();
+                async move { c.heartbeat(request).await }
+            })
+            .await
+        {
+            Ok(response) => response,
+            Err(CallError::Rpc(status))
+                if matches!(status.code(), Code::NotFound | Code::FailedPrecondition) =>
+            {
+                return self.register_again(status).await;
+            }
+            Err(e) => return Err(e.into_py("Heartbeat failed")),
+        };
+
+        for command in &response.pending_commands {
+            self.dispatch_command(command);
+        }
+        Ok(response.acknowledged)
+    }
+
+    /// Register again after the coordinator lost the worker or its session
+    ///
+    /// This happens when the coordinator restarts or removes the worker as
+    /// dead. Returns whether the worker is registered again.
+    async fn register_again(&self, status: Status) -> PyResult<bool> {
+        let Some(request) = self.registration.lock().await.clone() else {
+            return Err(PyRuntimeError::new_err(format!(
+                "Heartbeat failed: {}",
+                status
+            )));
+        };
+        tracing::warn!(
+            error = %status.message(),
+            "Coordinator dropped the worker, registering again"
+        );
+        self.register_worker(request).await?;
+        Ok(true)
+    }
+
+    /// Call the handlers registered for a `name:argument` command
+    ///
+    /// A handler that raises is reported through `sys.unraisablehook` so
+    /// the other handlers and the heartbeat still run.
+    fn dispatch_command(&self, command: &str) {
+        let (name, argument) = match command.split_once(':') {
+            Some((name, argument)) => (name, Some(argument)),
+            None => (command, None),
+        };
+        Python::with_gil(|py| {
+            // Copied out so handlers can register more handlers
+            let handlers: Vec<PyObject> = self
+                .command_handlers
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .get(name)
+                .map(|handlers| handlers.iter().map(|h| h.clone_ref(py)).collect())
+                .unwrap_or_default();
+            if handlers.is_empty() {
+                tracing::debug!(command = %command, "No handler for worker command");
+            }
+            for handler in handlers {
+                if let Err(e) = handler.call1(py, (argument,)) {
+                    e.write_unraisable_bound(py, Some(handler.bind(py)));
+                }
+            }
+        });
+    }
+
+    /// Status carrying the progress last set with `update_progress`
+    fn progress_status(&self) -> coordinator::proto::WorkerStatus {
+        let progress = self
+            .progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        worker_status(
+            progress.step,
+            progress.epoch,
+            Some(progress.shard_progress),
+            progress.loss,
+        )
+    }
+
+    /// Send heartbeats carrying the latest progress every `interval`
+    async fn heartbeat_loop(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match self.heartbeat(self.progress_status(), &[]).await {
+                Ok(true) => {}
+                Ok(false) => tracing::warn!("Background heartbeat not acknowledged"),
+                Err(e) => tracing::warn!(error = %e, "Background heartbeat failed"),
+            }
+        }
+    }
+
+    /// Pass membership changes to `callback` until aborted
+    ///
+    /// Each connection starts with the current members reported as joined,
+    /// so after reconnecting the callback sees the membership afresh.
+    async fn membership_loop(self: Arc<Self>, callback: PyObject) {
+        let subscriber_id = match self.worker_id.lock().await.clone() {
+            Some(worker_id) => worker_id,
+            None => runtime_core::id::next_id("watch"),
+        };
+        let request = coordinator::proto::WatchWorkersRequest {
+            subscriber_id,
+            include_snapshot: true,
+        };
+        let mut attempt = 0;
+        loop {
+            let stream = self
+                .call("Failed to watch workers", true, |mut c| {
+                    let request = request.clone();
+                    async move { c.watch_workers(request).await }
+                })
+                .await;
+            match stream {
+                Ok(mut stream) => {
+                    let mut members = Members::default();
+                    loop {
+                        match stream.message().await {
+                            Ok(Some(event)) => {
+                                attempt = 0;
+                                let event = members.apply(event);
+                                Python::with_gil(|py| {
+                                    if let Err(e) = callback.call1(py, (event,)) {
+                                        e.write_unraisable_bound(py, Some(callback.bind(py)));
+                                    }
+                                });
+                            }
+                            Ok(None) => break,
+                            Err(status) => {
+                                tracing::warn!(error = %status, "Membership watch interrupted");
+                                break;
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!(error = %e, "Failed to watch membership"),
+            }
+            if self.closed.load(Ordering::Relaxed) {
+                return;
+            }
+            tokio::time::sleep(runtime_core::retry::backoff_delay(&self.retry, attempt)).await;
+            attempt = attempt.saturating_add(1);
+        }
+    }
+
+    async fn register_dataset(&self, request: coordinator::proto::DatasetInfo) -> PyResult<i64> {
+        let response = self
+            .call("Failed to register dataset", false, |mut c| {
+                let request = request.clone();
+                async move { c.register_dataset(request).await }
+            })
+            .await?;
+
+        Ok(response.total_shards)
+    }
+
+    async fn get_shard(
+        &self,
+        dataset_id: String,
+        epoch: i64,
+        gpu_count: i32,
+        available_memory_bytes: i64,
+    ) -> PyResult<CoordinatorShardInfo> {
+        let request = coordinator::proto::ShardRequest {
+            worker_id: self.worker_id().await?,
+            dataset_id,
+            epoch,
+            available_memory_bytes,
+            gpu_count,
+            max_shards: 0,
+            use_current_epoch: false,
+        };
+
+        let response = self
+            .call("Failed to get shard", true, |mut c| {
+                let request = request.clone();
+                async move { c.get_data_shard(request).await }
+            })
+            .await?;
+
+        Ok(response.into())
+    }
+
+    async fn get_shards(
+        &self,
+        dataset_id: String,
+        epoch: i64,
+        max_shards: i32,
+    ) -> PyResult<Vec<CoordinatorShardInfo>> {
+        let request = coordinator::proto::ShardRequest {
+            worker_id: self.worker_id().await?,
+            dataset_id,
+            epoch,
+            max_shards,
+            ..Default::default()
+        };
+
+        let response = self
+            .call("Failed to get shards", true, |mut c| {
+                let request = request.clone();
+                async move { c.get_data_shards(request).await }
+            })
+            .await?;
+
+        Ok(response
+            .assignments
+            .into_iter()
+            .map(CoordinatorShardInfo::from)
+            .collect())
+    }
+
+    /// Wait for a barrier, giving up with `BarrierTimeout` after `timeout`
+    async fn barrier(
+        &self,
+        barrier_id: String,
+        step: i64,
+        timeout: Option<Duration>,
+    ) -> PyResult<BarrierResult> {
+        let Some(timeout) = timeout else {
+            return self.arrive(barrier_id, step, false).await;
+        };
+        tokio::time::timeout(timeout, self.arrive(barrier_id.clone(), step, false))
+            .await
+            .map_err(|_| {
+                BarrierTimeout::new_err(format!(
+                    "Barrier {} not released within {:.1}s",
+                    barrier_id,
+                    timeout.as_secs_f64()
+                ))
+            })?
+    }
+
+    /// Arrive at a barrier, waiting for its release unless `no_wait`
+    async fn arrive(
+        &self,
+        barrier_id: String,
+        step: i64,
+        no_wait: bool,
+    ) -> PyResult<BarrierResult> {
+        let request = coordinator::proto::BarrierRequest {
+            worker_id: self.worker_id().await?,
+            barrier_id,
+            step,
+            generation: 0,
+            no_wait,
+        };
+
+        let result = self
+            .call("Barrier failed", true, |mut c| {
+                let request = request.clone();
+                async move { c.wait_barrier(request).await }
+            })
+            .await?;
+        if result.aborted {
+            return Err(PyRuntimeError::new_err(format!(
+                "Barrier {} was aborted by the coordinator",
+                result.barrier_id
+            )));
+        }
+        Ok(BarrierResult {
+            released: result.released,
+            participants: result.participants,
+            arrival_order: result.arrival_order,
+            already_released: result.already_released,
+            // Only reported for no_wait arrivals that didn't release the barrier
+            expected: if result.expected > 0 {
+                result.expected
+            } else {
+                result.participants
+            },
+        })
+    }
+
+    async fn cluster_state(
+        &self,
+        context: &str,
+        max_checkpoints: i32,
+    ) -> PyResult<coordinator::proto::ClusterState> {
+        let request = coordinator::proto::ClusterStateRequest { max_checkpoints };
+        self.call(context, true, |mut c| async move {
+            c.get_cluster_state(request).await
+        })
+        .await
+    }
+
+    async fn cluster_metrics(&self) -> PyResult<ClusterMetrics> {
+        let request = coordinator::proto::ClusterMetricsRequest {};
+        self.call("Failed to get cluster metrics", true, |mut c| async move {
+            c.get_cluster_metrics(request).await
+        })
+        .await
+        .map(ClusterMetrics::from)
+    }
+
+    async fn current_epoch(&self, dataset_id: String) -> PyResult<i64> {
+        let state = self.cluster_state("Failed to get epoch", 0).await?;
+
+        state
+            .datasets
+            .into_iter()
+            .find(|d| d.info.as_ref().is_some_and(|i| i.dataset_id == dataset_id))
+            .map(|d| d.current_epoch)
+            .ok_or_else(|| {
+                PyRuntimeError::new_err(format!("Dataset {} not registered", dataset_id))
+            })
+    }
+
+    async fn advance_epoch(&self, dataset_id: String, epoch: Option<i64>) -> PyResult<EpochStatus> {
+        let epoch = match epoch {
+            Some(epoch) => epoch,
+            None => self.current_epoch(dataset_id.clone()).await?,
+        };
+        let request = coordinator::proto::EpochCompleteRequest {
+            worker_id: self.worker_id().await?,
+            dataset_id,
+            epoch,
+            session: self.session.load(Ordering::Relaxed),
+        };
+
+        let response = self
+            .call("Failed to advance epoch", true, |mut c| {
+                let request = request.clone();
+                async move { c.report_epoch_complete(request).await }
+            })
+            .await?;
+
+        Ok(EpochStatus {
+            advanced: response.advanced,
+            current_epoch: response.current_epoch,
+            reported: response.reported,
+            required: response.required,
+        })
+    }
+
+    async fn get_latest_checkpoint(&self, job_id: String) -> PyResult<Option<RecoveryInfo>> {
+        let request = coordinator::proto::RecoveryRequest {
+            worker_id: self.worker_id().await?,
+            job_id,
+        };
+
+        let response = self
+            .call("Failed to get latest checkpoint", true, |mut c| {
+                let request = request.clone();
+                async move { c.get_latest_checkpoint(request).await }
+            })
+            .await?;
+
+        let Some(checkpoint) = response
+            .latest_checkpoint
+            .filter(|_| response.has_checkpoint)
+        else {
+            return Ok(None);
+        };
+        Ok(Some(RecoveryInfo {
+            checkpoint_id: checkpoint.checkpoint_id,
+            worker_id: checkpoint.worker_id,
+            storage_path: checkpoint.storage_path,
+            size_bytes: checkpoint.size_bytes,
+            resume_step: response.resume_step,
+            resume_epoch: response.resume_epoch,
+            metadata: checkpoint.metadata,
+            shard_assignments: response
+                .shard_assignments
+                .into_iter()
+                .map(CoordinatorShardInfo::from)
+                .collect(),
+        }))
+    }
+
+    async fn deregister(&self) -> PyResult<()> {
+        let request = coordinator::proto::WorkerInfo {
+            worker_id: self.worker_id().await?,
+            hostname: String::new(),
+            port: 0,
+            gpu_count: 0,
+            memory_bytes: 0,
+            metadata: HashMap::new(),
+            protocol_version: coordinator::PROTOCOL_VERSION,
+            labels: HashMap::new(),
+            taints: Vec::new(),
+        };
+
+        self.call("Failed to deregister", false, |mut c| {
+            let request = request.clone();
+            async move { c.deregister_worker(request).await }
+        })
+        .await?;
+
+        // Heartbeats must not bring a deregistered worker back
+        *self.registration.lock().await = None;
+        Ok(())
+    }
+}
+
+/// Barrier timeout in seconds from Python
+fn barrier_timeout(secs: Option<f64>) -> PyResult<Option<Duration>> {
+    secs.map(|secs| {
+        Duration::try_from_secs_f64(secs)
+            .map_err(|_| PyValueError::new_err("timeout must be non-negative"))
+    })
+    .transpose()
+}
+
+/// Why a coordinator call gave up
+enum CallError {
+    /// The orchestrator was closed
+    Closed,
+    /// The coordinator could not be reached
+    Connect(Status),
+    /// The coordinator returned an error
+    Rpc(Status),
+}
+
+impl CallError {
+    /// Python exception for the error, with `context` prefixing RPC errors
+    fn into_py(self, context: &str) -> PyErr {
+        match self {
+            CallError::Closed => PyRuntimeError::new_err("TrainingOrchestrator is closed"),
+            CallError::Connect(status) => connect_error(status),
+            CallError::Rpc(status) => PyRuntimeError::new_err(format!("{}: {}", context, status)),
+        }
+    }
+}
+
+/// Python exception for a failure to reach the coordinator
+fn connect_error(status: Status) -> PyErr {
+    match status.code() {
+        Code::InvalidArgument => PyValueError::new_err(status.message().to_string()),
+        _ => PyConnectionError::new_err(status.message().to_string()),
+    }
+}
+
+fn worker_info(
+    worker_id: &str,
+    hostname: &str,
+    port: i32,
+    gpu_count: i32,
+    memory_bytes: i64,
+    metadata: Option<HashMap<String, String>>,
+) -> coordinator::proto::WorkerInfo {
+    coordinator::proto::WorkerInfo {
+        worker_id: worker_id.to_string(),
+        hostname: hostname.to_string(),
+        port,
+        gpu_count,
+        memory_bytes,
+        metadata: metadata.unwrap_or_default(),
+        protocol_version: coordinator::PROTOCOL_VERSION,
+        labels: HashMap::new(),
+        taints: Vec::new(),
+    }
+}
+
+fn worker_status(
+    current_step: i64,
+    current_epoch: i64,
+    shard_progress: Option<Vec<(String, i64, i64)>>,
+    loss: Option<f64>,
+) -> coordinator::proto::WorkerStatus {
+    coordinator::proto::WorkerStatus {
+        state: coordinator::proto::worker_status::State::Training as i32,
+        current_step,
+        current_epoch,
+        current_task: String::new(),
+        shard_progress: shard_progress
+            .unwrap_or_default()
+            .into_iter()
+            .map(
+                |(dataset_id, shard_id, samples_consumed)| coordinator::proto::ShardProgress {
+                    dataset_id,
+                    shard_id,
+                    samples_consumed,
+                },
+            )
+            .collect(),
+        loss: loss.unwrap_or_default(),
+    }
+}
+
+fn dataset_info(
+    dataset_id: &str,
+    path: &str,
+    total_samples: i64,
+    shard_size: i64,
+    shuffle: bool,
+    seed: i64,
+) -> coordinator::proto::DatasetInfo {
+    coordinator::proto::DatasetInfo {
+        dataset_id: dataset_id.to_string(),
+        path: path.to_string(),
+        format: "auto".to_string(),
+        total_samples,
+        shard_size,
+        shuffle,
+        seed,
+        metadata: HashMap::new(),
+    }
+}
+
+/// High-level training orchestrator for distributed training coordination
+///
+/// Connects to a coordinator gRPC server to manage worker registration,
+/// heartbeats, data sharding, and synchronization barriers.
+///
+/// Example:
+///     orch = TrainingOrchestrator("http://localhost:50051")
+///     config = orch.register_worker("worker-0", "localhost", 50052, gpu_count=8)
+///     print(f"Registered as rank {config.rank} of {config.world_size}")
+///     
+///     # Get data shard for this worker
+///     shard = orch.get_shard("imagenet", epoch=0)
+///     
+///     # Synchronize with other workers
+///     orch.barrier("epoch-0", step=100)
+///
+///     # Or, from a coroutine
+///     await orch.barrier_async("epoch-0", step=100)
+#[pyclass(weakref)]
+pub struct TrainingOrchestrator {
+    runtime: SharedRuntime,
+    inner: Arc<Inner>,
+    /// Background heartbeat task from `start_heartbeat`
+    heartbeat_task: std::sync::Mutex<Option<ProcessLocal<JoinHandle<()>>>>,
+    /// Membership watch from `watch_membership`
+    membership_task: std::sync::Mutex<Option<ProcessLocal<JoinHandle<()>>>>,
+    /// Coordinator started by `local`, stopped by `close`
+    local: std::sync::Mutex<Option<ProcessLocal<LocalCoordinator>>>,
+}
+
+#[pymethods]
+impl TrainingOrchestrator {
+    /// Create a new training orchestrator
+    ///
+    /// Calls that are safe to repeat (shard and epoch queries, barriers and
+    /// recovery) are retried with exponential backoff while the coordinator
+    /// is unavailable, e.g. during a restart. Other calls, heartbeats
+    /// included, fail at once; every call reconnects after a lost connection.
+    ///
+    /// Args:
+    ///     coordinator_url: URL of the coordinator gRPC server (e.g., "http://localhost:50051")
+    ///     max_retries: Retries of an idempotent call, 0 to disable (default: 3)
+    ///     retry_initial_delay: Seconds before the first retry, doubling
+    ///         after each one (default: 0.1)
+    ///     retry_max_delay: Longest delay between retries in seconds
+    ///         (default: 10)
+    #[new]
+    #[pyo3(signature = (
+        coordinator_url,
+        max_retries=3,
+        retry_initial_delay=0.1,
+        retry_max_delay=10.0
+    ))]
+    fn new(
+        coordinator_url: &str,
+        max_retries: u32,
+        retry_initial_delay: f64,
+        retry_max_delay: f64,
+    ) -> PyResult<Self> {
+        let delay = |secs: f64, name: &str| {
+            Duration::try_from_secs_f64(secs)
+                .map_err(|_| PyValueError::new_err(format!("{} must be non-negative", name)))
+        };
+        let retry = RetryConfig {
+            max_retries,
+            initial_delay: delay(retry_initial_delay, "retry_initial_delay")?,
+            max_delay: delay(retry_max_delay, "retry_max_delay")?,
+            ..Default::default()
+        };
+
+        Ok(Self::with_runtime(
+            SharedRuntime::new()?,
+            coordinator_url.to_string(),
+            retry,
+            None,
+        ))
+    }
+
+    /// Create an orchestrator with its own coordinator in this process
+    ///
+    /// For single-node training and tests without a coordinator server. The
+    /// coordinator runs on the shared async runtime and listens on a free
+    /// loopback port, so shard assignments (including shuffling for a given
+    /// seed), barriers, epochs and recovery behave exactly as against a
+    /// server. Other orchestrators in the process can share it through
+    /// `coordinator_url`. It stops when this orchestrator is closed.
+    ///
+    /// Args:
+    ///     state_dir: Directory for the coordinator's checkpoints, and the
+    ///         cluster state saved when it stops. By default a temporary
+    ///         directory is used and removed on close.
+    ///
+    /// Example:
+    ///     with TrainingOrchestrator.local() as orch:
+    ///         orch.register_worker("worker-0", "localhost", 50052)
+    ///         orch.register_dataset("train", "/data/train", 10000, 1000)
+    ///         shard = orch.get_shard("train", epoch=0)
+    #[staticmethod]
+    #[pyo3(signature = (state_dir=None))]
+    fn local(py: Python<'_>, state_dir: Option<PathBuf>) -> PyResult<Self> {
+        let runtime = SharedRuntime::new()?;
+        let rt = runtime.get()?;
+        let local = py.allow_threads(|| rt.block_on(LocalCoordinator::start(state_dir)))?;
+        Ok(Self::with_runtime(
+            runtime,
+            local.url.clone(),
+            RetryConfig::default(),
+            Some(local),
+        ))
+    }
+
+    /// URL of the coordinator, e.g. of the one started by `local`
+    #[getter]
+    fn coordinator_url(&self) -> &str {
+        &self.inner.coordinator_url
+    }
+
+    /// Connect to the coordinator server
+    ///
+    /// This is called automatically by other methods if not already connected.
+    fn connect(&self, py: Python<'_>) -> PyResult<()> {
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            inner.connect().await.map(|_| ()).map_err(connect_error)
+        })
+    }
+
+    /// Register this worker with the coordinator
+    ///
+    /// Args:
+    ///     worker_id: Unique identifier for this worker
+    ///     hostname: Hostname or IP address
+    ///     port: Port number for worker-to-worker communication
+    ///     gpu_count: Number of GPUs available (default: 0)
+    ///     memory_bytes: Available memory in bytes (default: 0)
+    ///     metadata: Optional metadata dictionary
+    ///
+    /// Returns:
+    ///     WorkerConfig with assigned rank and world size
+    #[pyo3(signature = (worker_id, hostname, port, gpu_count=0, memory_bytes=0, metadata=None))]
+    fn register_worker(
+        slf: &Bound<'_, Self>,
+        worker_id: &str,
+        hostname: &str,
+        port: i32,
+        gpu_count: i32,
+        memory_bytes: i64,
+        metadata: Option<HashMap<String, String>>,
+    ) -> PyResult<WorkerConfig> {
+        Self::close_at_exit_if_registered(slf)?;
+        let request = worker_info(worker_id, hostname, port, gpu_count, memory_bytes, metadata);
+        let this = slf.borrow();
+        let inner = this.inner.clone();
+        this.block_on(
+            slf.py(),
+            async move { inner.register_worker(request).await },
+        )
+    }
+
+    /// Awaitable form of `register_worker`
+    #[pyo3(signature = (worker_id, hostname, port, gpu_count=0, memory_bytes=0, metadata=None))]
+    fn register_worker_async<'py>(
+        slf: &Bound<'py, Self>,
+        worker_id: &str,
+        hostname: &str,
+        port: i32,
+        gpu_count: i32,
+        memory_bytes: i64,
+        metadata: Option<HashMap<String, String>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        Self::close_at_exit_if_registered(slf)?;
+        let request = worker_info(worker_id, hostname, port, gpu_count, memory_bytes, metadata);
+        let this = slf.borrow();
+        let inner = this.inner.clone();
+        this.runtime.future_into_py(
+            slf.py(),
+            async move { inner.register_worker(request).await },
+        )
+    }
+
+    /// Send a heartbeat to the coordinator
+    ///
+    /// CPU, memory, disk, network and (with NVML) GPU usage of this host are
+    /// sampled and attached automatically. Commands in the response are
+    /// passed to the callbacks registered with `on_command`. If the
+    /// coordinator no longer knows the worker, e.g. after it restarted, the
+    /// worker registers again. Failed heartbeats are not retried, as that
+    /// could lose commands.
+    ///
+    /// Args:
+    ///     current_step: Current training step (default: 0)
+    ///     current_epoch: Current training epoch (default: 0)
+    ///     shard_progress: Optional list of (dataset_id, shard_id, samples_consumed)
+    ///     loss: Optional training loss, used by adaptive checkpoint scheduling
+    ///
+    /// Returns:
+    ///     True if acknowledged
+    #[pyo3(signature = (current_step=0, current_epoch=0, shard_progress=None, loss=None))]
+    fn heartbeat(
+        &self,
+        py: Python<'_>,
+        current_step: i64,
+        current_epoch: i64,
+        shard_progress: Option<Vec<(String, i64, i64)>>,
+        loss: Option<f64>,
+    ) -> PyResult<bool> {
+        let status = worker_status(current_step, current_epoch, shard_progress, loss);
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.heartbeat(status, &[]).await })
+    }
+
+    /// Awaitable form of `heartbeat`
+    #[pyo3(signature = (current_step=0, current_epoch=0, shard_progress=None, loss=None))]
+    fn heartbeat_async<'py>(
+        &self,
+        py: Python<'py>,
+        current_step: i64,
+        current_epoch: i64,
+        shard_progress: Option<Vec<(String, i64, i64)>>,
+        loss: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let status = worker_status(current_step, current_epoch, shard_progress, loss);
+        let inner = self.inner.clone();
+        self.runtime
+            .future_into_py(py, async move { inner.heartbeat(status, &[]).await })
+    }
+
+    /// Send heartbeats from a background task until `stop_heartbeat`
+    ///
+    /// Each heartbeat carries the progress last set with `update_progress`.
+    /// Calling it again restarts the task with the new interval.
+    ///
+    /// Args:
+    ///     interval: Seconds between heartbeats (default: the interval
+    ///         recommended by the coordinator at registration)
+    ///
+    /// Raises:
+    ///     RuntimeError: If the worker is not registered
+    #[pyo3(signature = (interval=None))]
+    fn start_heartbeat(&self, interval: Option<f64>) -> PyResult<()> {
+        let recommended_ms = self.inner.heartbeat_interval_ms.load(Ordering::Relaxed);
+        if recommended_ms <= 0 {
+            return Err(PyRuntimeError::new_err(
+                "Worker not registered. Call register_worker() first.",
+            ));
+        }
+        let interval = match interval {
+            Some(secs) => Duration::try_from_secs_f64(secs)
+                .ok()
+                .filter(|d| !d.is_zero())
+                .ok_or_else(|| PyValueError::new_err("interval must be positive"))?,
+            None => Duration::from_millis(recommended_ms as u64),
+        };
+
+        let task = self
+            .runtime
+            .get()?
+            .spawn(self.inner.clone().heartbeat_loop(interval));
+        let previous = self
+            .heartbeat_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(ProcessLocal::new(task));
+        if let Some(previous) = previous.and_then(ProcessLocal::into_inner) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the background heartbeats started by `start_heartbeat`
+    fn stop_heartbeat(&self) {
+        self.stop_heartbeat_task();
+    }
+
+    /// Whether background heartbeats are running
+    #[getter]
+    fn heartbeat_running(&self) -> bool {
+        self.heartbeat_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .and_then(ProcessLocal::get)
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    /// Set the progress sent by background heartbeats
+    ///
+    /// Only the given values change.
+    ///
+    /// Args:
+    ///     step: Current training step
+    ///     epoch: Current training epoch
+    ///     loss: Latest training loss
+    ///     shard_progress: List of (dataset_id, shard_id, samples_consumed)
+    #[pyo3(signature = (step=None, epoch=None, loss=None, shard_progress=None))]
+    fn update_progress(
+        &self,
+        step: Option<i64>,
+        epoch: Option<i64>,
+        loss: Option<f64>,
+        shard_progress: Option<Vec<(String, i64, i64)>>,
+    ) {
+        let mut progress = self
+            .inner
+            .progress
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        if let Some(step) = step {
+            progress.step = step;
+        }
+        if let Some(epoch) = epoch {
+            progress.epoch = epoch;
+        }
+        if loss.is_some() {
+            progress.loss = loss;
+        }
+        if let Some(shard_progress) = shard_progress {
+            progress.shard_progress = shard_progress;
+        }
+    }
+
+    /// Call `callback` when a heartbeat response carries command `name`
+    ///
+    /// Commands come from the coordinator, e.g. `checkpoint_now` when it
+    /// schedules a checkpoint or `pause_task` from an administrator. The
+    /// callback gets the command argument as a string, such as the step
+    /// for `checkpoint_now`, or None if it has none. Several callbacks can
+    /// be registered for one command.
+    ///
+    /// Callbacks run on the thread sending the heartbeat, which is a
+    /// background thread after `start_heartbeat()`, so they should only
+    /// hand the command to the training loop, e.g. by setting a flag.
+    ///
+    /// Args:
+    ///     name: Command name, e.g. "checkpoint_now"
+    ///     callback: Called with the command argument
+    ///
+    /// Example:
+    ///     checkpoint_at = []
+    ///     orch.on_command("checkpoint_now", lambda step: checkpoint_at.append(int(step)))
+    fn on_command(&self, name: &str, callback: &Bound<'_, PyAny>) -> PyResult<()> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("callback must be callable"));
+        }
+        self.inner
+            .command_handlers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .entry(name.to_string())
+            .or_default()
+            .push(callback.clone().unbind());
+        Ok(())
+    }
+
+    /// Remove the callbacks registered for command `name`
+    fn off_command(&self, name: &str) {
+        self.inner
+            .command_handlers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(name);
+    }
+
+    /// Call `callback` with each change in cluster membership
+    ///
+    /// Changes stream from the coordinator's `WatchWorkers` RPC: workers
+    /// joining, leaving, dying, changing state or getting a new rank. The
+    /// callback gets a `MembershipEvent`, whose `world_size` counts the
+    /// live workers, so elastic training can rebuild its process group when
+    /// the world size or its own rank changes. The current members come
+    /// first, reported as joined, and again after the watch reconnects.
+    ///
+    /// Callbacks run on a background thread, so like `on_command` callbacks
+    /// they should hand the change to the training loop. Calling it again
+    /// replaces the callback.
+    ///
+    /// Args:
+    ///     callback: Called with each MembershipEvent
+    ///
+    /// Example:
+    ///     changes = queue.Queue()
+    ///     orch.watch_membership(changes.put)
+    fn watch_membership(&self, callback: &Bound<'_, PyAny>) -> PyResult<()> {
+        if !callback.is_callable() {
+            return Err(PyTypeError::new_err("callback must be callable"));
+        }
+        let task = self.runtime.get()?.spawn(
+            self.inner
+                .clone()
+                .membership_loop(callback.clone().unbind()),
+        );
+        let previous = self
+            .membership_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .replace(ProcessLocal::new(task));
+        if let Some(previous) = previous.and_then(ProcessLocal::into_inner) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    /// Stop the membership watch started by `watch_membership`
+    fn unwatch_membership(&self) {
+        self.stop_membership_task();
+    }
+
+    /// Register a dataset with the coordinator
+    ///
+    /// Args:
+    ///     dataset_id: Unique dataset identifier
+    ///     path: Path to the dataset
+    ///     total_samples: Total number of samples
+    ///     shard_size: Samples per shard
+    ///     shuffle: Whether to shuffle (default: True)
+    ///     seed: Random seed (default: 42)
+    #[pyo3(signature = (dataset_id, path, total_samples, shard_size, shuffle=true, seed=42))]
+    #[allow(clippy::too_many_arguments)]
+    fn register_dataset(
+        &self,
+        py: Python<'_>,
+        dataset_id: &str,
+        path: &str,
+        total_samples: i64,
+        shard_size: i64,
+        shuffle: bool,
+        seed: i64,
+    ) -> PyResult<i64> {
+        let request = dataset_info(dataset_id, path, total_samples, shard_size, shuffle, seed);
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.register_dataset(request).await })
+    }
+
+    /// Register a dataset from its files, counting their samples
+    ///
+    /// Samples are counted from Parquet footers, from index files next to
+    /// the data (`train-0.tfrecord.idx`), or from the lines of JSON Lines
+    /// files, so no total needs working out by hand.
+    ///
+    /// Args:
+    ///     dataset_id: Unique dataset identifier
+    ///     paths: A glob, directory or file (local or s3://bucket/...), or a list of files
+    ///     shard_size: Samples per shard (default: about one shard per file)
+    ///     shuffle: Whether to shuffle (default: True)
+    ///     seed: Random seed (default: 42)
+    ///     endpoint: Custom S3 endpoint URL, for s3:// paths
+    ///     region: S3 region, for s3:// paths
+    ///
+    /// Returns:
+    ///     DatasetManifest listing the files and their samples
+    #[pyo3(signature = (dataset_id, paths, shard_size=None, shuffle=true, seed=42, endpoint=None, region=None))]
+    #[allow(clippy::too_many_arguments)]
+    fn register_dataset_from_manifest(
+        &self,
+        py: Python<'_>,
+        dataset_id: &str,
+        paths: DatasetPaths,
+        shard_size: Option<u64>,
+        shuffle: bool,
+        seed: i64,
+        endpoint: Option<String>,
+        region: Option<String>,
+    ) -> PyResult<DatasetManifest> {
+        if shard_size == Some(0) {
+            return Err(PyValueError::new_err("shard_size must be positive"));
+        }
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            let (manifest, path) = dataset::build_manifest(paths, endpoint, region).await?;
+            let shard_size = shard_size.unwrap_or_else(|| manifest.default_shard_size());
+            let mut request = dataset_info(
+                &dataset_id,
+                &path,
+                manifest.total_samples() as i64,
+                shard_size as i64,
+                shuffle,
+                seed,
+            );
+            request.format = manifest.format().to_string();
+            request
+                .metadata
+                .insert("file_count".to_string(), manifest.files().len().to_string());
+            let total_shards = inner.register_dataset(request).await?;
+            Ok(DatasetManifest::new(
+                dataset_id,
+                path,
+                manifest,
+                shard_size,
+                total_shards as u64,
+            ))
+        })
+    }
+
+    /// Get shard assignment for this worker
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///     epoch: Training epoch
+    ///     gpu_count: GPUs on this worker, used to size the shard batch (default: 0)
+    ///     available_memory_bytes: Memory available for shard buffers (default: 0)
+    ///
+    /// Returns:
+    ///     CoordinatorShardInfo with shard assignment details
+    #[pyo3(signature = (dataset_id, epoch, gpu_count=0, available_memory_bytes=0))]
+    fn get_shard(
+        &self,
+        py: Python<'_>,
+        dataset_id: &str,
+        epoch: i64,
+        gpu_count: i32,
+        available_memory_bytes: i64,
+    ) -> PyResult<CoordinatorShardInfo> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            inner
+                .get_shard(dataset_id, epoch, gpu_count, available_memory_bytes)
+                .await
+        })
+    }
+
+    /// Awaitable form of `get_shard`
+    #[pyo3(signature = (dataset_id, epoch, gpu_count=0, available_memory_bytes=0))]
+    fn get_shard_async<'py>(
+        &self,
+        py: Python<'py>,
+        dataset_id: &str,
+        epoch: i64,
+        gpu_count: i32,
+        available_memory_bytes: i64,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.runtime.future_into_py(py, async move {
+            inner
+                .get_shard(dataset_id, epoch, gpu_count, available_memory_bytes)
+                .await
+        })
+    }
+
+    /// Get every shard assigned to this worker
+    ///
+    /// `get_shard` returns a batch sized for the worker's resources; this
+    /// returns the complete assignment, one entry per shard.
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///     epoch: Training epoch
+    ///     max_shards: Return at most this many shards, 0 for all (default: 0)
+    ///
+    /// Returns:
+    ///     List of CoordinatorShardInfo, in assignment order
+    #[pyo3(signature = (dataset_id, epoch, max_shards=0))]
+    fn get_shards(
+        &self,
+        py: Python<'_>,
+        dataset_id: &str,
+        epoch: i64,
+        max_shards: i32,
+    ) -> PyResult<Vec<CoordinatorShardInfo>> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            inner.get_shards(dataset_id, epoch, max_shards).await
+        })
+    }
+
+    /// Awaitable form of `get_shards`
+    #[pyo3(signature = (dataset_id, epoch, max_shards=0))]
+    fn get_shards_async<'py>(
+        &self,
+        py: Python<'py>,
+        dataset_id: &str,
+        epoch: i64,
+        max_shards: i32,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.runtime.future_into_py(py, async move {
+            inner.get_shards(dataset_id, epoch, max_shards).await
+        })
+    }
+
+    /// Wait at a synchronization barrier
+    ///
+    /// Args:
+    ///     barrier_id: Unique barrier identifier
+    ///     step: Training step for this barrier, used as the barrier generation
+    ///     timeout: Seconds to wait for the other workers before raising
+    ///         BarrierTimeout (default: wait as long as the coordinator does)
+    ///
+    /// Returns:
+    ///     BarrierResult with synchronization details
+    ///
+    /// Raises:
+    ///     BarrierTimeout: If the barrier isn't released within `timeout`.
+    ///         This worker still counts as arrived, so waiting again or
+    ///         polling with `try_barrier` continues where it left off.
+    ///     RuntimeError: If the barrier fails or an administrator aborts it
+    #[pyo3(signature = (barrier_id, step, timeout=None))]
+    fn barrier(
+        &self,
+        py: Python<'_>,
+        barrier_id: &str,
+        step: i64,
+        timeout: Option<f64>,
+    ) -> PyResult<BarrierResult> {
+        let barrier_id = barrier_id.to_string();
+        let timeout = barrier_timeout(timeout)?;
+        let inner = self.inner.clone();
+        self.block_on(
+            py,
+            async move { inner.barrier(barrier_id, step, timeout).await },
+        )
+    }
+
+    /// Awaitable form of `barrier`
+    #[pyo3(signature = (barrier_id, step, timeout=None))]
+    fn barrier_async<'py>(
+        &self,
+        py: Python<'py>,
+        barrier_id: &str,
+        step: i64,
+        timeout: Option<f64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let barrier_id = barrier_id.to_string();
+        let timeout = barrier_timeout(timeout)?;
+        let inner = self.inner.clone();
+        self.runtime.future_into_py(
+            py,
+            async move { inner.barrier(barrier_id, step, timeout).await },
+        )
+    }
+
+    /// Arrive at a barrier without waiting for it
+    ///
+    /// Counts this worker as arrived and returns at once. While other
+    /// workers are still expected, `released` is False and `participants`
+    /// of `expected` workers have arrived; call again to poll, or `barrier`
+    /// to wait. Lets training loops fall back to their own logic instead of
+    /// blocking on a straggler.
+    ///
+    /// Args:
+    ///     barrier_id: Unique barrier identifier
+    ///     step: Training step for this barrier, used as the barrier generation
+    ///
+    /// Returns:
+    ///     BarrierResult with the arrivals so far
+    ///
+    /// Example:
+    ///     while not orch.try_barrier("epoch-3", step=3000).released:
+    ///         prefetch_next_epoch()
+    fn try_barrier(&self, py: Python<'_>, barrier_id: &str, step: i64) -> PyResult<BarrierResult> {
+        let barrier_id = barrier_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(
+            py,
+            async move { inner.arrive(barrier_id, step, true).await },
+        )
+    }
+
+    /// Current epoch of a dataset, as tracked by the coordinator
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///
+    /// Raises:
+    ///     RuntimeError: If the dataset is not registered
+    fn current_epoch(&self, py: Python<'_>, dataset_id: &str) -> PyResult<i64> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.current_epoch(dataset_id).await })
+    }
+
+    /// Report that this worker finished an epoch of a dataset
+    ///
+    /// The coordinator moves the dataset to the next epoch once every
+    /// healthy worker has reported, so all workers agree on the epoch
+    /// without tracking it locally. Reports for an epoch that already
+    /// advanced are acknowledged without effect.
+    ///
+    /// Args:
+    ///     dataset_id: Dataset identifier
+    ///     epoch: Epoch finished (default: the dataset's current epoch)
+    ///
+    /// Returns:
+    ///     EpochStatus with the quorum progress and resulting epoch
+    ///
+    /// Example:
+    ///     status = orch.advance_epoch("imagenet")
+    ///     orch.barrier(f"epoch-{status.current_epoch}", step)
+    ///     epoch = orch.current_epoch("imagenet")
+    #[pyo3(signature = (dataset_id, epoch=None))]
+    fn advance_epoch(
+        &self,
+        py: Python<'_>,
+        dataset_id: &str,
+        epoch: Option<i64>,
+    ) -> PyResult<EpochStatus> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(
+            py,
+            async move { inner.advance_epoch(dataset_id, epoch).await },
+        )
+    }
+
+    /// Awaitable form of `advance_epoch`
+    #[pyo3(signature = (dataset_id, epoch=None))]
+    fn advance_epoch_async<'py>(
+        &self,
+        py: Python<'py>,
+        dataset_id: &str,
+        epoch: Option<i64>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let dataset_id = dataset_id.to_string();
+        let inner = self.inner.clone();
+        self.runtime.future_into_py(
+            py,
+            async move { inner.advance_epoch(dataset_id, epoch).await },
+        )
+    }
+
+    /// Latest checkpoint to recover from
+    ///
+    /// Args:
+    ///     job_id: Training job identifier (default: "")
+    ///
+    /// Returns:
+    ///     RecoveryInfo with the checkpoint, resume step and epoch and this
+    ///     worker's shard assignments, or None if no checkpoint exists
+    ///
+    /// Example:
+    ///     info = orch.get_latest_checkpoint()
+    ///     if info is not None:
+    ///         model.load_state_dict(load(info.checkpoint_id))
+    ///         start_step = info.resume_step
+    #[pyo3(signature = (job_id=""))]
+    fn get_latest_checkpoint(
+        &self,
+        py: Python<'_>,
+        job_id: &str,
+    ) -> PyResult<Option<RecoveryInfo>> {
+        let job_id = job_id.to_string();
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.get_latest_checkpoint(job_id).await })
+    }
+
+    /// Awaitable form of `get_latest_checkpoint`
+    #[pyo3(signature = (job_id=""))]
+    fn get_latest_checkpoint_async<'py>(
+        &self,
+        py: Python<'py>,
+        job_id: &str,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let job_id = job_id.to_string();
+        let inner = self.inner.clone();
+        self.runtime
+            .future_into_py(py, async move { inner.get_latest_checkpoint(job_id).await })
+    }
+
+    /// Workers registered with the coordinator, in rank order
+    ///
+    /// Returns:
+    ///     List of WorkerSnapshot
+    fn list_workers(&self, py: Python<'_>) -> PyResult<Vec<WorkerSnapshot>> {
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            let state = inner.cluster_state("Failed to list workers", 0).await?;
+            let mut workers: Vec<_> = state
+                .workers
+                .into_iter()
+                .map(WorkerSnapshot::from)
+                .collect();
+            workers.sort_by_key(|w| w.rank);
+            Ok(workers)
+        })
+    }
+
+    /// Aggregate metrics of the cluster, as served by `/api/metrics`
+    ///
+    /// Returns:
+    ///     ClusterMetrics
+    fn cluster_metrics(&self, py: Python<'_>) -> PyResult<ClusterMetrics> {
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.cluster_metrics().await })
+    }
+
+    /// Checkpoints recorded by the coordinator, most recent first
+    ///
+    /// Args:
+    ///     limit: Most checkpoints to return, all of them by default
+    ///
+    /// Returns:
+    ///     List of CoordinatorCheckpointInfo
+    #[pyo3(signature = (limit=None))]
+    fn list_checkpoints(
+        &self,
+        py: Python<'_>,
+        limit: Option<u32>,
+    ) -> PyResult<Vec<CoordinatorCheckpointInfo>> {
+        let max_checkpoints = match limit {
+            Some(0) => return Ok(Vec::new()),
+            Some(limit) => i32::try_from(limit).unwrap_or(i32::MAX),
+            None => i32::MAX,
+        };
+        let inner = self.inner.clone();
+        self.block_on(py, async move {
+            let state = inner
+                .cluster_state("Failed to list checkpoints", max_checkpoints)
+                .await?;
+            Ok(state
+                .checkpoints
+                .into_iter()
+                .map(CoordinatorCheckpointInfo::from)
+                .collect())
+        })
+    }
+
+    /// Deregister this worker from the coordinator
+    ///
+    /// Stops background heartbeats first.
+    fn deregister(&self, py: Python<'_>) -> PyResult<()> {
+        self.stop_heartbeat_task();
+        let inner = self.inner.clone();
+        self.block_on(py, async move { inner.deregister().await })
+    }
+
+    /// Stop heartbeats and the membership watch, deregister if registered,
+    /// and disconnect
+    ///
+    /// The orchestrator can't be used afterwards. Closing twice is a no-op.
+    /// In a forked child, the worker registered by the parent stays
+    /// registered and a coordinator started by `local` keeps running.
+    ///
+    /// Orchestrators still registered are closed when the interpreter
+    /// exits or they are garbage collected, giving up on deregistering
+    /// after a few seconds.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        self.close_within(py, None)
+    }
+
+    /// Connect to the coordinator
+    fn __enter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<PyRef<'py, Self>> {
+        slf.connect(py)?;
+        Ok(slf)
+    }
+
+    /// Close the orchestrator, see `close`
+    ///
+    /// If the block raised, a failure to deregister is logged instead of
+    /// hiding the original exception.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        match self.close(py) {
+            Err(e) if exc_type.is_some() => {
+                tracing::warn!(error = %e, "Failed to close orchestrator");
+                Ok(false)
+            }
+            result => result.map(|()| false),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!("TrainingOrchestrator(url='{}')", self.inner.coordinator_url)
+    }
+}
+
+impl TrainingOrchestrator {
+    fn with_runtime(
+        runtime: SharedRuntime,
+        coordinator_url: String,
+        retry: RetryConfig,
+        local: Option<LocalCoordinator>,
+    ) -> Self {
+        Self {
+            runtime,
+            inner: Arc::new(Inner {
+                coordinator_url,
+                client: Mutex::new(None),
+                closed: AtomicBool::new(false),
+                retry,
+                worker_id: Mutex::new(None),
+                registration: Mutex::new(None),
+                registered_in: AtomicU64::new(0),
+                session: AtomicI64::new(0),
+                heartbeat_interval_ms: AtomicI64::new(0),
+                progress: std::sync::Mutex::new(Progress::default()),
+                resources: std::sync::Mutex::new(ResourceCollector::new()),
+                command_handlers: std::sync::Mutex::new(HashMap::new()),
+            }),
+            heartbeat_task: std::sync::Mutex::new(None),
+            membership_task: std::sync::Mutex::new(None),
+            local: std::sync::Mutex::new(local.map(ProcessLocal::new)),
+        }
+    }
+
+    /// Run `future` to completion with the GIL released
+    fn block_on<F, T>(&self, py: Python<'_>, future: F) -> PyResult<T>
+    where
+        F: Future<Output = PyResult<T>> + Send,
+        T: Send,
+    {
+        let runtime = self.runtime.get()?;
+        py.allow_threads(|| runtime.block_on(future))
+    }
+
+    /// Close, giving up on deregistering after `deregister_timeout`
+    fn close_within(&self, py: Python<'_>, deregister_timeout: Option<Duration>) -> PyResult<()> {
+        self.stop_heartbeat_task();
+        self.stop_membership_task();
+        if self.runtime.is_closed() {
+            return Ok(());
+        }
+
+        let inner = self.inner.clone();
+        let local = self
+            .local
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .and_then(ProcessLocal::into_inner);
+        let result = self.block_on(py, async move {
+            let registered = inner.worker_id.lock().await.is_some()
+                && inner.registered_in.load(Ordering::Relaxed) == runtime::forks();
+            let result = match (registered, deregister_timeout) {
+                (false, _) => Ok(()),
+                (true, None) => inner.deregister().await,
+                (true, Some(timeout)) => tokio::time::timeout(timeout, inner.deregister())
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(PyTimeoutError::new_err("Timed out deregistering worker"))
+                    }),
+            };
+            inner.closed.store(true, Ordering::Relaxed);
+            *inner.worker_id.lock().await = None;
+            *inner.client.lock().await = None;
+            match local {
+                Some(local) => result.and(local.stop().await),
+                None => result,
+            }
+        });
+        self.runtime.close();
+        // Callbacks often reference the orchestrator, keeping it alive
+        self.inner
+            .command_handlers
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+        result
+    }
+
+    /// Have the orchestrator closed at interpreter exit, once it registers
+    fn close_at_exit_if_registered(slf: &Bound<'_, Self>) -> PyResult<()> {
+        let py = slf.py();
+        let registered = REGISTERED.get_or_try_init(py, || {
+            py.import_bound("weakref")?
+                .call_method0("WeakSet")
+                .map(Bound::unbind)
+        })?;
+        registered.bind(py).call_method1("add", (slf,))?;
+        Ok(())
+    }
+
+    /// Whether this process registered a worker that is still registered
+    fn registered_here(&self) -> bool {
+        !self.inner.closed.load(Ordering::Relaxed)
+            && self.inner.registered_in.load(Ordering::Relaxed) == runtime::forks()
+            && self
+                .inner
+                .worker_id
+                .try_lock()
+                .is_ok_and(|worker_id| worker_id.is_some())
+    }
+
+    fn stop_membership_task(&self) {
+        let task = self
+            .membership_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .and_then(ProcessLocal::into_inner);
+        if let Some(task) = task {
+            task.abort();
+        }
+    }
+
+    fn stop_heartbeat_task(&self) {
+        let task = self
+            .heartbeat_task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .and_then(ProcessLocal::into_inner);
+        if let Some(task) = task {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for TrainingOrchestrator {
+    fn drop(&mut self) {
+        // Garbage collected without being closed. Blocking on the runtime
+        // from within one would panic, so the worker is left to time out.
+        if self.registered_here() && tokio::runtime::Handle::try_current().is_err() {
+            Python::with_gil(|py| {
+                if let Err(e) = self.close_within(py, Some(IMPLICIT_CLOSE_TIMEOUT)) {
+                    tracing::warn!(error = %e, "Failed to close orchestrator");
+                }
+            });
+        }
+        self.stop_heartbeat_task();
+        self.stop_membership_task();
+    }
+}
+
+/// Metrics waiting to be sent by a `MetricsReporter`
+#[derive(Default)]
+struct MetricsBuffer {
+    points: VecDeque<coordinator::proto::MetricPoint>,
+    /// Points discarded because the buffer was full
+    dropped: u64,
+}
+
+/// State shared by a `MetricsReporter` and its background task
+struct Reporter {
+    inner: Arc<Inner>,
+    buffer: std::sync::Mutex<MetricsBuffer>,
+    /// Points buffered before the oldest are dropped
+    capacity: usize,
+    sent: AtomicU64,
+}
+
+impl Reporter {
+    fn buffer(&self) -> std::sync::MutexGuard<'_, MetricsBuffer> {
+        self.buffer.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Buffer metrics by step, also updating the heartbeat progress
+    ///
+    /// Metrics without a step are logged at the latest step seen.
+    fn record(&self, records: Vec<(Option<i64>, HashMap<String, f64>)>) {
+        let timestamp_ms = chrono::Utc::now().timestamp_millis();
+        let mut points = Vec::new();
+        {
+            let mut progress = self
+                .inner
+                .progress
+                .lock()
+                .unwrap_or_else(|e| e.into_inner());
+            for (step, metrics) in records {
+                let step = step.unwrap_or(progress.step);
+                progress.step = progress.step.max(step);
+                if let Some(&loss) = metrics.get("loss") {
+                    progress.loss = Some(loss);
+                }
+                points.extend(metrics.into_iter().map(|(name, value)| {
+                    coordinator::proto::MetricPoint {
+                        name,
+                        value,
+                        step,
+                        timestamp_ms,
+                    }
+                }));
+            }
+        }
+
+        let mut buffer = self.buffer();
+        for point in points {
+            if buffer.points.len() >= self.capacity {
+                buffer.points.pop_front();
+                buffer.dropped += 1;
+            }
+            buffer.points.push_back(point);
+        }
+    }
+
+    /// Send the buffered metrics with a heartbeat
+    ///
+    /// Metrics that fail to send go back into the buffer for the next try.
+    async fn report(&self) -> PyResult<()> {
+        let points: Vec<_> = self.buffer().points.drain(..).collect();
+        let result = self
+            .inner
+            .heartbeat(self.inner.progress_status(), &points)
+            .await;
+        match result {
+            Ok(_) => {
+                self.sent.fetch_add(points.len() as u64, Ordering::Relaxed);
+                Ok(())
+            }
+            Err(e) => {
+                let mut buffer = self.buffer();
+                // Newer points logged in the meantime are kept over these
+                for point in points.into_iter().rev() {
+                    if buffer.points.len() >= self.capacity {
+                        buffer.dropped += 1;
+                    } else {
+                        buffer.points.push_front(point);
+                    }
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Report every `interval` until aborted or the orchestrator closes
+    async fn run(self: Arc<Self>, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        // The first tick completes at once
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if self.inner.closed.load(Ordering::Relaxed) {
+                return;
+            }
+            if let Err(e) = self.report().await {
+                tracing::warn!(error = %e, "Failed to report metrics");
+            }
+        }
+    }
+}
+
+/// Batched training metrics and heartbeats, sent from a background thread
+///
+/// Logging only appends to a buffer. Every `interval` the buffered metrics
+/// are sent to the coordinator with a heartbeat carrying the worker's
+/// progress and resource usage, without taking the GIL. Metrics that fail
+/// to send are retried with the next report; when more than `capacity` are
+/// waiting, the oldest are dropped.
+///
+/// Logging a step or a "loss" also sets the progress sent by
+/// `TrainingOrchestrator.start_heartbeat`, so the reporter can replace it.
+///
+/// Example:
+///     with MetricsReporter(orch, interval=5.0) as metrics:
+///         for step, batch in enumerate(loader):
+///             loss = train_step(batch)
+///             metrics.log({"loss": loss, "samples_per_sec": rate}, step=step)
+#[pyclass]
+pub struct MetricsReporter {
+    runtime: SharedRuntime,
+    reporter: Arc<Reporter>,
+    /// Background reports, stopped by `close`
+    task: std::sync::Mutex<Option<ProcessLocal<JoinHandle<()>>>>,
+}
+
+#[pymethods]
+impl MetricsReporter {
+    /// Start reporting for a registered worker
+    ///
+    /// Args:
+    ///     orchestrator: Orchestrator the worker registered with
+    ///     interval: Seconds between reports (default: the heartbeat interval
+    ///         recommended by the coordinator at registration)
+    ///     capacity: Metrics buffered before the oldest are dropped (default: 10000)
+    ///
+    /// Raises:
+    ///     RuntimeError: If the worker is not registered
+    #[new]
+    #[pyo3(signature = (orchestrator, interval=None, capacity=10_000))]
+    fn new(
+        orchestrator: PyRef<'_, TrainingOrchestrator>,
+        interval: Option<f64>,
+        capacity: usize,
+    ) -> PyResult<Self> {
+        let inner = orchestrator.inner.clone();
+        let recommended_ms = inner.heartbeat_interval_ms.load(Ordering::Relaxed);
+        if recommended_ms <= 0 {
+            return Err(PyRuntimeError::new_err(
+                "Worker not registered. Call register_worker() first.",
+            ));
+        }
+        if capacity == 0 {
+            return Err(PyValueError::new_err("capacity must be positive"));
+        }
+        let interval = match interval {
+            Some(secs) => Duration::try_from_secs_f64(secs)
+                .ok()
+                .filter(|d| !d.is_zero())
+                .ok_or_else(|| PyValueError::new_err("interval must be positive"))?,
+            None => Duration::from_millis(recommended_ms as u64),
+        };
+
+        let runtime = SharedRuntime::new()?;
+        let reporter = Arc::new(Reporter {
+            inner,
+            buffer: std::sync::Mutex::new(MetricsBuffer::default()),
+            capacity,
+            sent: AtomicU64::new(0),
+        });
+        let task = runtime.get()?.spawn(reporter.clone().run(interval));
+        Ok(Self {
+            runtime,
+            reporter,
+            task: std::sync::Mutex::new(Some(ProcessLocal::new(task))),
+        })
+    }
+
+    /// Buffer metrics for one step
+    ///
+    /// Args:
+    ///     metrics: Values by name, e.g. {"loss": 0.42, "samples_per_sec": 1800.0}
+    ///     step: Training step (default: the latest step logged)
+    #[pyo3(signature = (metrics, step=None))]
+    fn log(&self, metrics: HashMap<String, f64>, step: Option<i64>) -> PyResult<()> {
+        self.check_open()?;
+        self.reporter.record(vec![(step, metrics)]);
+        Ok(())
+    }
+
+    /// Buffer metrics for several steps at once
+    ///
+    /// Args:
+    ///     records: List of (step, {name: value})
+    fn log_batch(&self, records: Vec<(i64, HashMap<String, f64>)>) -> PyResult<()> {
+        self.check_open()?;
+        self.reporter.record(
+            records
+                .into_iter()
+                .map(|(step, metrics)| (Some(step), metrics))
+                .collect(),
+        );
+        Ok(())
+    }
+
+    /// Send the buffered metrics now
+    ///
+    /// Raises:
+    ///     ConnectionError: If the coordinator can't be reached; the metrics
+    ///         stay buffered
+    fn flush(&self, py: Python<'_>) -> PyResult<()> {
+        let runtime = self.runtime.get()?;
+        let reporter = self.reporter.clone();
+        py.allow_threads(|| runtime.block_on(async move { reporter.report().await }))
+    }
+
+    /// Stop reporting, sending the buffered metrics first
+    ///
+    /// Safe to call more than once.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        self.stop_task();
+        if self.runtime.is_closed() {
+            return Ok(());
+        }
+        // Nothing can be sent once the orchestrator closed
+        let result = if self.reporter.inner.closed.load(Ordering::Relaxed) {
+            Ok(())
+        } else {
+            self.flush(py)
+        };
+        self.runtime.close();
+        result
+    }
+
+    /// Metrics waiting to be sent
+    #[getter]
+    fn pending(&self) -> usize {
+        self.reporter.buffer().points.len()
+    }
+
+    /// Metrics sent to the coordinator
+    #[getter]
+    fn sent(&self) -> u64 {
+        self.reporter.sent.load(Ordering::Relaxed)
+    }
+
+    /// Metrics dropped because the buffer was full
+    #[getter]
+    fn dropped(&self) -> u64 {
+        self.reporter.buffer().dropped
+    }
+
+    /// Whether background reports are running
+    #[getter]
+    fn running(&self) -> bool {
+        self.task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .and_then(ProcessLocal::get)
+            .is_some_and(|task| !task.is_finished())
+    }
+
+    fn __enter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    /// Close the reporter, see `close`
+    ///
+    /// If the block raised, a failure to send is logged instead of hiding
+    /// the original exception.
+    #[pyo3(signature = (exc_type, _exc_value, _traceback))]
+    fn __exit__(
+        &self,
+        py: Python<'_>,
+        exc_type: Option<&Bound<'_, PyAny>>,
+        _exc_value: Option<&Bound<'_, PyAny>>,
+        _traceback: Option<&Bound<'_, PyAny>>,
+    ) -> PyResult<bool> {
+        match self.close(py) {
+            Err(e) if exc_type.is_some() => {
+                tracing::warn!(error = %e, "Failed to send metrics");
+                Ok(false)
+            }
+            result => result.map(|()| false),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MetricsReporter(pending={}, sent={}, dropped={})",
+            self.pending(),
+            self.sent(),
+            self.dropped()
+        )
+    }
+}
+
+impl MetricsReporter {
+    fn check_open(&self) -> PyResult<()> {
+        if self.runtime.is_closed() {
+            return Err(PyRuntimeError::new_err("Already closed"));
+        }
+        Ok(())
+    }
+
+    fn stop_task(&self) {
+        let task = self
+            .task
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .and_then(ProcessLocal::into_inner);
+        if let Some(task) = task {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for MetricsReporter {
+    fn drop(&mut self) {
+        self.stop_task();
     }
 }
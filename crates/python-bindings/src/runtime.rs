@@ -0,0 +1,247 @@
+//! Tokio runtime shared by the Python objects
+//!
+//! Orchestrators and checkpoint managers run their futures on one
+//! multi-threaded runtime, built on first use and shut down when the
+//! interpreter exits. Each object reaches it through a [`SharedRuntime`],
+//! which raises once the object is closed; closing stops the object's own
+//! tasks and leaves the runtime to the others. Awaitables returned by the
+//! `*_async` methods run on the same runtime, through [`SharedTokio`], rather
+//! than on the separate one `pyo3_async_runtimes::tokio` would start.
+//!
+//! A forked child, such as a PyTorch DataLoader worker, inherits the
+//! runtime's memory but none of its threads. An `os.register_at_fork` hook
+//! counts forks so the child can tell, and builds a runtime of its own the
+//! next time one is needed, for blocking calls and awaitables alike. Values
+//! tied to the parent's threads are wrapped in [`ProcessLocal`] and leaked in
+//! the child rather than dropped.
+
+use pyo3::exceptions::PyRuntimeError;
+use pyo3::prelude::*;
+use pyo3::types::IntoPyDict;
+use pyo3_async_runtimes::generic::{ContextExt, Runtime as AsyncRuntime};
+use pyo3_async_runtimes::TaskLocals;
+use std::future::Future;
+use std::mem::ManuallyDrop;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+
+/// How long shutting down at exit waits for background tasks to stop
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Runtime shared by the objects in this process, built on first use
+static SHARED: Mutex<Option<ProcessLocal<Arc<Runtime>>>> = Mutex::new(None);
+
+/// Set once the shared runtime was shut down at exit
+static EXITED: AtomicBool = AtomicBool::new(false);
+
+/// Forks this process descends from, bumped in each child
+static FORKS: AtomicU64 = AtomicU64::new(0);
+
+/// Forks this process descends from, 0 in the process that imported us
+pub(crate) fn forks() -> u64 {
+    FORKS.load(Ordering::Relaxed)
+}
+
+#[pyfunction]
+fn after_fork_in_child() {
+    FORKS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count forks with an `os.register_at_fork` hook
+pub(crate) fn register_fork_hook(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+    let os = py.import_bound("os")?;
+    // Missing on Windows, which doesn't fork
+    if !os.hasattr("register_at_fork")? {
+        return Ok(());
+    }
+    let hook = wrap_pyfunction!(after_fork_in_child, m)?;
+    let kwargs = [("after_in_child", hook)].into_py_dict_bound(py);
+    os.call_method("register_at_fork", (), Some(&kwargs))?;
+    Ok(())
+}
+
+/// A value that only works in the process that created it, e.g. a task
+/// running on a runtime's threads
+///
+/// In a forked child the value is unusable, and dropping it could wait on
+/// threads that don't exist there, so it is leaked instead.
+pub(crate) struct ProcessLocal<T> {
+    value: ManuallyDrop<T>,
+    forks: u64,
+}
+
+impl<T> ProcessLocal<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Self {
+            value: ManuallyDrop::new(value),
+            forks: forks(),
+        }
+    }
+
+    /// Whether the value was created in this process
+    pub(crate) fn is_local(&self) -> bool {
+        self.forks == forks()
+    }
+
+    /// The value, if it was created in this process
+    pub(crate) fn get(&self) -> Option<&T> {
+        self.is_local().then_some(&*self.value)
+    }
+
+    /// Take the value, if it was created in this process
+    pub(crate) fn into_inner(self) -> Option<T> {
+        let mut this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never dropped, so the value is taken only once
+        this.is_local()
+            .then(|| unsafe { ManuallyDrop::take(&mut this.value) })
+    }
+}
+
+impl<T> Drop for ProcessLocal<T> {
+    fn drop(&mut self) {
+        if self.is_local() {
+            // SAFETY: the value isn't used after drop
+            unsafe { ManuallyDrop::drop(&mut self.value) }
+        }
+    }
+}
+
+/// Shut the shared runtime down when the interpreter exits
+///
+/// Registered before the hooks closing objects, so it runs after them.
+pub(crate) fn register_exit_hook(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let hook = wrap_pyfunction!(shutdown_at_exit, m)?;
+    m.py()
+        .import_bound("atexit")?
+        .call_method1("register", (hook,))?;
+    Ok(())
+}
+
+#[pyfunction]
+fn shutdown_at_exit(py: Python<'_>) {
+    EXITED.store(true, Ordering::Relaxed);
+    let runtime = SHARED.lock().unwrap_or_else(|e| e.into_inner()).take();
+    // Calls still running keep the runtime alive until they return
+    if let Some(runtime) = runtime
+        .and_then(ProcessLocal::into_inner)
+        .and_then(|r| Arc::try_unwrap(r).ok())
+    {
+        py.allow_threads(|| runtime.shutdown_timeout(SHUTDOWN_TIMEOUT));
+    }
+}
+
+/// The shared runtime, built on first use in each process
+fn shared() -> PyResult<Arc<Runtime>> {
+    if EXITED.load(Ordering::Relaxed) {
+        return Err(PyRuntimeError::new_err("Interpreter is shutting down"));
+    }
+    let mut shared = SHARED.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(runtime) = shared.as_ref().and_then(ProcessLocal::get) {
+        return Ok(runtime.clone());
+    }
+    let fresh = new_runtime()?;
+    *shared = Some(ProcessLocal::new(fresh.clone()));
+    Ok(fresh)
+}
+
+/// The shared runtime as seen by one object, until the object is closed
+pub(crate) struct SharedRuntime {
+    closed: AtomicBool,
+}
+
+impl SharedRuntime {
+    pub(crate) fn new() -> PyResult<Self> {
+        shared()?;
+        Ok(Self {
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    /// The runtime, or an error once closed
+    ///
+    /// After a fork, the first call in the child starts a new runtime.
+    pub(crate) fn get(&self) -> PyResult<Arc<Runtime>> {
+        if self.is_closed() {
+            return Err(PyRuntimeError::new_err("Already closed"));
+        }
+        shared()
+    }
+
+    /// Run `future` on the runtime, returning a Python awaitable
+    ///
+    /// Raises once closed, like [`get`](Self::get), instead of starting the
+    /// call.
+    pub(crate) fn future_into_py<'py, F, T>(
+        &self,
+        py: Python<'py>,
+        future: F,
+    ) -> PyResult<Bound<'py, PyAny>>
+    where
+        F: Future<Output = PyResult<T>> + Send + 'static,
+        T: IntoPy<PyObject>,
+    {
+        let runtime = self.get()?;
+        // `SharedTokio` spawns onto the runtime entered here
+        let _entered = runtime.enter();
+        pyo3_async_runtimes::generic::future_into_py::<SharedTokio, _, _>(py, future)
+    }
+
+    /// Refuse further calls, the runtime keeps serving other objects
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::Relaxed);
+    }
+
+    pub(crate) fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+}
+
+fn new_runtime() -> PyResult<Arc<Runtime>> {
+    Runtime::new()
+        .map(Arc::new)
+        .map_err(|e| PyRuntimeError::new_err(format!("Failed to create async runtime: {}", e)))
+}
+
+tokio::task_local! {
+    /// Event loop and context of the Python call awaiting the current task
+    static TASK_LOCALS: TaskLocals;
+}
+
+/// Drives awaitables on the shared runtime
+///
+/// Spawns onto the runtime of the calling task, which
+/// [`SharedRuntime::future_into_py`] enters, so a forked child uses the
+/// runtime it built for itself.
+struct SharedTokio;
+
+impl AsyncRuntime for SharedTokio {
+    type JoinError = tokio::task::JoinError;
+    type JoinHandle = JoinHandle<()>;
+
+    fn spawn<F>(fut: F) -> Self::JoinHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(fut)
+    }
+}
+
+impl ContextExt for SharedTokio {
+    fn scope<F, R>(locals: TaskLocals, fut: F) -> Pin<Box<dyn Future<Output = R> + Send>>
+    where
+        F: Future<Output = R> + Send + 'static,
+    {
+        Box::pin(TASK_LOCALS.scope(locals, fut))
+    }
+
+    fn get_task_locals() -> Option<TaskLocals> {
+        TASK_LOCALS
+            .try_with(|locals| Python::with_gil(|py| locals.clone_ref(py)))
+            .ok()
+    }
+}
@@ -0,0 +1,102 @@
+//! Time sources
+//!
+//! Heartbeat deadlines, rank settling, epoch timing and rate limits read the
+//! time through a [`Clock`], so tests can move time forward with a
+//! [`MockClock`] instead of sleeping.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+/// A source of monotonic and wall-clock time
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    /// Monotonic time, for measuring durations
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time, for timestamps
+    fn utc_now(&self) -> DateTime<Utc>;
+}
+
+/// A clock shared between components
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    /// The system clock as a [`SharedClock`]
+    pub fn shared() -> SharedClock {
+        Arc::new(SystemClock)
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// A clock that only moves when advanced
+///
+/// # Example
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use runtime_core::clock::{Clock, MockClock};
+///
+/// let clock = Arc::new(MockClock::new());
+/// let start = clock.now();
+/// clock.advance(Duration::from_secs(30));
+/// assert_eq!(clock.now() - start, Duration::from_secs(30));
+/// ```
+#[derive(Debug)]
+pub struct MockClock {
+    start: Instant,
+    start_utc: DateTime<Utc>,
+    elapsed: Mutex<Duration>,
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockClock {
+    /// Create a clock stopped at the current time
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_utc: Utc::now(),
+            elapsed: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, duration: Duration) {
+        *self.elapsed.lock() += duration;
+    }
+
+    /// Time the clock has been advanced by since creation
+    pub fn elapsed(&self) -> Duration {
+        *self.elapsed.lock()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed()
+    }
+
+    fn utc_now(&self) -> DateTime<Utc> {
+        self.start_utc + chrono::Duration::from_std(self.elapsed()).unwrap_or(chrono::Duration::MAX)
+    }
+}
@@ -0,0 +1,223 @@
+//! Versioned envelopes for persisted state
+//!
+//! Coordinator state written to disk is wrapped in an [`Envelope`] naming
+//! what it holds and the schema version it was written with:
+//!
+//! ```json
+//! {"kind": "shard_manager", "schema_version": 2, "state": {...}}
+//! ```
+//!
+//! When a [`Versioned`] type changes shape it bumps
+//! [`SCHEMA_VERSION`](Versioned::SCHEMA_VERSION) and teaches
+//! [`migrate`](Versioned::migrate) to upgrade the old JSON, so state written
+//! before an upgrade still loads after it. State written before envelopes
+//! existed is read as version 1.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{Error, Result};
+
+/// State that can be persisted in an [`Envelope`]
+///
+/// # Example
+///
+/// ```
+/// use runtime_core::envelope::{self, Versioned};
+/// use runtime_core::Result;
+/// use serde::{Deserialize, Serialize};
+/// use serde_json::Value;
+///
+/// #[derive(Debug, PartialEq, Serialize, Deserialize)]
+/// struct RingState {
+///     nodes: Vec<String>,
+///     virtual_nodes: usize,
+/// }
+///
+/// impl Versioned for RingState {
+///     const KIND: &'static str = "ring";
+///     const SCHEMA_VERSION: u32 = 2;
+///
+///     fn migrate(from: u32, mut state: Value) -> Result<Value> {
+///         // Version 2 added `virtual_nodes`
+///         if from == 1 {
+///             state["virtual_nodes"] = 150.into();
+///         }
+///         Ok(state)
+///     }
+/// }
+///
+/// let restored: RingState = envelope::decode(br#"{"nodes": ["a"]}"#).unwrap();
+/// assert_eq!(restored.virtual_nodes, 150);
+///
+/// let bytes = envelope::encode(&restored).unwrap();
+/// assert_eq!(envelope::decode::<RingState>(&bytes).unwrap(), restored);
+/// ```
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Name of the state, checked when decoding
+    const KIND: &'static str;
+
+    /// Schema version written by this build
+    const SCHEMA_VERSION: u32;
+
+    /// Upgrade state written with schema version `from` to `from + 1`
+    ///
+    /// Called once per version between the stored one and
+    /// [`SCHEMA_VERSION`](Versioned::SCHEMA_VERSION).
+    fn migrate(from: u32, state: Value) -> Result<Value> {
+        let _ = state;
+        Err(Error::serialization(format!(
+            "No migration for {} from schema version {}",
+            Self::KIND,
+            from
+        )))
+    }
+}
+
+/// Persisted state with its kind and schema version
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    /// What the state is, e.g. `shard_manager`
+    pub kind: String,
+    /// Schema version the state was written with
+    pub schema_version: u32,
+    /// The state itself
+    pub state: T,
+}
+
+impl<T: Versioned> Envelope<T> {
+    /// Wrap state at the current schema version
+    pub fn new(state: T) -> Self {
+        Self {
+            kind: T::KIND.to_string(),
+            schema_version: T::SCHEMA_VERSION,
+            state,
+        }
+    }
+}
+
+impl Envelope<Value> {
+    /// Read an envelope without decoding its state
+    ///
+    /// JSON that is not an envelope is taken as bare state at version 1.
+    pub fn from_slice(bytes: &[u8]) -> Result<Self> {
+        let value: Value = serde_json::from_slice(bytes)?;
+        let is_envelope = value.get("schema_version").is_some() && value.get("state").is_some();
+        if is_envelope {
+            Ok(serde_json::from_value(value)?)
+        } else {
+            Ok(Self {
+                kind: String::new(),
+                schema_version: 1,
+                state: value,
+            })
+        }
+    }
+
+    /// Migrate the state to the current schema version of `T` and decode it
+    pub fn upgrade<T: Versioned>(self) -> Result<T> {
+        if !self.kind.is_empty() && self.kind != T::KIND {
+            return Err(Error::serialization(format!(
+                "Expected {} state, found {}",
+                T::KIND,
+                self.kind
+            )));
+        }
+        if self.schema_version > T::SCHEMA_VERSION {
+            return Err(Error::serialization(format!(
+                "{} state has schema version {}, newer than supported version {}",
+                T::KIND,
+                self.schema_version,
+                T::SCHEMA_VERSION
+            )));
+        }
+
+        let mut state = self.state;
+        for from in self.schema_version..T::SCHEMA_VERSION {
+            state = T::migrate(from, state)?;
+        }
+        Ok(serde_json::from_value(state)?)
+    }
+}
+
+/// Serialize state in an envelope at the current schema version
+pub fn encode<T: Versioned>(state: &T) -> Result<Vec<u8>> {
+    let envelope = Envelope {
+        kind: T::KIND.to_string(),
+        schema_version: T::SCHEMA_VERSION,
+        state,
+    };
+    Ok(serde_json::to_vec(&envelope)?)
+}
+
+/// Decode enveloped or bare state, migrating it to the current schema version
+pub fn decode<T: Versioned>(bytes: &[u8]) -> Result<T> {
+    Envelope::from_slice(bytes)?.upgrade()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Counter {
+        count: u64,
+        label: String,
+    }
+
+    impl Versioned for Counter {
+        const KIND: &'static str = "counter";
+        const SCHEMA_VERSION: u32 = 3;
+
+        fn migrate(from: u32, mut state: Value) -> Result<Value> {
+            match from {
+                // Version 2 renamed `n` to `count`
+                1 => {
+                    state["count"] = state["n"].take();
+                    Ok(state)
+                }
+                // Version 3 added `label`
+                2 => {
+                    state["label"] = "default".into();
+                    Ok(state)
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_round_trip_and_migrate() {
+        let counter = Counter {
+            count: 5,
+            label: "steps".to_string(),
+        };
+        let bytes = encode(&counter).unwrap();
+        let envelope = Envelope::from_slice(&bytes).unwrap();
+        assert_eq!(envelope.kind, "counter");
+        assert_eq!(envelope.schema_version, 3);
+        assert_eq!(decode::<Counter>(&bytes).unwrap(), counter);
+
+        let v2 = br#"{"kind": "counter", "schema_version": 2, "state": {"count": 7}}"#;
+        let migrated: Counter = decode(v2).unwrap();
+        assert_eq!(migrated.count, 7);
+        assert_eq!(migrated.label, "default");
+
+        // Written before envelopes existed
+        let migrated: Counter = decode(br#"{"n": 9}"#).unwrap();
+        assert_eq!(migrated.count, 9);
+    }
+
+    #[test]
+    fn test_rejects_newer_or_other_state() {
+        let newer = br#"{"kind": "counter", "schema_version": 4, "state": {}}"#;
+        assert!(matches!(
+            decode::<Counter>(newer),
+            Err(Error::Serialization { .. })
+        ));
+
+        let other = br#"{"kind": "ring", "schema_version": 1, "state": {}}"#;
+        assert!(decode::<Counter>(other).is_err());
+    }
+}
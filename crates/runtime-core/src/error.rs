@@ -1,10 +1,35 @@
 //! Error types for the distributed training runtime
+//!
+//! Errors keep what they are about in fields (`path`, `worker_id`,
+//! `checkpoint_id`, ...) and the error that caused them as their
+//! [`source`](std::error::Error::source), instead of formatting both into a
+//! message. [`Error::context`] lists the fields for logs and gRPC clients.
 
 use thiserror::Error;
 
 /// Result type alias using the runtime Error
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// An underlying error kept as the source of an [`Error`]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync + 'static>;
+
+/// `" at {path}: {source}"`, whichever parts are set
+///
+/// The source is part of the message so log lines that only print the error
+/// stay complete.
+fn detail(path: &Option<String>, source: &Option<BoxError>) -> String {
+    let mut detail = String::new();
+    if let Some(path) = path {
+        detail.push_str(" at ");
+        detail.push_str(path);
+    }
+    if let Some(source) = source {
+        detail.push_str(": ");
+        detail.push_str(&source.to_string());
+    }
+    detail
+}
+
 /// Core error type for the distributed training runtime
 #[derive(Error, Debug)]
 pub enum Error {
@@ -21,6 +46,13 @@ pub enum Error {
         last_seen_ms: u64,
     },
 
+    #[error("Stale session for worker {worker_id}: {session} (current {current})")]
+    StaleSession {
+        worker_id: String,
+        session: u64,
+        current: u64,
+    },
+
     #[error("Worker in invalid state: expected {expected:?}, got {actual:?}")]
     InvalidWorkerState {
         expected: Vec<String>,
@@ -31,8 +63,13 @@ pub enum Error {
     #[error("Checkpoint not found: {checkpoint_id}")]
     CheckpointNotFound { checkpoint_id: String },
 
-    #[error("Checkpoint write failed: {message}")]
-    CheckpointWriteFailed { message: String },
+    #[error("Checkpoint write failed: {message}{}", detail(.path, .source))]
+    CheckpointWriteFailed {
+        message: String,
+        checkpoint_id: Option<String>,
+        path: Option<String>,
+        source: Option<BoxError>,
+    },
 
     #[error("Checkpoint corrupted: {checkpoint_id} - {reason}")]
     CheckpointCorrupted {
@@ -54,8 +91,12 @@ pub enum Error {
     InvalidShardConfig { message: String },
 
     // Storage errors
-    #[error("Storage error: {message}")]
-    Storage { message: String },
+    #[error("Storage error: {message}{}", detail(.path, .source))]
+    Storage {
+        message: String,
+        path: Option<String>,
+        source: Option<BoxError>,
+    },
 
     #[error("Storage backend not available: {backend}")]
     StorageUnavailable { backend: String },
@@ -77,13 +118,23 @@ pub enum Error {
     #[error("Invalid configuration: {message}")]
     InvalidConfig { message: String },
 
+    #[error("Invalid {kind} ID {id:?}: {reason}")]
+    InvalidId {
+        kind: &'static str,
+        id: String,
+        reason: String,
+    },
+
     // I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
 
     // Serialization errors
-    #[error("Serialization error: {0}")]
-    Serialization(String),
+    #[error("Serialization error: {message}{}", detail(&None, .source))]
+    Serialization {
+        message: String,
+        source: Option<BoxError>,
+    },
 
     // gRPC errors
     #[error("gRPC error: {0}")]
@@ -102,19 +153,318 @@ pub enum Error {
     ChannelClosed { channel: String },
 }
 
+/// Stable identifier of an error kind
+///
+/// Sent to gRPC clients in the [`ERROR_CODE_METADATA`] header so they can
+/// branch on the kind of failure instead of parsing messages. The string
+/// forms never change once released.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    WorkerNotFound,
+    WorkerAlreadyRegistered,
+    WorkerHeartbeatTimeout,
+    StaleSession,
+    InvalidWorkerState,
+    CheckpointNotFound,
+    CheckpointWriteFailed,
+    CheckpointCorrupted,
+    NoCheckpointForRecovery,
+    DatasetNotFound,
+    ShardNotFound,
+    InvalidShardConfig,
+    Storage,
+    StorageUnavailable,
+    StoragePathNotFound,
+    BarrierTimeout,
+    BarrierExists,
+    CoordinatorUnavailable,
+    InvalidConfig,
+    InvalidId,
+    Io,
+    Serialization,
+    Grpc,
+    Internal,
+    Timeout,
+    ChannelClosed,
+}
+
+/// gRPC metadata key carrying the [`ErrorCode`] of a failed call
+pub const ERROR_CODE_METADATA: &str = "error-code";
+
+/// gRPC metadata key carrying the suggested retry delay in milliseconds
+pub const RETRY_PUSHBACK_METADATA: &str = "grpc-retry-pushback-ms";
+
+const ERROR_CODES: [(ErrorCode, &str); 26] = [
+    (ErrorCode::WorkerNotFound, "WORKER_NOT_FOUND"),
+    (
+        ErrorCode::WorkerAlreadyRegistered,
+        "WORKER_ALREADY_REGISTERED",
+    ),
+    (
+        ErrorCode::WorkerHeartbeatTimeout,
+        "WORKER_HEARTBEAT_TIMEOUT",
+    ),
+    (ErrorCode::StaleSession, "STALE_SESSION"),
+    (ErrorCode::InvalidWorkerState, "INVALID_WORKER_STATE"),
+    (ErrorCode::CheckpointNotFound, "CHECKPOINT_NOT_FOUND"),
+    (ErrorCode::CheckpointWriteFailed, "CHECKPOINT_WRITE_FAILED"),
+    (ErrorCode::CheckpointCorrupted, "CHECKPOINT_CORRUPTED"),
+    (
+        ErrorCode::NoCheckpointForRecovery,
+        "NO_CHECKPOINT_FOR_RECOVERY",
+    ),
+    (ErrorCode::DatasetNotFound, "DATASET_NOT_FOUND"),
+    (ErrorCode::ShardNotFound, "SHARD_NOT_FOUND"),
+    (ErrorCode::InvalidShardConfig, "INVALID_SHARD_CONFIG"),
+    (ErrorCode::Storage, "STORAGE"),
+    (ErrorCode::StorageUnavailable, "STORAGE_UNAVAILABLE"),
+    (ErrorCode::StoragePathNotFound, "STORAGE_PATH_NOT_FOUND"),
+    (ErrorCode::BarrierTimeout, "BARRIER_TIMEOUT"),
+    (ErrorCode::BarrierExists, "BARRIER_EXISTS"),
+    (ErrorCode::CoordinatorUnavailable, "COORDINATOR_UNAVAILABLE"),
+    (ErrorCode::InvalidConfig, "INVALID_CONFIG"),
+    (ErrorCode::InvalidId, "INVALID_ID"),
+    (ErrorCode::Io, "IO"),
+    (ErrorCode::Serialization, "SERIALIZATION"),
+    (ErrorCode::Grpc, "GRPC"),
+    (ErrorCode::Internal, "INTERNAL"),
+    (ErrorCode::Timeout, "TIMEOUT"),
+    (ErrorCode::ChannelClosed, "CHANNEL_CLOSED"),
+];
+
+impl ErrorCode {
+    /// The stable string form, e.g. `WORKER_NOT_FOUND`
+    pub fn as_str(&self) -> &'static str {
+        ERROR_CODES
+            .iter()
+            .find(|(code, _)| code == self)
+            .map(|(_, name)| *name)
+            .expect("every error code has a name")
+    }
+
+    /// The error code attached to a gRPC status, if any
+    pub fn from_status(status: &tonic::Status) -> Option<Self> {
+        status
+            .metadata()
+            .get(ERROR_CODE_METADATA)?
+            .to_str()
+            .ok()?
+            .parse()
+            .ok()
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for ErrorCode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ERROR_CODES
+            .iter()
+            .find(|(_, name)| *name == s)
+            .map(|(code, _)| *code)
+            .ok_or_else(|| Error::Internal {
+                message: format!("unknown error code {:?}", s),
+            })
+    }
+}
+
 impl Error {
+    /// A storage error, see [`with_path`](Self::with_path) and
+    /// [`with_source`](Self::with_source) to say more
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::error::Error as _;
+    /// use runtime_core::Error;
+    ///
+    /// let io = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+    /// let err = Error::storage("Failed to read file")
+    ///     .with_path("/ckpt/step-10.bin")
+    ///     .with_source(io);
+    /// assert_eq!(err.context(), [("path", "/ckpt/step-10.bin")]);
+    /// assert!(err.source().is_some());
+    /// ```
+    pub fn storage(message: impl Into<String>) -> Self {
+        Error::Storage {
+            message: message.into(),
+            path: None,
+            source: None,
+        }
+    }
+
+    /// A failed checkpoint write
+    pub fn checkpoint_write_failed(message: impl Into<String>) -> Self {
+        Error::CheckpointWriteFailed {
+            message: message.into(),
+            checkpoint_id: None,
+            path: None,
+            source: None,
+        }
+    }
+
+    /// A serialization error
+    pub fn serialization(message: impl Into<String>) -> Self {
+        Error::Serialization {
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    /// Record the path the error is about, if the error has a path field
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        if let Error::Storage { path: slot, .. } | Error::CheckpointWriteFailed { path: slot, .. } =
+            &mut self
+        {
+            *slot = Some(path.into());
+        }
+        self
+    }
+
+    /// Record the checkpoint the error is about, if the error has a
+    /// checkpoint field
+    pub fn with_checkpoint_id(mut self, checkpoint_id: impl Into<String>) -> Self {
+        if let Error::CheckpointWriteFailed {
+            checkpoint_id: slot,
+            ..
+        } = &mut self
+        {
+            *slot = Some(checkpoint_id.into());
+        }
+        self
+    }
+
+    /// Keep the error that caused this one, if the error can hold a source
+    pub fn with_source(mut self, source: impl Into<BoxError>) -> Self {
+        if let Error::Storage { source: slot, .. }
+        | Error::CheckpointWriteFailed { source: slot, .. }
+        | Error::Serialization { source: slot, .. } = &mut self
+        {
+            *slot = Some(source.into());
+        }
+        self
+    }
+
+    /// What the error is about, as `(field, value)` pairs
+    ///
+    /// Covers `worker_id`, `checkpoint_id`, `dataset_id`, `barrier_id` and
+    /// `path`. Sent to gRPC clients as `error-<field>` metadata.
+    pub fn context(&self) -> Vec<(&'static str, &str)> {
+        let mut context = Vec::new();
+        match self {
+            Error::WorkerNotFound { worker_id }
+            | Error::WorkerAlreadyRegistered { worker_id }
+            | Error::WorkerHeartbeatTimeout { worker_id, .. }
+            | Error::StaleSession { worker_id, .. } => {
+                context.push(("worker_id", worker_id.as_str()))
+            }
+            Error::CheckpointNotFound { checkpoint_id }
+            | Error::CheckpointCorrupted { checkpoint_id, .. } => {
+                context.push(("checkpoint_id", checkpoint_id.as_str()))
+            }
+            Error::CheckpointWriteFailed {
+                checkpoint_id,
+                path,
+                ..
+            } => {
+                context.extend(checkpoint_id.as_deref().map(|id| ("checkpoint_id", id)));
+                context.extend(path.as_deref().map(|path| ("path", path)));
+            }
+            Error::DatasetNotFound { dataset_id } | Error::ShardNotFound { dataset_id, .. } => {
+                context.push(("dataset_id", dataset_id.as_str()))
+            }
+            Error::Storage { path, .. } => {
+                context.extend(path.as_deref().map(|path| ("path", path)))
+            }
+            Error::StoragePathNotFound { path } => context.push(("path", path.as_str())),
+            Error::BarrierTimeout { barrier_id, .. } | Error::BarrierExists { barrier_id } => {
+                context.push(("barrier_id", barrier_id.as_str()))
+            }
+            _ => {}
+        }
+        context
+    }
+
+    /// The stable code of this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::WorkerNotFound { .. } => ErrorCode::WorkerNotFound,
+            Error::WorkerAlreadyRegistered { .. } => ErrorCode::WorkerAlreadyRegistered,
+            Error::WorkerHeartbeatTimeout { .. } => ErrorCode::WorkerHeartbeatTimeout,
+            Error::StaleSession { .. } => ErrorCode::StaleSession,
+            Error::InvalidWorkerState { .. } => ErrorCode::InvalidWorkerState,
+            Error::CheckpointNotFound { .. } => ErrorCode::CheckpointNotFound,
+            Error::CheckpointWriteFailed { .. } => ErrorCode::CheckpointWriteFailed,
+            Error::CheckpointCorrupted { .. } => ErrorCode::CheckpointCorrupted,
+            Error::NoCheckpointForRecovery => ErrorCode::NoCheckpointForRecovery,
+            Error::DatasetNotFound { .. } => ErrorCode::DatasetNotFound,
+            Error::ShardNotFound { .. } => ErrorCode::ShardNotFound,
+            Error::InvalidShardConfig { .. } => ErrorCode::InvalidShardConfig,
+            Error::Storage { .. } => ErrorCode::Storage,
+            Error::StorageUnavailable { .. } => ErrorCode::StorageUnavailable,
+            Error::StoragePathNotFound { .. } => ErrorCode::StoragePathNotFound,
+            Error::BarrierTimeout { .. } => ErrorCode::BarrierTimeout,
+            Error::BarrierExists { .. } => ErrorCode::BarrierExists,
+            Error::CoordinatorUnavailable { .. } => ErrorCode::CoordinatorUnavailable,
+            Error::InvalidConfig { .. } => ErrorCode::InvalidConfig,
+            Error::InvalidId { .. } => ErrorCode::InvalidId,
+            Error::Io(_) => ErrorCode::Io,
+            Error::Serialization { .. } => ErrorCode::Serialization,
+            Error::Grpc(_) => ErrorCode::Grpc,
+            Error::Internal { .. } => ErrorCode::Internal,
+            Error::Timeout { .. } => ErrorCode::Timeout,
+            Error::ChannelClosed { .. } => ErrorCode::ChannelClosed,
+        }
+    }
+
     /// Returns true if this error is retryable
+    ///
+    /// Retryable errors are transient: the same call may succeed later
+    /// without any change on the caller's side.
     pub fn is_retryable(&self) -> bool {
-        matches!(
-            self,
+        match self {
             Error::WorkerHeartbeatTimeout { .. }
-                | Error::Storage { .. }
-                | Error::StorageUnavailable { .. }
-                | Error::CoordinatorUnavailable { .. }
-                | Error::BarrierTimeout { .. }
-                | Error::Timeout { .. }
-                | Error::Grpc(_)
-        )
+            | Error::Storage { .. }
+            | Error::StorageUnavailable { .. }
+            | Error::CoordinatorUnavailable { .. }
+            | Error::BarrierTimeout { .. }
+            | Error::Timeout { .. }
+            | Error::Grpc(_) => true,
+            Error::Io(e) => matches!(
+                e.kind(),
+                std::io::ErrorKind::Interrupted
+                    | std::io::ErrorKind::TimedOut
+                    | std::io::ErrorKind::WouldBlock
+                    | std::io::ErrorKind::ConnectionRefused
+                    | std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+            ),
+            Error::WorkerNotFound { .. }
+            | Error::WorkerAlreadyRegistered { .. }
+            | Error::StaleSession { .. }
+            | Error::InvalidWorkerState { .. }
+            | Error::CheckpointNotFound { .. }
+            | Error::CheckpointWriteFailed { .. }
+            | Error::CheckpointCorrupted { .. }
+            | Error::NoCheckpointForRecovery
+            | Error::DatasetNotFound { .. }
+            | Error::ShardNotFound { .. }
+            | Error::InvalidShardConfig { .. }
+            | Error::StoragePathNotFound { .. }
+            | Error::BarrierExists { .. }
+            | Error::InvalidConfig { .. }
+            | Error::InvalidId { .. }
+            | Error::Serialization { .. }
+            | Error::Internal { .. }
+            | Error::ChannelClosed { .. } => false,
+        }
     }
 
     /// Returns true if this error indicates a fatal condition
@@ -137,14 +487,80 @@ impl Error {
             Error::BarrierTimeout { .. } => Some(500),
             Error::Timeout { .. } => Some(1000),
             Error::Grpc(_) => Some(100),
+            Error::Io(_) if self.is_retryable() => Some(100),
             _ => None,
         }
     }
+
+    /// The gRPC status code matching this error
+    pub fn grpc_code(&self) -> tonic::Code {
+        use tonic::Code;
+
+        match self {
+            Error::WorkerNotFound { .. }
+            | Error::CheckpointNotFound { .. }
+            | Error::NoCheckpointForRecovery
+            | Error::DatasetNotFound { .. }
+            | Error::ShardNotFound { .. }
+            | Error::StoragePathNotFound { .. } => Code::NotFound,
+            Error::WorkerAlreadyRegistered { .. } | Error::BarrierExists { .. } => {
+                Code::AlreadyExists
+            }
+            Error::StaleSession { .. } | Error::InvalidWorkerState { .. } => {
+                Code::FailedPrecondition
+            }
+            Error::InvalidShardConfig { .. }
+            | Error::InvalidConfig { .. }
+            | Error::InvalidId { .. } => Code::InvalidArgument,
+            Error::CheckpointCorrupted { .. } => Code::DataLoss,
+            Error::WorkerHeartbeatTimeout { .. }
+            | Error::BarrierTimeout { .. }
+            | Error::Timeout { .. } => Code::DeadlineExceeded,
+            Error::StorageUnavailable { .. }
+            | Error::CoordinatorUnavailable { .. }
+            | Error::Storage { .. }
+            | Error::Grpc(_) => Code::Unavailable,
+            Error::Io(_) if self.is_retryable() => Code::Unavailable,
+            Error::CheckpointWriteFailed { .. }
+            | Error::Io(_)
+            | Error::Serialization { .. }
+            | Error::Internal { .. }
+            | Error::ChannelClosed { .. } => Code::Internal,
+        }
+    }
+}
+
+/// Converts to a status carrying the error's code in [`ERROR_CODE_METADATA`],
+/// for retryable errors its delay hint in [`RETRY_PUSHBACK_METADATA`], and its
+/// [`context`](Error::context) as `error-worker-id`, `error-path`, ...
+impl From<Error> for tonic::Status {
+    fn from(error: Error) -> Self {
+        let mut status = tonic::Status::new(error.grpc_code(), error.to_string());
+        let metadata = status.metadata_mut();
+        metadata.insert(
+            ERROR_CODE_METADATA,
+            tonic::metadata::MetadataValue::from_static(error.code().as_str()),
+        );
+        if let Some(delay_ms) = error.retry_delay_hint_ms() {
+            metadata.insert(RETRY_PUSHBACK_METADATA, delay_ms.into());
+        }
+        for (field, value) in error.context() {
+            let key = format!("error-{}", field.replace('_', "-"));
+            // Values that can't be sent as ASCII headers stay in the message
+            if let (Ok(key), Ok(value)) = (
+                tonic::metadata::MetadataKey::from_bytes(key.as_bytes()),
+                value.parse(),
+            ) {
+                metadata.insert(key, value);
+            }
+        }
+        status
+    }
 }
 
 impl From<serde_json::Error> for Error {
     fn from(e: serde_json::Error) -> Self {
-        Error::Serialization(e.to_string())
+        Error::serialization("Invalid JSON").with_source(e)
     }
 }
 
@@ -166,6 +582,104 @@ mod tests {
         assert!(!err.is_retryable());
     }
 
+    #[test]
+    fn test_retryable_errors_have_delay_hint() {
+        let errors = [
+            Error::Io(std::io::Error::from(std::io::ErrorKind::ConnectionReset)),
+            Error::Io(std::io::Error::from(std::io::ErrorKind::PermissionDenied)),
+            Error::storage("throttled"),
+            Error::WorkerNotFound {
+                worker_id: "worker-1".to_string(),
+            },
+        ];
+        for err in &errors {
+            assert_eq!(err.is_retryable(), err.retry_delay_hint_ms().is_some());
+        }
+        assert!(errors[0].is_retryable());
+        assert!(!errors[1].is_retryable());
+    }
+
+    #[test]
+    fn test_error_code_round_trip() {
+        for (code, name) in ERROR_CODES {
+            assert_eq!(code.as_str(), name);
+            assert_eq!(name.parse::<ErrorCode>().unwrap(), code);
+        }
+        assert!("NOT_A_CODE".parse::<ErrorCode>().is_err());
+    }
+
+    #[test]
+    fn test_into_status() {
+        let status = tonic::Status::from(Error::WorkerNotFound {
+            worker_id: "worker-1".to_string(),
+        });
+        assert_eq!(status.code(), tonic::Code::NotFound);
+        assert_eq!(status.message(), "Worker not found: worker-1");
+        assert_eq!(
+            ErrorCode::from_status(&status),
+            Some(ErrorCode::WorkerNotFound)
+        );
+        assert!(status.metadata().get(RETRY_PUSHBACK_METADATA).is_none());
+        assert_eq!(
+            status.metadata().get("error-worker-id").unwrap(),
+            "worker-1"
+        );
+
+        let status = tonic::Status::from(Error::StorageUnavailable {
+            backend: "s3".to_string(),
+        });
+        assert_eq!(status.code(), tonic::Code::Unavailable);
+        assert_eq!(
+            status.metadata().get(RETRY_PUSHBACK_METADATA).unwrap(),
+            "5000"
+        );
+    }
+
+    #[test]
+    fn test_source_and_context() {
+        use std::error::Error as _;
+
+        let io = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "access denied");
+        let err = Error::checkpoint_write_failed("Failed to write shard")
+            .with_checkpoint_id("ckpt-7")
+            .with_path("/ckpt/ckpt-7/shard-0.bin")
+            .with_source(io);
+        assert_eq!(
+            err.to_string(),
+            "Checkpoint write failed: Failed to write shard at /ckpt/ckpt-7/shard-0.bin: access denied"
+        );
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<std::io::Error>().unwrap().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+        assert_eq!(
+            err.context(),
+            [
+                ("checkpoint_id", "ckpt-7"),
+                ("path", "/ckpt/ckpt-7/shard-0.bin")
+            ]
+        );
+
+        let status = tonic::Status::from(err);
+        assert_eq!(
+            status.metadata().get("error-checkpoint-id").unwrap(),
+            "ckpt-7"
+        );
+        assert_eq!(
+            status.metadata().get("error-path").unwrap(),
+            "/ckpt/ckpt-7/shard-0.bin"
+        );
+
+        let err = Error::from(serde_json::from_str::<u32>("x").unwrap_err());
+        assert!(err.source().unwrap().is::<serde_json::Error>());
+        assert!(Error::storage("throttled").source().is_none());
+        assert!(Error::storage("throttled")
+            .with_checkpoint_id("c")
+            .context()
+            .is_empty());
+    }
+
     #[test]
     fn test_error_fatal() {
         let err = Error::InvalidConfig {
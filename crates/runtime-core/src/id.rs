@@ -0,0 +1,252 @@
+//! Time-ordered ID generation
+//!
+//! Checkpoint, task and event IDs are 63-bit snowflake-style [`Id`]s: the
+//! milliseconds since [`ID_EPOCH_MS`], then a node number, then a sequence
+//! number within the millisecond. IDs from one generator never repeat and
+//! always increase, and IDs from different nodes sort by creation time.
+//!
+//! They are written as 16 lowercase hex digits behind a prefix naming the
+//! kind of object, e.g. `task_00b1c3f2a4c01000`, so string order matches
+//! creation order too.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, TimeZone, Utc};
+use parking_lot::Mutex;
+
+use crate::clock::{SharedClock, SystemClock};
+use crate::{Error, Result};
+
+/// Start of ID time, 2024-01-01T00:00:00Z in ms since the Unix epoch
+pub const ID_EPOCH_MS: i64 = 1_704_067_200_000;
+
+const NODE_BITS: u32 = 10;
+const SEQUENCE_BITS: u32 = 12;
+const TIMESTAMP_BITS: u32 = 41;
+
+/// Largest node number
+pub const MAX_NODE: u16 = (1 << NODE_BITS) - 1;
+const MAX_SEQUENCE: u64 = (1 << SEQUENCE_BITS) - 1;
+const MAX_TIMESTAMP: u64 = (1 << TIMESTAMP_BITS) - 1;
+
+/// A time-ordered unique ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Id(u64);
+
+impl Id {
+    fn from_parts(timestamp: u64, node: u16, sequence: u64) -> Self {
+        Self(
+            (timestamp << (NODE_BITS + SEQUENCE_BITS))
+                | ((node as u64) << SEQUENCE_BITS)
+                | sequence,
+        )
+    }
+
+    /// Parse the ID out of a prefixed string such as `task_00b1c3f2a4c01000`
+    pub fn from_prefixed(s: &str) -> Result<Self> {
+        s.rsplit_once('_').map_or(s, |(_, id)| id).parse()
+    }
+
+    /// The raw 63-bit value
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// When the ID was generated, in ms since the Unix epoch
+    pub fn timestamp_ms(&self) -> i64 {
+        ID_EPOCH_MS + (self.0 >> (NODE_BITS + SEQUENCE_BITS)) as i64
+    }
+
+    /// When the ID was generated
+    pub fn created_at(&self) -> DateTime<Utc> {
+        Utc.timestamp_millis_opt(self.timestamp_ms())
+            .single()
+            .unwrap_or_default()
+    }
+
+    /// Node of the generator that produced the ID
+    pub fn node(&self) -> u16 {
+        ((self.0 >> SEQUENCE_BITS) & MAX_NODE as u64) as u16
+    }
+
+    /// Position of the ID within its millisecond
+    pub fn sequence(&self) -> u64 {
+        self.0 & MAX_SEQUENCE
+    }
+
+    /// The ID behind `prefix`, e.g. `task_00b1c3f2a4c01000`
+    pub fn with_prefix(&self, prefix: &str) -> String {
+        format!("{}_{}", prefix, self)
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for Id {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match u64::from_str_radix(s, 16) {
+            Ok(value) if s.len() == 16 && value >> 63 == 0 => Ok(Self(value)),
+            _ => Err(Error::InvalidId {
+                kind: "id",
+                id: s.to_string(),
+                reason: "must be 16 hex digits".to_string(),
+            }),
+        }
+    }
+}
+
+/// Generates increasing [`Id`]s for one node
+///
+/// # Example
+///
+/// ```
+/// use runtime_core::id::IdGenerator;
+///
+/// let ids = IdGenerator::new(7);
+/// let first = ids.next();
+/// let second = ids.next();
+/// assert!(second > first);
+/// assert_eq!(first.node(), 7);
+/// assert!(ids.next_prefixed("task").starts_with("task_"));
+/// ```
+#[derive(Debug)]
+pub struct IdGenerator {
+    node: u16,
+    clock: SharedClock,
+    /// Timestamp and sequence of the last ID handed out
+    last: Mutex<(u64, u64)>,
+}
+
+impl IdGenerator {
+    /// Create a generator for `node`, keeping its low 10 bits
+    pub fn new(node: u16) -> Self {
+        Self {
+            node: node & MAX_NODE,
+            clock: SystemClock::shared(),
+            last: Mutex::new((0, 0)),
+        }
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Node number stamped into every ID
+    pub fn node(&self) -> u16 {
+        self.node
+    }
+
+    /// The next ID
+    ///
+    /// If the clock goes backwards or a millisecond's sequence numbers run
+    /// out, the last timestamp is reused or moved forward so IDs keep
+    /// increasing.
+    pub fn next(&self) -> Id {
+        let now = (self.clock.utc_now().timestamp_millis() - ID_EPOCH_MS).max(0) as u64;
+        let mut last = self.last.lock();
+        let (last_timestamp, last_sequence) = *last;
+
+        let next = if now > last_timestamp {
+            (now, 0)
+        } else if last_sequence < MAX_SEQUENCE {
+            (last_timestamp, last_sequence + 1)
+        } else {
+            (last_timestamp + 1, 0)
+        };
+        *last = next;
+        Id::from_parts(next.0.min(MAX_TIMESTAMP), self.node, next.1)
+    }
+
+    /// The next ID behind `prefix`
+    pub fn next_prefixed(&self, prefix: &str) -> String {
+        self.next().with_prefix(prefix)
+    }
+}
+
+static GLOBAL: OnceLock<IdGenerator> = OnceLock::new();
+
+/// Set the node number of the process-wide generator
+///
+/// Must be called before the first [`next_id`]; returns `false` if the
+/// generator already exists. Without it a random node number is used.
+pub fn set_node_id(node: u16) -> bool {
+    GLOBAL.set(IdGenerator::new(node)).is_ok()
+}
+
+/// The process-wide generator
+pub fn global() -> &'static IdGenerator {
+    GLOBAL.get_or_init(|| {
+        let random = uuid::Uuid::new_v4().as_u128() as u16;
+        IdGenerator::new(random)
+    })
+}
+
+/// The next ID from the process-wide generator, behind `prefix`
+pub fn next_id(prefix: &str) -> String {
+    global().next_prefixed(prefix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{Clock, MockClock};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_ids_increase_and_decode() {
+        let clock = Arc::new(MockClock::new());
+        let ids = IdGenerator::new(MAX_NODE + 5).with_clock(clock.clone());
+        assert_eq!(ids.node(), 4);
+
+        let first = ids.next();
+        let second = ids.next();
+        assert_eq!(first.node(), 4);
+        assert_eq!(first.sequence(), 0);
+        assert_eq!(second.sequence(), 1);
+        assert_eq!(first.timestamp_ms(), clock.utc_now().timestamp_millis());
+
+        clock.advance(Duration::from_millis(3));
+        let third = ids.next();
+        assert_eq!(third.sequence(), 0);
+        assert_eq!(third.timestamp_ms(), first.timestamp_ms() + 3);
+        assert!(first < second && second < third);
+        assert!(first.to_string() < second.to_string());
+        assert!(second.to_string() < third.to_string());
+
+        let prefixed = third.with_prefix("ckpt_txn");
+        assert_eq!(Id::from_prefixed(&prefixed).unwrap(), third);
+        assert_eq!(third.to_string().parse::<Id>().unwrap(), third);
+        assert!("xyz".parse::<Id>().is_err());
+        assert!("ffffffffffffffff".parse::<Id>().is_err());
+    }
+
+    #[test]
+    fn test_sequence_overflow_moves_forward() {
+        let clock = Arc::new(MockClock::new());
+        let ids = IdGenerator::new(1).with_clock(clock.clone());
+        let start = ids.next().timestamp_ms();
+
+        let last = (0..MAX_SEQUENCE).map(|_| ids.next()).last().unwrap();
+        assert_eq!(last.sequence(), MAX_SEQUENCE);
+        assert_eq!(last.timestamp_ms(), start);
+
+        let overflow = ids.next();
+        assert_eq!(overflow.sequence(), 0);
+        assert_eq!(overflow.timestamp_ms(), start + 1);
+
+        // The clock catching up to the borrowed millisecond continues from it
+        clock.advance(Duration::from_millis(1));
+        assert_eq!(ids.next().sequence(), 1);
+    }
+}
@@ -0,0 +1,318 @@
+//! Worker labels, taints and selectors
+//!
+//! Workers carry labels such as `gpu=a100` or `zone=us-east-1b` that datasets,
+//! tasks and the scheduler select on with a [`LabelSelector`]. A [`Taint`]
+//! keeps a worker out of selection unless the selector tolerates it, e.g. to
+//! reserve nodes for evaluation jobs.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Longest allowed label key or value in bytes
+pub const MAX_LABEL_LEN: usize = 63;
+
+/// Check a label key or value, `/` is allowed in keys for prefixes like
+/// `strata.io/pool`
+fn validate_label(kind: &str, s: &str, is_key: bool) -> Result<()> {
+    let reason = if is_key && s.is_empty() {
+        "must not be empty".to_string()
+    } else if s.len() > MAX_LABEL_LEN {
+        format!("must be at most {} characters", MAX_LABEL_LEN)
+    } else if let Some(c) = s.chars().find(|c| {
+        !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') || (is_key && *c == '/'))
+    }) {
+        format!("contains {:?}", c)
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidConfig {
+        message: format!("Invalid {} {:?}: {}", kind, s, reason),
+    })
+}
+
+/// Check every key and value of a label map
+pub fn validate_labels(labels: &HashMap<String, String>) -> Result<()> {
+    labels.iter().try_for_each(|(key, value)| {
+        validate_label("label key", key, true)?;
+        validate_label("label value", value, false)
+    })
+}
+
+/// Keeps a worker from being selected unless the selector tolerates it
+///
+/// Written as `key` or `key=value`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Taint {
+    pub key: String,
+    pub value: Option<String>,
+}
+
+impl Taint {
+    /// Create a taint, validating the key and value
+    pub fn new(key: impl Into<String>, value: Option<String>) -> Result<Self> {
+        let key = key.into();
+        validate_label("taint key", &key, true)?;
+        if let Some(value) = &value {
+            validate_label("taint value", value, false)?;
+        }
+        Ok(Self { key, value })
+    }
+}
+
+impl FromStr for Taint {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.split_once('=') {
+            Some((key, value)) => Self::new(key.trim(), Some(value.trim().to_string())),
+            None => Self::new(s.trim(), None),
+        }
+    }
+}
+
+impl TryFrom<String> for Taint {
+    type Error = Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl From<Taint> for String {
+    fn from(taint: Taint) -> Self {
+        taint.to_string()
+    }
+}
+
+impl fmt::Display for Taint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            Some(value) => write!(f, "{}={}", self.key, value),
+            None => write!(f, "{}", self.key),
+        }
+    }
+}
+
+/// One condition of a [`LabelSelector`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Requirement {
+    Equals(String, String),
+    NotEquals(String, String),
+    Exists(String),
+    NotExists(String),
+}
+
+impl Requirement {
+    fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        match self {
+            Requirement::Equals(key, value) => labels.get(key) == Some(value),
+            Requirement::NotEquals(key, value) => labels.get(key) != Some(value),
+            Requirement::Exists(key) => labels.contains_key(key),
+            Requirement::NotExists(key) => !labels.contains_key(key),
+        }
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Requirement::Equals(key, value) => write!(f, "{}={}", key, value),
+            Requirement::NotEquals(key, value) => write!(f, "{}!={}", key, value),
+            Requirement::Exists(key) => write!(f, "{}", key),
+            Requirement::NotExists(key) => write!(f, "!{}", key),
+        }
+    }
+}
+
+/// Selects workers by their labels and taints
+///
+/// Parses from a comma-separated list of `key=value`, `key!=value`, `key`
+/// (label set) and `!key` (label unset). An empty selector matches every
+/// untainted worker.
+///
+/// # Example
+///
+/// ```
+/// use std::collections::HashMap;
+/// use runtime_core::labels::{LabelSelector, Taint};
+///
+/// let selector: LabelSelector = "gpu=a100,zone!=us-east-1a,!spot".parse().unwrap();
+/// let labels = HashMap::from([
+///     ("gpu".to_string(), "a100".to_string()),
+///     ("zone".to_string(), "us-east-1b".to_string()),
+/// ]);
+/// assert!(selector.matches(&labels));
+///
+/// let reserved: Taint = "pool=eval".parse().unwrap();
+/// assert!(!selector.tolerates(&[reserved.clone()]));
+/// assert!(selector.tolerate(reserved.clone()).tolerates(&[reserved]));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LabelSelector {
+    requirements: Vec<Requirement>,
+    tolerations: Vec<Taint>,
+}
+
+impl LabelSelector {
+    /// Create a selector matching every untainted worker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require label `key` to be `value`
+    pub fn equals(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.requirements
+            .push(Requirement::Equals(key.into(), value.into()));
+        self
+    }
+
+    /// Require label `key` to be unset or differ from `value`
+    pub fn not_equals(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.requirements
+            .push(Requirement::NotEquals(key.into(), value.into()));
+        self
+    }
+
+    /// Require label `key` to be set
+    pub fn exists(mut self, key: impl Into<String>) -> Self {
+        self.requirements.push(Requirement::Exists(key.into()));
+        self
+    }
+
+    /// Require label `key` to be unset
+    pub fn not_exists(mut self, key: impl Into<String>) -> Self {
+        self.requirements.push(Requirement::NotExists(key.into()));
+        self
+    }
+
+    /// Select workers with `taint`; a taint without a value tolerates every
+    /// value of its key
+    pub fn tolerate(mut self, taint: Taint) -> Self {
+        self.tolerations.push(taint);
+        self
+    }
+
+    /// Whether `labels` meet every requirement
+    pub fn matches(&self, labels: &HashMap<String, String>) -> bool {
+        self.requirements.iter().all(|r| r.matches(labels))
+    }
+
+    /// Whether every one of `taints` is tolerated
+    pub fn tolerates(&self, taints: &[Taint]) -> bool {
+        taints.iter().all(|taint| {
+            self.tolerations.iter().any(|toleration| {
+                toleration.key == taint.key
+                    && (toleration.value.is_none() || toleration.value == taint.value)
+            })
+        })
+    }
+}
+
+impl FromStr for LabelSelector {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut selector = Self::new();
+        for term in s.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let requirement = if let Some((key, value)) = term.split_once("!=") {
+                Requirement::NotEquals(key.trim().to_string(), value.trim().to_string())
+            } else if let Some((key, value)) = term.split_once('=') {
+                Requirement::Equals(key.trim().to_string(), value.trim().to_string())
+            } else if let Some(key) = term.strip_prefix('!') {
+                Requirement::NotExists(key.trim().to_string())
+            } else {
+                Requirement::Exists(term.to_string())
+            };
+
+            match &requirement {
+                Requirement::Equals(key, value) | Requirement::NotEquals(key, value) => {
+                    validate_label("label key", key, true)?;
+                    validate_label("label value", value, false)?;
+                }
+                Requirement::Exists(key) | Requirement::NotExists(key) => {
+                    validate_label("label key", key, true)?;
+                }
+            }
+            selector.requirements.push(requirement);
+        }
+        Ok(selector)
+    }
+}
+
+impl fmt::Display for LabelSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, requirement) in self.requirements.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}", requirement)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn labels(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_selector_parse_and_match() {
+        let selector: LabelSelector = " gpu=a100, zone!=us-east-1a ,ssd,!spot".parse().unwrap();
+        assert_eq!(selector.to_string(), "gpu=a100,zone!=us-east-1a,ssd,!spot");
+
+        assert!(selector.matches(&labels(&[("gpu", "a100"), ("ssd", "")])));
+        assert!(selector.matches(&labels(&[
+            ("gpu", "a100"),
+            ("ssd", "nvme"),
+            ("zone", "us-east-1b"),
+        ])));
+        assert!(!selector.matches(&labels(&[("gpu", "h100"), ("ssd", "")])));
+        assert!(!selector.matches(&labels(&[("gpu", "a100")])));
+        assert!(!selector.matches(&labels(&[
+            ("gpu", "a100"),
+            ("ssd", ""),
+            ("zone", "us-east-1a"),
+        ])));
+        assert!(!selector.matches(&labels(&[("gpu", "a100"), ("ssd", ""), ("spot", "true"),])));
+
+        assert!(""
+            .parse::<LabelSelector>()
+            .unwrap()
+            .matches(&HashMap::new()));
+        assert!("gpu=a 100".parse::<LabelSelector>().is_err());
+        assert!("=a100".parse::<LabelSelector>().is_err());
+    }
+
+    #[test]
+    fn test_taints() {
+        let eval: Taint = "pool=eval".parse().unwrap();
+        let maintenance: Taint = "maintenance".parse().unwrap();
+        assert_eq!(eval.to_string(), "pool=eval");
+        assert_eq!(maintenance.value, None);
+        assert!("bad key".parse::<Taint>().is_err());
+
+        let selector = LabelSelector::new();
+        assert!(selector.tolerates(&[]));
+        assert!(!selector.tolerates(std::slice::from_ref(&eval)));
+
+        let selector = selector.tolerate("pool".parse().unwrap());
+        assert!(selector.tolerates(std::slice::from_ref(&eval)));
+        assert!(!selector.tolerates(&[eval.clone(), maintenance.clone()]));
+
+        let selector = LabelSelector::new().tolerate("pool=train".parse().unwrap());
+        assert!(!selector.tolerates(&[eval]));
+    }
+}
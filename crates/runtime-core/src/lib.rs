@@ -3,14 +3,34 @@
 //! Provides core types, error handling, and async runtime utilities
 //! for the distributed training data and checkpoint system.
 
+pub mod clock;
 pub mod config;
+pub mod envelope;
 pub mod error;
+pub mod id;
+pub mod labels;
+pub mod logging;
+pub mod metrics;
+pub mod prometheus;
+pub mod resources;
+pub mod retry;
 pub mod runtime;
+pub mod shutdown;
+pub mod supervisor;
 pub mod types;
 pub mod worker;
 
+pub use clock::{Clock, SharedClock};
 pub use config::RuntimeConfig;
-pub use error::{Error, Result};
+pub use error::{Error, ErrorCode, Result};
+pub use labels::{LabelSelector, Taint};
+pub use metrics::{Counter, Gauge, Histogram, MetricsRegistry};
+pub use resources::ResourceCollector;
 pub use runtime::RuntimeManager;
+pub use shutdown::ShutdownToken;
+pub use supervisor::{RestartPolicy, ShutdownSignal, Supervisor};
 pub use types::*;
-pub use worker::{WorkerInfo, WorkerRegistry, WorkerRegistryHandle, WorkerState};
+pub use worker::{
+    MembershipChange, MembershipEvent, WorkerInfo, WorkerRegistry, WorkerRegistryHandle,
+    WorkerState,
+};
@@ -0,0 +1,136 @@
+//! Log volume control for hot paths
+//!
+//! Heartbeats and shard requests arrive thousands of times per second on a
+//! large cluster, so logging each one costs more than the work itself.
+//! [`LogThrottle`] lets a log line through at most once per interval and
+//! [`LogSampler`] lets one in N through. Both have `const` constructors so
+//! they can live in a `static` next to the log line:
+//!
+//! ```
+//! use std::time::Duration;
+//! use runtime_core::logging::{LogSampler, LogThrottle};
+//!
+//! static HEARTBEAT_LOG: LogSampler = LogSampler::new(100);
+//! static UNKNOWN_SHARD_LOG: LogThrottle = LogThrottle::new(Duration::from_secs(10));
+//!
+//! if HEARTBEAT_LOG.sample() {
+//!     tracing::debug!(sample_rate = HEARTBEAT_LOG.rate(), "Heartbeat processed");
+//! }
+//! if let Some(suppressed) = UNKNOWN_SHARD_LOG.allow() {
+//!     tracing::warn!(suppressed, "Ignoring progress for unknown shard");
+//! }
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use parking_lot::{const_mutex, Mutex};
+
+/// Lets a log line through at most once per interval
+#[derive(Debug)]
+pub struct LogThrottle {
+    interval: Duration,
+    /// When a line was last let through
+    last: Mutex<Option<Instant>>,
+    /// Lines held back since then
+    suppressed: AtomicU64,
+}
+
+impl LogThrottle {
+    /// Create a throttle letting one line through per `interval`
+    pub const fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            last: const_mutex(None),
+            suppressed: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether to log now
+    ///
+    /// Returns the number of lines held back since the last one let through,
+    /// or `None` to skip this one.
+    pub fn allow(&self) -> Option<u64> {
+        self.allow_at(Instant::now())
+    }
+
+    /// Whether to log at `now`
+    pub fn allow_at(&self, now: Instant) -> Option<u64> {
+        // Another thread deciding at the same moment wins
+        let allowed = self.last.try_lock().is_some_and(|mut last| {
+            let due = last.is_none_or(|at| now.saturating_duration_since(at) >= self.interval);
+            if due {
+                *last = Some(now);
+            }
+            due
+        });
+
+        if allowed {
+            Some(self.suppressed.swap(0, Ordering::Relaxed))
+        } else {
+            self.suppressed.fetch_add(1, Ordering::Relaxed);
+            None
+        }
+    }
+}
+
+/// Lets one in N log lines through
+#[derive(Debug)]
+pub struct LogSampler {
+    rate: u64,
+    count: AtomicU64,
+}
+
+impl LogSampler {
+    /// Create a sampler letting every `rate`-th line through, starting with
+    /// the first
+    ///
+    /// A rate of 0 or 1 lets every line through.
+    pub const fn new(rate: u64) -> Self {
+        Self {
+            rate: if rate == 0 { 1 } else { rate },
+            count: AtomicU64::new(0),
+        }
+    }
+
+    /// Whether to log this line
+    pub fn sample(&self) -> bool {
+        self.count
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(self.rate)
+    }
+
+    /// One line is logged per this many
+    pub fn rate(&self) -> u64 {
+        self.rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_counts_suppressed() {
+        let throttle = LogThrottle::new(Duration::from_secs(10));
+        let start = Instant::now();
+
+        assert_eq!(throttle.allow_at(start), Some(0));
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(1)), None);
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(9)), None);
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(10)), Some(2));
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(15)), None);
+        assert_eq!(throttle.allow_at(start + Duration::from_secs(30)), Some(1));
+    }
+
+    #[test]
+    fn test_sampler() {
+        let sampler = LogSampler::new(3);
+        let sampled: Vec<bool> = (0..7).map(|_| sampler.sample()).collect();
+        assert_eq!(sampled, vec![true, false, false, true, false, false, true]);
+
+        let every = LogSampler::new(0);
+        assert_eq!(every.rate(), 1);
+        assert!((0..5).all(|_| every.sample()));
+    }
+}
@@ -0,0 +1,499 @@
+//! Metrics shared by the runtime's components
+//!
+//! Components keep cheap [`Counter`], [`Gauge`] and [`Histogram`] handles and
+//! publish them in a [`MetricsRegistry`], which encodes everything registered
+//! in the Prometheus text format. Handles work whether or not they have been
+//! registered, so a component used on its own still counts.
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::RwLock;
+
+use crate::prometheus::{format_value, MetricType, PrometheusWriter};
+
+/// Default histogram buckets in seconds, for latencies from 1ms to 10s
+pub const DEFAULT_BUCKETS: &[f64] = &[
+    0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// A count that only goes up
+#[derive(Debug, Clone, Default)]
+pub struct Counter(Arc<AtomicU64>);
+
+impl Counter {
+    /// Create a counter at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add one
+    pub fn inc(&self) {
+        self.inc_by(1);
+    }
+
+    /// Add `n`
+    pub fn inc_by(&self, n: u64) {
+        self.0.fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Current count
+    pub fn get(&self) -> u64 {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// A value that goes up and down
+#[derive(Debug, Clone, Default)]
+pub struct Gauge(Arc<AtomicU64>);
+
+impl Gauge {
+    /// Create a gauge at zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the value
+    pub fn set(&self, value: f64) {
+        self.0.store(value.to_bits(), Ordering::Relaxed);
+    }
+
+    /// Add `delta`, which may be negative
+    pub fn add(&self, delta: f64) {
+        add_f64(&self.0, delta);
+    }
+
+    /// Add one
+    pub fn inc(&self) {
+        self.add(1.0);
+    }
+
+    /// Subtract one
+    pub fn dec(&self) {
+        self.add(-1.0);
+    }
+
+    /// Current value
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+/// Observations counted into buckets
+#[derive(Debug, Clone)]
+pub struct Histogram(Arc<HistogramInner>);
+
+#[derive(Debug)]
+struct HistogramInner {
+    /// Upper bounds, ascending
+    bounds: Vec<f64>,
+    /// Observations per bucket, not cumulative
+    buckets: Vec<AtomicU64>,
+    /// Sum of observations as f64 bits
+    sum: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new(DEFAULT_BUCKETS)
+    }
+}
+
+impl Histogram {
+    /// Create a histogram with the given bucket upper bounds
+    ///
+    /// Bounds are sorted and non-finite ones dropped; the `+Inf` bucket is implied.
+    pub fn new(bounds: &[f64]) -> Self {
+        let mut bounds: Vec<f64> = bounds.iter().copied().filter(|b| b.is_finite()).collect();
+        bounds.sort_by(f64::total_cmp);
+        bounds.dedup();
+        let buckets = bounds.iter().map(|_| AtomicU64::new(0)).collect();
+        Self(Arc::new(HistogramInner {
+            bounds,
+            buckets,
+            sum: AtomicU64::new(0.0f64.to_bits()),
+            count: AtomicU64::new(0),
+        }))
+    }
+
+    /// Record an observation
+    pub fn observe(&self, value: f64) {
+        let inner = &self.0;
+        let bucket = inner.bounds.partition_point(|bound| *bound < value);
+        if let Some(count) = inner.buckets.get(bucket) {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+        add_f64(&inner.sum, value);
+        inner.count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Record a duration in seconds
+    pub fn observe_duration(&self, duration: Duration) {
+        self.observe(duration.as_secs_f64());
+    }
+
+    /// Number of observations
+    pub fn count(&self) -> u64 {
+        self.0.count.load(Ordering::Relaxed)
+    }
+
+    /// Sum of observations
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.0.sum.load(Ordering::Relaxed))
+    }
+
+    /// Upper bound and cumulative count of each bucket, excluding `+Inf`
+    pub fn buckets(&self) -> Vec<(f64, u64)> {
+        let mut total = 0;
+        self.0
+            .bounds
+            .iter()
+            .zip(&self.0.buckets)
+            .map(|(bound, count)| {
+                total += count.load(Ordering::Relaxed);
+                (*bound, total)
+            })
+            .collect()
+    }
+}
+
+fn add_f64(cell: &AtomicU64, delta: f64) {
+    let _ = cell.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+        Some((f64::from_bits(bits) + delta).to_bits())
+    });
+}
+
+/// A metric handle held by the registry
+#[derive(Debug, Clone)]
+pub enum Metric {
+    Counter(Counter),
+    Gauge(Gauge),
+    Histogram(Histogram),
+}
+
+impl Metric {
+    fn kind(&self) -> MetricType {
+        match self {
+            Metric::Counter(_) => MetricType::Counter,
+            Metric::Gauge(_) => MetricType::Gauge,
+            Metric::Histogram(_) => MetricType::Histogram,
+        }
+    }
+}
+
+impl From<Counter> for Metric {
+    fn from(counter: Counter) -> Self {
+        Metric::Counter(counter)
+    }
+}
+
+impl From<Gauge> for Metric {
+    fn from(gauge: Gauge) -> Self {
+        Metric::Gauge(gauge)
+    }
+}
+
+impl From<Histogram> for Metric {
+    fn from(histogram: Histogram) -> Self {
+        Metric::Histogram(histogram)
+    }
+}
+
+/// Label pairs of a series, sorted by name
+type Labels = Vec<(String, String)>;
+
+#[derive(Debug)]
+struct Family {
+    help: String,
+    kind: MetricType,
+    series: BTreeMap<Labels, Metric>,
+}
+
+/// Metrics published by the runtime's components
+///
+/// A metric is identified by its name and labels. Asking for one that is
+/// already registered returns the existing handle.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    families: RwLock<BTreeMap<String, Family>>,
+}
+
+impl MetricsRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get or register a counter
+    pub fn counter(&self, name: &str, help: &str) -> Counter {
+        self.counter_with_labels(name, help, &[])
+    }
+
+    /// Get or register a counter with labels
+    pub fn counter_with_labels(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Counter {
+        match self.get_or_register(name, help, labels, MetricType::Counter, || {
+            Counter::new().into()
+        }) {
+            Metric::Counter(counter) => counter,
+            _ => unreachable!("family kind checked on registration"),
+        }
+    }
+
+    /// Get or register a gauge
+    pub fn gauge(&self, name: &str, help: &str) -> Gauge {
+        self.gauge_with_labels(name, help, &[])
+    }
+
+    /// Get or register a gauge with labels
+    pub fn gauge_with_labels(&self, name: &str, help: &str, labels: &[(&str, &str)]) -> Gauge {
+        match self.get_or_register(name, help, labels, MetricType::Gauge, || {
+            Gauge::new().into()
+        }) {
+            Metric::Gauge(gauge) => gauge,
+            _ => unreachable!("family kind checked on registration"),
+        }
+    }
+
+    /// Get or register a histogram with the given bucket upper bounds
+    pub fn histogram(&self, name: &str, help: &str, buckets: &[f64]) -> Histogram {
+        self.histogram_with_labels(name, help, buckets, &[])
+    }
+
+    /// Get or register a histogram with labels
+    ///
+    /// The buckets only apply when the histogram is created.
+    pub fn histogram_with_labels(
+        &self,
+        name: &str,
+        help: &str,
+        buckets: &[f64],
+        labels: &[(&str, &str)],
+    ) -> Histogram {
+        match self.get_or_register(name, help, labels, MetricType::Histogram, || {
+            Histogram::new(buckets).into()
+        }) {
+            Metric::Histogram(histogram) => histogram,
+            _ => unreachable!("family kind checked on registration"),
+        }
+    }
+
+    /// Publish a handle a component already holds
+    ///
+    /// Replaces any metric registered under the same name and labels.
+    ///
+    /// # Panics
+    ///
+    /// If `name` is registered as a different type of metric.
+    pub fn register(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        metric: impl Into<Metric>,
+    ) {
+        let metric = metric.into();
+        let mut families = self.families.write();
+        let family = family_entry(&mut families, name, help, metric.kind());
+        family.series.insert(sorted_labels(labels), metric);
+    }
+
+    /// Names of the registered metrics, sorted
+    pub fn names(&self) -> Vec<String> {
+        self.families.read().keys().cloned().collect()
+    }
+
+    /// Write every registered metric, sorted by name and labels
+    pub fn encode(&self, out: &mut PrometheusWriter) {
+        for (name, family) in self.families.read().iter() {
+            out.family(name, family.kind, &family.help);
+            for (labels, metric) in &family.series {
+                let labels: Vec<(&str, &str)> = labels
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()))
+                    .collect();
+                match metric {
+                    Metric::Counter(counter) => {
+                        out.sample(name, &labels, counter.get() as f64);
+                    }
+                    Metric::Gauge(gauge) => {
+                        out.sample(name, &labels, gauge.get());
+                    }
+                    Metric::Histogram(histogram) => {
+                        encode_histogram(out, name, &labels, histogram);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Encode every registered metric as a text format payload
+    pub fn render(&self) -> String {
+        let mut out = PrometheusWriter::new();
+        self.encode(&mut out);
+        out.finish()
+    }
+
+    /// # Panics
+    ///
+    /// If `name` is registered as a different type of metric.
+    fn get_or_register(
+        &self,
+        name: &str,
+        help: &str,
+        labels: &[(&str, &str)],
+        kind: MetricType,
+        make: impl FnOnce() -> Metric,
+    ) -> Metric {
+        let labels = sorted_labels(labels);
+        if let Some(family) = self.families.read().get(name) {
+            check_kind(name, family.kind, kind);
+            if let Some(metric) = family.series.get(&labels) {
+                return metric.clone();
+            }
+        }
+
+        let mut families = self.families.write();
+        let family = family_entry(&mut families, name, help, kind);
+        family.series.entry(labels).or_insert_with(make).clone()
+    }
+}
+
+fn family_entry<'a>(
+    families: &'a mut BTreeMap<String, Family>,
+    name: &str,
+    help: &str,
+    kind: MetricType,
+) -> &'a mut Family {
+    let family = families.entry(name.to_string()).or_insert_with(|| Family {
+        help: help.to_string(),
+        kind,
+        series: BTreeMap::new(),
+    });
+    check_kind(name, family.kind, kind);
+    family
+}
+
+fn check_kind(name: &str, registered: MetricType, requested: MetricType) {
+    assert_eq!(
+        registered, requested,
+        "metric {} is already registered as a different type",
+        name
+    );
+}
+
+fn sorted_labels(labels: &[(&str, &str)]) -> Labels {
+    let mut labels: Labels = labels
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    labels.sort();
+    labels
+}
+
+fn encode_histogram(
+    out: &mut PrometheusWriter,
+    name: &str,
+    labels: &[(&str, &str)],
+    histogram: &Histogram,
+) {
+    let bucket_name = format!("{}_bucket", name);
+    for (bound, count) in histogram.buckets() {
+        let le = format_value(bound);
+        let mut labels = labels.to_vec();
+        labels.push(("le", &le));
+        out.sample(&bucket_name, &labels, count as f64);
+    }
+    let mut inf_labels = labels.to_vec();
+    inf_labels.push(("le", "+Inf"));
+    out.sample(&bucket_name, &inf_labels, histogram.count() as f64);
+    out.sample(&format!("{}_sum", name), labels, histogram.sum());
+    out.sample(&format!("{}_count", name), labels, histogram.count() as f64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles() {
+        let counter = Counter::new();
+        counter.inc();
+        counter.inc_by(4);
+        assert_eq!(counter.get(), 5);
+
+        let gauge = Gauge::new();
+        gauge.set(3.0);
+        gauge.inc();
+        gauge.add(-1.5);
+        assert_eq!(gauge.get(), 2.5);
+
+        let histogram = Histogram::new(&[1.0, 0.1, f64::NAN]);
+        for value in [0.05, 0.1, 0.5, 7.0] {
+            histogram.observe(value);
+        }
+        assert_eq!(histogram.buckets(), vec![(0.1, 2), (1.0, 3)]);
+        assert_eq!(histogram.count(), 4);
+        assert!((histogram.sum() - 7.65).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_registry_returns_existing_handles() {
+        let registry = MetricsRegistry::new();
+        registry.counter("strata_writes_total", "Writes").inc();
+        registry.counter("strata_writes_total", "Writes").inc();
+        assert_eq!(registry.counter("strata_writes_total", "Writes").get(), 2);
+
+        let a = registry.gauge_with_labels("strata_queue", "Queue", &[("a", "1"), ("b", "2")]);
+        let b = registry.gauge_with_labels("strata_queue", "Queue", &[("b", "2"), ("a", "1")]);
+        a.set(7.0);
+        assert_eq!(b.get(), 7.0);
+
+        let published = Counter::new();
+        published.inc_by(9);
+        registry.register("strata_published_total", "Published", &[], published);
+        assert_eq!(
+            registry.names(),
+            vec![
+                "strata_published_total",
+                "strata_queue",
+                "strata_writes_total"
+            ]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "already registered as a different type")]
+    fn test_registry_rejects_type_change() {
+        let registry = MetricsRegistry::new();
+        registry.counter("strata_things", "Things");
+        registry.gauge("strata_things", "Things");
+    }
+
+    #[test]
+    fn test_render() {
+        let registry = MetricsRegistry::new();
+        registry
+            .counter_with_labels("strata_ops_total", "Operations", &[("op", "read")])
+            .inc_by(3);
+        let latency = registry.histogram("strata_op_seconds", "Operation latency", &[0.1, 1.0]);
+        latency.observe(0.05);
+        latency.observe(2.0);
+
+        assert_eq!(
+            registry.render(),
+            "# HELP strata_op_seconds Operation latency\n\
+             # TYPE strata_op_seconds histogram\n\
+             strata_op_seconds_bucket{le=\"0.1\"} 1\n\
+             strata_op_seconds_bucket{le=\"1\"} 1\n\
+             strata_op_seconds_bucket{le=\"+Inf\"} 2\n\
+             strata_op_seconds_sum 2.05\n\
+             strata_op_seconds_count 2\n\
+             # HELP strata_ops_total Operations\n\
+             # TYPE strata_ops_total counter\n\
+             strata_ops_total{op=\"read\"} 3\n"
+        );
+    }
+}
@@ -0,0 +1,120 @@
+//! Prometheus text exposition format
+//!
+//! A minimal encoder for the [text format] scraped from `/metrics`. Metric
+//! families are written one at a time: a `# HELP` and `# TYPE` header followed
+//! by its samples.
+//!
+//! [text format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use std::fmt::Write as _;
+
+/// Content type of the text exposition format
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prometheus metric types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    Counter,
+    Gauge,
+    Histogram,
+    Summary,
+}
+
+impl MetricType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            MetricType::Counter => "counter",
+            MetricType::Gauge => "gauge",
+            MetricType::Histogram => "histogram",
+            MetricType::Summary => "summary",
+        }
+    }
+}
+
+/// Builds a text format payload
+#[derive(Debug, Default)]
+pub struct PrometheusWriter {
+    out: String,
+}
+
+impl PrometheusWriter {
+    /// Create an empty payload
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a metric family
+    pub fn family(&mut self, name: &str, kind: MetricType, help: &str) -> &mut Self {
+        let help = help.replace('\\', "\\\\").replace('\n', "\\n");
+        let _ = writeln!(self.out, "# HELP {} {}", name, help);
+        let _ = writeln!(self.out, "# TYPE {} {}", name, kind.as_str());
+        self
+    }
+
+    /// Write a sample of the current family
+    pub fn sample(&mut self, name: &str, labels: &[(&str, &str)], value: f64) -> &mut Self {
+        self.out.push_str(name);
+        if !labels.is_empty() {
+            self.out.push('{');
+            for (i, (key, val)) in labels.iter().enumerate() {
+                if i > 0 {
+                    self.out.push(',');
+                }
+                let _ = write!(self.out, "{}=\"{}\"", key, escape_label(val));
+            }
+            self.out.push('}');
+        }
+        let _ = writeln!(self.out, " {}", format_value(value));
+        self
+    }
+
+    /// The encoded payload
+    pub fn finish(self) -> String {
+        self.out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+pub(crate) fn format_value(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "+Inf" } else { "-Inf" }.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer() {
+        let mut writer = PrometheusWriter::new();
+        writer
+            .family("strata_workers", MetricType::Gauge, "Registered workers")
+            .sample("strata_workers", &[("state", "idle")], 2.0)
+            .sample("strata_workers", &[("state", "a\"b\\c")], 0.5);
+        writer
+            .family("strata_up", MetricType::Counter, "Up")
+            .sample("strata_up", &[], f64::INFINITY);
+
+        assert_eq!(
+            writer.finish(),
+            "# HELP strata_workers Registered workers\n\
+             # TYPE strata_workers gauge\n\
+             strata_workers{state=\"idle\"} 2\n\
+             strata_workers{state=\"a\\\"b\\\\c\"} 0.5\n\
+             # HELP strata_up Up\n\
+             # TYPE strata_up counter\n\
+             strata_up +Inf\n"
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Resource usage sampling for heartbeats
+//!
+//! [`ResourceCollector`] reads CPU, memory, disk and network usage of the
+//! host through `sysinfo`. With the `nvml` feature it also reads GPU
+//! utilization, memory and temperature through NVIDIA's management library,
+//! falling back to no GPU metrics when the library or driver is missing.
+
+use sysinfo::{Networks, Pid, System};
+#[cfg(feature = "nvml")]
+use tracing::debug;
+
+use crate::types::{GpuMetrics, ResourceMetrics};
+
+/// Network interfaces left out of the traffic totals
+const LOOPBACK_INTERFACES: &[&str] = &["lo", "lo0"];
+
+/// Samples resource usage of this host and process
+///
+/// Disk and network figures are the traffic since the previous call to
+/// [`collect`](Self::collect), so call it once per heartbeat. CPU usage is
+/// also measured between calls and reads 0 on the first one.
+pub struct ResourceCollector {
+    system: System,
+    networks: Networks,
+    /// This process, whose disk traffic is reported
+    pid: Option<Pid>,
+    #[cfg(feature = "nvml")]
+    nvml: Option<nvml_wrapper::Nvml>,
+}
+
+impl Default for ResourceCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ResourceCollector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceCollector")
+            .field("pid", &self.pid)
+            .field("gpu_support", &self.gpu_support())
+            .finish()
+    }
+}
+
+impl ResourceCollector {
+    /// Create a collector and take the baseline sample
+    pub fn new() -> Self {
+        let mut system = System::new();
+        system.refresh_cpu_usage();
+        let pid = sysinfo::get_current_pid().ok();
+        if let Some(pid) = pid {
+            system.refresh_process(pid);
+        }
+
+        Self {
+            system,
+            networks: Networks::new_with_refreshed_list(),
+            pid,
+            #[cfg(feature = "nvml")]
+            nvml: match nvml_wrapper::Nvml::init() {
+                Ok(nvml) => Some(nvml),
+                Err(e) => {
+                    debug!(error = %e, "NVML unavailable, GPU metrics disabled");
+                    None
+                }
+            },
+        }
+    }
+
+    /// Whether GPU metrics are collected
+    pub fn gpu_support(&self) -> bool {
+        #[cfg(feature = "nvml")]
+        {
+            self.nvml.is_some()
+        }
+        #[cfg(not(feature = "nvml"))]
+        {
+            false
+        }
+    }
+
+    /// Sample current resource usage
+    pub fn collect(&mut self) -> ResourceMetrics {
+        self.system.refresh_cpu_usage();
+        self.system.refresh_memory();
+        self.networks.refresh();
+
+        let (disk_read_bytes, disk_write_bytes) = self
+            .pid
+            .filter(|pid| self.system.refresh_process(*pid))
+            .and_then(|pid| self.system.process(pid))
+            .map(|process| {
+                let usage = process.disk_usage();
+                (usage.read_bytes, usage.written_bytes)
+            })
+            .unwrap_or_default();
+
+        let (network_rx_bytes, network_tx_bytes) = self
+            .networks
+            .iter()
+            .filter(|(name, _)| !LOOPBACK_INTERFACES.contains(&name.as_str()))
+            .fold((0, 0), |(rx, tx), (_, data)| {
+                (rx + data.received(), tx + data.transmitted())
+            });
+
+        ResourceMetrics {
+            cpu_percent: self.system.global_cpu_info().cpu_usage() as f64,
+            memory_used_bytes: self.system.used_memory(),
+            gpu_metrics: self.collect_gpus(),
+            disk_read_bytes,
+            disk_write_bytes,
+            network_rx_bytes,
+            network_tx_bytes,
+        }
+    }
+
+    #[cfg(feature = "nvml")]
+    fn collect_gpus(&self) -> Vec<GpuMetrics> {
+        use nvml_wrapper::enum_wrappers::device::{
+            Clock, EccCounter, MemoryError, TemperatureSensor,
+        };
+
+        let Some(nvml) = &self.nvml else {
+            return Vec::new();
+        };
+        let count = match nvml.device_count() {
+            Ok(count) => count,
+            Err(e) => {
+                debug!(error = %e, "Failed to count GPUs");
+                return Vec::new();
+            }
+        };
+
+        (0..count)
+            .filter_map(|index| match nvml.device_by_index(index) {
+                Ok(device) => Some((index, device)),
+                Err(e) => {
+                    debug!(gpu_id = index, error = %e, "Failed to open GPU");
+                    None
+                }
+            })
+            .map(|(index, device)| {
+                let memory = device.memory_info().ok();
+                GpuMetrics {
+                    gpu_id: index,
+                    utilization_percent: device
+                        .utilization_rates()
+                        .map(|u| u.gpu as f64)
+                        .unwrap_or_default(),
+                    memory_used_bytes: memory.as_ref().map(|m| m.used).unwrap_or_default(),
+                    memory_total_bytes: memory.as_ref().map(|m| m.total).unwrap_or_default(),
+                    temperature_celsius: device
+                        .temperature(TemperatureSensor::Gpu)
+                        .map(|t| t as f64)
+                        .unwrap_or_default(),
+                    // Reported in milliwatts
+                    power_draw_watts: device
+                        .power_usage()
+                        .map(|mw| mw as f64 / 1000.0)
+                        .unwrap_or_default(),
+                    sm_clock_mhz: device.clock_info(Clock::SM).unwrap_or_default(),
+                    memory_clock_mhz: device.clock_info(Clock::Memory).unwrap_or_default(),
+                    // Unsupported without ECC memory
+                    ecc_errors_corrected: device
+                        .total_ecc_errors(MemoryError::Corrected, EccCounter::Aggregate)
+                        .unwrap_or_default(),
+                    ecc_errors_uncorrected: device
+                        .total_ecc_errors(MemoryError::Uncorrected, EccCounter::Aggregate)
+                        .unwrap_or_default(),
+                }
+            })
+            .collect()
+    }
+
+    #[cfg(not(feature = "nvml"))]
+    fn collect_gpus(&self) -> Vec<GpuMetrics> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collect() {
+        let mut collector = ResourceCollector::new();
+        std::thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        let metrics = collector.collect();
+        assert!(metrics.memory_used_bytes > 0);
+        assert!((0.0..=100.0 * 1024.0).contains(&metrics.cpu_percent));
+        if !collector.gpu_support() {
+            assert!(metrics.gpu_metrics.is_empty());
+        }
+    }
+}
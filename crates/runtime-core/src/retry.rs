@@ -0,0 +1,220 @@
+//! Retries with exponential backoff
+//!
+//! [`retry`] reruns an async operation while it fails with a retryable error,
+//! waiting longer after each attempt as configured by a [`RetryConfig`].
+//! Callers that need more control over the loop, such as failing over to
+//! another endpoint between attempts, can drive a [`Backoff`] directly.
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use tracing::warn;
+
+use crate::config::RetryConfig;
+use crate::Error;
+
+/// Largest share of the delay added as jitter
+const MAX_JITTER: f64 = 0.25;
+
+/// An error that may go away when the operation is retried
+pub trait Retryable {
+    /// Whether the operation may be retried
+    fn is_retryable(&self) -> bool;
+
+    /// Shortest delay before the next attempt
+    fn min_retry_delay(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl Retryable for Error {
+    fn is_retryable(&self) -> bool {
+        Error::is_retryable(self)
+    }
+
+    fn min_retry_delay(&self) -> Option<Duration> {
+        self.retry_delay_hint_ms().map(Duration::from_millis)
+    }
+}
+
+/// Delay before retry number `attempt`, counting from 0
+///
+/// Grows by `backoff_multiplier` per attempt up to `max_delay`, plus up to
+/// 25% jitter when enabled so clients don't retry in lockstep.
+pub fn backoff_delay(config: &RetryConfig, attempt: u32) -> Duration {
+    let base = config.initial_delay.as_secs_f64() * config.backoff_multiplier.powi(attempt as i32);
+    let capped = base.min(config.max_delay.as_secs_f64());
+
+    let jitter = if config.jitter {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos())
+            .unwrap_or(0);
+        capped * MAX_JITTER * (nanos as f64 / 1_000_000_000.0)
+    } else {
+        0.0
+    };
+
+    Duration::from_secs_f64(capped + jitter)
+}
+
+/// Delays between the attempts of one operation
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    config: RetryConfig,
+    retries: u32,
+}
+
+impl Backoff {
+    /// Start backing off according to `config`
+    pub fn new(config: RetryConfig) -> Self {
+        Self { config, retries: 0 }
+    }
+
+    /// Retries handed out so far
+    pub fn retries(&self) -> u32 {
+        self.retries
+    }
+
+    /// Delay before the next retry, or `None` once `max_retries` are used up
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.retries >= self.config.max_retries {
+            return None;
+        }
+        let delay = backoff_delay(&self.config, self.retries);
+        self.retries += 1;
+        Some(delay)
+    }
+}
+
+/// Run `op` until it succeeds, fails with an error that is not retryable, or
+/// the retries in `config` are used up
+///
+/// `operation` names the operation in logs.
+///
+/// # Example
+///
+/// ```no_run
+/// use runtime_core::config::RetryConfig;
+/// use runtime_core::retry::retry;
+/// use runtime_core::Error;
+///
+/// # async fn example() -> runtime_core::Result<()> {
+/// let body = retry(&RetryConfig::default(), "fetch", || async {
+///     Err::<String, _>(Error::storage("connection reset"))
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry<T, E, F, Fut>(config: &RetryConfig, operation: &str, mut op: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Retryable + Display,
+{
+    let mut backoff = Backoff::new(config.clone());
+    loop {
+        let error = match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if e.is_retryable() => e,
+            Err(e) => return Err(e),
+        };
+
+        let Some(delay) = backoff.next_delay() else {
+            warn!(
+                %operation,
+                attempts = backoff.retries() + 1,
+                error = %error,
+                "Retries exhausted"
+            );
+            return Err(error);
+        };
+        let delay = error.min_retry_delay().map_or(delay, |min| delay.max(min));
+        warn!(
+            %operation,
+            attempt = backoff.retries(),
+            max_retries = config.max_retries,
+            delay_ms = delay.as_millis() as u64,
+            error = %error,
+            "Retrying after transient error"
+        );
+        tokio::time::sleep(delay).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn config(max_retries: u32) -> RetryConfig {
+        RetryConfig {
+            max_retries,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(4),
+            backoff_multiplier: 2.0,
+            jitter: false,
+        }
+    }
+
+    #[test]
+    fn test_backoff() {
+        let retry = RetryConfig {
+            jitter: false,
+            ..Default::default()
+        };
+        assert_eq!(backoff_delay(&retry, 0), Duration::from_millis(100));
+        assert_eq!(backoff_delay(&retry, 2), Duration::from_millis(400));
+        assert_eq!(backoff_delay(&retry, 20), retry.max_delay);
+
+        let jittered = RetryConfig {
+            jitter: true,
+            ..retry.clone()
+        };
+        let delay = backoff_delay(&jittered, 0);
+        assert!(delay >= Duration::from_millis(100) && delay <= Duration::from_millis(125));
+
+        let mut backoff = Backoff::new(config(3));
+        let delays: Vec<_> = std::iter::from_fn(|| backoff.next_delay()).collect();
+        assert_eq!(delays, [1, 2, 4].map(Duration::from_millis).to_vec());
+        assert_eq!(backoff.retries(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry() {
+        // Succeeds on the third attempt
+        let attempts = AtomicU32::new(0);
+        let result = retry(&config(3), "flaky", || async {
+            match attempts.fetch_add(1, Ordering::SeqCst) {
+                0 | 1 => Err(Error::storage("connection reset")),
+                n => Ok(n),
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 2);
+
+        // Gives up after max_retries
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&config(2), "down", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::storage("connection reset"))
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        // Permanent errors are returned at once
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = retry(&config(3), "missing", || async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Err(Error::StoragePathNotFound {
+                path: "a.bin".to_string(),
+            })
+        })
+        .await;
+        assert!(matches!(result, Err(Error::StoragePathNotFound { .. })));
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
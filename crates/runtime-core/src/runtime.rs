@@ -1,5 +1,6 @@
 //! Async runtime manager
 
+use crate::supervisor::{run_periodic, RestartPolicy, Supervisor, TaskStatus};
 use crate::{Error, Result, RuntimeConfig, WorkerRegistry, WorkerRegistryHandle};
 use std::sync::Arc;
 use std::time::Duration;
@@ -26,11 +27,18 @@ pub struct RuntimeManager {
 
     /// Shutdown signal sender
     shutdown_tx: ShutdownSender,
+
+    /// Background tasks
+    supervisor: Supervisor,
 }
 
 impl RuntimeManager {
     /// Create a new runtime manager
+    ///
+    /// Fails with [`Error::InvalidConfig`] listing every invalid setting.
     pub fn new(config: RuntimeConfig) -> Result<Self> {
+        config.validate()?;
+
         let runtime = Builder::new_multi_thread()
             .worker_threads(config.worker.io_threads)
             .enable_all()
@@ -40,18 +48,23 @@ impl RuntimeManager {
                 message: format!("Failed to build Tokio runtime: {}", e),
             })?;
 
-        let worker_registry = Arc::new(WorkerRegistry::new(
-            config.coordinator.max_workers,
-            config.coordinator.heartbeat_timeout,
-        ));
+        let worker_registry = Arc::new(
+            WorkerRegistry::new(
+                config.coordinator.max_workers,
+                config.coordinator.heartbeat_timeout,
+            )
+            .with_rank_policy(config.coordinator.rank_policy),
+        );
 
         let (shutdown_tx, _) = broadcast::channel(1);
+        let supervisor = Supervisor::new(runtime.handle().clone());
 
         Ok(Self {
             runtime: Some(runtime),
             config,
             worker_registry,
             shutdown_tx,
+            supervisor,
         })
     }
 
@@ -80,12 +93,25 @@ impl RuntimeManager {
         self.shutdown_tx.subscribe()
     }
 
+    /// Supervisor running the runtime's background tasks
+    pub fn supervisor(&self) -> &Supervisor {
+        &self.supervisor
+    }
+
     /// Signal shutdown to all components
     pub fn shutdown(&self) {
         info!("Initiating runtime shutdown");
         let _ = self.shutdown_tx.send(());
     }
 
+    /// Signal shutdown, then stop the background tasks newest first
+    ///
+    /// Each task gets `timeout` to stop before it is aborted.
+    pub async fn shutdown_gracefully(&self, timeout: Duration) -> Vec<TaskStatus> {
+        self.shutdown();
+        self.supervisor.shutdown(timeout).await
+    }
+
     /// Block on a future until completion
     pub fn block_on<F: std::future::Future>(&self, future: F) -> F::Output {
         self.runtime().block_on(future)
@@ -100,31 +126,37 @@ impl RuntimeManager {
         self.runtime().spawn(future)
     }
 
-    /// Run the dead worker check loop
-    pub async fn run_dead_worker_check(&self) {
+    /// Start the supervised loop removing workers that stopped heartbeating
+    pub fn spawn_dead_worker_check(&self) {
         let registry = self.worker_registry();
         let interval = self.config.coordinator.dead_worker_check_interval;
-        let mut shutdown_rx = self.shutdown_receiver();
 
         info!(
             interval_secs = interval.as_secs(),
             "Starting dead worker check loop"
         );
 
-        loop {
-            tokio::select! {
-                _ = tokio::time::sleep(interval) => {
-                    let dead = registry.check_dead_workers();
-                    if !dead.is_empty() {
-                        info!(count = dead.len(), "Detected dead workers");
-                    }
-                }
-                _ = shutdown_rx.recv() => {
-                    info!("Dead worker check loop shutting down");
-                    break;
+        self.supervisor.spawn(
+            "dead-worker-check",
+            RestartPolicy::OnFailure {
+                max_restarts: 5,
+                backoff: Duration::from_secs(1),
+            },
+            move |shutdown| {
+                let registry = registry.clone();
+                async move {
+                    run_periodic(interval, shutdown, || {
+                        let dead = registry.check_dead_workers();
+                        if !dead.is_empty() {
+                            info!(count = dead.len(), "Detected dead workers");
+                        }
+                        registry.compact_ranks_if_settled();
+                    })
+                    .await;
+                    Ok(())
                 }
-            }
-        }
+            },
+        );
     }
 }
 
@@ -216,4 +248,15 @@ mod tests {
 
         assert_eq!(result, 42);
     }
+
+    #[test]
+    fn test_graceful_shutdown() {
+        let manager = RuntimeManagerBuilder::new().build().unwrap();
+        manager.spawn_dead_worker_check();
+
+        let stopped = manager.block_on(manager.shutdown_gracefully(Duration::from_secs(1)));
+        assert_eq!(stopped.len(), 1);
+        assert_eq!(stopped[0].name, "dead-worker-check");
+        assert_eq!(stopped[0].state, crate::supervisor::TaskState::Stopped);
+    }
 }
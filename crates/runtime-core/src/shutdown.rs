@@ -0,0 +1,191 @@
+//! Graceful shutdown
+//!
+//! A [`ShutdownToken`] is shared by everything that has to stop when the
+//! process exits: servers, the checkpoint writer and background tasks.
+//! [`wait_for_signal`] waits for Ctrl+C or SIGTERM so every binary handles
+//! them the same way.
+
+use std::fmt;
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::signal;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::supervisor::ShutdownSignal;
+
+/// A process signal asking for shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// Ctrl+C or SIGINT
+    Interrupt,
+    /// SIGTERM
+    Terminate,
+}
+
+impl fmt::Display for Signal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Signal::Interrupt => write!(f, "SIGINT"),
+            Signal::Terminate => write!(f, "SIGTERM"),
+        }
+    }
+}
+
+/// Wait for Ctrl+C or SIGTERM
+///
+/// A signal whose handler cannot be installed is logged and never fires.
+pub async fn wait_for_signal() -> Signal {
+    let ctrl_c = async {
+        if let Err(e) = signal::ctrl_c().await {
+            error!(error = %e, "Failed to install Ctrl+C handler");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(signal::unix::SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                error!(error = %e, "Failed to install SIGTERM handler");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    let signal = tokio::select! {
+        _ = ctrl_c => Signal::Interrupt,
+        _ = terminate => Signal::Terminate,
+    };
+    info!(signal = %signal, "Received signal, initiating graceful shutdown");
+    signal
+}
+
+/// Requests and observes process shutdown
+///
+/// Clones share the same state. Shutdown happens once; later requests are
+/// ignored and the first reason is kept.
+///
+/// # Example
+///
+/// ```
+/// use runtime_core::ShutdownToken;
+///
+/// let token = ShutdownToken::new();
+/// let observer = token.clone();
+/// assert!(token.shutdown("maintenance"));
+/// assert!(observer.is_shutdown());
+/// assert_eq!(observer.reason().as_deref(), Some("maintenance"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownToken {
+    state: Arc<watch::Sender<bool>>,
+    reason: Arc<Mutex<Option<String>>>,
+}
+
+impl ShutdownToken {
+    /// Create a token that has not been shut down
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request shutdown
+    ///
+    /// Returns `false` if shutdown had already been requested.
+    pub fn shutdown(&self, reason: &str) -> bool {
+        self.state.send_if_modified(|shutdown| {
+            if *shutdown {
+                return false;
+            }
+            *self.reason.lock() = Some(reason.to_string());
+            *shutdown = true;
+            true
+        })
+    }
+
+    /// Whether shutdown has been requested
+    pub fn is_shutdown(&self) -> bool {
+        *self.state.borrow()
+    }
+
+    /// Why shutdown was requested
+    pub fn reason(&self) -> Option<String> {
+        self.reason.lock().clone()
+    }
+
+    /// Wait until shutdown is requested
+    pub async fn wait(&self) {
+        let mut rx = self.state.subscribe();
+        let _ = rx.wait_for(|shutdown| *shutdown).await;
+    }
+
+    /// A [`ShutdownSignal`] that fires with this token
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal(self.state.subscribe())
+    }
+
+    /// Request shutdown on the first Ctrl+C or SIGTERM
+    pub fn shutdown_on_signal(&self) -> JoinHandle<()> {
+        let token = self.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                signal = wait_for_signal() => {
+                    if !token.shutdown(&format!("{signal} received")) {
+                        warn!(signal = %signal, "Already shutting down");
+                    }
+                }
+                _ = token.wait() => {}
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_first_shutdown_wins() {
+        let token = ShutdownToken::new();
+        assert!(!token.is_shutdown());
+        assert_eq!(token.reason(), None);
+
+        assert!(token.clone().shutdown("first"));
+        assert!(!token.shutdown("second"));
+        assert!(token.is_shutdown());
+        assert_eq!(token.reason().as_deref(), Some("first"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_and_signal() {
+        let token = ShutdownToken::new();
+        let mut signal = token.signal();
+        let waiter = tokio::spawn({
+            let token = token.clone();
+            async move { token.wait().await }
+        });
+        assert!(!signal.is_shutdown());
+
+        token.shutdown("test");
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+        tokio::time::timeout(Duration::from_secs(1), signal.wait())
+            .await
+            .unwrap();
+        assert!(signal.is_shutdown());
+
+        // Already shut down, returns at once
+        token.wait().await;
+    }
+}
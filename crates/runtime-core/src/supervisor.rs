@@ -0,0 +1,369 @@
+//! Supervised background tasks
+//!
+//! A [`Supervisor`] runs named long-lived tasks such as reapers, garbage
+//! collectors and samplers. Panics are caught and reported like errors, failed
+//! tasks are restarted according to their [`RestartPolicy`], and shutdown stops
+//! the tasks one at a time, newest first, so later tasks can rely on the ones
+//! started before them.
+
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use parking_lot::Mutex;
+use tokio::runtime::Handle;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::Result;
+
+/// What to do when a supervised task ends before shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Leave the task stopped
+    Never,
+    /// Restart after a panic or error, at most `max_restarts` times
+    OnFailure {
+        max_restarts: u32,
+        backoff: Duration,
+    },
+    /// Restart whenever the task ends
+    Always { backoff: Duration },
+}
+
+impl RestartPolicy {
+    /// Delay before the next attempt, or `None` to leave the task stopped
+    fn restart_after(&self, failed: bool, restarts: u32) -> Option<Duration> {
+        match *self {
+            RestartPolicy::Never => None,
+            RestartPolicy::OnFailure {
+                max_restarts,
+                backoff,
+            } => (failed && restarts < max_restarts).then_some(backoff),
+            RestartPolicy::Always { backoff } => Some(backoff),
+        }
+    }
+}
+
+/// Tells a supervised task to stop
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal(pub(crate) watch::Receiver<bool>);
+
+impl ShutdownSignal {
+    /// Whether the task has been asked to stop
+    pub fn is_shutdown(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until the task is asked to stop
+    pub async fn wait(&mut self) {
+        let _ = self.0.wait_for(|stop| *stop).await;
+    }
+}
+
+/// Run `tick` every `interval` until shutdown, starting immediately
+pub async fn run_periodic(
+    interval: Duration,
+    mut shutdown: ShutdownSignal,
+    mut tick: impl FnMut(),
+) {
+    let mut ticker = tokio::time::interval(interval);
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => tick(),
+            _ = shutdown.wait() => break,
+        }
+    }
+}
+
+/// Lifecycle state of a supervised task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    Running,
+    /// Waiting out the backoff before the next attempt
+    Restarting,
+    /// Ended on its own or stopped at shutdown
+    Stopped,
+    /// Failed and not restarted
+    Failed,
+    /// Did not stop within the shutdown timeout
+    Aborted,
+}
+
+/// Status of a supervised task
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    /// Times the task has been restarted
+    pub restarts: u32,
+    /// Error or panic message of the last failed attempt
+    pub last_error: Option<String>,
+}
+
+struct SupervisedTask {
+    stop: watch::Sender<bool>,
+    join: JoinHandle<()>,
+    status: Arc<Mutex<TaskStatus>>,
+}
+
+/// Runs and restarts named background tasks
+pub struct Supervisor {
+    handle: Handle,
+    /// In spawn order
+    tasks: Mutex<Vec<SupervisedTask>>,
+}
+
+impl Supervisor {
+    /// Create a supervisor spawning tasks on the given runtime
+    pub fn new(handle: Handle) -> Self {
+        Self {
+            handle,
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Start a supervised task
+    ///
+    /// `task` is called again for every restart. It should return once its
+    /// [`ShutdownSignal`] fires.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, policy: RestartPolicy, task: F)
+    where
+        F: Fn(ShutdownSignal) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<()>> + Send + 'static,
+    {
+        let name = name.into();
+        let (stop, stop_rx) = watch::channel(false);
+        let status = Arc::new(Mutex::new(TaskStatus {
+            name: name.clone(),
+            state: TaskState::Running,
+            restarts: 0,
+            last_error: None,
+        }));
+
+        let join = self.handle.spawn(supervise(
+            self.handle.clone(),
+            name.clone(),
+            policy,
+            task,
+            ShutdownSignal(stop_rx),
+            status.clone(),
+        ));
+        info!(task = %name, "Started background task");
+
+        self.tasks
+            .lock()
+            .push(SupervisedTask { stop, join, status });
+    }
+
+    /// Status of every task, in spawn order
+    pub fn tasks(&self) -> Vec<TaskStatus> {
+        self.tasks
+            .lock()
+            .iter()
+            .map(|task| task.status.lock().clone())
+            .collect()
+    }
+
+    /// Stop every task, newest first
+    ///
+    /// Each task gets `timeout` to return after being signalled before it is
+    /// aborted. Returns the final status of each task in the order stopped.
+    pub async fn shutdown(&self, timeout: Duration) -> Vec<TaskStatus> {
+        let tasks = std::mem::take(&mut *self.tasks.lock());
+        let mut stopped = Vec::with_capacity(tasks.len());
+
+        for mut task in tasks.into_iter().rev() {
+            let _ = task.stop.send(true);
+            let name = task.status.lock().name.clone();
+            match tokio::time::timeout(timeout, &mut task.join).await {
+                Ok(_) => {
+                    let mut status = task.status.lock();
+                    if status.state != TaskState::Failed {
+                        status.state = TaskState::Stopped;
+                    }
+                    info!(task = %name, "Stopped background task");
+                }
+                Err(_) => {
+                    task.join.abort();
+                    task.status.lock().state = TaskState::Aborted;
+                    warn!(
+                        task = %name,
+                        timeout_ms = timeout.as_millis() as u64,
+                        "Background task did not stop in time, aborted"
+                    );
+                }
+            }
+            stopped.push(task.status.lock().clone());
+        }
+
+        stopped
+    }
+}
+
+/// Aborts an attempt when the supervising task is dropped or aborted
+struct AbortOnDrop(JoinHandle<Result<()>>);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+async fn supervise<F, Fut>(
+    handle: Handle,
+    name: String,
+    policy: RestartPolicy,
+    task: F,
+    mut shutdown: ShutdownSignal,
+    status: Arc<Mutex<TaskStatus>>,
+) where
+    F: Fn(ShutdownSignal) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<()>> + Send + 'static,
+{
+    loop {
+        status.lock().state = TaskState::Running;
+        let mut attempt = AbortOnDrop(handle.spawn(task(shutdown.clone())));
+        let error = match (&mut attempt.0).await {
+            Ok(Ok(())) => None,
+            Ok(Err(e)) => Some(e.to_string()),
+            Err(e) if e.is_panic() => Some(format!("panicked: {}", panic_message(e.into_panic()))),
+            Err(e) => Some(e.to_string()),
+        };
+
+        if shutdown.is_shutdown() {
+            status.lock().state = TaskState::Stopped;
+            return;
+        }
+
+        let restarts = {
+            let mut status = status.lock();
+            if let Some(error) = &error {
+                error!(task = %name, error = %error, "Background task failed");
+                status.last_error = Some(error.clone());
+            }
+            status.restarts
+        };
+
+        let Some(backoff) = policy.restart_after(error.is_some(), restarts) else {
+            status.lock().state = if error.is_some() {
+                TaskState::Failed
+            } else {
+                TaskState::Stopped
+            };
+            warn!(task = %name, "Background task ended and will not be restarted");
+            return;
+        };
+
+        {
+            let mut status = status.lock();
+            status.state = TaskState::Restarting;
+            status.restarts += 1;
+        }
+        warn!(
+            task = %name,
+            restarts = restarts + 1,
+            backoff_ms = backoff.as_millis() as u64,
+            "Restarting background task"
+        );
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = shutdown.wait() => {
+                status.lock().state = TaskState::Stopped;
+                return;
+            }
+        }
+    }
+}
+
+fn panic_message(panic: Box<dyn Any + Send>) -> String {
+    if let Some(message) = panic.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = panic.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_restarts_after_panic_and_error() {
+        let supervisor = Supervisor::new(Handle::current());
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let counter = attempts.clone();
+        supervisor.spawn(
+            "flaky",
+            RestartPolicy::OnFailure {
+                max_restarts: 2,
+                backoff: Duration::from_millis(1),
+            },
+            move |_| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst);
+                async move {
+                    match attempt {
+                        0 => panic!("boom"),
+                        _ => Err(Error::Internal {
+                            message: "still broken".to_string(),
+                        }),
+                    }
+                }
+            },
+        );
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let status = &supervisor.tasks()[0];
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert_eq!(status.state, TaskState::Failed);
+        assert_eq!(status.restarts, 2);
+        assert!(status
+            .last_error
+            .as_deref()
+            .unwrap()
+            .contains("still broken"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_newest_first() {
+        let supervisor = Supervisor::new(Handle::current());
+        let order = Arc::new(Mutex::new(Vec::new()));
+
+        for name in ["reaper", "sampler"] {
+            let order = order.clone();
+            supervisor.spawn(name, RestartPolicy::Never, move |mut shutdown| {
+                let order = order.clone();
+                async move {
+                    shutdown.wait().await;
+                    order.lock().push(name);
+                    Ok(())
+                }
+            });
+        }
+        supervisor.spawn("stuck", RestartPolicy::Never, |_| {
+            std::future::pending::<Result<()>>()
+        });
+
+        let stopped = supervisor.shutdown(Duration::from_millis(50)).await;
+        let states: Vec<_> = stopped.iter().map(|s| (s.name.as_str(), s.state)).collect();
+        assert_eq!(
+            states,
+            vec![
+                ("stuck", TaskState::Aborted),
+                ("sampler", TaskState::Stopped),
+                ("reaper", TaskState::Stopped),
+            ]
+        );
+        assert_eq!(*order.lock(), vec!["sampler", "reaper"]);
+        assert!(supervisor.tasks().is_empty());
+    }
+}
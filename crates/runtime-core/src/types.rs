@@ -2,13 +2,165 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::borrow::Borrow;
 use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// Defines a validated string identifier
+///
+/// IDs may only contain ASCII letters, digits, `-`, `_` and `.`, so they are
+/// safe to use in file names and URLs. They serialize as plain strings.
+macro_rules! string_id {
+    ($(#[$doc:meta])* $name:ident, $kind:literal, $max_len:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            /// Longest allowed ID in bytes
+            pub const MAX_LEN: usize = $max_len;
+
+            /// Validate an ID
+            pub fn new(id: impl Into<String>) -> Result<Self> {
+                let id = id.into();
+                validate_id($kind, &id, Self::MAX_LEN)?;
+                Ok(Self(id))
+            }
+
+            /// The ID as a string slice
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+
+            /// The ID as an owned string
+            pub fn into_inner(self) -> String {
+                self.0
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = Error;
+
+            fn try_from(id: String) -> Result<Self> {
+                Self::new(id)
+            }
+        }
+
+        impl TryFrom<&str> for $name {
+            type Error = Error;
+
+            fn try_from(id: &str) -> Result<Self> {
+                Self::new(id)
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(id: &str) -> Result<Self> {
+                Self::new(id)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> Self {
+                id.0
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialEq<String> for $name {
+            fn eq(&self, other: &String) -> bool {
+                &self.0 == other
+            }
+        }
+
+        impl PartialEq<$name> for String {
+            fn eq(&self, other: &$name) -> bool {
+                self == &other.0
+            }
+        }
+    };
+}
+
+fn validate_id(kind: &'static str, id: &str, max_len: usize) -> Result<()> {
+    let reason = if id.is_empty() {
+        "must not be empty".to_string()
+    } else if id.len() > max_len {
+        format!("must be at most {} characters", max_len)
+    } else if let Some(c) = id
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        format!(
+            "contains {:?}, only letters, digits, '-', '_' and '.' are allowed",
+            c
+        )
+    } else {
+        return Ok(());
+    };
+    Err(Error::InvalidId {
+        kind,
+        id: id.to_string(),
+        reason,
+    })
+}
+
+string_id!(
+    /// Identifies a worker
+    WorkerId,
+    "worker",
+    128
+);
+string_id!(
+    /// Identifies a registered dataset
+    DatasetId,
+    "dataset",
+    256
+);
+string_id!(
+    /// Identifies a checkpoint, also used in its file name
+    CheckpointId,
+    "checkpoint",
+    256
+);
 
 /// Unique identifier types
-pub type WorkerId = String;
-pub type DatasetId = String;
 pub type ShardId = u64;
-pub type CheckpointId = String;
 pub type BarrierId = String;
 pub type JobId = String;
 
@@ -207,6 +359,58 @@ pub struct ResourceMetrics {
     pub network_tx_bytes: u64,
 }
 
+impl ResourceMetrics {
+    /// Totals and averages over this report's GPUs
+    pub fn gpu_summary(&self) -> GpuSummary {
+        self.gpu_metrics.iter().collect()
+    }
+
+    /// Disk and network throughput, when this report covers `elapsed`
+    pub fn rates_over(&self, elapsed: Duration) -> ResourceRates {
+        let secs = elapsed.as_secs_f64();
+        if secs <= 0.0 {
+            return ResourceRates::default();
+        }
+        ResourceRates {
+            disk_read_bytes_per_sec: self.disk_read_bytes as f64 / secs,
+            disk_write_bytes_per_sec: self.disk_write_bytes as f64 / secs,
+            network_rx_bytes_per_sec: self.network_rx_bytes as f64 / secs,
+            network_tx_bytes_per_sec: self.network_tx_bytes as f64 / secs,
+        }
+    }
+}
+
+/// Disk and network throughput
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceRates {
+    pub disk_read_bytes_per_sec: f64,
+    pub disk_write_bytes_per_sec: f64,
+    pub network_rx_bytes_per_sec: f64,
+    pub network_tx_bytes_per_sec: f64,
+}
+
+impl std::ops::Add for ResourceRates {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            disk_read_bytes_per_sec: self.disk_read_bytes_per_sec + other.disk_read_bytes_per_sec,
+            disk_write_bytes_per_sec: self.disk_write_bytes_per_sec
+                + other.disk_write_bytes_per_sec,
+            network_rx_bytes_per_sec: self.network_rx_bytes_per_sec
+                + other.network_rx_bytes_per_sec,
+            network_tx_bytes_per_sec: self.network_tx_bytes_per_sec
+                + other.network_tx_bytes_per_sec,
+        }
+    }
+}
+
+impl std::iter::Sum for ResourceRates {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, rates| total + rates)
+    }
+}
+
 /// GPU metrics
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct GpuMetrics {
@@ -224,6 +428,73 @@ pub struct GpuMetrics {
 
     /// GPU temperature in Celsius
     pub temperature_celsius: f64,
+
+    /// Power draw in watts
+    #[serde(default)]
+    pub power_draw_watts: f64,
+
+    /// Streaming multiprocessor clock in MHz
+    #[serde(default)]
+    pub sm_clock_mhz: u32,
+
+    /// Memory clock in MHz
+    #[serde(default)]
+    pub memory_clock_mhz: u32,
+
+    /// ECC errors corrected over the lifetime of the GPU
+    #[serde(default)]
+    pub ecc_errors_corrected: u64,
+
+    /// ECC errors that could not be corrected over the lifetime of the GPU
+    #[serde(default)]
+    pub ecc_errors_uncorrected: u64,
+}
+
+/// GPU metrics summed or averaged over a set of GPUs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuSummary {
+    pub gpu_count: u32,
+    /// Mean utilization percentage
+    pub avg_utilization_percent: f64,
+    pub memory_used_bytes: u64,
+    pub memory_total_bytes: u64,
+    pub power_draw_watts: f64,
+    /// Hottest GPU in Celsius
+    pub max_temperature_celsius: f64,
+    pub ecc_errors_corrected: u64,
+    pub ecc_errors_uncorrected: u64,
+}
+
+impl GpuSummary {
+    /// Share of GPU memory in use (0.0 - 1.0)
+    pub fn memory_utilization(&self) -> f64 {
+        if self.memory_total_bytes == 0 {
+            return 0.0;
+        }
+        self.memory_used_bytes as f64 / self.memory_total_bytes as f64
+    }
+}
+
+impl<'a> FromIterator<&'a GpuMetrics> for GpuSummary {
+    fn from_iter<I: IntoIterator<Item = &'a GpuMetrics>>(gpus: I) -> Self {
+        let mut summary = Self::default();
+        let mut total_utilization = 0.0;
+        for gpu in gpus {
+            summary.gpu_count += 1;
+            total_utilization += gpu.utilization_percent;
+            summary.memory_used_bytes += gpu.memory_used_bytes;
+            summary.memory_total_bytes += gpu.memory_total_bytes;
+            summary.power_draw_watts += gpu.power_draw_watts;
+            summary.max_temperature_celsius =
+                summary.max_temperature_celsius.max(gpu.temperature_celsius);
+            summary.ecc_errors_corrected += gpu.ecc_errors_corrected;
+            summary.ecc_errors_uncorrected += gpu.ecc_errors_uncorrected;
+        }
+        if summary.gpu_count > 0 {
+            summary.avg_utilization_percent = total_utilization / summary.gpu_count as f64;
+        }
+        summary
+    }
 }
 
 /// Training progress summary
@@ -261,13 +532,37 @@ pub struct TrainingProgress {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_string_ids() {
+        let id = WorkerId::new("gpu-node_0.rank-3").unwrap();
+        assert_eq!(id, "gpu-node_0.rank-3");
+        assert_eq!(serde_json::to_string(&id).unwrap(), "\"gpu-node_0.rank-3\"");
+
+        let mut owners: HashMap<WorkerId, u32> = HashMap::new();
+        owners.insert(id, 3);
+        assert_eq!(owners.get("gpu-node_0.rank-3"), Some(&3));
+
+        for bad in ["", "../etc/passwd", "worker 1", "ckpt\n"] {
+            assert!(matches!(
+                CheckpointId::new(bad),
+                Err(Error::InvalidId {
+                    kind: "checkpoint",
+                    ..
+                })
+            ));
+        }
+        assert!(WorkerId::new("w".repeat(WorkerId::MAX_LEN + 1)).is_err());
+        let err = serde_json::from_str::<DatasetId>("\"images/train\"").unwrap_err();
+        assert!(err.to_string().contains("Invalid dataset ID"));
+    }
+
     #[test]
     fn test_barrier_state() {
         let mut barrier = BarrierState::new("barrier-1".to_string(), 100, 3);
 
-        assert!(!barrier.arrive("worker-1".to_string()));
-        assert!(!barrier.arrive("worker-2".to_string()));
-        assert!(barrier.arrive("worker-3".to_string()));
+        assert!(!barrier.arrive("worker-1".parse().unwrap()));
+        assert!(!barrier.arrive("worker-2".parse().unwrap()));
+        assert!(barrier.arrive("worker-3".parse().unwrap()));
         assert!(barrier.released);
         assert_eq!(barrier.arrival_order("worker-1"), Some(1));
         assert_eq!(barrier.arrival_order("worker-2"), Some(2));
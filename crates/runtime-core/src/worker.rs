@@ -1,15 +1,28 @@
 //! Worker state and registry management
 
-use crate::{Error, ResourceMetrics, Result, WorkerId};
+use crate::clock::{SharedClock, SystemClock};
+use crate::config::RankPolicy;
+use crate::id;
+use crate::labels::{LabelSelector, Taint};
+use crate::{Error, GpuSummary, ResourceMetrics, ResourceRates, Result, WorkerId};
 use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
 use tracing::{info, warn};
 
+/// Membership events buffered per subscriber before it starts lagging
+const MEMBERSHIP_EVENT_CAPACITY: usize = 1024;
+
+/// Heartbeats kept per worker for throughput, about 30s at the default 5s
+/// heartbeat
+pub const RESOURCE_WINDOW_SIZE: usize = 7;
+
 /// Worker state enumeration
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub enum WorkerState {
@@ -93,6 +106,11 @@ pub struct WorkerInfo {
     /// Registration timestamp
     pub registered_at: DateTime<Utc>,
 
+    /// Token issued on registration; a worker that re-registers under the
+    /// same ID gets a new one, so requests from the old process are rejected
+    #[serde(default)]
+    pub session: u64,
+
     /// Current training step
     pub current_step: u64,
 
@@ -105,8 +123,20 @@ pub struct WorkerInfo {
     /// Resource metrics
     pub resources: ResourceMetrics,
 
+    /// Recent heartbeats, for disk and network throughput
+    #[serde(skip)]
+    pub resource_window: ResourceWindow,
+
     /// Additional metadata
     pub metadata: HashMap<String, String>,
+
+    /// Labels selectors match on, e.g. `gpu=a100`
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+
+    /// Keep the worker out of selectors that don't tolerate them
+    #[serde(default)]
+    pub taints: Vec<Taint>,
 }
 
 impl WorkerInfo {
@@ -124,23 +154,43 @@ impl WorkerInfo {
             state: WorkerState::Initializing,
             last_heartbeat: now,
             registered_at: now,
+            session: 0,
             current_step: 0,
             current_epoch: 0,
             current_task: String::new(),
             resources: ResourceMetrics::default(),
+            resource_window: ResourceWindow::default(),
             metadata: HashMap::new(),
+            labels: HashMap::new(),
+            taints: Vec::new(),
         }
     }
 
     /// Update heartbeat timestamp and resources
     pub fn heartbeat(&mut self, resources: ResourceMetrics) {
-        self.last_heartbeat = Utc::now();
+        self.heartbeat_at(resources, Utc::now());
+    }
+
+    /// Record a heartbeat received at `at`
+    pub fn heartbeat_at(&mut self, resources: ResourceMetrics, at: DateTime<Utc>) {
+        self.last_heartbeat = at;
+        self.resource_window.push(at, &resources);
         self.resources = resources;
     }
 
+    /// Whether the worker matches `selector` and it tolerates every taint
+    pub fn selected_by(&self, selector: &LabelSelector) -> bool {
+        selector.matches(&self.labels) && selector.tolerates(&self.taints)
+    }
+
     /// Check if worker is considered dead based on timeout
     pub fn is_dead(&self, timeout: Duration) -> bool {
-        let elapsed = Utc::now()
+        self.is_dead_at(timeout, Utc::now())
+    }
+
+    /// Check if worker is considered dead at time `now`
+    pub fn is_dead_at(&self, timeout: Duration, now: DateTime<Utc>) -> bool {
+        let elapsed = now
             .signed_duration_since(self.last_heartbeat)
             .to_std()
             .unwrap_or(Duration::MAX);
@@ -156,6 +206,163 @@ impl WorkerInfo {
     }
 }
 
+/// Byte counters of the last few heartbeats of a worker
+///
+/// Each heartbeat reports the bytes moved since the previous one, so the
+/// throughput over the window is the bytes of every heartbeat but the oldest,
+/// divided by the time between the oldest and the newest.
+#[derive(Debug, Clone, Default)]
+pub struct ResourceWindow {
+    /// Oldest first
+    samples: VecDeque<(DateTime<Utc>, ResourceMetrics)>,
+}
+
+impl ResourceWindow {
+    /// Add a heartbeat, dropping the oldest once the window is full
+    pub fn push(&mut self, at: DateTime<Utc>, resources: &ResourceMetrics) {
+        if self.samples.len() >= RESOURCE_WINDOW_SIZE {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((
+            at,
+            ResourceMetrics {
+                gpu_metrics: Vec::new(),
+                ..resources.clone()
+            },
+        ));
+    }
+
+    /// Throughput over the window, unset until two heartbeats arrived
+    pub fn rates(&self) -> Option<ResourceRates> {
+        let (first, _) = self.samples.front()?;
+        let (last, _) = self.samples.back()?;
+        let elapsed = last.signed_duration_since(*first).to_std().ok()?;
+        if elapsed.is_zero() {
+            return None;
+        }
+
+        let mut total = ResourceMetrics::default();
+        for (_, resources) in self.samples.iter().skip(1) {
+            total.disk_read_bytes += resources.disk_read_bytes;
+            total.disk_write_bytes += resources.disk_write_bytes;
+            total.network_rx_bytes += resources.network_rx_bytes;
+            total.network_tx_bytes += resources.network_tx_bytes;
+        }
+        Some(total.rates_over(elapsed))
+    }
+}
+
+/// Criteria for [`WorkerRegistry::find_workers`]
+///
+/// An empty query matches every worker.
+///
+/// # Example
+///
+/// ```
+/// use runtime_core::worker::WorkerQuery;
+/// use runtime_core::WorkerState;
+///
+/// let query = WorkerQuery::new()
+///     .min_gpus(8)
+///     .in_state(WorkerState::Idle)
+///     .label("gpu", "a100");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorkerQuery {
+    min_gpus: u32,
+    /// Allowed states, any state when empty
+    states: Vec<WorkerState>,
+    excluded_states: Vec<WorkerState>,
+    /// Labels the worker must have
+    labels: HashMap<String, String>,
+    /// Selector the worker must match, taints included
+    selector: Option<LabelSelector>,
+}
+
+impl WorkerQuery {
+    /// Create a query matching every worker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only workers with at least `count` GPUs
+    pub fn min_gpus(mut self, count: u32) -> Self {
+        self.min_gpus = count;
+        self
+    }
+
+    /// Allow workers in `state`; once called, workers in other states are skipped
+    pub fn in_state(mut self, state: WorkerState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    /// Skip workers in `state`
+    pub fn not_in_state(mut self, state: WorkerState) -> Self {
+        self.excluded_states.push(state);
+        self
+    }
+
+    /// Only workers with label `key` set to `value`
+    pub fn label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
+    /// Only workers selected by `selector`
+    ///
+    /// Unlike the other criteria this also skips workers with taints the
+    /// selector doesn't tolerate, so use it when placing work.
+    pub fn selector(mut self, selector: LabelSelector) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    /// Whether `worker` meets every criterion
+    pub fn matches(&self, worker: &WorkerInfo) -> bool {
+        worker.gpu_count >= self.min_gpus
+            && (self.states.is_empty() || self.states.contains(&worker.state))
+            && !self.excluded_states.contains(&worker.state)
+            && self
+                .labels
+                .iter()
+                .all(|(key, value)| worker.labels.get(key) == Some(value))
+            && self
+                .selector
+                .as_ref()
+                .is_none_or(|selector| worker.selected_by(selector))
+    }
+}
+
+/// What changed about a worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipChange {
+    /// The worker registered
+    Joined,
+    /// The worker deregistered
+    Left,
+    /// The worker missed its heartbeat deadline and was marked dead
+    Dead,
+    /// A dead worker was removed from the registry
+    Removed,
+    /// The worker reported a different state
+    StateChanged { from: WorkerState, to: WorkerState },
+    /// The worker's rank was reassigned by compaction
+    RankChanged { from: u32 },
+}
+
+/// A change to the registered workers, see [`WorkerRegistry::subscribe`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipEvent {
+    pub worker_id: WorkerId,
+    pub change: MembershipChange,
+    /// Rank of the worker after the change
+    pub rank: u32,
+    /// Membership generation after the change
+    pub generation: u64,
+    pub timestamp: DateTime<Utc>,
+}
+
 /// Thread-safe worker registry
 pub struct WorkerRegistry {
     /// Map of worker ID to worker info
@@ -164,11 +371,27 @@ pub struct WorkerRegistry {
     /// Counter for assigning ranks
     rank_counter: AtomicU64,
 
+    /// How ranks are assigned and compacted
+    rank_policy: RankPolicy,
+
+    /// When membership last changed; held while ranks are assigned
+    last_change: Mutex<Instant>,
+
+    /// Membership generation, bumped whenever a worker joins or leaves or
+    /// ranks are compacted
+    generation: AtomicU64,
+
     /// Maximum workers allowed
     max_workers: usize,
 
-    /// Heartbeat timeout duration
-    heartbeat_timeout: Duration,
+    /// Heartbeat timeout in milliseconds, adjustable at runtime
+    heartbeat_timeout_ms: AtomicU64,
+
+    /// Broadcasts membership and state changes
+    events: broadcast::Sender<MembershipEvent>,
+
+    /// Time source for heartbeats and rank settling
+    clock: SharedClock,
 }
 
 impl WorkerRegistry {
@@ -177,13 +400,67 @@ impl WorkerRegistry {
         Self {
             workers: DashMap::new(),
             rank_counter: AtomicU64::new(0),
+            rank_policy: RankPolicy::default(),
+            last_change: Mutex::new(Instant::now()),
+            generation: AtomicU64::new(0),
             max_workers,
-            heartbeat_timeout,
+            heartbeat_timeout_ms: AtomicU64::new(heartbeat_timeout.as_millis() as u64),
+            events: broadcast::channel(MEMBERSHIP_EVENT_CAPACITY).0,
+            clock: SystemClock::shared(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.last_change = Mutex::new(clock.now());
+        self.clock = clock;
+        self
+    }
+
+    /// Set how ranks are assigned and compacted
+    pub fn with_rank_policy(mut self, policy: RankPolicy) -> Self {
+        self.rank_policy = policy;
+        self
+    }
+
+    /// How ranks are assigned and compacted
+    pub fn rank_policy(&self) -> RankPolicy {
+        self.rank_policy
+    }
+
+    /// Time without a heartbeat after which a worker is considered dead
+    pub fn heartbeat_timeout(&self) -> Duration {
+        Duration::from_millis(self.heartbeat_timeout_ms.load(Ordering::Relaxed))
+    }
+
+    /// Change the heartbeat timeout for all workers
+    pub fn set_heartbeat_timeout(&self, timeout: Duration) {
+        self.heartbeat_timeout_ms
+            .store(timeout.as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Subscribe to membership and state changes
+    ///
+    /// Only changes after the call are delivered; take a snapshot with
+    /// [`Self::all_workers`] afterwards to start from the full membership.
+    /// A subscriber that falls more than 1024 events behind misses the oldest.
+    pub fn subscribe(&self) -> broadcast::Receiver<MembershipEvent> {
+        self.events.subscribe()
+    }
+
+    fn publish(&self, worker_id: &WorkerId, change: MembershipChange, rank: u32) {
+        let _ = self.events.send(MembershipEvent {
+            worker_id: worker_id.clone(),
+            change,
+            rank,
+            generation: self.generation(),
+            timestamp: self.clock.utc_now(),
+        });
+    }
+
     /// Register a new worker
     pub fn register(&self, mut worker: WorkerInfo) -> Result<WorkerInfo> {
+        let mut last_change = self.last_change.lock();
         if self.workers.len() >= self.max_workers {
             return Err(Error::InvalidConfig {
                 message: format!("Maximum workers ({}) reached", self.max_workers),
@@ -192,14 +469,21 @@ impl WorkerRegistry {
 
         if self.workers.contains_key(&worker.id) {
             return Err(Error::WorkerAlreadyRegistered {
-                worker_id: worker.id.clone(),
+                worker_id: worker.id.to_string(),
             });
         }
 
         // Assign rank
-        let rank = self.rank_counter.fetch_add(1, Ordering::SeqCst) as u32;
+        let rank = match self.rank_policy {
+            RankPolicy::Monotonic => self.rank_counter.fetch_add(1, Ordering::SeqCst) as u32,
+            RankPolicy::Compact { .. } => self.lowest_free_rank(),
+        };
         worker.rank = rank;
         worker.state = WorkerState::Idle;
+        worker.registered_at = self.clock.utc_now();
+        worker.last_heartbeat = worker.registered_at;
+        // Time-ordered, so never reused even across coordinator restarts
+        worker.session = id::global().next().as_u64();
 
         info!(
             worker_id = %worker.id,
@@ -210,15 +494,28 @@ impl WorkerRegistry {
 
         let result = worker.clone();
         self.workers.insert(worker.id.clone(), worker);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *last_change = self.clock.now();
+        self.publish(&result.id, MembershipChange::Joined, rank);
         Ok(result)
     }
 
+    /// Smallest rank not held by a registered worker
+    fn lowest_free_rank(&self) -> u32 {
+        let taken: HashSet<u32> = self.workers.iter().map(|w| w.rank).collect();
+        (0..).find(|rank| !taken.contains(rank)).unwrap_or_default()
+    }
+
     /// Deregister a worker
-    pub fn deregister(&self, worker_id: &str) -> Result<WorkerInfo> {
+    pub fn deregister(&self, worker_id: &WorkerId) -> Result<WorkerInfo> {
+        let mut last_change = self.last_change.lock();
         self.workers
             .remove(worker_id)
             .map(|(_, w)| {
+                self.generation.fetch_add(1, Ordering::SeqCst);
+                *last_change = self.clock.now();
                 info!(worker_id = %worker_id, "Worker deregistered");
+                self.publish(worker_id, MembershipChange::Left, w.rank);
                 w
             })
             .ok_or_else(|| Error::WorkerNotFound {
@@ -227,14 +524,14 @@ impl WorkerRegistry {
     }
 
     /// Get worker info by ID
-    pub fn get(&self, worker_id: &str) -> Option<WorkerInfo> {
+    pub fn get(&self, worker_id: &WorkerId) -> Option<WorkerInfo> {
         self.workers.get(worker_id).map(|w| w.clone())
     }
 
     /// Update worker heartbeat
     pub fn heartbeat(
         &self,
-        worker_id: &str,
+        worker_id: &WorkerId,
         state: WorkerState,
         resources: ResourceMetrics,
     ) -> Result<()> {
@@ -245,15 +542,46 @@ impl WorkerRegistry {
                 worker_id: worker_id.to_string(),
             })?;
 
-        worker.heartbeat(resources);
-        worker.state = state;
+        worker.heartbeat_at(resources, self.clock.utc_now());
+        let from = std::mem::replace(&mut worker.state, state);
+        let rank = worker.rank;
+        drop(worker);
+        if from != state {
+            self.publish(
+                worker_id,
+                MembershipChange::StateChanged { from, to: state },
+                rank,
+            );
+        }
         Ok(())
     }
 
+    /// Check that a request comes from the worker's current registration
+    ///
+    /// A session of 0 is not checked, for clients that predate sessions.
+    pub fn check_session(&self, worker_id: &WorkerId, session: u64) -> Result<()> {
+        let current = self
+            .workers
+            .get(worker_id)
+            .ok_or_else(|| Error::WorkerNotFound {
+                worker_id: worker_id.to_string(),
+            })?
+            .session;
+        if session == 0 || session == current {
+            Ok(())
+        } else {
+            Err(Error::StaleSession {
+                worker_id: worker_id.to_string(),
+                session,
+                current,
+            })
+        }
+    }
+
     /// Update worker training progress
     pub fn update_progress(
         &self,
-        worker_id: &str,
+        worker_id: &WorkerId,
         step: u64,
         epoch: u64,
         task: Option<String>,
@@ -273,6 +601,33 @@ impl WorkerRegistry {
         Ok(())
     }
 
+    /// Replace a worker's labels
+    pub fn update_labels(
+        &self,
+        worker_id: &WorkerId,
+        labels: HashMap<String, String>,
+    ) -> Result<()> {
+        crate::labels::validate_labels(&labels)?;
+        self.workers
+            .get_mut(worker_id)
+            .ok_or_else(|| Error::WorkerNotFound {
+                worker_id: worker_id.to_string(),
+            })?
+            .labels = labels;
+        Ok(())
+    }
+
+    /// Replace a worker's taints
+    pub fn update_taints(&self, worker_id: &WorkerId, taints: Vec<Taint>) -> Result<()> {
+        self.workers
+            .get_mut(worker_id)
+            .ok_or_else(|| Error::WorkerNotFound {
+                worker_id: worker_id.to_string(),
+            })?
+            .taints = taints;
+        Ok(())
+    }
+
     /// Get all active workers
     pub fn active_workers(&self) -> Vec<WorkerInfo> {
         self.workers
@@ -290,17 +645,56 @@ impl WorkerRegistry {
             .collect()
     }
 
+    /// Workers matching `query`, by rank
+    pub fn find_workers(&self, query: &WorkerQuery) -> Vec<WorkerInfo> {
+        let mut workers: Vec<WorkerInfo> = self
+            .workers
+            .iter()
+            .filter(|entry| query.matches(entry.value()))
+            .map(|entry| entry.value().clone())
+            .collect();
+        workers.sort_by_key(|w| w.rank);
+        workers
+    }
+
+    /// Number of workers matching `query`
+    pub fn count_workers(&self, query: &WorkerQuery) -> usize {
+        self.workers
+            .iter()
+            .filter(|entry| query.matches(entry.value()))
+            .count()
+    }
+
+    /// GPUs across all workers that are not dead
+    pub fn total_gpu_count(&self) -> u64 {
+        self.workers
+            .iter()
+            .filter(|entry| entry.value().state != WorkerState::Dead)
+            .map(|entry| entry.value().gpu_count as u64)
+            .sum()
+    }
+
     /// Get current world size (number of registered workers)
     pub fn world_size(&self) -> usize {
         self.workers.len()
     }
 
+    /// Get the current membership generation
+    ///
+    /// The generation increases every time the set of registered workers
+    /// changes, so workers can detect a stale rank or world size.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
     /// Check for dead workers and mark them
     pub fn check_dead_workers(&self) -> Vec<WorkerId> {
         let mut dead_workers = Vec::new();
 
         for mut entry in self.workers.iter_mut() {
-            if entry.value().is_dead(self.heartbeat_timeout)
+            if entry
+                .value()
+                .is_dead_at(self.heartbeat_timeout(), self.clock.utc_now())
                 && entry.value().state != WorkerState::Dead
             {
                 warn!(
@@ -309,15 +703,22 @@ impl WorkerRegistry {
                     "Worker marked as dead"
                 );
                 entry.value_mut().state = WorkerState::Dead;
-                dead_workers.push(entry.key().clone());
+                dead_workers.push((entry.key().clone(), entry.value().rank));
             }
         }
 
         dead_workers
+            .into_iter()
+            .map(|(worker_id, rank)| {
+                self.publish(&worker_id, MembershipChange::Dead, rank);
+                worker_id
+            })
+            .collect()
     }
 
     /// Remove all dead workers from registry
     pub fn remove_dead_workers(&self) -> Vec<WorkerInfo> {
+        let mut last_change = self.last_change.lock();
         let dead_ids: Vec<_> = self
             .workers
             .iter()
@@ -325,10 +726,80 @@ impl WorkerRegistry {
             .map(|entry| entry.key().clone())
             .collect();
 
-        dead_ids
+        let removed: Vec<_> = dead_ids
             .into_iter()
             .filter_map(|id| self.workers.remove(&id).map(|(_, w)| w))
-            .collect()
+            .collect();
+
+        if !removed.is_empty() {
+            self.generation.fetch_add(1, Ordering::SeqCst);
+            *last_change = self.clock.now();
+        }
+        for worker in &removed {
+            self.publish(&worker.id, MembershipChange::Removed, worker.rank);
+        }
+
+        removed
+    }
+
+    /// Reassign ranks to `0..world_size`, keeping the workers' relative order
+    ///
+    /// Returns the new membership generation if any rank changed, so workers
+    /// holding an older generation know to re-fetch their rank.
+    pub fn compact_ranks(&self) -> Option<u64> {
+        let _last_change = self.last_change.lock();
+        let mut ranks: Vec<(u32, WorkerId)> = self
+            .workers
+            .iter()
+            .map(|w| (w.rank, w.key().clone()))
+            .collect();
+        ranks.sort();
+
+        let mut changed = Vec::new();
+        for (new_rank, (old_rank, worker_id)) in ranks.iter().enumerate() {
+            let new_rank = new_rank as u32;
+            if *old_rank != new_rank {
+                if let Some(mut worker) = self.workers.get_mut(worker_id) {
+                    worker.rank = new_rank;
+                    changed.push((worker_id, *old_rank, new_rank));
+                }
+            }
+        }
+        self.rank_counter
+            .store(ranks.len() as u64, Ordering::SeqCst);
+
+        if changed.is_empty() {
+            return None;
+        }
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        info!(
+            workers = ranks.len(),
+            changed = changed.len(),
+            generation = generation,
+            "Compacted worker ranks"
+        );
+        for (worker_id, from, rank) in changed {
+            self.publish(worker_id, MembershipChange::RankChanged { from }, rank);
+        }
+        Some(generation)
+    }
+
+    /// Compact ranks if the policy asks for it and membership has settled
+    ///
+    /// Meant to be called periodically; see [`Self::compact_ranks`].
+    pub fn compact_ranks_if_settled(&self) -> Option<u64> {
+        let RankPolicy::Compact { settle } = self.rank_policy else {
+            return None;
+        };
+        if self
+            .clock
+            .now()
+            .saturating_duration_since(*self.last_change.lock())
+            < settle
+        {
+            return None;
+        }
+        self.compact_ranks()
     }
 
     /// Get aggregate resource metrics across all active workers
@@ -353,6 +824,35 @@ impl WorkerRegistry {
 
         aggregate
     }
+
+    /// GPU totals and averages across all active workers
+    pub fn gpu_summary(&self) -> GpuSummary {
+        self.aggregate_resources().gpu_summary()
+    }
+
+    /// GPU totals and averages of each active worker with GPUs
+    pub fn worker_gpu_summaries(&self) -> Vec<(WorkerId, GpuSummary)> {
+        self.workers
+            .iter()
+            .filter(|entry| entry.value().state.is_active())
+            .map(|entry| (entry.key().clone(), entry.value().resources.gpu_summary()))
+            .filter(|(_, summary)| summary.gpu_count > 0)
+            .collect()
+    }
+
+    /// Disk and network throughput of a worker over its recent heartbeats
+    pub fn resource_rates(&self, worker_id: &WorkerId) -> Option<ResourceRates> {
+        self.workers.get(worker_id)?.resource_window.rates()
+    }
+
+    /// Combined disk and network throughput of all active workers
+    pub fn aggregate_rates(&self) -> ResourceRates {
+        self.workers
+            .iter()
+            .filter(|entry| entry.value().state.is_active())
+            .filter_map(|entry| entry.value().resource_window.rates())
+            .sum()
+    }
 }
 
 impl Default for WorkerRegistry {
@@ -367,12 +867,18 @@ pub type WorkerRegistryHandle = Arc<WorkerRegistry>;
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::clock::MockClock;
+    use crate::GpuMetrics;
+
+    fn worker_id(id: &str) -> WorkerId {
+        id.parse().unwrap()
+    }
 
     #[test]
     fn test_worker_registration() {
         let registry = WorkerRegistry::new(10, Duration::from_secs(30));
 
-        let worker = WorkerInfo::new("worker-1".to_string(), "host1".to_string(), 50052, 0, 1);
+        let worker = WorkerInfo::new(worker_id("worker-1"), "host1".to_string(), 50052, 0, 1);
 
         let registered = registry.register(worker).unwrap();
         assert_eq!(registered.rank, 0);
@@ -383,29 +889,323 @@ mod tests {
     fn test_worker_heartbeat() {
         let registry = WorkerRegistry::new(10, Duration::from_secs(30));
 
-        let worker = WorkerInfo::new("worker-1".to_string(), "host1".to_string(), 50052, 0, 1);
+        let worker = WorkerInfo::new(worker_id("worker-1"), "host1".to_string(), 50052, 0, 1);
         registry.register(worker).unwrap();
 
         registry
             .heartbeat(
-                "worker-1",
+                &worker_id("worker-1"),
                 WorkerState::Training,
                 ResourceMetrics::default(),
             )
             .unwrap();
 
-        let updated = registry.get("worker-1").unwrap();
+        let updated = registry.get(&worker_id("worker-1")).unwrap();
         assert_eq!(updated.state, WorkerState::Training);
     }
 
+    #[test]
+    fn test_session_changes_on_reregistration() {
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30));
+        let id = worker_id("worker-1");
+        let worker = WorkerInfo::new(id.clone(), "host1".to_string(), 50052, 0, 1);
+
+        let old = registry.register(worker.clone()).unwrap().session;
+        assert_ne!(old, 0);
+        registry.check_session(&id, old).unwrap();
+
+        registry.deregister(&id).unwrap();
+        let new = registry.register(worker).unwrap().session;
+        assert!(new > old);
+        registry.check_session(&id, new).unwrap();
+        registry.check_session(&id, 0).unwrap();
+        assert!(matches!(
+            registry.check_session(&id, old),
+            Err(Error::StaleSession { current, .. }) if current == new
+        ));
+    }
+
     #[test]
     fn test_duplicate_registration() {
         let registry = WorkerRegistry::new(10, Duration::from_secs(30));
 
-        let worker = WorkerInfo::new("worker-1".to_string(), "host1".to_string(), 50052, 0, 1);
+        let worker = WorkerInfo::new(worker_id("worker-1"), "host1".to_string(), 50052, 0, 1);
 
         registry.register(worker.clone()).unwrap();
         let result = registry.register(worker);
         assert!(matches!(result, Err(Error::WorkerAlreadyRegistered { .. })));
     }
+
+    #[test]
+    fn test_membership_generation() {
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30));
+        assert_eq!(registry.generation(), 0);
+
+        let worker = WorkerInfo::new(worker_id("worker-1"), "host1".to_string(), 50052, 0, 1);
+        registry.register(worker.clone()).unwrap();
+        assert_eq!(registry.generation(), 1);
+
+        // Failed registration does not change membership
+        assert!(registry.register(worker).is_err());
+        assert_eq!(registry.generation(), 1);
+
+        registry.deregister(&worker_id("worker-1")).unwrap();
+        assert_eq!(registry.generation(), 2);
+    }
+
+    #[test]
+    fn test_find_workers() {
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30));
+        for (id, gpus, zone) in [
+            ("worker-1", 8, "us-east-1a"),
+            ("worker-2", 2, "us-east-1a"),
+            ("worker-3", 8, "us-east-1b"),
+        ] {
+            let mut worker = WorkerInfo::new(worker_id(id), "host".to_string(), 50052, 0, 1);
+            worker.gpu_count = gpus;
+            worker.labels.insert("zone".to_string(), zone.to_string());
+            registry.register(worker).unwrap();
+        }
+        registry
+            .update_taints(&worker_id("worker-3"), vec!["pool=eval".parse().unwrap()])
+            .unwrap();
+        registry
+            .heartbeat(
+                &worker_id("worker-1"),
+                WorkerState::Training,
+                ResourceMetrics::default(),
+            )
+            .unwrap();
+
+        let ids = |query: WorkerQuery| -> Vec<String> {
+            registry
+                .find_workers(&query)
+                .into_iter()
+                .map(|w| w.id.into())
+                .collect()
+        };
+        assert_eq!(
+            ids(WorkerQuery::new().min_gpus(8)),
+            vec!["worker-1", "worker-3"]
+        );
+        assert_eq!(
+            ids(WorkerQuery::new().min_gpus(8).in_state(WorkerState::Idle)),
+            vec!["worker-3"]
+        );
+        assert_eq!(
+            ids(WorkerQuery::new()
+                .label("zone", "us-east-1a")
+                .not_in_state(WorkerState::Training)),
+            vec!["worker-2"]
+        );
+        assert_eq!(registry.count_workers(&WorkerQuery::new()), 3);
+        assert_eq!(registry.total_gpu_count(), 18);
+
+        // Selectors skip tainted workers unless tolerated
+        let selector: LabelSelector = "zone".parse().unwrap();
+        assert_eq!(
+            ids(WorkerQuery::new().min_gpus(8).selector(selector.clone())),
+            vec!["worker-1"]
+        );
+        assert_eq!(
+            ids(WorkerQuery::new()
+                .min_gpus(8)
+                .selector(selector.tolerate("pool".parse().unwrap()))),
+            vec!["worker-1", "worker-3"]
+        );
+    }
+
+    #[test]
+    fn test_resource_rates() {
+        let clock = Arc::new(MockClock::new());
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30)).with_clock(clock.clone());
+        let id = worker_id("worker-1");
+        registry
+            .register(WorkerInfo::new(
+                id.clone(),
+                "host1".to_string(),
+                50052,
+                0,
+                1,
+            ))
+            .unwrap();
+
+        let heartbeat = |disk_read_bytes| {
+            registry
+                .heartbeat(
+                    &id,
+                    WorkerState::Training,
+                    ResourceMetrics {
+                        disk_read_bytes,
+                        network_rx_bytes: 2 * disk_read_bytes,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+        };
+
+        // The first report covers an unknown interval
+        heartbeat(1_000_000_000);
+        assert_eq!(registry.resource_rates(&id), None);
+
+        for _ in 0..RESOURCE_WINDOW_SIZE + 2 {
+            clock.advance(Duration::from_secs(5));
+            heartbeat(50_000_000);
+        }
+        let rates = registry.resource_rates(&id).unwrap();
+        assert_eq!(rates.disk_read_bytes_per_sec, 10_000_000.0);
+        assert_eq!(rates.network_rx_bytes_per_sec, 20_000_000.0);
+        assert_eq!(rates.disk_write_bytes_per_sec, 0.0);
+        assert_eq!(registry.aggregate_rates(), rates);
+    }
+
+    #[test]
+    fn test_gpu_summary() {
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30));
+        let gpu = |gpu_id, utilization_percent, temperature_celsius| GpuMetrics {
+            gpu_id,
+            utilization_percent,
+            memory_used_bytes: 20 << 30,
+            memory_total_bytes: 80 << 30,
+            temperature_celsius,
+            power_draw_watts: 300.0,
+            ecc_errors_corrected: 1,
+            ..Default::default()
+        };
+        for (name, gpus) in [
+            ("worker-1", vec![gpu(0, 90.0, 70.0), gpu(1, 70.0, 75.0)]),
+            ("worker-2", vec![gpu(0, 20.0, 60.0)]),
+            ("worker-3", vec![]),
+        ] {
+            let id = worker_id(name);
+            registry
+                .register(WorkerInfo::new(id.clone(), "host".to_string(), 50052, 0, 3))
+                .unwrap();
+            registry
+                .heartbeat(
+                    &id,
+                    WorkerState::Training,
+                    ResourceMetrics {
+                        gpu_metrics: gpus,
+                        ..Default::default()
+                    },
+                )
+                .unwrap();
+        }
+
+        let cluster = registry.gpu_summary();
+        assert_eq!(cluster.gpu_count, 3);
+        assert_eq!(cluster.avg_utilization_percent, 60.0);
+        assert_eq!(cluster.power_draw_watts, 900.0);
+        assert_eq!(cluster.max_temperature_celsius, 75.0);
+        assert_eq!(cluster.ecc_errors_corrected, 3);
+        assert_eq!(cluster.memory_utilization(), 0.25);
+
+        let mut per_worker = registry.worker_gpu_summaries();
+        per_worker.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(per_worker.len(), 2);
+        assert_eq!(per_worker[0].1.avg_utilization_percent, 80.0);
+        assert_eq!(per_worker[1].1.gpu_count, 1);
+    }
+
+    #[test]
+    fn test_subscribe() {
+        let clock = Arc::new(MockClock::new());
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30)).with_clock(clock.clone());
+        let mut events = registry.subscribe();
+
+        let worker = WorkerInfo::new(worker_id("worker-1"), "host1".to_string(), 50052, 0, 1);
+        registry.register(worker).unwrap();
+        registry
+            .heartbeat(
+                &worker_id("worker-1"),
+                WorkerState::Training,
+                ResourceMetrics::default(),
+            )
+            .unwrap();
+        // Same state again is not a change
+        registry
+            .heartbeat(
+                &worker_id("worker-1"),
+                WorkerState::Training,
+                ResourceMetrics::default(),
+            )
+            .unwrap();
+        clock.advance(Duration::from_secs(31));
+        registry.check_dead_workers();
+        registry.remove_dead_workers();
+
+        let mut changes = Vec::new();
+        while let Ok(event) = events.try_recv() {
+            assert_eq!(event.worker_id, worker_id("worker-1"));
+            changes.push((event.change, event.generation));
+        }
+        assert_eq!(
+            changes,
+            vec![
+                (MembershipChange::Joined, 1),
+                (
+                    MembershipChange::StateChanged {
+                        from: WorkerState::Idle,
+                        to: WorkerState::Training,
+                    },
+                    1
+                ),
+                (MembershipChange::Dead, 1),
+                (MembershipChange::Removed, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rank_compaction() {
+        let clock = Arc::new(MockClock::new());
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30))
+            .with_rank_policy(RankPolicy::Compact {
+                settle: Duration::from_secs(10),
+            })
+            .with_clock(clock.clone());
+        for i in 0..4 {
+            let id = format!("worker-{}", i);
+            let worker = WorkerInfo::new(worker_id(&id), "host".to_string(), 50052, 0, 1);
+            registry.register(worker).unwrap();
+        }
+        registry.deregister(&worker_id("worker-0")).unwrap();
+        registry.deregister(&worker_id("worker-2")).unwrap();
+
+        // A joining worker fills the lowest gap
+        let worker = WorkerInfo::new(worker_id("worker-4"), "host".to_string(), 50052, 0, 1);
+        assert_eq!(registry.register(worker).unwrap().rank, 0);
+
+        // Membership has not settled yet
+        assert_eq!(registry.compact_ranks_if_settled(), None);
+        clock.advance(Duration::from_secs(10));
+
+        let generation = registry.generation();
+        assert_eq!(registry.compact_ranks_if_settled(), Some(generation + 1));
+        let rank = |id: &str| registry.get(&worker_id(id)).unwrap().rank;
+        assert_eq!(
+            (rank("worker-4"), rank("worker-1"), rank("worker-3")),
+            (0, 1, 2)
+        );
+
+        // Already dense
+        assert_eq!(registry.compact_ranks(), None);
+        assert_eq!(registry.generation(), generation + 1);
+    }
+
+    #[test]
+    fn test_monotonic_ranks_are_not_compacted() {
+        let registry = WorkerRegistry::new(10, Duration::from_secs(30))
+            .with_rank_policy(RankPolicy::Monotonic);
+        for id in ["worker-1", "worker-2"] {
+            let worker = WorkerInfo::new(worker_id(id), "host".to_string(), 50052, 0, 1);
+            registry.register(worker).unwrap();
+        }
+        registry.deregister(&worker_id("worker-1")).unwrap();
+
+        let worker = WorkerInfo::new(worker_id("worker-3"), "host".to_string(), 50052, 0, 1);
+        assert_eq!(registry.register(worker).unwrap().rank, 2);
+        assert_eq!(registry.compact_ranks_if_settled(), None);
+    }
 }
@@ -3,7 +3,7 @@
 //! Defines the async interface that all storage backends must implement.
 
 use async_trait::async_trait;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use runtime_core::Result;
 
 /// Async trait for storage backends
@@ -24,6 +24,31 @@ pub trait StorageBackend: Send + Sync {
     /// Returns error if path doesn't exist or read fails
     async fn read(&self, path: &str) -> Result<Bytes>;
 
+    /// Read up to `len` bytes starting at `offset`
+    ///
+    /// Returns fewer bytes when the file ends first. The default reads the
+    /// whole file; backends override it to avoid that.
+    ///
+    /// # Errors
+    /// Returns error if path doesn't exist or read fails
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> Result<Bytes> {
+        let data = self.read(path).await?;
+        let start = offset.min(data.len() as u64) as usize;
+        let end = offset.saturating_add(len).min(data.len() as u64) as usize;
+        Ok(data.slice(start..end))
+    }
+
+    /// Size of the data at `path` in bytes
+    ///
+    /// The default reads the whole file; backends override it to look the
+    /// size up instead.
+    ///
+    /// # Errors
+    /// Returns error if path doesn't exist or the lookup fails
+    async fn size(&self, path: &str) -> Result<u64> {
+        Ok(self.read(path).await?.len() as u64)
+    }
+
     /// Write data to the given path
     ///
     /// Creates parent directories if they don't exist.
@@ -40,6 +65,24 @@ pub trait StorageBackend: Send + Sync {
     /// Returns error if write fails
     async fn write(&self, path: &str, data: Bytes) -> Result<u64>;
 
+    /// Write `parts` one after another as the data at `path`
+    ///
+    /// Lets callers add a header to a large payload without copying the
+    /// payload. The default concatenates the parts and calls [`write`];
+    /// backends override it to write the parts as they are.
+    ///
+    /// [`write`]: StorageBackend::write
+    ///
+    /// # Errors
+    /// Returns error if write fails
+    async fn write_parts(&self, path: &str, parts: Vec<Bytes>) -> Result<u64> {
+        let mut data = BytesMut::with_capacity(parts.iter().map(Bytes::len).sum());
+        for part in &parts {
+            data.extend_from_slice(part);
+        }
+        self.write(path, data.freeze()).await
+    }
+
     /// Delete data at the given path
     ///
     /// # Arguments
@@ -66,4 +109,10 @@ pub trait StorageBackend: Send + Sync {
     /// # Returns
     /// Vector of paths matching the prefix
     async fn list(&self, prefix: &str) -> Result<Vec<String>>;
+
+    /// Full location of `path`, such as an absolute file path or an
+    /// `s3://` URL, for metadata and logs
+    fn uri(&self, path: &str) -> String {
+        path.to_string()
+    }
 }
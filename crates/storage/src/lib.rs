@@ -3,6 +3,7 @@
 //! Provides async storage operations with support for:
 //! - Local filesystem (default feature)
 //! - Amazon S3 / S3-compatible storage (with `s3` feature)
+//! - Operation metrics for any backend via [`MeteredStorage`]
 //!
 //! # Example
 //!
@@ -20,12 +21,14 @@
 
 mod backend;
 mod local;
+mod metered;
 
 #[cfg(feature = "s3")]
 mod s3;
 
 pub use backend::StorageBackend;
 pub use local::LocalStorage;
+pub use metered::MeteredStorage;
 
 #[cfg(feature = "s3")]
-pub use s3::S3Storage;
+pub use s3::{S3Config, S3Storage};
@@ -2,13 +2,14 @@
 //!
 //! Provides async file I/O with atomic writes to prevent partial/corrupt files.
 
+use std::collections::BTreeSet;
 use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use bytes::Bytes;
 use runtime_core::{Error, Result};
 use tokio::fs;
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 use tracing::{debug, instrument};
 use uuid::Uuid;
 
@@ -57,6 +58,80 @@ impl LocalStorage {
         );
         full_path.with_file_name(temp_name)
     }
+
+    /// List the first `limit` files matching `prefix` in sorted order,
+    /// skipping hidden files
+    ///
+    /// The whole tree is walked, but no more than `limit` paths are held at once.
+    #[instrument(skip(self), fields(backend = "local"))]
+    pub async fn list_at_most(&self, prefix: &str, limit: usize) -> Result<Vec<String>> {
+        let mut results = BTreeSet::new();
+        self.walk(prefix, |path| {
+            let hidden = Path::new(&path)
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+            if !hidden {
+                results.insert(path);
+                if results.len() > limit {
+                    results.pop_last();
+                }
+            }
+        })
+        .await;
+
+        debug!(count = results.len(), "Found files");
+        Ok(results.into_iter().collect())
+    }
+
+    /// Call `visit` with the relative path of every file matching `prefix`
+    async fn walk(&self, prefix: &str, mut visit: impl FnMut(String) + Send) {
+        let search_path = self.resolve_path(prefix);
+
+        debug!(?search_path, "Listing files with prefix");
+
+        // Determine the directory to scan
+        let dir_to_scan = if search_path.is_dir() {
+            search_path.clone()
+        } else if let Some(parent) = search_path.parent() {
+            if parent.is_dir() {
+                parent.to_path_buf()
+            } else {
+                return;
+            }
+        } else {
+            return;
+        };
+
+        // Recursively walk the directory
+        let mut stack = vec![dir_to_scan];
+        while let Some(dir) = stack.pop() {
+            let mut entries = match fs::read_dir(&dir).await {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+
+            while let Ok(Some(entry)) = entries.next_entry().await {
+                let entry_path = entry.path();
+                let metadata = match entry.metadata().await {
+                    Ok(m) => m,
+                    Err(_) => continue,
+                };
+
+                if metadata.is_dir() {
+                    stack.push(entry_path);
+                } else if metadata.is_file() {
+                    // Convert to relative path
+                    if let Ok(relative) = entry_path.strip_prefix(&self.base_path) {
+                        let relative_str = relative.to_string_lossy().to_string();
+                        // Only include if it matches the prefix
+                        if relative_str.starts_with(prefix) {
+                            visit(relative_str);
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -71,50 +146,97 @@ impl StorageBackend for LocalStorage {
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::StoragePathNotFound {
                 path: path.to_string(),
             }),
-            Err(e) => Err(Error::Storage {
-                message: format!("Failed to read {}: {}", path, e),
-            }),
+            Err(e) => Err(Error::storage("Failed to read")
+                .with_path(path)
+                .with_source(e)),
         }
     }
 
+    #[instrument(skip(self), fields(backend = "local"))]
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> Result<Bytes> {
+        let full_path = self.resolve_path(path);
+        debug!(?full_path, offset, len, "Reading file range");
+
+        let mut file = match fs::File::open(&full_path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(Error::StoragePathNotFound {
+                    path: path.to_string(),
+                })
+            }
+            Err(e) => {
+                return Err(Error::storage("Failed to open")
+                    .with_path(path)
+                    .with_source(e))
+            }
+        };
+
+        file.seek(std::io::SeekFrom::Start(offset))
+            .await
+            .map_err(|e| {
+                Error::storage("Failed to seek")
+                    .with_path(path)
+                    .with_source(e)
+            })?;
+
+        let mut data = Vec::new();
+        file.take(len).read_to_end(&mut data).await.map_err(|e| {
+            Error::storage("Failed to read")
+                .with_path(path)
+                .with_source(e)
+        })?;
+        Ok(Bytes::from(data))
+    }
+
     #[instrument(skip(self, data), fields(backend = "local", size = data.len()))]
     async fn write(&self, path: &str, data: Bytes) -> Result<u64> {
+        self.write_parts(path, vec![data]).await
+    }
+
+    #[instrument(skip(self, parts), fields(backend = "local"))]
+    async fn write_parts(&self, path: &str, parts: Vec<Bytes>) -> Result<u64> {
         let full_path = self.resolve_path(path);
         let temp_path = self.temp_path(path);
-        let size = data.len() as u64;
+        let size = parts.iter().map(|part| part.len() as u64).sum::<u64>();
 
         debug!(?full_path, ?temp_path, size, "Writing file atomically");
 
         // Ensure parent directory exists
         if let Some(parent) = full_path.parent() {
-            fs::create_dir_all(parent)
-                .await
-                .map_err(|e| Error::Storage {
-                    message: format!("Failed to create directory {:?}: {}", parent, e),
-                })?;
+            fs::create_dir_all(parent).await.map_err(|e| {
+                Error::storage("Failed to create directory")
+                    .with_path(parent.display().to_string())
+                    .with_source(e)
+            })?;
         }
 
         // Write to temporary file
-        let mut file = fs::File::create(&temp_path)
-            .await
-            .map_err(|e| Error::Storage {
-                message: format!("Failed to create temp file {:?}: {}", temp_path, e),
-            })?;
-
-        file.write_all(&data).await.map_err(|e| Error::Storage {
-            message: format!("Failed to write data: {}", e),
+        let mut file = fs::File::create(&temp_path).await.map_err(|e| {
+            Error::storage("Failed to create temp file")
+                .with_path(temp_path.display().to_string())
+                .with_source(e)
         })?;
 
-        file.sync_all().await.map_err(|e| Error::Storage {
-            message: format!("Failed to sync file: {}", e),
+        for part in &parts {
+            file.write_all(part).await.map_err(|e| {
+                Error::storage("Failed to write data")
+                    .with_path(path)
+                    .with_source(e)
+            })?;
+        }
+
+        file.sync_all().await.map_err(|e| {
+            Error::storage("Failed to sync file")
+                .with_path(path)
+                .with_source(e)
         })?;
 
         // Atomic rename
-        fs::rename(&temp_path, &full_path)
-            .await
-            .map_err(|e| Error::Storage {
-                message: format!("Failed to rename {:?} to {:?}: {}", temp_path, full_path, e),
-            })?;
+        fs::rename(&temp_path, &full_path).await.map_err(|e| {
+            Error::storage(format!("Failed to rename {:?}", temp_path))
+                .with_path(full_path.display().to_string())
+                .with_source(e)
+        })?;
 
         debug!(?full_path, size, "File written successfully");
         Ok(size)
@@ -130,9 +252,9 @@ impl StorageBackend for LocalStorage {
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::StoragePathNotFound {
                 path: path.to_string(),
             }),
-            Err(e) => Err(Error::Storage {
-                message: format!("Failed to delete {}: {}", path, e),
-            }),
+            Err(e) => Err(Error::storage("Failed to delete")
+                .with_path(path)
+                .with_source(e)),
         }
     }
 
@@ -142,60 +264,32 @@ impl StorageBackend for LocalStorage {
         Ok(fs::metadata(&full_path).await.is_ok())
     }
 
+    #[instrument(skip(self), fields(backend = "local"))]
+    async fn size(&self, path: &str) -> Result<u64> {
+        match fs::metadata(self.resolve_path(path)).await {
+            Ok(metadata) => Ok(metadata.len()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err(Error::StoragePathNotFound {
+                path: path.to_string(),
+            }),
+            Err(e) => Err(Error::storage("Failed to stat")
+                .with_path(path)
+                .with_source(e)),
+        }
+    }
+
     #[instrument(skip(self), fields(backend = "local"))]
     async fn list(&self, prefix: &str) -> Result<Vec<String>> {
-        let search_path = self.resolve_path(prefix);
         let mut results = Vec::new();
-
-        debug!(?search_path, "Listing files with prefix");
-
-        // Determine the directory to scan
-        let dir_to_scan = if search_path.is_dir() {
-            search_path.clone()
-        } else if let Some(parent) = search_path.parent() {
-            if parent.is_dir() {
-                parent.to_path_buf()
-            } else {
-                return Ok(results);
-            }
-        } else {
-            return Ok(results);
-        };
-
-        // Recursively walk the directory
-        let mut stack = vec![dir_to_scan];
-        while let Some(dir) = stack.pop() {
-            let mut entries = match fs::read_dir(&dir).await {
-                Ok(entries) => entries,
-                Err(_) => continue,
-            };
-
-            while let Ok(Some(entry)) = entries.next_entry().await {
-                let entry_path = entry.path();
-                let metadata = match entry.metadata().await {
-                    Ok(m) => m,
-                    Err(_) => continue,
-                };
-
-                if metadata.is_dir() {
-                    stack.push(entry_path);
-                } else if metadata.is_file() {
-                    // Convert to relative path
-                    if let Ok(relative) = entry_path.strip_prefix(&self.base_path) {
-                        let relative_str = relative.to_string_lossy().to_string();
-                        // Only include if it matches the prefix
-                        if relative_str.starts_with(prefix) {
-                            results.push(relative_str);
-                        }
-                    }
-                }
-            }
-        }
+        self.walk(prefix, |path| results.push(path)).await;
 
         results.sort();
         debug!(count = results.len(), "Found files");
         Ok(results)
     }
+
+    fn uri(&self, path: &str) -> String {
+        self.resolve_path(path).to_string_lossy().to_string()
+    }
 }
 
 #[cfg(test)]
@@ -211,7 +305,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_write_and_read() {
-        let (_temp_dir, storage) = setup().await;
+        let (temp_dir, storage) = setup().await;
         let data = Bytes::from("hello world");
 
         let written = storage.write("test.txt", data.clone()).await.unwrap();
@@ -219,6 +313,60 @@ mod tests {
 
         let read_data = storage.read("test.txt").await.unwrap();
         assert_eq!(read_data, data);
+        assert_eq!(
+            storage.uri("test.txt"),
+            temp_dir.path().join("test.txt").to_string_lossy()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_parts() {
+        let (_temp_dir, storage) = setup().await;
+        let parts = vec![Bytes::from("hello "), Bytes::new(), Bytes::from("world")];
+
+        assert_eq!(storage.write_parts("parts.txt", parts).await.unwrap(), 11);
+        assert_eq!(storage.read("parts.txt").await.unwrap(), "hello world");
+    }
+
+    #[tokio::test]
+    async fn test_read_range() {
+        let (_temp_dir, storage) = setup().await;
+        storage
+            .write("range.txt", Bytes::from("hello world"))
+            .await
+            .unwrap();
+
+        let data = storage.read_range("range.txt", 6, 3).await.unwrap();
+        assert_eq!(data, Bytes::from("wor"));
+
+        // Ranges past the end are cut short
+        let data = storage.read_range("range.txt", 6, 100).await.unwrap();
+        assert_eq!(data, Bytes::from("world"));
+        assert!(storage
+            .read_range("range.txt", 50, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        assert!(matches!(
+            storage.read_range("missing.txt", 0, 10).await,
+            Err(Error::StoragePathNotFound { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_size() {
+        let (_temp_dir, storage) = setup().await;
+        storage
+            .write("sized.txt", Bytes::from("hello world"))
+            .await
+            .unwrap();
+
+        assert_eq!(storage.size("sized.txt").await.unwrap(), 11);
+        assert!(matches!(
+            storage.size("missing.txt").await,
+            Err(Error::StoragePathNotFound { .. })
+        ));
     }
 
     #[tokio::test]
@@ -299,6 +447,16 @@ mod tests {
 
         let all = storage.list("").await.unwrap();
         assert_eq!(all.len(), 3);
+
+        storage
+            .write(".hidden", Bytes::from("hidden"))
+            .await
+            .unwrap();
+        let some = storage.list_at_most("", 2).await.unwrap();
+        assert_eq!(
+            some,
+            vec!["checkpoints/epoch-1.bin", "checkpoints/epoch-2.bin"]
+        );
     }
 
     #[tokio::test]
@@ -0,0 +1,213 @@
+//! Storage backend wrapper recording operation metrics
+//!
+//! Counts calls, failures and bytes moved, and times every operation of the
+//! wrapped backend under `strata_storage_*` metrics labelled by backend and
+//! operation.
+
+use std::time::Instant;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use runtime_core::metrics::DEFAULT_BUCKETS;
+use runtime_core::{Counter, Histogram, MetricsRegistry, Result};
+
+use crate::StorageBackend;
+
+/// Metrics of one storage operation
+#[derive(Debug, Clone)]
+struct OpMetrics {
+    calls: Counter,
+    errors: Counter,
+    seconds: Histogram,
+}
+
+impl OpMetrics {
+    fn register(registry: &MetricsRegistry, backend: &str, op: &str) -> Self {
+        let labels = [("backend", backend), ("op", op)];
+        Self {
+            calls: registry.counter_with_labels(
+                "strata_storage_operations_total",
+                "Storage operations, by backend and operation",
+                &labels,
+            ),
+            errors: registry.counter_with_labels(
+                "strata_storage_errors_total",
+                "Storage operations that failed, by backend and operation",
+                &labels,
+            ),
+            seconds: registry.histogram_with_labels(
+                "strata_storage_operation_seconds",
+                "Storage operation latency, by backend and operation",
+                DEFAULT_BUCKETS,
+                &labels,
+            ),
+        }
+    }
+
+    fn record<T>(&self, start: Instant, result: &Result<T>) {
+        self.calls.inc();
+        self.seconds.observe_duration(start.elapsed());
+        if result.is_err() {
+            self.errors.inc();
+        }
+    }
+}
+
+/// A storage backend that records metrics for every operation
+///
+/// # Example
+///
+/// ```no_run
+/// use runtime_core::MetricsRegistry;
+/// use storage::{LocalStorage, MeteredStorage};
+///
+/// let registry = MetricsRegistry::new();
+/// let storage = MeteredStorage::new(LocalStorage::new("/tmp/data"), &registry, "local");
+/// ```
+#[derive(Debug, Clone)]
+pub struct MeteredStorage<B> {
+    inner: B,
+    read: OpMetrics,
+    write: OpMetrics,
+    delete: OpMetrics,
+    exists: OpMetrics,
+    size: OpMetrics,
+    list: OpMetrics,
+    read_bytes: Counter,
+    written_bytes: Counter,
+}
+
+impl<B: StorageBackend> MeteredStorage<B> {
+    /// Wrap `inner`, registering its metrics under the `backend` label
+    pub fn new(inner: B, registry: &MetricsRegistry, backend: &str) -> Self {
+        Self {
+            inner,
+            read: OpMetrics::register(registry, backend, "read"),
+            write: OpMetrics::register(registry, backend, "write"),
+            delete: OpMetrics::register(registry, backend, "delete"),
+            exists: OpMetrics::register(registry, backend, "exists"),
+            size: OpMetrics::register(registry, backend, "size"),
+            list: OpMetrics::register(registry, backend, "list"),
+            read_bytes: registry.counter_with_labels(
+                "strata_storage_read_bytes_total",
+                "Bytes read from storage, by backend",
+                &[("backend", backend)],
+            ),
+            written_bytes: registry.counter_with_labels(
+                "strata_storage_written_bytes_total",
+                "Bytes written to storage, by backend",
+                &[("backend", backend)],
+            ),
+        }
+    }
+
+    /// The wrapped backend
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<B: StorageBackend> StorageBackend for MeteredStorage<B> {
+    async fn read(&self, path: &str) -> Result<Bytes> {
+        let start = Instant::now();
+        let result = self.inner.read(path).await;
+        self.read.record(start, &result);
+        if let Ok(data) = &result {
+            self.read_bytes.inc_by(data.len() as u64);
+        }
+        result
+    }
+
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> Result<Bytes> {
+        let start = Instant::now();
+        let result = self.inner.read_range(path, offset, len).await;
+        self.read.record(start, &result);
+        if let Ok(data) = &result {
+            self.read_bytes.inc_by(data.len() as u64);
+        }
+        result
+    }
+
+    async fn write(&self, path: &str, data: Bytes) -> Result<u64> {
+        let start = Instant::now();
+        let result = self.inner.write(path, data).await;
+        self.write.record(start, &result);
+        if let Ok(size) = &result {
+            self.written_bytes.inc_by(*size);
+        }
+        result
+    }
+
+    async fn write_parts(&self, path: &str, parts: Vec<Bytes>) -> Result<u64> {
+        let start = Instant::now();
+        let result = self.inner.write_parts(path, parts).await;
+        self.write.record(start, &result);
+        if let Ok(size) = &result {
+            self.written_bytes.inc_by(*size);
+        }
+        result
+    }
+
+    async fn delete(&self, path: &str) -> Result<()> {
+        let start = Instant::now();
+        let result = self.inner.delete(path).await;
+        self.delete.record(start, &result);
+        result
+    }
+
+    async fn exists(&self, path: &str) -> Result<bool> {
+        let start = Instant::now();
+        let result = self.inner.exists(path).await;
+        self.exists.record(start, &result);
+        result
+    }
+
+    async fn size(&self, path: &str) -> Result<u64> {
+        let start = Instant::now();
+        let result = self.inner.size(path).await;
+        self.size.record(start, &result);
+        result
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let start = Instant::now();
+        let result = self.inner.list(prefix).await;
+        self.list.record(start, &result);
+        result
+    }
+
+    fn uri(&self, path: &str) -> String {
+        self.inner.uri(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LocalStorage;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_metered_storage() {
+        let temp_dir = TempDir::new().unwrap();
+        let registry = MetricsRegistry::new();
+        let storage = MeteredStorage::new(LocalStorage::new(temp_dir.path()), &registry, "local");
+
+        storage
+            .write("a.bin", Bytes::from_static(b"hello"))
+            .await
+            .unwrap();
+        storage.read_range("a.bin", 1, 3).await.unwrap();
+        assert!(storage.read("missing.bin").await.is_err());
+
+        let body = registry.render();
+        assert!(body.contains("strata_storage_operations_total{backend=\"local\",op=\"read\"} 2\n"));
+        assert!(body.contains("strata_storage_errors_total{backend=\"local\",op=\"read\"} 1\n"));
+        assert!(body.contains(
+            "strata_storage_operation_seconds_count{backend=\"local\",op=\"write\"} 1\n"
+        ));
+        assert!(body.contains("strata_storage_read_bytes_total{backend=\"local\"} 3\n"));
+        assert!(body.contains("strata_storage_written_bytes_total{backend=\"local\"} 5\n"));
+    }
+}
@@ -5,8 +5,6 @@
 //! - Exponential backoff retry logic
 //! - Custom endpoint support (for MinIO, LocalStack, etc.)
 
-use std::time::Duration;
-
 use async_trait::async_trait;
 use aws_config::BehaviorVersion;
 use aws_sdk_s3::{
@@ -15,9 +13,11 @@ use aws_sdk_s3::{
     types::{CompletedMultipartUpload, CompletedPart},
     Client,
 };
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
+use runtime_core::config::RetryConfig;
+use runtime_core::retry::retry;
 use runtime_core::{Error, Result};
-use tracing::{debug, instrument, warn};
+use tracing::{debug, instrument};
 
 use crate::StorageBackend;
 
@@ -27,12 +27,6 @@ const MULTIPART_THRESHOLD: usize = 5 * 1024 * 1024;
 /// Part size for multipart uploads (5 MB minimum required by S3)
 const MULTIPART_PART_SIZE: usize = 5 * 1024 * 1024;
 
-/// Maximum retry attempts for transient failures
-const MAX_RETRIES: u32 = 3;
-
-/// Base delay for exponential backoff (milliseconds)
-const BASE_RETRY_DELAY_MS: u64 = 100;
-
 /// S3-compatible storage backend
 ///
 /// Supports Amazon S3 and S3-compatible services like MinIO.
@@ -41,6 +35,7 @@ pub struct S3Storage {
     client: Client,
     bucket: String,
     prefix: String,
+    retry: RetryConfig,
 }
 
 /// Configuration for S3Storage
@@ -56,6 +51,8 @@ pub struct S3Config {
     pub region: Option<String>,
     /// Force path-style addressing (required for MinIO)
     pub force_path_style: bool,
+    /// Retry and backoff settings for transient failures
+    pub retry: RetryConfig,
 }
 
 impl Default for S3Config {
@@ -66,6 +63,7 @@ impl Default for S3Config {
             endpoint_url: None,
             region: Some("us-east-1".to_string()),
             force_path_style: false,
+            retry: RetryConfig::default(),
         }
     }
 }
@@ -107,6 +105,7 @@ impl S3Storage {
             client,
             bucket: config.bucket,
             prefix: config.prefix.unwrap_or_default(),
+            retry: config.retry,
         }
     }
 
@@ -130,46 +129,9 @@ impl S3Storage {
         }
     }
 
-    /// Execute an async operation with exponential backoff retry
-    async fn with_retry<T, F, Fut>(&self, operation: &str, f: F) -> Result<T>
-    where
-        F: Fn() -> Fut,
-        Fut: std::future::Future<Output = Result<T>>,
-    {
-        let mut last_error = None;
-
-        for attempt in 0..MAX_RETRIES {
-            match f().await {
-                Ok(result) => return Ok(result),
-                Err(e) => {
-                    if !e.is_retryable() {
-                        return Err(e);
-                    }
-
-                    let delay = Duration::from_millis(BASE_RETRY_DELAY_MS * (1 << attempt));
-                    warn!(
-                        %operation,
-                        attempt = attempt + 1,
-                        max_retries = MAX_RETRIES,
-                        delay_ms = delay.as_millis(),
-                        error = %e,
-                        "Retrying after transient error"
-                    );
-
-                    tokio::time::sleep(delay).await;
-                    last_error = Some(e);
-                }
-            }
-        }
-
-        Err(last_error.unwrap_or_else(|| Error::Storage {
-            message: format!("{} failed after {} retries", operation, MAX_RETRIES),
-        }))
-    }
-
     /// Perform multipart upload for large files
-    async fn multipart_upload(&self, key: &str, data: Bytes) -> Result<u64> {
-        let size = data.len() as u64;
+    async fn multipart_upload(&self, key: &str, parts: &[Bytes]) -> Result<u64> {
+        let size = parts.iter().map(|part| part.len() as u64).sum::<u64>();
 
         // Initiate multipart upload
         let create_result = self
@@ -179,23 +141,23 @@ impl S3Storage {
             .key(key)
             .send()
             .await
-            .map_err(|e| Error::Storage {
-                message: format!("Failed to initiate multipart upload: {}", e),
+            .map_err(|e| {
+                Error::storage("Failed to initiate multipart upload")
+                    .with_path(key)
+                    .with_source(e)
             })?;
 
-        let upload_id = create_result.upload_id().ok_or_else(|| Error::Storage {
-            message: "No upload_id returned".to_string(),
-        })?;
+        let upload_id = create_result
+            .upload_id()
+            .ok_or_else(|| Error::storage("No upload_id returned"))?;
 
         debug!(key, upload_id, size, "Started multipart upload");
 
         let mut completed_parts = Vec::new();
         let mut offset = 0;
-        let mut part_number = 1;
 
-        while offset < data.len() {
-            let end = std::cmp::min(offset + MULTIPART_PART_SIZE, data.len());
-            let part_data = data.slice(offset..end);
+        for (part_number, part_data) in (1..).zip(chunk_parts(parts, MULTIPART_PART_SIZE)) {
+            let end = offset + part_data.len();
 
             let upload_part_result = self
                 .client
@@ -204,15 +166,15 @@ impl S3Storage {
                 .key(key)
                 .upload_id(upload_id)
                 .part_number(part_number)
-                .body(ByteStream::from(part_data.to_vec()))
+                .body(ByteStream::from(part_data))
                 .send()
                 .await
                 .map_err(|e| {
                     // Attempt to abort the upload on failure
                     self.abort_multipart_upload(key, upload_id);
-                    Error::Storage {
-                        message: format!("Failed to upload part {}: {}", part_number, e),
-                    }
+                    Error::storage(format!("Failed to upload part {}", part_number))
+                        .with_path(key)
+                        .with_source(e)
                 })?;
 
             let etag = upload_part_result.e_tag().map(String::from);
@@ -225,7 +187,6 @@ impl S3Storage {
 
             debug!(part_number, offset, end, "Uploaded part");
             offset = end;
-            part_number += 1;
         }
 
         // Complete multipart upload
@@ -241,8 +202,10 @@ impl S3Storage {
             .multipart_upload(completed_upload)
             .send()
             .await
-            .map_err(|e| Error::Storage {
-                message: format!("Failed to complete multipart upload: {}", e),
+            .map_err(|e| {
+                Error::storage("Failed to complete multipart upload")
+                    .with_path(key)
+                    .with_source(e)
             })?;
 
         debug!(key, size, "Completed multipart upload");
@@ -268,6 +231,35 @@ impl S3Storage {
     }
 }
 
+/// `parts` regrouped into chunks of `size` bytes, the last one shorter
+///
+/// Chunks that lie within one part share its memory; only chunks spanning
+/// several parts are copied, so at most one chunk is copied at a time.
+fn chunk_parts(parts: &[Bytes], size: usize) -> Vec<Bytes> {
+    let mut chunks = Vec::new();
+    let mut pending = BytesMut::new();
+    for (index, part) in parts.iter().enumerate() {
+        let last = index + 1 == parts.len();
+        let mut part = part.clone();
+        while !part.is_empty() {
+            if pending.is_empty() && (part.len() >= size || last) {
+                let len = part.len().min(size);
+                chunks.push(part.split_to(len));
+                continue;
+            }
+            let len = part.len().min(size - pending.len());
+            pending.extend_from_slice(&part.split_to(len));
+            if pending.len() == size {
+                chunks.push(pending.split().freeze());
+            }
+        }
+    }
+    if !pending.is_empty() {
+        chunks.push(pending.freeze());
+    }
+    chunks
+}
+
 #[async_trait]
 impl StorageBackend for S3Storage {
     #[instrument(skip(self), fields(backend = "s3", bucket = %self.bucket))]
@@ -275,7 +267,7 @@ impl StorageBackend for S3Storage {
         let key = self.s3_key(path);
         debug!(%key, "Reading from S3");
 
-        self.with_retry("read", || async {
+        retry(&self.retry, "read", || async {
             let result = self
                 .client
                 .get_object()
@@ -289,14 +281,61 @@ impl StorageBackend for S3Storage {
                             path: path.to_string(),
                         }
                     } else {
-                        Error::Storage {
-                            message: format!("S3 get_object failed: {}", e),
-                        }
+                        Error::storage("S3 get_object failed")
+                            .with_path(&key)
+                            .with_source(e)
                     }
                 })?;
 
-            let bytes = result.body.collect().await.map_err(|e| Error::Storage {
-                message: format!("Failed to read S3 response body: {}", e),
+            let bytes = result.body.collect().await.map_err(|e| {
+                Error::storage("Failed to read S3 response body")
+                    .with_path(&key)
+                    .with_source(e)
+            })?;
+
+            Ok(Bytes::from(bytes.to_vec()))
+        })
+        .await
+    }
+
+    #[instrument(skip(self), fields(backend = "s3", bucket = %self.bucket))]
+    async fn read_range(&self, path: &str, offset: u64, len: u64) -> Result<Bytes> {
+        if len == 0 {
+            return Ok(Bytes::new());
+        }
+        let key = self.s3_key(path);
+        let range = format!("bytes={}-{}", offset, offset.saturating_add(len - 1));
+        debug!(%key, %range, "Reading range from S3");
+
+        retry(&self.retry, "read_range", || async {
+            let result = match self
+                .client
+                .get_object()
+                .bucket(&self.bucket)
+                .key(&key)
+                .range(&range)
+                .send()
+                .await
+            {
+                Ok(result) => result,
+                // The range starts past the end of the object
+                Err(e) if e.to_string().contains("InvalidRange") => return Ok(Bytes::new()),
+                Err(e) if e.to_string().contains("NoSuchKey") => {
+                    return Err(Error::StoragePathNotFound {
+                        path: path.to_string(),
+                    })
+                }
+                Err(e) => {
+                    return Err(Error::storage("S3 get_object failed")
+                        .with_path(&key)
+                        .with_source(e))
+                }
+            };
+
+            let bytes = result.body.collect().await.map_err(|e| {
+                Error::storage("Failed to read S3 response body")
+                    .with_path(&key)
+                    .with_source(e)
             })?;
 
             Ok(Bytes::from(bytes.to_vec()))
@@ -306,15 +345,25 @@ impl StorageBackend for S3Storage {
 
     #[instrument(skip(self, data), fields(backend = "s3", bucket = %self.bucket, size = data.len()))]
     async fn write(&self, path: &str, data: Bytes) -> Result<u64> {
+        self.write_parts(path, vec![data]).await
+    }
+
+    #[instrument(skip(self, parts), fields(backend = "s3", bucket = %self.bucket))]
+    async fn write_parts(&self, path: &str, parts: Vec<Bytes>) -> Result<u64> {
         let key = self.s3_key(path);
-        let size = data.len();
+        let size = parts.iter().map(Bytes::len).sum::<usize>();
         debug!(%key, size, "Writing to S3");
 
         if size > MULTIPART_THRESHOLD {
-            return self.multipart_upload(&key, data).await;
+            return self.multipart_upload(&key, &parts).await;
         }
 
-        self.with_retry("write", || {
+        // Fits one request body
+        let data = chunk_parts(&parts, MULTIPART_THRESHOLD)
+            .pop()
+            .unwrap_or_default();
+
+        retry(&self.retry, "write", || {
             let data = data.clone();
             let key = key.clone();
             async move {
@@ -322,11 +371,13 @@ impl StorageBackend for S3Storage {
                     .put_object()
                     .bucket(&self.bucket)
                     .key(&key)
-                    .body(ByteStream::from(data.to_vec()))
+                    .body(ByteStream::from(data))
                     .send()
                     .await
-                    .map_err(|e| Error::Storage {
-                        message: format!("S3 put_object failed: {}", e),
+                    .map_err(|e| {
+                        Error::storage("S3 put_object failed")
+                            .with_path(&key)
+                            .with_source(e)
                     })?;
 
                 Ok(size as u64)
@@ -340,15 +391,17 @@ impl StorageBackend for S3Storage {
         let key = self.s3_key(path);
         debug!(%key, "Deleting from S3");
 
-        self.with_retry("delete", || async {
+        retry(&self.retry, "delete", || async {
             self.client
                 .delete_object()
                 .bucket(&self.bucket)
                 .key(&key)
                 .send()
                 .await
-                .map_err(|e| Error::Storage {
-                    message: format!("S3 delete_object failed: {}", e),
+                .map_err(|e| {
+                    Error::storage("S3 delete_object failed")
+                        .with_path(&key)
+                        .with_source(e)
                 })?;
 
             Ok(())
@@ -374,14 +427,36 @@ impl StorageBackend for S3Storage {
                 if e.to_string().contains("NotFound") || e.to_string().contains("404") {
                     Ok(false)
                 } else {
-                    Err(Error::Storage {
-                        message: format!("S3 head_object failed: {}", e),
-                    })
+                    Err(Error::storage("S3 head_object failed")
+                        .with_path(&key)
+                        .with_source(e))
                 }
             }
         }
     }
 
+    #[instrument(skip(self), fields(backend = "s3", bucket = %self.bucket))]
+    async fn size(&self, path: &str) -> Result<u64> {
+        let key = self.s3_key(path);
+        let response = self
+            .client
+            .head_object()
+            .bucket(&self.bucket)
+            .key(&key)
+            .send()
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("NotFound") || e.to_string().contains("404") {
+                    Error::StoragePathNotFound { path: key.clone() }
+                } else {
+                    Error::storage("S3 head_object failed")
+                        .with_path(&key)
+                        .with_source(e)
+                }
+            })?;
+        Ok(response.content_length().unwrap_or(0).max(0) as u64)
+    }
+
     #[instrument(skip(self), fields(backend = "s3", bucket = %self.bucket))]
     async fn list(&self, prefix: &str) -> Result<Vec<String>> {
         let s3_prefix = self.s3_key(prefix);
@@ -401,8 +476,10 @@ impl StorageBackend for S3Storage {
                 request = request.continuation_token(token);
             }
 
-            let response = request.send().await.map_err(|e| Error::Storage {
-                message: format!("S3 list_objects failed: {}", e),
+            let response = request.send().await.map_err(|e| {
+                Error::storage("S3 list_objects failed")
+                    .with_path(&s3_prefix)
+                    .with_source(e)
             })?;
 
             for object in response.contents() {
@@ -429,6 +506,10 @@ impl StorageBackend for S3Storage {
         debug!(count = results.len(), "Found S3 objects");
         Ok(results)
     }
+
+    fn uri(&self, path: &str) -> String {
+        format!("s3://{}/{}", self.bucket, self.s3_key(path))
+    }
 }
 
 #[cfg(test)]
@@ -470,6 +551,22 @@ mod tests {
         assert_eq!(make_s3_key(prefix_no_slash, "file.bin"), "data/file.bin");
     }
 
+    #[test]
+    fn test_chunk_parts() {
+        let payload = Bytes::from(vec![7u8; 10]);
+        let chunks = chunk_parts(&[Bytes::from_static(b"head"), payload.clone()], 4);
+        let lens: Vec<usize> = chunks.iter().map(Bytes::len).collect();
+        assert_eq!(lens, vec![4, 4, 4, 2]);
+        assert_eq!(chunks.concat(), [b"head".as_slice(), &payload[..]].concat());
+        // Chunks within the payload are not copied
+        assert_eq!(chunks[1].as_ptr(), payload.as_ptr());
+
+        let chunks = chunk_parts(&[Bytes::from_static(b"ab"), payload], 4);
+        let lens: Vec<usize> = chunks.iter().map(Bytes::len).collect();
+        assert_eq!(lens, vec![4, 4, 4]);
+        assert!(chunk_parts(&[], 4).is_empty());
+    }
+
     #[test]
     fn test_s3_config_default() {
         let config = S3Config::default();
@@ -488,6 +585,7 @@ mod tests {
             endpoint_url: Some("http://localhost:9000".to_string()),
             region: Some("us-west-2".to_string()),
             force_path_style: true,
+            ..Default::default()
         };
 
         assert_eq!(config.bucket, "my-bucket");
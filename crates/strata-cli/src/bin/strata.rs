@@ -0,0 +1,34 @@
+//! `strata` binary entry point
+//!
+//! Runs one administrative command against the coordinator and exits
+//! non-zero if it fails.
+
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use strata_cli::Cli;
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match cli.run(&mut std::io::stdout().lock()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            // Transport errors only say what failed in their sources, some
+            // of which repeat the error they wrap
+            let mut message = err.to_string();
+            let mut source = std::error::Error::source(&err);
+            while let Some(err) = source {
+                let text = err.to_string();
+                if !message.ends_with(&text) {
+                    message.push_str(": ");
+                    message.push_str(&text);
+                }
+                source = err.source();
+            }
+            eprintln!("error: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,728 @@
+//! Command-line arguments and the coordinator calls behind them
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::time::Duration;
+
+use chrono::{DateTime, SecondsFormat};
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+
+use coordinator::proto::coordinator_client::CoordinatorClient;
+use coordinator::proto::{
+    worker_status, BarrierSnapshot, CheckpointInfo, CheckpointType, ClusterState,
+    ClusterStateRequest, DatasetInfo, DrainWorkerRequest, ReleaseBarrierRequest,
+};
+
+use crate::output::{Format, Output, Record};
+
+/// How long to wait for the coordinator to accept a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long to wait for a response
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Checkpoints fetched when filtering or looking one up by ID
+const CHECKPOINT_SCAN_LIMIT: i32 = 10_000;
+
+/// Errors reported by the CLI
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("invalid coordinator address {address}")]
+    Address {
+        address: String,
+        source: tonic::transport::Error,
+    },
+
+    #[error("cannot reach coordinator at {address}")]
+    Connect {
+        address: String,
+        source: tonic::transport::Error,
+    },
+
+    #[error("{} ({:?})", .0.message(), .0.code())]
+    Rpc(Status),
+
+    #[error("{0}")]
+    NotFound(String),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+// Not `#[from]`: that would make the status a source, and its `Display`
+// repeats the message with the response metadata
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        Error::Rpc(status)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Administer a Strata cluster through its coordinator
+#[derive(Parser, Debug)]
+#[command(name = "strata", version)]
+pub struct Cli {
+    /// Coordinator gRPC address
+    #[arg(
+        long,
+        short,
+        global = true,
+        env = "STRATA_COORDINATOR",
+        default_value = "http://localhost:50051"
+    )]
+    pub coordinator: String,
+
+    /// Output format
+    #[arg(long, short, global = true, value_enum, default_value_t = Format::Table)]
+    pub output: Format,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Registered workers
+    #[command(subcommand, visible_alias = "worker")]
+    Workers(WorkersCommand),
+
+    /// Registered datasets
+    #[command(subcommand, visible_alias = "dataset")]
+    Datasets(DatasetsCommand),
+
+    /// Recorded checkpoints
+    #[command(subcommand, visible_alias = "checkpoints")]
+    Ckpt(CkptCommand),
+
+    /// Open barriers
+    #[command(subcommand, visible_alias = "barriers")]
+    Barrier(BarrierCommand),
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WorkersCommand {
+    /// List registered workers
+    List,
+
+    /// Take a worker out of rotation; its shards move to the other workers
+    Drain {
+        /// Worker to drain
+        worker_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum DatasetsCommand {
+    /// List registered datasets
+    List,
+
+    /// Register a dataset and shard it across the workers
+    Register {
+        /// Unique dataset identifier
+        dataset_id: String,
+
+        /// Dataset location, e.g. /data/train or s3://bucket/train
+        path: String,
+
+        /// Total number of samples
+        #[arg(long)]
+        samples: u64,
+
+        /// Samples per shard
+        #[arg(long)]
+        shard_size: u64,
+
+        /// Storage format, e.g. parquet or tfrecord
+        #[arg(long, default_value = "auto")]
+        format: String,
+
+        /// Keep shards in order every epoch
+        #[arg(long)]
+        no_shuffle: bool,
+
+        /// Shuffle seed
+        #[arg(long, default_value_t = 42)]
+        seed: i64,
+
+        /// Extra metadata, as KEY=VALUE; repeatable
+        #[arg(long = "metadata", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        metadata: Vec<(String, String)>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CkptCommand {
+    /// List the most recent checkpoints
+    List {
+        /// Checkpoints to show
+        #[arg(long, default_value_t = 20)]
+        limit: usize,
+
+        /// Only checkpoints written by this worker
+        #[arg(long)]
+        worker: Option<String>,
+    },
+
+    /// Show every field of a checkpoint
+    Inspect {
+        /// Checkpoint to show
+        checkpoint_id: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BarrierCommand {
+    /// List open and recently closed barriers
+    List,
+
+    /// Release the workers waiting on a barrier that is stuck on missing workers
+    Release {
+        /// Barrier to close
+        barrier_id: String,
+
+        /// Generation to close; every open generation if not given
+        #[arg(long)]
+        generation: Option<u64>,
+
+        /// Abort the barrier instead, failing the waiting workers
+        #[arg(long)]
+        abort: bool,
+
+        /// Reason recorded in the coordinator's events
+        #[arg(long)]
+        reason: Option<String>,
+    },
+}
+
+impl Cli {
+    /// Run the command against the coordinator and print its result
+    pub async fn run(&self, out: &mut impl Write) -> Result<()> {
+        let mut client = connect(&self.coordinator).await?;
+        let output = match &self.command {
+            Command::Workers(WorkersCommand::List) => list_workers(&mut client).await?,
+            Command::Workers(WorkersCommand::Drain { worker_id }) => {
+                drain_worker(&mut client, worker_id).await?
+            }
+            Command::Datasets(DatasetsCommand::List) => list_datasets(&mut client).await?,
+            Command::Datasets(DatasetsCommand::Register {
+                dataset_id,
+                path,
+                samples,
+                shard_size,
+                format,
+                no_shuffle,
+                seed,
+                metadata,
+            }) => {
+                let info = DatasetInfo {
+                    dataset_id: dataset_id.clone(),
+                    path: path.clone(),
+                    format: format.clone(),
+                    total_samples: *samples as i64,
+                    shard_size: *shard_size as i64,
+                    shuffle: !no_shuffle,
+                    seed: *seed,
+                    metadata: metadata.iter().cloned().collect::<HashMap<_, _>>(),
+                };
+                register_dataset(&mut client, info).await?
+            }
+            Command::Ckpt(CkptCommand::List { limit, worker }) => {
+                list_checkpoints(&mut client, *limit, worker.as_deref()).await?
+            }
+            Command::Ckpt(CkptCommand::Inspect { checkpoint_id }) => {
+                inspect_checkpoint(&mut client, checkpoint_id).await?
+            }
+            Command::Barrier(BarrierCommand::List) => list_barriers(&mut client).await?,
+            Command::Barrier(BarrierCommand::Release {
+                barrier_id,
+                generation,
+                abort,
+                reason,
+            }) => {
+                let request = ReleaseBarrierRequest {
+                    barrier_id: barrier_id.clone(),
+                    generation: generation.unwrap_or(0) as i64,
+                    abort: *abort,
+                    reason: reason.clone().unwrap_or_default(),
+                };
+                release_barrier(&mut client, request).await?
+            }
+        };
+        output.write(self.output, out)?;
+        Ok(())
+    }
+}
+
+/// Connect to the coordinator, defaulting to plain HTTP without a scheme
+async fn connect(address: &str) -> Result<CoordinatorClient<Channel>> {
+    let address = if address.contains("://") {
+        address.to_string()
+    } else {
+        format!("http://{address}")
+    };
+    let endpoint = Endpoint::from_shared(address.clone())
+        .map_err(|source| Error::Address {
+            address: address.clone(),
+            source,
+        })?
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(REQUEST_TIMEOUT);
+    let channel = endpoint
+        .connect()
+        .await
+        .map_err(|source| Error::Connect { address, source })?;
+    Ok(CoordinatorClient::new(channel))
+}
+
+fn parse_key_value(s: &str) -> std::result::Result<(String, String), String> {
+    match s.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("expected KEY=VALUE, got {s:?}")),
+    }
+}
+
+/// RFC 3339 time of a ms timestamp, null when unset
+fn timestamp(ms: i64) -> Value {
+    match DateTime::from_timestamp_millis(ms) {
+        Some(time) if ms > 0 => json!(time.to_rfc3339_opts(SecondsFormat::Secs, true)),
+        _ => Value::Null,
+    }
+}
+
+async fn cluster_state(
+    client: &mut CoordinatorClient<Channel>,
+    max_checkpoints: i32,
+) -> Result<ClusterState> {
+    let state = client
+        .get_cluster_state(Request::new(ClusterStateRequest { max_checkpoints }))
+        .await?
+        .into_inner();
+    Ok(state)
+}
+
+async fn list_workers(client: &mut CoordinatorClient<Channel>) -> Result<Output> {
+    let mut workers = cluster_state(client, 0).await?.workers;
+    workers.sort_by_key(|w| w.rank);
+
+    let rows = workers
+        .into_iter()
+        .map(|w| {
+            let state = worker_status::State::try_from(w.state)
+                .unwrap_or(worker_status::State::Unknown)
+                .as_str_name()
+                .to_lowercase();
+            vec![
+                ("worker_id", json!(w.worker_id)),
+                ("hostname", json!(w.hostname)),
+                ("port", json!(w.port)),
+                ("rank", json!(w.rank)),
+                ("state", json!(state)),
+                ("draining", json!(w.draining)),
+                ("step", json!(w.current_step)),
+                ("epoch", json!(w.current_epoch)),
+                ("last_heartbeat", timestamp(w.last_heartbeat_ms)),
+                ("registered_at", timestamp(w.registered_at_ms)),
+            ]
+        })
+        .collect();
+    Ok(Output::List {
+        columns: &[
+            "worker_id",
+            "hostname",
+            "rank",
+            "state",
+            "draining",
+            "step",
+            "epoch",
+            "last_heartbeat",
+        ],
+        rows,
+    })
+}
+
+async fn drain_worker(client: &mut CoordinatorClient<Channel>, worker_id: &str) -> Result<Output> {
+    let response = client
+        .drain_worker(Request::new(DrainWorkerRequest {
+            worker_id: worker_id.to_string(),
+        }))
+        .await?
+        .into_inner();
+    Ok(Output::Item(vec![
+        ("worker_id", json!(worker_id)),
+        ("already_draining", json!(response.already_draining)),
+    ]))
+}
+
+async fn list_datasets(client: &mut CoordinatorClient<Channel>) -> Result<Output> {
+    let mut datasets = cluster_state(client, 0).await?.datasets;
+    datasets.sort_by(|a, b| {
+        let id =
+            |d: &coordinator::proto::DatasetState| d.info.as_ref().map(|i| i.dataset_id.clone());
+        id(a).cmp(&id(b))
+    });
+
+    let rows = datasets
+        .into_iter()
+        .map(|dataset| {
+            let info = dataset.info.unwrap_or_default();
+            vec![
+                ("dataset_id", json!(info.dataset_id)),
+                ("path", json!(info.path)),
+                ("format", json!(info.format)),
+                ("samples", json!(info.total_samples)),
+                ("shard_size", json!(info.shard_size)),
+                ("shards", json!(dataset.total_shards)),
+                ("epoch", json!(dataset.current_epoch)),
+                ("shuffle", json!(info.shuffle)),
+                ("seed", json!(info.seed)),
+                ("metadata", json!(info.metadata)),
+            ]
+        })
+        .collect();
+    Ok(Output::List {
+        columns: &["dataset_id", "path", "format", "samples", "shards", "epoch"],
+        rows,
+    })
+}
+
+async fn register_dataset(
+    client: &mut CoordinatorClient<Channel>,
+    info: DatasetInfo,
+) -> Result<Output> {
+    let ack = client
+        .register_dataset(Request::new(info))
+        .await?
+        .into_inner();
+    Ok(Output::Item(vec![
+        ("dataset_id", json!(ack.dataset_id)),
+        ("shards", json!(ack.total_shards)),
+        ("message", json!(ack.message)),
+    ]))
+}
+
+fn checkpoint_record(checkpoint: CheckpointInfo) -> Record {
+    let kind = CheckpointType::try_from(checkpoint.r#type)
+        .unwrap_or(CheckpointType::Full)
+        .as_str_name()
+        .to_lowercase();
+    vec![
+        ("checkpoint_id", json!(checkpoint.checkpoint_id)),
+        ("worker_id", json!(checkpoint.worker_id)),
+        ("step", json!(checkpoint.step)),
+        ("epoch", json!(checkpoint.epoch)),
+        ("type", json!(kind)),
+        ("size_bytes", json!(checkpoint.size_bytes)),
+        ("created", timestamp(checkpoint.timestamp_ms)),
+        ("storage_path", json!(checkpoint.storage_path)),
+        ("metadata", json!(checkpoint.metadata)),
+    ]
+}
+
+async fn list_checkpoints(
+    client: &mut CoordinatorClient<Channel>,
+    limit: usize,
+    worker: Option<&str>,
+) -> Result<Output> {
+    // The filter runs here, so look further back when there is one
+    let max_checkpoints = match worker {
+        Some(_) => CHECKPOINT_SCAN_LIMIT,
+        None => limit.clamp(1, CHECKPOINT_SCAN_LIMIT as usize) as i32,
+    };
+    let rows = cluster_state(client, max_checkpoints)
+        .await?
+        .checkpoints
+        .into_iter()
+        .filter(|c| worker.is_none_or(|w| c.worker_id == w))
+        .take(limit)
+        .map(checkpoint_record)
+        .collect();
+    Ok(Output::List {
+        columns: &[
+            "checkpoint_id",
+            "worker_id",
+            "step",
+            "epoch",
+            "type",
+            "size_bytes",
+            "created",
+        ],
+        rows,
+    })
+}
+
+async fn inspect_checkpoint(
+    client: &mut CoordinatorClient<Channel>,
+    checkpoint_id: &str,
+) -> Result<Output> {
+    cluster_state(client, CHECKPOINT_SCAN_LIMIT)
+        .await?
+        .checkpoints
+        .into_iter()
+        .find(|c| c.checkpoint_id == checkpoint_id)
+        .map(|c| Output::Item(checkpoint_record(c)))
+        .ok_or_else(|| Error::NotFound(format!("checkpoint {checkpoint_id} not found")))
+}
+
+fn barrier_record(barrier: BarrierSnapshot) -> Record {
+    let status = if barrier.aborted {
+        "aborted"
+    } else if barrier.released {
+        "released"
+    } else {
+        "waiting"
+    };
+    vec![
+        ("barrier_id", json!(barrier.barrier_id)),
+        ("generation", json!(barrier.generation)),
+        ("arrived", json!(barrier.arrived)),
+        ("expected", json!(barrier.expected)),
+        ("status", json!(status)),
+    ]
+}
+
+const BARRIER_COLUMNS: &[&str] = &["barrier_id", "generation", "arrived", "expected", "status"];
+
+async fn list_barriers(client: &mut CoordinatorClient<Channel>) -> Result<Output> {
+    let rows = cluster_state(client, 0)
+        .await?
+        .barriers
+        .into_iter()
+        .map(barrier_record)
+        .collect();
+    Ok(Output::List {
+        columns: BARRIER_COLUMNS,
+        rows,
+    })
+}
+
+async fn release_barrier(
+    client: &mut CoordinatorClient<Channel>,
+    request: ReleaseBarrierRequest,
+) -> Result<Output> {
+    let rows = client
+        .release_barrier(Request::new(request))
+        .await?
+        .into_inner()
+        .barriers
+        .into_iter()
+        .map(barrier_record)
+        .collect();
+    Ok(Output::List {
+        columns: BARRIER_COLUMNS,
+        rows,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinator::proto::WorkerInfo;
+    use coordinator::{CoordinatorServer, CoordinatorService};
+    use runtime_core::config::RuntimeConfig;
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    struct TestCoordinator {
+        address: String,
+        _stop: oneshot::Sender<()>,
+        _dir: tempfile::TempDir,
+    }
+
+    async fn start_coordinator() -> TestCoordinator {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = RuntimeConfig::default();
+        config.storage.base_path = dir.path().to_string_lossy().into_owned();
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (stop, stopped) = oneshot::channel::<()>();
+        tokio::spawn(
+            CoordinatorServer::new(service.clone()).run_on_listener(listener, async {
+                let _ = stopped.await;
+            }),
+        );
+
+        TestCoordinator {
+            address,
+            _stop: stop,
+            _dir: dir,
+        }
+    }
+
+    async fn run_json(coordinator: &TestCoordinator, args: &[&str]) -> Result<Value> {
+        let cli = Cli::try_parse_from(
+            [
+                "strata",
+                "--coordinator",
+                &coordinator.address,
+                "-o",
+                "json",
+            ]
+            .iter()
+            .chain(args),
+        )
+        .unwrap();
+        let mut out = Vec::new();
+        cli.run(&mut out).await?;
+        Ok(serde_json::from_slice(&out).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        let cli = Cli::try_parse_from([
+            "strata",
+            "datasets",
+            "register",
+            "train",
+            "/data/train",
+            "--samples",
+            "1000",
+            "--shard-size",
+            "100",
+            "--metadata",
+            "owner=ml",
+            "-o",
+            "json",
+        ])
+        .unwrap();
+        assert_eq!(cli.output, Format::Json);
+        match cli.command {
+            Command::Datasets(DatasetsCommand::Register {
+                samples, metadata, ..
+            }) => {
+                assert_eq!(samples, 1000);
+                assert_eq!(metadata, vec![("owner".to_string(), "ml".to_string())]);
+            }
+            other => panic!("unexpected command {other:?}"),
+        }
+
+        // Singular aliases read better for commands on one item
+        let cli = Cli::try_parse_from(["strata", "worker", "drain", "w1"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Command::Workers(WorkersCommand::Drain { .. })
+        ));
+
+        assert!(Cli::try_parse_from([
+            "strata",
+            "datasets",
+            "register",
+            "a",
+            "b",
+            "--metadata",
+            "x"
+        ])
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_commands() {
+        let coordinator = start_coordinator().await;
+        let mut client = connect(&coordinator.address).await.unwrap();
+        for id in ["worker-1", "worker-2"] {
+            client
+                .register_worker(WorkerInfo {
+                    worker_id: id.to_string(),
+                    hostname: "localhost".to_string(),
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+
+        let workers = run_json(&coordinator, &["workers", "list"]).await.unwrap();
+        assert_eq!(workers.as_array().unwrap().len(), 2);
+        assert_eq!(workers[0]["draining"], json!(false));
+
+        let drained = run_json(&coordinator, &["worker", "drain", "worker-1"])
+            .await
+            .unwrap();
+        assert_eq!(drained["already_draining"], json!(false));
+        let workers = run_json(&coordinator, &["workers", "list"]).await.unwrap();
+        let worker = |id: &str| {
+            workers
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|w| w["worker_id"] == id)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(worker("worker-1")["draining"], json!(true));
+
+        let err = run_json(&coordinator, &["worker", "drain", "missing"])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Rpc(ref s) if s.code() == tonic::Code::NotFound));
+
+        let ack = run_json(
+            &coordinator,
+            &[
+                "datasets",
+                "register",
+                "train",
+                "/data/train",
+                "--samples",
+                "1000",
+                "--shard-size",
+                "100",
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(ack["shards"], json!(10));
+        let datasets = run_json(&coordinator, &["datasets", "list"]).await.unwrap();
+        assert_eq!(datasets[0]["dataset_id"], json!("train"));
+        assert_eq!(datasets[0]["format"], json!("auto"));
+
+        for (worker_id, step) in [("worker-1", 100), ("worker-2", 200)] {
+            client
+                .notify_checkpoint(CheckpointInfo {
+                    worker_id: worker_id.to_string(),
+                    checkpoint_id: format!("ckpt-{step}"),
+                    step,
+                    storage_path: format!("/ckpt/{step}"),
+                    size_bytes: 1024,
+                    ..Default::default()
+                })
+                .await
+                .unwrap();
+        }
+        let checkpoints = run_json(&coordinator, &["ckpt", "list"]).await.unwrap();
+        assert_eq!(checkpoints[0]["checkpoint_id"], json!("ckpt-200"));
+        let checkpoints = run_json(&coordinator, &["ckpt", "list", "--worker", "worker-1"])
+            .await
+            .unwrap();
+        assert_eq!(checkpoints.as_array().unwrap().len(), 1);
+        let checkpoint = run_json(&coordinator, &["ckpt", "inspect", "ckpt-100"])
+            .await
+            .unwrap();
+        assert_eq!(checkpoint["storage_path"], json!("/ckpt/100"));
+        let err = run_json(&coordinator, &["ckpt", "inspect", "missing"])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+
+        let err = run_json(&coordinator, &["barrier", "release", "sync"])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Rpc(_)));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_coordinator() {
+        let cli =
+            Cli::try_parse_from(["strata", "--coordinator", "127.0.0.1:1", "workers", "list"])
+                .unwrap();
+        let err = cli.run(&mut Vec::new()).await.unwrap_err();
+        assert!(matches!(err, Error::Connect { .. }));
+    }
+}
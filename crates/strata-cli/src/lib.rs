@@ -0,0 +1,29 @@
+//! Command-line administration for Strata
+//!
+//! The `strata` binary talks to the coordinator's gRPC API, for operators
+//! who script against the cluster or do not use the dashboard:
+//! - `strata workers list` and `strata worker drain <id>`
+//! - `strata datasets list` and `strata datasets register <id> <path>`
+//! - `strata ckpt list` and `strata ckpt inspect <id>`
+//! - `strata barrier list` and `strata barrier release <id>`
+//!
+//! Results print as aligned columns, or as JSON with `--output json`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use clap::Parser;
+//! use strata_cli::Cli;
+//!
+//! # async fn example() -> strata_cli::Result<()> {
+//! let cli = Cli::parse_from(["strata", "--output", "json", "workers", "list"]);
+//! cli.run(&mut std::io::stdout()).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod cli;
+pub mod output;
+
+pub use cli::{Cli, Error, Result};
+pub use output::{Format, Output};
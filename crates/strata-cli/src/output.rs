@@ -0,0 +1,182 @@
+//! Printing command results as a table or JSON
+
+use std::io::{self, Write};
+
+use clap::ValueEnum;
+use serde_json::{Map, Value};
+
+/// Named fields of one item, in display order
+pub type Record = Vec<(&'static str, Value)>;
+
+/// How results are printed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// Aligned columns
+    Table,
+    /// Pretty-printed JSON, for scripts
+    Json,
+}
+
+/// The result of a command
+#[derive(Clone, Debug, PartialEq)]
+pub enum Output {
+    /// One row per item; tables show `columns`, JSON every field
+    List {
+        columns: &'static [&'static str],
+        rows: Vec<Record>,
+    },
+    /// A single item, one field per line in a table
+    Item(Record),
+}
+
+impl Output {
+    /// Print in the given format
+    pub fn write(&self, format: Format, out: &mut impl Write) -> io::Result<()> {
+        match format {
+            Format::Json => {
+                let value = match self {
+                    Output::List { rows, .. } => rows.iter().map(to_json).collect(),
+                    Output::Item(record) => to_json(record),
+                };
+                serde_json::to_writer_pretty(&mut *out, &value)?;
+                writeln!(out)
+            }
+            Format::Table => match self {
+                Output::List { columns, rows } => {
+                    let header = columns.iter().map(|c| c.to_uppercase()).collect();
+                    let cells = rows
+                        .iter()
+                        .map(|row| {
+                            columns
+                                .iter()
+                                .map(|column| {
+                                    let value = row.iter().find(|(name, _)| name == column);
+                                    cell(value.map(|(_, value)| value))
+                                })
+                                .collect()
+                        })
+                        .collect::<Vec<_>>();
+                    write_table(out, header, &cells)
+                }
+                Output::Item(record) => {
+                    let width = record.iter().map(|(name, _)| name.len()).max();
+                    for (name, value) in record {
+                        let label = format!("{name}:");
+                        writeln!(
+                            out,
+                            "{label:<width$}  {}",
+                            cell(Some(value)),
+                            width = width.unwrap_or(0) + 1
+                        )?;
+                    }
+                    Ok(())
+                }
+            },
+        }
+    }
+}
+
+fn to_json(record: &Record) -> Value {
+    let fields: Map<String, Value> = record
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+    Value::Object(fields)
+}
+
+/// Text of a value in a table, `-` when missing
+fn cell(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => "-".to_string(),
+        Some(Value::String(s)) if s.is_empty() => "-".to_string(),
+        Some(Value::String(s)) => s.clone(),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| cell(Some(item)))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(Value::Object(fields)) if fields.is_empty() => "-".to_string(),
+        Some(Value::Object(fields)) => fields
+            .iter()
+            .map(|(key, value)| format!("{key}={}", cell(Some(value))))
+            .collect::<Vec<_>>()
+            .join(", "),
+        Some(value) => value.to_string(),
+    }
+}
+
+fn write_table(out: &mut impl Write, header: Vec<String>, rows: &[Vec<String>]) -> io::Result<()> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    for row in std::iter::once(&header).chain(rows) {
+        let mut line = String::new();
+        for (i, (cell, width)) in row.iter().zip(&widths).enumerate() {
+            if i > 0 {
+                line.push_str("  ");
+            }
+            line.push_str(cell);
+            line.extend(std::iter::repeat_n(' ', width - cell.chars().count()));
+        }
+        writeln!(out, "{}", line.trim_end())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn render(output: &Output, format: Format) -> String {
+        let mut out = Vec::new();
+        output.write(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_table() {
+        let output = Output::List {
+            columns: &["worker_id", "rank", "labels"],
+            rows: vec![
+                vec![
+                    ("worker_id", json!("worker-10")),
+                    ("rank", json!(0)),
+                    ("labels", json!({"zone": "a"})),
+                    ("hidden", json!(true)),
+                ],
+                vec![("worker_id", json!("w2")), ("rank", json!(1))],
+            ],
+        };
+        assert_eq!(
+            render(&output, Format::Table),
+            "WORKER_ID  RANK  LABELS\n\
+             worker-10  0     zone=a\n\
+             w2         1     -\n"
+        );
+
+        let output = Output::Item(vec![("id", json!("ckpt-1")), ("size_bytes", json!(10))]);
+        assert_eq!(
+            render(&output, Format::Table),
+            "id:          ckpt-1\nsize_bytes:  10\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        let output = Output::List {
+            columns: &["worker_id"],
+            rows: vec![vec![("worker_id", json!("w1")), ("draining", json!(true))]],
+        };
+        let value: Value = serde_json::from_str(&render(&output, Format::Json)).unwrap();
+        assert_eq!(value, json!([{"worker_id": "w1", "draining": true}]));
+
+        let output = Output::Item(vec![("already_draining", json!(false))]);
+        let value: Value = serde_json::from_str(&render(&output, Format::Json)).unwrap();
+        assert_eq!(value, json!({"already_draining": false}));
+    }
+}
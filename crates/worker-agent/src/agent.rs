@@ -0,0 +1,640 @@
+//! Worker agent
+//!
+//! A [`WorkerAgent`] runs next to the training process on each host. It
+//! registers the host with the coordinator, sends a heartbeat with the host's
+//! resource usage every interval, keeps the files of the shards assigned to
+//! the host prefetched and carries out the commands that come back:
+//!
+//! - `checkpoint_now` runs `worker.checkpoint_command` with the step in
+//!   `STRATA_CHECKPOINT_STEP`; the worker reports as checkpointing meanwhile
+//! - `drain` stops prefetching, waits up to [`CHECKPOINT_DRAIN_TIMEOUT`] for
+//!   a running checkpoint command, deregisters and ends [`WorkerAgent::run`]
+//! - task and checkpoint deletion commands are for the training process and
+//!   are only logged
+//!
+//! Heartbeats go over a `StreamHeartbeats` stream, and each waits for its
+//! response before the next is sent. A stream that fails is dropped; the
+//! next heartbeat opens a new one, retrying and failing over between
+//! coordinators like every other call of the resilient client.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use tokio::process::Command;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tonic::{Code, Status, Streaming};
+use tracing::{debug, info, warn};
+
+use coordinator::client::{ResilientClientConfig, ResilientCoordinatorClient};
+use coordinator::commands::WorkerCommand;
+use coordinator::proto::{
+    self, worker_status::State, HeartbeatRequest, HeartbeatResponse, ShardAssignment, ShardRequest,
+    WorkerInfo, WorkerStatus,
+};
+use runtime_core::config::{RuntimeConfig, StorageConfig, WorkerConfig};
+use runtime_core::{Error, ResourceCollector, Result};
+
+use crate::prefetch::{PrefetchStats, Prefetcher};
+
+/// How often shard assignments are refreshed while membership is unchanged,
+/// so the prefetcher follows the dataset into the next epoch
+pub const ASSIGNMENT_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a heartbeat waits for its response before the stream is dropped
+pub const HEARTBEAT_RESPONSE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How long draining waits for a running checkpoint command before killing it
+pub const CHECKPOINT_DRAIN_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Environment variable carrying the step to the checkpoint command
+pub const CHECKPOINT_STEP_ENV: &str = "STRATA_CHECKPOINT_STEP";
+
+/// Environment variable carrying the worker ID to the checkpoint command
+pub const WORKER_ID_ENV: &str = "STRATA_WORKER_ID";
+
+/// Why [`WorkerAgent::run`] returned
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AgentExit {
+    /// The coordinator drained the worker
+    Drained,
+    /// The shutdown signal fired
+    Shutdown,
+}
+
+/// Registers a host with the coordinator and keeps it in the cluster
+pub struct WorkerAgent {
+    config: WorkerConfig,
+    storage: StorageConfig,
+    client: ResilientCoordinatorClient,
+    info: WorkerInfo,
+    registration: Option<proto::WorkerConfig>,
+    /// Open heartbeat stream, if any
+    heartbeats: Option<HeartbeatStream>,
+    collector: ResourceCollector,
+    prefetcher: Option<Prefetcher>,
+    /// Checkpoint command started for the last `checkpoint_now`
+    checkpoint: Option<JoinHandle<()>>,
+    /// How long draining waits for the checkpoint command
+    checkpoint_drain_timeout: Duration,
+    /// Membership generation and time of the last assignment refresh
+    refreshed: Option<(i64, Instant)>,
+    /// Shards last assigned to this worker, by dataset
+    assignments: HashMap<String, Vec<ShardAssignment>>,
+}
+
+impl WorkerAgent {
+    /// Create an agent from the `worker` and `storage` sections
+    ///
+    /// `worker.coordinator_address` may list several coordinators separated
+    /// by commas; the agent fails over between them. The worker ID defaults
+    /// to the host name.
+    pub fn new(config: &RuntimeConfig) -> Result<Self> {
+        let endpoints: Vec<String> = config
+            .worker
+            .coordinator_address
+            .split(',')
+            .map(str::trim)
+            .filter(|address| !address.is_empty())
+            .map(|address| {
+                if address.contains("://") {
+                    address.to_string()
+                } else {
+                    format!("http://{}", address)
+                }
+            })
+            .collect();
+        let client = ResilientCoordinatorClient::new(
+            endpoints,
+            ResilientClientConfig {
+                connect_timeout: config.network.connect_timeout,
+                ..Default::default()
+            },
+        )
+        .map_err(status_error)?;
+
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "localhost".to_string());
+        let mut system = sysinfo::System::new();
+        system.refresh_memory();
+        let mut collector = ResourceCollector::new();
+        let info = WorkerInfo {
+            worker_id: config
+                .worker
+                .worker_id
+                .clone()
+                .unwrap_or_else(|| hostname.clone()),
+            hostname,
+            gpu_count: collector.collect().gpu_metrics.len() as i32,
+            memory_bytes: system.total_memory() as i64,
+            metadata: HashMap::from([(
+                "agent".to_string(),
+                format!("worker-agent/{}", env!("CARGO_PKG_VERSION")),
+            )]),
+            ..Default::default()
+        };
+
+        Ok(Self {
+            config: config.worker.clone(),
+            storage: config.storage.clone(),
+            client,
+            info,
+            registration: None,
+            heartbeats: None,
+            collector,
+            prefetcher: None,
+            checkpoint: None,
+            checkpoint_drain_timeout: CHECKPOINT_DRAIN_TIMEOUT,
+            refreshed: None,
+            assignments: HashMap::new(),
+        })
+    }
+
+    /// ID the worker registers under
+    pub fn worker_id(&self) -> &str {
+        &self.info.worker_id
+    }
+
+    /// Configuration from the current registration
+    pub fn registration(&self) -> Option<&proto::WorkerConfig> {
+        self.registration.as_ref()
+    }
+
+    /// Prefetch counters, if any datasets are prefetched
+    pub fn prefetch_stats(&self) -> Option<PrefetchStats> {
+        self.prefetcher.as_ref().map(Prefetcher::stats)
+    }
+
+    /// Time between heartbeats, as set by the coordinator or the config
+    pub fn heartbeat_interval(&self) -> Duration {
+        match &self.registration {
+            Some(registration) if registration.heartbeat_interval_ms > 0 => {
+                Duration::from_millis(registration.heartbeat_interval_ms as u64)
+            }
+            _ => self.config.heartbeat_interval,
+        }
+    }
+
+    /// Register with the coordinator and start prefetching
+    pub async fn register(&mut self) -> Result<&proto::WorkerConfig> {
+        let registration = self
+            .client
+            .register_worker(self.info.clone())
+            .await
+            .map_err(status_error)?;
+        info!(
+            worker_id = %registration.assigned_id,
+            rank = registration.rank,
+            world_size = registration.world_size,
+            "Registered with coordinator"
+        );
+        self.info.worker_id = registration.assigned_id.clone();
+        self.refreshed = None;
+        if self.prefetcher.is_none() && !self.config.datasets.is_empty() {
+            self.prefetcher = Some(Prefetcher::spawn(
+                &self.config.cache_dir,
+                self.config.prefetch_buffer_size,
+                self.storage.clone(),
+            ));
+        }
+        Ok(self.registration.insert(registration))
+    }
+
+    /// Send one heartbeat and act on the response
+    ///
+    /// Registers again when the coordinator no longer knows this session,
+    /// e.g. after removing the worker as dead. Returns
+    /// [`AgentExit::Drained`] once the worker has been drained.
+    pub async fn heartbeat(&mut self) -> Result<Option<AgentExit>> {
+        let session = match &self.registration {
+            Some(registration) => registration.session,
+            None => self.register().await?.session,
+        };
+        let request = HeartbeatRequest {
+            worker_id: self.info.worker_id.clone(),
+            timestamp_ms: chrono::Utc::now().timestamp_millis(),
+            status: Some(WorkerStatus {
+                state: self.state() as i32,
+                ..Default::default()
+            }),
+            resources: Some(self.collector.collect().into()),
+            session,
+            metrics: Vec::new(),
+        };
+
+        let response = match self.send_heartbeat(request).await {
+            Ok(response) => response,
+            Err(status) if matches!(status.code(), Code::NotFound | Code::FailedPrecondition) => {
+                warn!(error = %status.message(), "Coordinator dropped the worker, registering again");
+                self.registration = None;
+                self.register().await?;
+                return Ok(None);
+            }
+            Err(status) => return Err(status_error(status)),
+        };
+
+        for command in &response.pending_commands {
+            match command.parse::<WorkerCommand>() {
+                Ok(WorkerCommand::Drain) => {
+                    self.drain().await;
+                    return Ok(Some(AgentExit::Drained));
+                }
+                Ok(command) => self.apply(command),
+                Err(e) => warn!(command = %command, error = %e, "Ignoring command"),
+            }
+        }
+        self.refresh_assignments(response.membership_generation)
+            .await;
+        Ok(None)
+    }
+
+    /// Send a heartbeat on the heartbeat stream and wait for its response
+    ///
+    /// The stream is opened on first use and dropped after any failure.
+    async fn send_heartbeat(
+        &mut self,
+        request: HeartbeatRequest,
+    ) -> std::result::Result<HeartbeatResponse, Status> {
+        let mut stream = match self.heartbeats.take() {
+            Some(stream) => stream,
+            None => {
+                let (requests, responses) = self.client.stream_heartbeats().await?;
+                debug!("Opened heartbeat stream");
+                HeartbeatStream {
+                    requests,
+                    responses,
+                }
+            }
+        };
+        let response = stream.exchange(request).await?;
+        self.heartbeats = Some(stream);
+        Ok(response)
+    }
+
+    /// Register, then heartbeat until drained or `shutdown` completes
+    ///
+    /// Heartbeats that fail after the client's retries are logged and the
+    /// next one is sent on schedule. The worker deregisters on shutdown.
+    pub async fn run(mut self, shutdown: impl Future<Output = ()>) -> Result<AgentExit> {
+        tokio::pin!(shutdown);
+        self.register().await?;
+
+        loop {
+            match self.heartbeat().await {
+                Ok(Some(exit)) => return Ok(exit),
+                Ok(None) => {}
+                Err(e) => warn!(error = %e, "Heartbeat failed"),
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(self.heartbeat_interval()) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+
+        info!(worker_id = %self.info.worker_id, "Shutting down");
+        if let Some(prefetcher) = self.prefetcher.take() {
+            prefetcher.stop().await;
+        }
+        self.deregister().await;
+        Ok(AgentExit::Shutdown)
+    }
+
+    /// Leave the cluster
+    pub async fn deregister(&mut self) {
+        self.heartbeats = None;
+        if self.registration.take().is_none() {
+            return;
+        }
+        match self.client.deregister_worker(self.info.clone()).await {
+            Ok(_) => info!(worker_id = %self.info.worker_id, "Deregistered from coordinator"),
+            Err(e) => warn!(error = %e.message(), "Failed to deregister"),
+        }
+    }
+
+    /// State reported in heartbeats
+    fn state(&self) -> State {
+        match &self.checkpoint {
+            Some(checkpoint) if !checkpoint.is_finished() => State::Checkpointing,
+            _ => State::Idle,
+        }
+    }
+
+    fn apply(&mut self, command: WorkerCommand) {
+        match command {
+            WorkerCommand::CheckpointNow { step } => self.start_checkpoint(step),
+            command => debug!(command = %command, "Leaving command to the training process"),
+        }
+    }
+
+    /// Run the checkpoint command in the background
+    fn start_checkpoint(&mut self, step: u64) {
+        let Some((program, args)) = self.config.checkpoint_command.split_first() else {
+            warn!(
+                step,
+                "Got checkpoint_now but worker.checkpoint_command is not set"
+            );
+            return;
+        };
+        if self.state() == State::Checkpointing {
+            warn!(
+                step,
+                "Checkpoint command still running, skipping checkpoint_now"
+            );
+            return;
+        }
+
+        let mut command = Command::new(program);
+        command
+            .args(args)
+            .env(CHECKPOINT_STEP_ENV, step.to_string())
+            .env(WORKER_ID_ENV, &self.info.worker_id)
+            .kill_on_drop(true);
+        info!(step, "Running checkpoint command");
+        self.checkpoint = Some(tokio::spawn(async move {
+            match command.status().await {
+                Ok(status) if status.success() => info!(step, "Checkpoint command finished"),
+                Ok(status) => warn!(step, status = %status, "Checkpoint command failed"),
+                Err(e) => warn!(step, error = %e, "Failed to start checkpoint command"),
+            }
+        }));
+    }
+
+    /// Stop prefetching, let a running checkpoint command finish and deregister
+    async fn drain(&mut self) {
+        info!(worker_id = %self.info.worker_id, "Draining");
+        if let Some(prefetcher) = self.prefetcher.take() {
+            prefetcher.stop().await;
+        }
+        if let Some(mut checkpoint) = self.checkpoint.take() {
+            let timeout = self.checkpoint_drain_timeout;
+            if tokio::time::timeout(timeout, &mut checkpoint)
+                .await
+                .is_err()
+            {
+                warn!(
+                    timeout_secs = timeout.as_secs(),
+                    "Checkpoint command still running, killing it"
+                );
+                checkpoint.abort();
+            }
+        }
+        self.deregister().await;
+    }
+
+    /// Hand the shards assigned to this worker to the prefetcher
+    ///
+    /// Assignments for each dataset's current epoch are fetched when
+    /// membership changed or the last refresh is older than
+    /// [`ASSIGNMENT_REFRESH_INTERVAL`]. Datasets the coordinator does not know
+    /// yet are skipped until a later refresh. A dataset whose assignments
+    /// could not be fetched keeps its previous ones, so a transient error
+    /// does not evict its cached files, and is tried again next heartbeat.
+    async fn refresh_assignments(&mut self, generation: i64) {
+        let Some(prefetcher) = &self.prefetcher else {
+            return;
+        };
+        if self.refreshed.is_some_and(|(refreshed, at)| {
+            refreshed == generation && at.elapsed() < ASSIGNMENT_REFRESH_INTERVAL
+        }) {
+            return;
+        }
+
+        let mut failed = false;
+        for dataset_id in &self.config.datasets {
+            let request = ShardRequest {
+                worker_id: self.info.worker_id.clone(),
+                dataset_id: dataset_id.clone(),
+                use_current_epoch: true,
+                ..Default::default()
+            };
+            match self.client.get_data_shards(request).await {
+                Ok(list) => {
+                    self.assignments
+                        .insert(dataset_id.clone(), list.assignments);
+                }
+                Err(e) if e.code() == Code::NotFound => {
+                    debug!(dataset_id = %dataset_id, "Dataset not registered yet");
+                    self.assignments.remove(dataset_id);
+                }
+                Err(e) => {
+                    warn!(dataset_id = %dataset_id, error = %e.message(), "Failed to get shards");
+                    failed = true;
+                }
+            }
+        }
+
+        let assignments: Vec<ShardAssignment> = self
+            .config
+            .datasets
+            .iter()
+            .filter_map(|dataset_id| self.assignments.get(dataset_id))
+            .flatten()
+            .cloned()
+            .collect();
+        let shards = assignments.len();
+        if prefetcher.set_assignments(assignments) {
+            info!(shards, "Shard assignments changed");
+        }
+        if !failed {
+            self.refreshed = Some((generation, Instant::now()));
+        }
+    }
+}
+
+/// An open `StreamHeartbeats` stream
+struct HeartbeatStream {
+    requests: mpsc::Sender<HeartbeatRequest>,
+    responses: Streaming<HeartbeatResponse>,
+}
+
+impl HeartbeatStream {
+    /// Send a heartbeat and wait for its response
+    async fn exchange(
+        &mut self,
+        request: HeartbeatRequest,
+    ) -> std::result::Result<HeartbeatResponse, Status> {
+        self.requests
+            .send(request)
+            .await
+            .map_err(|_| Status::unavailable("Heartbeat stream closed"))?;
+        match tokio::time::timeout(HEARTBEAT_RESPONSE_TIMEOUT, self.responses.message()).await {
+            Ok(Ok(Some(response))) => Ok(response),
+            Ok(Ok(None)) => Err(Status::unavailable("Heartbeat stream ended")),
+            Ok(Err(status)) => Err(status),
+            Err(_) => Err(Status::deadline_exceeded(
+                "No response on the heartbeat stream",
+            )),
+        }
+    }
+}
+
+fn status_error(status: Status) -> Error {
+    Error::Grpc(format!("{}: {}", status.code(), status.message()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use coordinator::proto::DatasetInfo;
+    use coordinator::{CoordinatorServer, CoordinatorService};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    /// Coordinator serving on a free loopback port
+    struct TestCoordinator {
+        service: CoordinatorService,
+        address: String,
+        stop: oneshot::Sender<()>,
+        _dir: tempfile::TempDir,
+    }
+
+    async fn start_coordinator() -> TestCoordinator {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = RuntimeConfig::default();
+        config.storage.base_path = dir.path().to_string_lossy().into_owned();
+        // Handed to workers on registration
+        config.worker.heartbeat_interval = Duration::from_millis(50);
+        let service = CoordinatorService::from_runtime_config(&config)
+            .await
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (stop, stopped) = oneshot::channel::<()>();
+        tokio::spawn(
+            CoordinatorServer::new(service.clone()).run_on_listener(listener, async {
+                let _ = stopped.await;
+            }),
+        );
+
+        TestCoordinator {
+            service,
+            address,
+            stop,
+            _dir: dir,
+        }
+    }
+
+    fn agent_config(coordinator: &TestCoordinator, worker_id: &str) -> RuntimeConfig {
+        let mut config = RuntimeConfig::default();
+        config.worker.coordinator_address = coordinator.address.clone();
+        config.worker.worker_id = Some(worker_id.to_string());
+        config
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_and_drain() {
+        let coordinator = start_coordinator().await;
+        let worker_id: runtime_core::WorkerId = "agent-1".parse().unwrap();
+        let mut agent = WorkerAgent::new(&agent_config(&coordinator, "agent-1")).unwrap();
+
+        assert_eq!(agent.heartbeat().await.unwrap(), None);
+        assert_eq!(agent.registration().unwrap().assigned_id, "agent-1");
+        assert!(agent.prefetch_stats().is_none());
+        assert_eq!(coordinator.service.get_workers_for_api().len(), 1);
+
+        // A worker removed as dead registers again on its next heartbeat
+        coordinator.service.remove_worker(&worker_id).unwrap();
+        assert_eq!(agent.heartbeat().await.unwrap(), None);
+        assert_eq!(coordinator.service.get_workers_for_api().len(), 1);
+
+        coordinator.service.drain_worker(&worker_id).unwrap();
+        assert_eq!(agent.heartbeat().await.unwrap(), Some(AgentExit::Drained));
+        assert!(agent.registration().is_none());
+        assert!(coordinator.service.get_workers_for_api().is_empty());
+        let _ = coordinator.stop.send(());
+    }
+
+    #[tokio::test]
+    async fn test_drain_kills_hung_checkpoint() {
+        let coordinator = start_coordinator().await;
+        let mut config = agent_config(&coordinator, "agent-1");
+        config.worker.checkpoint_command = vec!["sleep".to_string(), "60".to_string()];
+        let mut agent = WorkerAgent::new(&config).unwrap();
+        agent.checkpoint_drain_timeout = Duration::from_millis(50);
+        agent.register().await.unwrap();
+
+        agent.start_checkpoint(1);
+        assert_eq!(agent.state(), State::Checkpointing);
+        tokio::time::timeout(Duration::from_secs(10), agent.drain())
+            .await
+            .expect("drain waited for the checkpoint command");
+        assert!(agent.registration().is_none());
+        assert!(coordinator.service.get_workers_for_api().is_empty());
+        let _ = coordinator.stop.send(());
+    }
+
+    #[tokio::test]
+    async fn test_run_prefetches_and_checkpoints() {
+        let coordinator = start_coordinator().await;
+        let data = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        let marker = data.path().join("checkpointed");
+        for name in ["a.jsonl", "b.jsonl"] {
+            std::fs::write(data.path().join(name), "{}\n".repeat(50)).unwrap();
+        }
+
+        let mut client = ResilientCoordinatorClient::new(
+            [format!("http://{}", coordinator.address)],
+            ResilientClientConfig::default(),
+        )
+        .unwrap();
+        client
+            .register_dataset(DatasetInfo {
+                dataset_id: "train".to_string(),
+                path: data.path().to_string_lossy().into_owned(),
+                format: "jsonl".to_string(),
+                total_samples: 100,
+                shard_size: 50,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let mut config = agent_config(&coordinator, "agent-1");
+        config.worker.datasets = vec!["train".to_string(), "unknown".to_string()];
+        config.worker.cache_dir = cache.path().to_string_lossy().into_owned();
+        config.worker.checkpoint_command = vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            format!(
+                "echo ${} ${} > {}",
+                WORKER_ID_ENV,
+                CHECKPOINT_STEP_ENV,
+                marker.display()
+            ),
+        ];
+        let agent = WorkerAgent::new(&config).unwrap();
+        let (shutdown, stopped) = oneshot::channel::<()>();
+        let run = tokio::spawn(agent.run(async {
+            let _ = stopped.await;
+        }));
+
+        // The only worker is assigned both shards
+        let cached = cache.path().join("train");
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !(cached.join("a.jsonl").exists() && cached.join("b.jsonl").exists()) {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("shards were not prefetched");
+
+        coordinator.service.trigger_checkpoint(&[]).unwrap();
+        tokio::time::timeout(Duration::from_secs(10), async {
+            while !marker.exists() {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        })
+        .await
+        .expect("checkpoint command did not run");
+        assert!(std::fs::read_to_string(&marker)
+            .unwrap()
+            .starts_with("agent-1 "));
+
+        shutdown.send(()).unwrap();
+        assert_eq!(run.await.unwrap().unwrap(), AgentExit::Shutdown);
+        assert!(coordinator.service.get_workers_for_api().is_empty());
+        let _ = coordinator.stop.send(());
+    }
+}
@@ -0,0 +1,52 @@
+//! Worker agent binary entry point
+//!
+//! Registers this host with the coordinator and keeps it in the cluster
+//! until drained or interrupted.
+
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+use runtime_core::config::RuntimeConfig;
+use runtime_core::shutdown::wait_for_signal;
+use worker_agent::WorkerAgent;
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "worker_agent=info,coordinator=info".into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+
+    // Config file from STRATA_CONFIG, with STRATA_<SECTION>__<FIELD> overrides
+    let config = match std::env::var("STRATA_CONFIG") {
+        Ok(path) => {
+            tracing::info!("Loading configuration from {}", path);
+            RuntimeConfig::from_file(&path)?
+        }
+        Err(_) => RuntimeConfig::from_env()?,
+    };
+
+    // Report every invalid setting at once instead of failing on the first
+    if let Err(violations) = config.validate() {
+        for violation in &violations {
+            tracing::error!("Invalid configuration: {}", violation);
+        }
+        return Err(runtime_core::Error::from(violations).into());
+    }
+
+    let agent = WorkerAgent::new(&config)?;
+    tracing::info!(
+        worker_id = %agent.worker_id(),
+        coordinator = %config.worker.coordinator_address,
+        "Starting worker agent"
+    );
+    let exit = agent
+        .run(async {
+            wait_for_signal().await;
+        })
+        .await?;
+    tracing::info!(?exit, "Worker agent stopped");
+    Ok(())
+}
@@ -0,0 +1,34 @@
+//! Worker agent for distributed ML training
+//!
+//! The daemon that runs on every training host next to the training
+//! process, so each job does not have to reimplement it:
+//! - **Registration** with the coordinator, and again if it drops the worker
+//! - **Heartbeats** carrying the host's CPU, memory, disk, network and GPU usage
+//! - **Commands** from the coordinator: `checkpoint_now` runs a configured
+//!   command and `drain` deregisters the worker
+//! - **Prefetching** of the files behind the worker's shards into a local cache
+//!
+//! It is configured by the `worker` section of [`RuntimeConfig`](runtime_core::config::RuntimeConfig).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use runtime_core::config::RuntimeConfig;
+//! use worker_agent::WorkerAgent;
+//!
+//! # async fn example() -> runtime_core::Result<()> {
+//! let config = RuntimeConfig::from_file("worker.toml")?;
+//! let agent = WorkerAgent::new(&config)?;
+//! agent.run(async {
+//!     let _ = tokio::signal::ctrl_c().await;
+//! })
+//! .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod agent;
+pub mod prefetch;
+
+pub use agent::{AgentExit, WorkerAgent};
+pub use prefetch::{PrefetchStats, Prefetcher};
@@ -0,0 +1,599 @@
+//! Shard prefetching
+//!
+//! A [`Prefetcher`] copies the files behind a worker's shard assignments into
+//! a local cache in the order the shards will be read, keeping at most
+//! `capacity` files, so training reads from local disk. When the assignments
+//! change, files no longer needed are evicted and the new ones fetched.
+//! Files land in `<cache_dir>/<dataset_id>/<key>`, where the key is the
+//! file's path within the dataset. Each dataset directory holds a
+//! [`CACHE_MARKER`] file; files an earlier run left in marked directories
+//! count against the capacity and are evicted like any other once
+//! unassigned, while anything else in `<cache_dir>` is left alone.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use parking_lot::Mutex;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use coordinator::proto::ShardAssignment;
+use data_shard::{split_pattern, DatasetManifest};
+use runtime_core::config::StorageConfig;
+use runtime_core::Result;
+use storage::{LocalStorage, StorageBackend};
+
+/// File marking a dataset directory as created by the prefetcher
+pub const CACHE_MARKER: &str = ".strata-prefetch";
+
+/// Bytes read from storage per request while copying a file
+const FETCH_CHUNK_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Prefetch counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrefetchStats {
+    /// Files copied into the cache
+    pub files_fetched: u64,
+    /// Bytes copied into the cache
+    pub bytes_fetched: u64,
+    /// Files removed because their shards moved elsewhere
+    pub files_evicted: u64,
+    /// Datasets that could not be listed and files that could not be copied
+    pub errors: u64,
+    /// Files in the cache
+    pub cached_files: usize,
+}
+
+/// Background task keeping the files of assigned shards in a local cache
+pub struct Prefetcher {
+    assignments: watch::Sender<Vec<ShardAssignment>>,
+    stats: Arc<Mutex<PrefetchStats>>,
+    task: JoinHandle<()>,
+}
+
+impl Prefetcher {
+    /// Start prefetching into `cache_dir`, keeping at most `capacity` files
+    ///
+    /// `storage` supplies the region and endpoint of `s3://` datasets.
+    pub fn spawn(cache_dir: impl Into<PathBuf>, capacity: usize, storage: StorageConfig) -> Self {
+        let (assignments, updates) = watch::channel(Vec::new());
+        let stats = Arc::new(Mutex::new(PrefetchStats::default()));
+        let cache = Cache {
+            dir: cache_dir.into(),
+            capacity,
+            storage,
+            datasets: HashMap::new(),
+            cached: HashSet::new(),
+            stats: stats.clone(),
+        };
+        let task = tokio::spawn(cache.run(updates));
+
+        Self {
+            assignments,
+            stats,
+            task,
+        }
+    }
+
+    /// Replace the shards to prefetch, in the order they will be read
+    ///
+    /// Returns false if they did not change.
+    pub fn set_assignments(&self, assignments: Vec<ShardAssignment>) -> bool {
+        self.assignments.send_if_modified(|current| {
+            if *current == assignments {
+                return false;
+            }
+            *current = assignments;
+            true
+        })
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> PrefetchStats {
+        *self.stats.lock()
+    }
+
+    /// Stop once the file being copied is done, leaving the cache in place
+    pub async fn stop(self) {
+        drop(self.assignments);
+        let _ = self.task.await;
+    }
+}
+
+/// A dataset's files and the storage they are read from
+struct Dataset {
+    storage: Arc<dyn StorageBackend>,
+    manifest: DatasetManifest,
+}
+
+/// A file to have in the cache
+struct CacheEntry {
+    storage: Arc<dyn StorageBackend>,
+    key: String,
+    /// Cache directory of the file's dataset
+    dir: PathBuf,
+    dest: PathBuf,
+}
+
+struct Cache {
+    dir: PathBuf,
+    capacity: usize,
+    storage: StorageConfig,
+    /// Datasets by path, listed once
+    datasets: HashMap<String, Arc<Dataset>>,
+    /// Files copied into the cache
+    cached: HashSet<PathBuf>,
+    stats: Arc<Mutex<PrefetchStats>>,
+}
+
+impl Cache {
+    async fn run(mut self, mut updates: watch::Receiver<Vec<ShardAssignment>>) {
+        self.scan().await;
+        while updates.changed().await.is_ok() {
+            let assignments = updates.borrow_and_update().clone();
+            let (wanted, unresolved) = self.resolve(&assignments).await;
+            self.evict(&wanted, &unresolved).await;
+
+            for entry in wanted {
+                // Start over as soon as the assignments change
+                if updates.has_changed().unwrap_or(true) {
+                    break;
+                }
+                if self.cached.contains(&entry.dest) {
+                    continue;
+                }
+                let fetched = fetch(&entry, FETCH_CHUNK_BYTES).await;
+                let mut stats = self.stats.lock();
+                match fetched {
+                    Ok(bytes) => {
+                        debug!(path = %entry.dest.display(), "Prefetched shard file");
+                        self.cached.insert(entry.dest);
+                        stats.cached_files = self.cached.len();
+                        if let Some(bytes) = bytes {
+                            stats.files_fetched += 1;
+                            stats.bytes_fetched += bytes;
+                        }
+                    }
+                    Err(e) => {
+                        warn!(key = %entry.key, error = %e, "Failed to prefetch shard file");
+                        stats.errors += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Pick up the files an earlier run left in the cache
+    ///
+    /// Only dataset directories holding the [`CACHE_MARKER`] are searched.
+    /// Complete files are tracked so the first update evicts those no longer
+    /// assigned. Partial copies from an interrupted fetch are removed.
+    async fn scan(&mut self) {
+        let mut dirs = Vec::new();
+        for (path, is_dir) in list_dir(&self.dir).await {
+            if is_dir
+                && tokio::fs::try_exists(path.join(CACHE_MARKER))
+                    .await
+                    .unwrap_or(false)
+            {
+                dirs.push(path);
+            }
+        }
+
+        while let Some(dir) = dirs.pop() {
+            for (path, is_dir) in list_dir(&dir).await {
+                if is_dir {
+                    dirs.push(path);
+                } else if is_partial(&path) {
+                    let _ = tokio::fs::remove_file(&path).await;
+                } else if path.file_name() != Some(CACHE_MARKER.as_ref()) {
+                    self.cached.insert(path);
+                }
+            }
+        }
+
+        if !self.cached.is_empty() {
+            info!(
+                files = self.cached.len(),
+                "Found shard files from an earlier run"
+            );
+        }
+        self.stats.lock().cached_files = self.cached.len();
+    }
+
+    /// The files behind `assignments`, in order, up to the capacity
+    ///
+    /// Also returns the cache directories of datasets that could not be
+    /// listed, whose files are kept until they can be.
+    async fn resolve(
+        &mut self,
+        assignments: &[ShardAssignment],
+    ) -> (Vec<CacheEntry>, HashSet<PathBuf>) {
+        let mut wanted = Vec::new();
+        let mut unresolved = HashSet::new();
+        let mut seen = HashSet::new();
+        for assignment in assignments {
+            for path in &assignment.file_paths {
+                let Some(dataset) = self.dataset(path).await else {
+                    unresolved.insert(self.dir.join(&assignment.dataset_id));
+                    continue;
+                };
+                let files = dataset
+                    .manifest
+                    .files_in(assignment.start_index as u64, assignment.end_index as u64);
+                let dir = self.dir.join(&assignment.dataset_id);
+                for (file, _) in files {
+                    let dest = dir.join(&file.key);
+                    if !seen.insert(dest.clone()) {
+                        continue;
+                    }
+                    if wanted.len() == self.capacity {
+                        return (wanted, unresolved);
+                    }
+                    wanted.push(CacheEntry {
+                        storage: dataset.storage.clone(),
+                        key: file.key.clone(),
+                        dir: dir.clone(),
+                        dest,
+                    });
+                }
+            }
+        }
+        (wanted, unresolved)
+    }
+
+    /// The dataset at `path`, listing it on first use
+    ///
+    /// Datasets that fail to list are tried again on the next update.
+    async fn dataset(&mut self, path: &str) -> Option<Arc<Dataset>> {
+        if let Some(dataset) = self.datasets.get(path) {
+            return Some(dataset.clone());
+        }
+        match open_dataset(path, &self.storage).await {
+            Ok(dataset) => {
+                info!(
+                    path = %path,
+                    files = dataset.manifest.files().len(),
+                    "Listed dataset for prefetching"
+                );
+                let dataset = Arc::new(dataset);
+                self.datasets.insert(path.to_string(), dataset.clone());
+                Some(dataset)
+            }
+            Err(e) => {
+                warn!(path = %path, error = %e, "Failed to list dataset for prefetching");
+                self.stats.lock().errors += 1;
+                None
+            }
+        }
+    }
+
+    /// Remove cached files that are no longer wanted
+    ///
+    /// Files under `unresolved` dataset directories are kept.
+    async fn evict(&mut self, wanted: &[CacheEntry], unresolved: &HashSet<PathBuf>) {
+        let keep: HashSet<&PathBuf> = wanted.iter().map(|entry| &entry.dest).collect();
+        let stale: Vec<PathBuf> = self
+            .cached
+            .iter()
+            .filter(|path| !keep.contains(path))
+            .filter(|path| !unresolved.iter().any(|dir| path.starts_with(dir)))
+            .cloned()
+            .collect();
+
+        for path in stale {
+            if let Err(e) = tokio::fs::remove_file(&path).await {
+                if e.kind() != std::io::ErrorKind::NotFound {
+                    warn!(path = %path.display(), error = %e, "Failed to evict shard file");
+                }
+            }
+            self.cached.remove(&path);
+            self.stats.lock().files_evicted += 1;
+        }
+        self.stats.lock().cached_files = self.cached.len();
+    }
+}
+
+/// Copy a file into the cache, returning its size or `None` if it was there
+///
+/// The file is read `chunk` bytes at a time, so large shards are never held
+/// in memory whole. It is written under a temporary name and renamed, so one
+/// left by an earlier run is complete and kept.
+async fn fetch(entry: &CacheEntry, chunk: u64) -> Result<Option<u64>> {
+    if let Some(parent) = entry.dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let marker = entry.dir.join(CACHE_MARKER);
+    if !tokio::fs::try_exists(&marker).await? {
+        tokio::fs::write(&marker, "").await?;
+    }
+    if tokio::fs::try_exists(&entry.dest).await? {
+        return Ok(None);
+    }
+
+    let name = entry.dest.file_name().unwrap_or_default().to_string_lossy();
+    let temp = entry.dest.with_file_name(format!(".{}.partial", name));
+    match copy_chunks(entry, &temp, chunk).await {
+        Ok(bytes) => {
+            tokio::fs::rename(&temp, &entry.dest).await?;
+            Ok(Some(bytes))
+        }
+        Err(e) => {
+            let _ = tokio::fs::remove_file(&temp).await;
+            Err(e)
+        }
+    }
+}
+
+/// Entries of `dir` and whether each is a directory, or none if it cannot be read
+async fn list_dir(dir: &Path) -> Vec<(PathBuf, bool)> {
+    let mut entries = match tokio::fs::read_dir(dir).await {
+        Ok(entries) => entries,
+        Err(e) => {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                warn!(path = %dir.display(), error = %e, "Failed to scan shard cache");
+            }
+            return Vec::new();
+        }
+    };
+    let mut listed = Vec::new();
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if let Ok(file_type) = entry.file_type().await {
+            listed.push((entry.path(), file_type.is_dir()));
+        }
+    }
+    listed
+}
+
+/// Whether `path` is the temporary file of an unfinished copy
+fn is_partial(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.') && name.ends_with(".partial"))
+}
+
+/// Copy `entry` into `temp` in ranges of `chunk` bytes, returning its size
+async fn copy_chunks(entry: &CacheEntry, temp: &Path, chunk: u64) -> Result<u64> {
+    let mut file = tokio::fs::File::create(temp).await?;
+    let mut offset = 0;
+    loop {
+        let data = entry.storage.read_range(&entry.key, offset, chunk).await?;
+        file.write_all(&data).await?;
+        offset += data.len() as u64;
+        // A short range means the file ended
+        if (data.len() as u64) < chunk {
+            break;
+        }
+    }
+    file.flush().await?;
+    // On disk before the rename, so a crash cannot leave a truncated file
+    // under the final name
+    file.sync_all().await?;
+    Ok(offset)
+}
+
+/// List the dataset at `path`
+///
+/// `path` is a directory, a file or a pattern such as `/data/train/*.jsonl`,
+/// on local disk or under `s3://bucket/`.
+async fn open_dataset(path: &str, config: &StorageConfig) -> Result<Dataset> {
+    if let Some(location) = path.strip_prefix("s3://") {
+        return open_s3_dataset(location, config).await;
+    }
+
+    let path = path.strip_prefix("file://").unwrap_or(path);
+    let (base, pattern) = split_pattern(path);
+    let base = Path::new(if base.is_empty() { "." } else { base });
+    let (root, pattern) = match base.file_name() {
+        Some(name) if pattern.is_empty() && base.is_file() => (
+            base.parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .unwrap_or(Path::new(".")),
+            name.to_string_lossy().into_owned(),
+        ),
+        _ => (base, pattern.to_string()),
+    };
+
+    let storage = LocalStorage::new(root);
+    let manifest = DatasetManifest::build(&storage, &pattern).await?;
+    Ok(Dataset {
+        storage: Arc::new(storage),
+        manifest,
+    })
+}
+
+#[cfg(feature = "s3")]
+async fn open_s3_dataset(location: &str, config: &StorageConfig) -> Result<Dataset> {
+    use runtime_core::config::StorageBackend as Backend;
+    use storage::{S3Config, S3Storage};
+
+    let (bucket, key) = location.split_once('/').unwrap_or((location, ""));
+    let (prefix, pattern) = split_pattern(key);
+    let (endpoint_url, region) = match &config.backend {
+        Backend::S3 {
+            endpoint, region, ..
+        } => (endpoint.clone(), Some(region.clone())),
+        Backend::Local => (None, None),
+    };
+
+    let defaults = S3Config::default();
+    let storage = S3Storage::with_config(S3Config {
+        bucket: bucket.to_string(),
+        prefix: (!prefix.is_empty()).then(|| prefix.to_string()),
+        force_path_style: endpoint_url.is_some(),
+        endpoint_url,
+        region: region.or(defaults.region),
+        retry: config.retry.clone(),
+    })
+    .await;
+    let manifest = DatasetManifest::build(&storage, pattern).await?;
+    Ok(Dataset {
+        storage: Arc::new(storage),
+        manifest,
+    })
+}
+
+/// S3 datasets need the `s3` feature
+#[cfg(not(feature = "s3"))]
+async fn open_s3_dataset(_location: &str, _config: &StorageConfig) -> Result<Dataset> {
+    Err(runtime_core::Error::StorageUnavailable {
+        backend: "s3".to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn write_lines(dir: &Path, name: &str, lines: usize) {
+        std::fs::write(dir.join(name), "{}\n".repeat(lines)).unwrap();
+    }
+
+    fn assignment(dataset: &Path, shard_id: i64, start: i64, end: i64) -> ShardAssignment {
+        ShardAssignment {
+            dataset_id: "train".to_string(),
+            shard_id,
+            start_index: start,
+            end_index: end,
+            file_paths: vec![dataset.to_string_lossy().into_owned()],
+            ..Default::default()
+        }
+    }
+
+    async fn wait_for(prefetcher: &Prefetcher, done: impl Fn(&PrefetchStats) -> bool) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while !done(&prefetcher.stats()) {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("prefetcher stuck at {:?}", prefetcher.stats()));
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_and_evict() {
+        let data = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        for name in ["a.jsonl", "b.jsonl", "c.jsonl"] {
+            write_lines(data.path(), name, 10);
+        }
+
+        let prefetcher = Prefetcher::spawn(cache.path(), 2, StorageConfig::default());
+        // Samples 5..25 span all three files, but only two fit
+        assert!(prefetcher.set_assignments(vec![assignment(data.path(), 0, 5, 25)]));
+        assert!(!prefetcher.set_assignments(vec![assignment(data.path(), 0, 5, 25)]));
+        wait_for(&prefetcher, |s| s.files_fetched == 2).await;
+        let cached = cache.path().join("train");
+        assert!(cached.join("a.jsonl").exists());
+        assert!(cached.join("b.jsonl").exists());
+        assert!(!cached.join("c.jsonl").exists());
+        assert_eq!(prefetcher.stats().bytes_fetched, 60);
+
+        // The shard moved on to the last file
+        prefetcher.set_assignments(vec![assignment(data.path(), 1, 20, 30)]);
+        wait_for(&prefetcher, |s| s.files_fetched == 3).await;
+        let stats = prefetcher.stats();
+        assert_eq!((stats.files_evicted, stats.cached_files), (2, 1));
+        assert!(!cached.join("a.jsonl").exists());
+        assert!(cached.join("c.jsonl").exists());
+
+        // Unknown datasets are counted as errors, and their files kept
+        prefetcher.set_assignments(vec![assignment(&data.path().join("missing"), 2, 0, 10)]);
+        wait_for(&prefetcher, |s| s.errors == 1).await;
+        prefetcher.stop().await;
+        assert!(cached.join("c.jsonl").exists());
+    }
+
+    #[tokio::test]
+    async fn test_clean_up_earlier_run() {
+        let data = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        write_lines(data.path(), "a.jsonl", 10);
+        let cached = cache.path().join("train");
+        std::fs::create_dir(&cached).unwrap();
+        std::fs::write(cached.join(CACHE_MARKER), "").unwrap();
+        std::fs::write(cached.join("a.jsonl"), "{}\n".repeat(10)).unwrap();
+        std::fs::write(cached.join("old.jsonl"), "{}\n").unwrap();
+        std::fs::write(cached.join(".b.jsonl.partial"), "{").unwrap();
+        // Files the prefetcher did not create are left alone
+        std::fs::write(cache.path().join("notes.txt"), "").unwrap();
+        std::fs::create_dir(cache.path().join("other")).unwrap();
+        std::fs::write(cache.path().join("other/model.pt"), "").unwrap();
+
+        let prefetcher = Prefetcher::spawn(cache.path(), 2, StorageConfig::default());
+        wait_for(&prefetcher, |s| s.cached_files == 2).await;
+        assert!(!cached.join(".b.jsonl.partial").exists());
+
+        // The file still assigned is kept, the other evicted
+        prefetcher.set_assignments(vec![assignment(data.path(), 0, 0, 10)]);
+        wait_for(&prefetcher, |s| s.files_evicted == 1).await;
+        let stats = prefetcher.stats();
+        assert_eq!((stats.files_fetched, stats.cached_files), (0, 1));
+        assert!(cached.join("a.jsonl").exists());
+        assert!(!cached.join("old.jsonl").exists());
+        assert!(cache.path().join("notes.txt").exists());
+        assert!(cache.path().join("other/model.pt").exists());
+        prefetcher.stop().await;
+    }
+
+    #[tokio::test]
+    async fn test_fetch_in_chunks() {
+        let data = tempfile::tempdir().unwrap();
+        let cache = tempfile::tempdir().unwrap();
+        write_lines(data.path(), "a.jsonl", 10);
+        write_lines(data.path(), "b.jsonl", 12);
+        let storage: Arc<dyn StorageBackend> = Arc::new(LocalStorage::new(data.path()));
+
+        // 30 bytes ends mid-chunk, 36 on a chunk boundary
+        for (key, size) in [("a.jsonl", 30), ("b.jsonl", 36)] {
+            let entry = CacheEntry {
+                storage: storage.clone(),
+                key: key.to_string(),
+                dir: cache.path().to_path_buf(),
+                dest: cache.path().join(key),
+            };
+            assert_eq!(fetch(&entry, 4).await.unwrap(), Some(size));
+            assert_eq!(
+                std::fs::read(&entry.dest).unwrap(),
+                std::fs::read(data.path().join(key)).unwrap()
+            );
+            assert_eq!(fetch(&entry, 4).await.unwrap(), None);
+        }
+        assert!(!cache.path().join(".a.jsonl.partial").exists());
+
+        // A failed copy leaves nothing behind
+        let entry = CacheEntry {
+            storage,
+            key: "missing.jsonl".to_string(),
+            dir: cache.path().to_path_buf(),
+            dest: cache.path().join("missing.jsonl"),
+        };
+        assert!(fetch(&entry, 4).await.is_err());
+        assert!(!cache.path().join(".missing.jsonl.partial").exists());
+    }
+
+    #[tokio::test]
+    async fn test_open_dataset() {
+        let data = tempfile::tempdir().unwrap();
+        std::fs::create_dir(data.path().join("train")).unwrap();
+        write_lines(&data.path().join("train"), "a.jsonl", 3);
+        write_lines(&data.path().join("train"), "b.jsonl", 4);
+        let config = StorageConfig::default();
+
+        let dir = data.path().join("train");
+        let dataset = open_dataset(dir.to_str().unwrap(), &config).await.unwrap();
+        assert_eq!(dataset.manifest.total_samples(), 7);
+
+        let file = data.path().join("train/b.jsonl");
+        let dataset = open_dataset(file.to_str().unwrap(), &config).await.unwrap();
+        assert_eq!(dataset.manifest.files()[0].key, "b.jsonl");
+
+        let pattern = format!("{}/*/a.*", data.path().display());
+        let dataset = open_dataset(&pattern, &config).await.unwrap();
+        assert_eq!(dataset.manifest.files()[0].key, "train/a.jsonl");
+    }
+}
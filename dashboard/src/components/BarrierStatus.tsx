@@ -1,4 +1,4 @@
-import { Check, Loader2 } from 'lucide-react'
+import { Check, Loader2, X } from 'lucide-react'
 import { useDashboardStore } from '../store'
 import { cn } from '../lib/utils'
 
@@ -17,6 +17,7 @@ export function BarrierStatus() {
         {barriers.map((barrier) => {
           const progress = (barrier.arrived / barrier.total) * 100
           const isComplete = barrier.status === 'complete'
+          const isAborted = barrier.status === 'aborted'
           
           return (
             <div key={barrier.id} className="space-y-2" role="listitem">
@@ -24,6 +25,8 @@ export function BarrierStatus() {
                 <div className="flex items-center gap-2">
                   {isComplete ? (
                     <Check className="w-4 h-4 text-emerald-400" aria-hidden="true" />
+                  ) : isAborted ? (
+                    <X className="w-4 h-4 text-red-400" aria-hidden="true" />
                   ) : (
                     <Loader2 className="w-4 h-4 text-blue-400 animate-spin" aria-hidden="true" />
                   )}
@@ -38,7 +41,7 @@ export function BarrierStatus() {
                 <div
                   className={cn(
                     'h-full rounded-full transition-all duration-500',
-                    isComplete ? 'bg-emerald-500' : 'bg-blue-500'
+                    isComplete ? 'bg-emerald-500' : isAborted ? 'bg-red-500' : 'bg-blue-500'
                   )}
                   style={{ width: `${progress}%` }}
                   role="progressbar"
@@ -1,5 +1,5 @@
 import { useState } from 'react'
-import { Play, Square, Eye, Clock, CheckCircle, XCircle, AlertCircle } from 'lucide-react'
+import { Play, Pause, Square, Eye, Clock, CheckCircle, XCircle, AlertCircle } from 'lucide-react'
 import { useDashboardStore } from '../store'
 import { api } from '../lib/api'
 
@@ -7,7 +7,7 @@ interface Task {
   id: string
   name: string
   type: string
-  status: 'running' | 'completed' | 'failed' | 'pending'
+  status: 'running' | 'completed' | 'failed' | 'pending' | 'paused' | 'stopped'
   worker_ids: string[]
   dataset_id: string
   started_at: number
@@ -57,6 +57,20 @@ export function TaskManager() {
     }
   }
 
+  const handleTaskAction = async (taskId: string, action: 'pause' | 'resume') => {
+    try {
+      if (action === 'pause') {
+        await api.pauseTask(taskId)
+      } else {
+        await api.resumeTask(taskId)
+      }
+      await useDashboardStore.getState().fetchLiveData()
+    } catch (error) {
+      console.error(`Failed to ${action} task:`, error)
+      alert(`Failed to ${action} task: ${error instanceof Error ? error.message : 'Unknown error'}`)
+    }
+  }
+
   const getStatusIcon = (status: string) => {
     switch (status) {
       case 'running':
@@ -67,6 +81,8 @@ export function TaskManager() {
         return <XCircle className="w-4 h-4 text-red-400" />
       case 'pending':
         return <Clock className="w-4 h-4 text-yellow-400" />
+      case 'paused':
+        return <Pause className="w-4 h-4 text-yellow-400" />
       default:
         return <AlertCircle className="w-4 h-4 text-gray-400" />
     }
@@ -126,6 +142,24 @@ export function TaskManager() {
                         <Eye className="w-4 h-4" />
                       </button>
                       {task.status === 'running' && (
+                        <button
+                          onClick={() => handleTaskAction(task.id, 'pause')}
+                          className="p-2 text-yellow-400 hover:text-yellow-300"
+                          title="Pause task"
+                        >
+                          <Pause className="w-4 h-4" />
+                        </button>
+                      )}
+                      {task.status === 'paused' && (
+                        <button
+                          onClick={() => handleTaskAction(task.id, 'resume')}
+                          className="p-2 text-blue-400 hover:text-blue-300"
+                          title="Resume task"
+                        >
+                          <Play className="w-4 h-4" />
+                        </button>
+                      )}
+                      {['pending', 'running', 'paused'].includes(task.status) && (
                         <button
                           onClick={() => handleStopTask(task.id)}
                           className="p-2 text-red-400 hover:text-red-300"
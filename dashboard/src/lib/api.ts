@@ -15,6 +15,52 @@ export interface ApiWorker {
   current_task: string
 }
 
+export interface ApiResourceSample {
+  timestamp_ms: number
+  cpu_percent: number
+  memory_used_bytes: number
+  gpus: {
+    gpu_id: number
+    utilization_percent: number
+    memory_used_bytes: number
+    temperature_celsius: number
+  }[]
+}
+
+export interface ApiIoRates {
+  disk_read_bytes_per_sec: number
+  disk_write_bytes_per_sec: number
+  network_rx_bytes_per_sec: number
+  network_tx_bytes_per_sec: number
+}
+
+export interface ApiGpuSummary {
+  gpu_count: number
+  avg_utilization_percent: number
+  memory_used_bytes: number
+  memory_total_bytes: number
+  power_draw_watts: number
+  max_temperature_celsius: number
+  ecc_errors_corrected: number
+  ecc_errors_uncorrected: number
+}
+
+export interface ApiWorkerDetail extends ApiWorker {
+  rank: number
+  world_size: number
+  registered_at: number
+  metadata: Record<string, string>
+  labels: Record<string, string>
+  taints: string[]
+  shards: Record<string, number[]>
+  resource_history: ApiResourceSample[]
+  io_rates: ApiIoRates | null
+  gpus: ApiGpuSummary
+  transitions: { timestamp_ms: number; from: string | null; to: string }[]
+  metrics: Record<string, { timestamp_ms: number; step: number; value: number }[]>
+  last_error: { timestamp_ms: number; message: string } | null
+}
+
 export interface ApiDataset {
   id: string
   name: string
@@ -26,6 +72,53 @@ export interface ApiDataset {
   registered_at: number
 }
 
+export interface ApiEpoch {
+  dataset_id: string
+  epoch: number
+  samples_consumed: number
+  total_samples: number
+  completed_shards: number
+  total_shards: number
+  progress: number
+  elapsed_seconds: number
+  eta_seconds: number | null
+  workers_reported: string[]
+  workers_required: number
+}
+
+export interface ApiShard {
+  shard_id: number
+  start_index: number
+  end_index: number
+  samples_consumed: number
+  worker_id: string | null
+}
+
+export interface ApiDatasetPreview {
+  path: string
+  lines: string[]
+  binary: boolean
+  truncated: boolean
+}
+
+export interface ApiDatasetDetail {
+  id: string
+  path: string
+  format: string
+  total_samples: number
+  shard_size: number
+  shard_count: number
+  shuffle: boolean
+  seed: number
+  metadata: Record<string, string>
+  file_paths: string[]
+  file_count: number
+  files_truncated: boolean
+  epoch: ApiEpoch
+  shards: ApiShard[]
+  preview: ApiDatasetPreview | null
+}
+
 export interface ApiCheckpoint {
   id: string
   step: number
@@ -46,6 +139,32 @@ export interface ApiBarrier {
   created_at: number
 }
 
+// Runtime settings that can change without a restart
+export interface ApiConfigUpdate {
+  heartbeat_timeout_ms?: number
+  checkpoint_keep_count?: number
+  http_rate_limit?: {
+    enabled: boolean
+    requests_per_second: number
+    burst: number
+    routes: { path_prefix: string; requests_per_second: number; burst: number }[]
+  }
+}
+
+export interface ApiMetricsSample {
+  timestamp_ms: number
+  total_workers: number
+  active_workers: number
+  requests_per_second: number
+  checkpoints_per_minute: number
+  datasets: Record<string, { epoch: number; progress: number }>
+}
+
+export interface ApiMetricsHistory {
+  range_seconds: number
+  samples: ApiMetricsSample[]
+}
+
 export interface ApiMetrics {
   checkpoint_throughput: number
   coordinator_rps: number
@@ -53,6 +172,8 @@ export interface ApiMetrics {
   total_workers: number
   barrier_latency_p99: number
   shard_assignment_time: number
+  io_rates: ApiIoRates
+  gpus: ApiGpuSummary
 }
 
 export interface ApiStatus {
@@ -66,7 +187,7 @@ export interface ApiTask {
   id: string
   name: string
   type: string
-  status: 'running' | 'completed' | 'failed' | 'pending'
+  status: 'running' | 'completed' | 'failed' | 'pending' | 'paused' | 'stopped'
   worker_ids: string[]
   dataset_id: string
   started_at: number
@@ -75,6 +196,37 @@ export interface ApiTask {
   logs: string[]
 }
 
+export interface ApiTaskTemplate {
+  name: string
+  type: string
+  // A trailing '*' picks the newest matching dataset, e.g. 'snapshot-*'
+  dataset_id: string
+  worker_count: number
+  epochs?: number
+  config?: Record<string, any>
+}
+
+export interface ApiScheduleRun {
+  scheduled_at_ms: number
+  started_at_ms: number
+  manual: boolean
+  dataset_id: string | null
+  task_id: string | null
+  error: string | null
+}
+
+export interface ApiSchedule {
+  id: string
+  name: string
+  cron: string
+  template: ApiTaskTemplate
+  enabled: boolean
+  allow_overlap: boolean
+  created_at_ms: number
+  next_run_ms: number | null
+  history: ApiScheduleRun[]
+}
+
 export interface ApiLogEntry {
   id: string
   timestamp: number
@@ -85,6 +237,76 @@ export interface ApiLogEntry {
   worker_id?: string
 }
 
+// Event pushed on /api/stream; extra fields depend on the event type
+export interface ApiCoordinatorEvent {
+  seq: number
+  id: string
+  timestamp_ms: number
+  type: string
+  worker_id?: string
+  [key: string]: unknown
+}
+
+// On-demand checkpoint requested via /api/checkpoints/trigger
+export interface ApiCheckpointTransaction {
+  id: string
+  step: number
+  worker_ids: string[]
+  checkpoint_ids: Record<string, string>
+  state: 'pending' | 'completed' | 'failed'
+  created_at_ms: number
+  deadline_ms: number
+  completed_at_ms: number | null
+  error: string | null
+}
+
+// Filters accepted by /api/logs
+export interface ApiLogFilter {
+  level?: 'trace' | 'debug' | 'info' | 'warn' | 'error'
+  source?: string
+  worker_id?: string
+  task_id?: string
+  since?: number
+  until?: number
+}
+
+// Event types sent by the coordinator on /api/stream
+export const COORDINATOR_EVENT_TYPES = [
+  'worker_joined',
+  'worker_left',
+  'worker_dead',
+  'worker_state_changed',
+  'worker_draining',
+  'worker_blacklisted',
+  'shards_rebalanced',
+  'dataset_registered',
+  'dataset_removed',
+  'dataset_updated',
+  'epoch_advanced',
+  'barrier_released',
+  'barrier_closed',
+  'checkpoint_committed',
+  'checkpoint_deleted',
+  'shutting_down',
+  'task_state_changed',
+  'schedule_ran',
+  'config_updated',
+] as const
+
+// Callbacks for the messages pushed on /api/stream
+export interface StreamHandlers {
+  onEvent?: (event: ApiCoordinatorEvent) => void
+  // Sent with the whole collection after it changes
+  onWorkers?: (workers: ApiWorker[]) => void
+  onDatasets?: (datasets: ApiDataset[]) => void
+  onCheckpoints?: (checkpoints: ApiCheckpoint[]) => void
+  onBarriers?: (barriers: ApiBarrier[]) => void
+  onTasks?: (tasks: ApiTask[]) => void
+  // A log line recorded by the coordinator or shipped by a worker
+  onLog?: (line: ApiLogEntry) => void
+  onError?: () => void
+}
+
 export interface ApiDashboardState {
   coordinator: ApiStatus
   workers: ApiWorker[]
@@ -123,10 +345,132 @@ class CoordinatorApi {
     return this.fetch('/workers')
   }
 
+  async getWorker(workerId: string): Promise<ApiWorkerDetail> {
+    return this.fetch(`/workers/${workerId}`)
+  }
+
+  // Take a worker out of rotation: drain, remove or blacklist
+  async manageWorker(
+    workerId: string,
+    action: 'drain' | 'remove' | 'blacklist',
+    reason?: string
+  ): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/workers/${workerId}/${action}`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(action === 'blacklist' ? { reason: reason ?? '' } : {})
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to ${action} worker: ${response.status} ${response.statusText}`)
+    }
+  }
+
   async getDatasets(): Promise<ApiDataset[]> {
     return this.fetch('/datasets')
   }
 
+  async registerDataset(dataset: {
+    dataset_id: string
+    path: string
+    format: string
+    total_samples: number
+    shard_size: number
+    shuffle?: boolean
+    seed?: number
+    metadata?: Record<string, string>
+  }): Promise<{ dataset_id: string; total_shards: number; message: string }> {
+    const response = await fetch(`${this.baseUrl}/datasets`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(dataset)
+    })
+    if (!response.ok) {
+      const body = await response.json().catch(() => ({}))
+      throw new Error(body.error || `Failed to register dataset: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async updateDataset(
+    datasetId: string,
+    update: { shuffle?: boolean; seed?: number }
+  ): Promise<ApiDataset> {
+    const response = await fetch(`${this.baseUrl}/datasets/${datasetId}`, {
+      method: 'PATCH',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(update)
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to update dataset: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async deleteDataset(datasetId: string): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/datasets/${datasetId}`, {
+      method: 'DELETE'
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to delete dataset: ${response.status} ${response.statusText}`)
+    }
+  }
+
+  async getDataset(datasetId: string, previewLines = 0): Promise<ApiDatasetDetail> {
+    const query = previewLines > 0 ? `?preview=${previewLines}` : ''
+    const response = await fetch(`${this.baseUrl}/datasets/${datasetId}${query}`)
+    if (!response.ok) {
+      throw new Error(`Failed to fetch dataset: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async getDatasetEpoch(datasetId: string): Promise<ApiEpoch> {
+    const response = await fetch(`${this.baseUrl}/datasets/${datasetId}/epoch`)
+    if (!response.ok) {
+      throw new Error(`Failed to fetch epoch: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async advanceDatasetEpoch(datasetId: string, expectedEpoch?: number): Promise<ApiEpoch> {
+    const response = await fetch(`${this.baseUrl}/datasets/${datasetId}/epoch/advance`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ expected_epoch: expectedEpoch })
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to advance epoch: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async deleteCheckpoint(checkpointId: string, deleteData: boolean = false): Promise<void> {
+    const response = await fetch(
+      `${this.baseUrl}/checkpoints/${checkpointId}?delete_data=${deleteData}`,
+      { method: 'DELETE' }
+    )
+    if (!response.ok) {
+      throw new Error(`Failed to delete checkpoint: ${response.status} ${response.statusText}`)
+    }
+  }
+
+  async triggerCheckpoint(workerIds: string[] = []): Promise<ApiCheckpointTransaction> {
+    const response = await fetch(`${this.baseUrl}/checkpoints/trigger`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({ worker_ids: workerIds })
+    })
+    if (!response.ok) {
+      const body = await response.json().catch(() => ({}))
+      throw new Error(body.error || `Failed to trigger checkpoint: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async getCheckpointTransaction(txnId: string): Promise<ApiCheckpointTransaction> {
+    return this.fetch(`/checkpoints/transactions/${txnId}`)
+  }
+
   async getCheckpoints(): Promise<ApiCheckpoint[]> {
     return this.fetch('/checkpoints')
   }
@@ -135,10 +479,45 @@ class CoordinatorApi {
     return this.fetch('/barriers')
   }
 
+  // Abort a stuck barrier, or release its waiting workers
+  async closeBarrier(barrierId: string, release: boolean = false): Promise<ApiBarrier[]> {
+    const response = await fetch(
+      `${this.baseUrl}/barriers/${encodeURIComponent(barrierId)}?release=${release}`,
+      { method: 'DELETE' }
+    )
+    if (!response.ok) {
+      throw new Error(`Failed to close barrier: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
   async getMetrics(): Promise<ApiMetrics> {
     return this.fetch('/metrics')
   }
 
+  // Effective runtime configuration, secrets redacted
+  async getConfig(): Promise<Record<string, any>> {
+    return this.fetch('/config')
+  }
+
+  async updateConfig(update: ApiConfigUpdate): Promise<Record<string, any>> {
+    const response = await fetch(`${this.baseUrl}/config`, {
+      method: 'PUT',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(update)
+    })
+    if (!response.ok) {
+      const body = await response.json().catch(() => ({}))
+      throw new Error(body.error || `Failed to update config: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  // Sampled metrics for charts; range like '15m', '1h' or '1d'
+  async getMetricsHistory(range: string = '1h'): Promise<ApiMetricsHistory> {
+    return this.fetch(`/metrics/history?range=${encodeURIComponent(range)}`)
+  }
+
   async getDashboardState(): Promise<ApiDashboardState> {
     return this.fetch('/dashboard')
   }
@@ -176,12 +555,126 @@ class CoordinatorApi {
     return response.json()
   }
 
+  async pauseTask(taskId: string): Promise<ApiTask> {
+    const response = await fetch(`${this.baseUrl}/tasks/${taskId}/pause`, {
+      method: 'POST'
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to pause task: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async resumeTask(taskId: string): Promise<ApiTask> {
+    const response = await fetch(`${this.baseUrl}/tasks/${taskId}/resume`, {
+      method: 'POST'
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to resume task: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  // Schedules start tasks from a template on a cron timetable (UTC)
+  async getSchedules(): Promise<ApiSchedule[]> {
+    return this.fetch('/schedules')
+  }
+
+  async createSchedule(schedule: {
+    name: string
+    cron: string
+    template: ApiTaskTemplate
+    enabled?: boolean
+    allow_overlap?: boolean
+  }): Promise<ApiSchedule> {
+    const response = await fetch(`${this.baseUrl}/schedules`, {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(schedule)
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to create schedule: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async updateSchedule(
+    scheduleId: string,
+    update: { cron?: string; enabled?: boolean; allow_overlap?: boolean }
+  ): Promise<ApiSchedule> {
+    const response = await fetch(`${this.baseUrl}/schedules/${scheduleId}`, {
+      method: 'PATCH',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify(update)
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to update schedule: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
+  async deleteSchedule(scheduleId: string): Promise<void> {
+    const response = await fetch(`${this.baseUrl}/schedules/${scheduleId}`, {
+      method: 'DELETE'
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to delete schedule: ${response.status} ${response.statusText}`)
+    }
+  }
+
+  async runSchedule(scheduleId: string): Promise<ApiScheduleRun> {
+    const response = await fetch(`${this.baseUrl}/schedules/${scheduleId}/run`, {
+      method: 'POST'
+    })
+    if (!response.ok) {
+      throw new Error(`Failed to run schedule: ${response.status} ${response.statusText}`)
+    }
+    return response.json()
+  }
+
   async getTaskLogs(taskId: string): Promise<ApiLogEntry[]> {
     return this.fetch(`/tasks/${taskId}/logs`)
   }
 
-  async getLogs(limit: number = 100): Promise<ApiLogEntry[]> {
-    return this.fetch(`/logs?limit=${limit}`)
+  async getLogs(limit: number = 100, filter: ApiLogFilter = {}): Promise<ApiLogEntry[]> {
+    const params = new URLSearchParams({ limit: String(limit) })
+    for (const [key, value] of Object.entries(filter)) {
+      if (value !== undefined && value !== '') {
+        params.set(key, String(value))
+      }
+    }
+    return this.fetch(`/logs?${params}`)
+  }
+
+  // Subscribe to /api/stream; returns a function that closes the stream
+  subscribeStream(handlers: StreamHandlers): () => void {
+    const source = new EventSource(`${this.baseUrl}/stream`)
+    const listen = <T>(type: string, handler?: (payload: T) => void) => {
+      if (!handler) return
+      source.addEventListener(type, (message: MessageEvent) => {
+        let payload: T
+        try {
+          payload = JSON.parse(message.data)
+        } catch {
+          // Ignore malformed payloads
+          return
+        }
+        handler(payload)
+      })
+    }
+    for (const type of COORDINATOR_EVENT_TYPES) {
+      listen(type, handlers.onEvent)
+    }
+    listen('workers', handlers.onWorkers)
+    listen('datasets', handlers.onDatasets)
+    listen('checkpoints', handlers.onCheckpoints)
+    listen('barriers', handlers.onBarriers)
+    listen('tasks', handlers.onTasks)
+    listen('log', handlers.onLog)
+    if (handlers.onError) {
+      source.onerror = handlers.onError
+    }
+    return () => source.close()
   }
 }
 
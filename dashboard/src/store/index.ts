@@ -1,12 +1,13 @@
 import { create } from 'zustand'
 import type { Worker, Dataset, Checkpoint, SystemMetrics, LogEntry, CoordinatorStatus, BarrierStatus } from '../types'
 import { api } from '../lib/api'
+import type { ApiBarrier, ApiCheckpoint, ApiDataset, ApiLogEntry, ApiTask, ApiWorker } from '../lib/api'
 
 interface Task {
   id: string
   name: string
   type: string
-  status: 'running' | 'completed' | 'failed' | 'pending'
+  status: 'running' | 'completed' | 'failed' | 'pending' | 'paused' | 'stopped'
   worker_ids: string[]
   dataset_id: string
   started_at: number
@@ -41,6 +42,79 @@ interface DashboardState {
 }
 
 let pollingInterval: ReturnType<typeof setInterval> | null = null
+let closeEventStream: (() => void) | null = null
+
+// Polling is only a fallback once pushed events are flowing
+const POLL_INTERVAL_MS = 5000
+// Log lines kept in the store
+const MAX_LOGS = 100
+
+const toWorker = (w: ApiWorker): Worker => ({
+  id: w.id,
+  ip: w.ip,
+  port: w.port,
+  status: w.status as Worker['status'],
+  gpuCount: w.gpu_count,
+  lastHeartbeat: w.last_heartbeat,
+  assignedShards: w.assigned_shards,
+  currentEpoch: w.current_epoch,
+  currentStep: w.current_step,
+  currentTask: w.current_task,
+})
+
+const toDataset = (d: ApiDataset): Dataset => ({
+  id: d.id,
+  name: d.name,
+  totalSamples: d.total_samples,
+  shardSize: d.shard_size,
+  shardCount: d.shard_count,
+  format: d.format,
+  shuffle: d.shuffle,
+  registeredAt: d.registered_at,
+})
+
+const toCheckpoint = (c: ApiCheckpoint): Checkpoint => ({
+  id: c.id,
+  step: c.step,
+  epoch: c.epoch,
+  size: c.size,
+  path: c.path,
+  createdAt: c.created_at,
+  workerId: c.worker_id,
+  status: c.status as Checkpoint['status'],
+})
+
+const toBarrier = (b: ApiBarrier): BarrierStatus => ({
+  id: b.id,
+  name: b.name,
+  arrived: b.arrived,
+  total: b.total,
+  status: b.status as BarrierStatus['status'],
+  createdAt: b.created_at,
+})
+
+const toTask = (t: ApiTask): Task => ({
+  id: t.id,
+  name: t.name,
+  type: t.type,
+  status: t.status,
+  worker_ids: t.worker_ids,
+  dataset_id: t.dataset_id,
+  started_at: t.started_at,
+  completed_at: t.completed_at,
+  progress: t.progress,
+  logs: t.logs,
+})
+
+const toLog = (l: ApiLogEntry): LogEntry => ({
+  id: l.id,
+  timestamp: l.timestamp,
+  level: l.level,
+  message: l.message,
+  source: l.source,
+  taskId: l.task_id,
+  workerId: l.worker_id,
+})
 
 export const useDashboardStore = create<DashboardState>((set, get) => ({
   // Initial state
@@ -78,7 +152,7 @@ export const useDashboardStore = create<DashboardState>((set, get) => ({
       timestamp: Date.now(),
     }
     set((state) => ({
-      logs: [log, ...state.logs].slice(0, 100),
+      logs: [log, ...state.logs].slice(0, MAX_LOGS),
     }))
   },
 
@@ -91,51 +165,11 @@ export const useDashboardStore = create<DashboardState>((set, get) => ({
     try {
       const data = await api.getDashboardState()
       
-      // Map API response to our types
-      const workers: Worker[] = data.workers.map(w => ({
-        id: w.id,
-        ip: w.ip,
-        port: w.port,
-        status: w.status as 'active' | 'idle' | 'failed' | 'unknown',
-        gpuCount: w.gpu_count,
-        lastHeartbeat: w.last_heartbeat,
-        assignedShards: w.assigned_shards,
-        currentEpoch: w.current_epoch,
-        currentStep: w.current_step,
-        currentTask: w.current_task,
-      }))
-      
-      const datasets: Dataset[] = data.datasets.map(d => ({
-        id: d.id,
-        name: d.name,
-        totalSamples: d.total_samples,
-        shardSize: d.shard_size,
-        shardCount: d.shard_count,
-        format: d.format,
-        shuffle: d.shuffle,
-        registeredAt: d.registered_at,
-      }))
-      
-      const checkpoints: Checkpoint[] = data.checkpoints.map(c => ({
-        id: c.id,
-        step: c.step,
-        epoch: c.epoch,
-        size: c.size,
-        path: c.path,
-        createdAt: c.created_at,
-        workerId: c.worker_id,
-        status: c.status as 'completed' | 'in_progress' | 'failed',
-      }))
-      
-      const barriers: BarrierStatus[] = data.barriers.map(b => ({
-        id: b.id,
-        name: b.name,
-        arrived: b.arrived,
-        total: b.total,
-        status: b.status as 'waiting' | 'complete',
-        createdAt: b.created_at,
-      }))
-      
+      const workers = data.workers.map(toWorker)
+      const datasets = data.datasets.map(toDataset)
+      const checkpoints = data.checkpoints.map(toCheckpoint)
+      const barriers = data.barriers.map(toBarrier)
+
       const metrics: SystemMetrics = {
         checkpointThroughput: data.metrics.checkpoint_throughput,
         coordinatorRps: data.metrics.coordinator_rps,
@@ -145,29 +179,9 @@ export const useDashboardStore = create<DashboardState>((set, get) => ({
         shardAssignmentTime: data.metrics.shard_assignment_time,
       }
 
-      const tasks: Task[] = (data.tasks || []).map(t => ({
-        id: t.id,
-        name: t.name,
-        type: t.type,
-        status: t.status,
-        worker_ids: t.worker_ids,
-        dataset_id: t.dataset_id,
-        started_at: t.started_at,
-        completed_at: t.completed_at,
-        progress: t.progress,
-        logs: t.logs,
-      }))
+      const tasks = (data.tasks || []).map(toTask)
+      const systemLogs = (data.logs || []).map(toLog)
 
-      const systemLogs: LogEntry[] = (data.logs || []).map(l => ({
-        id: l.id,
-        timestamp: l.timestamp,
-        level: l.level,
-        message: l.message,
-        source: l.source,
-        taskId: l.task_id,
-        workerId: l.worker_id,
-      }))
-      
       set({
         workers,
         datasets,
@@ -202,10 +216,27 @@ export const useDashboardStore = create<DashboardState>((set, get) => ({
     // Fetch immediately
     get().fetchLiveData()
     
-    // Then poll every 2 seconds
+    // Apply the state and log lines the coordinator pushes as they change
+    closeEventStream = api.subscribeStream({
+      onWorkers: (workers) => set({ workers: workers.map(toWorker) }),
+      onDatasets: (datasets) => set({ datasets: datasets.map(toDataset) }),
+      onCheckpoints: (checkpoints) => set({ checkpoints: checkpoints.map(toCheckpoint) }),
+      onBarriers: (barriers) => set({ barriers: barriers.map(toBarrier) }),
+      onTasks: (tasks) => set({ tasks: tasks.map(toTask) }),
+      onLog: (line) => {
+        const log = toLog(line)
+        set((state) =>
+          state.logs.some((l) => l.id === log.id)
+            ? state
+            : { logs: [log, ...state.logs].slice(0, MAX_LOGS) }
+        )
+      },
+    })
+    
+    // Poll as a fallback for state that changes without events
     pollingInterval = setInterval(() => {
       get().fetchLiveData()
-    }, 2000)
+    }, POLL_INTERVAL_MS)
   },
 
   stopLiveMode: () => {
@@ -213,5 +244,9 @@ export const useDashboardStore = create<DashboardState>((set, get) => ({
       clearInterval(pollingInterval)
       pollingInterval = null
     }
+    if (closeEventStream) {
+      closeEventStream()
+      closeEventStream = null
+    }
   },
 }))
@@ -4,7 +4,7 @@ export interface Worker {
   id: string
   ip: string
   port: number
-  status: 'active' | 'idle' | 'failed' | 'unknown'
+  status: 'active' | 'idle' | 'draining' | 'failed' | 'unknown'
   gpuCount: number
   lastHeartbeat: number
   assignedShards: number
@@ -49,7 +49,7 @@ export interface BarrierStatus {
   name: string
   arrived: number
   total: number
-  status: 'waiting' | 'complete'
+  status: 'waiting' | 'complete' | 'aborted'
   createdAt: number
 }
 
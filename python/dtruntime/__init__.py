@@ -24,24 +24,58 @@ from ._core import (
     # Dataset sharding
     DatasetRegistry,
     ShardInfo,
+    DatasetManifest,
+    ConsistentHash,
+    EpochCoordinator,
     # Checkpoint management  
     CheckpointManager,
     CheckpointInfo,
+    CheckpointBuffer,
+    CheckpointWriter,
+    PendingCheckpoint,
     # Distributed orchestration
     TrainingOrchestrator,
     WorkerConfig,
+    BarrierTimeout,
+    MetricsReporter,
+    # Cluster introspection
+    WorkerSnapshot,
+    MembershipEvent,
+    ClusterMetrics,
+    CoordinatorCheckpointInfo,
+    # Logging
+    enable_logging,
 )
+from .data import SampleRange, StrataIterableDataset
 
 __all__ = [
     # Dataset sharding
     "DatasetRegistry",
     "ShardInfo",
+    "DatasetManifest",
+    "ConsistentHash",
+    "EpochCoordinator",
     # Checkpoint management
     "CheckpointManager", 
     "CheckpointInfo",
+    "CheckpointBuffer",
+    "CheckpointWriter",
+    "PendingCheckpoint",
     # Distributed orchestration
     "TrainingOrchestrator",
     "WorkerConfig",
+    "BarrierTimeout",
+    "MetricsReporter",
+    # Cluster introspection
+    "WorkerSnapshot",
+    "MembershipEvent",
+    "ClusterMetrics",
+    "CoordinatorCheckpointInfo",
+    # Logging
+    "enable_logging",
+    # PyTorch data loading
+    "StrataIterableDataset",
+    "SampleRange",
 ]
 
 __version__ = "0.1.0"
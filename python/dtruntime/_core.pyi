@@ -0,0 +1,1466 @@
+# Generated by scripts/generate_stubs.py from the PyO3 bindings, do not edit
+
+import os
+from typing import Any, Awaitable, Dict, List, Optional, Tuple, Union
+
+__version__: str
+
+class BarrierTimeout(TimeoutError): ...
+
+def enable_logging(level: Optional[Any] = None) -> None:
+    """Forward Rust log events to Python `logging`
+
+    Events from the coordinator client, checkpoint writer and the rest of
+    the runtime go to loggers under `dtruntime`, named after the Rust
+    module, with matching levels. Calling it again changes the level.
+
+    Args:
+        level: Least severe level forwarded, as a `logging` level or its
+            name (default: the effective level of the `dtruntime` logger)
+
+    Raises:
+        RuntimeError: If another tracing subscriber is already installed
+
+    Example:
+        logging.basicConfig(level=logging.INFO)
+        dtruntime.enable_logging()
+    """
+    ...
+
+class CheckpointBuffer:
+    """Read-only checkpoint data exposed through the buffer protocol
+
+    Returned wrapped in a `memoryview` by `CheckpointManager.load` for large
+    checkpoints, so e.g. `numpy.frombuffer` reads it without a copy.
+    """
+    def __buffer__(self, flags: int, /) -> memoryview: ...
+    def __len__(self) -> int: ...
+
+class CheckpointInfo:
+    """Metadata about a saved checkpoint"""
+    @property
+    def checkpoint_id(self) -> str: ...
+    @property
+    def step(self) -> int: ...
+    @property
+    def epoch(self) -> int: ...
+    @property
+    def path(self) -> str: ...
+    @property
+    def size_bytes(self) -> int: ...
+    @property
+    def created_at(self) -> str: ...
+    @property
+    def metadata(self) -> Dict[str, str]: ...
+    def __repr__(self) -> str: ...
+
+class PendingCheckpoint:
+    """A checkpoint write started by `save` that hasn't completed"""
+    @property
+    def checkpoint_id(self) -> str: ...
+    @property
+    def step(self) -> int: ...
+    @property
+    def epoch(self) -> int: ...
+    @property
+    def size_bytes(self) -> int: ...
+    @property
+    def status(self) -> str: ...
+    @property
+    def error(self) -> Optional[str]: ...
+    def __repr__(self) -> str: ...
+
+class CheckpointManager:
+    """Checkpoint manager for saving and loading training checkpoints
+
+    Provides async checkpoint writing with configurable retention.
+
+    Example:
+        ckpt = CheckpointManager("/tmp/checkpoints", keep_count=5)
+        # or straight to object storage
+        ckpt = CheckpointManager(backend="s3", bucket="ckpts", prefix="run-1/")
+
+        # Save a checkpoint
+        checkpoint_id = ckpt.save(model_bytes, step=1000, epoch=5)
+
+        # Load the latest checkpoint
+        info = ckpt.latest()
+        data = ckpt.load(info.checkpoint_id)
+    """
+    def __init__(
+        self,
+        base_path: Optional[str] = None,
+        keep_count: int = 5,
+        compression: bool = True,
+        backend: str = "local",
+        bucket: Optional[str] = None,
+        prefix: Optional[str] = None,
+        endpoint: Optional[str] = None,
+        region: Optional[str] = None,
+    ) -> None:
+        """Create a new checkpoint manager
+
+        Args:
+            base_path: Directory to store checkpoints, required for the local
+                backend
+            keep_count: Number of checkpoints to retain (default: 5)
+            compression: Enable compression (default: True)
+            backend: Where checkpoint data is written, "local" or "s3"
+                (default: "local")
+            bucket: S3 bucket, required for the s3 backend
+            prefix: Prefix for checkpoint keys in the bucket
+            endpoint: Custom S3 endpoint such as MinIO, uses path-style
+                addressing
+            region: AWS region (default: "us-east-1")
+
+        Raises:
+            ValueError: If the backend is unknown or missing its location
+        """
+        ...
+    def save(
+        self,
+        data: Any,
+        step: int,
+        epoch: int,
+        metadata: Optional[Dict[str, str]] = None,
+    ) -> str:
+        """Save a checkpoint asynchronously
+
+        The data is read in place rather than copied, so a mutable buffer
+        such as a numpy array must not change until `wait_pending()` returns.
+
+        Args:
+            data: Checkpoint data, bytes or any contiguous buffer such as a
+                numpy array
+            step: Current training step
+            epoch: Current training epoch
+            metadata: Optional metadata dictionary
+
+        Returns:
+            Checkpoint ID string
+        """
+        ...
+    def open_save(
+        self,
+        step: int,
+        epoch: int,
+        metadata: Optional[Dict[str, str]] = None,
+    ) -> CheckpointWriter:
+        """Start a checkpoint to be written in chunks
+
+        Useful with `torch.save`, which can write straight into the returned
+        writer instead of building the whole checkpoint as `bytes` first.
+        The checkpoint is queued for saving when the writer is closed.
+
+        Args:
+            step: Current training step
+            epoch: Current training epoch
+            metadata: Optional metadata dictionary
+
+        Returns:
+            A file-like CheckpointWriter
+
+        Example:
+            with ckpt.open_save(step=1000, epoch=5) as f:
+                torch.save(model.state_dict(), f)
+            checkpoint_id = f.checkpoint_id
+        """
+        ...
+    def load(self, checkpoint_id: str) -> Any:
+        """Load checkpoint data by ID
+
+        Args:
+            checkpoint_id: The checkpoint ID to load
+
+        Returns:
+            Checkpoint data as bytes, or as a read-only memoryview without a
+            copy for checkpoints of 64 MiB or more
+        """
+        ...
+    def load_latest(self) -> Optional[Tuple[Any, CheckpointInfo]]:
+        """Load the latest checkpoint
+
+        Unlike `latest()` followed by `load()`, a checkpoint removed by the
+        retention policy in between is skipped for the newer one instead of
+        failing.
+
+        Returns:
+            `(data, info)` with data as returned by `load`, or None if no
+            checkpoints exist
+        """
+        ...
+    def resume_or_none(self) -> Optional[Tuple[Any, int, int]]:
+        """Where to resume training from
+
+        Returns:
+            `(data, step, epoch)` of the latest checkpoint, or None when
+            training starts from scratch
+
+        Example:
+            start_step, start_epoch = 0, 0
+            resume = ckpt.resume_or_none()
+            if resume is not None:
+                data, start_step, start_epoch = resume
+        """
+        ...
+    def save_torch(
+        self,
+        state_dict: Any,
+        step: int,
+        epoch: int,
+        metadata: Optional[Dict[str, str]] = None,
+        format: str = "torch",
+    ) -> str:
+        """Save a PyTorch state dict
+
+        Serializes `state_dict` with `torch.save`, or with safetensors for
+        `format="safetensors"`, which only takes a flat dict of tensors. The
+        format and torch version are added to the metadata under `format`,
+        `framework` and `framework_version` so `load_torch` can read the
+        checkpoint back.
+
+        Args:
+            state_dict: State to save, e.g. `model.state_dict()`
+            step: Current training step
+            epoch: Current training epoch
+            metadata: Optional metadata dictionary
+            format: "torch" (default) or "safetensors"
+
+        Returns:
+            Checkpoint ID string
+
+        Example:
+            ckpt_id = ckpt.save_torch(model.state_dict(), step=1000, epoch=5)
+            model.load_state_dict(ckpt.load_torch(ckpt_id, map_location="cpu"))
+        """
+        ...
+    def load_torch(
+        self,
+        checkpoint_id: str,
+        map_location: Optional[Any] = None,
+        weights_only: bool = True,
+    ) -> Any:
+        """Load a state dict saved with `save_torch`
+
+        Args:
+            checkpoint_id: The checkpoint ID to load
+            map_location: Device to load tensors onto, as for `torch.load`
+            weights_only: Only unpickle tensors and plain containers, as for
+                `torch.load` (default: True). Ignored for safetensors.
+
+        Returns:
+            The saved state dict
+        """
+        ...
+    def latest(self) -> Optional[CheckpointInfo]:
+        """Get the latest checkpoint info
+
+        Returns:
+            CheckpointInfo or None if no checkpoints exist
+        """
+        ...
+    def get_by_step(self, step: int) -> Optional[CheckpointInfo]:
+        """Get checkpoint info by step
+
+        Args:
+            step: Training step to look up
+
+        Returns:
+            CheckpointInfo or None if not found
+        """
+        ...
+    def all_checkpoints(self) -> List[CheckpointInfo]:
+        """Get all checkpoint infos
+
+        Returns:
+            List of CheckpointInfo objects
+        """
+        ...
+    def pending(self) -> List[PendingCheckpoint]:
+        """Get the checkpoint writes that haven't completed
+
+        Writes are queued, then written one at a time. Failed writes stay
+        listed so they can be reported; completed ones show up in
+        `all_checkpoints()` instead.
+
+        Returns:
+            List of PendingCheckpoint objects, oldest step first
+
+        Example:
+            for write in ckpt.pending():
+                print(write.checkpoint_id, write.status)
+        """
+        ...
+    def wait_pending(self) -> None:
+        """Wait for all pending checkpoint writes to complete"""
+        ...
+    def close(self) -> None:
+        """Wait for pending writes, then stop the writer
+
+        The manager can't save or load afterwards. Closing twice is a no-op.
+        In a forked child, writes pending in the parent are left to it.
+        """
+        ...
+    def __enter__(self) -> CheckpointManager: ...
+    def __exit__(
+        self,
+        exc_type: Optional[Any],
+        _exc_value: Optional[Any],
+        _traceback: Optional[Any],
+    ) -> bool:
+        """Close the manager, see `close`
+
+        If the block raised, failed writes are logged instead of hiding the
+        original exception.
+        """
+        ...
+    def __repr__(self) -> str: ...
+
+class CheckpointWriter:
+    """File-like writer for a checkpoint saved in chunks
+
+    Returned by `CheckpointManager.open_save`. Chunks are copied into memory
+    owned by the manager as they are written, and the checkpoint is queued
+    for saving on `close()`. Leaving a `with` block with an exception
+    discards it instead.
+    """
+    def write(self, data: Any) -> int:
+        """Append a chunk, bytes or any contiguous buffer
+
+        Returns:
+            Number of bytes written
+        """
+        ...
+    def tell(self) -> int:
+        """Bytes written so far"""
+        ...
+    def flush(self) -> None:
+        """No-op, data is held until `close()`"""
+        ...
+    def writable(self) -> bool: ...
+    @property
+    def closed(self) -> bool:
+        """Whether the writer was closed or aborted"""
+        ...
+    @property
+    def checkpoint_id(self) -> Optional[str]:
+        """ID of the saved checkpoint, `None` until closed"""
+        ...
+    def close(self) -> Optional[str]:
+        """Queue the checkpoint for saving
+
+        Like `save`, the write happens in the background; call
+        `wait_pending()` on the manager to wait for it. Closing twice
+        returns the same ID.
+
+        Returns:
+            Checkpoint ID string, or None if the writer was aborted
+        """
+        ...
+    def abort(self) -> None:
+        """Discard the data written so far without saving it"""
+        ...
+    def __enter__(self) -> CheckpointWriter: ...
+    def __exit__(
+        self,
+        exc_type: Optional[Any],
+        _exc_value: Optional[Any],
+        _traceback: Optional[Any],
+    ) -> bool:
+        """Close the writer, or abort it if the block raised"""
+        ...
+    def __repr__(self) -> str: ...
+
+class ShardInfo:
+    """Information about a shard assignment"""
+    @property
+    def dataset_id(self) -> str: ...
+    @property
+    def shard_id(self) -> int: ...
+    @property
+    def total_shards(self) -> int: ...
+    @property
+    def start_index(self) -> int: ...
+    @property
+    def end_index(self) -> int: ...
+    @property
+    def epoch(self) -> int: ...
+    @property
+    def file_paths(self) -> List[str]: ...
+    def __repr__(self) -> str: ...
+    @property
+    def num_samples(self) -> int:
+        """Number of samples in this shard"""
+        ...
+
+class DatasetRegistry:
+    """Dataset registry for managing distributed data sharding
+
+    Example:
+        registry = DatasetRegistry()
+        registry.register_worker("worker-0")
+        registry.register_dataset("imagenet", total_samples=1281167, shard_size=10000)
+        shards = registry.get_shards("imagenet", "worker-0", epoch=0)
+    """
+    def __init__(self, coordinator_url: Optional[str] = None) -> None:
+        """Create a new dataset registry
+
+        Args:
+            coordinator_url: Optional URL of the coordinator server (for distributed mode)
+        """
+        ...
+    def register_worker(self, worker_id: str) -> None:
+        """Register a worker with the registry
+
+        Args:
+            worker_id: Unique identifier for the worker
+        """
+        ...
+    def remove_worker(self, worker_id: str) -> None:
+        """Remove a worker from the registry
+
+        Args:
+            worker_id: Identifier of the worker to remove
+        """
+        ...
+    def register_dataset(
+        self,
+        dataset_id: str,
+        total_samples: int,
+        shard_size: int,
+        shuffle: bool = True,
+        seed: int = 42,
+    ) -> None:
+        """Register a dataset for sharding
+
+        Args:
+            dataset_id: Unique identifier for the dataset
+            total_samples: Total number of samples in the dataset
+            shard_size: Number of samples per shard
+            shuffle: Whether to shuffle shards each epoch (default: True)
+            seed: Random seed for shuffling (default: 42)
+        """
+        ...
+    def get_shards(self, dataset_id: str, worker_id: str, epoch: int) -> List[ShardInfo]:
+        """Get shard assignments for a worker in a given epoch
+
+        Args:
+            dataset_id: Dataset identifier
+            worker_id: Worker identifier
+            epoch: Training epoch number
+
+        Returns:
+            List of ShardInfo objects assigned to this worker
+        """
+        ...
+    def advance_epoch(self, dataset_id: str) -> int:
+        """Advance to the next epoch for a dataset
+
+        Args:
+            dataset_id: Dataset identifier
+
+        Returns:
+            The new epoch number
+        """
+        ...
+    def current_epoch(self, dataset_id: str) -> int:
+        """Get the current epoch for a dataset
+
+        Args:
+            dataset_id: Dataset identifier
+
+        Returns:
+            Current epoch number
+        """
+        ...
+    @property
+    def worker_count(self) -> int:
+        """Get the number of active workers"""
+        ...
+    @property
+    def dataset_count(self) -> int:
+        """Get the number of registered datasets"""
+        ...
+    def active_workers(self) -> List[str]:
+        """Get list of active worker IDs"""
+        ...
+    def datasets(self) -> List[str]:
+        """Get list of registered dataset IDs"""
+        ...
+    def __repr__(self) -> str: ...
+
+class ConsistentHash:
+    """Consistent hash ring assigning unshuffled shards to workers
+
+    The ring the coordinator uses for datasets registered with
+    `shuffle=False`, keyed by worker ID.
+
+    Example:
+        ring = ConsistentHash()
+        ring.add_node("worker-0")
+        ring.add_node("worker-1")
+        shards = ring.get_shards_for_node("worker-0", "imagenet", total_shards=128)
+    """
+    def __init__(self, virtual_nodes: Optional[int] = None) -> None:
+        """Create an empty hash ring
+
+        Args:
+            virtual_nodes: Ring positions per node (default: as the coordinator)
+        """
+        ...
+    def add_node(self, node_id: str) -> None:
+        """Add a node to the ring"""
+        ...
+    def remove_node(self, node_id: str) -> None:
+        """Remove a node from the ring"""
+        ...
+    def get_node(self, key: str) -> Optional[str]:
+        """Node responsible for a key, or None for an empty ring"""
+        ...
+    def get_node_for_shard(self, dataset_id: str, shard_id: int) -> Optional[str]:
+        """Node responsible for a shard of a dataset"""
+        ...
+    def get_shards_for_node(
+        self,
+        node_id: str,
+        dataset_id: str,
+        total_shards: int,
+    ) -> List[int]:
+        """Shards of a dataset assigned to a node, in shard order
+
+        Args:
+            node_id: Node identifier
+            dataset_id: Dataset identifier
+            total_shards: Number of shards in the dataset
+        """
+        ...
+    def nodes(self) -> List[str]:
+        """Node IDs, in the order they were added"""
+        ...
+    def clear(self) -> None:
+        """Remove every node"""
+        ...
+    def __len__(self) -> int: ...
+    def __contains__(self, node_id: str) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class EpochCoordinator:
+    """Deterministic per-epoch shard shuffling
+
+    Shuffles like the coordinator does for a dataset registered with
+    `shuffle=True` and the same seed, and deals the shuffled shards to
+    workers round-robin by rank.
+
+    Example:
+        epochs = EpochCoordinator(seed=42)
+        order = epochs.get_shuffled_shards("imagenet", epoch=0, total_shards=128)
+        mine = epochs.get_worker_shards("imagenet", 0, 128, worker_rank=rank, world_size=8)
+    """
+    def __init__(self, seed: Optional[int] = None) -> None:
+        """Create an epoch coordinator
+
+        Args:
+            seed: Seed for every dataset without its own (default: random)
+        """
+        ...
+    @property
+    def base_seed(self) -> int:
+        """Seed for datasets without their own"""
+        ...
+    def set_seed(self, dataset_id: str, seed: int) -> None:
+        """Shuffle a dataset with its own seed, as given to `register_dataset`"""
+        ...
+    def seed(self, dataset_id: str) -> int:
+        """Seed a dataset is shuffled with"""
+        ...
+    def current_epoch(self, dataset_id: str) -> int:
+        """Current epoch for a dataset"""
+        ...
+    def init_epoch(self, dataset_id: str, epoch: int) -> None:
+        """Set the current epoch for a dataset"""
+        ...
+    def advance_epoch(self, dataset_id: str) -> int:
+        """Advance a dataset to its next epoch
+
+        Returns:
+            The new epoch number
+        """
+        ...
+    def get_shuffled_shards(self, dataset_id: str, epoch: int, total_shards: int) -> List[int]:
+        """Every shard of a dataset in its shuffled order for an epoch
+
+        Args:
+            dataset_id: Dataset identifier
+            epoch: Training epoch
+            total_shards: Number of shards in the dataset
+        """
+        ...
+    def get_worker_shards(
+        self,
+        dataset_id: str,
+        epoch: int,
+        total_shards: int,
+        worker_rank: int,
+        world_size: int,
+    ) -> List[int]:
+        """Shards of a dataset for one worker in an epoch, in shuffled order
+
+        Args:
+            dataset_id: Dataset identifier
+            epoch: Training epoch
+            total_shards: Number of shards in the dataset
+            worker_rank: Rank of the worker
+            world_size: Number of workers
+        """
+        ...
+    def remove_dataset(self, dataset_id: str) -> None:
+        """Forget a dataset's epoch, seed and cached shuffles"""
+        ...
+    def __repr__(self) -> str: ...
+
+class DatasetManifest:
+    """Manifest of the files behind a registered dataset
+
+    Returned by `TrainingOrchestrator.register_dataset_from_manifest`.
+    """
+    @property
+    def dataset_id(self) -> str: ...
+    @property
+    def path(self) -> str: ...
+    @property
+    def format(self) -> str: ...
+    @property
+    def shard_size(self) -> int: ...
+    @property
+    def total_shards(self) -> int: ...
+    @property
+    def total_samples(self) -> int:
+        """Total number of samples across the files"""
+        ...
+    @property
+    def files(self) -> List[Tuple[str, int, int]]:
+        """Files in sample order, as (path, first sample, sample count)"""
+        ...
+    def locate(self, index: int) -> Optional[Tuple[str, int]]:
+        """The file holding a sample, and the sample's index within it
+
+        Args:
+            index: Sample index in the dataset
+
+        Returns:
+            (path, index in file), or None past the last sample
+        """
+        ...
+    def files_in(self, start_index: int, end_index: int) -> List[Tuple[str, int, int]]:
+        """The parts of files holding a range of samples, e.g. a shard
+
+        Args:
+            start_index: First sample (inclusive)
+            end_index: Last sample (exclusive)
+
+        Returns:
+            List of (path, start in file, end in file)
+        """
+        ...
+    def __len__(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class WorkerConfig:
+    """Worker configuration returned after registration"""
+    @property
+    def worker_id(self) -> str: ...
+    @property
+    def rank(self) -> int: ...
+    @property
+    def world_size(self) -> int: ...
+    @property
+    def heartbeat_interval_ms(self) -> int: ...
+    @property
+    def membership_generation(self) -> int: ...
+    @property
+    def protocol_version(self) -> int: ...
+    @property
+    def session(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class CoordinatorShardInfo:
+    """Shard assignment from coordinator"""
+    @property
+    def dataset_id(self) -> str: ...
+    @property
+    def shard_id(self) -> int: ...
+    @property
+    def total_shards(self) -> int: ...
+    @property
+    def start_index(self) -> int: ...
+    @property
+    def end_index(self) -> int: ...
+    @property
+    def file_paths(self) -> List[str]: ...
+    @property
+    def epoch(self) -> int: ...
+    @property
+    def shards(self) -> List[Tuple[int, int, int]]: ...
+    @property
+    def prefetch_depth(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class BarrierResult:
+    """Barrier synchronization result"""
+    @property
+    def released(self) -> bool: ...
+    @property
+    def participants(self) -> int: ...
+    @property
+    def arrival_order(self) -> int: ...
+    @property
+    def already_released(self) -> bool: ...
+    @property
+    def expected(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class EpochStatus:
+    """Result of reporting an epoch complete"""
+    @property
+    def advanced(self) -> bool: ...
+    @property
+    def current_epoch(self) -> int: ...
+    @property
+    def reported(self) -> int: ...
+    @property
+    def required(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class RecoveryInfo:
+    """Where to resume training after a restart"""
+    @property
+    def checkpoint_id(self) -> str: ...
+    @property
+    def worker_id(self) -> str: ...
+    @property
+    def storage_path(self) -> str: ...
+    @property
+    def size_bytes(self) -> int: ...
+    @property
+    def resume_step(self) -> int: ...
+    @property
+    def resume_epoch(self) -> int: ...
+    @property
+    def metadata(self) -> Dict[str, str]: ...
+    @property
+    def shard_assignments(self) -> List[CoordinatorShardInfo]: ...
+    def __repr__(self) -> str: ...
+
+class MembershipEvent:
+    """A change in cluster membership, passed to `watch_membership` callbacks"""
+    @property
+    def kind(self) -> str: ...
+    @property
+    def worker_id(self) -> str: ...
+    @property
+    def rank(self) -> int: ...
+    @property
+    def state(self) -> str: ...
+    @property
+    def membership_generation(self) -> int: ...
+    @property
+    def world_size(self) -> int: ...
+    @property
+    def timestamp_ms(self) -> int: ...
+    def __repr__(self) -> str: ...
+
+class WorkerSnapshot:
+    """A worker as seen by the coordinator, returned by `list_workers`"""
+    @property
+    def worker_id(self) -> str: ...
+    @property
+    def hostname(self) -> str: ...
+    @property
+    def port(self) -> int: ...
+    @property
+    def rank(self) -> int: ...
+    @property
+    def state(self) -> str: ...
+    @property
+    def current_step(self) -> int: ...
+    @property
+    def current_epoch(self) -> int: ...
+    @property
+    def last_heartbeat_ms(self) -> int: ...
+    @property
+    def registered_at_ms(self) -> int: ...
+    @property
+    def draining(self) -> bool: ...
+    def __repr__(self) -> str: ...
+
+class CoordinatorCheckpointInfo:
+    """A checkpoint recorded by the coordinator, returned by `list_checkpoints`"""
+    @property
+    def checkpoint_id(self) -> str: ...
+    @property
+    def worker_id(self) -> str: ...
+    @property
+    def step(self) -> int: ...
+    @property
+    def epoch(self) -> int: ...
+    @property
+    def storage_path(self) -> str: ...
+    @property
+    def size_bytes(self) -> int: ...
+    @property
+    def timestamp_ms(self) -> int: ...
+    @property
+    def checkpoint_type(self) -> str: ...
+    @property
+    def metadata(self) -> Dict[str, str]: ...
+    def __repr__(self) -> str: ...
+
+class ClusterMetrics:
+    """Aggregate cluster metrics, returned by `cluster_metrics`
+
+    The same numbers as the coordinator's `/api/metrics` endpoint.
+    """
+    @property
+    def checkpoint_throughput(self) -> int: ...
+    @property
+    def coordinator_rps(self) -> int: ...
+    @property
+    def active_workers(self) -> int: ...
+    @property
+    def total_workers(self) -> int: ...
+    @property
+    def barrier_latency_p99_ms(self) -> int: ...
+    @property
+    def shard_assignment_time_ms(self) -> int: ...
+    @property
+    def duplicate_checkpoint_notifications(self) -> int: ...
+    @property
+    def disk_read_bytes_per_sec(self) -> float: ...
+    @property
+    def disk_write_bytes_per_sec(self) -> float: ...
+    @property
+    def network_rx_bytes_per_sec(self) -> float: ...
+    @property
+    def network_tx_bytes_per_sec(self) -> float: ...
+    @property
+    def gpu_count(self) -> int: ...
+    @property
+    def avg_gpu_utilization_percent(self) -> float: ...
+    @property
+    def gpu_memory_used_bytes(self) -> int: ...
+    @property
+    def gpu_memory_total_bytes(self) -> int: ...
+    @property
+    def gpu_power_draw_watts(self) -> float: ...
+    @property
+    def max_gpu_temperature_celsius(self) -> float: ...
+    def __repr__(self) -> str: ...
+
+class TrainingOrchestrator:
+    """High-level training orchestrator for distributed training coordination
+
+    Connects to a coordinator gRPC server to manage worker registration,
+    heartbeats, data sharding, and synchronization barriers.
+
+    Example:
+        orch = TrainingOrchestrator("http://localhost:50051")
+        config = orch.register_worker("worker-0", "localhost", 50052, gpu_count=8)
+        print(f"Registered as rank {config.rank} of {config.world_size}")
+
+        # Get data shard for this worker
+        shard = orch.get_shard("imagenet", epoch=0)
+
+        # Synchronize with other workers
+        orch.barrier("epoch-0", step=100)
+
+        # Or, from a coroutine
+        await orch.barrier_async("epoch-0", step=100)
+    """
+    def __init__(
+        self,
+        coordinator_url: str,
+        max_retries: int = 3,
+        retry_initial_delay: float = 0.1,
+        retry_max_delay: float = 10.0,
+    ) -> None:
+        """Create a new training orchestrator
+
+        Calls that are safe to repeat (shard and epoch queries, barriers and
+        recovery) are retried with exponential backoff while the coordinator
+        is unavailable, e.g. during a restart. Other calls, heartbeats
+        included, fail at once; every call reconnects after a lost connection.
+
+        Args:
+            coordinator_url: URL of the coordinator gRPC server (e.g., "http://localhost:50051")
+            max_retries: Retries of an idempotent call, 0 to disable (default: 3)
+            retry_initial_delay: Seconds before the first retry, doubling
+                after each one (default: 0.1)
+            retry_max_delay: Longest delay between retries in seconds
+                (default: 10)
+        """
+        ...
+    @staticmethod
+    def local(state_dir: Optional[Union[str, os.PathLike[str]]] = None) -> TrainingOrchestrator:
+        """Create an orchestrator with its own coordinator in this process
+
+        For single-node training and tests without a coordinator server. The
+        coordinator runs on the shared async runtime and listens on a free
+        loopback port, so shard assignments (including shuffling for a given
+        seed), barriers, epochs and recovery behave exactly as against a
+        server. Other orchestrators in the process can share it through
+        `coordinator_url`. It stops when this orchestrator is closed.
+
+        Args:
+            state_dir: Directory for the coordinator's checkpoints, and the
+                cluster state saved when it stops. By default a temporary
+                directory is used and removed on close.
+
+        Example:
+            with TrainingOrchestrator.local() as orch:
+                orch.register_worker("worker-0", "localhost", 50052)
+                orch.register_dataset("train", "/data/train", 10000, 1000)
+                shard = orch.get_shard("train", epoch=0)
+        """
+        ...
+    @property
+    def coordinator_url(self) -> str:
+        """URL of the coordinator, e.g. of the one started by `local`"""
+        ...
+    def connect(self) -> None:
+        """Connect to the coordinator server
+
+        This is called automatically by other methods if not already connected.
+        """
+        ...
+    def register_worker(
+        self,
+        worker_id: str,
+        hostname: str,
+        port: int,
+        gpu_count: int = 0,
+        memory_bytes: int = 0,
+        metadata: Optional[Dict[str, str]] = None,
+    ) -> WorkerConfig:
+        """Register this worker with the coordinator
+
+        Args:
+            worker_id: Unique identifier for this worker
+            hostname: Hostname or IP address
+            port: Port number for worker-to-worker communication
+            gpu_count: Number of GPUs available (default: 0)
+            memory_bytes: Available memory in bytes (default: 0)
+            metadata: Optional metadata dictionary
+
+        Returns:
+            WorkerConfig with assigned rank and world size
+        """
+        ...
+    def register_worker_async(
+        self,
+        worker_id: str,
+        hostname: str,
+        port: int,
+        gpu_count: int = 0,
+        memory_bytes: int = 0,
+        metadata: Optional[Dict[str, str]] = None,
+    ) -> Awaitable[WorkerConfig]:
+        """Awaitable form of `register_worker`"""
+        ...
+    def heartbeat(
+        self,
+        current_step: int = 0,
+        current_epoch: int = 0,
+        shard_progress: Optional[List[Tuple[str, int, int]]] = None,
+        loss: Optional[float] = None,
+    ) -> bool:
+        """Send a heartbeat to the coordinator
+
+        CPU, memory, disk, network and (with NVML) GPU usage of this host are
+        sampled and attached automatically. Commands in the response are
+        passed to the callbacks registered with `on_command`. If the
+        coordinator no longer knows the worker, e.g. after it restarted, the
+        worker registers again. Failed heartbeats are not retried, as that
+        could lose commands.
+
+        Args:
+            current_step: Current training step (default: 0)
+            current_epoch: Current training epoch (default: 0)
+            shard_progress: Optional list of (dataset_id, shard_id, samples_consumed)
+            loss: Optional training loss, used by adaptive checkpoint scheduling
+
+        Returns:
+            True if acknowledged
+        """
+        ...
+    def heartbeat_async(
+        self,
+        current_step: int = 0,
+        current_epoch: int = 0,
+        shard_progress: Optional[List[Tuple[str, int, int]]] = None,
+        loss: Optional[float] = None,
+    ) -> Awaitable[bool]:
+        """Awaitable form of `heartbeat`"""
+        ...
+    def start_heartbeat(self, interval: Optional[float] = None) -> None:
+        """Send heartbeats from a background task until `stop_heartbeat`
+
+        Each heartbeat carries the progress last set with `update_progress`.
+        Calling it again restarts the task with the new interval.
+
+        Args:
+            interval: Seconds between heartbeats (default: the interval
+                recommended by the coordinator at registration)
+
+        Raises:
+            RuntimeError: If the worker is not registered
+        """
+        ...
+    def stop_heartbeat(self) -> None:
+        """Stop the background heartbeats started by `start_heartbeat`"""
+        ...
+    @property
+    def heartbeat_running(self) -> bool:
+        """Whether background heartbeats are running"""
+        ...
+    def update_progress(
+        self,
+        step: Optional[int] = None,
+        epoch: Optional[int] = None,
+        loss: Optional[float] = None,
+        shard_progress: Optional[List[Tuple[str, int, int]]] = None,
+    ) -> None:
+        """Set the progress sent by background heartbeats
+
+        Only the given values change.
+
+        Args:
+            step: Current training step
+            epoch: Current training epoch
+            loss: Latest training loss
+            shard_progress: List of (dataset_id, shard_id, samples_consumed)
+        """
+        ...
+    def on_command(self, name: str, callback: Any) -> None:
+        """Call `callback` when a heartbeat response carries command `name`
+
+        Commands come from the coordinator, e.g. `checkpoint_now` when it
+        schedules a checkpoint or `pause_task` from an administrator. The
+        callback gets the command argument as a string, such as the step
+        for `checkpoint_now`, or None if it has none. Several callbacks can
+        be registered for one command.
+
+        Callbacks run on the thread sending the heartbeat, which is a
+        background thread after `start_heartbeat()`, so they should only
+        hand the command to the training loop, e.g. by setting a flag.
+
+        Args:
+            name: Command name, e.g. "checkpoint_now"
+            callback: Called with the command argument
+
+        Example:
+            checkpoint_at = []
+            orch.on_command("checkpoint_now", lambda step: checkpoint_at.append(int(step)))
+        """
+        ...
+    def off_command(self, name: str) -> None:
+        """Remove the callbacks registered for command `name`"""
+        ...
+    def watch_membership(self, callback: Any) -> None:
+        """Call `callback` with each change in cluster membership
+
+        Changes stream from the coordinator's `WatchWorkers` RPC: workers
+        joining, leaving, dying, changing state or getting a new rank. The
+        callback gets a `MembershipEvent`, whose `world_size` counts the
+        live workers, so elastic training can rebuild its process group when
+        the world size or its own rank changes. The current members come
+        first, reported as joined, and again after the watch reconnects.
+
+        Callbacks run on a background thread, so like `on_command` callbacks
+        they should hand the change to the training loop. Calling it again
+        replaces the callback.
+
+        Args:
+            callback: Called with each MembershipEvent
+
+        Example:
+            changes = queue.Queue()
+            orch.watch_membership(changes.put)
+        """
+        ...
+    def unwatch_membership(self) -> None:
+        """Stop the membership watch started by `watch_membership`"""
+        ...
+    def register_dataset(
+        self,
+        dataset_id: str,
+        path: str,
+        total_samples: int,
+        shard_size: int,
+        shuffle: bool = True,
+        seed: int = 42,
+    ) -> int:
+        """Register a dataset with the coordinator
+
+        Args:
+            dataset_id: Unique dataset identifier
+            path: Path to the dataset
+            total_samples: Total number of samples
+            shard_size: Samples per shard
+            shuffle: Whether to shuffle (default: True)
+            seed: Random seed (default: 42)
+        """
+        ...
+    def register_dataset_from_manifest(
+        self,
+        dataset_id: str,
+        paths: Union[str, List[str]],
+        shard_size: Optional[int] = None,
+        shuffle: bool = True,
+        seed: int = 42,
+        endpoint: Optional[str] = None,
+        region: Optional[str] = None,
+    ) -> DatasetManifest:
+        """Register a dataset from its files, counting their samples
+
+        Samples are counted from Parquet footers, from index files next to
+        the data (`train-0.tfrecord.idx`), or from the lines of JSON Lines
+        files, so no total needs working out by hand.
+
+        Args:
+            dataset_id: Unique dataset identifier
+            paths: A glob, directory or file (local or s3://bucket/...), or a list of files
+            shard_size: Samples per shard (default: about one shard per file)
+            shuffle: Whether to shuffle (default: True)
+            seed: Random seed (default: 42)
+            endpoint: Custom S3 endpoint URL, for s3:// paths
+            region: S3 region, for s3:// paths
+
+        Returns:
+            DatasetManifest listing the files and their samples
+        """
+        ...
+    def get_shard(
+        self,
+        dataset_id: str,
+        epoch: int,
+        gpu_count: int = 0,
+        available_memory_bytes: int = 0,
+    ) -> CoordinatorShardInfo:
+        """Get shard assignment for this worker
+
+        Args:
+            dataset_id: Dataset identifier
+            epoch: Training epoch
+            gpu_count: GPUs on this worker, used to size the shard batch (default: 0)
+            available_memory_bytes: Memory available for shard buffers (default: 0)
+
+        Returns:
+            CoordinatorShardInfo with shard assignment details
+        """
+        ...
+    def get_shard_async(
+        self,
+        dataset_id: str,
+        epoch: int,
+        gpu_count: int = 0,
+        available_memory_bytes: int = 0,
+    ) -> Awaitable[CoordinatorShardInfo]:
+        """Awaitable form of `get_shard`"""
+        ...
+    def get_shards(
+        self,
+        dataset_id: str,
+        epoch: int,
+        max_shards: int = 0,
+    ) -> List[CoordinatorShardInfo]:
+        """Get every shard assigned to this worker
+
+        `get_shard` returns a batch sized for the worker's resources; this
+        returns the complete assignment, one entry per shard.
+
+        Args:
+            dataset_id: Dataset identifier
+            epoch: Training epoch
+            max_shards: Return at most this many shards, 0 for all (default: 0)
+
+        Returns:
+            List of CoordinatorShardInfo, in assignment order
+        """
+        ...
+    def get_shards_async(
+        self,
+        dataset_id: str,
+        epoch: int,
+        max_shards: int = 0,
+    ) -> Awaitable[List[CoordinatorShardInfo]]:
+        """Awaitable form of `get_shards`"""
+        ...
+    def barrier(
+        self,
+        barrier_id: str,
+        step: int,
+        timeout: Optional[float] = None,
+    ) -> BarrierResult:
+        """Wait at a synchronization barrier
+
+        Args:
+            barrier_id: Unique barrier identifier
+            step: Training step for this barrier, used as the barrier generation
+            timeout: Seconds to wait for the other workers before raising
+                BarrierTimeout (default: wait as long as the coordinator does)
+
+        Returns:
+            BarrierResult with synchronization details
+
+        Raises:
+            BarrierTimeout: If the barrier isn't released within `timeout`.
+                This worker still counts as arrived, so waiting again or
+                polling with `try_barrier` continues where it left off.
+            RuntimeError: If the barrier fails or an administrator aborts it
+        """
+        ...
+    def barrier_async(
+        self,
+        barrier_id: str,
+        step: int,
+        timeout: Optional[float] = None,
+    ) -> Awaitable[BarrierResult]:
+        """Awaitable form of `barrier`"""
+        ...
+    def try_barrier(self, barrier_id: str, step: int) -> BarrierResult:
+        """Arrive at a barrier without waiting for it
+
+        Counts this worker as arrived and returns at once. While other
+        workers are still expected, `released` is False and `participants`
+        of `expected` workers have arrived; call again to poll, or `barrier`
+        to wait. Lets training loops fall back to their own logic instead of
+        blocking on a straggler.
+
+        Args:
+            barrier_id: Unique barrier identifier
+            step: Training step for this barrier, used as the barrier generation
+
+        Returns:
+            BarrierResult with the arrivals so far
+
+        Example:
+            while not orch.try_barrier("epoch-3", step=3000).released:
+                prefetch_next_epoch()
+        """
+        ...
+    def current_epoch(self, dataset_id: str) -> int:
+        """Current epoch of a dataset, as tracked by the coordinator
+
+        Args:
+            dataset_id: Dataset identifier
+
+        Raises:
+            RuntimeError: If the dataset is not registered
+        """
+        ...
+    def advance_epoch(self, dataset_id: str, epoch: Optional[int] = None) -> EpochStatus:
+        """Report that this worker finished an epoch of a dataset
+
+        The coordinator moves the dataset to the next epoch once every
+        healthy worker has reported, so all workers agree on the epoch
+        without tracking it locally. Reports for an epoch that already
+        advanced are acknowledged without effect.
+
+        Args:
+            dataset_id: Dataset identifier
+            epoch: Epoch finished (default: the dataset's current epoch)
+
+        Returns:
+            EpochStatus with the quorum progress and resulting epoch
+
+        Example:
+            status = orch.advance_epoch("imagenet")
+            orch.barrier(f"epoch-{status.current_epoch}", step)
+            epoch = orch.current_epoch("imagenet")
+        """
+        ...
+    def advance_epoch_async(
+        self,
+        dataset_id: str,
+        epoch: Optional[int] = None,
+    ) -> Awaitable[EpochStatus]:
+        """Awaitable form of `advance_epoch`"""
+        ...
+    def get_latest_checkpoint(self, job_id: str = "") -> Optional[RecoveryInfo]:
+        """Latest checkpoint to recover from
+
+        Args:
+            job_id: Training job identifier (default: "")
+
+        Returns:
+            RecoveryInfo with the checkpoint, resume step and epoch and this
+            worker's shard assignments, or None if no checkpoint exists
+
+        Example:
+            info = orch.get_latest_checkpoint()
+            if info is not None:
+                model.load_state_dict(load(info.checkpoint_id))
+                start_step = info.resume_step
+        """
+        ...
+    def get_latest_checkpoint_async(
+        self,
+        job_id: str = "",
+    ) -> Awaitable[Optional[RecoveryInfo]]:
+        """Awaitable form of `get_latest_checkpoint`"""
+        ...
+    def list_workers(self) -> List[WorkerSnapshot]:
+        """Workers registered with the coordinator, in rank order
+
+        Returns:
+            List of WorkerSnapshot
+        """
+        ...
+    def cluster_metrics(self) -> ClusterMetrics:
+        """Aggregate metrics of the cluster, as served by `/api/metrics`
+
+        Returns:
+            ClusterMetrics
+        """
+        ...
+    def list_checkpoints(self, limit: Optional[int] = None) -> List[CoordinatorCheckpointInfo]:
+        """Checkpoints recorded by the coordinator, most recent first
+
+        Args:
+            limit: Most checkpoints to return, all of them by default
+
+        Returns:
+            List of CoordinatorCheckpointInfo
+        """
+        ...
+    def deregister(self) -> None:
+        """Deregister this worker from the coordinator
+
+        Stops background heartbeats first.
+        """
+        ...
+    def close(self) -> None:
+        """Stop heartbeats and the membership watch, deregister if registered,
+        and disconnect
+
+        The orchestrator can't be used afterwards. Closing twice is a no-op.
+        In a forked child, the worker registered by the parent stays
+        registered and a coordinator started by `local` keeps running.
+
+        Orchestrators still registered are closed when the interpreter
+        exits or they are garbage collected, giving up on deregistering
+        after a few seconds.
+        """
+        ...
+    def __enter__(self) -> TrainingOrchestrator:
+        """Connect to the coordinator"""
+        ...
+    def __exit__(
+        self,
+        exc_type: Optional[Any],
+        _exc_value: Optional[Any],
+        _traceback: Optional[Any],
+    ) -> bool:
+        """Close the orchestrator, see `close`
+
+        If the block raised, a failure to deregister is logged instead of
+        hiding the original exception.
+        """
+        ...
+    def __repr__(self) -> str: ...
+
+class MetricsReporter:
+    """Batched training metrics and heartbeats, sent from a background thread
+
+    Logging only appends to a buffer. Every `interval` the buffered metrics
+    are sent to the coordinator with a heartbeat carrying the worker's
+    progress and resource usage, without taking the GIL. Metrics that fail
+    to send are retried with the next report; when more than `capacity` are
+    waiting, the oldest are dropped.
+
+    Logging a step or a "loss" also sets the progress sent by
+    `TrainingOrchestrator.start_heartbeat`, so the reporter can replace it.
+
+    Example:
+        with MetricsReporter(orch, interval=5.0) as metrics:
+            for step, batch in enumerate(loader):
+                loss = train_step(batch)
+                metrics.log({"loss": loss, "samples_per_sec": rate}, step=step)
+    """
+    def __init__(
+        self,
+        orchestrator: TrainingOrchestrator,
+        interval: Optional[float] = None,
+        capacity: int = 10_000,
+    ) -> None:
+        """Start reporting for a registered worker
+
+        Args:
+            orchestrator: Orchestrator the worker registered with
+            interval: Seconds between reports (default: the heartbeat interval
+                recommended by the coordinator at registration)
+            capacity: Metrics buffered before the oldest are dropped (default: 10000)
+
+        Raises:
+            RuntimeError: If the worker is not registered
+        """
+        ...
+    def log(self, metrics: Dict[str, float], step: Optional[int] = None) -> None:
+        """Buffer metrics for one step
+
+        Args:
+            metrics: Values by name, e.g. {"loss": 0.42, "samples_per_sec": 1800.0}
+            step: Training step (default: the latest step logged)
+        """
+        ...
+    def log_batch(self, records: List[Tuple[int, Dict[str, float]]]) -> None:
+        """Buffer metrics for several steps at once
+
+        Args:
+            records: List of (step, {name: value})
+        """
+        ...
+    def flush(self) -> None:
+        """Send the buffered metrics now
+
+        Raises:
+            ConnectionError: If the coordinator can't be reached; the metrics
+                stay buffered
+        """
+        ...
+    def close(self) -> None:
+        """Stop reporting, sending the buffered metrics first
+
+        Safe to call more than once.
+        """
+        ...
+    @property
+    def pending(self) -> int:
+        """Metrics waiting to be sent"""
+        ...
+    @property
+    def sent(self) -> int:
+        """Metrics sent to the coordinator"""
+        ...
+    @property
+    def dropped(self) -> int:
+        """Metrics dropped because the buffer was full"""
+        ...
+    @property
+    def running(self) -> bool:
+        """Whether background reports are running"""
+        ...
+    def __enter__(self) -> MetricsReporter: ...
+    def __exit__(
+        self,
+        exc_type: Optional[Any],
+        _exc_value: Optional[Any],
+        _traceback: Optional[Any],
+    ) -> bool:
+        """Close the reporter, see `close`
+
+        If the block raised, a failure to send is logged instead of hiding
+        the original exception.
+        """
+        ...
+    def __repr__(self) -> str: ...
@@ -0,0 +1,186 @@
+"""
+PyTorch data loading on top of coordinator shard assignments
+
+`StrataIterableDataset` asks the coordinator which shards this worker owns
+for an epoch and yields them to a `torch.utils.data.DataLoader`, splitting
+them across the DataLoader's worker processes.
+
+Example:
+    >>> from torch.utils.data import DataLoader
+    >>> from dtruntime import TrainingOrchestrator
+    >>> from dtruntime.data import StrataIterableDataset
+    >>>
+    >>> orch = TrainingOrchestrator("http://localhost:50051")
+    >>> orch.register_worker("worker-0", "localhost", 50052, gpu_count=8)
+    >>> dataset = StrataIterableDataset(orch, "imagenet", reader=read_samples)
+    >>> loader = DataLoader(dataset, batch_size=256, num_workers=4)
+    >>> for epoch in range(10):
+    ...     dataset.set_epoch(epoch)
+    ...     for batch in loader:
+    ...         train_step(batch)
+"""
+
+from typing import Any, Callable, Iterable, Iterator, List, NamedTuple, Optional
+
+try:
+    from torch.utils.data import IterableDataset, get_worker_info
+except ImportError:  # pragma: no cover - torch is an optional dependency
+    IterableDataset = object  # type: ignore[assignment,misc]
+
+    def get_worker_info():  # type: ignore[no-redef]
+        return None
+
+
+class SampleRange(NamedTuple):
+    """A range of sample indices from one shard"""
+
+    dataset_id: str
+    shard_id: int
+    epoch: int
+    #: First sample index (inclusive)
+    start: int
+    #: Last sample index (exclusive)
+    end: int
+    #: Files backing the shard, if the dataset has any
+    file_paths: List[str]
+
+    @property
+    def num_samples(self) -> int:
+        """Number of samples in this range"""
+        return self.end - self.start
+
+    def indices(self) -> range:
+        """The sample indices in this range"""
+        return range(self.start, self.end)
+
+
+#: Turns a sample range into records, e.g. by reading rows from `file_paths`
+Reader = Callable[[SampleRange], Iterable[Any]]
+
+
+def split_ranges(ranges: List[SampleRange], worker_id: int, num_workers: int) -> List[SampleRange]:
+    """The part of `ranges` read by DataLoader worker `worker_id`
+
+    Whole shards are dealt out round-robin. When there are fewer shards than
+    workers, each shard is cut into `num_workers` contiguous pieces instead so
+    no worker sits idle.
+    """
+    if num_workers <= 1:
+        return list(ranges)
+    if len(ranges) >= num_workers:
+        return ranges[worker_id::num_workers]
+
+    pieces = []
+    for r in ranges:
+        size = r.num_samples
+        start = r.start + size * worker_id // num_workers
+        end = r.start + size * (worker_id + 1) // num_workers
+        if start < end:
+            pieces.append(r._replace(start=start, end=end))
+    return pieces
+
+
+class StrataIterableDataset(IterableDataset):
+    """Iterable dataset over the shards the coordinator assigns to this worker
+
+    Args:
+        orchestrator: A registered `TrainingOrchestrator`
+        dataset_id: Dataset registered with the coordinator
+        reader: Turns each `SampleRange` into records; without one the
+            ranges themselves are yielded
+        epoch: Epoch to start at (default: 0)
+        gpu_count: GPUs on this worker, used to size the shard batch
+        available_memory_bytes: Memory available for shard buffers
+
+    Shards are fetched in the main process by `set_epoch`, or on first
+    iteration if it wasn't called, so DataLoader workers never talk to the
+    coordinator themselves. Each worker then reads its part of them, see
+    `split_ranges`.
+    """
+
+    def __init__(
+        self,
+        orchestrator: Any,
+        dataset_id: str,
+        reader: Optional[Reader] = None,
+        epoch: int = 0,
+        gpu_count: int = 0,
+        available_memory_bytes: int = 0,
+    ) -> None:
+        super().__init__()
+        self.orchestrator = orchestrator
+        self.dataset_id = dataset_id
+        self.reader = reader
+        self.gpu_count = gpu_count
+        self.available_memory_bytes = available_memory_bytes
+        self.epoch = epoch
+        self._ranges: Optional[List[SampleRange]] = None
+        # Set by `worker_init_fn`, otherwise read from `get_worker_info()`
+        self._worker: Optional[tuple] = None
+
+    def set_epoch(self, epoch: int) -> None:
+        """Fetch the shard assignment for `epoch`
+
+        Call before iterating a DataLoader over this dataset each epoch.
+        """
+        self.epoch = epoch
+        self._ranges = self.fetch_ranges(epoch)
+
+    def fetch_ranges(self, epoch: int) -> List[SampleRange]:
+        """Ask the coordinator for this worker's shards in `epoch`"""
+        shard = self.orchestrator.get_shard(
+            self.dataset_id,
+            epoch,
+            gpu_count=self.gpu_count,
+            available_memory_bytes=self.available_memory_bytes,
+        )
+        # Coordinators that hand out one shard at a time leave `shards` empty
+        shards = shard.shards or [(shard.shard_id, shard.start_index, shard.end_index)]
+        return [
+            SampleRange(
+                dataset_id=shard.dataset_id,
+                shard_id=shard_id,
+                epoch=shard.epoch,
+                start=start,
+                end=end,
+                file_paths=list(shard.file_paths),
+            )
+            for shard_id, start, end in shards
+        ]
+
+    @staticmethod
+    def worker_init_fn(worker_id: int) -> None:
+        """`worker_init_fn` for a DataLoader over this dataset
+
+        Pins the worker's copy of the dataset, or of each dataset in a
+        `ChainDataset`, to its part of the shards. Without it the split is
+        found with `get_worker_info()` when iteration starts.
+        """
+        info = get_worker_info()
+        if info is None:
+            return
+        datasets = getattr(info.dataset, "datasets", [info.dataset])
+        for dataset in datasets:
+            if isinstance(dataset, StrataIterableDataset):
+                dataset._worker = (worker_id, info.num_workers)
+
+    def ranges(self) -> List[SampleRange]:
+        """The sample ranges this process reads in the current epoch"""
+        if self._ranges is None:
+            self._ranges = self.fetch_ranges(self.epoch)
+
+        worker = self._worker
+        if worker is None:
+            info = get_worker_info()
+            worker = (info.id, info.num_workers) if info is not None else (0, 1)
+        return split_ranges(self._ranges, *worker)
+
+    def __iter__(self) -> Iterator[Any]:
+        for sample_range in self.ranges():
+            if self.reader is None:
+                yield sample_range
+            else:
+                yield from self.reader(sample_range)
+
+    def __repr__(self) -> str:
+        return f"StrataIterableDataset(dataset_id='{self.dataset_id}', epoch={self.epoch})"
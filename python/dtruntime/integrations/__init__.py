@@ -0,0 +1,189 @@
+"""
+Trainer integrations
+
+Callbacks that connect a training framework's trainer to the coordinator.
+Added to a trainer, they register the worker when training starts, keep
+heartbeats going with the trainer's step, epoch and loss, wait for the other
+workers at the end of each epoch and upload the checkpoints the trainer saves.
+
+- `dtruntime.integrations.lightning.StrataCallback` for PyTorch Lightning
+- `dtruntime.integrations.huggingface.StrataCallback` for the Transformers
+  `Trainer`
+
+Both share `TrainingHooks`, which other frameworks can call from their own
+callbacks.
+
+Example:
+    >>> from lightning.pytorch import Trainer
+    >>> from dtruntime.integrations.lightning import StrataCallback
+    >>>
+    >>> trainer = Trainer(callbacks=[StrataCallback("http://coordinator:50051")])
+"""
+
+import io
+import os
+import socket
+import tarfile
+from typing import Any, Dict, Optional
+
+from .._core import CheckpointManager, TrainingOrchestrator
+
+#: Used when neither an orchestrator nor `COORDINATOR_URL` is given
+DEFAULT_COORDINATOR_URL = "http://localhost:50051"
+
+#: Barrier workers wait at after each epoch, with the epoch as its step
+EPOCH_END_BARRIER = "strata-epoch-end"
+
+
+def local_gpu_count() -> int:
+    """GPUs visible to this process, 0 without torch or CUDA"""
+    try:
+        import torch
+    except ImportError:
+        return 0
+    return torch.cuda.device_count() if torch.cuda.is_available() else 0
+
+
+class TrainingHooks:
+    """Orchestrator calls made at trainer events
+
+    Without an `orchestrator`, one is created when training starts rather
+    than here, so callbacks configured with just a URL can be pickled, e.g.
+    by spawn-based launchers.
+
+    Args:
+        coordinator_url: Coordinator to connect to (default: `COORDINATOR_URL`
+            or http://localhost:50051). Ignored when `orchestrator` is given.
+        orchestrator: An existing `TrainingOrchestrator` to use instead
+        worker_id: Worker to register as (default: `WORKER_ID`, or the host
+            name followed by the trainer's global rank)
+        register: Register the worker when training starts and deregister it
+            when training ends (default: only for an orchestrator created
+            by the hooks)
+        checkpoint_manager: Uploads the checkpoints the trainer saves; none
+            are uploaded without one
+        barrier_on_epoch_end: Wait for all workers at the end of each epoch
+        barrier_timeout: Seconds to wait at the barrier before raising
+            `BarrierTimeout` (default: no limit)
+        heartbeat_interval: Seconds between heartbeats (default: the
+            coordinator's recommendation)
+    """
+
+    def __init__(
+        self,
+        coordinator_url: Optional[str] = None,
+        orchestrator: Optional[TrainingOrchestrator] = None,
+        worker_id: Optional[str] = None,
+        register: Optional[bool] = None,
+        checkpoint_manager: Optional[CheckpointManager] = None,
+        barrier_on_epoch_end: bool = True,
+        barrier_timeout: Optional[float] = None,
+        heartbeat_interval: Optional[float] = None,
+    ) -> None:
+        self.coordinator_url = coordinator_url
+        self.orchestrator = orchestrator
+        self.worker_id = worker_id
+        self.register = orchestrator is None if register is None else register
+        self.checkpoint_manager = checkpoint_manager
+        self.barrier_on_epoch_end = barrier_on_epoch_end
+        self.barrier_timeout = barrier_timeout
+        self.heartbeat_interval = heartbeat_interval
+        #: Registration from the last `start`, if it registered
+        self.worker_config = None
+        #: ID of the last checkpoint uploaded
+        self.last_checkpoint_id: Optional[str] = None
+        self._owns_orchestrator = orchestrator is None
+        self._started = False
+
+    def start(self, rank: int = 0) -> None:
+        """Connect, register if configured, and start heartbeats"""
+        if self._started:
+            return
+        if self.orchestrator is None:
+            url = self.coordinator_url or os.environ.get("COORDINATOR_URL", DEFAULT_COORDINATOR_URL)
+            self.orchestrator = TrainingOrchestrator(url)
+            self._owns_orchestrator = True
+
+        if self.register:
+            worker_id = (
+                self.worker_id
+                or os.environ.get("WORKER_ID")
+                or f"{socket.gethostname()}-{rank}"
+            )
+            self.worker_config = self.orchestrator.register_worker(
+                worker_id, socket.gethostname(), 0, gpu_count=local_gpu_count()
+            )
+        self.orchestrator.start_heartbeat(self.heartbeat_interval)
+        self._started = True
+
+    def progress(self, step: int, epoch: int, loss: Optional[float] = None) -> None:
+        """Report the trainer's position with the next heartbeat"""
+        if self._started:
+            self.orchestrator.update_progress(step=step, epoch=epoch, loss=loss)
+
+    def epoch_end(self, epoch: int, step: int) -> None:
+        """Report progress and wait for the other workers to finish `epoch`"""
+        if not self._started:
+            return
+        self.progress(step, epoch)
+        if self.barrier_on_epoch_end:
+            self.orchestrator.barrier(EPOCH_END_BARRIER, step=epoch, timeout=self.barrier_timeout)
+
+    def save_state(
+        self, state: Any, step: int, epoch: int, metadata: Dict[str, str]
+    ) -> Optional[str]:
+        """Upload a state dict with `save_torch`, if there's a checkpoint manager"""
+        if self.checkpoint_manager is None:
+            return None
+        self.last_checkpoint_id = self.checkpoint_manager.save_torch(state, step, epoch, metadata)
+        return self.last_checkpoint_id
+
+    def save_directory(
+        self, path: str, step: int, epoch: int, metadata: Dict[str, str]
+    ) -> Optional[str]:
+        """Upload a checkpoint directory as a tar archive, see `restore_directory`"""
+        if self.checkpoint_manager is None:
+            return None
+        archive = io.BytesIO()
+        with tarfile.open(fileobj=archive, mode="w") as tar:
+            tar.add(path, arcname=os.path.basename(os.path.normpath(path)))
+        metadata = {**metadata, "format": "tar"}
+        self.last_checkpoint_id = self.checkpoint_manager.save(
+            archive.getbuffer(), step, epoch, metadata
+        )
+        return self.last_checkpoint_id
+
+    def end(self) -> None:
+        """Flush uploads, stop heartbeats and leave the cluster"""
+        try:
+            if self.checkpoint_manager is not None:
+                self.checkpoint_manager.wait_pending()
+        finally:
+            if self._started:
+                self._started = False
+                if self._owns_orchestrator:
+                    self.orchestrator.close()
+                    self.orchestrator = None
+                else:
+                    self.orchestrator.stop_heartbeat()
+                    if self.register:
+                        self.orchestrator.deregister()
+
+
+def restore_directory(
+    checkpoint_manager: CheckpointManager, checkpoint_id: str, parent: str
+) -> str:
+    """Unpack a checkpoint uploaded by `save_directory` under `parent`
+
+    Returns:
+        Path of the restored directory
+    """
+    data = checkpoint_manager.load(checkpoint_id)
+    with tarfile.open(fileobj=io.BytesIO(data), mode="r") as tar:
+        name = tar.getnames()[0].split("/")[0]
+        if hasattr(tarfile, "data_filter"):
+            # Refuse absolute paths and links out of `parent`
+            tar.extractall(parent, filter="data")
+        else:  # pragma: no cover - Python without extraction filters
+            tar.extractall(parent)
+    return os.path.join(parent, name)
@@ -0,0 +1,91 @@
+"""
+Hugging Face Transformers integration
+
+Example:
+    >>> from transformers import Trainer
+    >>> from dtruntime import CheckpointManager
+    >>> from dtruntime.integrations.huggingface import StrataCallback
+    >>>
+    >>> ckpt = CheckpointManager("/checkpoints")
+    >>> trainer = Trainer(model, args, callbacks=[StrataCallback(checkpoint_manager=ckpt)])
+    >>> trainer.train()
+"""
+
+import math
+import os
+from typing import Any, Dict, Optional
+
+from .._core import CheckpointManager
+from . import TrainingHooks, restore_directory
+
+try:
+    from transformers import TrainerCallback
+except ImportError:  # pragma: no cover - transformers is an optional dependency
+    TrainerCallback = object  # type: ignore[assignment,misc]
+
+#: Prefix of the checkpoint directories `Trainer` saves under `output_dir`
+CHECKPOINT_PREFIX = "checkpoint"
+
+
+class StrataCallback(TrainerCallback):
+    """Transformers callback reporting training to the coordinator
+
+    Registers the worker and starts heartbeats when training starts, reports
+    the global step, epoch and logged loss, waits for all workers at the end
+    of each epoch, and uploads each checkpoint directory `Trainer` saves from
+    the main process as a tar archive. Takes the arguments of
+    `dtruntime.integrations.TrainingHooks`.
+
+    To resume, unpack an uploaded checkpoint with `restore` and pass the
+    directory to `trainer.train(resume_from_checkpoint=...)`.
+    """
+
+    def __init__(self, coordinator_url: Optional[str] = None, **kwargs: Any) -> None:
+        self.hooks = TrainingHooks(coordinator_url, **kwargs)
+
+    @property
+    def orchestrator(self) -> Any:
+        """The `TrainingOrchestrator`, once training has started"""
+        return self.hooks.orchestrator
+
+    @staticmethod
+    def restore(checkpoint_manager: CheckpointManager, checkpoint_id: str, output_dir: str) -> str:
+        """Unpack an uploaded checkpoint into `output_dir`, returning its path"""
+        return restore_directory(checkpoint_manager, checkpoint_id, output_dir)
+
+    def on_train_begin(self, args: Any, state: Any, control: Any, **kwargs: Any) -> None:
+        self.hooks.start(args.process_index)
+
+    def on_step_end(self, args: Any, state: Any, control: Any, **kwargs: Any) -> None:
+        self.hooks.progress(state.global_step, int(state.epoch or 0))
+
+    def on_log(
+        self,
+        args: Any,
+        state: Any,
+        control: Any,
+        logs: Optional[Dict[str, float]] = None,
+        **kwargs: Any,
+    ) -> None:
+        if logs and "loss" in logs:
+            self.hooks.progress(state.global_step, int(state.epoch or 0), logs["loss"])
+
+    def on_epoch_end(self, args: Any, state: Any, control: Any, **kwargs: Any) -> None:
+        # `state.epoch` counts completed epochs by now, e.g. 1.0 after the first
+        epoch = max(math.ceil(state.epoch or 0) - 1, 0)
+        self.hooks.epoch_end(epoch, state.global_step)
+
+    def on_save(self, args: Any, state: Any, control: Any, **kwargs: Any) -> None:
+        if not state.is_world_process_zero:
+            return
+        path = os.path.join(args.output_dir, f"{CHECKPOINT_PREFIX}-{state.global_step}")
+        if os.path.isdir(path):
+            self.hooks.save_directory(
+                path,
+                state.global_step,
+                int(state.epoch or 0),
+                {"trainer": "transformers"},
+            )
+
+    def on_train_end(self, args: Any, state: Any, control: Any, **kwargs: Any) -> None:
+        self.hooks.end()
@@ -0,0 +1,87 @@
+"""
+PyTorch Lightning integration
+
+Example:
+    >>> from lightning.pytorch import Trainer
+    >>> from dtruntime import CheckpointManager
+    >>> from dtruntime.integrations.lightning import StrataCallback
+    >>>
+    >>> ckpt = CheckpointManager("/checkpoints")
+    >>> trainer = Trainer(callbacks=[StrataCallback(checkpoint_manager=ckpt)])
+    >>> trainer.fit(model)
+"""
+
+from typing import Any, Dict, Optional
+
+from . import TrainingHooks
+
+try:
+    from lightning.pytorch.callbacks import Callback
+except ImportError:  # pragma: no cover - lightning is an optional dependency
+    try:
+        from pytorch_lightning.callbacks import Callback
+    except ImportError:
+        Callback = object  # type: ignore[assignment,misc]
+
+
+def batch_loss(outputs: Any) -> Optional[float]:
+    """Loss from what `training_step` returned, if it has one"""
+    loss = outputs.get("loss") if isinstance(outputs, dict) else outputs
+    if loss is None:
+        return None
+    try:
+        return float(loss)
+    except (TypeError, ValueError):
+        return None
+
+
+class StrataCallback(Callback):
+    """Lightning callback reporting training to the coordinator
+
+    Registers the worker and starts heartbeats when training starts, reports
+    the global step, epoch and loss, waits for all workers at the end of each
+    training epoch, and uploads the checkpoints Lightning saves on global
+    rank 0 with `CheckpointManager.save_torch`. Takes the arguments of
+    `dtruntime.integrations.TrainingHooks`.
+
+    The loss is read every `log_every_n_steps` batches, as reading it waits
+    for the GPU. Uploaded checkpoints hold the whole Lightning checkpoint,
+    so load them with `load_torch(checkpoint_id, weights_only=False)`.
+    """
+
+    def __init__(self, coordinator_url: Optional[str] = None, **kwargs: Any) -> None:
+        super().__init__()
+        self.hooks = TrainingHooks(coordinator_url, **kwargs)
+
+    @property
+    def orchestrator(self) -> Any:
+        """The `TrainingOrchestrator`, once training has started"""
+        return self.hooks.orchestrator
+
+    def on_train_start(self, trainer: Any, pl_module: Any) -> None:
+        self.hooks.start(trainer.global_rank)
+
+    def on_train_batch_end(
+        self, trainer: Any, pl_module: Any, outputs: Any, batch: Any, batch_idx: int
+    ) -> None:
+        log_every = max(getattr(trainer, "log_every_n_steps", 1), 1)
+        loss = batch_loss(outputs) if trainer.global_step % log_every == 0 else None
+        self.hooks.progress(trainer.global_step, trainer.current_epoch, loss)
+
+    def on_train_epoch_end(self, trainer: Any, pl_module: Any) -> None:
+        self.hooks.epoch_end(trainer.current_epoch, trainer.global_step)
+
+    def on_save_checkpoint(self, trainer: Any, pl_module: Any, checkpoint: Dict[str, Any]) -> None:
+        if trainer.is_global_zero:
+            self.hooks.save_state(
+                checkpoint,
+                trainer.global_step,
+                trainer.current_epoch,
+                {"trainer": "lightning"},
+            )
+
+    def on_train_end(self, trainer: Any, pl_module: Any) -> None:
+        self.hooks.end()
+
+    def on_exception(self, trainer: Any, pl_module: Any, exception: BaseException) -> None:
+        self.hooks.end()
@@ -0,0 +1,473 @@
+#!/usr/bin/env python3
+"""
+Generate type stubs for the dtruntime._core extension module
+
+Reads the PyO3 bindings in crates/python-bindings/src and writes
+python/dtruntime/_core.pyi, which maturin ships next to the extension so IDEs
+and mypy understand it. Parameter names and defaults come from the
+`#[pyo3(signature = ...)]` attributes, types from the Rust signatures and
+docstrings from the doc comments, so the stubs follow the bindings without
+being written by hand.
+
+Usage:
+    python scripts/generate_stubs.py          # rewrite the stubs
+    python scripts/generate_stubs.py --check  # fail if they are stale
+"""
+
+import argparse
+import re
+import sys
+from pathlib import Path
+from typing import Dict, List, NamedTuple, Optional, Set, Tuple
+
+ROOT = Path(__file__).resolve().parent.parent
+SOURCE_DIR = ROOT / "crates" / "python-bindings" / "src"
+STUB_PATH = ROOT / "python" / "dtruntime" / "_core.pyi"
+
+# Longest signature kept on one line
+MAX_LINE = 100
+
+HEADER = "# Generated by scripts/generate_stubs.py from the PyO3 bindings, do not edit\n"
+
+INT_TYPES = {"u8", "u16", "u32", "u64", "usize", "i8", "i16", "i32", "i64", "isize"}
+PLAIN_TYPES = {
+    "String": "str",
+    "str": "str",
+    "bool": "bool",
+    "f32": "float",
+    "f64": "float",
+    "()": "None",
+    "PathBuf": "Union[str, os.PathLike[str]]",
+    "PyObject": "Any",
+    "PyAny": "Any",
+    "PyBytes": "bytes",
+    "PyDict": "Dict[str, Any]",
+    "DatasetPaths": "Union[str, List[str]]",
+}
+# Containers whose Python form takes the same arguments
+GENERIC_TYPES = {"Option": "Optional", "Vec": "List", "HashMap": "Dict"}
+# Wrappers that are the wrapped type as far as Python can tell
+TRANSPARENT_TYPES = {"PyResult", "Bound", "Py", "PyRef", "PyRefMut"}
+
+
+class Param(NamedTuple):
+    name: str
+    type: str
+    default: Optional[str]
+
+
+class Method(NamedTuple):
+    name: str
+    # "function", "method", "new", "getter", "staticmethod" or "classmethod"
+    kind: str
+    params: List[Param]
+    returns: str
+    doc: List[str]
+
+
+class Class(NamedTuple):
+    name: str
+    doc: List[str]
+    fields: List[Tuple[str, str]]
+    methods: List[Method]
+
+
+def split_top_level(text: str, sep: str = ",") -> List[str]:
+    """Split on `sep` outside brackets and string literals"""
+    parts, depth, start, quote = [], 0, 0, False
+    for i, c in enumerate(text):
+        if c == '"' and (i == 0 or text[i - 1] != "\\"):
+            quote = not quote
+        elif quote:
+            continue
+        elif c in "<([":
+            depth += 1
+        elif c in ">)]" and not (c == ">" and text[i - 1] == "-"):
+            depth -= 1
+        elif c == sep and depth == 0:
+            parts.append(text[start:i].strip())
+            start = i + 1
+    parts.append(text[start:].strip())
+    return [p for p in parts if p]
+
+
+def skip_literal(text: str, i: int) -> int:
+    """Index just past a string, char or comment starting at `i`, or `i`"""
+    if text.startswith("//", i):
+        end = text.find("\n", i)
+        return len(text) if end == -1 else end
+    if text.startswith("/*", i):
+        return text.index("*/", i) + 2
+    raw = re.match(r'b?r(#*)"', text[i:])
+    if raw:
+        closing = '"' + raw.group(1)
+        return text.index(closing, i + raw.end()) + len(closing)
+    if text[i] == '"' or text.startswith('b"', i):
+        j = i + 1 if text[i] == '"' else i + 2
+        while text[j] != '"':
+            j += 2 if text[j] == "\\" else 1
+        return j + 1
+    char = re.match(r"b?'(\\.[^']*|[^\\'])'", text[i:])
+    if char:
+        return i + char.end()
+    return i
+
+
+def matching(text: str, i: int, open_: str, close: str) -> int:
+    """Index just past the bracket closing the one at `i`"""
+    depth = 0
+    while True:
+        j = skip_literal(text, i)
+        if j != i:
+            i = j
+            continue
+        if text[i] == open_:
+            depth += 1
+        elif text[i] == close and not text.startswith("->", i - 1):
+            depth -= 1
+            if depth == 0:
+                return i + 1
+        i += 1
+
+
+def python_type(rust: str, self_name: str, classes: Set[str]) -> str:
+    """Python type for a Rust type in a binding signature"""
+    rust = re.sub(r"'\w+\s*,?\s*", "", rust).strip()
+    rust = re.sub(r"^&\s*(mut\s+)?", "", rust).strip()
+    rust = rust.split("::")[-1] if "<" not in rust else rust
+
+    if rust.startswith("(") and rust != "()":
+        items = [python_type(t, self_name, classes) for t in split_top_level(rust[1:-1])]
+        return f"Tuple[{', '.join(items)}]"
+
+    generic = re.match(r"(?:[\w:]+::)?(\w+)<(.*)>$", rust, re.S)
+    if generic:
+        name, args = generic.group(1), split_top_level(generic.group(2))
+        if name in TRANSPARENT_TYPES:
+            return python_type(args[-1], self_name, classes)
+        if name in GENERIC_TYPES:
+            items = [python_type(t, self_name, classes) for t in args]
+            return f"{GENERIC_TYPES[name]}[{', '.join(items)}]"
+        raise ValueError(f"No Python type for {rust}")
+
+    if rust == "Self":
+        return self_name
+    if rust in INT_TYPES:
+        return "int"
+    if rust in PLAIN_TYPES:
+        return PLAIN_TYPES[rust]
+    if rust in classes:
+        return rust
+    raise ValueError(f"No Python type for {rust}")
+
+
+def python_default(rust: str) -> str:
+    return {"true": "True", "false": "False", "None": "None"}.get(rust, rust)
+
+
+class Item(NamedTuple):
+    """A Rust item with the doc comments and attributes before it"""
+
+    doc: List[str]
+    attrs: List[str]
+    text: str
+
+
+def items(body: str) -> List[Item]:
+    """Split Rust source into items, skipping their bodies"""
+    result, doc, attrs, i = [], [], [], 0
+    while i < len(body):
+        if body[i].isspace():
+            i += 1
+        elif body.startswith("///", i) or body.startswith("//!", i):
+            end = body.find("\n", i)
+            end = len(body) if end == -1 else end
+            line = body[i + 3 : end]
+            doc.append(line[1:] if line.startswith(" ") else line)
+            i = end
+        elif body.startswith("//", i) or body.startswith("/*", i):
+            i = skip_literal(body, i)
+        elif body.startswith("#[", i) or body.startswith("#![", i):
+            start = body.index("[", i)
+            end = matching(body, start, "[", "]")
+            attrs.append(body[start + 1 : end - 1].strip())
+            i = end
+        else:
+            # The item runs to its body or to a `;` outside brackets
+            j = i
+            while j < len(body) and body[j] not in "{;":
+                k = skip_literal(body, j)
+                if k != j:
+                    j = k
+                elif body[j] in "([<" and not body.startswith("<-", j):
+                    j = matching(body, j, body[j], {"(": ")", "[": "]", "<": ">"}[body[j]])
+                else:
+                    j += 1
+            if j < len(body) and body[j] == "{":
+                end = matching(body, j, "{", "}")
+            else:
+                end = j + 1
+            result.append(Item(doc, attrs, body[i:end]))
+            doc, attrs, i = [], [], end
+    return result
+
+
+def pyo3_arg(attrs: List[str], key: str) -> Optional[str]:
+    """Value of `key = ...` in a `#[pyo3(...)]` or `#[pyclass(...)]` attribute"""
+    for attr in attrs:
+        match = re.match(r"(pyo3|pyclass|getter)\s*\((.*)\)$", attr, re.S)
+        if not match:
+            continue
+        for part in split_top_level(match.group(2)):
+            name, _, value = part.partition("=")
+            if name.strip() == key:
+                return value.strip()
+    return None
+
+
+def parse_fn(item: Item, self_name: str, classes: Set[str]) -> Method:
+    header = item.text[: item.text.index("(")]
+    name = re.search(r"fn\s+(\w+)", header).group(1)
+    if name == "__getbuffer__":
+        # Rendered as `__buffer__`, its raw pointer arguments aren't Python's
+        return Method(name, "method", [], "memoryview", [])
+    open_paren = item.text.index("(")
+    close_paren = matching(item.text, open_paren, "(", ")")
+    rust_params = split_top_level(item.text[open_paren + 1 : close_paren - 1])
+    returns = re.match(r"\s*->\s*(.*?)\s*(where\b.*)?(\{.*)?$", item.text[close_paren:], re.S)
+    rust_returns = returns.group(1).strip() if returns else "()"
+
+    types: Dict[str, str] = {}
+    for param in rust_params:
+        if re.match(r"&?('\w+\s+)?(mut\s+)?self$", param):
+            continue
+        pname, _, rust = param.partition(":")
+        rust = rust.strip()
+        if re.match(r"Python\s*<", rust) or pname.strip() == "slf":
+            continue
+        types[pname.strip().removeprefix("mut ")] = python_type(rust, self_name, classes)
+
+    signature = pyo3_arg(item.attrs, "signature")
+    if signature is not None:
+        params = []
+        for part in split_top_level(signature.strip()[1:-1]):
+            pname, _, default = part.partition("=")
+            pname = pname.strip()
+            if pname in ("*", "/") or pname.startswith("*"):
+                params.append(Param(pname, "", None))
+            else:
+                default = python_default(default.strip()) if default else None
+                params.append(Param(pname, types[pname], default))
+    else:
+        params = [Param(pname, ptype, None) for pname, ptype in types.items()]
+
+    kind = "method"
+    for attr in item.attrs:
+        if attr == "new":
+            kind = "new"
+        elif attr in ("staticmethod", "classmethod"):
+            kind = attr
+        elif attr.startswith("getter"):
+            kind = "getter"
+            getter_name = re.match(r"getter\s*\((\w+)\)", attr)
+            if getter_name:
+                name = getter_name.group(1)
+    renamed = pyo3_arg(item.attrs, "name")
+    if renamed:
+        name = renamed.strip('"')
+
+    return Method(name, kind, params, python_type(rust_returns, self_name, classes), item.doc)
+
+
+def parse_class(item: Item, classes: Set[str]) -> Class:
+    name = re.search(r"struct\s+(\w+)", item.text).group(1)
+    python_name = (pyo3_arg(item.attrs, "name") or name).strip('"')
+    fields = []
+    body = item.text[item.text.index("{") + 1 : -1] if "{" in item.text else ""
+    for field in items_with_fields(body):
+        gettable = any(
+            re.match(r"pyo3\s*\(.*\bget\b", attr, re.S) for attr in field.attrs
+        )
+        if gettable:
+            fname, _, rust = field.text.rstrip(",").partition(":")
+            fname = fname.replace("pub", "").strip()
+            fields.append((fname, python_type(rust.strip(), python_name, classes)))
+    return Class(python_name, item.doc, fields, [])
+
+
+def items_with_fields(body: str) -> List[Item]:
+    """Split struct fields, which end in commas rather than semicolons"""
+    return [
+        item._replace(text=item.text.rstrip(";").strip())
+        for item in items(body.replace(",\n", ";\n"))
+    ]
+
+
+def parse_module(paths: List[Path]) -> Tuple[List[Class], List[Method], List[Tuple[str, str]]]:
+    """Classes, functions and exceptions defined by the bindings"""
+    sources = {path: items(path.read_text()) for path in paths}
+    names = {
+        re.search(r"struct\s+(\w+)", item.text).group(1)
+        for parsed in sources.values()
+        for item in parsed
+        if "pyclass" in " ".join(item.attrs) and "struct" in item.text
+    }
+
+    classes: Dict[str, Class] = {}
+    functions, exceptions = [], []
+    for parsed in sources.values():
+        for item in parsed:
+            if any(a.startswith("pyclass") for a in item.attrs):
+                cls = parse_class(item, names)
+                classes[cls.name] = cls
+    for parsed in sources.values():
+        for item in parsed:
+            if "pymethods" in item.attrs:
+                self_name = re.search(r"impl\s+(\w+)", item.text).group(1)
+                body = item.text[item.text.index("{") + 1 : -1]
+                for method in items(body):
+                    if re.search(r"\bfn\s", method.text):
+                        classes[self_name].methods.append(parse_fn(method, self_name, names))
+            elif "pyfunction" in item.attrs:
+                functions.append(parse_fn(item, "", names)._replace(kind="function"))
+            elif "create_exception!" in item.text:
+                args = split_top_level(item.text[item.text.index("(") + 1 : item.text.rindex(")")])
+                base = args[2].split("::")[-1].removeprefix("Py")
+                exceptions.append((args[1], base))
+    return list(classes.values()), functions, exceptions
+
+
+def module_sources() -> List[Path]:
+    """Rust modules in the order lib.rs declares them, lib.rs first"""
+    lib = SOURCE_DIR / "lib.rs"
+    modules = re.findall(r"^mod\s+(\w+);", lib.read_text(), re.M)
+    return [lib] + [SOURCE_DIR / f"{module}.rs" for module in modules]
+
+
+def exported(lib: str) -> Tuple[Set[str], Set[str]]:
+    """Functions and exceptions the module init adds to `_core`"""
+    functions = set(re.findall(r"wrap_pyfunction!\(\s*(?:\w+::)*(\w+)", lib))
+    exceptions = set(re.findall(r'm\.add\(\s*"(\w+)",\s*m\.py\(\)\.get_type_bound', lib))
+    return functions, exceptions
+
+
+def docstring(doc: List[str], indent: str) -> List[str]:
+    while doc and not doc[-1].strip():
+        doc = doc[:-1]
+    if not doc:
+        return []
+    if len(doc) == 1:
+        return [f'{indent}"""{doc[0]}"""']
+    lines = [f'{indent}"""{doc[0]}']
+    lines += [f"{indent}{line}".rstrip() for line in doc[1:]]
+    lines.append(f'{indent}"""')
+    return lines
+
+
+def render_params(params: List[Param], first: Optional[str]) -> List[str]:
+    rendered = [first] if first else []
+    for p in params:
+        if not p.type:
+            rendered.append(p.name)
+        elif p.default is None:
+            rendered.append(f"{p.name}: {p.type}")
+        else:
+            rendered.append(f"{p.name}: {p.type} = {p.default}")
+    return rendered
+
+
+def render_function(method: Method, indent: str, returns: str) -> List[str]:
+    first = {"method": "self", "new": "self", "getter": "self", "classmethod": "cls"}.get(method.kind)
+    name = "__init__" if method.kind == "new" else method.name
+    lines = []
+    if method.kind == "getter":
+        lines.append(f"{indent}@property")
+    elif method.kind in ("staticmethod", "classmethod"):
+        lines.append(f"{indent}@{method.kind}")
+
+    if name == "__getbuffer__":
+        # Python sees the buffer protocol as `__buffer__` (PEP 688)
+        name, params = "__buffer__", ["self", "flags: int", "/"]
+    else:
+        params = render_params(method.params, first)
+    signature = f"{indent}def {name}({', '.join(params)}) -> {returns}:"
+    if len(signature) + 4 > MAX_LINE:
+        wrapped = "".join(f"\n{indent}    {param}," for param in params)
+        signature = f"{indent}def {name}({wrapped}\n{indent}) -> {returns}:"
+
+    doc = docstring(method.doc, indent + "    ")
+    if doc:
+        lines.append(signature)
+        lines += doc
+        lines.append(f"{indent}    ...")
+    else:
+        lines.append(f"{signature} ...")
+    return lines
+
+
+def render_class(cls: Class) -> List[str]:
+    lines = [f"class {cls.name}:"]
+    lines += docstring(cls.doc, "    ")
+    for fname, ftype in cls.fields:
+        lines += ["    @property", f"    def {fname}(self) -> {ftype}: ..."]
+
+    returns = {m.name: m.returns for m in cls.methods}
+    for method in cls.methods:
+        result = "None" if method.kind == "new" else method.returns
+        # Awaitable variants return what their blocking form does
+        sibling = method.name.removesuffix("_async")
+        if method.name.endswith("_async") and method.returns == "Any" and sibling in returns:
+            result = f"Awaitable[{returns[sibling]}]"
+        lines += render_function(method, "    ", result)
+    if len(lines) == 1:
+        lines.append("    ...")
+    return lines
+
+
+def render() -> str:
+    sources = module_sources()
+    classes, functions, exceptions = parse_module(sources)
+    exported_functions, exported_exceptions = exported(sources[0].read_text())
+
+    blocks = [["__version__: str"]]
+    for name, base in exceptions:
+        if name in exported_exceptions:
+            blocks.append([f"class {name}({base}): ..."])
+    for function in functions:
+        if function.name in exported_functions:
+            blocks.append(render_function(function, "", function.returns))
+    blocks += [render_class(cls) for cls in classes]
+    body = "\n\n".join("\n".join(block) for block in blocks)
+
+    signatures = re.sub(r'""".*?"""', "", body, flags=re.S)
+    typing = [
+        name
+        for name in ("Any", "Awaitable", "Dict", "List", "Optional", "Tuple", "Union")
+        if re.search(rf"\b{name}\b", signatures)
+    ]
+    imports = []
+    if "os.PathLike" in body:
+        imports.append("import os")
+    if typing:
+        imports.append(f"from typing import {', '.join(typing)}")
+    return HEADER + "\n" + "\n".join(imports) + "\n\n" + body + "\n"
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    parser = argparse.ArgumentParser(description=__doc__.strip().splitlines()[0])
+    parser.add_argument("--check", action="store_true", help="fail if the stubs are stale")
+    parser.add_argument("--output", type=Path, default=STUB_PATH, help="stub file to write")
+    args = parser.parse_args(argv)
+
+    stubs = render()
+    if args.check:
+        if not args.output.exists() or args.output.read_text() != stubs:
+            print(f"{args.output} is stale, run scripts/generate_stubs.py", file=sys.stderr)
+            return 1
+        return 0
+    args.output.write_text(stubs)
+    return 0
+
+
+if __name__ == "__main__":
+    sys.exit(main())
@@ -97,7 +97,7 @@ echo "  export CHECKPOINT_BUCKET=$BUCKET_NAME"
 echo ""
 echo "Or update config/production.toml:"
 echo ""
-echo "  [storage.s3]"
+echo "  [storage.backend.S3]"
 echo "  bucket = \"$BUCKET_NAME\""
 echo "  region = \"$REGION\""
 echo ""
@@ -13,7 +13,7 @@ def find_free_port():
         return s.getsockname()[1]
 
 @pytest.fixture(scope="session")
-def coordinator_server():
+def coordinator_server(tmp_path_factory):
     """Starts the coordinator server as a subprocess and returns its address."""
     port = find_free_port()
     addr = f"127.0.0.1:{port}"
@@ -22,12 +22,15 @@ def coordinator_server():
     
     # Run cargo run -p coordinator --bin coordinator -- <addr>
     # Note: This assumes cargo is in path and we are in root
+    # Keep the coordinator's state out of the checkout's ./data
+    env = dict(os.environ, STRATA_STORAGE__BASE_PATH=str(tmp_path_factory.mktemp("coordinator")))
     proc = subprocess.Popen(
         ["cargo", "run", "-p", "coordinator", "--bin", "coordinator", "--", addr],
         stdout=subprocess.PIPE,
         stderr=subprocess.PIPE,
         text=True,
-        cwd=os.getcwd()
+        cwd=os.getcwd(),
+        env=env
     )
     
     # Wait for port to be open
@@ -1,6 +1,7 @@
 import pytest
 from dtruntime import CheckpointManager
 import asyncio
+import os
 
 @pytest.mark.asyncio
 async def test_async_checkpoint(temp_checkpoint_dir):
@@ -46,3 +47,152 @@ async def test_load_checkpoint(temp_checkpoint_dir):
         
     loaded = await mgr.load(step=200)
     assert loaded == data
+
+
+def test_save_buffer_and_load_large(temp_checkpoint_dir):
+    np = pytest.importorskip("numpy")
+    mgr = CheckpointManager(temp_checkpoint_dir, compression=False)
+
+    # Typed, non-bytes buffers are saved as their raw bytes
+    weights = np.arange(4, dtype=np.float32)
+    ckpt_id = mgr.save(weights, step=1, epoch=0)
+    mgr.wait_pending()
+    assert mgr.load(ckpt_id) == weights.tobytes()
+
+    with pytest.raises(ValueError):
+        mgr.save(np.arange(8, dtype=np.uint8)[::2], step=2, epoch=0)
+
+    # Large checkpoints load as a read-only memoryview without a copy
+    big = np.ones(64 * 1024 * 1024, dtype=np.uint8)
+    ckpt_id = mgr.save(big, step=3, epoch=0)
+    mgr.wait_pending()
+    loaded = mgr.load(ckpt_id)
+    assert isinstance(loaded, memoryview)
+    assert loaded.readonly
+    assert np.array_equal(np.frombuffer(loaded, dtype=np.uint8), big)
+
+
+def test_context_manager_flushes_writes(temp_checkpoint_dir):
+    with CheckpointManager(temp_checkpoint_dir) as mgr:
+        ckpt_id = mgr.save(b"state", step=1, epoch=0)
+
+    assert [c.checkpoint_id for c in mgr.all_checkpoints()] == [ckpt_id]
+    with pytest.raises(RuntimeError):
+        mgr.load(ckpt_id)
+    mgr.close()
+
+
+def test_load_latest_and_resume(temp_checkpoint_dir):
+    with CheckpointManager(temp_checkpoint_dir, keep_count=1) as mgr:
+        assert mgr.load_latest() is None
+        assert mgr.resume_or_none() is None
+
+        mgr.save(b"first", step=10, epoch=1)
+        mgr.save(b"second", step=20, epoch=2)
+        mgr.wait_pending()
+
+        data, info = mgr.load_latest()
+        assert bytes(data) == b"second"
+        assert (info.step, info.epoch) == (20, 2)
+        assert mgr.resume_or_none() == (b"second", 20, 2)
+
+
+def test_pending_writes(temp_checkpoint_dir):
+    with CheckpointManager(temp_checkpoint_dir) as mgr:
+        assert mgr.pending() == []
+
+        ckpt_id = mgr.save(b"weights", step=5, epoch=1)
+        for write in mgr.pending():
+            assert write.checkpoint_id == ckpt_id
+            assert (write.step, write.epoch, write.size_bytes) == (5, 1, 7)
+            assert write.status in ("queued", "writing")
+            assert write.error is None
+
+        mgr.wait_pending()
+        assert mgr.pending() == []
+
+
+def test_backend_arguments_are_checked():
+    with pytest.raises(ValueError):
+        CheckpointManager()
+    with pytest.raises(ValueError):
+        CheckpointManager(backend="gcs")
+    with pytest.raises(ValueError):
+        CheckpointManager(backend="s3")
+
+
+def test_open_save_streams_chunks(temp_checkpoint_dir):
+    mgr = CheckpointManager(temp_checkpoint_dir)
+
+    with mgr.open_save(step=1, epoch=0, metadata={"model": "mlp"}) as f:
+        assert f.write(b"layer-0;") == 8
+        f.write(memoryview(b"layer-1;"))
+        assert f.tell() == 16
+    assert f.closed
+    mgr.wait_pending()
+    assert mgr.load(f.checkpoint_id) == b"layer-0;layer-1;"
+    assert f.close() == f.checkpoint_id
+    with pytest.raises(ValueError):
+        f.write(b"more")
+
+    # An exception inside the block discards the checkpoint
+    with pytest.raises(RuntimeError):
+        with mgr.open_save(step=2, epoch=0) as f:
+            f.write(b"partial")
+            raise RuntimeError("serialization failed")
+    assert f.checkpoint_id is None
+    assert mgr.get_by_step(2) is None
+
+
+def test_torch_state_dict_helpers(temp_checkpoint_dir):
+    torch = pytest.importorskip("torch")
+    mgr = CheckpointManager(temp_checkpoint_dir)
+    state = {"weight": torch.arange(6.0).reshape(2, 3), "step": 10}
+
+    ckpt_id = mgr.save_torch(state, step=10, epoch=1, metadata={"model": "mlp"})
+    mgr.wait_pending()
+    info = mgr.latest()
+    assert info.metadata["framework"] == "torch"
+    assert info.metadata["framework_version"] == torch.__version__
+    assert info.metadata["format"] == "torch"
+    assert info.metadata["model"] == "mlp"
+
+    loaded = mgr.load_torch(ckpt_id, map_location="cpu")
+    assert torch.equal(loaded["weight"], state["weight"])
+    assert loaded["step"] == 10
+
+    with pytest.raises(ValueError):
+        mgr.save_torch(state, step=11, epoch=1, format="pickle")
+
+
+def test_torch_safetensors_format(temp_checkpoint_dir):
+    torch = pytest.importorskip("torch")
+    pytest.importorskip("safetensors")
+    mgr = CheckpointManager(temp_checkpoint_dir)
+    state = {"weight": torch.ones(4), "bias": torch.zeros(2)}
+
+    ckpt_id = mgr.save_torch(state, step=1, epoch=0, format="safetensors")
+    mgr.wait_pending()
+    assert mgr.get_by_step(1).metadata["format"] == "safetensors"
+
+    loaded = mgr.load_torch(ckpt_id, map_location="cpu")
+    assert set(loaded) == {"weight", "bias"}
+    assert torch.equal(loaded["weight"], state["weight"])
+
+
+@pytest.mark.skipif(not os.path.isdir("/proc/self/task"), reason="needs /proc")
+def test_managers_share_one_runtime(tmp_path):
+    CheckpointManager(str(tmp_path / "first")).close()
+    threads = len(os.listdir("/proc/self/task"))
+
+    managers = [CheckpointManager(str(tmp_path / str(i))) for i in range(16)]
+    # A runtime each would start a worker thread per core for every manager
+    assert len(os.listdir("/proc/self/task")) < threads + 16
+    for mgr in managers:
+        mgr.close()
+
+    # Closing one leaves the runtime to the others
+    with CheckpointManager(str(tmp_path / "after")) as mgr:
+        mgr.save(b"weights", step=1)
+        mgr.wait_pending()
+        assert mgr.latest().step == 1
@@ -1,5 +1,5 @@
 import pytest
-from dtruntime import DatasetRegistry
+from dtruntime import ConsistentHash, DatasetRegistry, EpochCoordinator, TrainingOrchestrator
 
 def test_register_dataset(coordinator_server, temp_dataset_dir):
     registry = DatasetRegistry(coordinator_server)
@@ -24,3 +24,56 @@ def test_get_shard(coordinator_server, temp_dataset_dir):
     shard = registry.get_shard("test-dataset-py-2", worker_rank=0, epoch=0)
     assert shard is not None
     assert len(shard) > 0 # Should have file paths
+
+
+def test_sharding_primitives_match_coordinator():
+    workers = ["worker-py-shard-0", "worker-py-shard-1"]
+    with TrainingOrchestrator.local() as first, TrainingOrchestrator(first.coordinator_url) as second:
+        first.register_worker(workers[0], "127.0.0.1", 8101)
+        second.register_worker(workers[1], "127.0.0.1", 8102)
+        first.register_dataset("py-shuffled", "/data/a", total_samples=1000, shard_size=10, seed=7)
+        first.register_dataset("py-ordered", "/data/b", total_samples=1000, shard_size=10, shuffle=False)
+
+        # Shuffled datasets are dealt by rank from the seeded shuffle
+        epochs = EpochCoordinator(seed=7)
+        for orch, rank in ((first, 0), (second, 1)):
+            shards = [s.shard_id for s in orch.get_shards("py-shuffled", epoch=1)]
+            assert shards == epochs.get_worker_shards("py-shuffled", 1, 100, worker_rank=rank, world_size=2)
+
+        # Unshuffled datasets follow the hash ring
+        ring = ConsistentHash()
+        for worker in workers:
+            ring.add_node(worker)
+        for orch, worker in zip((first, second), workers):
+            shards = [s.shard_id for s in orch.get_shards("py-ordered", epoch=0)]
+            assert shards == ring.get_shards_for_node(worker, "py-ordered", 100)
+
+
+def test_epoch_coordinator():
+    epochs = EpochCoordinator(seed=42)
+    order = epochs.get_shuffled_shards("py-epochs", 0, 50)
+    assert sorted(order) == list(range(50))
+    assert order == EpochCoordinator(seed=42).get_shuffled_shards("py-epochs", 0, 50)
+    assert order != epochs.get_shuffled_shards("py-epochs", 1, 50)
+
+    # A dataset's own seed replaces the base seed
+    epochs.set_seed("py-epochs", 3)
+    assert epochs.seed("py-epochs") == 3
+    assert epochs.get_shuffled_shards("py-epochs", 0, 50) == EpochCoordinator(seed=3).get_shuffled_shards(
+        "py-epochs", 0, 50
+    )
+
+    assert epochs.advance_epoch("py-epochs") == 1
+    assert epochs.current_epoch("py-epochs") == 1
+    with pytest.raises(ValueError):
+        epochs.get_worker_shards("py-epochs", 0, 50, worker_rank=2, world_size=2)
+
+    ring = ConsistentHash()
+    assert ring.get_node("key") is None
+    ring.add_node("a")
+    ring.add_node("b")
+    assert len(ring) == 2 and "a" in ring
+    owners = {ring.get_node_for_shard("py-epochs", shard) for shard in range(50)}
+    assert owners == {"a", "b"}
+    ring.remove_node("a")
+    assert ring.nodes() == ["b"]
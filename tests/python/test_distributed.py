@@ -1,6 +1,15 @@
 import pytest
-from dtruntime import TrainingOrchestrator
+from dtruntime import BarrierTimeout, MetricsReporter, TrainingOrchestrator, enable_logging
 import asyncio
+import gc
+import logging
+import os
+import queue
+import socket
+import subprocess
+import sys
+import time
+import urllib.request
 
 @pytest.mark.asyncio
 async def test_distributed_orchestration(coordinator_server):
@@ -35,3 +44,416 @@ async def test_distributed_orchestration(coordinator_server):
     await orch.wait_barrier("start-training")
     
     assert True
+
+
+@pytest.mark.asyncio
+async def test_async_orchestrator_calls(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    config = await orch.register_worker_async("worker-py-async", "127.0.0.1", 8082)
+    assert config.world_size >= 1
+
+    # Calls run concurrently without blocking the event loop
+    acked, _ = await asyncio.gather(
+        orch.heartbeat_async(current_step=1),
+        asyncio.sleep(0),
+    )
+    assert acked
+    await orch.barrier_async("async-start", step=0)
+
+    # Awaitables share the blocking calls' runtime, so closing stops both
+    orch.close()
+    with pytest.raises(RuntimeError, match="Already closed"):
+        await orch.heartbeat_async()
+
+
+def test_background_heartbeat(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    with pytest.raises(RuntimeError):
+        orch.start_heartbeat()
+
+    orch.register_worker("worker-py-heartbeat", "127.0.0.1", 8083)
+    orch.update_progress(step=10, epoch=1, loss=0.5)
+    orch.start_heartbeat(interval=0.05)
+    assert orch.heartbeat_running
+
+    time.sleep(0.2)
+    orch.stop_heartbeat()
+    assert not orch.heartbeat_running
+
+
+def test_heartbeat_registers_again(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    orch.register_worker("worker-py-rejoin", "127.0.0.1", 8087)
+
+    # The coordinator forgets the worker, as after a restart; its HTTP API
+    # listens on the gRPC port + 1000
+    host, port = coordinator_server.removeprefix("http://").rsplit(":", 1)
+    api = f"http://{host}:{int(port) + 1000}/api/workers/worker-py-rejoin/remove"
+    urllib.request.urlopen(urllib.request.Request(api, method="POST")).close()
+
+    assert orch.heartbeat(current_step=1)
+    assert orch.heartbeat(current_step=2)
+    orch.close()
+
+
+def test_context_manager_deregisters(coordinator_server):
+    with pytest.raises(ValueError):
+        with TrainingOrchestrator(coordinator_server) as orch:
+            orch.register_worker("worker-py-ctx", "127.0.0.1", 8084)
+            orch.start_heartbeat(interval=0.05)
+            raise ValueError("training failed")
+
+    assert not orch.heartbeat_running
+    with pytest.raises(RuntimeError):
+        orch.heartbeat()
+
+
+def test_get_shards_returns_full_assignment(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    orch.register_worker("worker-py-shards", "127.0.0.1", 8085)
+    orch.register_dataset("py-shards", "/data/py-shards", total_samples=1000, shard_size=100)
+
+    shards = orch.get_shards("py-shards", epoch=0)
+    assert shards
+    assert shards[0].shard_id == orch.get_shard("py-shards", epoch=0).shard_id
+    assert len({s.shard_id for s in shards}) == len(shards)
+    assert len(orch.get_shards("py-shards", epoch=0, max_shards=1)) == 1
+
+
+def test_epoch_control(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    orch.register_worker("worker-py-epochs", "127.0.0.1", 8086)
+    orch.register_dataset("py-epochs", "/data/py-epochs", total_samples=100, shard_size=10)
+    assert orch.current_epoch("py-epochs") == 0
+
+    # Other workers on the shared coordinator may still have to report
+    status = orch.advance_epoch("py-epochs")
+    assert status.reported >= 1
+    assert status.current_epoch == (1 if status.advanced else 0)
+    assert orch.current_epoch("py-epochs") == status.current_epoch
+
+    with pytest.raises(RuntimeError):
+        orch.current_epoch("not-registered")
+
+
+def test_get_latest_checkpoint(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    with pytest.raises(RuntimeError):
+        orch.get_latest_checkpoint()
+
+    orch.register_worker("worker-py-recovery", "127.0.0.1", 8087)
+    info = orch.get_latest_checkpoint("job-1")
+    # The shared coordinator only has a checkpoint if another test reported one
+    if info is not None:
+        assert info.checkpoint_id
+        assert info.resume_step >= 0
+        assert all(s.epoch == info.resume_epoch for s in info.shard_assignments)
+
+
+def test_command_callbacks(coordinator_server):
+    orch = TrainingOrchestrator(coordinator_server)
+    with pytest.raises(TypeError):
+        orch.on_command("checkpoint_now", "not callable")
+
+    requested = []
+    orch.on_command("checkpoint_now", lambda step: requested.append(int(step)))
+    orch.register_worker("worker-py-commands", "127.0.0.1", 8088)
+    assert orch.heartbeat(current_step=1000)
+    # Whether a checkpoint is due depends on the coordinator's strategy
+    assert all(step > 0 for step in requested)
+
+    orch.off_command("checkpoint_now")
+    orch.close()
+
+
+def test_retries_while_coordinator_unavailable():
+    with pytest.raises(ValueError):
+        TrainingOrchestrator("http://127.0.0.1:1", retry_initial_delay=-1)
+
+    with socket.socket() as s:
+        s.bind(("127.0.0.1", 0))
+        port = s.getsockname()[1]
+    orch = TrainingOrchestrator(
+        f"http://127.0.0.1:{port}", max_retries=2, retry_initial_delay=0.05
+    )
+
+    # Idempotent calls back off 0.05s, then 0.1s, before giving up
+    start = time.monotonic()
+    with pytest.raises(ConnectionError):
+        orch.current_epoch("imagenet")
+    assert time.monotonic() - start >= 0.15
+
+    # Registration isn't repeated
+    start = time.monotonic()
+    with pytest.raises(ConnectionError):
+        orch.register_worker("worker-py-retry", "127.0.0.1", 8089)
+    assert time.monotonic() - start < 0.15
+
+
+def test_local_coordinator(tmp_path):
+    def assignment(orch):
+        orch.register_worker("worker-py-local", "127.0.0.1", 8089)
+        orch.register_dataset("py-local", "/data/py-local", total_samples=1000, shard_size=100, seed=7)
+        return [(s.shard_id, s.start_index, s.end_index) for s in orch.get_shards("py-local", epoch=0)]
+
+    # Independent coordinators agree on the assignment for the same seed
+    with TrainingOrchestrator.local() as first, TrainingOrchestrator.local() as second:
+        assert first.coordinator_url.startswith("http://127.0.0.1:")
+        assert assignment(first) == assignment(second)
+
+        # Other orchestrators can join the same coordinator
+        peer = TrainingOrchestrator(first.coordinator_url)
+        peer.register_worker("worker-py-local-peer", "127.0.0.1", 8090)
+        assert peer.get_shards("py-local", epoch=0)
+        peer.close()
+
+    with pytest.raises(RuntimeError):
+        first.heartbeat()
+
+    # Closing saves the cluster state, which is kept in a given directory
+    with TrainingOrchestrator.local(state_dir=str(tmp_path)) as orch:
+        orch.register_worker("worker-py-local", "127.0.0.1", 8089)
+    assert (tmp_path / "cluster_state.pb").exists()
+
+
+def test_cluster_introspection():
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-inspect", "127.0.0.1", 8091, gpu_count=2)
+
+        workers = orch.list_workers()
+        assert [w.worker_id for w in workers] == ["worker-py-inspect"]
+        assert workers[0].port == 8091
+        assert workers[0].registered_at_ms > 0
+
+        metrics = orch.cluster_metrics()
+        assert metrics.total_workers == 1
+        assert metrics.active_workers <= metrics.total_workers
+
+        assert orch.list_checkpoints() == []
+        assert orch.list_checkpoints(limit=0) == []
+
+
+def test_barrier_timeout_and_try_barrier():
+    with TrainingOrchestrator.local() as first:
+        first.register_worker("worker-py-barrier-1", "127.0.0.1", 8092)
+        second = TrainingOrchestrator(first.coordinator_url)
+        second.register_worker("worker-py-barrier-2", "127.0.0.1", 8093)
+
+        status = first.try_barrier("py-sync", step=1)
+        assert not status.released
+        assert (status.participants, status.expected) == (1, 2)
+
+        start = time.monotonic()
+        with pytest.raises(BarrierTimeout):
+            first.barrier("py-sync", step=1, timeout=0.2)
+        assert time.monotonic() - start < 5
+        assert issubclass(BarrierTimeout, TimeoutError)
+
+        assert second.try_barrier("py-sync", step=1).released
+        assert first.barrier("py-sync", step=1, timeout=5).released
+        second.close()
+
+
+def test_rust_logs_reach_python_logging(caplog):
+    caplog.set_level(logging.INFO, logger="dtruntime")
+    enable_logging(logging.INFO)
+
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-logging", "127.0.0.1", 8094)
+        orch.deregister()
+
+    # Records are handed over from a background thread
+    deadline = time.monotonic() + 5
+    while time.monotonic() < deadline:
+        records = [r for r in caplog.records if "worker-py-logging" in r.getMessage()]
+        if records:
+            break
+        time.sleep(0.05)
+    assert records
+    assert records[0].name.startswith("dtruntime.coordinator")
+    assert records[0].levelno == logging.INFO
+
+    with pytest.raises(ValueError):
+        enable_logging("loud")
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="needs fork")
+def test_orchestrator_after_fork():
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-fork", "127.0.0.1", 8095)
+        orch.register_dataset("py-fork", "/data/py-fork", total_samples=1000, shard_size=100)
+        orch.start_heartbeat(interval=0.1)
+        expected = [s.shard_id for s in orch.get_shards("py-fork", epoch=0)]
+
+        pid = os.fork()
+        if pid == 0:
+            # Like a DataLoader worker: reconnects on a runtime of its own
+            code = 1
+            try:
+                shards = [s.shard_id for s in orch.get_shards("py-fork", epoch=0)]
+                if shards == expected and not orch.heartbeat_running:
+                    code = 0
+                orch.close()
+            finally:
+                os._exit(code)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0
+
+        # The child left the worker registered and the coordinator running
+        assert orch.heartbeat_running
+        assert orch.heartbeat()
+
+
+@pytest.mark.skipif(not hasattr(os, "fork"), reason="needs fork")
+def test_async_after_fork():
+    def run(make):
+        async def main():
+            return await asyncio.wait_for(make(), timeout=10)
+
+        return asyncio.run(main())
+
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-async-fork", "127.0.0.1", 8100)
+        # The parent's awaitables ran on threads the child won't have
+        assert run(lambda: orch.heartbeat_async(current_step=1))
+
+        pid = os.fork()
+        if pid == 0:
+            code = 1
+            try:
+                if run(lambda: orch.heartbeat_async(current_step=2)):
+                    with TrainingOrchestrator(orch.coordinator_url) as fresh:
+                        run(lambda: fresh.register_worker_async("worker-py-async-child", "127.0.0.1", 8101))
+                    code = 0
+            finally:
+                os._exit(code)
+
+        _, status = os.waitpid(pid, 0)
+        assert os.waitstatus_to_exitcode(status) == 0
+        assert run(lambda: orch.heartbeat_async(current_step=3))
+
+
+def test_worker_deregistered_at_exit():
+    with TrainingOrchestrator.local() as orch:
+        # Registers and exits without closing, like a notebook kernel shutting down
+        script = (
+            "from dtruntime import TrainingOrchestrator\n"
+            f"orch = TrainingOrchestrator({orch.coordinator_url!r})\n"
+            "orch.register_worker('worker-py-exit', '127.0.0.1', 8096)\n"
+        )
+        subprocess.run([sys.executable, "-c", script], check=True, timeout=60)
+
+        assert "worker-py-exit" not in [w.worker_id for w in orch.list_workers()]
+
+
+def test_worker_deregistered_when_collected():
+    with TrainingOrchestrator.local() as orch:
+        other = TrainingOrchestrator(orch.coordinator_url)
+        other.register_worker("worker-py-gc", "127.0.0.1", 8097)
+        assert "worker-py-gc" in [w.worker_id for w in orch.list_workers()]
+
+        del other
+        gc.collect()
+        assert "worker-py-gc" not in [w.worker_id for w in orch.list_workers()]
+
+
+def test_watch_membership():
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-watch-0", "127.0.0.1", 8098)
+        events = queue.Queue()
+        orch.watch_membership(events.put)
+
+        def next_event(kind):
+            while True:
+                event = events.get(timeout=10)
+                if event.kind == kind:
+                    return event
+
+        # Current members come first
+        event = next_event("joined")
+        assert (event.worker_id, event.world_size) == ("worker-py-watch-0", 1)
+
+        with TrainingOrchestrator(orch.coordinator_url) as other:
+            other.register_worker("worker-py-watch-1", "127.0.0.1", 8099)
+            event = next_event("joined")
+            assert (event.worker_id, event.world_size) == ("worker-py-watch-1", 2)
+
+        event = next_event("left")
+        assert (event.worker_id, event.world_size) == ("worker-py-watch-1", 1)
+
+        orch.unwatch_membership()
+        with pytest.raises(TypeError):
+            orch.watch_membership("not callable")
+
+
+def test_register_dataset_from_manifest(tmp_path):
+    data = tmp_path / "train"
+    (data / "part").mkdir(parents=True)
+    (data / "a.jsonl").write_text("".join(f'{{"x": {i}}}\n' for i in range(30)))
+    (data / "part" / "b.jsonl").write_text("".join(f'{{"x": {i}}}\n' for i in range(20)))
+    # Indexed files are counted from their index
+    (data / "c.tfrecord").write_bytes(b"\0" * 64)
+    (data / "c.tfrecord.idx").write_text("0 16\n16 16\n32 16\n48 16\n")
+    (data / "notes.txt").write_text("not data")
+
+    with TrainingOrchestrator.local() as orch:
+        orch.register_worker("worker-py-manifest", "127.0.0.1", 8100)
+
+        manifest = orch.register_dataset_from_manifest("py-jsonl", str(data / "**" / "*.jsonl"), seed=3)
+        assert manifest.files == [
+            (str(data / "a.jsonl"), 0, 30),
+            (str(data / "part" / "b.jsonl"), 30, 20),
+        ]
+        assert (manifest.total_samples, manifest.format, manifest.path) == (50, "jsonl", str(data))
+        assert (manifest.shard_size, manifest.total_shards) == (25, 2)
+        assert manifest.locate(42) == (str(data / "part" / "b.jsonl"), 12)
+        assert manifest.locate(50) is None
+
+        shards = orch.get_shards("py-jsonl", epoch=0)
+        assert sum(s.end_index - s.start_index for s in shards) == 50
+        assert manifest.files_in(20, 40) == [
+            (str(data / "a.jsonl"), 20, 30),
+            (str(data / "part" / "b.jsonl"), 0, 10),
+        ]
+
+        manifest = orch.register_dataset_from_manifest(
+            "py-files", [str(data / "c.tfrecord"), str(data / "a.jsonl")], shard_size=10
+        )
+        assert [f[2] for f in manifest.files] == [4, 30]
+        assert (manifest.format, manifest.total_shards) == ("mixed", 4)
+
+        with pytest.raises(ValueError):
+            orch.register_dataset_from_manifest("py-none", str(data / "*.parquet"))
+        with pytest.raises(IOError):
+            orch.register_dataset_from_manifest("py-txt", str(data / "notes.txt"))
+
+
+def test_metrics_reporter():
+    with TrainingOrchestrator.local() as orch:
+        with pytest.raises(RuntimeError):
+            MetricsReporter(orch)
+        orch.register_worker("worker-py-metrics", "127.0.0.1", 8103)
+
+        with MetricsReporter(orch, interval=0.5, capacity=4) as metrics:
+            assert metrics.running
+            metrics.log_batch([(step, {"loss": 1.0 / (step + 1)}) for step in range(6)])
+            # Only the newest points fit in the buffer
+            assert (metrics.pending, metrics.dropped) == (4, 2)
+
+            deadline = time.monotonic() + 10
+            while metrics.pending and time.monotonic() < deadline:
+                time.sleep(0.05)
+            assert (metrics.pending, metrics.sent) == (0, 4)
+
+            # Steps logged reach the coordinator with the heartbeats
+            metrics.log({"loss": 0.1, "samples_per_sec": 900.0}, step=42)
+            metrics.flush()
+            assert metrics.sent == 6
+            worker = orch.list_workers()[0]
+            assert worker.current_step == 42
+
+            metrics.log({"loss": 0.05})
+        assert (metrics.sent, metrics.running) == (7, False)
+        with pytest.raises(RuntimeError):
+            metrics.log({"loss": 0.0})
@@ -0,0 +1,71 @@
+from types import SimpleNamespace
+
+import pytest
+from dtruntime import CheckpointManager, TrainingOrchestrator
+from dtruntime.integrations import huggingface, lightning
+
+
+def test_lightning_callback(tmp_path):
+    torch = pytest.importorskip("torch")
+    with TrainingOrchestrator.local() as coordinator, CheckpointManager(str(tmp_path)) as ckpt:
+        callback = lightning.StrataCallback(
+            coordinator.coordinator_url,
+            worker_id="worker-py-lightning",
+            checkpoint_manager=ckpt,
+        )
+        trainer = SimpleNamespace(
+            global_rank=0,
+            global_step=0,
+            current_epoch=0,
+            log_every_n_steps=1,
+            is_global_zero=True,
+        )
+
+        callback.on_train_start(trainer, None)
+        assert callback.orchestrator.heartbeat_running
+        assert [w.worker_id for w in coordinator.list_workers()] == ["worker-py-lightning"]
+
+        trainer.global_step = 1
+        callback.on_train_batch_end(trainer, None, {"loss": torch.tensor(0.5)}, None, 0)
+        callback.on_train_epoch_end(trainer, None)
+        callback.on_save_checkpoint(trainer, None, {"state_dict": {"w": torch.ones(2)}})
+        checkpoint_id = callback.hooks.last_checkpoint_id
+
+        callback.on_train_end(trainer, None)
+        assert callback.orchestrator is None
+        assert coordinator.list_workers() == []
+        assert ckpt.latest().metadata["trainer"] == "lightning"
+        state = ckpt.load_torch(checkpoint_id, weights_only=False)
+        assert torch.equal(state["state_dict"]["w"], torch.ones(2))
+
+
+def test_huggingface_callback(tmp_path):
+    ckpt = CheckpointManager(str(tmp_path / "ckpt"))
+    with TrainingOrchestrator.local() as coordinator, ckpt:
+        coordinator.register_worker("worker-py-hf", "127.0.0.1", 8096)
+        callback = huggingface.StrataCallback(orchestrator=coordinator, checkpoint_manager=ckpt)
+        args = SimpleNamespace(process_index=0, output_dir=str(tmp_path / "out"))
+        state = SimpleNamespace(global_step=0, epoch=0.0, is_world_process_zero=True)
+
+        # The orchestrator was registered by the caller, so it stays registered
+        callback.on_train_begin(args, state, None)
+        assert coordinator.heartbeat_running
+
+        state.global_step, state.epoch = 10, 1.0
+        callback.on_log(args, state, None, logs={"loss": 0.25})
+        callback.on_epoch_end(args, state, None)
+
+        saved = tmp_path / "out" / "checkpoint-10"
+        saved.mkdir(parents=True)
+        (saved / "trainer_state.json").write_text('{"global_step": 10}')
+        callback.on_save(args, state, None)
+        callback.on_train_end(args, state, None)
+        assert not coordinator.heartbeat_running
+        assert [w.worker_id for w in coordinator.list_workers()] == ["worker-py-hf"]
+
+        restored = huggingface.StrataCallback.restore(
+            ckpt, callback.hooks.last_checkpoint_id, str(tmp_path / "restored")
+        )
+        assert restored.endswith("checkpoint-10")
+        with open(f"{restored}/trainer_state.json") as f:
+            assert f.read() == '{"global_step": 10}'
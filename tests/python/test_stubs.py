@@ -0,0 +1,59 @@
+import ast
+import inspect
+import subprocess
+import sys
+from pathlib import Path
+
+from dtruntime import _core
+
+ROOT = Path(__file__).resolve().parents[2]
+STUBS = ROOT / "python" / "dtruntime" / "_core.pyi"
+
+
+def stub_definitions():
+    """Top-level names in the stubs, with the parameters of each method"""
+    definitions = {}
+    for node in ast.parse(STUBS.read_text()).body:
+        if isinstance(node, ast.ClassDef):
+            definitions[node.name] = {
+                item.name: [a.arg for a in item.args.posonlyargs + item.args.args]
+                for item in node.body
+                if isinstance(item, ast.FunctionDef)
+            }
+        elif isinstance(node, ast.FunctionDef):
+            definitions[node.name] = [a.arg for a in node.args.args]
+        elif isinstance(node, ast.AnnAssign):
+            definitions[node.target.id] = None
+    return definitions
+
+
+def test_stubs_up_to_date():
+    result = subprocess.run(
+        [sys.executable, str(ROOT / "scripts" / "generate_stubs.py"), "--check"],
+        capture_output=True,
+        text=True,
+    )
+    assert result.returncode == 0, result.stderr
+
+
+def test_stubs_match_module():
+    definitions = stub_definitions()
+    exported = [name for name in dir(_core) if not name.startswith("_")]
+    assert sorted(set(exported) - set(definitions)) == []
+
+    # Parameters follow the signatures PyO3 reports
+    for name, methods in definitions.items():
+        cls = getattr(_core, name, None)
+        if not isinstance(methods, dict) or not isinstance(cls, type):
+            continue
+        for method, params in methods.items():
+            attr = inspect.getattr_static(cls, method, None)
+            if not callable(attr) or method == "__init__":
+                continue
+            try:
+                signature = inspect.signature(getattr(cls, method))
+            except (TypeError, ValueError):
+                continue
+            expected = [p for p in params if p not in ("self", "cls")]
+            actual = [p for p in signature.parameters if p not in ("self", "cls")]
+            assert actual == expected, f"{name}.{method}"
@@ -0,0 +1,58 @@
+import pytest
+
+torch = pytest.importorskip("torch")
+
+from torch.utils.data import DataLoader
+from dtruntime import SampleRange, StrataIterableDataset
+from dtruntime.data import split_ranges
+
+
+class FakeShard:
+    def __init__(self, epoch):
+        self.dataset_id = "ds"
+        self.shard_id = 0
+        self.start_index = 0
+        self.end_index = 10
+        self.epoch = epoch
+        self.file_paths = ["part-0.parquet"]
+        self.shards = [(0, 0, 10), (1, 10, 20)]
+
+
+class FakeOrchestrator:
+    def __init__(self):
+        self.requests = []
+
+    def get_shard(self, dataset_id, epoch, gpu_count=0, available_memory_bytes=0):
+        self.requests.append((dataset_id, epoch))
+        return FakeShard(epoch)
+
+
+def test_yields_assigned_ranges():
+    orch = FakeOrchestrator()
+    dataset = StrataIterableDataset(orch, "ds")
+    ranges = list(dataset)
+    assert [(r.shard_id, r.start, r.end) for r in ranges] == [(0, 0, 10), (1, 10, 20)]
+
+    dataset.set_epoch(3)
+    assert all(r.epoch == 3 for r in dataset)
+    assert orch.requests == [("ds", 0), ("ds", 3)]
+
+
+def test_split_ranges():
+    ranges = [SampleRange("ds", i, 0, i * 10, i * 10 + 10, []) for i in range(4)]
+    assert [r.shard_id for r in split_ranges(ranges, 1, 2)] == [1, 3]
+
+    pieces = [split_ranges(ranges[:1], w, 3) for w in range(3)]
+    assert [(p[0].start, p[0].end) for p in pieces] == [(0, 3), (3, 6), (6, 10)]
+
+
+def test_dataloader_workers_read_every_sample_once():
+    dataset = StrataIterableDataset(FakeOrchestrator(), "ds", reader=lambda r: r.indices())
+    dataset.set_epoch(0)
+    loader = DataLoader(
+        dataset,
+        batch_size=None,
+        num_workers=2,
+        worker_init_fn=StrataIterableDataset.worker_init_fn,
+    )
+    assert sorted(int(i) for i in loader) == list(range(20))
@@ -51,6 +51,7 @@ async fn start_coordinator() -> Result<(String, tokio::sync::oneshot::Sender<()>
 /// Simulates a training worker
 struct SimulatedWorker {
     id: String,
+    session: i64,
     client: CoordinatorClient<tonic::transport::Channel>,
     rank: i32,
     world_size: i32,
@@ -68,6 +69,9 @@ impl SimulatedWorker {
                 gpu_count: 8,
                 memory_bytes: 64 * 1024 * 1024 * 1024, // 64GB
                 metadata: Default::default(),
+                protocol_version: coordinator::PROTOCOL_VERSION,
+                labels: Default::default(),
+                taints: Vec::new(),
             })
             .await?;
 
@@ -75,6 +79,7 @@ impl SimulatedWorker {
 
         Ok(Self {
             id: worker_id.to_string(),
+            session: config.session,
             client,
             rank: config.rank,
             world_size: config.world_size,
@@ -85,14 +90,18 @@ impl SimulatedWorker {
         self.client
             .heartbeat(HeartbeatRequest {
                 worker_id: self.id.clone(),
+                session: self.session,
                 timestamp_ms: chrono::Utc::now().timestamp_millis(),
                 status: Some(WorkerStatus {
                     state: state as i32,
                     current_step: step as i64,
                     current_epoch: epoch as i64,
                     current_task: format!("training_step_{}", step),
+                    shard_progress: vec![],
+                    loss: 0.0,
                 }),
                 resources: None,
+                metrics: vec![],
             })
             .await?;
         Ok(())
@@ -105,6 +114,7 @@ impl SimulatedWorker {
                 dataset_id: dataset_id.to_string(),
                 worker_id: self.id.clone(),
                 epoch: epoch as i64,
+                ..Default::default()
             })
             .await?;
 
@@ -128,12 +138,15 @@ impl SimulatedWorker {
         Ok(())
     }
 
+    #[allow(dead_code)]
     async fn wait_barrier(&mut self, barrier_id: &str, step: u64) -> Result<()> {
         self.client
             .wait_barrier(BarrierRequest {
                 worker_id: self.id.clone(),
                 barrier_id: barrier_id.to_string(),
                 step: step as i64,
+                generation: 0,
+                no_wait: false,
             })
             .await?;
         Ok(())
@@ -155,6 +168,9 @@ async fn test_multi_worker_training_simulation() -> Result<()> {
     // Note: world_size captured at registration time reflects the count at that moment
     // The last worker registered should have world_size=4
     assert_eq!(workers[3].world_size, 4);
+    for (i, worker) in workers.iter().enumerate() {
+        assert_eq!(worker.rank, i as i32);
+    }
 
     // Register dataset
     let mut client = CoordinatorClient::connect(addr.clone()).await?;
@@ -207,6 +223,8 @@ async fn test_multi_worker_training_simulation() -> Result<()> {
                             worker_id,
                             barrier_id,
                             step: step as i64,
+                            generation: 0,
+                            no_wait: false,
                         })
                         .await
                 }));
@@ -327,6 +345,9 @@ async fn test_worker_failure_recovery() -> Result<()> {
             gpu_count: 8,
             memory_bytes: 64 * 1024 * 1024 * 1024,
             metadata: Default::default(),
+            protocol_version: coordinator::PROTOCOL_VERSION,
+            labels: Default::default(),
+            taints: Vec::new(),
         })
         .await?;
 
@@ -363,6 +384,9 @@ async fn test_concurrent_barrier_sync() -> Result<()> {
                 gpu_count: 1,
                 memory_bytes: 8 * 1024 * 1024 * 1024,
                 metadata: Default::default(),
+                protocol_version: coordinator::PROTOCOL_VERSION,
+                labels: Default::default(),
+                taints: Vec::new(),
             })
             .await?;
     }
@@ -389,6 +413,8 @@ async fn test_concurrent_barrier_sync() -> Result<()> {
                     worker_id: format!("barrier-worker-{}", i),
                     barrier_id: "epoch-sync".to_string(),
                     step: 0,
+                    generation: 0,
+                    no_wait: false,
                 })
                 .await
                 .unwrap();
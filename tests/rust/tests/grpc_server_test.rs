@@ -0,0 +1,162 @@
+//! gRPC server integration tests
+//!
+//! Verifies that the coordinator server exposes the standard
+//! `grpc.health.v1.Health` and server reflection services, and that the
+//! resilient client fails over to a live coordinator.
+
+use anyhow::Result;
+use coordinator::proto::coordinator_client::CoordinatorClient;
+use coordinator::proto::ClusterStateRequest;
+use coordinator::server::ServerConfig;
+use coordinator::{
+    CoordinatorServer, CoordinatorService, ResilientClientConfig, ResilientCoordinatorClient,
+};
+use runtime_core::config::RetryConfig;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+use tempfile::{tempdir, TempDir};
+use tokio::time::sleep;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::Channel;
+use tonic_health::pb::health_check_response::ServingStatus;
+use tonic_health::pb::health_client::HealthClient;
+use tonic_health::pb::HealthCheckRequest;
+use tonic_reflection::pb::v1::server_reflection_client::ServerReflectionClient;
+use tonic_reflection::pb::v1::server_reflection_request::MessageRequest;
+use tonic_reflection::pb::v1::server_reflection_response::MessageResponse;
+use tonic_reflection::pb::v1::ServerReflectionRequest;
+
+/// Start a coordinator server on a free port, returning its URL and shutdown handle
+async fn start_server() -> Result<(String, TempDir, tokio::sync::oneshot::Sender<()>)> {
+    let dir = tempdir()?;
+    let service = CoordinatorService::with_config(
+        checkpoint::CheckpointManagerConfig {
+            base_path: dir.path().to_path_buf(),
+            ..Default::default()
+        },
+        100,
+        Duration::from_secs(30),
+    )
+    .await
+    .map_err(|e| anyhow::anyhow!(e))?;
+
+    let port = portpicker::pick_unused_port().expect("No ports free");
+    let addr = SocketAddr::from_str(&format!("127.0.0.1:{}", port))?;
+    let config = ServerConfig {
+        addr,
+        ..Default::default()
+    };
+
+    let (tx, rx) = tokio::sync::oneshot::channel::<()>();
+    let server = CoordinatorServer::with_config(service, config);
+    tokio::spawn(server.run_with_shutdown(async {
+        rx.await.ok();
+    }));
+    sleep(Duration::from_millis(100)).await;
+
+    Ok((format!("http://127.0.0.1:{}", port), dir, tx))
+}
+
+#[tokio::test]
+async fn test_grpc_health_check() -> Result<()> {
+    let (url, _dir, shutdown) = start_server().await?;
+
+    let channel = Channel::from_shared(url)?.connect().await?;
+    let mut client = HealthClient::new(channel);
+
+    for service_name in ["", "coordinator.Coordinator"] {
+        let response = client
+            .check(HealthCheckRequest {
+                service: service_name.to_string(),
+            })
+            .await?
+            .into_inner();
+        assert_eq!(response.status(), ServingStatus::Serving);
+    }
+
+    let unknown = client
+        .check(HealthCheckRequest {
+            service: "does.not.Exist".to_string(),
+        })
+        .await;
+    assert_eq!(unknown.unwrap_err().code(), tonic::Code::NotFound);
+
+    shutdown.send(()).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grpc_reflection_lists_services() -> Result<()> {
+    let (url, _dir, shutdown) = start_server().await?;
+
+    let channel = Channel::from_shared(url)?.connect().await?;
+    let mut client = ServerReflectionClient::new(channel);
+
+    let request = ServerReflectionRequest {
+        host: String::new(),
+        message_request: Some(MessageRequest::ListServices(String::new())),
+    };
+    let mut responses = client
+        .server_reflection_info(tokio_stream::once(request))
+        .await?
+        .into_inner();
+
+    let response = responses.message().await?.expect("reflection response");
+    let Some(MessageResponse::ListServicesResponse(list)) = response.message_response else {
+        panic!("unexpected reflection response");
+    };
+    let names: Vec<_> = list.service.into_iter().map(|s| s.name).collect();
+    assert!(names.contains(&"coordinator.Coordinator".to_string()));
+    assert!(names.contains(&"grpc.health.v1.Health".to_string()));
+
+    shutdown.send(()).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_resilient_client_fails_over() -> Result<()> {
+    let (url, _dir, shutdown) = start_server().await?;
+
+    // Nothing listens on the first address
+    let dead_port = portpicker::pick_unused_port().expect("No ports free");
+    let config = ResilientClientConfig {
+        connect_timeout: Duration::from_millis(500),
+        retry: RetryConfig {
+            initial_delay: Duration::from_millis(10),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut client = ResilientCoordinatorClient::new(
+        [format!("http://127.0.0.1:{}", dead_port), url.clone()],
+        config,
+    )?;
+
+    let state = client
+        .get_cluster_state(ClusterStateRequest { max_checkpoints: 0 })
+        .await?;
+    assert!(state.workers.is_empty());
+    assert_eq!(client.current_endpoint(), url);
+
+    shutdown.send(()).ok();
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_grpc_compression_negotiated() -> Result<()> {
+    let (url, _dir, shutdown) = start_server().await?;
+
+    let channel = Channel::from_shared(url)?.connect().await?;
+    let mut client = CoordinatorClient::new(channel)
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Zstd);
+
+    let response = client
+        .get_cluster_state(ClusterStateRequest { max_checkpoints: 0 })
+        .await?;
+    assert_eq!(response.metadata().get("grpc-encoding").unwrap(), "zstd");
+
+    shutdown.send(()).ok();
+    Ok(())
+}
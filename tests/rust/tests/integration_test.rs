@@ -58,6 +58,9 @@ async fn test_full_flow() -> Result<()> {
             gpu_count: 0,
             memory_bytes: 1024,
             metadata: Default::default(),
+            protocol_version: coordinator::PROTOCOL_VERSION,
+            labels: Default::default(),
+            taints: Vec::new(),
         })
         .await?;
     assert!(!resp.get_ref().assigned_id.is_empty());
@@ -89,6 +92,7 @@ async fn test_full_flow() -> Result<()> {
             dataset_id: dataset_id.to_string(),
             worker_id: worker_id.to_string(),
             epoch: 0,
+            ..Default::default()
         })
         .await?;
     let shard = resp.get_ref();
@@ -150,6 +154,8 @@ async fn test_barrier() -> Result<()> {
                 worker_id: "w1".to_string(),
                 barrier_id: barrier_id.to_string(),
                 step: 1,
+                generation: 0,
+                no_wait: false,
             })
             .await
     });
@@ -166,6 +172,8 @@ async fn test_barrier() -> Result<()> {
                 worker_id: "w2".to_string(),
                 barrier_id: barrier_id.to_string(),
                 step: 1,
+                generation: 0,
+                no_wait: false,
             })
             .await
     });